@@ -0,0 +1,159 @@
+//! Benchmarks of decoding, encoding, disassembly and assembly.
+//!
+//! Bulk disassembly runs over the `.text` section of the ELF file (or the raw binary) named by the
+//! `RISCV_CODEC_BENCH_FIRMWARE` environment variable, so that the instruction mix is that of real firmware. Without
+//! it a synthetic image assembled from a mix of common instructions is used.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use riscv_codec::{
+    assembly::assemble_line,
+    cinstruction::CInstruction,
+    instruction::{Inst, Instruction},
+};
+use std::hint::black_box;
+
+/// Instructions in the proportions typical of compiled code: loads, stores, immediates, branches and compressed forms.
+const MIX: &[&str] = &[
+    "addi sp,sp,-48",
+    "sd ra,40(sp)",
+    "c.sdsp s0,32",
+    "c.mv s0,a0",
+    "lw a5,12(a0)",
+    "c.li a4,0",
+    "beq a5,zero,24",
+    "slli a3,a4,3",
+    "c.add a3,s0",
+    "ld a2,0(a3)",
+    "mul a2,a2,a5",
+    "c.addi a4,1",
+    "bltu a4,a5,-16",
+    "lui a0,74565",
+    "addiw a0,a0,1656",
+    "jal ra,2048",
+    "amoadd.w.aqrl a0,a1,(a2)",
+    "fadd.s fa0,fa1,fa2,rne",
+    "csrrs a0,0x001,zero",
+    "c.ldsp ra,40",
+    "c.addi16sp 48",
+    "c.jr ra",
+];
+
+/// The size of the synthetic image in bytes.
+const SYNTHETIC_SIZE: usize = 64 * 1024;
+
+/// Loads the image to disassemble, see the module documentation.
+fn firmware() -> Vec<u8> {
+    if let Ok(path) = std::env::var("RISCV_CODEC_BENCH_FIRMWARE") {
+        let data = std::fs::read(&path).unwrap_or_else(|e| panic!("failed to read {path}: {e}"));
+        #[cfg(feature = "elf")]
+        if let Ok(elf) = riscv_codec::elf::Elf::parse(&data) {
+            let text = elf
+                .section(".text")
+                .unwrap_or_else(|| panic!("{path} has no .text section"));
+            return elf.section_data(text).unwrap().to_vec();
+        }
+        return data;
+    }
+    let mix: Vec<u8> = MIX
+        .iter()
+        .flat_map(|line| assemble_line(line).unwrap().encode_bytes())
+        .collect();
+    mix.iter().copied().cycle().take(SYNTHETIC_SIZE).collect()
+}
+
+/// Decodes and formats every instruction of an image, skipping halfwords that fail to decode.
+fn disassemble(image: &[u8]) -> usize {
+    let mut offset = 0;
+    let mut text = 0;
+    while offset + 2 <= image.len() {
+        let low = u16::from_le_bytes([image[offset], image[offset + 1]]);
+        let (string, length) = if low & 0b11 == 0b11 {
+            if offset + 4 > image.len() {
+                break;
+            }
+            let word = u32::from_le_bytes(image[offset..offset + 4].try_into().unwrap());
+            (Instruction::decode(word).map(|i| i.to_string()), 4)
+        } else {
+            (CInstruction::decode(low).map(|c| c.to_string()), 2)
+        };
+        match string {
+            Ok(string) => {
+                text += string.len();
+                offset += length;
+            }
+            Err(_) => offset += 2,
+        }
+    }
+    text
+}
+
+fn decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("decode");
+    for line in [
+        "add a0,a1,a2",
+        "ld a0,-8(sp)",
+        "fadd.s fa0,fa1,fa2,rne",
+        "amoswap.d.aq a0,a1,(a2)",
+    ] {
+        let word = Instruction::encode(&assemble_line(line).unwrap().i());
+        group.bench_with_input(BenchmarkId::from_parameter(line), &word, |b, word| {
+            b.iter(|| Instruction::decode(black_box(*word)))
+        });
+    }
+    let half = CInstruction::encode(&assemble_line("c.addi a0,1").unwrap().c());
+    group.bench_function("c.addi a0,1", |b| {
+        b.iter(|| CInstruction::decode(black_box(half)))
+    });
+    group.finish();
+}
+
+fn encode_instructions(c: &mut Criterion) {
+    let instructions: Vec<Instruction> = MIX
+        .iter()
+        .filter_map(|line| match assemble_line(line).unwrap() {
+            Inst::Full(i) => Some(i),
+            Inst::Compressed(_) => None,
+        })
+        .collect();
+    let mut group = c.benchmark_group("encode");
+    group.throughput(Throughput::Elements(instructions.len() as u64));
+    group.bench_function("mix", |b| {
+        b.iter(|| {
+            black_box(&instructions)
+                .iter()
+                .map(Instruction::encode)
+                .fold(0, u32::wrapping_add)
+        })
+    });
+    group.finish();
+}
+
+fn disassemble_image(c: &mut Criterion) {
+    let image = firmware();
+    let mut group = c.benchmark_group("disassemble");
+    group.throughput(Throughput::Bytes(image.len() as u64));
+    group.bench_function("firmware", |b| b.iter(|| disassemble(black_box(&image))));
+    group.finish();
+}
+
+fn assemble(c: &mut Criterion) {
+    let mut group = c.benchmark_group("assemble");
+    group.throughput(Throughput::Elements(MIX.len() as u64));
+    group.bench_function("mix", |b| {
+        b.iter(|| {
+            for line in black_box(MIX) {
+                black_box(assemble_line(line).unwrap());
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    decode,
+    encode_instructions,
+    disassemble_image,
+    assemble
+);
+criterion_main!(benches);
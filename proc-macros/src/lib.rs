@@ -52,6 +52,30 @@ pub fn r_assemble(input: TokenStream) -> TokenStream {
     }
 }
 
+/// Assembles an instruction with a destination and a single source register
+#[proc_macro]
+pub fn ru_assemble(input: TokenStream) -> TokenStream {
+    if let TokenTree::Ident(i) = input.into_iter().next().unwrap() {
+        let name = i.to_string();
+        let lower = name.to_lowercase();
+        format!(
+            "
+        if operands.len() != 2 {{
+            Err(\"{lower} instruction requires 2 operands\".to_owned())
+        }} else {{
+            Ok(Instruction::{name}{{
+                dest: IRegister::from_string(operands[0])?,
+                src: IRegister::from_string(operands[1])?,
+            }})
+        }}"
+        )
+        .parse()
+        .unwrap()
+    } else {
+        panic!("expected identifier");
+    }
+}
+
 /// Assembles a load type instruction
 #[proc_macro]
 pub fn l_assemble(input: TokenStream) -> TokenStream {
@@ -151,7 +175,7 @@ pub fn sh_assemble(input: TokenStream) -> TokenStream {
             Ok(Instruction::{name}{{
                 dest: IRegister::from_string(operands[0])?,
                 src: IRegister::from_string(operands[1])?,
-                shamt: Shamt::try_from(parse_int(operands[2])?)?,
+                shamt: parse_shamt(operands[2], config)?,
             }})
         }}"
         )
@@ -187,66 +211,68 @@ pub fn shw_assemble(input: TokenStream) -> TokenStream {
     }
 }
 
+// assembles an atomic memory operation, the sizes it takes can follow the name (`AMOADD b h w d`) and default to w and d
 #[proc_macro]
 pub fn amo_assemble(input: TokenStream) -> TokenStream {
-    if let TokenTree::Ident(i) = input.into_iter().next().unwrap() {
+    let mut tokens = input.into_iter();
+    if let Some(TokenTree::Ident(i)) = tokens.next() {
         let name = i.to_string();
         let lower = name.to_lowercase();
-        let dname = name.clone() + "D";
-        let wname = name.clone() + "W";
+        let mut sizes: Vec<String> = tokens.map(|t| t.to_string()).collect();
+        if sizes.is_empty() {
+            sizes = vec!["w".to_owned(), "d".to_owned()];
+        }
+        let arms: String = sizes
+            .iter()
+            .map(|size| {
+                let variant = format!("{name}{}", size.to_uppercase());
+                format!(
+                    "
+                \"{size}\" => Ok(Instruction::{variant} {{
+                    dest: IRegister::from_string(operands[0])?,
+                    addr: IRegister::from_string(operands[1])?,
+                    src: IRegister::from_string(operands[2])?,
+                    aq,
+                    rl,
+                }}),"
+                )
+            })
+            .collect();
+        let names = sizes
+            .iter()
+            .map(|size| match size.as_str() {
+                "b" => "byte (b)",
+                "h" => "halfword (h)",
+                "w" => "word (w)",
+                "d" => "doubleword (d)",
+                _ => panic!("unknown size {size}"),
+            })
+            .collect::<Vec<_>>();
+        let names = match names.split_last() {
+            Some((last, [])) => last.to_string(),
+            Some((last, rest)) => format!("{} or {last}", rest.join(", ")),
+            None => unreachable!(),
+        };
+        let short = sizes.join("/");
         let p = format!(
             "
-            if mnemonics.len() == 1 {{
-                Err(\"{lower} must have size (w/d)\".to_owned())
-            }} else if mnemonics.len() == 2 {{
-                if mnemonics[1] == \"w\" {{
-                    Ok(Instruction::{wname}{{
-                        dest: IRegister::from_string(operands[0])?,
-                        addr: IRegister::from_string(operands[1])?,
-                        src: IRegister::from_string(operands[2])?,
-                        rl: false,
-                        aq: false,
-                }})
-                }} else if mnemonics[1] == \"d\" {{
-                    Ok(Instruction::{dname}{{
-                        dest: IRegister::from_string(operands[0])?,
-                        addr: IRegister::from_string(operands[1])?,
-                        src: IRegister::from_string(operands[2])?,
-                        rl: false,
-                        aq: false,
-                    }})
-                }} else {{
-                    Err(\"size of {lower} instruction must be word (w) or doubleword (d)\".to_owned())
-                }}
-            }} else if mnemonics.len() == 3 {{
-                let (aq, rl) = match mnemonics[2] {{
-                    \"\" => (false, false),
-                    \"aq\" => (true, false),
-                    \"rl\" => (false, true),
-                    \"aqrl\" => (true, true),
+            if operands.len() != 3 {{
+                Err(\"{lower} instruction requires 3 operands\".to_owned())
+            }} else if mnemonics.len() == 1 {{
+                Err(\"{lower} must have size ({short})\".to_owned())
+            }} else if mnemonics.len() > 3 {{
+                Err(\"{lower} instruction has too many suffixes, expected {lower}.size.ordering\".to_owned())
+            }} else {{
+                let (aq, rl) = match mnemonics.get(2) {{
+                    None | Some(&\"\") => (false, false),
+                    Some(&\"aq\") => (true, false),
+                    Some(&\"rl\") => (false, true),
+                    Some(&\"aqrl\") => (true, true),
                     _ => return Err(\"ordering should be (aq)(rl)\".to_owned()),
                 }};
-                if mnemonics[1] == \"w\" {{
-                    Ok(Instruction::{wname}{{
-                        dest: IRegister::from_string(operands[0])?,
-                        addr: IRegister::from_string(operands[1])?,
-                        src: IRegister::from_string(operands[2])?,
-                        aq,
-                        rl,
-                    }})
-                }} else if mnemonics[1] == \"d\" {{
-                    Ok(Instruction::{dname}{{
-                        dest: IRegister::from_string(operands[0])?,
-                        addr: IRegister::from_string(operands[1])?,
-                        src: IRegister::from_string(operands[2])?,
-                        aq,
-                        rl,
-                    }})
-                }} else {{
-                    Err(\"size of {lower} isntruction must be word (w) or doubleword (d)\".to_owned())
+                match mnemonics[1] {{{arms}
+                    _ => Err(\"size of {lower} instruction must be {names}\".to_owned()),
                 }}
-            }} else {{
-                Err(\"{lower} instruction has too many suffixes, expected {lower}.size.ordering\".to_owned())
             }}
         "
         );
@@ -264,31 +290,86 @@ pub fn amo_assemble(input: TokenStream) -> TokenStream {
 pub fn fr_assemble(input: TokenStream) -> TokenStream {
     if let TokenTree::Ident(i) = input.into_iter().next().unwrap() {
         let name = i.to_string();
-        let sname = name.clone() + "S";
-        let _dname = name.clone() + "D";
         let lower = name.to_lowercase();
         format!(
             "
         if operands.len() != 3 {{
                 Err(\"{lower} instruction requires 3 operands\".to_owned())
         }} else {{
-                if mnemonics.len() == 2 {{
-                    Ok(Instruction::{sname}{{
-                        dest: FRegister::try_from(operands[0])?,
-                        src1: FRegister::try_from(operands[1])?,
-                        src2: FRegister::try_from(operands[2])?,
-                        rm: RoundingMode::DYN,
-                    }})
-        }}else if mnemonics.len() == 3 {{
-                    Ok(Instruction::{sname}{{
-                        dest: FRegister::try_from(operands[0])?,
-                        src1: FRegister::try_from(operands[1])?,
-                        src2: FRegister::try_from(operands[2])?,
-                        rm: RoundingMode::from_str(mnemonics[2])?, 
-                    }})
-        }}else{{
-                    Err(\"fadd instruction requires a suffix {{s,d}}\".to_owned())
+                let quad = quad_precision(&mnemonics)?;
+                let dest = FRegister::try_from(operands[0])?;
+                let src1 = FRegister::try_from(operands[1])?;
+                let src2 = FRegister::try_from(operands[2])?;
+                let rm = float_rounding_mode(&mnemonics, 2)?;
+                if quad {{
+                    Ok(Instruction::{name}Q {{ dest, src1, src2, rm }})
+                }} else {{
+                    Ok(Instruction::{name}S {{ dest, src1, src2, rm }})
+                }}
+        }}
+            "
+        )
+        .parse()
+        .unwrap()
+    } else {
+        panic!("expected identifier");
+    }
+}
+
+// assembles an fr type instruction without a rounding mode
+#[proc_macro]
+pub fn frr_assemble(input: TokenStream) -> TokenStream {
+    if let TokenTree::Ident(i) = input.into_iter().next().unwrap() {
+        let name = i.to_string();
+        let lower = name.to_lowercase();
+        format!(
+            "
+        if operands.len() != 3 {{
+                Err(\"{lower} instruction requires 3 operands\".to_owned())
+        }} else if mnemonics.len() > 2 {{
+                Err(\"{lower} instruction does not take a rounding mode\".to_owned())
+        }} else {{
+                let quad = quad_precision(&mnemonics)?;
+                let dest = FRegister::try_from(operands[0])?;
+                let src1 = FRegister::try_from(operands[1])?;
+                let src2 = FRegister::try_from(operands[2])?;
+                if quad {{
+                    Ok(Instruction::{name}Q {{ dest, src1, src2 }})
+                }} else {{
+                    Ok(Instruction::{name}S {{ dest, src1, src2 }})
+                }}
         }}
+            "
+        )
+        .parse()
+        .unwrap()
+    } else {
+        panic!("expected identifier");
+    }
+}
+
+// assembles an r4 type fused multiply-add instruction
+#[proc_macro]
+pub fn fr4_assemble(input: TokenStream) -> TokenStream {
+    if let TokenTree::Ident(i) = input.into_iter().next().unwrap() {
+        let name = i.to_string();
+        let lower = name.to_lowercase();
+        format!(
+            "
+        if operands.len() != 4 {{
+                Err(\"{lower} instruction requires 4 operands\".to_owned())
+        }} else {{
+                let quad = quad_precision(&mnemonics)?;
+                let dest = FRegister::try_from(operands[0])?;
+                let src1 = FRegister::try_from(operands[1])?;
+                let src2 = FRegister::try_from(operands[2])?;
+                let src3 = FRegister::try_from(operands[3])?;
+                let rm = float_rounding_mode(&mnemonics, 2)?;
+                if quad {{
+                    Ok(Instruction::{name}Q {{ dest, src1, src2, src3, rm }})
+                }} else {{
+                    Ok(Instruction::{name}S {{ dest, src1, src2, src3, rm }})
+                }}
         }}
             "
         )
@@ -312,7 +393,7 @@ pub fn ci_assemble(input: TokenStream) -> TokenStream {
         }} else {{
             Ok(CInstruction::{name}{{
                 dest: IRegister::from_string(operands[0])?,
-                imm: CIImmediate::try_from(parse_int(operands[1])?)?,
+                imm: compressed_immediate(\"{lower}\", parse_int(operands[1])?)?,
             }})
         }}"
         )
@@ -335,8 +416,8 @@ pub fn cr_assemble(input: TokenStream) -> TokenStream {
             Err(\"c.{lower} instruction requires 2 operands\".to_owned())
         }} else {{
             Ok(CInstruction::{name}{{
-                dest: CIRegister::try_from(operands[0])?,
-                src: CIRegister::try_from(operands[1])?,
+                dest: compressed_register(\"{lower}\", operands[0])?,
+                src: compressed_register(\"{lower}\", operands[1])?,
             }})
         }}"
         )
@@ -444,21 +525,19 @@ pub fn make_immediate(input: TokenStream) -> TokenStream {
 
         let struct_string = format!(
             "
-            #[derive(Debug, PartialEq, Copy, Clone)]
+            #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Copy, Clone)]
             pub struct {name} {{
                 val: {typ},
             }}
         "
         );
 
-        let bounds_condition = if signed {
-            format!(
-                "value > 2i64.pow({}) - 1 || value < -2i64.pow({})",
-                size - 1,
-                size - 1
-            )
+        let alignment = align_pattern + 1;
+        // the smallest and largest aligned values this immediate can hold
+        let (min, max): (i64, i64) = if signed {
+            (-(1 << (size - 1)), ((1 << (size - 1)) - 1) & !align_pattern)
         } else {
-            format!("value > 2i64.pow({}) - 1 || value < 0", size)
+            (0, ((1 << size) - 1) & !align_pattern)
         };
 
         let impl_string = format!(
@@ -467,10 +546,10 @@ pub fn make_immediate(input: TokenStream) -> TokenStream {
             type Error = String;
 
             fn try_from(value: i64) -> Result<Self, Self::Error> {{
-                if {bounds_condition} {{
-                    Err(format!(\"attempted to construct out of range {name}\"))
+                if !(<{name} as Immediate>::MIN..=<{name} as Immediate>::MAX).contains(&value) {{
+                    Err(format!(\"{{value}} is out of range for {name} ({min} to {max})\"))
                 }}else if value & {align_pattern} != 0 {{
-                    Err(format!(\"attempted to construct unaligned {name}\"))
+                    Err(format!(\"{{value}} is not a multiple of {alignment} for {name}\"))
                 }}else {{
                     Ok({name} {{ val: value as {typ} }})
                 }}
@@ -487,6 +566,16 @@ pub fn make_immediate(input: TokenStream) -> TokenStream {
             pub fn val(self) -> i64 {{
                 return self.into()
             }}
+
+            /// The value of this immediate.
+            pub fn as_i32(self) -> i32 {{
+                self.val() as i32
+            }}
+
+            /// The bits of this immediate, without sign extension.
+            pub fn as_u32(self) -> u32 {{
+                (self.val() & ((1 << {size}) - 1)) as u32
+            }}
         }}
 
         "
@@ -559,6 +648,12 @@ pub fn make_immediate(input: TokenStream) -> TokenStream {
 
             let ret = format!("return ({insertions}) as {instr_typ};");
 
+            let fields = if compressed {
+                "self.to_u16() as u32"
+            } else {
+                "self.to_u32()"
+            };
+
             format!(
                 "
             impl {name} {{
@@ -567,6 +662,11 @@ pub fn make_immediate(input: TokenStream) -> TokenStream {
                     {extractions}
                     {ret}
                 }}
+
+                /// The bits of this immediate positioned as they are in the instruction word.
+                pub fn to_u32_fields(&self) -> u32 {{
+                    {fields}
+                }}
             }}
             "
             )
@@ -581,6 +681,58 @@ pub fn make_immediate(input: TokenStream) -> TokenStream {
             }}"
         );
 
+        let trait_string = if compressed {
+            format!(
+                "
+            impl Immediate for {name} {{
+                const BITS: u32 = {size};
+                const MIN: i64 = {min};
+                const MAX: i64 = {max};
+
+                fn decode(instruction: u32) -> Self {{
+                    {name}::from_u16(instruction as u16)
+                }}
+
+                fn try_new(value: i64) -> Result<Self, String> {{
+                    {name}::try_from(value)
+                }}
+
+                fn value(self) -> i64 {{
+                    self.val()
+                }}
+
+                fn encode(self) -> u32 {{
+                    self.to_u32_fields()
+                }}
+            }}"
+            )
+        } else {
+            format!(
+                "
+            impl Immediate for {name} {{
+                const BITS: u32 = {size};
+                const MIN: i64 = {min};
+                const MAX: i64 = {max};
+
+                fn decode(instruction: u32) -> Self {{
+                    {name}::from_u32(instruction)
+                }}
+
+                fn try_new(value: i64) -> Result<Self, String> {{
+                    {name}::try_from(value)
+                }}
+
+                fn value(self) -> i64 {{
+                    self.val()
+                }}
+
+                fn encode(self) -> u32 {{
+                    self.to_u32_fields()
+                }}
+            }}"
+            )
+        };
+
         let final_str = format!(
             "
             {struct_string}
@@ -588,6 +740,7 @@ pub fn make_immediate(input: TokenStream) -> TokenStream {
             {extract_fn}
             {insert_fn}
             {display_string}
+            {trait_string}
             "
         );
         // println!("{}", final_str);
@@ -596,4 +749,4 @@ pub fn make_immediate(input: TokenStream) -> TokenStream {
     } else {
         panic!("first token should be an Identifier")
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,1068 @@
+//! Analyses of regions of decoded machine code.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+
+use crate::cinstruction::CInstruction;
+use crate::config::{DecoderConfig, FormatOptions, RegisterStyle};
+use crate::instruction::Instruction;
+use crate::register::IRegister;
+use crate::semantics::Effect;
+
+/// An instruction decoded from a region of code, compressed instructions are expanded.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Decoded {
+    pub address: u64,
+    /// The length of the encoding in bytes (2 or 4).
+    pub length: u8,
+    pub instruction: Instruction,
+}
+
+/// Converts a dump of big endian 32-bit words, such as one captured from a big endian bus, to the little endian byte
+/// order that code is stored in.
+///
+/// Each word holds one 32-bit instruction or two 16-bit parcels, with the first parcel in the low half.
+pub fn from_be_words(bytes: &[u8]) -> Result<Vec<u8>, String> {
+    if !bytes.len().is_multiple_of(4) {
+        return Err(format!(
+            "a dump of 32-bit words can not have a length of {} bytes",
+            bytes.len()
+        ));
+    }
+    Ok(bytes
+        .chunks(4)
+        .flat_map(|word| u32::from_be_bytes(word.try_into().unwrap()).to_le_bytes())
+        .collect())
+}
+
+/// Decodes a little endian region of code starting at `base` from start to end.
+///
+/// Encodings that can not be decoded are skipped.
+pub fn decode_region(bytes: &[u8], base: u64, config: &DecoderConfig) -> Vec<Decoded> {
+    let mut decoded = Vec::new();
+    let mut offset = 0;
+    while offset + 2 <= bytes.len() {
+        let low = u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+        let (length, instruction) = if low & 0b11 == 0b11 {
+            if offset + 4 > bytes.len() {
+                break;
+            }
+            let high = u16::from_le_bytes([bytes[offset + 2], bytes[offset + 3]]);
+            (
+                4,
+                Instruction::decode_with(low as u32 | (high as u32) << 16, config),
+            )
+        } else {
+            (2, CInstruction::decode_expanded_with(low, config))
+        };
+        if let Ok(instruction) = instruction {
+            decoded.push(Decoded {
+                address: base + offset as u64,
+                length,
+                instruction,
+            });
+        }
+        offset += length as usize;
+    }
+    decoded
+}
+
+/// A line of a disassembly listing, either an instruction or an encoding that could not be decoded.
+pub(crate) struct ListingEntry {
+    pub address: u64,
+    pub raw: u32,
+    pub length: u8,
+    /// The disassembly, compressed instructions are shown unexpanded.
+    pub text: String,
+    /// The decoded instruction, compressed instructions are expanded.
+    pub instruction: Option<Instruction>,
+}
+
+impl ListingEntry {
+    /// The raw encoding in hex, padded to the width of a 32-bit encoding.
+    pub fn raw_hex(&self) -> String {
+        match self.length {
+            2 => format!("{:04x}    ", self.raw),
+            _ => format!("{:08x}", self.raw),
+        }
+    }
+}
+
+/// Decodes a region of code for a listing, keeping undecodable encodings as `.2byte` or `.4byte` directives.
+pub(crate) fn listing(bytes: &[u8], base: u64, config: &DecoderConfig) -> Vec<ListingEntry> {
+    let mut entries = Vec::new();
+    let mut offset = 0;
+    while offset + 2 <= bytes.len() {
+        let address = base + offset as u64;
+        let low = u16::from_le_bytes([bytes[offset], bytes[offset + 1]]);
+        let entry = if low & 0b11 == 0b11 && offset + 4 <= bytes.len() {
+            let raw = u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+            let instruction = Instruction::decode_with(raw, config).ok();
+            ListingEntry {
+                address,
+                raw,
+                length: 4,
+                text: match &instruction {
+                    Some(i) => i.to_string(),
+                    None => format!(".4byte {raw:#x}"),
+                },
+                instruction,
+            }
+        } else {
+            let compressed = CInstruction::decode_with(low, config).ok();
+            ListingEntry {
+                address,
+                raw: low as u32,
+                length: 2,
+                text: match &compressed {
+                    Some(c) => c.to_string(),
+                    None => format!(".2byte {low:#x}"),
+                },
+                instruction: compressed.and_then(|c| c.try_expand().ok()),
+            }
+        };
+        offset += entry.length as usize;
+        entries.push(entry);
+    }
+    entries
+}
+
+/// An instruction that allocates or frees a stack frame.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum FrameMarker {
+    /// `addi sp,sp,-frame` followed by a save of ra or s0.
+    Prologue { frame: u64 },
+    /// `addi sp,sp,frame` followed by a `ret`.
+    Epilogue { frame: u64 },
+}
+
+/// Displays the marker as it is annotated in listings (`prologue, frame=32`).
+impl Display for FrameMarker {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FrameMarker::Prologue { frame } => write!(f, "prologue, frame={frame}"),
+            FrameMarker::Epilogue { frame } => write!(f, "epilogue, frame={frame}"),
+        }
+    }
+}
+
+/// The number of instructions after the allocation of a frame that the saves of ra or s0 are looked for in.
+const PROLOGUE_WINDOW: usize = 4;
+
+/// Finds the standard sequences that set up and tear down stack frames, returning a marker for the address of each
+/// instruction that adjusts sp.
+///
+/// A prologue decrements sp and saves ra or s0 to the stack within the next few instructions. An epilogue increments
+/// sp and returns, with only restores of registers from the stack in between.
+pub fn find_frames(code: &[Decoded]) -> BTreeMap<u64, FrameMarker> {
+    let mut frames = BTreeMap::new();
+    for (index, decoded) in code.iter().enumerate() {
+        let Instruction::ADDI {
+            dest: IRegister::StackPointer,
+            src: IRegister::StackPointer,
+            imm,
+        } = decoded.instruction
+        else {
+            continue;
+        };
+        let frame = imm.val().unsigned_abs();
+        let rest = &code[index + 1..];
+        let marker = if imm.val() < 0 {
+            let saves = rest.iter().take(PROLOGUE_WINDOW).any(|d| {
+                matches!(
+                    d.instruction,
+                    Instruction::SD { src, base: IRegister::StackPointer, .. }
+                        | Instruction::SW { src, base: IRegister::StackPointer, .. }
+                        if matches!(src, IRegister::ReturnAddress | IRegister::FramePointer)
+                )
+            });
+            saves.then_some(FrameMarker::Prologue { frame })
+        } else {
+            let end = rest.iter().find(|d| {
+                !matches!(
+                    d.instruction,
+                    Instruction::LD {
+                        base: IRegister::StackPointer,
+                        ..
+                    } | Instruction::LW {
+                        base: IRegister::StackPointer,
+                        ..
+                    }
+                )
+            });
+            let returns = end.is_some_and(|d| {
+                matches!(
+                    d.instruction,
+                    Instruction::JALR { dest: IRegister::Zero, base: IRegister::ReturnAddress, offset }
+                        if offset.val() == 0
+                )
+            });
+            returns.then_some(FrameMarker::Epilogue { frame })
+        };
+        if let Some(marker) = marker.filter(|_| frame != 0) {
+            frames.insert(decoded.address, marker);
+        }
+    }
+    frames
+}
+
+/// A named address, such as a function from a symbol table.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Symbol {
+    pub address: u64,
+    pub name: String,
+}
+
+/// Returns `symbol+offset` for the closest symbol at or before an address.
+pub(crate) fn describe(symbols: &[Symbol], address: u64) -> Option<String> {
+    let symbol = symbols
+        .iter()
+        .filter(|s| s.address <= address)
+        .max_by_key(|s| s.address)?;
+    Some(match address - symbol.address {
+        0 => symbol.name.clone(),
+        offset => format!("{}+{offset:#x}", symbol.name),
+    })
+}
+
+/// Returns the name of the symbol at an address, or the address in hex.
+fn name(symbols: &[Symbol], address: u64) -> String {
+    symbols
+        .iter()
+        .find(|s| s.address == address)
+        .map(|s| s.name.clone())
+        .unwrap_or_else(|| format!("{address:#x}"))
+}
+
+/// Returns the target of a direct call (jal or auipc+jalr with a link register) at `code[index]`.
+fn call_target(code: &[Decoded], index: usize) -> Option<u64> {
+    match code[index].instruction {
+        Instruction::JAL { dest, offset } if dest != IRegister::Zero => {
+            Some(code[index].address.wrapping_add(offset.val() as u64))
+        }
+        Instruction::AUIPC { dest, imm } => match code.get(index + 1)?.instruction {
+            Instruction::JALR {
+                dest: link,
+                base,
+                offset,
+            } if base == dest && link != IRegister::Zero => Some(
+                code[index]
+                    .address
+                    .wrapping_add((imm.val() << 12) as u64)
+                    .wrapping_add(offset.val() as u64),
+            ),
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// A graph of the direct calls between functions.
+///
+/// Functions are identified by their start address. They are taken from the symbols and the targets of calls, and
+/// each instruction belongs to the closest function that starts before it.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct CallGraph {
+    /// The callees of each function, functions without calls are included with no callees.
+    pub calls: BTreeMap<u64, BTreeSet<u64>>,
+    pub symbols: Vec<Symbol>,
+}
+
+impl CallGraph {
+    /// Builds the call graph of a region of decoded code.
+    pub fn build(code: &[Decoded], symbols: &[Symbol]) -> CallGraph {
+        let targets: Vec<(u64, u64)> = (0..code.len())
+            .filter_map(|i| call_target(code, i).map(|target| (code[i].address, target)))
+            .collect();
+        let mut calls: BTreeMap<u64, BTreeSet<u64>> = symbols
+            .iter()
+            .map(|s| s.address)
+            .chain(targets.iter().map(|(_, target)| *target))
+            .map(|function| (function, BTreeSet::new()))
+            .collect();
+        for (address, target) in targets {
+            if let Some((_, callees)) = calls.range_mut(..=address).next_back() {
+                callees.insert(target);
+            }
+        }
+        CallGraph {
+            calls,
+            symbols: symbols.to_vec(),
+        }
+    }
+
+    /// Returns the functions called by a function.
+    pub fn callees(&self, function: u64) -> impl Iterator<Item = u64> + '_ {
+        self.calls.get(&function).into_iter().flatten().copied()
+    }
+
+    /// Returns the functions that call a function.
+    pub fn callers(&self, function: u64) -> impl Iterator<Item = u64> + '_ {
+        self.calls
+            .iter()
+            .filter(move |(_, callees)| callees.contains(&function))
+            .map(|(caller, _)| *caller)
+    }
+
+    /// Returns the name of a function, or its address in hex if it has no symbol.
+    pub fn name(&self, function: u64) -> String {
+        name(&self.symbols, function)
+    }
+}
+
+/// Prints one `caller -> callee` edge per line.
+impl Display for CallGraph {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (caller, callees) in &self.calls {
+            for callee in callees {
+                writeln!(f, "{} -> {}", self.name(*caller), self.name(*callee))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// How an instruction refers to an address.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord)]
+pub enum XrefKind {
+    /// A jump that saves a return address.
+    Call,
+    /// A jump or branch without a return address.
+    Jump,
+    Load,
+    Store,
+}
+
+/// A reference to an address from an instruction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Xref {
+    /// The address of the referencing instruction.
+    pub from: u64,
+    pub kind: XrefKind,
+}
+
+/// An index of the references to each address in a region of code.
+///
+/// Jumps and branches are found from their immediates and from auipc+jalr pairs. Loads and stores are found when
+/// their base register holds a constant built with lui or auipc and addi. Constants are forgotten at every jump or
+/// branch, so only references within a straight line of code are found.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct XrefIndex {
+    /// The references to each address, in the order of the referencing instructions.
+    pub refs: BTreeMap<u64, Vec<Xref>>,
+}
+
+impl XrefIndex {
+    /// Builds the cross-reference index of a region of decoded code.
+    pub fn build(code: &[Decoded]) -> XrefIndex {
+        let mut index = XrefIndex::default();
+        let mut constants: BTreeMap<IRegister, u64> = BTreeMap::new();
+        for (i, decoded) in code.iter().enumerate() {
+            let address = decoded.address;
+            if let Some(target) = call_target(code, i) {
+                index.insert(target, address, XrefKind::Call);
+            } else if let Some(target) = decoded.instruction.branch_target(address) {
+                index.insert(target, address, XrefKind::Jump);
+            }
+            if let Instruction::JALR {
+                dest: IRegister::Zero,
+                base,
+                offset,
+            } = decoded.instruction
+                && let Some(c) = constants.get(&base)
+            {
+                index.insert(c.wrapping_add(offset.val() as u64), address, XrefKind::Jump);
+            }
+            if let Some((base, offset, kind)) = memory_access(&decoded.instruction)
+                && let Some(c) = constants.get(&base)
+            {
+                index.insert(c.wrapping_add(offset as u64), address, kind);
+            }
+
+            let constant = match decoded.instruction {
+                Instruction::LUI { dest, imm } => Some((dest, (imm.val() << 12) as u64)),
+                Instruction::AUIPC { dest, imm } => {
+                    Some((dest, address.wrapping_add((imm.val() << 12) as u64)))
+                }
+                Instruction::ADDI { dest, src, imm } => constants
+                    .get(&src)
+                    .map(|c| (dest, c.wrapping_add(imm.val() as u64))),
+                _ => None,
+            };
+            let control = matches!(
+                decoded.instruction,
+                Instruction::JAL { .. } | Instruction::JALR { .. }
+            ) || decoded.instruction.branch_target(address).is_some();
+            match (constant, writes(&decoded.instruction)) {
+                _ if control => constants.clear(),
+                (Some((IRegister::Zero, _)), _) => {}
+                (Some((dest, c)), _) => {
+                    constants.insert(dest, c);
+                }
+                (None, Some(dest)) => {
+                    constants.remove(&dest);
+                }
+                // float and csr instructions have no semantics, but only float loads and stores leave the integer
+                // registers alone
+                (None, None) if decoded.instruction.semantics().is_none() => {
+                    if memory_access(&decoded.instruction).is_none() {
+                        constants.clear();
+                    }
+                }
+                (None, None) => {}
+            }
+        }
+        index
+    }
+
+    fn insert(&mut self, target: u64, from: u64, kind: XrefKind) {
+        self.refs
+            .entry(target)
+            .or_default()
+            .push(Xref { from, kind });
+    }
+
+    /// Returns the references to an address.
+    pub fn to(&self, address: u64) -> &[Xref] {
+        self.refs.get(&address).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    /// Returns the references of one kind to an address.
+    pub fn to_kind(&self, address: u64, kind: XrefKind) -> impl Iterator<Item = u64> + '_ {
+        self.to(address)
+            .iter()
+            .filter(move |x| x.kind == kind)
+            .map(|x| x.from)
+    }
+
+    /// Returns the addresses of the instructions that call an address.
+    pub fn callers(&self, address: u64) -> impl Iterator<Item = u64> + '_ {
+        self.to_kind(address, XrefKind::Call)
+    }
+}
+
+/// Returns the base register, offset and kind of a load or store.
+fn memory_access(instruction: &Instruction) -> Option<(IRegister, i64, XrefKind)> {
+    match *instruction {
+        Instruction::LB { base, offset, .. }
+        | Instruction::LH { base, offset, .. }
+        | Instruction::LW { base, offset, .. }
+        | Instruction::LBU { base, offset, .. }
+        | Instruction::LHU { base, offset, .. }
+        | Instruction::LWU { base, offset, .. }
+        | Instruction::LD { base, offset, .. }
+        | Instruction::FLH { base, offset, .. }
+        | Instruction::FLW { base, offset, .. }
+        | Instruction::FLQ { base, offset, .. } => Some((base, offset.val(), XrefKind::Load)),
+        Instruction::SB { base, offset, .. }
+        | Instruction::SH { base, offset, .. }
+        | Instruction::SW { base, offset, .. }
+        | Instruction::SD { base, offset, .. }
+        | Instruction::FSH { base, offset, .. }
+        | Instruction::FSW { base, offset, .. }
+        | Instruction::FSQ { base, offset, .. } => Some((base, offset.val(), XrefKind::Store)),
+        Instruction::CBOZERO { base } => Some((base, 0, XrefKind::Store)),
+        #[cfg(feature = "rv128")]
+        Instruction::LQ { base, offset, .. } | Instruction::LDU { base, offset, .. } => {
+            Some((base, offset.val(), XrefKind::Load))
+        }
+        #[cfg(feature = "rv128")]
+        Instruction::SQ { base, offset, .. } => Some((base, offset.val(), XrefKind::Store)),
+        Instruction::Vector(ref i) => i.base().map(|base| {
+            let kind = if i.is_store() {
+                XrefKind::Store
+            } else {
+                XrefKind::Load
+            };
+            (base, 0, kind)
+        }),
+        _ => None,
+    }
+}
+
+/// A table of jump targets used by an indirect jump, as generated for `switch` statements.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct JumpTable {
+    /// The address of the indirect jump.
+    pub jump: u64,
+    /// The address of the first entry.
+    pub table: u64,
+    /// The size of each entry in bytes (4 or 8).
+    pub entry_size: u8,
+    /// Whether entries are offsets from the start of the table instead of absolute addresses.
+    pub relative: bool,
+    pub targets: Vec<u64>,
+}
+
+/// What is known about the value of a register while looking for a jump table.
+#[derive(Debug, Clone, Copy)]
+enum Value {
+    Constant(u64),
+    /// A scaled index, with the number of entries if it was bounds checked.
+    Index {
+        scale: u64,
+        count: Option<u64>,
+    },
+    /// The address of an entry.
+    Address {
+        table: u64,
+        scale: u64,
+        count: Option<u64>,
+    },
+    /// An entry loaded from a table.
+    Entry {
+        table: u64,
+        size: u8,
+        count: Option<u64>,
+    },
+    /// An entry added to the start of the table.
+    Relative {
+        table: u64,
+        size: u8,
+        count: Option<u64>,
+    },
+}
+
+/// The number of instructions before an indirect jump that are searched for the table.
+const JUMP_TABLE_WINDOW: usize = 16;
+
+/// Looks for jump tables used by the indirect jumps (`jr`) in a region of code.
+///
+/// This recognizes the common lowering of a bounds checked index that is scaled, added to the address of a table
+/// (built with lui or auipc and addi) and loaded, with the loaded entry either used directly or added to the address
+/// of the table first. Entries are read from `data`, which starts at `data_base`.
+pub fn find_jump_tables(code: &[Decoded], data: &[u8], data_base: u64) -> Vec<JumpTable> {
+    let mut tables = Vec::new();
+    for (i, decoded) in code.iter().enumerate() {
+        let Instruction::JALR {
+            dest: IRegister::Zero,
+            base,
+            offset,
+        } = decoded.instruction
+        else {
+            continue;
+        };
+        if offset.val() != 0 {
+            continue;
+        }
+        let values = track_values(&code[i.saturating_sub(JUMP_TABLE_WINDOW)..i]);
+        let (table, size, count, relative) = match values.get(&base) {
+            Some(Value::Entry {
+                table,
+                size,
+                count: Some(count),
+            }) => (*table, *size, *count, false),
+            Some(Value::Relative {
+                table,
+                size,
+                count: Some(count),
+            }) => (*table, *size, *count, true),
+            _ => continue,
+        };
+        let targets = (0..count)
+            .map(|n| {
+                let address = table.wrapping_add(n * size as u64);
+                let index = address.checked_sub(data_base)? as usize;
+                let bytes = data.get(index..index.checked_add(size as usize)?)?;
+                let entry = match size {
+                    4 => i32::from_le_bytes(bytes.try_into().unwrap()) as i64 as u64,
+                    _ => u64::from_le_bytes(bytes.try_into().unwrap()),
+                };
+                Some(if relative {
+                    table.wrapping_add(entry)
+                } else {
+                    entry
+                })
+            })
+            .collect::<Option<Vec<u64>>>();
+        if let Some(targets) = targets {
+            tables.push(JumpTable {
+                jump: decoded.address,
+                table,
+                entry_size: size,
+                relative,
+                targets,
+            });
+        }
+    }
+    tables
+}
+
+/// Follows the values of registers through a sequence of instructions.
+fn track_values(code: &[Decoded]) -> BTreeMap<IRegister, Value> {
+    let mut values: BTreeMap<IRegister, Value> = BTreeMap::new();
+    // the number of entries implied by bounds checks of each register
+    let mut bounds: BTreeMap<IRegister, u64> = BTreeMap::new();
+    let constant = |values: &BTreeMap<IRegister, Value>, r: IRegister| match r {
+        IRegister::Zero => Some(0),
+        r => match values.get(&r) {
+            Some(Value::Constant(c)) => Some(*c),
+            _ => None,
+        },
+    };
+    for decoded in code {
+        let (dest, value) = match decoded.instruction {
+            Instruction::LUI { dest, imm } => {
+                (dest, Some(Value::Constant((imm.val() << 12) as u64)))
+            }
+            Instruction::AUIPC { dest, imm } => (
+                dest,
+                Some(Value::Constant(
+                    decoded.address.wrapping_add((imm.val() << 12) as u64),
+                )),
+            ),
+            Instruction::ADDI { dest, src, imm } => (
+                dest,
+                constant(&values, src).map(|c| Value::Constant(c.wrapping_add(imm.val() as u64))),
+            ),
+            Instruction::SLLI { dest, src, shamt } => (
+                dest,
+                Some(Value::Index {
+                    scale: 1 << shamt.val(),
+                    count: bounds.get(&src).copied(),
+                }),
+            ),
+            Instruction::ADD { dest, src1, src2 } => {
+                let value = match (values.get(&src1), values.get(&src2)) {
+                    (Some(Value::Index { scale, count }), Some(Value::Constant(table)))
+                    | (Some(Value::Constant(table)), Some(Value::Index { scale, count })) => {
+                        Some(Value::Address {
+                            table: *table,
+                            scale: *scale,
+                            count: *count,
+                        })
+                    }
+                    (Some(Value::Entry { table, size, count }), Some(Value::Constant(base)))
+                    | (Some(Value::Constant(base)), Some(Value::Entry { table, size, count }))
+                        if base == table =>
+                    {
+                        Some(Value::Relative {
+                            table: *table,
+                            size: *size,
+                            count: *count,
+                        })
+                    }
+                    _ => None,
+                };
+                (dest, value)
+            }
+            Instruction::LW { dest, base, offset } | Instruction::LD { dest, base, offset } => {
+                let size = if let Instruction::LW { .. } = decoded.instruction {
+                    4
+                } else {
+                    8
+                };
+                let value = match values.get(&base) {
+                    Some(Value::Address {
+                        table,
+                        scale,
+                        count,
+                    }) if *scale == size as u64 => Some(Value::Entry {
+                        table: table.wrapping_add(offset.val() as u64),
+                        size,
+                        count: *count,
+                    }),
+                    _ => None,
+                };
+                (dest, value)
+            }
+            // a branch to the default case if the index is out of bounds
+            Instruction::BLTU { src1, src2, .. } => {
+                if let Some(max) = constant(&values, src1) {
+                    bounds.insert(src2, max + 1);
+                }
+                continue;
+            }
+            Instruction::BGEU { src1, src2, .. } => {
+                if let Some(count) = constant(&values, src2) {
+                    bounds.insert(src1, count);
+                }
+                continue;
+            }
+            _ => match writes(&decoded.instruction) {
+                Some(dest) => (dest, None),
+                None => continue,
+            },
+        };
+        bounds.remove(&dest);
+        match value {
+            Some(value) if dest != IRegister::Zero => values.insert(dest, value),
+            _ => values.remove(&dest),
+        };
+    }
+    values
+}
+
+/// Returns the integer register written by an instruction, if any.
+fn writes(instruction: &Instruction) -> Option<IRegister> {
+    instruction
+        .semantics()?
+        .into_iter()
+        .find_map(|effect| match effect {
+            Effect::Write(r, _) | Effect::StoreConditional { dest: r, .. } => Some(r),
+            _ => None,
+        })
+}
+
+/// Options controlling how two regions of code are compared.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct DiffOptions {
+    /// Treat instructions that only differ in their registers as equal, as long as the registers are renamed
+    /// consistently within each function.
+    pub ignore_registers: bool,
+}
+
+/// A difference between two regions of code at one address.
+#[derive(Debug, PartialEq, Clone)]
+pub enum Difference {
+    Changed {
+        address: u64,
+        old: Instruction,
+        new: Instruction,
+    },
+    Removed {
+        address: u64,
+        old: Instruction,
+    },
+    Added {
+        address: u64,
+        new: Instruction,
+    },
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Difference::Changed { address, old, new } => {
+                write!(f, "{address:#x}: -{old} +{new}")
+            }
+            Difference::Removed { address, old } => write!(f, "{address:#x}: -{old}"),
+            Difference::Added { address, new } => write!(f, "{address:#x}: +{new}"),
+        }
+    }
+}
+
+/// The differences within one function.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FunctionDiff {
+    /// The function containing the differences, `None` for code before the first symbol.
+    pub function: Option<Symbol>,
+    pub differences: Vec<Difference>,
+}
+
+impl Display for FunctionDiff {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.function {
+            Some(symbol) => writeln!(f, "{} ({:#x}):", symbol.name, symbol.address)?,
+            None => writeln!(f, "(no symbol):")?,
+        }
+        for difference in &self.differences {
+            writeln!(f, "  {difference}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Splits the numeric disassembly of an instruction into its registers and everything else.
+fn registers_and_shape(instruction: &Instruction) -> (Vec<String>, String) {
+    let options = FormatOptions {
+        register_style: RegisterStyle::Numeric,
+        ..Default::default()
+    };
+    let text = instruction.display_with(&options).to_string();
+    let (mnemonic, operands) = text.split_once(' ').unwrap_or((&text, ""));
+    let mut registers = Vec::new();
+    let mut shape = format!("{mnemonic} ");
+    let mut token = String::new();
+    for c in operands.chars().chain(std::iter::once(',')) {
+        if c.is_ascii_alphanumeric() {
+            token.push(c);
+            continue;
+        }
+        let is_register = token.len() > 1
+            && (token.starts_with('x') || token.starts_with('f'))
+            && token[1..].chars().all(|c| c.is_ascii_digit());
+        if is_register {
+            shape.push(token.as_bytes()[0] as char);
+            registers.push(std::mem::take(&mut token));
+        } else {
+            shape.push_str(&std::mem::take(&mut token));
+        }
+        shape.push(c);
+    }
+    (registers, shape)
+}
+
+/// Compares two instructions, extending the register renaming when registers are ignored.
+fn equivalent(
+    old: &Instruction,
+    new: &Instruction,
+    renaming: &mut BTreeMap<String, String>,
+    options: &DiffOptions,
+) -> bool {
+    if !options.ignore_registers {
+        return old == new;
+    }
+    let (old_registers, old_shape) = registers_and_shape(old);
+    let (new_registers, new_shape) = registers_and_shape(new);
+    if old_shape != new_shape {
+        return false;
+    }
+    let mut extended = renaming.clone();
+    for (old, new) in old_registers.into_iter().zip(new_registers) {
+        // x0 always means zero, so it can not be renamed
+        if (old == "x0" || new == "x0") && old != new {
+            return false;
+        }
+        match extended.get(&old) {
+            Some(renamed) if *renamed != new => return false,
+            Some(_) => {}
+            None if extended.values().any(|renamed| *renamed == new) => return false,
+            None => {
+                extended.insert(old, new);
+            }
+        }
+    }
+    *renaming = extended;
+    true
+}
+
+/// Compares two regions of decoded code address by address, grouping the differences by function.
+pub fn diff(
+    old: &[Decoded],
+    new: &[Decoded],
+    symbols: &[Symbol],
+    options: &DiffOptions,
+) -> Vec<FunctionDiff> {
+    let old: BTreeMap<u64, &Instruction> =
+        old.iter().map(|d| (d.address, &d.instruction)).collect();
+    let new: BTreeMap<u64, &Instruction> =
+        new.iter().map(|d| (d.address, &d.instruction)).collect();
+    let addresses: BTreeSet<u64> = old.keys().chain(new.keys()).copied().collect();
+    let mut functions: Vec<Symbol> = symbols.to_vec();
+    functions.sort_by_key(|s| s.address);
+
+    let mut diffs: Vec<FunctionDiff> = Vec::new();
+    let mut renaming = BTreeMap::new();
+    let mut current: Option<Option<Symbol>> = None;
+    for address in addresses {
+        let function = functions
+            .iter()
+            .rev()
+            .find(|s| s.address <= address)
+            .cloned();
+        if current.as_ref() != Some(&function) {
+            renaming.clear();
+            current = Some(function.clone());
+        }
+        let difference = match (old.get(&address), new.get(&address)) {
+            (Some(o), Some(n)) if equivalent(o, n, &mut renaming, options) => continue,
+            (Some(o), Some(n)) => Difference::Changed {
+                address,
+                old: **o,
+                new: **n,
+            },
+            (Some(o), None) => Difference::Removed { address, old: **o },
+            (None, Some(n)) => Difference::Added { address, new: **n },
+            (None, None) => unreachable!(),
+        };
+        match diffs.last_mut() {
+            Some(last) if last.function == function => last.differences.push(difference),
+            _ => diffs.push(FunctionDiff {
+                function,
+                differences: vec![difference],
+            }),
+        }
+    }
+    diffs
+}
+
+/// A pair of adjacent instructions that common microarchitectures fuse into a single operation.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+pub enum FusionKind {
+    /// `lui rd,hi` followed by `addi rd,rd,lo` or `addiw rd,rd,lo`, a 32-bit constant (`li`).
+    LoadImmediate,
+    /// `auipc rd,hi` followed by `addi rd,rd,lo`, a PC-relative address (`la`).
+    PcRelativeAddress,
+    /// `lui rd,hi` or `auipc rd,hi` followed by a load to rd from `lo(rd)`, a load from an absolute or PC-relative
+    /// address.
+    LoadGlobal,
+    /// `auipc rd,hi` followed by `jalr` through rd, a far call or tail call (`call`, `tail`).
+    FarJump,
+    /// `slli rd,rs,1..3` followed by `add rd,rd,rs2`, the address of an array element (`sh1add` to `sh3add`).
+    IndexedAddress,
+    /// `add rd,rs1,rs2` followed by a load to rd from `0(rd)`, an indexed load.
+    IndexedLoad,
+    /// `slli rd,rs,32` followed by `srli rd,rd,29..32`, a zero extension of the low word that may be shifted left by up
+    /// to 3 (`zext.w`, `slli.uw`).
+    ZeroExtend,
+}
+
+impl Display for FusionKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            FusionKind::LoadImmediate => "load immediate",
+            FusionKind::PcRelativeAddress => "pc-relative address",
+            FusionKind::LoadGlobal => "load global",
+            FusionKind::FarJump => "far jump",
+            FusionKind::IndexedAddress => "indexed address",
+            FusionKind::IndexedLoad => "indexed load",
+            FusionKind::ZeroExtend => "zero extend",
+        })
+    }
+}
+
+/// An opportunity to fuse the instruction at `address` with the instruction that follows it.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Fusion {
+    /// The address of the first instruction of the pair.
+    pub address: u64,
+    pub kind: FusionKind,
+    /// The register written by the pair, for far jumps the link register (zero for a tail call).
+    pub dest: IRegister,
+    /// The constant or address the pair computes, loads from or jumps to when it does not depend on other registers,
+    /// so that the pair can be folded into a single pseudo-instruction.
+    pub value: Option<u64>,
+}
+
+/// Returns the destination, base register and offset of an integer load.
+fn integer_load(instruction: &Instruction) -> Option<(IRegister, IRegister, i64)> {
+    match *instruction {
+        Instruction::LB { dest, base, offset }
+        | Instruction::LH { dest, base, offset }
+        | Instruction::LW { dest, base, offset }
+        | Instruction::LBU { dest, base, offset }
+        | Instruction::LHU { dest, base, offset }
+        | Instruction::LWU { dest, base, offset }
+        | Instruction::LD { dest, base, offset } => Some((dest, base, offset.val())),
+        _ => None,
+    }
+}
+
+/// Returns the fusion of two adjacent instructions, if they form a fusible pair.
+fn fuse(first: &Decoded, second: &Decoded) -> Option<Fusion> {
+    let fusion = |kind, dest, value| {
+        Some(Fusion {
+            address: first.address,
+            kind,
+            dest,
+            value,
+        })
+    };
+    match (first.instruction, second.instruction) {
+        (
+            Instruction::LUI { dest, imm },
+            Instruction::ADDI {
+                dest: d,
+                src,
+                imm: lo,
+            },
+        )
+        | (
+            Instruction::LUI { dest, imm },
+            Instruction::ADDIW {
+                dest: d,
+                src,
+                imm: lo,
+            },
+        ) if d == dest && src == dest => {
+            let mut value = (imm.val() << 12).wrapping_add(lo.val());
+            if let Instruction::ADDIW { .. } = second.instruction {
+                value = value as i32 as i64;
+            }
+            fusion(FusionKind::LoadImmediate, dest, Some(value as u64))
+        }
+        (
+            Instruction::AUIPC { dest, imm },
+            Instruction::ADDI {
+                dest: d,
+                src,
+                imm: lo,
+            },
+        ) if d == dest && src == dest => {
+            let address = first
+                .address
+                .wrapping_add((imm.val() << 12) as u64)
+                .wrapping_add(lo.val() as u64);
+            fusion(FusionKind::PcRelativeAddress, dest, Some(address))
+        }
+        (Instruction::LUI { dest, imm } | Instruction::AUIPC { dest, imm }, _)
+            if let Some((d, base, offset)) = integer_load(&second.instruction)
+                && d == dest
+                && base == dest =>
+        {
+            let upper = (imm.val() << 12) as u64;
+            let upper = match first.instruction {
+                Instruction::AUIPC { .. } => first.address.wrapping_add(upper),
+                _ => upper,
+            };
+            let address = upper.wrapping_add(offset as u64);
+            fusion(FusionKind::LoadGlobal, dest, Some(address))
+        }
+        (
+            Instruction::AUIPC { dest, imm },
+            Instruction::JALR {
+                dest: link,
+                base,
+                offset,
+            },
+        ) if base == dest && (link == dest || link == IRegister::Zero) => {
+            let target = first
+                .address
+                .wrapping_add((imm.val() << 12) as u64)
+                .wrapping_add(offset.val() as u64);
+            fusion(FusionKind::FarJump, link, Some(target))
+        }
+        (
+            Instruction::SLLI { dest, shamt, .. },
+            Instruction::ADD {
+                dest: d,
+                src1,
+                src2,
+            },
+        ) if (1..=3).contains(&shamt.val()) && d == dest && (src1 == dest) != (src2 == dest) => {
+            fusion(FusionKind::IndexedAddress, dest, None)
+        }
+        (Instruction::ADD { dest, .. }, _)
+            if let Some((d, base, 0)) = integer_load(&second.instruction)
+                && d == dest
+                && base == dest =>
+        {
+            fusion(FusionKind::IndexedLoad, dest, None)
+        }
+        (
+            Instruction::SLLI { dest, shamt, .. },
+            Instruction::SRLI {
+                dest: d,
+                src,
+                shamt: s,
+            },
+        ) if shamt.val() == 32 && (29..=32).contains(&s.val()) && d == dest && src == dest => {
+            fusion(FusionKind::ZeroExtend, dest, None)
+        }
+        _ => None,
+    }
+}
+
+/// Finds the pairs of adjacent instructions in a region of decoded code that common microarchitectures fuse.
+///
+/// The instructions of a pair must be contiguous and the second must overwrite the result of the first, apart from far
+/// jumps which jump through it. Pairs do not overlap, an instruction that ends a pair is not considered as the start of
+/// another.
+pub fn find_fusions(code: &[Decoded]) -> Vec<Fusion> {
+    let mut fusions = Vec::new();
+    let mut index = 0;
+    while index + 1 < code.len() {
+        let (first, second) = (&code[index], &code[index + 1]);
+        let contiguous = first.address.wrapping_add(first.length as u64) == second.address;
+        let hint = writes(&first.instruction).is_none_or(|r| r == IRegister::Zero);
+        match fuse(first, second).filter(|_| contiguous && !hint) {
+            Some(fusion) => {
+                fusions.push(fusion);
+                index += 2;
+            }
+            None => index += 1,
+        }
+    }
+    fusions
+}
@@ -0,0 +1,465 @@
+//! The XAndesPerf vendor extension of Andes cores such as the D25 and D45.
+//!
+//! Every instruction is in the custom-2 major opcode and is written with the `nds.` prefix used by GNU and LLVM
+//! (`nds.bbc a0,3,16`). Decoding custom-2 as XAndesPerf requires both the `xandesperf` feature and
+//! `Extensions::xandesperf`, since other vendors use the same opcode. The GP-relative loads and stores of XAndesPerf
+//! are not supported.
+
+use std::fmt::{Display, Formatter};
+
+use crate::assembly::parse_int;
+use crate::config::{FormatOptions, Xlen};
+use crate::immediates::{Immediate, NdsBImmediate, NdsBitImmediate, NdsCImmediate, NdsLsb, NdsMsb};
+use crate::register::IRegister;
+
+/// The custom-2 major opcode.
+pub(crate) const OPCODE: u32 = 0b1011011;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum AndesInstruction {
+    /// Branch if bit `bit` of `src` is clear.
+    BBC {
+        src: IRegister,
+        bit: NdsBitImmediate,
+        offset: NdsBImmediate,
+    },
+    /// Branch if bit `bit` of `src` is set.
+    BBS {
+        src: IRegister,
+        bit: NdsBitImmediate,
+        offset: NdsBImmediate,
+    },
+    /// Branch if `src` is equal to a constant.
+    BEQC {
+        src: IRegister,
+        imm: NdsCImmediate,
+        offset: NdsBImmediate,
+    },
+    /// Branch if `src` is not equal to a constant.
+    BNEC {
+        src: IRegister,
+        imm: NdsCImmediate,
+        offset: NdsBImmediate,
+    },
+    /// Extract or insert a bit field, zero extending.
+    BFOZ {
+        dest: IRegister,
+        src: IRegister,
+        msb: NdsMsb,
+        lsb: NdsLsb,
+    },
+    /// Extract or insert a bit field, sign extending.
+    BFOS {
+        dest: IRegister,
+        src: IRegister,
+        msb: NdsMsb,
+        lsb: NdsLsb,
+    },
+    /// `src1 + (src2 << 1)`
+    LEAH {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    LEAW {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// RV64 only
+    LEAD {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// `src1 + zext32(src2)`, RV64 only
+    LEABZE {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// RV64 only
+    LEAHZE {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// RV64 only
+    LEAWZE {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Find the first byte of `src1` equal to the low byte of `src2`.
+    FFB {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Find the first byte of `src1` that is zero or differs from the byte of `src2` at the same position.
+    FFZMISM {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Find the first byte of `src1` that differs from the byte of `src2` at the same position.
+    FFMISM {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Find the last byte of `src1` that differs from the byte of `src2` at the same position.
+    FLMISM {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+}
+
+/// The funct7 of the register-register instructions, which all have a funct3 of 0.
+const RR_FUNCT7: [(&str, u32); 10] = [
+    ("lea.h", 0b0000101),
+    ("lea.w", 0b0000110),
+    ("lea.d", 0b0000111),
+    ("lea.b.ze", 0b0001000),
+    ("lea.h.ze", 0b0001001),
+    ("lea.w.ze", 0b0001010),
+    ("ffb", 0b0010000),
+    ("ffzmism", 0b0010001),
+    ("ffmism", 0b0010010),
+    ("flmism", 0b0010011),
+];
+
+impl AndesInstruction {
+    /// Constructs a register-register instruction from its mnemonic without the `nds.` prefix.
+    fn rr(name: &str, dest: IRegister, src1: IRegister, src2: IRegister) -> Option<Self> {
+        Some(match name {
+            "lea.h" => AndesInstruction::LEAH { dest, src1, src2 },
+            "lea.w" => AndesInstruction::LEAW { dest, src1, src2 },
+            "lea.d" => AndesInstruction::LEAD { dest, src1, src2 },
+            "lea.b.ze" => AndesInstruction::LEABZE { dest, src1, src2 },
+            "lea.h.ze" => AndesInstruction::LEAHZE { dest, src1, src2 },
+            "lea.w.ze" => AndesInstruction::LEAWZE { dest, src1, src2 },
+            "ffb" => AndesInstruction::FFB { dest, src1, src2 },
+            "ffzmism" => AndesInstruction::FFZMISM { dest, src1, src2 },
+            "ffmism" => AndesInstruction::FFMISM { dest, src1, src2 },
+            "flmism" => AndesInstruction::FLMISM { dest, src1, src2 },
+            _ => return None,
+        })
+    }
+
+    /// The mnemonic, without the `nds.` prefix.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            AndesInstruction::BBC { .. } => "bbc",
+            AndesInstruction::BBS { .. } => "bbs",
+            AndesInstruction::BEQC { .. } => "beqc",
+            AndesInstruction::BNEC { .. } => "bnec",
+            AndesInstruction::BFOZ { .. } => "bfoz",
+            AndesInstruction::BFOS { .. } => "bfos",
+            AndesInstruction::LEAH { .. } => "lea.h",
+            AndesInstruction::LEAW { .. } => "lea.w",
+            AndesInstruction::LEAD { .. } => "lea.d",
+            AndesInstruction::LEABZE { .. } => "lea.b.ze",
+            AndesInstruction::LEAHZE { .. } => "lea.h.ze",
+            AndesInstruction::LEAWZE { .. } => "lea.w.ze",
+            AndesInstruction::FFB { .. } => "ffb",
+            AndesInstruction::FFZMISM { .. } => "ffzmism",
+            AndesInstruction::FFMISM { .. } => "ffmism",
+            AndesInstruction::FLMISM { .. } => "flmism",
+        }
+    }
+
+    /// Returns true for the branches.
+    pub fn is_branch(&self) -> bool {
+        matches!(
+            self,
+            AndesInstruction::BBC { .. }
+                | AndesInstruction::BBS { .. }
+                | AndesInstruction::BEQC { .. }
+                | AndesInstruction::BNEC { .. }
+        )
+    }
+
+    /// Returns the target of a branch located at `address`.
+    pub fn branch_target(&self, address: u64) -> Option<u64> {
+        match self {
+            AndesInstruction::BBC { offset, .. }
+            | AndesInstruction::BBS { offset, .. }
+            | AndesInstruction::BEQC { offset, .. }
+            | AndesInstruction::BNEC { offset, .. } => {
+                Some(address.wrapping_add(offset.val() as u64))
+            }
+            _ => None,
+        }
+    }
+
+    /// Decodes an instruction in the custom-2 major opcode.
+    pub fn decode(instruction: u32, xlen: Xlen) -> Result<Self, String> {
+        let funct3 = (instruction >> 12) & 0b111;
+        let rd = IRegister::from_int((instruction >> 7) & 0b1_1111);
+        let rs1 = IRegister::from_int((instruction >> 15) & 0b1_1111);
+        let rs2 = IRegister::from_int((instruction >> 20) & 0b1_1111);
+        let offset = NdsBImmediate::from_u32(instruction);
+        let rv32 = xlen == Xlen::Rv32;
+        match funct3 {
+            0b000 => {
+                let funct7 = instruction >> 25;
+                let name = RR_FUNCT7
+                    .iter()
+                    .find(|(_, f)| *f == funct7)
+                    .map(|(name, _)| *name)
+                    .ok_or_else(|| format!("unknown XAndesPerf funct7: {funct7:#09b}"))?;
+                if rv32 && matches!(name, "lea.d" | "lea.b.ze" | "lea.h.ze" | "lea.w.ze") {
+                    return Err(format!("nds.{name} is only available on RV64"));
+                }
+                Ok(AndesInstruction::rr(name, rd, rs1, rs2).unwrap())
+            }
+            0b010 | 0b011 => {
+                let msb = NdsMsb::from_u32(instruction);
+                let lsb = NdsLsb::from_u32(instruction);
+                if rv32 && (msb.val() >= 32 || lsb.val() >= 32) {
+                    return Err(format!(
+                        "the bit positions of nds.bfoz and nds.bfos must be less than 32 on RV32, got {msb} and {lsb}"
+                    ));
+                }
+                Ok(if funct3 == 0b010 {
+                    AndesInstruction::BFOZ {
+                        dest: rd,
+                        src: rs1,
+                        msb,
+                        lsb,
+                    }
+                } else {
+                    AndesInstruction::BFOS {
+                        dest: rd,
+                        src: rs1,
+                        msb,
+                        lsb,
+                    }
+                })
+            }
+            0b101 | 0b110 => {
+                let imm = NdsCImmediate::from_u32(instruction);
+                Ok(if funct3 == 0b101 {
+                    AndesInstruction::BEQC {
+                        src: rs1,
+                        imm,
+                        offset,
+                    }
+                } else {
+                    AndesInstruction::BNEC {
+                        src: rs1,
+                        imm,
+                        offset,
+                    }
+                })
+            }
+            0b111 => {
+                let bit = NdsBitImmediate::from_u32(instruction);
+                if rv32 && bit.val() >= 32 {
+                    return Err(format!(
+                        "the bit of nds.bbc and nds.bbs must be less than 32 on RV32, got {bit}"
+                    ));
+                }
+                Ok(if (instruction >> 30) & 1 == 0 {
+                    AndesInstruction::BBC {
+                        src: rs1,
+                        bit,
+                        offset,
+                    }
+                } else {
+                    AndesInstruction::BBS {
+                        src: rs1,
+                        bit,
+                        offset,
+                    }
+                })
+            }
+            _ => Err(format!("unknown XAndesPerf funct3: {funct3:#05b}")),
+        }
+    }
+
+    // zero fields are kept so that every encoding lists all of its fields
+    #[allow(clippy::identity_op)]
+    pub fn encode(&self) -> u32 {
+        let rr = |funct7: u32, dest: &IRegister, src1: &IRegister, src2: &IRegister| {
+            funct7 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | OPCODE
+        };
+        match self {
+            AndesInstruction::BBC { src, bit, offset } => {
+                0 << 30 | offset.to_u32() | bit.to_u32() | src.rs1() | 0b111 << 12 | OPCODE
+            }
+            AndesInstruction::BBS { src, bit, offset } => {
+                1 << 30 | offset.to_u32() | bit.to_u32() | src.rs1() | 0b111 << 12 | OPCODE
+            }
+            AndesInstruction::BEQC { src, imm, offset } => {
+                offset.to_u32() | imm.to_u32() | src.rs1() | 0b101 << 12 | OPCODE
+            }
+            AndesInstruction::BNEC { src, imm, offset } => {
+                offset.to_u32() | imm.to_u32() | src.rs1() | 0b110 << 12 | OPCODE
+            }
+            AndesInstruction::BFOZ {
+                dest,
+                src,
+                msb,
+                lsb,
+            } => msb.to_u32() | lsb.to_u32() | src.rs1() | 0b010 << 12 | dest.rd() | OPCODE,
+            AndesInstruction::BFOS {
+                dest,
+                src,
+                msb,
+                lsb,
+            } => msb.to_u32() | lsb.to_u32() | src.rs1() | 0b011 << 12 | dest.rd() | OPCODE,
+            AndesInstruction::LEAH { dest, src1, src2 }
+            | AndesInstruction::LEAW { dest, src1, src2 }
+            | AndesInstruction::LEAD { dest, src1, src2 }
+            | AndesInstruction::LEABZE { dest, src1, src2 }
+            | AndesInstruction::LEAHZE { dest, src1, src2 }
+            | AndesInstruction::LEAWZE { dest, src1, src2 }
+            | AndesInstruction::FFB { dest, src1, src2 }
+            | AndesInstruction::FFZMISM { dest, src1, src2 }
+            | AndesInstruction::FFMISM { dest, src1, src2 }
+            | AndesInstruction::FLMISM { dest, src1, src2 } => {
+                let (_, funct7) = RR_FUNCT7
+                    .iter()
+                    .find(|(name, _)| *name == self.mnemonic())
+                    .unwrap();
+                rr(*funct7, dest, src1, src2)
+            }
+        }
+    }
+
+    /// Assembles an instruction from its mnemonic without the `nds.` prefix, split at the dots.
+    pub(crate) fn assemble(
+        mnemonics: &[&str],
+        operands: &[&str],
+        xlen: Xlen,
+    ) -> Result<Self, String> {
+        let name = mnemonics.join(".");
+        let register = |str: &str| IRegister::from_string(str);
+        let instruction = match (name.as_str(), operands) {
+            ("bbc" | "bbs", [src, bit, offset]) => {
+                let src = register(src)?;
+                let bit = NdsBitImmediate::try_from(parse_int(bit)?)?;
+                let offset = NdsBImmediate::try_from(parse_int(offset)?)?;
+                if name == "bbc" {
+                    AndesInstruction::BBC { src, bit, offset }
+                } else {
+                    AndesInstruction::BBS { src, bit, offset }
+                }
+            }
+            ("beqc" | "bnec", [src, imm, offset]) => {
+                let src = register(src)?;
+                let imm = NdsCImmediate::try_from(parse_int(imm)?)?;
+                let offset = NdsBImmediate::try_from(parse_int(offset)?)?;
+                if name == "beqc" {
+                    AndesInstruction::BEQC { src, imm, offset }
+                } else {
+                    AndesInstruction::BNEC { src, imm, offset }
+                }
+            }
+            ("bfoz" | "bfos", [dest, src, msb, lsb]) => {
+                let dest = register(dest)?;
+                let src = register(src)?;
+                let msb = NdsMsb::try_from(parse_int(msb)?)?;
+                let lsb = NdsLsb::try_from(parse_int(lsb)?)?;
+                if name == "bfoz" {
+                    AndesInstruction::BFOZ {
+                        dest,
+                        src,
+                        msb,
+                        lsb,
+                    }
+                } else {
+                    AndesInstruction::BFOS {
+                        dest,
+                        src,
+                        msb,
+                        lsb,
+                    }
+                }
+            }
+            (_, [dest, src1, src2]) if RR_FUNCT7.iter().any(|(n, _)| *n == name) => {
+                AndesInstruction::rr(&name, register(dest)?, register(src1)?, register(src2)?)
+                    .unwrap()
+            }
+            ("bbc" | "bbs" | "beqc" | "bnec", _) => {
+                return Err(format!("nds.{name} requires 3 operands"));
+            }
+            ("bfoz" | "bfos", _) => return Err(format!("nds.{name} requires 4 operands")),
+            _ if RR_FUNCT7.iter().any(|(n, _)| *n == name) => {
+                return Err(format!("nds.{name} requires 3 operands"));
+            }
+            _ => return Err(format!("unknown mnemonic: nds.{name}")),
+        };
+        // check the restrictions of RV32 by decoding
+        AndesInstruction::decode(instruction.encode(), xlen)
+    }
+
+    pub(crate) fn fmt_with(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &FormatOptions,
+    ) -> Result<(), std::fmt::Error> {
+        let mnemonic = self.mnemonic();
+        let style = options.register_style;
+        match self {
+            AndesInstruction::BBC { src, bit, offset }
+            | AndesInstruction::BBS { src, bit, offset } => {
+                write!(
+                    f,
+                    "nds.{mnemonic} {src},{bit},{offset}",
+                    src = src.display_with(style),
+                    offset = offset.display_with(options.immediate_style)
+                )
+            }
+            AndesInstruction::BEQC { src, imm, offset }
+            | AndesInstruction::BNEC { src, imm, offset } => write!(
+                f,
+                "nds.{mnemonic} {src},{imm},{offset}",
+                src = src.display_with(style),
+                offset = offset.display_with(options.immediate_style)
+            ),
+            AndesInstruction::BFOZ {
+                dest,
+                src,
+                msb,
+                lsb,
+            }
+            | AndesInstruction::BFOS {
+                dest,
+                src,
+                msb,
+                lsb,
+            } => write!(
+                f,
+                "nds.{mnemonic} {dest},{src},{msb},{lsb}",
+                dest = dest.display_with(style),
+                src = src.display_with(style)
+            ),
+            AndesInstruction::LEAH { dest, src1, src2 }
+            | AndesInstruction::LEAW { dest, src1, src2 }
+            | AndesInstruction::LEAD { dest, src1, src2 }
+            | AndesInstruction::LEABZE { dest, src1, src2 }
+            | AndesInstruction::LEAHZE { dest, src1, src2 }
+            | AndesInstruction::LEAWZE { dest, src1, src2 }
+            | AndesInstruction::FFB { dest, src1, src2 }
+            | AndesInstruction::FFZMISM { dest, src1, src2 }
+            | AndesInstruction::FFMISM { dest, src1, src2 }
+            | AndesInstruction::FLMISM { dest, src1, src2 } => write!(
+                f,
+                "nds.{mnemonic} {dest},{src1},{src2}",
+                dest = dest.display_with(style),
+                src1 = src1.display_with(style),
+                src2 = src2.display_with(style)
+            ),
+        }
+    }
+}
+
+impl Display for AndesInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.fmt_with(f, &FormatOptions::default())
+    }
+}
@@ -1,50 +1,385 @@
+use std::fmt::{Display, Formatter};
+
 use riscv_codec_proc_macros::{
-    amo_assemble, b_assemble, ci_assemble, cr_assemble, fr_assemble, i_assemble, l_assemble,
-    r_assemble, s_assemble, sh_assemble, shw_assemble,
+    amo_assemble, b_assemble, ci_assemble, cr_assemble, fr_assemble, fr4_assemble, frr_assemble,
+    i_assemble, l_assemble, r_assemble, ru_assemble, s_assemble, sh_assemble, shw_assemble,
 };
 
+#[cfg(feature = "xandesperf")]
+use crate::andes::AndesInstruction;
+use crate::cinstruction::CInstruction;
+use crate::config::{AssemblerConfig, Xlen};
 use crate::immediates::*;
 use crate::instruction::RoundingMode;
-use crate::register::{CFRegister, CIRegister, FRegister, IRegister};
-use crate::{cinstruction::CInstruction, instruction::Instruction};
+use crate::instruction::{Inst, Instruction};
+use crate::register::{CFRegister, CIRegister, CSRegister, FRegister, IRegister, RegisterList};
+#[cfg(feature = "p")]
+use crate::simd::{self, SimdInstruction};
+#[cfg(feature = "xthead")]
+use crate::thead::TheadInstruction;
+use crate::vector::VectorInstruction;
 
-fn parse_int(str: &str) -> Result<i64, String> {
-    match str.parse::<i64>() {
+/// Parses a decimal or `0x` prefixed hexadecimal integer.
+pub(crate) fn parse_int(str: &str) -> Result<i64, String> {
+    let (negative, digits) = match str.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, str),
+    };
+    let value = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => i64::from_str_radix(hex, 16),
+        None => digits.parse::<i64>(),
+    };
+    match value {
+        Ok(e) if negative => Ok(-e),
         Ok(e) => Ok(e),
         Err(_) => Err(format!("unable to parse int:{str}").to_owned()),
     }
 }
 
-fn parse_address_expression(str: &str) -> Result<(IRegister, i64), String> {
-    let (offset, register): (&str, &str) = if let Some(x) = str.split_once("(") {
-        x
+/// Parses the immediate of lui or auipc.
+///
+/// Both the unsigned form (`0xfffff`) and the sign extended form (`-1`) are accepted.
+fn parse_u_immediate(str: &str) -> Result<UImmediate, String> {
+    let int: i64 = parse_int(str)?;
+    // the unsigned form is the bits of the immediate without sign extension
+    let unsigned_max = (1 << UImmediate::BITS) - 1;
+    if !(UImmediate::MIN..=unsigned_max).contains(&int) {
+        Err("UImmediate out of range".to_owned())
+    } else if int > UImmediate::MAX {
+        UImmediate::try_from(int - (1 << UImmediate::BITS))
     } else {
-        panic!("no (");
+        UImmediate::try_from(int)
+    }
+}
+
+/// Splits the operands of an instruction at the commas that are not inside the braces of a register list
+/// (`{ra, s0-s1}, -16`), trimming whitespace around each operand.
+fn split_operands(operands: &str) -> Vec<&str> {
+    let mut split = Vec::new();
+    let mut depth = 0;
+    let mut start = 0;
+    for (i, c) in operands.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                split.push(operands[start..i].trim());
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    split.push(operands[start..].trim());
+    split
+}
+
+/// Splits an address of the form `offset(register)` into the offset and the register name.
+///
+/// The offset is 0 when it is left out (`(a0)`), and whitespace is allowed around the offset and the register
+/// (`8 ( sp )`).
+fn split_address_expression(str: &str) -> Result<(i64, &str), String> {
+    let Some((offset, register)) = str.split_once('(') else {
+        return Err(format!(
+            "expected an address of the form offset(register), got {str}"
+        ));
     };
-    match register.strip_suffix(")") {
-        Some(y) => {
-            let r = IRegister::from_string(y)?;
-            let i = parse_int(offset)?;
-            Ok((r, i))
+    let Some(register) = register.trim_end().strip_suffix(')') else {
+        return Err("Address expression should end in a )".to_owned());
+    };
+    let offset = match offset.trim() {
+        "" => 0,
+        offset => parse_int(offset)?,
+    };
+    Ok((offset, register.trim()))
+}
+
+pub(crate) fn parse_address_expression(str: &str) -> Result<(IRegister, i64), String> {
+    let (offset, register) = split_address_expression(str)?;
+    Ok((IRegister::from_string(register)?, offset))
+}
+
+fn parse_address_expression_compressed(
+    mnemonic: &str,
+    str: &str,
+) -> Result<(CIRegister, i64), String> {
+    let (offset, register) = split_address_expression(str)?;
+    Ok((compressed_register(mnemonic, register)?, offset))
+}
+
+/// The 32-bit instruction that a compressed instruction expands to.
+///
+/// This is suggested as an alternative when an operand cannot be encoded in the compressed instruction.
+fn uncompressed_mnemonic(mnemonic: &str) -> &str {
+    match mnemonic {
+        "addi4spn" | "li" | "addi16sp" => "addi",
+        "mv" => "add",
+        "j" => "jal",
+        "jr" => "jalr",
+        "beqz" => "beq",
+        "bnez" => "bne",
+        m => m.strip_suffix("sp").unwrap_or(m),
+    }
+}
+
+/// The mnemonics accepted by the assembler with the syntax of their operands, without ordering or rounding mode
+/// suffixes.
+#[rustfmt::skip]
+const MNEMONICS: &[(&str, &str)] = &[
+    ("addi", "rd, rs1, imm"), ("addiw", "rd, rs1, imm"), ("andi", "rd, rs1, imm"), ("ori", "rd, rs1, imm"), ("xori",
+    "rd, rs1, imm"), ("slti", "rd, rs1, imm"), ("sltiu", "rd, rs1, imm"),
+    ("slli", "rd, rs1, shamt"), ("srai", "rd, rs1, shamt"), ("sraiw", "rd, rs1, shamt"), ("srli", "rd, rs1, shamt"),
+    ("srliw", "rd, rs1, shamt"), ("slliw", "rd, rs1, shamt"),
+    ("add", "rd, rs1, rs2"), ("addw", "rd, rs1, rs2"), ("subw", "rd, rs1, rs2"), ("and", "rd, rs1, rs2"), ("sub", "rd,
+    rs1, rs2"), ("or", "rd, rs1, rs2"), ("xor", "rd, rs1, rs2"), ("sllw", "rd, rs1, rs2"), ("srl", "rd, rs1, rs2"),
+    ("sra", "rd, rs1, rs2"), ("srlw", "rd, rs1, rs2"), ("sraw", "rd, rs1, rs2"), ("sll", "rd, rs1, rs2"), ("slt", "rd,
+    rs1, rs2"), ("sltu", "rd, rs1, rs2"), ("mul", "rd, rs1, rs2"), ("mulh", "rd, rs1, rs2"), ("mulhsu", "rd, rs1, rs2"),
+    ("mulhu", "rd, rs1, rs2"), ("div", "rd, rs1, rs2"), ("divu", "rd, rs1, rs2"), ("rem", "rd, rs1, rs2"), ("remu", "rd,
+    rs1, rs2"), ("mulw", "rd, rs1, rs2"), ("divw", "rd, rs1, rs2"), ("divuw", "rd, rs1, rs2"), ("remw", "rd, rs1, rs2"),
+    ("remuw", "rd, rs1, rs2"),
+    ("pack", "rd, rs1, rs2"), ("packh", "rd, rs1, rs2"), ("packw", "rd, rs1, rs2"), ("clmul", "rd, rs1, rs2"),
+    ("clmulh", "rd, rs1, rs2"), ("xperm4", "rd, rs1, rs2"), ("xperm8", "rd, rs1, rs2"),
+    ("brev8", "rd, rs1"), ("zip", "rd, rs1"), ("unzip", "rd, rs1"), ("andn", "rd, rs1, rs2"), ("orn", "rd, rs1, rs2"),
+    ("xnor", "rd, rs1, rs2"), ("rol", "rd, rs1, rs2"), ("ror", "rd, rs1, rs2"), ("rori", "rd, rs1, shamt"),
+    ("rolw", "rd, rs1, rs2"), ("rorw", "rd, rs1, rs2"), ("roriw", "rd, rs1, shamt"), ("rev8", "rd, rs1"),
+    ("aes32esi", "rd, rs1, rs2, bs"), ("aes32esmi", "rd, rs1, rs2, bs"), ("aes32dsi", "rd, rs1, rs2, bs"),
+    ("aes32dsmi", "rd, rs1, rs2, bs"),
+    ("aes64es", "rd, rs1, rs2"), ("aes64esm", "rd, rs1, rs2"), ("aes64ds", "rd, rs1, rs2"), ("aes64dsm", "rd, rs1, rs2"),
+    ("aes64ks2", "rd, rs1, rs2"), ("aes64im", "rd, rs1"), ("aes64ks1i", "rd, rs1, rnum"),
+    ("sha256sig0", "rd, rs1"), ("sha256sig1", "rd, rs1"), ("sha256sum0", "rd, rs1"), ("sha256sum1", "rd, rs1"),
+    ("sha512sig0", "rd, rs1"), ("sha512sig1", "rd, rs1"), ("sha512sum0", "rd, rs1"), ("sha512sum1", "rd, rs1"),
+    ("sha512sum0r", "rd, rs1, rs2"), ("sha512sum1r", "rd, rs1, rs2"), ("sha512sig0l", "rd, rs1, rs2"), ("sha512sig0h",
+    "rd, rs1, rs2"), ("sha512sig1l", "rd, rs1, rs2"), ("sha512sig1h", "rd, rs1, rs2"),
+    ("lb", "rd, offset(rs1)"), ("lbu", "rd, offset(rs1)"), ("lhu", "rd, offset(rs1)"), ("lw", "rd, offset(rs1)"),
+    ("lwu", "rd, offset(rs1)"), ("lh", "rd, offset(rs1)"), ("ld", "rd, offset(rs1)"), ("jalr", "rd, offset(rs1)"),
+    ("sd", "rs2, offset(rs1)"), ("sw", "rs2, offset(rs1)"), ("sh", "rs2, offset(rs1)"), ("sb", "rs2, offset(rs1)"),
+    ("blt", "rs1, rs2, offset"), ("beq", "rs1, rs2, offset"), ("bne", "rs1, rs2, offset"), ("bge", "rs1, rs2, offset"),
+    ("bgeu", "rs1, rs2, offset"), ("bltu", "rs1, rs2, offset"),
+    ("jal", "rd, offset"),
+    ("lui", "rd, imm"), ("auipc", "rd, imm"),
+    ("fence", "pred, succ"), ("fence.tso", "pred, succ"),
+    ("fence.i", ""),
+    ("cbo.clean", "(rs1)"), ("cbo.flush", "(rs1)"), ("cbo.inval", "(rs1)"), ("cbo.zero", "(rs1)"),
+    ("prefetch.i", "offset(rs1)"), ("prefetch.r", "offset(rs1)"), ("prefetch.w", "offset(rs1)"),
+    ("ecall", ""), ("ebreak", ""),
+    ("sret", ""), ("mret", ""), ("mnret", ""), ("dret", ""), ("wfi", ""), ("sfence.vma", "rs1, rs2"),
+    ("pause", ""), ("ntl.p1", ""), ("ntl.pall", ""), ("ntl.s1", ""), ("ntl.all", ""),
+    ("lpad", "label"), ("sspush", "rs2"), ("sspopchk", "rs1"), ("ssrdp", "rd"),
+    ("lr.w", "rd, rs1"), ("lr.d", "rd, rs1"),
+    ("sc.w", "rd, rs1, rs2"), ("sc.d", "rd, rs1, rs2"), ("amoswap.w", "rd, rs1, rs2"), ("amoswap.d", "rd, rs1, rs2"),
+    ("amoadd.w", "rd, rs1, rs2"), ("amoadd.d", "rd, rs1, rs2"), ("amoxor.w", "rd, rs1, rs2"), ("amoxor.d", "rd, rs1,
+    rs2"), ("amoand.w", "rd, rs1, rs2"), ("amoand.d", "rd, rs1, rs2"), ("amoor.w", "rd, rs1, rs2"), ("amoor.d", "rd,
+    rs1, rs2"), ("amomin.w", "rd, rs1, rs2"), ("amomin.d", "rd, rs1, rs2"), ("amomax.w", "rd, rs1, rs2"), ("amomax.d",
+    "rd, rs1, rs2"), ("amominu.w", "rd, rs1, rs2"), ("amominu.d", "rd, rs1, rs2"), ("amomaxu.w", "rd, rs1, rs2"),
+    ("amomaxu.d", "rd, rs1, rs2"),
+    ("amoswap.b", "rd, rs1, rs2"), ("amoswap.h", "rd, rs1, rs2"), ("amoadd.b", "rd, rs1, rs2"),
+    ("amoadd.h", "rd, rs1, rs2"), ("amoxor.b", "rd, rs1, rs2"), ("amoxor.h", "rd, rs1, rs2"),
+    ("amoand.b", "rd, rs1, rs2"), ("amoand.h", "rd, rs1, rs2"), ("amoor.b", "rd, rs1, rs2"),
+    ("amoor.h", "rd, rs1, rs2"), ("amomin.b", "rd, rs1, rs2"), ("amomin.h", "rd, rs1, rs2"),
+    ("amomax.b", "rd, rs1, rs2"), ("amomax.h", "rd, rs1, rs2"), ("amominu.b", "rd, rs1, rs2"),
+    ("amominu.h", "rd, rs1, rs2"), ("amomaxu.b", "rd, rs1, rs2"), ("amomaxu.h", "rd, rs1, rs2"),
+    ("amocas.b", "rd, rs1, rs2"), ("amocas.h", "rd, rs1, rs2"),
+    ("ssamoswap.w", "rd, rs1, rs2"), ("ssamoswap.d", "rd, rs1, rs2"),
+    ("flw", "fd, offset(rs1)"), ("flq", "fd, offset(rs1)"),
+    ("fsw", "fs2, offset(rs1)"), ("fsq", "fs2, offset(rs1)"),
+    ("fsqrt.s", "fd, fs1"),
+    ("fadd.s", "fd, fs1, fs2"), ("fsub.s", "fd, fs1, fs2"), ("fmul.s", "fd, fs1, fs2"), ("fdiv.s", "fd, fs1, fs2"),
+    ("fmin.s", "fd, fs1, fs2"), ("fmax.s", "fd, fs1, fs2"), ("fsgnj.s", "fd, fs1, fs2"), ("fsgnjn.s", "fd, fs1, fs2"),
+    ("fsgnjx.s", "fd, fs1, fs2"),
+    ("fmadd.s", "fd, fs1, fs2, fs3"), ("fmsub.s", "fd, fs1, fs2, fs3"), ("fnmsub.s", "fd, fs1, fs2, fs3"),
+    ("fnmadd.s", "fd, fs1, fs2, fs3"),
+    ("fcvt.w.s", "rd, fs1"), ("fcvt.wu.s", "rd, fs1"), ("fcvt.l.s", "rd, fs1"), ("fcvt.lu.s", "rd, fs1"), ("fmv.x.w",
+    "rd, fs1"), ("fclass.s", "rd, fs1"),
+    ("fcvt.s.w", "fd, rs1"), ("fcvt.s.wu", "fd, rs1"), ("fcvt.s.l", "fd, rs1"), ("fcvt.s.lu", "fd, rs1"), ("fmv.w.x",
+    "fd, rs1"),
+    ("feq.s", "rd, fs1, fs2"), ("flt.s", "rd, fs1, fs2"), ("fle.s", "rd, fs1, fs2"),
+    ("fsqrt.q", "fd, fs1"),
+    ("fadd.q", "fd, fs1, fs2"), ("fsub.q", "fd, fs1, fs2"), ("fmul.q", "fd, fs1, fs2"), ("fdiv.q", "fd, fs1, fs2"),
+    ("fmin.q", "fd, fs1, fs2"), ("fmax.q", "fd, fs1, fs2"), ("fsgnj.q", "fd, fs1, fs2"), ("fsgnjn.q", "fd, fs1, fs2"),
+    ("fsgnjx.q", "fd, fs1, fs2"),
+    ("fmadd.q", "fd, fs1, fs2, fs3"), ("fmsub.q", "fd, fs1, fs2, fs3"), ("fnmsub.q", "fd, fs1, fs2, fs3"),
+    ("fnmadd.q", "fd, fs1, fs2, fs3"),
+    ("fcvt.w.q", "rd, fs1"), ("fcvt.wu.q", "rd, fs1"), ("fcvt.l.q", "rd, fs1"), ("fcvt.lu.q", "rd, fs1"), ("fclass.q",
+    "rd, fs1"),
+    ("fcvt.q.w", "fd, rs1"), ("fcvt.q.wu", "fd, rs1"), ("fcvt.q.l", "fd, rs1"), ("fcvt.q.lu", "fd, rs1"),
+    ("fcvt.s.q", "fd, fs1"), ("fcvt.q.s", "fd, fs1"), ("fcvt.d.q", "fd, fs1"), ("fcvt.q.d", "fd, fs1"),
+    ("feq.q", "rd, fs1, fs2"), ("flt.q", "rd, fs1, fs2"), ("fle.q", "rd, fs1, fs2"),
+    ("flh", "fd, offset(rs1)"), ("fsh", "fs2, offset(rs1)"), ("fmv.x.h", "rd, fs1"), ("fmv.h.x", "fd, rs1"),
+    ("fcvt.bf16.s", "fd, fs1"), ("fcvt.s.bf16", "fd, fs1"),
+    ("csrrw", "rd, csr, rs1"), ("csrrs", "rd, csr, rs1"), ("csrrc", "rd, csr, rs1"),
+    ("csrrwi", "rd, csr, uimm"), ("csrrsi", "rd, csr, uimm"), ("csrrci", "rd, csr, uimm"),
+    ("c.lw", "rd', offset(rs1')"), ("c.ld", "rd', offset(rs1')"),
+    ("c.flw", "fd', offset(rs1')"), ("c.fld", "fd', offset(rs1')"),
+    ("c.sw", "rs2', offset(rs1')"), ("c.sd", "rs2', offset(rs1')"),
+    ("c.fsw", "fs2', offset(rs1')"), ("c.fsd", "fs2', offset(rs1')"),
+    ("c.addi4spn", "rd', uimm"),
+    ("c.addi", "rd, imm"), ("c.addiw", "rd, imm"), ("c.li", "rd, imm"), ("c.lui", "rd, imm"),
+    ("c.slli", "rd, shamt"),
+    ("c.srli", "rd', shamt"), ("c.srai", "rd', shamt"),
+    ("c.andi", "rd', imm"),
+    ("c.sub", "rd', rs2'"), ("c.xor", "rd', rs2'"), ("c.or", "rd', rs2'"), ("c.and", "rd', rs2'"), ("c.subw", "rd',
+    rs2'"), ("c.addw", "rd', rs2'"),
+    ("c.j", "offset"), ("c.jal", "offset"),
+    ("c.beqz", "rs1', offset"), ("c.bnez", "rs1', offset"),
+    ("c.lwsp", "rd, offset"), ("c.ldsp", "rd, offset"),
+    ("c.flwsp", "fd, offset"), ("c.fldsp", "fd, offset"),
+    ("c.swsp", "rs2, offset"), ("c.sdsp", "rs2, offset"),
+    ("c.fswsp", "fs2, offset"), ("c.fsdsp", "fs2, offset"),
+    ("c.jr", "rs1"), ("c.jalr", "rs1"),
+    ("c.ebreak", ""), ("c.nop", ""),
+    ("c.add", "rd, rs2"), ("c.mv", "rd, rs2"),
+    ("c.ntl.p1", ""), ("c.ntl.pall", ""), ("c.ntl.s1", ""), ("c.ntl.all", ""),
+    ("c.sspush", "rs2"), ("c.sspopchk", "rs1"),
+    ("c.addi16sp", "imm"),
+    ("cm.push", "rlist, -stack_adj"), ("cm.pop", "rlist, stack_adj"), ("cm.popretz", "rlist, stack_adj"),
+    ("cm.popret", "rlist, stack_adj"), ("cm.mvsa01", "r1s', r2s'"), ("cm.mva01s", "r1s', r2s'"),
+];
+
+/// The number of single character insertions, deletions and substitutions that turn one string into another.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a != *b);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
         }
-        _ => Err("Address expression should end in a )".to_owned()),
+        previous = current;
     }
+    previous[b.len()]
 }
 
-fn parse_address_expression_compressed(str: &str) -> Result<(CIRegister, i64), String> {
-    let (offset, register): (&str, &str) = if let Some(x) = str.split_once("(") {
-        x
+/// Returns the error for an unknown mnemonic, suggesting the closest supported mnemonics.
+fn unknown_mnemonic(message: &str, mnemonic: &str) -> String {
+    let distances: Vec<(usize, &str)> = MNEMONICS
+        .iter()
+        .map(|(m, _)| (edit_distance(mnemonic, m), *m))
+        .collect();
+    // allow more differences in longer mnemonics, but never as many as the mnemonic is long
+    let limit = (mnemonic.len() / 2).clamp(1, 3);
+    let closest = distances.iter().map(|(d, _)| *d).min().unwrap_or(0);
+    if closest > limit || closest >= mnemonic.len() {
+        return format!("{message}: {mnemonic}");
+    }
+    let mut candidates: Vec<&str> = distances
+        .iter()
+        .filter(|(d, _)| *d == closest)
+        .map(|(_, m)| *m)
+        .collect();
+    candidates.sort();
+    candidates.truncate(5);
+    format!(
+        "{message}: {mnemonic}, did you mean {}?",
+        candidates.join(", ")
+    )
+}
+
+/// Checks the syntax of an operand against its name in the syntax of a mnemonic.
+fn check_operand(name: &str, operand: &str) -> Result<(), String> {
+    if let Some((_, base)) = name.split_once('(') {
+        if !operand.contains('(') {
+            return Err(format!("expected {name}, got {operand}"));
+        }
+        let (_, register) = split_address_expression(operand)?;
+        check_operand(base.trim_end_matches(')'), register)
+    } else if name == "rlist" {
+        RegisterList::try_from(operand).map(|_| ())
+    } else if name.starts_with('r') {
+        IRegister::from_string(operand).map(|_| ())
+    } else if name.starts_with('f') {
+        FRegister::try_from(operand).map(|_| ())
+    } else if name == "pred" || name == "succ" {
+        if !operand.is_empty() && operand.chars().all(|c| "iorw".contains(c)) {
+            Ok(())
+        } else {
+            Err(format!("expected a set of i, o, r and w, got {operand}"))
+        }
     } else {
-        panic!("no (");
+        parse_int(operand).map(|_| ())
+    }
+}
+
+/// Explains an error in the operands of an instruction with the syntax of its mnemonic.
+///
+/// The error is kept if the number of operands is right and all operands are well formed, since it is then more
+/// specific (out of range immediates, registers that can not be used by compressed instructions).
+fn operand_error(mnemonic: &str, operands: &[&str], error: String) -> String {
+    let base = base_mnemonic(mnemonic);
+    let Some((_, syntax)) = MNEMONICS.iter().find(|(m, _)| *m == base) else {
+        return error;
     };
-    match register.strip_suffix(")") {
-        Some(y) => {
-            let r = CIRegister::try_from(y)?;
-            let i = parse_int(offset)?;
-            Ok((r, i))
+    let names: Vec<&str> = syntax.split(", ").filter(|n| !n.is_empty()).collect();
+    let expected = format!(
+        "{mnemonic} expects: {}",
+        format!("{base} {syntax}").trim_end()
+    );
+    if names.len() != operands.len() {
+        let plural = if operands.len() == 1 { "" } else { "s" };
+        return format!("{expected}, got {} operand{plural}", operands.len());
+    }
+    for (i, (name, operand)) in names.iter().zip(operands).enumerate() {
+        if let Err(e) = check_operand(name.trim_end_matches('\''), operand) {
+            return format!("{expected}, operand {} ({name}) is invalid: {e}", i + 1);
         }
-        _ => Err("Address expression should end in a )".to_owned()),
     }
+    error
+}
+
+/// Strips the ordering and rounding mode suffixes of a mnemonic (`amoadd.w.aqrl` to `amoadd.w`).
+fn base_mnemonic(mnemonic: &str) -> String {
+    let suffixes = ["aq", "rl", "aqrl", "rne", "rtz", "rdn", "rup", "rmm", "dyn"];
+    let parts: Vec<&str> = mnemonic.split('.').collect();
+    let end = parts.len()
+        - parts
+            .iter()
+            .rev()
+            .take_while(|p| suffixes.contains(p))
+            .count();
+    parts[..end].join(".")
+}
+
+/// Parses one of the integer registers (x8-x15) that can be encoded in 3 bits by compressed instructions.
+fn compressed_register(mnemonic: &str, str: &str) -> Result<CIRegister, String> {
+    CIRegister::try_from(str).map_err(|e| {
+        if IRegister::from_string(str).is_ok() {
+            format!(
+                "c.{mnemonic} requires a0-a5/s0-s1, got {str} (use {} instead)",
+                uncompressed_mnemonic(mnemonic)
+            )
+        } else {
+            e
+        }
+    })
+}
+
+/// Parses one of the float registers (f8-f15) that can be encoded in 3 bits by compressed instructions.
+fn compressed_float_register(mnemonic: &str, str: &str) -> Result<CFRegister, String> {
+    CFRegister::try_from(str).map_err(|e| {
+        if FRegister::try_from(str).is_ok() {
+            format!(
+                "c.{mnemonic} requires fa0-fa5/fs0-fs1, got {str} (use {} instead)",
+                uncompressed_mnemonic(mnemonic)
+            )
+        } else {
+            e
+        }
+    })
+}
+
+/// Constructs the immediate of a compressed instruction, explaining why it cannot be compressed on failure.
+fn compressed_immediate<T: TryFrom<i64, Error = String>>(
+    mnemonic: &str,
+    value: i64,
+) -> Result<T, String> {
+    T::try_from(value).map_err(|e| {
+        format!(
+            "c.{mnemonic} cannot encode {value}: {e} (use {} instead)",
+            uncompressed_mnemonic(mnemonic)
+        )
+    })
 }
 
 /// Converts a string representing operations into a fence u8
@@ -65,52 +400,677 @@ fn parse_fence_set(s: &str) -> u8 {
     x
 }
 
-#[derive(Debug, PartialEq)]
-pub enum AssemblyResult {
-    I(Instruction),
-    C(CInstruction),
+/// Parses the 5-bit unsigned immediate (uimm5) of csrrwi, csrrsi and csrrci.
+fn parse_csr_immediate(mnemonic: &str, str: &str) -> Result<CSRImmediate, String> {
+    let int = parse_int(str)?;
+    if !(CSRImmediate::MIN..=CSRImmediate::MAX).contains(&int) {
+        Err(format!(
+            "{mnemonic} immediate must be in the range {}-{}, got {int}",
+            CSRImmediate::MIN,
+            CSRImmediate::MAX
+        ))
+    } else {
+        CSRImmediate::try_from(int)
+    }
 }
-impl AssemblyResult {
-    pub fn c(self) -> CInstruction {
-        match self {
-            AssemblyResult::I(_) => panic!("c called on regular instruction"),
-            AssemblyResult::C(cinstruction) => cinstruction,
+
+/// Parses the shift amount of a shift immediate instruction, which may only use 5 bits on RV32.
+fn parse_shamt(str: &str, config: &AssemblerConfig) -> Result<Shamt, String> {
+    let shamt = Shamt::try_from(parse_int(str)?)?;
+    if config.xlen == Xlen::Rv32 && shamt.val() >= 32 {
+        Err(format!("shamt must be less than 32 on RV32, got {shamt}"))
+    } else {
+        Ok(shamt)
+    }
+}
+
+/// Constructs an `Instruction` from a line of assembly.
+pub fn assemble_line(line: &str) -> Result<Inst, String> {
+    assemble_line_with(line, &AssemblerConfig::default())
+}
+
+/// Converts the operands of lr, sc, the AMOs and the Zalasr loads and stores from the standard syntax, where the
+/// address is in parentheses after the source (`amoadd.w a0,a2,(a1)` or `lr.w a0,0(a1)`), to the order the assembler
+/// expects (`amoadd.w a0,a1,a2`).
+fn parenthesized_atomic(mnemonic: &str, operands: &str) -> Result<Option<String>, String> {
+    let zalasr = cfg!(feature = "zalasr")
+        && mnemonic.split_once('.').is_some_and(|(m, ordering)| {
+            ["lb", "lh", "lw", "ld", "sb", "sh", "sw", "sd"].contains(&m)
+                && ["aq", "rl", "aqrl"].contains(&ordering)
+        });
+    if !zalasr
+        && !["lr.", "sc.", "amo", "ssamoswap."]
+            .iter()
+            .any(|p| mnemonic.starts_with(p))
+    {
+        return Ok(None);
+    }
+    let mut operands: Vec<&str> = operands.split(',').map(str::trim).collect();
+    let address = operands.pop().unwrap();
+    let Some((offset, rest)) = address.split_once('(') else {
+        return Ok(None);
+    };
+    let Some(addr) = rest.strip_suffix(')') else {
+        return Ok(None);
+    };
+    if !matches!(offset.trim(), "" | "0") {
+        return Err(format!(
+            "the address of {mnemonic} can not have an offset, got {address}"
+        ));
+    }
+    if operands.is_empty() {
+        return Ok(None);
+    }
+    operands.insert(1, addr.trim());
+    Ok(Some(operands.join(",")))
+}
+
+/// Assembles the load-acquire and store-release instructions of the Zalasr proposal (`lw.aq a0,a1`, `sw.rl a1,a0`).
+#[cfg(feature = "zalasr")]
+fn zalasr_assemble(
+    mnemonics: &[&str],
+    operands: &[&str],
+    config: &AssemblerConfig,
+) -> Result<Instruction, String> {
+    let mnemonic = mnemonics.join(".");
+    let [register, addr] = operands else {
+        return Err(format!("{mnemonic} requires 2 operands"));
+    };
+    if config.xlen == Xlen::Rv32 && matches!(mnemonics[0], "ld" | "sd") {
+        return Err(format!("{mnemonic} is only available on RV64"));
+    }
+    let register = IRegister::from_string(register)?;
+    let addr = IRegister::from_string(addr)?;
+    // loads always acquire and stores always release, the other bit is optional
+    let other = match (mnemonics[0].starts_with('l'), &mnemonics[1..]) {
+        (true, ["aq"]) | (false, ["rl"]) => false,
+        (_, ["aqrl"]) => true,
+        (true, _) => return Err(format!("{mnemonic} must be ordered with aq or aqrl")),
+        (false, _) => return Err(format!("{mnemonic} must be ordered with rl or aqrl")),
+    };
+    Ok(match mnemonics[0] {
+        "lb" => Instruction::LBAQ {
+            dest: register,
+            addr,
+            rl: other,
+        },
+        "lh" => Instruction::LHAQ {
+            dest: register,
+            addr,
+            rl: other,
+        },
+        "lw" => Instruction::LWAQ {
+            dest: register,
+            addr,
+            rl: other,
+        },
+        "ld" => Instruction::LDAQ {
+            dest: register,
+            addr,
+            rl: other,
+        },
+        "sb" => Instruction::SBRL {
+            src: register,
+            addr,
+            aq: other,
+        },
+        "sh" => Instruction::SHRL {
+            src: register,
+            addr,
+            aq: other,
+        },
+        "sw" => Instruction::SWRL {
+            src: register,
+            addr,
+            aq: other,
+        },
+        "sd" => Instruction::SDRL {
+            src: register,
+            addr,
+            aq: other,
+        },
+        _ => unreachable!(),
+    })
+}
+
+/// Returns whether a float instruction is quad precision from the precision suffix of its mnemonic (`fadd.q`).
+fn quad_precision(mnemonics: &[&str]) -> Result<bool, String> {
+    match mnemonics.get(1) {
+        Some(&"s") => Ok(false),
+        Some(&"q") => Ok(true),
+        Some(&("d" | "h")) => Err(format!(
+            "{}.{} is not supported, only single and quad precision are",
+            mnemonics[0], mnemonics[1]
+        )),
+        _ => Err(format!("{} requires a suffix {{s,q}}", mnemonics[0])),
+    }
+}
+
+/// The rounding mode suffix of a float instruction at an index of its mnemonic, dynamic if there is none.
+fn float_rounding_mode(mnemonics: &[&str], index: usize) -> Result<RoundingMode, String> {
+    match mnemonics.get(index..) {
+        Some([]) => Ok(RoundingMode::DYN),
+        Some([rm]) => RoundingMode::from_str(rm),
+        _ => Err(format!("{} has too many suffixes", mnemonics.join("."))),
+    }
+}
+
+/// Moves a rounding mode given as an extra last operand (`fadd.s fa0,fa1,fa2,rtz` or `fadd.s fa0,fa1,fa2,1`) to the
+/// suffix of the mnemonic, where the assembler expects it.
+fn rounding_mode_operand<'a>(mnemonic: &str, operands: &'a str) -> Option<(String, &'a str)> {
+    let (rest, rm) = operands.rsplit_once(',')?;
+    let rm = rm.trim();
+    RoundingMode::from_str(rm).ok()?;
+    let (_, syntax) = MNEMONICS.iter().find(|(m, _)| *m == mnemonic)?;
+    let rounded = [
+        "fadd", "fsub", "fmul", "fdiv", "fsqrt", "fcvt", "fmadd", "fmsub", "fnmsub", "fnmadd",
+    ];
+    if !rounded.contains(&mnemonic.split('.').next()?)
+        || syntax.split(", ").count() != rest.split(',').count()
+    {
+        return None;
+    }
+    Some((format!("{mnemonic}.{rm}"), rest))
+}
+
+/// Replaces the integer registers given for the float operands of an instruction (`fadd.s a0,a1,a2`) by the float
+/// registers of the same number, which is how Zfinx encodes them. `None` if there are none.
+fn zfinx_operands(mnemonic: &str, operands: &str) -> Option<String> {
+    let (_, syntax) = MNEMONICS
+        .iter()
+        .find(|(m, _)| *m == base_mnemonic(mnemonic))?;
+    let mut replaced = false;
+    let operands: Vec<String> = split_operands(operands)
+        .into_iter()
+        .zip(syntax.split(", ").chain(std::iter::repeat("")))
+        .map(
+            |(operand, name)| match IRegister::from_string(operand.trim()) {
+                Ok(register) if name.starts_with('f') => {
+                    replaced = true;
+                    format!("f{}", register.number())
+                }
+                _ => operand.to_owned(),
+            },
+        )
+        .collect();
+    replaced.then(|| operands.join(","))
+}
+
+/// Constructs an `Instruction` from a line of assembly using the given configuration.
+///
+/// Rounding modes may be given as a suffix (`fcvt.w.s.rtz`) or as a last operand, either by name or by encoding.
+/// With Zfinx, the float operands may be given as integer registers, and with RVE only x0-x15 are accepted. Errors in
+/// the operands are explained with the expected syntax of the mnemonic.
+pub fn assemble_line_with(line: &str, config: &AssemblerConfig) -> Result<Inst, String> {
+    let (mnemonic, operands) = line.split_once(" ").unwrap_or((line, ""));
+    let atomic = parenthesized_atomic(mnemonic, operands)?;
+    let operands = atomic.as_deref().unwrap_or(operands);
+    let rounded = rounding_mode_operand(mnemonic, operands);
+    let (rounded_mnemonic, rounded_operands) = match &rounded {
+        Some((mnemonic, operands)) => (mnemonic.as_str(), *operands),
+        None => (mnemonic, operands),
+    };
+    let zfinx = config
+        .extensions
+        .zfinx
+        .then(|| zfinx_operands(rounded_mnemonic, rounded_operands))
+        .flatten();
+    let result = assemble(
+        rounded_mnemonic,
+        zfinx.as_deref().unwrap_or(rounded_operands),
+        config,
+    );
+    let result = result.map_err(|e| {
+        let operands: Vec<&str> = split_operands(operands)
+            .into_iter()
+            .filter(|_| !operands.is_empty())
+            .collect();
+        operand_error(mnemonic, &operands, e)
+    })?;
+    let missing = match &result {
+        // the prefetches are encoded as ori, which needs no extension
+        Inst::Full(
+            Instruction::PREFETCHI { .. }
+            | Instruction::PREFETCHR { .. }
+            | Instruction::PREFETCHW { .. },
+        ) if !config.extensions.zicbop => Some("Zicbop"),
+        // as are pause as a fence and the ntl hints as add
+        Inst::Full(Instruction::PAUSE) if !config.extensions.zihintpause => Some("Zihintpause"),
+        Inst::Full(
+            Instruction::NTLP1 | Instruction::NTLPALL | Instruction::NTLS1 | Instruction::NTLALL,
+        )
+        | Inst::Compressed(
+            CInstruction::NTLP1
+            | CInstruction::NTLPALL
+            | CInstruction::NTLS1
+            | CInstruction::NTLALL,
+        ) if !config.extensions.zihintntl => Some("Zihintntl"),
+        // and lpad as auipc
+        Inst::Full(Instruction::LPAD { .. }) if !config.extensions.zicfilp => Some("Zicfilp"),
+        // and the compressed shadow stack instructions as c.lui
+        Inst::Compressed(CInstruction::SSPUSH | CInstruction::SSPOPCHK)
+            if !config.extensions.zicfiss =>
+        {
+            Some("Zicfiss")
+        }
+        // and the Zcmp instructions as c.fsdsp
+        Inst::Compressed(
+            CInstruction::CMPUSH { .. }
+            | CInstruction::CMPOP { .. }
+            | CInstruction::CMPOPRETZ { .. }
+            | CInstruction::CMPOPRET { .. }
+            | CInstruction::CMMVSA01 { .. }
+            | CInstruction::CMMVA01S { .. },
+        ) if !config.extensions.zcmp => Some("Zcmp"),
+        #[cfg(feature = "p")]
+        Inst::Full(Instruction::P(_)) if !config.extensions.p => Some("P"),
+        Inst::Full(i) => config
+            .extensions
+            .missing(Instruction::encode(i), config.xlen),
+        Inst::Compressed(c) => config
+            .extensions
+            .missing_compressed(CInstruction::encode(c), config.xlen),
+    };
+    #[cfg(feature = "p")]
+    if config.extensions.p
+        && let Inst::Full(Instruction::Vector(v)) = &result
+        && v.encode() & 0b111_1111 == simd::OPCODE
+    {
+        return Err(format!(
+            "{mnemonic} is not available with P, which uses its opcode"
+        ));
+    }
+    #[cfg(feature = "rv128")]
+    if config.xlen != Xlen::Rv128 && result.full().is_some_and(|i| i.is_rv128_only()) {
+        return Err(format!("{mnemonic} is only available on RV128"));
+    }
+    if config.xlen == Xlen::Rv32 && result.full().is_some_and(|i| i.is_rv64_only()) {
+        return Err(format!("{mnemonic} is only available on RV64"));
+    }
+    let rve_register = match &result {
+        Inst::Full(i) => i.rve_register(config.extensions.zfinx),
+        Inst::Compressed(c) => c.rve_register(),
+    };
+    if config.extensions.e
+        && let Some(register) = rve_register
+    {
+        return Err(format!(
+            "{mnemonic} uses {register} but RVE only has x0-x15"
+        ));
+    }
+    if let Some(extension) = missing {
+        return Err(format!("{mnemonic} requires the {extension} extension"));
+    }
+    check_register_pair(&result, config.xlen)?;
+    Ok(result)
+}
+
+/// Checks that ld and sd on RV32, which access a register pair with Zilsd and Zclsd, name an even register.
+fn check_register_pair(inst: &Inst, xlen: Xlen) -> Result<(), String> {
+    let (mnemonic, register) = match *inst {
+        Inst::Full(Instruction::LD { dest, .. }) => ("ld", dest),
+        Inst::Full(Instruction::SD { src, .. }) => ("sd", src),
+        Inst::Compressed(CInstruction::LD { dest, .. }) => ("c.ld", dest.expand()),
+        Inst::Compressed(CInstruction::SD { src, .. }) => ("c.sd", src.expand()),
+        Inst::Compressed(CInstruction::LDSP { dest, .. }) => ("c.ldsp", dest),
+        Inst::Compressed(CInstruction::SDSP { src, .. }) => ("c.sdsp", src),
+        _ => return Ok(()),
+    };
+    if xlen == Xlen::Rv32 && register.number() % 2 == 1 {
+        Err(format!(
+            "{mnemonic} on RV32 accesses a register pair and must use an even register, got {register}"
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+/// Parses the constant of li, which on RV32 may be given as either a signed or an unsigned 32-bit value.
+fn parse_constant(str: &str, xlen: Xlen) -> Result<i64, String> {
+    let value = match parse_int(str) {
+        Ok(value) => value,
+        // the unsigned form of a negative 64-bit value, or the minimum which has no positive counterpart
+        Err(e) => match str.strip_prefix("0x") {
+            Some(hex) => u64::from_str_radix(hex, 16).map_err(|_| e)? as i64,
+            None => str.parse().map_err(|_| e)?,
+        },
+    };
+    match xlen {
+        Xlen::Rv32 if !(i32::MIN as i64..=u32::MAX as i64).contains(&value) => {
+            Err(format!("li on RV32 requires a 32-bit value, got {str}"))
         }
+        Xlen::Rv32 => Ok(value as i32 as i64),
+        Xlen::Rv64 => Ok(value),
+        #[cfg(feature = "rv128")]
+        Xlen::Rv128 => Ok(value),
     }
-    pub fn i(self) -> Instruction {
+}
+
+/// Appends the instructions that load a constant into `dest`.
+///
+/// Values of 12 bits take an addi and other 32-bit values a lui followed by an addi (addiw on RV64). Larger values
+/// load their upper bits the same way and then shift in the low 12 bits at a time.
+fn load_immediate(dest: &str, value: i64, xlen: Xlen, lines: &mut Vec<String>) {
+    if (-2048..2048).contains(&value) {
+        lines.push(format!("addi {dest},zero,{value}"));
+        return;
+    }
+    let lo = value << 52 >> 52;
+    let addi = if xlen == Xlen::Rv32 { "addi" } else { "addiw" };
+    if i32::try_from(value).is_ok() {
+        let hi = (value.wrapping_add(0x800) >> 12) & 0xfffff;
+        lines.push(format!("lui {dest},{hi:#x}"));
+        if lo != 0 {
+            lines.push(format!("{addi} {dest},{dest},{lo}"));
+        }
+        return;
+    }
+    let hi = (value as u64).wrapping_add(0x800) >> 12;
+    let shift = 12 + hi.trailing_zeros();
+    // sign extend the remaining bits
+    let hi = ((hi >> (shift - 12)) << shift) as i64 >> shift;
+    load_immediate(dest, hi, xlen, lines);
+    lines.push(format!("slli {dest},{dest},{shift}"));
+    if lo != 0 {
+        lines.push(format!("addi {dest},{dest},{lo}"));
+    }
+}
+
+/// Splits a pc-relative offset into the immediates of an auipc and the addi or jalr following it.
+fn pc_relative(str: &str) -> Result<(i64, i64), String> {
+    let offset = parse_int(str)?;
+    let hi = offset.wrapping_add(0x800) >> 12;
+    if !(-(1 << 19)..1 << 19).contains(&hi) {
+        return Err(format!("offset {str} is out of the range of auipc"));
+    }
+    Ok((hi, offset - (hi << 12)))
+}
+
+/// Expands a pseudo-instruction to the lines of assembly it stands for, `None` if the mnemonic is not a
+/// pseudo-instruction.
+fn expand_pseudo(
+    mnemonic: &str,
+    operands: &[&str],
+    xlen: Xlen,
+) -> Result<Option<Vec<String>>, String> {
+    let count = match mnemonic {
+        "nop" | "ret" => 0,
+        "j" | "jr" | "call" | "tail" => 1,
+        "jal" | "jalr" if operands.len() == 1 => 1,
+        "li" | "la" | "mv" | "not" | "neg" | "negw" | "sext.w" | "seqz" | "snez" | "sltz"
+        | "sgtz" | "beqz" | "bnez" | "blez" | "bgez" | "bltz" | "bgtz" => 2,
+        _ => return Ok(None),
+    };
+    if operands.len() != count {
+        return Err(format!("{mnemonic} requires {count} operands"));
+    }
+    let o = operands;
+    let line = match mnemonic {
+        "li" => {
+            let mut lines = Vec::new();
+            load_immediate(o[0], parse_constant(o[1], xlen)?, xlen, &mut lines);
+            return Ok(Some(lines));
+        }
+        "la" | "call" | "tail" => {
+            let (hi, lo) = pc_relative(o[o.len() - 1])?;
+            let (dest, second) = match mnemonic {
+                "la" => (o[0], format!("addi {0},{0},{lo}", o[0])),
+                "call" => ("ra", format!("jalr ra,{lo}(ra)")),
+                _ => ("t1", format!("jalr zero,{lo}(t1)")),
+            };
+            return Ok(Some(vec![format!("auipc {dest},{hi}"), second]));
+        }
+        "nop" => "addi zero,zero,0".to_owned(),
+        "ret" => "jalr zero,0(ra)".to_owned(),
+        "j" => format!("jal zero,{}", o[0]),
+        "jal" => format!("jal ra,{}", o[0]),
+        "jr" => format!("jalr zero,0({})", o[0]),
+        "jalr" => format!("jalr ra,0({})", o[0]),
+        "mv" => format!("addi {},{},0", o[0], o[1]),
+        "not" => format!("xori {},{},-1", o[0], o[1]),
+        "neg" => format!("sub {},zero,{}", o[0], o[1]),
+        "negw" => format!("subw {},zero,{}", o[0], o[1]),
+        "sext.w" => format!("addiw {},{},0", o[0], o[1]),
+        "seqz" => format!("sltiu {},{},1", o[0], o[1]),
+        "snez" => format!("sltu {},zero,{}", o[0], o[1]),
+        "sltz" => format!("slt {},{},zero", o[0], o[1]),
+        "sgtz" => format!("slt {},zero,{}", o[0], o[1]),
+        "beqz" => format!("beq {},zero,{}", o[0], o[1]),
+        "bnez" => format!("bne {},zero,{}", o[0], o[1]),
+        "blez" => format!("bge zero,{},{}", o[0], o[1]),
+        "bgez" => format!("bge {},zero,{}", o[0], o[1]),
+        "bltz" => format!("blt {},zero,{}", o[0], o[1]),
+        _ => format!("blt zero,{},{}", o[0], o[1]),
+    };
+    Ok(Some(vec![line]))
+}
+
+/// Constructs the instructions of a line of assembly, which may be a pseudo-instruction that expands to several.
+pub fn assemble_line_expanded(line: &str) -> Result<Vec<Inst>, String> {
+    assemble_line_expanded_with(line, &AssemblerConfig::default())
+}
+
+/// Constructs the instructions of a line of assembly using the given configuration.
+///
+/// The pseudo-instructions li, la, call and tail expand to sequences, li takes a constant and the others an offset
+/// from the first instruction of the sequence. The pseudo-instructions that the disassembler displays (`mv`, `ret`,
+/// `beqz`, ...) expand to one instruction, and all other lines are assembled by `assemble_line_with`.
+pub fn assemble_line_expanded_with(
+    line: &str,
+    config: &AssemblerConfig,
+) -> Result<Vec<Inst>, String> {
+    let (mnemonic, operands) = line.split_once(" ").unwrap_or((line, ""));
+    let operands: Vec<&str> = operands
+        .split(',')
+        .map(str::trim)
+        .filter(|_| !operands.is_empty())
+        .collect();
+    match expand_pseudo(mnemonic, &operands, config.xlen)? {
+        Some(lines) => lines
+            .iter()
+            .map(|line| assemble_line_with(line, config))
+            .collect(),
+        None => Ok(vec![assemble_line_with(line, config)?]),
+    }
+}
+
+/// The kind of a warning, each has a stable code.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum WarningKind {
+    /// A register is used against its role in the ABI, such as writing gp or linking into an argument register.
+    RegisterRole,
+    /// An immediate is sign-extended to a value that is unlikely to be intended.
+    SignExtension,
+    /// The instruction is a HINT that no standard extension defines, so it may be given a meaning in the future.
+    Hint,
+    /// A branch or jump target is only 2-byte aligned, which raises an exception without the C extension.
+    UnalignedTarget,
+}
+
+impl WarningKind {
+    pub fn code(self) -> &'static str {
         match self {
-            AssemblyResult::I(instruction) => instruction,
-            AssemblyResult::C(_) => panic!("i called on compressed instruction"),
+            WarningKind::RegisterRole => "W0001",
+            WarningKind::SignExtension => "W0002",
+            WarningKind::Hint => "W0003",
+            WarningKind::UnalignedTarget => "W0004",
         }
     }
 }
 
-/// Constructs an `Instruction` from a line of assembly.
-pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
-    let (mnemonic, operands): (&str, &str) = if let Some(x) = line.split_once(" ") {
-        x
-    } else {
-        (line, "")
+/// A non-fatal problem with an assembled instruction.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub message: String,
+}
+
+impl Display for Warning {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "warning[{}]: {}", self.kind.code(), self.message)
+    }
+}
+
+/// Returns true if a 32-bit encoding is a HINT other than the canonical nop and the HINTs defined by Zihintpause
+/// (pause), Zihintntl (ntl.*), Zicbop (prefetch.*) and Zicfilp (lpad).
+fn is_undefined_hint(word: u32) -> bool {
+    let rd = (word >> 7) & 0x1f;
+    let funct3 = (word >> 12) & 0b111;
+    match word & 0x7f {
+        // ADDI, its nop and the ORI of the prefetch instructions
+        0x13 if rd == 0 => {
+            word != 0x13 && !(funct3 == 0b110 && [0, 1, 3].contains(&((word >> 20) & 0x1f)))
+        }
+        // ADD with the ntl hints
+        0x33 if rd == 0 => !(0x00200033..=0x00500033).contains(&word) || word & 0x000ff000 != 0,
+        // LUI and the W instructions, but not AUIPC, whose encodings with x0 are lpad
+        0x1b | 0x3b | 0x37 => rd == 0,
+        // FENCE with an empty predecessor or successor set, except pause
+        0x0f if funct3 == 0 => {
+            (word >> 24 & 0xf == 0 || word >> 20 & 0xf == 0) && word != 0x0100000f
+        }
+        _ => false,
+    }
+}
+
+/// Returns the warnings for an assembled instruction.
+fn warnings(
+    result: &Inst,
+    mnemonic: &str,
+    operands: &[&str],
+    config: &AssemblerConfig,
+) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    let mut warn = |kind, message| warnings.push(Warning { kind, message });
+    let (word, hint) = match result {
+        Inst::Full(i) => (
+            Some(Instruction::encode(i)),
+            is_undefined_hint(Instruction::encode(i)),
+        ),
+        Inst::Compressed(c) => {
+            // c.add to x0 from x2-x5 are the c.ntl hints
+            let ntl = matches!(c, CInstruction::ADD { dest: IRegister::Zero, src }
+                if (2..=5).contains(&(*src as u32)))
+                || matches!(
+                    c,
+                    CInstruction::NTLP1
+                        | CInstruction::NTLPALL
+                        | CInstruction::NTLS1
+                        | CInstruction::NTLALL
+                );
+            (
+                c.try_expand().ok().map(|i| Instruction::encode(&i)),
+                c.is_hint() && !ntl,
+            )
+        }
     };
+    if hint {
+        warn(
+            WarningKind::Hint,
+            format!("{mnemonic} is a HINT that is not defined by a standard extension"),
+        );
+    }
+    if let Some(word) = word {
+        let rd = IRegister::from_int((word >> 7) & 0x1f);
+        match word & 0x7f {
+            0x67 | 0x6f
+                if ![IRegister::Zero, IRegister::ReturnAddress, IRegister::T0].contains(&rd) =>
+            {
+                warn(
+                    WarningKind::RegisterRole,
+                    format!(
+                        "{mnemonic} links into {rd}, the return address is kept in ra (or t0 for millicode)"
+                    ),
+                )
+            }
+            0x03 | 0x13 | 0x17 | 0x1b | 0x2f | 0x33 | 0x37 | 0x3b | 0x67 | 0x6f | 0x73
+                if rd == IRegister::GlobalPointer || rd == IRegister::ThreadPointer =>
+            {
+                let role = if rd == IRegister::GlobalPointer {
+                    "global"
+                } else {
+                    "thread"
+                };
+                warn(
+                    WarningKind::RegisterRole,
+                    format!("{mnemonic} writes {rd}, which holds the {role} pointer"),
+                )
+            }
+            _ => {}
+        }
+    }
+    if let Inst::Full(i) = result {
+        if let Some(offset) = i.branch_target(0)
+            && offset % 4 != 0
+        {
+            warn(
+                WarningKind::UnalignedTarget,
+                format!(
+                    "the target of {mnemonic} is only 2-byte aligned, which requires the C extension"
+                ),
+            );
+        }
+        if let Instruction::SLTIU { imm, .. } = i
+            && imm.val() < 0
+        {
+            warn(
+                WarningKind::SignExtension,
+                format!(
+                    "the immediate of sltiu is sign-extended, so it compares against {:#x}",
+                    imm.val() as u64
+                ),
+            );
+        }
+    }
+    // the upper immediates are sign-extended from bit 31 on RV64
+    if matches!(mnemonic, "lui" | "auipc")
+        && config.xlen != Xlen::Rv32
+        && let Some(Ok(value)) = operands.get(1).map(|o| parse_int(o))
+        && value >= 0x80000
+    {
+        warn(
+            WarningKind::SignExtension,
+            format!(
+                "the immediate of {mnemonic} is sign-extended to {:#x}",
+                ((value << 12) as i32) as i64 as u64
+            ),
+        );
+    }
+    warnings
+}
+
+/// Constructs an `Instruction` from a line of assembly, together with warnings about it.
+pub fn assemble_line_with_warnings(
+    line: &str,
+    config: &AssemblerConfig,
+) -> Result<(Inst, Vec<Warning>), String> {
+    let result = assemble_line_with(line, config)?;
+    let (mnemonic, operands) = line.split_once(" ").unwrap_or((line, ""));
+    let operands = split_operands(operands);
+    let warnings = warnings(&result, mnemonic, &operands, config);
+    Ok((result, warnings))
+}
 
+/// Constructs an `Instruction` from a mnemonic and its operands.
+fn assemble(mnemonic: &str, operands: &str, config: &AssemblerConfig) -> Result<Inst, String> {
     let mnemonics: Vec<&str> = mnemonic.split(".").collect();
 
     let operands: Vec<&str> = if operands.is_empty() {
         vec![]
     } else {
-        operands.split(',').collect()
+        split_operands(operands)
     };
-    let operands: Vec<&str> = operands
-        .iter()
-        .map(|operand| operand.to_owned().trim())
-        .collect();
 
     if mnemonics[0] == "c" {
         if mnemonics.len() == 1 {
             Err("compressed instruction must be specified".to_owned())
         } else {
-            compressed_assemble(&mnemonics[1..], operands).map(AssemblyResult::C)
+            compressed_assemble(&mnemonics[1..], operands, config)
+                .and_then(check_compressed_operands)
+                .map(Inst::Compressed)
         }
+    } else if mnemonics[0] == "cm" {
+        zcmp_assemble(mnemonic, operands, config).map(Inst::Compressed)
     } else {
         let x = match mnemonics[0] {
             // register-immediate instructions
@@ -140,6 +1100,31 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
             "sra" => r_assemble!(SRA),
             "srlw" => r_assemble!(SRLW),
             "sraw" => r_assemble!(SRAW),
+            // the draft RV128I instructions
+            #[cfg(feature = "rv128")]
+            "addid" => i_assemble!(ADDID),
+            #[cfg(feature = "rv128")]
+            "sllid" => sh_assemble!(SLLID),
+            #[cfg(feature = "rv128")]
+            "srlid" => sh_assemble!(SRLID),
+            #[cfg(feature = "rv128")]
+            "sraid" => sh_assemble!(SRAID),
+            #[cfg(feature = "rv128")]
+            "addd" => r_assemble!(ADDD),
+            #[cfg(feature = "rv128")]
+            "subd" => r_assemble!(SUBD),
+            #[cfg(feature = "rv128")]
+            "slld" => r_assemble!(SLLD),
+            #[cfg(feature = "rv128")]
+            "srld" => r_assemble!(SRLD),
+            #[cfg(feature = "rv128")]
+            "srad" => r_assemble!(SRAD),
+            #[cfg(feature = "rv128")]
+            "lq" => l_assemble!(LQ),
+            #[cfg(feature = "rv128")]
+            "ldu" => l_assemble!(LDU),
+            #[cfg(feature = "rv128")]
+            "sq" => s_assemble!(SQ),
             "sll" => r_assemble!(SLL),
             "slt" => r_assemble!(SLT),
             "sltu" => r_assemble!(SLTU),
@@ -156,7 +1141,145 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
             "divuw" => r_assemble!(DIVUW),
             "remw" => r_assemble!(REMW),
             "remuw" => r_assemble!(REMUW),
+            "pack" => r_assemble!(PACK),
+            "packh" => r_assemble!(PACKH),
+            "packw" => r_assemble!(PACKW),
+            "brev8" => ru_assemble!(BREV8),
+            "zip" | "unzip" if config.xlen == Xlen::Rv64 => {
+                Err(format!("{} is only available on RV32", mnemonics[0]))
+            }
+            "zip" => ru_assemble!(ZIP),
+            "unzip" => ru_assemble!(UNZIP),
+            "andn" => r_assemble!(ANDN),
+            "orn" => r_assemble!(ORN),
+            "xnor" => r_assemble!(XNOR),
+            "rol" => r_assemble!(ROL),
+            "ror" => r_assemble!(ROR),
+            "rori" => sh_assemble!(RORI),
+            "rolw" => r_assemble!(ROLW),
+            "rorw" => r_assemble!(RORW),
+            "roriw" => shw_assemble!(RORIW),
+            "rev8" if config.xlen == Xlen::Rv32 => ru_assemble!(REV8RV32),
+            "rev8" => ru_assemble!(REV8),
+            "clmul" => r_assemble!(CLMUL),
+            "clmulh" => r_assemble!(CLMULH),
+            "xperm4" => r_assemble!(XPERM4),
+            "xperm8" => r_assemble!(XPERM8),
+            "aes32esi" | "aes32esmi" | "aes32dsi" | "aes32dsmi" | "sha512sum0r" | "sha512sum1r"
+            | "sha512sig0l" | "sha512sig0h" | "sha512sig1l" | "sha512sig1h"
+                if config.xlen == Xlen::Rv64 =>
+            {
+                Err(format!("{} is only available on RV32", mnemonics[0]))
+            }
+            "aes64es" | "aes64esm" | "aes64ds" | "aes64dsm" | "aes64im" | "aes64ks1i"
+            | "aes64ks2" | "sha512sig0" | "sha512sig1" | "sha512sum0" | "sha512sum1"
+                if config.xlen == Xlen::Rv32 =>
+            {
+                Err(format!("{} is only available on RV64", mnemonics[0]))
+            }
+            "aes32esi" | "aes32esmi" | "aes32dsi" | "aes32dsmi" => {
+                if operands.len() != 4 {
+                    Err(format!("{} instruction requires 4 operands", mnemonics[0]))
+                } else {
+                    let dest = IRegister::from_string(operands[0])?;
+                    let src1 = IRegister::from_string(operands[1])?;
+                    let src2 = IRegister::from_string(operands[2])?;
+                    let bs = Bs::try_from(parse_int(operands[3])?)?;
+                    Ok(match mnemonics[0] {
+                        "aes32esi" => Instruction::AES32ESI {
+                            dest,
+                            src1,
+                            src2,
+                            bs,
+                        },
+                        "aes32esmi" => Instruction::AES32ESMI {
+                            dest,
+                            src1,
+                            src2,
+                            bs,
+                        },
+                        "aes32dsi" => Instruction::AES32DSI {
+                            dest,
+                            src1,
+                            src2,
+                            bs,
+                        },
+                        _ => Instruction::AES32DSMI {
+                            dest,
+                            src1,
+                            src2,
+                            bs,
+                        },
+                    })
+                }
+            }
+            "sha512sum0r" => r_assemble!(SHA512SUM0R),
+            "sha512sum1r" => r_assemble!(SHA512SUM1R),
+            "sha512sig0l" => r_assemble!(SHA512SIG0L),
+            "sha512sig0h" => r_assemble!(SHA512SIG0H),
+            "sha512sig1l" => r_assemble!(SHA512SIG1L),
+            "sha512sig1h" => r_assemble!(SHA512SIG1H),
+            "aes64es" => r_assemble!(AES64ES),
+            "aes64esm" => r_assemble!(AES64ESM),
+            "aes64ds" => r_assemble!(AES64DS),
+            "aes64dsm" => r_assemble!(AES64DSM),
+            "aes64im" => ru_assemble!(AES64IM),
+            "aes64ks1i" => {
+                if operands.len() != 3 {
+                    Err("aes64ks1i instruction requires 3 operands".to_owned())
+                } else {
+                    let rnum = parse_int(operands[2])?;
+                    if rnum > 0xa {
+                        return Err(format!(
+                            "aes64ks1i round number must be at most 10, got {rnum}"
+                        ));
+                    }
+                    Ok(Instruction::AES64KS1I {
+                        dest: IRegister::from_string(operands[0])?,
+                        src: IRegister::from_string(operands[1])?,
+                        rnum: Rnum::try_from(rnum)?,
+                    })
+                }
+            }
+            "aes64ks2" => r_assemble!(AES64KS2),
+            "sha256sig0" => ru_assemble!(SHA256SIG0),
+            "sha256sig1" => ru_assemble!(SHA256SIG1),
+            "sha256sum0" => ru_assemble!(SHA256SUM0),
+            "sha256sum1" => ru_assemble!(SHA256SUM1),
+            "sha512sig0" => ru_assemble!(SHA512SIG0),
+            "sha512sig1" => ru_assemble!(SHA512SIG1),
+            "sha512sum0" => ru_assemble!(SHA512SUM0),
+            "sha512sum1" => ru_assemble!(SHA512SUM1),
+            "vsetvli" | "vsetivli" | "vsetvl" => {
+                VectorInstruction::assemble(&mnemonics, &operands).map(Instruction::Vector)
+            }
+            m if m.starts_with('v') && mnemonics.len() > 1 => {
+                VectorInstruction::assemble(&mnemonics, &operands).map(Instruction::Vector)
+            }
+            #[cfg(feature = "p")]
+            _ if SimdInstruction::is_mnemonic(mnemonic) => {
+                SimdInstruction::assemble(mnemonic, &operands, config.xlen).map(Instruction::P)
+            }
             // load instructions
+            #[cfg(feature = "xandesperf")]
+            "nds" if mnemonics.len() > 1 && config.xlen.bits() == 128 => Err(
+                "the XAndesPerf instructions are not available on RV128, which uses their opcode"
+                    .to_owned(),
+            ),
+            #[cfg(feature = "xandesperf")]
+            "nds" if mnemonics.len() > 1 => {
+                AndesInstruction::assemble(&mnemonics[1..], &operands, config.xlen)
+                    .map(Instruction::XAndesPerf)
+            }
+            #[cfg(feature = "xthead")]
+            "th" if mnemonics.len() > 1 => {
+                TheadInstruction::assemble(&mnemonics[1..], &operands, config.xlen)
+                    .map(Instruction::XThead)
+            }
+            #[cfg(feature = "zalasr")]
+            "lb" | "lh" | "lw" | "ld" | "sb" | "sh" | "sw" | "sd" if mnemonics.len() > 1 => {
+                zalasr_assemble(&mnemonics, &operands, config)
+            }
             "lb" => l_assemble!(LB),
             "lbu" => l_assemble!(LBU),
             "lhu" => l_assemble!(LHU),
@@ -202,30 +1325,20 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
                 if operands.len() != 2 {
                     Err("lui instruction requires 2 operands".to_owned())
                 } else {
-                    let int: i64 = parse_int(operands[1])?;
-                    if int > 2i64.pow(19) - 1 || int < -2i64.pow(19) {
-                        Err("UImmediate out of range".to_owned())
-                    } else {
-                        Ok(Instruction::LUI {
-                            dest: IRegister::from_string(operands[0])?,
-                            imm: UImmediate::try_from(int)?,
-                        })
-                    }
+                    Ok(Instruction::LUI {
+                        dest: IRegister::from_string(operands[0])?,
+                        imm: parse_u_immediate(operands[1])?,
+                    })
                 }
             }
             "auipc" => {
                 if operands.len() != 2 {
                     Err("auipc instruction requires 2 operands".to_owned())
                 } else {
-                    let int: i64 = parse_int(operands[1])?;
-                    if int > 2i64.pow(19) - 1 || int < -2i64.pow(19) {
-                        Err("UImmediate out of range".to_owned())
-                    } else {
-                        Ok(Instruction::AUIPC {
-                            dest: IRegister::from_string(operands[0])?,
-                            imm: UImmediate::try_from(int)?,
-                        })
-                    }
+                    Ok(Instruction::AUIPC {
+                        dest: IRegister::from_string(operands[0])?,
+                        imm: parse_u_immediate(operands[1])?,
+                    })
                 }
             }
             "fence" => {
@@ -262,7 +1375,7 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
                         }
                     }
                 } else if mnemonics[1] == "i" {
-                    if operands.len() != 0 {
+                    if !operands.is_empty() {
                         Err("fence.i requires 0 operands".to_owned())
                     } else {
                         Ok(Instruction::FENCEI)
@@ -271,9 +1384,130 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
                     Err("invalid fence".to_owned())
                 }
             }
+            "ecall" | "ebreak" | "sret" | "mret" | "mnret" | "dret" | "wfi" => {
+                if !operands.is_empty() {
+                    Err(format!("{mnemonic} requires 0 operands"))
+                } else {
+                    match mnemonic {
+                        "ecall" => Ok(Instruction::ECALL),
+                        "ebreak" => Ok(Instruction::EBREAK),
+                        "sret" => Ok(Instruction::SRET),
+                        "mret" => Ok(Instruction::MRET),
+                        "mnret" => Ok(Instruction::MNRET),
+                        "dret" => Ok(Instruction::DRET),
+                        "wfi" => Ok(Instruction::WFI),
+                        _ => Err(unknown_mnemonic("unknown mnemonic", mnemonic)),
+                    }
+                }
+            }
+            // the operands default to x0, which fences every address and address space
+            "sfence" if mnemonic == "sfence.vma" => {
+                if operands.len() > 2 {
+                    Err("sfence.vma requires at most 2 operands".to_owned())
+                } else {
+                    let register = |i: usize| {
+                        operands
+                            .get(i)
+                            .map_or(Ok(IRegister::Zero), |r| IRegister::from_string(r))
+                    };
+                    Ok(Instruction::SFENCEVMA {
+                        vaddr: register(0)?,
+                        asid: register(1)?,
+                    })
+                }
+            }
+            "pause" => {
+                if !operands.is_empty() {
+                    Err("pause requires 0 operands".to_owned())
+                } else {
+                    Ok(Instruction::PAUSE)
+                }
+            }
+            "ntl" => {
+                if !operands.is_empty() {
+                    Err(format!("{mnemonic} requires 0 operands"))
+                } else {
+                    match mnemonics.get(1) {
+                        Some(&"p1") => Ok(Instruction::NTLP1),
+                        Some(&"pall") => Ok(Instruction::NTLPALL),
+                        Some(&"s1") => Ok(Instruction::NTLS1),
+                        Some(&"all") => Ok(Instruction::NTLALL),
+                        _ => Err(format!("unknown non-temporal locality hint {mnemonic}")),
+                    }
+                }
+            }
+            "cbo" => {
+                if operands.len() != 1 {
+                    Err(format!("{mnemonic} requires 1 operand"))
+                } else {
+                    let (base, offset) = parse_address_expression(operands[0])?;
+                    if offset != 0 {
+                        Err(format!("{mnemonic} does not take an offset"))
+                    } else {
+                        match mnemonics.get(1) {
+                            Some(&"clean") => Ok(Instruction::CBOCLEAN { base }),
+                            Some(&"flush") => Ok(Instruction::CBOFLUSH { base }),
+                            Some(&"inval") => Ok(Instruction::CBOINVAL { base }),
+                            Some(&"zero") => Ok(Instruction::CBOZERO { base }),
+                            _ => Err(format!("unknown cache block operation {mnemonic}")),
+                        }
+                    }
+                }
+            }
+            "prefetch" => {
+                if operands.len() != 1 {
+                    Err(format!("{mnemonic} requires 1 operand"))
+                } else {
+                    let (base, offset) = parse_address_expression(operands[0])?;
+                    let offset = PrefetchImmediate::try_from(offset)?;
+                    match mnemonics.get(1) {
+                        Some(&"i") => Ok(Instruction::PREFETCHI { base, offset }),
+                        Some(&"r") => Ok(Instruction::PREFETCHR { base, offset }),
+                        Some(&"w") => Ok(Instruction::PREFETCHW { base, offset }),
+                        _ => Err(format!("unknown prefetch {mnemonic}")),
+                    }
+                }
+            }
+            "lpad" => {
+                if operands.len() != 1 {
+                    Err("lpad requires 1 operand".to_owned())
+                } else {
+                    Ok(Instruction::LPAD {
+                        label: LpadImmediate::try_from(parse_int(operands[0])?)?,
+                    })
+                }
+            }
+            "sspush" | "sspopchk" => {
+                if operands.len() != 1 {
+                    Err(format!("{mnemonic} requires 1 operand"))
+                } else {
+                    let src = IRegister::from_string(operands[0])?;
+                    if ![IRegister::ReturnAddress, IRegister::T0].contains(&src) {
+                        Err(format!("{mnemonic} only takes ra or t0, got {src}"))
+                    } else if mnemonic == "sspush" {
+                        Ok(Instruction::SSPUSH { src })
+                    } else {
+                        Ok(Instruction::SSPOPCHK { src })
+                    }
+                }
+            }
+            "ssrdp" => {
+                if operands.len() != 1 {
+                    Err("ssrdp requires 1 operand".to_owned())
+                } else {
+                    let dest = IRegister::from_string(operands[0])?;
+                    if dest == IRegister::Zero {
+                        Err("ssrdp must not write to x0".to_owned())
+                    } else {
+                        Ok(Instruction::SSRDP { dest })
+                    }
+                }
+            }
             // LR can't use `amo_assemble!` because it only has two operands
             "lr" => {
-                if mnemonics.len() == 1 {
+                if operands.len() != 2 {
+                    Err("lr instruction requires 2 operands".to_owned())
+                } else if mnemonics.len() == 1 {
                     Err("lr must have size (w/d)".to_owned())
                 } else if mnemonics.len() == 2 {
                     if mnemonics[1] == "w" {
@@ -326,18 +1560,19 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
                 }
             }
             "sc" => amo_assemble!(SC),
-            "amoswap" => amo_assemble!(AMOSWAP),
-            "amoadd" => amo_assemble!(AMOADD),
-            "amoxor" => amo_assemble!(AMOXOR),
-            "amoand" => amo_assemble!(AMOAND),
-            "amoor" => amo_assemble!(AMOOR),
-            "amomin" => amo_assemble!(AMOMIN),
-            "amomax" => amo_assemble!(AMOMAX),
-            "amominu" => amo_assemble!(AMOMINU),
-            "amomaxu" => amo_assemble!(AMOMAXU),
+            "amoswap" => amo_assemble!(AMOSWAP b h w d),
+            "ssamoswap" => amo_assemble!(SSAMOSWAP),
+            "amoadd" => amo_assemble!(AMOADD b h w d),
+            "amoxor" => amo_assemble!(AMOXOR b h w d),
+            "amoand" => amo_assemble!(AMOAND b h w d),
+            "amoor" => amo_assemble!(AMOOR b h w d),
+            "amomin" => amo_assemble!(AMOMIN b h w d),
+            "amomax" => amo_assemble!(AMOMAX b h w d),
+            "amominu" => amo_assemble!(AMOMINU b h w d),
+            "amomaxu" => amo_assemble!(AMOMAXU b h w d),
+            "amocas" => amo_assemble!(AMOCAS b h),
             "flw" => {
                 if operands.len() != 2 {
-                    println!("{:?}", operands);
                     Err("flw instruction requires 2 operands".to_owned())
                 } else {
                     let (base, offset) = parse_address_expression(operands[1])?;
@@ -350,7 +1585,6 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
             }
             "fsw" => {
                 if operands.len() != 2 {
-                    println!("{:?}", operands);
                     Err("fsw instruction requires 2 operands".to_owned())
                 } else {
                     let (base, offset) = parse_address_expression(operands[1])?;
@@ -361,144 +1595,197 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
                     })
                 }
             }
-            "fsqrt" => {
+            "flh" => {
                 if operands.len() != 2 {
-                    Err("fsqrt instruction requires 2 operands".to_owned())
-                } else if mnemonics.len() == 2 {
-                    Ok(Instruction::FSQRTS {
-                        dest: FRegister::try_from(operands[0])?,
-                        src: FRegister::try_from(operands[1])?,
-                        rm: RoundingMode::DYN,
-                    })
-                } else if mnemonics.len() == 3 {
-                    Ok(Instruction::FSQRTS {
+                    Err("flh instruction requires 2 operands".to_owned())
+                } else {
+                    let (base, offset) = parse_address_expression(operands[1])?;
+                    Ok(Instruction::FLH {
                         dest: FRegister::try_from(operands[0])?,
-                        src: FRegister::try_from(operands[1])?,
-                        rm: RoundingMode::from_str(mnemonics[2])?,
+                        base,
+                        offset: IImmediate::try_from(offset)?,
                     })
+                }
+            }
+            "fsh" => {
+                if operands.len() != 2 {
+                    Err("fsh instruction requires 2 operands".to_owned())
                 } else {
-                    Err("fsqrt instruction requires a suffix {s,d}".to_owned())
+                    let (base, offset) = parse_address_expression(operands[1])?;
+                    Ok(Instruction::FSH {
+                        base,
+                        src: FRegister::try_from(operands[0])?,
+                        offset: SImmediate::try_from(offset)?,
+                    })
                 }
             }
-            "fadd" => fr_assemble!(FADD),
-            "fsub" => fr_assemble!(FSUB),
-            "fmul" => fr_assemble!(FMUL),
-            "fdiv" => fr_assemble!(FDIV),
-            "fmin" => {
-                if operands.len() != 3 {
-                    Err("fmin instruction requires 3 operands".to_owned())
-                } else if mnemonics.len() == 2 {
-                    Ok(Instruction::FMINS {
+            "flq" => {
+                if operands.len() != 2 {
+                    Err("flq instruction requires 2 operands".to_owned())
+                } else {
+                    let (base, offset) = parse_address_expression(operands[1])?;
+                    Ok(Instruction::FLQ {
                         dest: FRegister::try_from(operands[0])?,
-                        src1: FRegister::try_from(operands[1])?,
-                        src2: FRegister::try_from(operands[2])?,
+                        base,
+                        offset: IImmediate::try_from(offset)?,
                     })
-                } else {
-                    Err("fmin instruction requires a suffix {s,d}".to_owned())
                 }
             }
-            "fmax" => {
-                if operands.len() != 3 {
-                    Err("fmax instruction requires 3 operands".to_owned())
-                } else if mnemonics.len() == 2 {
-                    Ok(Instruction::FMAXS {
-                        dest: FRegister::try_from(operands[0])?,
-                        src1: FRegister::try_from(operands[1])?,
-                        src2: FRegister::try_from(operands[2])?,
+            "fsq" => {
+                if operands.len() != 2 {
+                    Err("fsq instruction requires 2 operands".to_owned())
+                } else {
+                    let (base, offset) = parse_address_expression(operands[1])?;
+                    Ok(Instruction::FSQ {
+                        base,
+                        src: FRegister::try_from(operands[0])?,
+                        offset: SImmediate::try_from(offset)?,
                     })
+                }
+            }
+            "fsqrt" => {
+                if operands.len() != 2 {
+                    Err("fsqrt instruction requires 2 operands".to_owned())
                 } else {
-                    Err("fmax instruction requires a suffix {s,d}".to_owned())
+                    let quad = quad_precision(&mnemonics)?;
+                    let dest = FRegister::try_from(operands[0])?;
+                    let src = FRegister::try_from(operands[1])?;
+                    let rm = float_rounding_mode(&mnemonics, 2)?;
+                    if quad {
+                        Ok(Instruction::FSQRTQ { dest, src, rm })
+                    } else {
+                        Ok(Instruction::FSQRTS { dest, src, rm })
+                    }
                 }
             }
+            "fadd" => fr_assemble!(FADD),
+            "fsub" => fr_assemble!(FSUB),
+            "fmul" => fr_assemble!(FMUL),
+            "fdiv" => fr_assemble!(FDIV),
+            "fmadd" => fr4_assemble!(FMADD),
+            "fmsub" => fr4_assemble!(FMSUB),
+            "fnmsub" => fr4_assemble!(FNMSUB),
+            "fnmadd" => fr4_assemble!(FNMADD),
+            "fsgnj" => frr_assemble!(FSGNJ),
+            "fsgnjn" => frr_assemble!(FSGNJN),
+            "fsgnjx" => frr_assemble!(FSGNJX),
+            "fmin" => frr_assemble!(FMIN),
+            "fmax" => frr_assemble!(FMAX),
             "fcvt" => {
                 if operands.len() != 2 {
                     Err("fcvt requires 3 operands".to_owned())
-                } else if mnemonics.len() == 3 {
-                    // default rounding mode
+                } else if mnemonics.len() == 3 || mnemonics.len() == 4 {
+                    let rm = float_rounding_mode(&mnemonics, 3)?;
                     match (mnemonics[1], mnemonics[2]) {
                         ("w", "s") => Ok(Instruction::FCVTWS {
                             dest: IRegister::from_string(operands[0])?,
                             src: FRegister::try_from(operands[1])?,
-                            rm: RoundingMode::DYN,
+                            rm,
                         }),
                         ("wu", "s") => Ok(Instruction::FCVTWUS {
                             dest: IRegister::from_string(operands[0])?,
                             src: FRegister::try_from(operands[1])?,
-                            rm: RoundingMode::DYN,
+                            rm,
                         }),
                         ("s", "w") => Ok(Instruction::FCVTSW {
                             dest: FRegister::try_from(operands[0])?,
                             src: IRegister::from_string(operands[1])?,
-                            rm: RoundingMode::DYN,
+                            rm,
                         }),
                         ("s", "wu") => Ok(Instruction::FCVTSWU {
                             dest: FRegister::try_from(operands[0])?,
                             src: IRegister::from_string(operands[1])?,
-                            rm: RoundingMode::DYN,
+                            rm,
                         }),
                         ("l", "s") => Ok(Instruction::FCVTLS {
                             dest: IRegister::from_string(operands[0])?,
                             src: FRegister::try_from(operands[1])?,
-                            rm: RoundingMode::DYN,
+                            rm,
                         }),
                         ("lu", "s") => Ok(Instruction::FCVTLUS {
                             dest: IRegister::from_string(operands[0])?,
                             src: FRegister::try_from(operands[1])?,
-                            rm: RoundingMode::DYN,
+                            rm,
                         }),
                         ("s", "l") => Ok(Instruction::FCVTSL {
                             dest: FRegister::try_from(operands[0])?,
                             src: IRegister::from_string(operands[1])?,
-                            rm: RoundingMode::DYN,
+                            rm,
                         }),
                         ("s", "lu") => Ok(Instruction::FCVTSLU {
                             dest: FRegister::try_from(operands[0])?,
                             src: IRegister::from_string(operands[1])?,
-                            rm: RoundingMode::DYN,
+                            rm,
                         }),
-                        _ => Err("invalid fcvt suffixes".to_owned()),
-                    }
-                } else if mnemonics.len() == 4 {
-                    match (mnemonics[1], mnemonics[2]) {
-                        ("w", "s") => Ok(Instruction::FCVTWS {
+                        ("w", "q") => Ok(Instruction::FCVTWQ {
                             dest: IRegister::from_string(operands[0])?,
                             src: FRegister::try_from(operands[1])?,
-                            rm: RoundingMode::from_str(mnemonics[3])?,
+                            rm,
                         }),
-                        ("wu", "s") => Ok(Instruction::FCVTWUS {
+                        ("wu", "q") => Ok(Instruction::FCVTWUQ {
                             dest: IRegister::from_string(operands[0])?,
                             src: FRegister::try_from(operands[1])?,
-                            rm: RoundingMode::from_str(mnemonics[3])?,
+                            rm,
                         }),
-                        ("s", "w") => Ok(Instruction::FCVTSW {
+                        ("q", "w") => Ok(Instruction::FCVTQW {
                             dest: FRegister::try_from(operands[0])?,
                             src: IRegister::from_string(operands[1])?,
-                            rm: RoundingMode::from_str(mnemonics[3])?,
+                            rm,
                         }),
-                        ("s", "wu") => Ok(Instruction::FCVTSWU {
+                        ("q", "wu") => Ok(Instruction::FCVTQWU {
                             dest: FRegister::try_from(operands[0])?,
                             src: IRegister::from_string(operands[1])?,
-                            rm: RoundingMode::from_str(mnemonics[3])?,
+                            rm,
                         }),
-                        ("l", "s") => Ok(Instruction::FCVTLS {
+                        ("l", "q") => Ok(Instruction::FCVTLQ {
                             dest: IRegister::from_string(operands[0])?,
                             src: FRegister::try_from(operands[1])?,
-                            rm: RoundingMode::from_str(mnemonics[3])?,
+                            rm,
                         }),
-                        ("lu", "s") => Ok(Instruction::FCVTLUS {
+                        ("lu", "q") => Ok(Instruction::FCVTLUQ {
                             dest: IRegister::from_string(operands[0])?,
                             src: FRegister::try_from(operands[1])?,
-                            rm: RoundingMode::from_str(mnemonics[3])?,
+                            rm,
                         }),
-                        ("s", "l") => Ok(Instruction::FCVTSL {
+                        ("q", "l") => Ok(Instruction::FCVTQL {
                             dest: FRegister::try_from(operands[0])?,
                             src: IRegister::from_string(operands[1])?,
-                            rm: RoundingMode::from_str(mnemonics[3])?,
+                            rm,
                         }),
-                        ("s", "lu") => Ok(Instruction::FCVTSLU {
+                        ("q", "lu") => Ok(Instruction::FCVTQLU {
                             dest: FRegister::try_from(operands[0])?,
                             src: IRegister::from_string(operands[1])?,
-                            rm: RoundingMode::from_str(mnemonics[3])?,
+                            rm,
+                        }),
+                        ("s", "q") => Ok(Instruction::FCVTSQ {
+                            dest: FRegister::try_from(operands[0])?,
+                            src: FRegister::try_from(operands[1])?,
+                            rm,
+                        }),
+                        ("bf16", "s") => Ok(Instruction::FCVTBF16S {
+                            dest: FRegister::try_from(operands[0])?,
+                            src: FRegister::try_from(operands[1])?,
+                            rm,
+                        }),
+                        ("s", "bf16") => Ok(Instruction::FCVTSBF16 {
+                            dest: FRegister::try_from(operands[0])?,
+                            src: FRegister::try_from(operands[1])?,
+                            rm,
+                        }),
+                        ("q", "s") => Ok(Instruction::FCVTQS {
+                            dest: FRegister::try_from(operands[0])?,
+                            src: FRegister::try_from(operands[1])?,
+                            rm,
+                        }),
+                        ("d", "q") => Ok(Instruction::FCVTDQ {
+                            dest: FRegister::try_from(operands[0])?,
+                            src: FRegister::try_from(operands[1])?,
+                            rm,
+                        }),
+                        ("q", "d") => Ok(Instruction::FCVTQD {
+                            dest: FRegister::try_from(operands[0])?,
+                            src: FRegister::try_from(operands[1])?,
+                            rm,
                         }),
                         _ => Err("invalid fcvt suffixes".to_owned()),
                     }
@@ -519,6 +1806,14 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
                             dest: FRegister::try_from(operands[0])?,
                             src: IRegister::from_string(operands[1])?,
                         }),
+                        ("x", "h") => Ok(Instruction::FMVXH {
+                            dest: IRegister::from_string(operands[0])?,
+                            src: FRegister::try_from(operands[1])?,
+                        }),
+                        ("h", "x") => Ok(Instruction::FMVHX {
+                            dest: FRegister::try_from(operands[0])?,
+                            src: IRegister::from_string(operands[1])?,
+                        }),
                         _ => Err("invalid fmv suffixes".to_owned()),
                     }
                 } else {
@@ -528,76 +1823,60 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
             "feq" => {
                 if operands.len() != 3 {
                     Err("feq requires 3 operands".to_owned())
-                } else if mnemonics.len() == 2 {
-                    match mnemonics[1] {
-                        "s" => Ok(Instruction::FEQS {
-                            dest: IRegister::from_string(operands[0])?,
-                            src1: FRegister::try_from(operands[1])?,
-                            src2: FRegister::try_from(operands[2])?,
-                        }),
-                        "d" => todo!(),
-                        "q" => todo!(),
-                        "h" => todo!(),
-                        _ => Err("feq requires a suffix {s,d}".to_owned()),
-                    }
                 } else {
-                    Err("feq requires a suffix {s,d}".to_owned())
+                    let quad = quad_precision(&mnemonics)?;
+                    let dest = IRegister::from_string(operands[0])?;
+                    let src1 = FRegister::try_from(operands[1])?;
+                    let src2 = FRegister::try_from(operands[2])?;
+                    if quad {
+                        Ok(Instruction::FEQQ { dest, src1, src2 })
+                    } else {
+                        Ok(Instruction::FEQS { dest, src1, src2 })
+                    }
                 }
             }
             "flt" => {
                 if operands.len() != 3 {
                     Err("flt requires 3 operands".to_owned())
-                } else if mnemonics.len() == 2 {
-                    match mnemonics[1] {
-                        "s" => Ok(Instruction::FLTS {
-                            dest: IRegister::from_string(operands[0])?,
-                            src1: FRegister::try_from(operands[1])?,
-                            src2: FRegister::try_from(operands[2])?,
-                        }),
-                        "d" => todo!(),
-                        "q" => todo!(),
-                        "h" => todo!(),
-                        _ => Err("flt requires a suffix {s,d}".to_owned()),
-                    }
                 } else {
-                    Err("flt requires a suffix {s,d}".to_owned())
+                    let quad = quad_precision(&mnemonics)?;
+                    let dest = IRegister::from_string(operands[0])?;
+                    let src1 = FRegister::try_from(operands[1])?;
+                    let src2 = FRegister::try_from(operands[2])?;
+                    if quad {
+                        Ok(Instruction::FLTQ { dest, src1, src2 })
+                    } else {
+                        Ok(Instruction::FLTS { dest, src1, src2 })
+                    }
                 }
             }
             "fle" => {
                 if operands.len() != 3 {
                     Err("fle requires 3 operands".to_owned())
-                } else if mnemonics.len() == 2 {
-                    match mnemonics[1] {
-                        "s" => Ok(Instruction::FLES {
-                            dest: IRegister::from_string(operands[0])?,
-                            src1: FRegister::try_from(operands[1])?,
-                            src2: FRegister::try_from(operands[2])?,
-                        }),
-                        "d" => todo!(),
-                        "q" => todo!(),
-                        "h" => todo!(),
-                        _ => Err("fle requires a suffix {s,d}".to_owned()),
-                    }
                 } else {
-                    Err("fle requires a suffix {s,d}".to_owned())
+                    let quad = quad_precision(&mnemonics)?;
+                    let dest = IRegister::from_string(operands[0])?;
+                    let src1 = FRegister::try_from(operands[1])?;
+                    let src2 = FRegister::try_from(operands[2])?;
+                    if quad {
+                        Ok(Instruction::FLEQ { dest, src1, src2 })
+                    } else {
+                        Ok(Instruction::FLES { dest, src1, src2 })
+                    }
                 }
             }
             "fclass" => {
                 if operands.len() != 2 {
                     Err("fclass requires 2 operands".to_owned())
-                } else if mnemonics.len() == 2 {
-                    match mnemonics[1] {
-                        "s" => Ok(Instruction::FCLASSS {
-                            dest: IRegister::from_string(operands[0])?,
-                            src: FRegister::try_from(operands[1])?,
-                        }),
-                        "d" => todo!(),
-                        "q" => todo!(),
-                        "h" => todo!(),
-                        _ => Err("fle requires a suffix {s,d}".to_owned()),
-                    }
                 } else {
-                    Err("fle requires a suffix {s,d}".to_owned())
+                    let quad = quad_precision(&mnemonics)?;
+                    let dest = IRegister::from_string(operands[0])?;
+                    let src = FRegister::try_from(operands[1])?;
+                    if quad {
+                        Ok(Instruction::FCLASSQ { dest, src })
+                    } else {
+                        Ok(Instruction::FCLASSS { dest, src })
+                    }
                 }
             }
             "csrrw" => {
@@ -639,7 +1918,7 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
                 } else {
                     Ok(Instruction::CSRRWI {
                         dest: IRegister::from_string(operands[0])?,
-                        imm: CSRImmediate::try_from(parse_int(operands[2])?)?,
+                        imm: parse_csr_immediate("csrrwi", operands[2])?,
                         csr: CSR::try_from(parse_int(operands[1])?)?,
                     })
                 }
@@ -650,7 +1929,7 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
                 } else {
                     Ok(Instruction::CSRRSI {
                         dest: IRegister::from_string(operands[0])?,
-                        imm: CSRImmediate::try_from(parse_int(operands[2])?)?,
+                        imm: parse_csr_immediate("csrrsi", operands[2])?,
                         csr: CSR::try_from(parse_int(operands[1])?)?,
                     })
                 }
@@ -661,26 +1940,94 @@ pub fn assemble_line(line: &str) -> Result<AssemblyResult, String> {
                 } else {
                     Ok(Instruction::CSRRCI {
                         dest: IRegister::from_string(operands[0])?,
-                        imm: CSRImmediate::try_from(parse_int(operands[2])?)?,
+                        imm: parse_csr_immediate("csrrci", operands[2])?,
                         csr: CSR::try_from(parse_int(operands[1])?)?,
                     })
                 }
             }
-            _ => Err(format!("unknown mnemonic: {}", mnemonic)),
+            _ => Err(unknown_mnemonic("unknown mnemonic", mnemonic)),
         };
-        x.map(AssemblyResult::I)
+        x.map(Inst::Full)
     }
 }
 
-fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstruction, String> {
+/// Rejects the operands of a compressed instruction that give a reserved encoding or the encoding of another
+/// instruction (`c.jalr zero` is c.ebreak and `c.lui sp,1` is c.addi16sp).
+fn check_compressed_operands(c: CInstruction) -> Result<CInstruction, String> {
+    use IRegister::{StackPointer, Zero};
+    let error = match c {
+        CInstruction::ADDI4SPN { imm, .. } if imm.val() == 0 => {
+            "c.addi4spn immediate must not be zero"
+        }
+        CInstruction::LWSP { dest: Zero, .. } => "c.lwsp must not load into x0",
+        CInstruction::LDSP { dest: Zero, .. } => "c.ldsp must not load into x0",
+        CInstruction::ADDIW { dest: Zero, .. } => "c.addiw must not write to x0",
+        CInstruction::ADDI16SP { imm } if imm.val() == 0 => "c.addi16sp immediate must not be zero",
+        CInstruction::LUI {
+            dest: StackPointer, ..
+        } => "c.lui can not write to sp, whose encoding is c.addi16sp",
+        CInstruction::JR { src: Zero } => "c.jr must not jump to x0",
+        CInstruction::JALR { src: Zero } => "c.jalr can not jump to x0, whose encoding is c.ebreak",
+        CInstruction::MV { src: Zero, .. } => {
+            "c.mv can not copy x0, whose encoding is c.jr (use c.li instead)"
+        }
+        CInstruction::ADD { src: Zero, .. } => "c.add can not add x0, whose encoding is c.jalr",
+        _ => return Ok(c),
+    };
+    Err(error.to_owned())
+}
+
+fn compressed_assemble(
+    mnemonics: &[&str],
+    operands: Vec<&str>,
+    config: &AssemblerConfig,
+) -> Result<CInstruction, String> {
+    // these pairs of instructions share an encoding, which one is available depends on XLEN
+    match (mnemonics[0], config.xlen) {
+        ("flw" | "fsw" | "jal" | "flwsp" | "fswsp", xlen) if xlen != Xlen::Rv32 => {
+            return Err(format!("c.{} is only available on RV32", mnemonics[0]));
+        }
+        #[cfg(feature = "rv128")]
+        ("fld" | "fsd" | "fldsp" | "fsdsp", Xlen::Rv128) => {
+            return Err(format!(
+                "c.{} is not available on RV128, which uses its encoding for the quad loads and stores",
+                mnemonics[0]
+            ));
+        }
+        ("ld" | "sd" | "ldsp" | "sdsp", Xlen::Rv32) if !config.extensions.zclsd => {
+            return Err(format!(
+                "c.{} is only available on RV64 or with Zclsd",
+                mnemonics[0]
+            ));
+        }
+        ("flw" | "fsw" | "flwsp" | "fswsp", Xlen::Rv32) if config.extensions.zclsd => {
+            return Err(format!(
+                "c.{} is not available with Zclsd, which uses its encoding",
+                mnemonics[0]
+            ));
+        }
+        ("fsdsp", _) if config.extensions.zcmp => {
+            return Err("c.fsdsp is not available with Zcmp, which uses its encoding".to_owned());
+        }
+        ("addiw" | "subw" | "addw", Xlen::Rv32) => {
+            return Err(format!("c.{} is only available on RV64", mnemonics[0]));
+        }
+        ("slli" | "srli" | "srai", Xlen::Rv32) if operands.len() == 2 => {
+            let shamt = parse_int(operands[1])?;
+            if shamt >= 32 {
+                return Err(format!("shamt must be less than 32 on RV32, got {shamt}"));
+            }
+        }
+        _ => {}
+    }
     match mnemonics[0] {
         "addi4spn" => {
             if operands.len() != 2 {
                 Err("c.addi4spn requires 2 operands".to_owned())
             } else {
                 Ok(CInstruction::ADDI4SPN {
-                    dest: CIRegister::try_from(operands[0])?,
-                    imm: CWideImmediate::try_from(parse_int(operands[1])?)?,
+                    dest: compressed_register("addi4spn", operands[0])?,
+                    imm: compressed_immediate("addi4spn", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -688,11 +2035,11 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             if operands.len() != 2 {
                 Err("c.fld requires 2 operands".to_owned())
             } else {
-                let (base, imm) = parse_address_expression_compressed(operands[1])?;
+                let (base, imm) = parse_address_expression_compressed("fld", operands[1])?;
                 Ok(CInstruction::FLD {
-                    dest: CFRegister::try_from(operands[0])?,
+                    dest: compressed_float_register("fld", operands[0])?,
                     base,
-                    offset: CDImmediate::try_from(imm)?,
+                    offset: compressed_immediate("fld", imm)?,
                 })
             }
         }
@@ -700,11 +2047,11 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             if operands.len() != 2 {
                 Err("c.lw requires 2 operands".to_owned())
             } else {
-                let (base, imm) = parse_address_expression_compressed(operands[1])?;
+                let (base, imm) = parse_address_expression_compressed("lw", operands[1])?;
                 Ok(CInstruction::LW {
-                    dest: CIRegister::try_from(operands[0])?,
+                    dest: compressed_register("lw", operands[0])?,
                     base,
-                    offset: CWImmediate::try_from(imm)?,
+                    offset: compressed_immediate("lw", imm)?,
                 })
             }
         }
@@ -712,11 +2059,11 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             if operands.len() != 2 {
                 Err("c.ld requires 2 operands".to_owned())
             } else {
-                let (base, imm) = parse_address_expression_compressed(operands[1])?;
+                let (base, imm) = parse_address_expression_compressed("ld", operands[1])?;
                 Ok(CInstruction::LD {
-                    dest: CIRegister::try_from(operands[0])?,
+                    dest: compressed_register("ld", operands[0])?,
                     base,
-                    offset: CDImmediate::try_from(imm)?,
+                    offset: compressed_immediate("ld", imm)?,
                 })
             }
         }
@@ -724,11 +2071,11 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             if operands.len() != 2 {
                 Err("c.fsd requires 2 operands".to_owned())
             } else {
-                let (base, imm) = parse_address_expression_compressed(operands[1])?;
+                let (base, imm) = parse_address_expression_compressed("fsd", operands[1])?;
                 Ok(CInstruction::FSD {
-                    src: CFRegister::try_from(operands[0])?,
+                    src: compressed_float_register("fsd", operands[0])?,
                     base,
-                    offset: CDImmediate::try_from(imm)?,
+                    offset: compressed_immediate("fsd", imm)?,
                 })
             }
         }
@@ -736,11 +2083,11 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             if operands.len() != 2 {
                 Err("c.sw requires 2 operands".to_owned())
             } else {
-                let (base, imm) = parse_address_expression_compressed(operands[1])?;
+                let (base, imm) = parse_address_expression_compressed("sw", operands[1])?;
                 Ok(CInstruction::SW {
-                    src: CIRegister::try_from(operands[0])?,
+                    src: compressed_register("sw", operands[0])?,
                     base,
-                    offset: CWImmediate::try_from(imm)?,
+                    offset: compressed_immediate("sw", imm)?,
                 })
             }
         }
@@ -748,11 +2095,35 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             if operands.len() != 2 {
                 Err("c.sd requires 2 operands".to_owned())
             } else {
-                let (base, imm) = parse_address_expression_compressed(operands[1])?;
+                let (base, imm) = parse_address_expression_compressed("sd", operands[1])?;
                 Ok(CInstruction::SD {
-                    src: CIRegister::try_from(operands[0])?,
+                    src: compressed_register("sd", operands[0])?,
                     base,
-                    offset: CDImmediate::try_from(imm)?,
+                    offset: compressed_immediate("sd", imm)?,
+                })
+            }
+        }
+        "flw" => {
+            if operands.len() != 2 {
+                Err("c.flw requires 2 operands".to_owned())
+            } else {
+                let (base, imm) = parse_address_expression_compressed("flw", operands[1])?;
+                Ok(CInstruction::FLW {
+                    dest: compressed_float_register("flw", operands[0])?,
+                    base,
+                    offset: compressed_immediate("flw", imm)?,
+                })
+            }
+        }
+        "fsw" => {
+            if operands.len() != 2 {
+                Err("c.fsw requires 2 operands".to_owned())
+            } else {
+                let (base, imm) = parse_address_expression_compressed("fsw", operands[1])?;
+                Ok(CInstruction::FSW {
+                    src: compressed_float_register("fsw", operands[0])?,
+                    base,
+                    offset: compressed_immediate("fsw", imm)?,
                 })
             }
         }
@@ -766,18 +2137,32 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
                 let i = parse_int(operands[0])?;
 
                 Ok(CInstruction::ADDI16SP {
-                    imm: C16SPImmediate::try_from(i)?,
+                    imm: compressed_immediate("addi16sp", i)?,
                 })
             }
         }
-        "lui" => ci_assemble!(LUI),
+        "lui" => {
+            if operands.len() != 2 {
+                Err("c.lui instruction requires 2 operands".to_owned())
+            } else {
+                let imm: CLUIImmediate = compressed_immediate("lui", parse_int(operands[1])?)?;
+                if imm.val() == 0 {
+                    Err("c.lui immediate must not be zero (use lui instead)".to_owned())
+                } else {
+                    Ok(CInstruction::LUI {
+                        dest: IRegister::from_string(operands[0])?,
+                        imm,
+                    })
+                }
+            }
+        }
         "srli" => {
             if operands.len() != 2 {
                 Err("c.srli requires 2 operands".to_owned())
             } else {
                 Ok(CInstruction::SRLI {
-                    dest: CIRegister::try_from(operands[0])?,
-                    shamt: CShamt::try_from(parse_int(operands[1])?)?,
+                    dest: compressed_register("srli", operands[0])?,
+                    shamt: compressed_immediate("srli", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -786,8 +2171,8 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
                 Err("c.srai requires 2 operands".to_owned())
             } else {
                 Ok(CInstruction::SRAI {
-                    dest: CIRegister::try_from(operands[0])?,
-                    shamt: CShamt::try_from(parse_int(operands[1])?)?,
+                    dest: compressed_register("srai", operands[0])?,
+                    shamt: compressed_immediate("srai", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -796,8 +2181,8 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
                 Err("c.andi requires 2 operands".to_owned())
             } else {
                 Ok(CInstruction::ANDI {
-                    dest: CIRegister::try_from(operands[0])?,
-                    imm: CIImmediate::try_from(parse_int(operands[1])?)?,
+                    dest: compressed_register("andi", operands[0])?,
+                    imm: compressed_immediate("andi", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -812,7 +2197,16 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
                 Err("c.j requires 1 operand".to_owned())
             } else {
                 Ok(CInstruction::J {
-                    offset: CJImmediate::try_from(parse_int(operands[0])?)?,
+                    offset: compressed_immediate("j", parse_int(operands[0])?)?,
+                })
+            }
+        }
+        "jal" => {
+            if operands.len() != 1 {
+                Err("c.jal requires 1 operand".to_owned())
+            } else {
+                Ok(CInstruction::JAL {
+                    offset: compressed_immediate("jal", parse_int(operands[0])?)?,
                 })
             }
         }
@@ -821,8 +2215,8 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
                 Err("c.beqz requires 2 operands".to_owned())
             } else {
                 Ok(CInstruction::BEQZ {
-                    src: CIRegister::try_from(operands[0])?,
-                    offset: CBImmediate::try_from(parse_int(operands[1])?)?,
+                    src: compressed_register("beqz", operands[0])?,
+                    offset: compressed_immediate("beqz", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -831,8 +2225,8 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
                 Err("c.bne requires 2 operands".to_owned())
             } else {
                 Ok(CInstruction::BNEZ {
-                    src: CIRegister::try_from(operands[0])?,
-                    offset: CBImmediate::try_from(parse_int(operands[1])?)?,
+                    src: compressed_register("bnez", operands[0])?,
+                    offset: compressed_immediate("bnez", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -842,7 +2236,7 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             } else {
                 Ok(CInstruction::SLLI {
                     dest: IRegister::from_string(operands[0])?,
-                    shamt: CShamt::try_from(parse_int(operands[1])?)?,
+                    shamt: compressed_immediate("slli", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -852,7 +2246,7 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             } else {
                 Ok(CInstruction::FLDSP {
                     dest: FRegister::try_from(operands[0])?,
-                    offset: CDSPImmediate::try_from(parse_int(operands[1])?)?,
+                    offset: compressed_immediate("fldsp", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -862,7 +2256,7 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             } else {
                 Ok(CInstruction::LDSP {
                     dest: IRegister::from_string(operands[0])?,
-                    offset: CDSPImmediate::try_from(parse_int(operands[1])?)?,
+                    offset: compressed_immediate("ldsp", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -872,7 +2266,17 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             } else {
                 Ok(CInstruction::LWSP {
                     dest: IRegister::from_string(operands[0])?,
-                    offset: CWSPImmediate::try_from(parse_int(operands[1])?)?,
+                    offset: compressed_immediate("lwsp", parse_int(operands[1])?)?,
+                })
+            }
+        }
+        "flwsp" => {
+            if operands.len() != 2 {
+                Err("c.flwsp requires 2 operands".to_owned())
+            } else {
+                Ok(CInstruction::FLWSP {
+                    dest: FRegister::try_from(operands[0])?,
+                    offset: compressed_immediate("flwsp", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -895,12 +2299,50 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             }
         }
         "ebreak" => {
-            if operands.len() != 0 {
-                Err("c.jr requires 0 operands".to_owned())
+            if !operands.is_empty() {
+                Err("c.ebreak requires 0 operands".to_owned())
             } else {
                 Ok(CInstruction::EBREAK)
             }
         }
+        "nop" => {
+            if !operands.is_empty() {
+                Err("c.nop requires 0 operands".to_owned())
+            } else {
+                Ok(CInstruction::NOP)
+            }
+        }
+        "ntl" => {
+            if !operands.is_empty() {
+                Err(format!("c.{} requires 0 operands", mnemonics.join(".")))
+            } else {
+                match mnemonics.get(1) {
+                    Some(&"p1") => Ok(CInstruction::NTLP1),
+                    Some(&"pall") => Ok(CInstruction::NTLPALL),
+                    Some(&"s1") => Ok(CInstruction::NTLS1),
+                    Some(&"all") => Ok(CInstruction::NTLALL),
+                    _ => Err(format!(
+                        "unknown non-temporal locality hint c.{}",
+                        mnemonics.join(".")
+                    )),
+                }
+            }
+        }
+        // the only operands are the link register that the encoding implies
+        "sspush" | "sspopchk" => {
+            let (link, instruction) = if mnemonics[0] == "sspush" {
+                (IRegister::ReturnAddress, CInstruction::SSPUSH)
+            } else {
+                (IRegister::T0, CInstruction::SSPOPCHK)
+            };
+            if operands.len() != 1 {
+                Err(format!("c.{} requires 1 operand", mnemonics[0]))
+            } else if IRegister::from_string(operands[0])? != link {
+                Err(format!("c.{} only takes {link}", mnemonics[0]))
+            } else {
+                Ok(instruction)
+            }
+        }
         "add" => {
             if operands.len() != 2 {
                 Err("c.add requires 2 operands".to_owned())
@@ -917,7 +2359,7 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             } else {
                 Ok(CInstruction::FSDSP {
                     src: FRegister::try_from(operands[0])?,
-                    offset: CSDSPImmediate::try_from(parse_int(operands[1])?)?,
+                    offset: compressed_immediate("fsdsp", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -927,7 +2369,7 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             } else {
                 Ok(CInstruction::SWSP {
                     src: IRegister::from_string(operands[0])?,
-                    offset: CSWSPImmediate::try_from(parse_int(operands[1])?)?,
+                    offset: compressed_immediate("swsp", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -937,7 +2379,17 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
             } else {
                 Ok(CInstruction::SDSP {
                     src: IRegister::from_string(operands[0])?,
-                    offset: CSDSPImmediate::try_from(parse_int(operands[1])?)?,
+                    offset: compressed_immediate("sdsp", parse_int(operands[1])?)?,
+                })
+            }
+        }
+        "fswsp" => {
+            if operands.len() != 2 {
+                Err("c.fswsp requires 2 operands".to_owned())
+            } else {
+                Ok(CInstruction::FSWSP {
+                    src: FRegister::try_from(operands[0])?,
+                    offset: compressed_immediate("fswsp", parse_int(operands[1])?)?,
                 })
             }
         }
@@ -951,9 +2403,63 @@ fn compressed_assemble(mnemonics: &[&str], operands: Vec<&str>) -> Result<CInstr
                 })
             }
         }
-        _ => Err(format!(
-            "unknown compressed instruction mnemonic: {}",
-            mnemonics[0]
+        _ => Err(unknown_mnemonic(
+            "unknown compressed instruction mnemonic",
+            &format!("c.{}", mnemonics.join(".")),
         )),
     }
 }
+
+fn zcmp_assemble(
+    mnemonic: &str,
+    operands: Vec<&str>,
+    config: &AssemblerConfig,
+) -> Result<CInstruction, String> {
+    match mnemonic {
+        "cm.push" | "cm.pop" | "cm.popretz" | "cm.popret" => {
+            if operands.len() != 2 {
+                return Err(format!("{mnemonic} requires 2 operands"));
+            }
+            let rlist = RegisterList::try_from(operands[0])?;
+            let value = parse_int(operands[1])?;
+            // push moves the stack pointer down, which is written as a negative adjustment
+            let value = match mnemonic {
+                "cm.push" if value > 0 => {
+                    return Err(format!(
+                        "the stack adjustment of cm.push must be negative, got {value}"
+                    ));
+                }
+                "cm.push" => -value,
+                _ => value,
+            };
+            let stack_adj = StackAdjustment::new(rlist, value, config.xlen)?;
+            Ok(match mnemonic {
+                "cm.push" => CInstruction::CMPUSH { rlist, stack_adj },
+                "cm.pop" => CInstruction::CMPOP { rlist, stack_adj },
+                "cm.popretz" => CInstruction::CMPOPRETZ { rlist, stack_adj },
+                _ => CInstruction::CMPOPRET { rlist, stack_adj },
+            })
+        }
+        "cm.mvsa01" | "cm.mva01s" => {
+            if operands.len() != 2 {
+                return Err(format!("{mnemonic} requires 2 operands"));
+            }
+            let r1s = CSRegister::try_from(operands[0])?;
+            let r2s = CSRegister::try_from(operands[1])?;
+            if mnemonic == "cm.mva01s" {
+                Ok(CInstruction::CMMVA01S {
+                    src1: r1s,
+                    src2: r2s,
+                })
+            } else if r1s == r2s {
+                Err("cm.mvsa01 must move into two different registers".to_owned())
+            } else {
+                Ok(CInstruction::CMMVSA01 {
+                    dest1: r1s,
+                    dest2: r2s,
+                })
+            }
+        }
+        _ => Err(unknown_mnemonic("unknown mnemonic", mnemonic)),
+    }
+}
@@ -1,16 +1,19 @@
 use std::fmt::{Display, Formatter};
 
 use crate::{
+    config::{DecoderConfig, Xlen},
+    error::DecodeError,
     immediates::{
         BImmediate, C16SPImmediate, CBImmediate, CDImmediate, CDSPImmediate, CIImmediate,
-        CJImmediate, CSDSPImmediate, CSWSPImmediate, CShamt, CWImmediate, CWSPImmediate,
-        CWideImmediate, IImmediate, JImmediate, SImmediate, Shamt,
+        CJImmediate, CLUIImmediate, CSDSPImmediate, CSWSPImmediate, CShamt, CWImmediate,
+        CWSPImmediate, CWideImmediate, IImmediate, JImmediate, SImmediate, Shamt, StackAdjustment,
+        UImmediate,
     },
     instruction::Instruction,
-    register::{CFRegister, CIRegister, FRegister, IRegister},
+    register::{CFRegister, CIRegister, CSRegister, FRegister, IRegister, RegisterList},
 };
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub enum CInstruction {
     //
     // Instructions in C extension
@@ -34,6 +37,12 @@ pub enum CInstruction {
         base: CIRegister,
         offset: CDImmediate,
     },
+    /// RV32 only, uses the encoding of c.ld
+    FLW {
+        dest: CFRegister,
+        base: CIRegister,
+        offset: CWImmediate,
+    },
     FSD {
         src: CFRegister,
         base: CIRegister,
@@ -49,6 +58,14 @@ pub enum CInstruction {
         base: CIRegister,
         offset: CDImmediate,
     },
+    /// RV32 only, uses the encoding of c.sd
+    FSW {
+        src: CFRegister,
+        base: CIRegister,
+        offset: CWImmediate,
+    },
+    /// The canonical no-op, encoded as c.addi x0,0
+    NOP,
     ADDI {
         dest: IRegister,
         imm: CIImmediate,
@@ -57,6 +74,10 @@ pub enum CInstruction {
         dest: IRegister,
         imm: CIImmediate,
     },
+    /// RV32 only, uses the encoding of c.addiw
+    JAL {
+        offset: CJImmediate,
+    },
     LI {
         dest: IRegister,
         imm: CIImmediate,
@@ -66,7 +87,7 @@ pub enum CInstruction {
     },
     LUI {
         dest: IRegister,
-        imm: CIImmediate,
+        imm: CLUIImmediate,
     },
     SRLI {
         dest: CIRegister,
@@ -131,6 +152,11 @@ pub enum CInstruction {
         dest: IRegister,
         offset: CDSPImmediate,
     },
+    /// RV32 only, uses the encoding of c.ldsp
+    FLWSP {
+        dest: FRegister,
+        offset: CWSPImmediate,
+    },
     JR {
         src: IRegister,
     },
@@ -146,6 +172,14 @@ pub enum CInstruction {
         dest: IRegister,
         src: IRegister,
     },
+    /// Zihintntl, uses the encodings of c.add to x0 from x2 to x5
+    NTLP1,
+    NTLPALL,
+    NTLS1,
+    NTLALL,
+    /// Zicfiss, c.sspush ra and c.sspopchk t0, which use the reserved encodings of c.lui with a zero immediate
+    SSPUSH,
+    SSPOPCHK,
     FSDSP {
         src: FRegister,
         offset: CSDSPImmediate,
@@ -158,6 +192,44 @@ pub enum CInstruction {
         src: IRegister,
         offset: CSDSPImmediate,
     },
+    /// RV32 only, uses the encoding of c.sdsp
+    FSWSP {
+        src: FRegister,
+        offset: CSWSPImmediate,
+    },
+    //
+    // Instructions in Zcmp extension, which use the encodings of c.fsdsp
+    //
+    /// Stores the registers below the stack pointer and then moves it down
+    CMPUSH {
+        rlist: RegisterList,
+        stack_adj: StackAdjustment,
+    },
+    /// Moves the stack pointer up and then loads the registers below it
+    CMPOP {
+        rlist: RegisterList,
+        stack_adj: StackAdjustment,
+    },
+    /// cm.pop followed by setting a0 to zero and returning
+    CMPOPRETZ {
+        rlist: RegisterList,
+        stack_adj: StackAdjustment,
+    },
+    /// cm.pop followed by returning
+    CMPOPRET {
+        rlist: RegisterList,
+        stack_adj: StackAdjustment,
+    },
+    /// Moves a0 and a1 into two different saved registers
+    CMMVSA01 {
+        dest1: CSRegister,
+        dest2: CSRegister,
+    },
+    /// Moves two saved registers into a0 and a1
+    CMMVA01S {
+        src1: CSRegister,
+        src2: CSRegister,
+    },
 }
 
 impl Display for CInstruction {
@@ -206,19 +278,106 @@ impl Display for CInstruction {
             CInstruction::JR { src } => write!(f, "c.jr {src}"),
             CInstruction::MV { dest, src } => write!(f, "c.mv {dest},{src}"),
             CInstruction::EBREAK => write!(f, "c.ebreak"),
+            CInstruction::NOP => write!(f, "c.nop"),
             CInstruction::JALR { src } => write!(f, "c.jalr {src}"),
             CInstruction::ADD { dest: rd, src: rs2 } => write!(f, "c.add {rd},{rs2}"),
+            CInstruction::NTLP1 => write!(f, "c.ntl.p1"),
+            CInstruction::NTLPALL => write!(f, "c.ntl.pall"),
+            CInstruction::NTLS1 => write!(f, "c.ntl.s1"),
+            CInstruction::NTLALL => write!(f, "c.ntl.all"),
+            CInstruction::SSPUSH => write!(f, "c.sspush ra"),
+            CInstruction::SSPOPCHK => write!(f, "c.sspopchk t0"),
             CInstruction::FSDSP { src, offset } => write!(f, "c.fsdsp {src},{offset}"),
             CInstruction::SWSP { src, offset } => write!(f, "c.swsp {src},{offset}"),
             CInstruction::SDSP { src, offset } => write!(f, "c.sdsp {src},{offset}"),
+            CInstruction::FLW { dest, base, offset } => write!(f, "c.flw {dest},{offset}({base})"),
+            CInstruction::FSW { src, base, offset } => write!(f, "c.fsw {src},{offset}({base})"),
+            CInstruction::JAL { offset } => write!(f, "c.jal {offset}"),
+            CInstruction::FLWSP { dest, offset } => write!(f, "c.flwsp {dest},{offset}"),
+            CInstruction::FSWSP { src, offset } => write!(f, "c.fswsp {src},{offset}"),
+            CInstruction::CMPUSH { rlist, stack_adj } => write!(f, "cm.push {rlist},-{stack_adj}"),
+            CInstruction::CMPOP { rlist, stack_adj } => write!(f, "cm.pop {rlist},{stack_adj}"),
+            CInstruction::CMPOPRETZ { rlist, stack_adj } => {
+                write!(f, "cm.popretz {rlist},{stack_adj}")
+            }
+            CInstruction::CMPOPRET { rlist, stack_adj } => {
+                write!(f, "cm.popret {rlist},{stack_adj}")
+            }
+            CInstruction::CMMVSA01 { dest1, dest2 } => write!(f, "cm.mvsa01 {dest1},{dest2}"),
+            CInstruction::CMMVA01S { src1, src2 } => write!(f, "cm.mva01s {src1},{src2}"),
         }
     }
 }
 
 impl CInstruction {
     /// Decodes a u16 into a `CInstruction`.
-    pub fn decode(instruction: u16) -> Result<Self, String> {
-        let crs2 = CIRegister::try_from((instruction >> 2) & 0b111).unwrap();
+    pub fn decode(instruction: u16) -> Result<Self, DecodeError> {
+        CInstruction::decode_with(instruction, &DecoderConfig::default())
+    }
+
+    /// Decodes the little endian bytes a `CInstruction` is stored as in memory.
+    pub fn from_le_bytes(bytes: [u8; 2], config: &DecoderConfig) -> Result<Self, DecodeError> {
+        CInstruction::decode_with(u16::from_le_bytes(bytes), config)
+    }
+
+    /// Decodes a u16 into a `CInstruction` using the given configuration.
+    ///
+    /// On RV32 the encodings of c.ld, c.sd, c.addiw, c.ldsp and c.sdsp are decoded as c.flw, c.fsw, c.jal, c.flwsp
+    /// and c.fswsp, except that c.ld, c.sd, c.ldsp and c.sdsp are kept with Zclsd, where they access register pairs.
+    /// With Zcmp the encodings of c.fsdsp are decoded as the push and pop instructions. On RV128 the encodings of c.fld,
+    /// c.fsd, c.fldsp and c.fsdsp are the quad loads and stores, which are rejected. Instructions of extensions that are
+    /// not enabled in the configuration are rejected, as are the ones using x16-x31 with RVE.
+    pub fn decode_with(instruction: u16, config: &DecoderConfig) -> Result<Self, DecodeError> {
+        let decoded = CInstruction::decode_fields(instruction, config)
+            .map_err(|message| DecodeError::compressed(instruction, message))?;
+        if config.extensions.e
+            && let Some(register) = decoded.rve_register()
+        {
+            return Err(DecodeError::compressed(
+                instruction,
+                format!(
+                    "unsupported instruction {decoded}, which uses {register} but RVE only has x0-x15"
+                ),
+            ));
+        }
+        match config
+            .extensions
+            .missing_compressed(instruction, config.xlen)
+        {
+            Some(extension) => Err(DecodeError::compressed(
+                instruction,
+                format!(
+                    "unsupported instruction {decoded}, which requires the {extension} extension"
+                ),
+            )),
+            None => Ok(decoded),
+        }
+    }
+
+    /// Decodes a u16 and expands it into the equivalent `Instruction`, see `try_expand`.
+    pub fn decode_expanded_with(
+        instruction: u16,
+        config: &DecoderConfig,
+    ) -> Result<Instruction, DecodeError> {
+        CInstruction::decode_with(instruction, config)?
+            .try_expand()
+            .map_err(|message| DecodeError::compressed(instruction, message))
+    }
+
+    fn decode_fields(instruction: u16, config: &DecoderConfig) -> Result<Self, String> {
+        let rv32 = config.xlen == Xlen::Rv32;
+        // the float loads and stores on RV32 without Zclsd, which uses their encodings for register pairs
+        let rv32f = rv32 && !config.extensions.zclsd;
+        let pair = |register: IRegister, mnemonic: &str| {
+            if rv32 && register.number() % 2 == 1 {
+                Err(format!(
+                    "{mnemonic} on RV32 accesses a register pair and must use an even register, got {register}"
+                ))
+            } else {
+                Ok(())
+            }
+        };
+        let crs2 = CIRegister::from((instruction >> 2) & 0b111);
         let cfrd = CFRegister::try_from((instruction >> 2) & 0b111).unwrap();
 
         let crs1 = CIRegister::from((instruction >> 7) & 0b111);
@@ -232,12 +391,25 @@ impl CInstruction {
         let rs2 = IRegister::from_int(((instruction >> 2) & 0b1_1111) as u32);
         let frs2 = FRegister::try_from(((instruction >> 2) & 0b1_1111) as u32).unwrap();
 
+        // c.lq, c.sq, c.lqsp and c.sqsp, which are not supported
+        #[cfg(feature = "rv128")]
+        if config.xlen == Xlen::Rv128
+            && instruction & 0b11 != 0b01
+            && matches!(instruction >> 13, 0b001 | 0b101)
+            && !(instruction & 0b11 == 0b10 && instruction >> 13 == 0b101 && config.extensions.zcmp)
+        {
+            return Err(
+                "the compressed quad loads and stores of RV128 are not supported".to_owned(),
+            );
+        }
         match instruction & 0b11 {
             0b00 => match instruction >> 13 {
                 0b000 => {
                     let imm = CWideImmediate::from_u16(instruction);
-                    if imm.val() == 0 {
-                        Err("compressed illegal instruction".to_owned())
+                    if instruction == 0 {
+                        Err("the all zero halfword is an illegal instruction".to_owned())
+                    } else if imm.val() == 0 {
+                        Err("c.addi4spn immediate must not be zero".to_owned())
                     } else {
                         Ok(CInstruction::ADDI4SPN { dest: crs2, imm })
                     }
@@ -252,7 +424,12 @@ impl CInstruction {
                     base: crs1,
                     offset: CWImmediate::from_u16(instruction),
                 }),
-                0b011 => Ok(CInstruction::LD {
+                0b011 if rv32f => Ok(CInstruction::FLW {
+                    dest: cfrd,
+                    base: crs1,
+                    offset: CWImmediate::from_u16(instruction),
+                }),
+                0b011 => pair(crs2.expand(), "c.ld").map(|_| CInstruction::LD {
                     dest: crs2,
                     base: crs1,
                     offset: CDImmediate::from_u16(instruction),
@@ -268,7 +445,12 @@ impl CInstruction {
                     base: crs1,
                     offset: CWImmediate::from_u16(instruction),
                 }),
-                0b111 => Ok(CInstruction::SD {
+                0b111 if rv32f => Ok(CInstruction::FSW {
+                    src: cfrd,
+                    base: crs1,
+                    offset: CWImmediate::from_u16(instruction),
+                }),
+                0b111 => pair(crs2.expand(), "c.sd").map(|_| CInstruction::SD {
                     src: crs2,
                     base: crs1,
                     offset: CDImmediate::from_u16(instruction),
@@ -276,10 +458,15 @@ impl CInstruction {
                 _ => unreachable!(),
             },
             0b01 => match instruction >> 13 {
+                0b000 if rd == IRegister::Zero && ciimmediate.val() == 0 => Ok(CInstruction::NOP),
                 0b000 => Ok(CInstruction::ADDI {
                     dest: rd,
                     imm: ciimmediate,
                 }),
+                0b001 if rv32 => Ok(CInstruction::JAL {
+                    offset: CJImmediate::from_u16(instruction),
+                }),
+                0b001 if rd == IRegister::Zero => Err("c.addiw must not write to x0".to_owned()),
                 0b001 => Ok(CInstruction::ADDIW {
                     dest: rd,
                     imm: ciimmediate,
@@ -289,17 +476,34 @@ impl CInstruction {
                     imm: ciimmediate,
                 }),
                 0b011 => {
-                    if (instruction >> 7) & 0b111 == 2 {
-                        Ok(CInstruction::ADDI16SP {
-                            imm: C16SPImmediate::from_u16(instruction),
-                        })
+                    if (instruction >> 7) & 0b1_1111 == 2 {
+                        let imm = C16SPImmediate::from_u16(instruction);
+                        if imm.val() == 0 {
+                            Err("c.addi16sp immediate must not be zero".to_owned())
+                        } else {
+                            Ok(CInstruction::ADDI16SP { imm })
+                        }
                     } else {
-                        Ok(CInstruction::LUI {
-                            dest: rd,
-                            imm: ciimmediate,
-                        })
+                        let imm = CLUIImmediate::from_u16(instruction);
+                        // the shadow stack instructions are c.lui to ra and t0 with a zero immediate
+                        if imm.val() == 0
+                            && config.extensions.zicfiss
+                            && rd == IRegister::ReturnAddress
+                        {
+                            Ok(CInstruction::SSPUSH)
+                        } else if imm.val() == 0 && config.extensions.zicfiss && rd == IRegister::T0
+                        {
+                            Ok(CInstruction::SSPOPCHK)
+                        } else if imm.val() == 0 {
+                            Err("c.lui immediate must not be zero".to_owned())
+                        } else {
+                            Ok(CInstruction::LUI { dest: rd, imm })
+                        }
                     }
                 }
+                0b100 if rv32 && (instruction >> 10) & 0b10 == 0 && cshamt.val() >= 32 => {
+                    Err(format!("shamt must be less than 32 on RV32, got {cshamt}"))
+                }
                 0b100 => match (instruction >> 10) & 0b11 {
                     0b00 => Ok(CInstruction::SRLI {
                         dest: crs1,
@@ -330,6 +534,9 @@ impl CInstruction {
                             dest: crs1,
                             src: crs2,
                         }),
+                        (_, 0b1) if rv32 => {
+                            Err("c.subw and c.addw are only available on RV64".to_owned())
+                        }
                         (0b00, 0b1) => Ok(CInstruction::SUBW {
                             dest: crs1,
                             src: crs2,
@@ -356,6 +563,9 @@ impl CInstruction {
                 _ => unreachable!(),
             },
             0b10 => match instruction >> 13 {
+                0b000 if rv32 && cshamt.val() >= 32 => {
+                    Err(format!("shamt must be less than 32 on RV32, got {cshamt}"))
+                }
                 0b000 => Ok(CInstruction::SLLI {
                     dest: rd,
                     shamt: cshamt,
@@ -364,11 +574,20 @@ impl CInstruction {
                     dest: frd,
                     offset: CDSPImmediate::from_u16(instruction),
                 }),
+                0b010 | 0b011
+                    if rd == IRegister::Zero && !(rv32f && instruction >> 13 == 0b011) =>
+                {
+                    Err("c.lwsp and c.ldsp must not load into x0".to_owned())
+                }
                 0b010 => Ok(CInstruction::LWSP {
                     dest: rd,
                     offset: CWSPImmediate::from_u16(instruction),
                 }),
-                0b011 => Ok(CInstruction::LDSP {
+                0b011 if rv32f => Ok(CInstruction::FLWSP {
+                    dest: frd,
+                    offset: CWSPImmediate::from_u16(instruction),
+                }),
+                0b011 => pair(rd, "c.ldsp").map(|_| CInstruction::LDSP {
                     dest: rd,
                     offset: CDSPImmediate::from_u16(instruction),
                 }),
@@ -378,14 +597,23 @@ impl CInstruction {
                         (instruction >> 7) & 0b1_1111,
                         (instruction >> 2) & 0b1_1111,
                     ) {
+                        (0, 0, 0) => Err("c.jr must not jump to x0".to_owned()),
                         (0, _, 0) => Ok(CInstruction::JR { src: rd }),
                         (0, _, _) => Ok(CInstruction::MV { dest: rd, src: rs2 }),
                         (1, 0, 0) => Ok(CInstruction::EBREAK),
                         (1, _, 0) => Ok(CInstruction::JALR { src: rd }),
+                        // the ntl hints are c.add to x0 without Zihintntl
+                        (1, 0, 2) if config.extensions.zihintntl => Ok(CInstruction::NTLP1),
+                        (1, 0, 3) if config.extensions.zihintntl => Ok(CInstruction::NTLPALL),
+                        (1, 0, 4) if config.extensions.zihintntl => Ok(CInstruction::NTLS1),
+                        (1, 0, 5) if config.extensions.zihintntl => Ok(CInstruction::NTLALL),
                         (1, _, _) => Ok(CInstruction::ADD { dest: rd, src: rs2 }),
                         _ => unreachable!(),
                     }
                 }
+                0b101 if config.extensions.zcmp => {
+                    CInstruction::decode_zcmp(instruction, config.xlen)
+                }
                 0b101 => Ok(CInstruction::FSDSP {
                     src: frs2,
                     offset: CSDSPImmediate::from_u16(instruction),
@@ -394,7 +622,11 @@ impl CInstruction {
                     src: rs2,
                     offset: CSWSPImmediate::from_u16(instruction),
                 }),
-                0b111 => Ok(CInstruction::SDSP {
+                0b111 if rv32f => Ok(CInstruction::FSWSP {
+                    src: frs2,
+                    offset: CSWSPImmediate::from_u16(instruction),
+                }),
+                0b111 => pair(rs2, "c.sdsp").map(|_| CInstruction::SDSP {
                     src: rs2,
                     offset: CSDSPImmediate::from_u16(instruction),
                 }),
@@ -407,6 +639,65 @@ impl CInstruction {
         }
     }
 
+    /// Decodes the push, pop and move instructions of Zcmp, which use the encodings of c.fsdsp.
+    fn decode_zcmp(instruction: u16, xlen: Xlen) -> Result<Self, String> {
+        let stack = || {
+            let rlist = RegisterList::try_from((instruction >> 4) & 0b1111)?;
+            let stack_adj = StackAdjustment::from_spimm(rlist, (instruction >> 2) & 0b11, xlen);
+            Ok::<_, String>((rlist, stack_adj))
+        };
+        let r1s = CSRegister::from((instruction >> 7) & 0b111);
+        let r2s = CSRegister::from((instruction >> 2) & 0b111);
+        match ((instruction >> 8) & 0b1_1111, (instruction >> 5) & 0b11) {
+            (0b11000, _) => {
+                stack().map(|(rlist, stack_adj)| CInstruction::CMPUSH { rlist, stack_adj })
+            }
+            (0b11010, _) => {
+                stack().map(|(rlist, stack_adj)| CInstruction::CMPOP { rlist, stack_adj })
+            }
+            (0b11100, _) => {
+                stack().map(|(rlist, stack_adj)| CInstruction::CMPOPRETZ { rlist, stack_adj })
+            }
+            (0b11110, _) => {
+                stack().map(|(rlist, stack_adj)| CInstruction::CMPOPRET { rlist, stack_adj })
+            }
+            (0b01100..=0b01111, 0b01) if r1s == r2s => {
+                Err("cm.mvsa01 must move into two different registers".to_owned())
+            }
+            (0b01100..=0b01111, 0b01) => Ok(CInstruction::CMMVSA01 {
+                dest1: r1s,
+                dest2: r2s,
+            }),
+            (0b01100..=0b01111, 0b11) => Ok(CInstruction::CMMVA01S {
+                src1: r1s,
+                src2: r2s,
+            }),
+            _ => Err("reserved Zcmp instruction".to_owned()),
+        }
+    }
+
+    /// Returns true if this instruction is one of the encodings reserved for HINTs.
+    ///
+    /// HINTs do not change any architectural state, but future extensions may use them to convey information to the
+    /// hardware (such as c.nop with a nonzero immediate). Reserved encodings which are not HINTs are instead rejected by
+    /// the decoder.
+    pub fn is_hint(&self) -> bool {
+        match self {
+            CInstruction::ADDI { dest, imm } => *dest == IRegister::Zero || imm.val() == 0,
+            CInstruction::LI { dest, .. }
+            | CInstruction::LUI { dest, .. }
+            | CInstruction::MV { dest, .. }
+            | CInstruction::ADD { dest, .. } => *dest == IRegister::Zero,
+            CInstruction::NTLP1
+            | CInstruction::NTLPALL
+            | CInstruction::NTLS1
+            | CInstruction::NTLALL => true,
+            CInstruction::SLLI { dest, shamt } => *dest == IRegister::Zero || shamt.val() == 0,
+            CInstruction::SRLI { shamt, .. } | CInstruction::SRAI { shamt, .. } => shamt.val() == 0,
+            _ => false,
+        }
+    }
+
     pub fn disassemble(instruction: &CInstruction) -> String {
         format!("{}", instruction)
     }
@@ -417,6 +708,9 @@ impl CInstruction {
     /// > Strictly speaking, C.JALR does not expand exactly to a base RVI instruction as the value added to the PC to
     /// > form the link address is 2 rather than 4 as in the base ISA, but supporting both offsets of 2 and 4 bytes
     /// > is only a very minor change to the base microarchitecture.
+    ///
+    /// Panics for c.fld, c.fsd, c.fldsp and c.fsdsp, which need the D extension, and for the Zcmp instructions, which
+    /// expand to sequences of instructions, use `try_expand` to get an error instead.
     pub fn expand(&self) -> Instruction {
         match self {
             CInstruction::ADDI4SPN { dest, imm } => Instruction::ADDI {
@@ -424,7 +718,7 @@ impl CInstruction {
                 src: IRegister::StackPointer,
                 imm: IImmediate::try_from(imm.val()).unwrap(),
             },
-            CInstruction::FLD { .. } => todo!(), // needs unimplemented double extension
+            CInstruction::FLD { .. } => panic!("{self} cannot be expanded without the D extension"),
             CInstruction::LW { dest, base, offset } => Instruction::LW {
                 dest: dest.expand(),
                 base: base.expand(),
@@ -435,7 +729,7 @@ impl CInstruction {
                 base: base.expand(),
                 offset: IImmediate::try_from(offset.val()).unwrap(),
             },
-            CInstruction::FSD { .. } => todo!(), // needs unimplemented double extension
+            CInstruction::FSD { .. } => panic!("{self} cannot be expanded without the D extension"),
             CInstruction::SW { src, base, offset } => Instruction::SW {
                 src: src.expand(),
                 base: base.expand(),
@@ -446,6 +740,11 @@ impl CInstruction {
                 base: base.expand(),
                 offset: SImmediate::try_from(offset.val()).unwrap(),
             },
+            CInstruction::NOP => Instruction::ADDI {
+                dest: IRegister::Zero,
+                src: IRegister::Zero,
+                imm: IImmediate::try_from(0).unwrap(),
+            },
             CInstruction::ADDI { dest, imm } => Instruction::ADDI {
                 dest: *dest,
                 src: *dest,
@@ -466,10 +765,9 @@ impl CInstruction {
                 src: IRegister::StackPointer,
                 imm: IImmediate::try_from(imm.val()).unwrap(),
             },
-            CInstruction::LUI { dest, imm } => Instruction::ADDI {
+            CInstruction::LUI { dest, imm } => Instruction::LUI {
                 dest: *dest,
-                src: IRegister::Zero,
-                imm: IImmediate::try_from(imm.val()).unwrap(),
+                imm: UImmediate::try_from(imm.val()).unwrap(),
             },
             CInstruction::SRLI { dest, shamt } => Instruction::SRLI {
                 dest: dest.expand(),
@@ -535,7 +833,9 @@ impl CInstruction {
                 src: *dest,
                 shamt: Shamt::try_from(shamt.val()).unwrap(),
             },
-            CInstruction::FLDSP { .. } => todo!(), // needs unimplemented double extension
+            CInstruction::FLDSP { .. } => {
+                panic!("{self} cannot be expanded without the D extension")
+            }
             CInstruction::LWSP { dest, offset } => Instruction::LW {
                 dest: *dest,
                 base: IRegister::StackPointer,
@@ -568,7 +868,17 @@ impl CInstruction {
                 src1: *dest,
                 src2: *src,
             },
-            CInstruction::FSDSP { .. } => todo!(), // needs unimplemented double extension
+            CInstruction::NTLP1 => Instruction::NTLP1,
+            CInstruction::NTLPALL => Instruction::NTLPALL,
+            CInstruction::NTLS1 => Instruction::NTLS1,
+            CInstruction::NTLALL => Instruction::NTLALL,
+            CInstruction::SSPUSH => Instruction::SSPUSH {
+                src: IRegister::ReturnAddress,
+            },
+            CInstruction::SSPOPCHK => Instruction::SSPOPCHK { src: IRegister::T0 },
+            CInstruction::FSDSP { .. } => {
+                panic!("{self} cannot be expanded without the D extension")
+            }
             CInstruction::SWSP { src, offset } => Instruction::SW {
                 src: *src,
                 base: IRegister::StackPointer,
@@ -579,10 +889,322 @@ impl CInstruction {
                 base: IRegister::StackPointer,
                 offset: SImmediate::try_from(offset.val()).unwrap(),
             },
+            CInstruction::FLW { dest, base, offset } => Instruction::FLW {
+                dest: dest.expand(),
+                base: base.expand(),
+                offset: IImmediate::try_from(offset.val()).unwrap(),
+            },
+            CInstruction::FSW { src, base, offset } => Instruction::FSW {
+                src: src.expand(),
+                base: base.expand(),
+                offset: SImmediate::try_from(offset.val()).unwrap(),
+            },
+            CInstruction::JAL { offset } => Instruction::JAL {
+                dest: IRegister::ReturnAddress,
+                offset: JImmediate::try_from(offset.val()).unwrap(),
+            },
+            CInstruction::FLWSP { dest, offset } => Instruction::FLW {
+                dest: *dest,
+                base: IRegister::StackPointer,
+                offset: IImmediate::try_from(offset.val()).unwrap(),
+            },
+            CInstruction::FSWSP { src, offset } => Instruction::FSW {
+                src: *src,
+                base: IRegister::StackPointer,
+                offset: SImmediate::try_from(offset.val()).unwrap(),
+            },
+            CInstruction::CMPUSH { .. }
+            | CInstruction::CMPOP { .. }
+            | CInstruction::CMPOPRETZ { .. }
+            | CInstruction::CMPOPRET { .. }
+            | CInstruction::CMMVSA01 { .. }
+            | CInstruction::CMMVA01S { .. } => {
+                panic!("{self} expands to a sequence of instructions")
+            }
+        }
+    }
+
+    /// Returns the first integer register of the instruction that RVE does not have (x16-x31), such as s2 in the
+    /// register list of a push.
+    pub fn rve_register(&self) -> Option<IRegister> {
+        match self {
+            CInstruction::CMPUSH { rlist, .. }
+            | CInstruction::CMPOP { rlist, .. }
+            | CInstruction::CMPOPRETZ { rlist, .. }
+            | CInstruction::CMPOPRET { rlist, .. } => {
+                rlist.registers().find(|register| register.number() >= 16)
+            }
+            CInstruction::CMMVSA01 {
+                dest1: r1,
+                dest2: r2,
+            }
+            | CInstruction::CMMVA01S { src1: r1, src2: r2 } => [r1, r2]
+                .into_iter()
+                .map(|register| register.expand())
+                .find(|register| register.number() >= 16),
+            // the compressed float loads and stores only have x8-x15 and sp as integer registers
+            _ => self.try_expand().ok()?.rve_register(false),
         }
     }
 
+    /// Like `expand`, but returns an error for instructions whose expansion is not supported (c.fld, c.fsd,
+    /// c.fldsp and c.fsdsp need the D extension) or is not a single instruction (Zcmp).
+    pub fn try_expand(&self) -> Result<Instruction, String> {
+        match self {
+            CInstruction::FLD { .. }
+            | CInstruction::FSD { .. }
+            | CInstruction::FLDSP { .. }
+            | CInstruction::FSDSP { .. } => {
+                Err(format!("{self} cannot be expanded without the D extension"))
+            }
+            CInstruction::CMPUSH { .. }
+            | CInstruction::CMPOP { .. }
+            | CInstruction::CMPOPRETZ { .. }
+            | CInstruction::CMPOPRET { .. }
+            | CInstruction::CMMVSA01 { .. }
+            | CInstruction::CMMVA01S { .. } => Err(format!(
+                "{self} expands to a sequence of instructions, not a single one"
+            )),
+            _ => Ok(self.expand()),
+        }
+    }
+
+    /// Returns the compressed instruction that expands to `instruction` on `xlen`, if there is one.
+    ///
+    /// This is the inverse of `expand`, instructions that only behave like a compressed instruction (`addi a0,a1,0`
+    /// and c.mv) are not compressed. Candidates are checked by decoding them, so reserved encodings and HINTs such as
+    /// c.addi with a zero immediate are never returned.
+    pub fn compress(instruction: &Instruction, xlen: Xlen) -> Option<CInstruction> {
+        use IRegister::{ReturnAddress, StackPointer, Zero};
+        let c = |r: IRegister| CIRegister::try_from(r).ok();
+        let cf = |r: FRegister| CFRegister::try_from(r).ok();
+        let candidates = match *instruction {
+            Instruction::ADDI { dest, src, imm } => {
+                let v = imm.val();
+                vec![
+                    (dest == Zero && src == Zero && v == 0).then_some(CInstruction::NOP),
+                    CIImmediate::try_from(v)
+                        .ok()
+                        .filter(|_| src == Zero)
+                        .map(|imm| CInstruction::LI { dest, imm }),
+                    C16SPImmediate::try_from(v)
+                        .ok()
+                        .filter(|_| dest == StackPointer && src == StackPointer)
+                        .map(|imm| CInstruction::ADDI16SP { imm }),
+                    c(dest)
+                        .zip(CWideImmediate::try_from(v).ok())
+                        .filter(|_| src == StackPointer)
+                        .map(|(dest, imm)| CInstruction::ADDI4SPN { dest, imm }),
+                    CIImmediate::try_from(v)
+                        .ok()
+                        .filter(|_| dest == src)
+                        .map(|imm| CInstruction::ADDI { dest, imm }),
+                ]
+            }
+            Instruction::ADDIW { dest, src, imm } if dest == src => {
+                vec![
+                    CIImmediate::try_from(imm.val())
+                        .ok()
+                        .map(|imm| CInstruction::ADDIW { dest, imm }),
+                ]
+            }
+            Instruction::LUI { dest, imm } => {
+                vec![
+                    CLUIImmediate::try_from(imm.val())
+                        .ok()
+                        .map(|imm| CInstruction::LUI { dest, imm }),
+                ]
+            }
+            Instruction::SLLI { dest, src, shamt } if dest == src => {
+                vec![
+                    CShamt::try_from(shamt.val())
+                        .ok()
+                        .map(|shamt| CInstruction::SLLI { dest, shamt }),
+                ]
+            }
+            Instruction::SRLI { dest, src, shamt } if dest == src => {
+                vec![
+                    c(dest)
+                        .zip(CShamt::try_from(shamt.val()).ok())
+                        .map(|(dest, shamt)| CInstruction::SRLI { dest, shamt }),
+                ]
+            }
+            Instruction::SRAI { dest, src, shamt } if dest == src => {
+                vec![
+                    c(dest)
+                        .zip(CShamt::try_from(shamt.val()).ok())
+                        .map(|(dest, shamt)| CInstruction::SRAI { dest, shamt }),
+                ]
+            }
+            Instruction::ANDI { dest, src, imm } if dest == src => {
+                vec![
+                    c(dest)
+                        .zip(CIImmediate::try_from(imm.val()).ok())
+                        .map(|(dest, imm)| CInstruction::ANDI { dest, imm }),
+                ]
+            }
+            Instruction::SUB { dest, src1, src2 } if dest == src1 => {
+                vec![
+                    c(dest)
+                        .zip(c(src2))
+                        .map(|(dest, src)| CInstruction::SUB { dest, src }),
+                ]
+            }
+            Instruction::XOR { dest, src1, src2 } if dest == src1 => {
+                vec![
+                    c(dest)
+                        .zip(c(src2))
+                        .map(|(dest, src)| CInstruction::XOR { dest, src }),
+                ]
+            }
+            Instruction::OR { dest, src1, src2 } if dest == src1 => {
+                vec![
+                    c(dest)
+                        .zip(c(src2))
+                        .map(|(dest, src)| CInstruction::OR { dest, src }),
+                ]
+            }
+            Instruction::AND { dest, src1, src2 } if dest == src1 => {
+                vec![
+                    c(dest)
+                        .zip(c(src2))
+                        .map(|(dest, src)| CInstruction::AND { dest, src }),
+                ]
+            }
+            Instruction::SUBW { dest, src1, src2 } if dest == src1 => {
+                vec![
+                    c(dest)
+                        .zip(c(src2))
+                        .map(|(dest, src)| CInstruction::SUBW { dest, src }),
+                ]
+            }
+            Instruction::ADDW { dest, src1, src2 } if dest == src1 => {
+                vec![
+                    c(dest)
+                        .zip(c(src2))
+                        .map(|(dest, src)| CInstruction::ADDW { dest, src }),
+                ]
+            }
+            Instruction::ADD { dest, src1, src2 } => vec![
+                (src1 == Zero).then_some(CInstruction::MV { dest, src: src2 }),
+                (dest == src1).then_some(CInstruction::ADD { dest, src: src2 }),
+            ],
+            Instruction::NTLP1 => vec![Some(CInstruction::NTLP1)],
+            Instruction::NTLPALL => vec![Some(CInstruction::NTLPALL)],
+            Instruction::NTLS1 => vec![Some(CInstruction::NTLS1)],
+            Instruction::NTLALL => vec![Some(CInstruction::NTLALL)],
+            Instruction::JAL { dest, offset } => {
+                let offset = CJImmediate::try_from(offset.val()).ok();
+                match dest {
+                    Zero => vec![offset.map(|offset| CInstruction::J { offset })],
+                    ReturnAddress => vec![offset.map(|offset| CInstruction::JAL { offset })],
+                    _ => return None,
+                }
+            }
+            Instruction::JALR { dest, base, offset } if offset.val() == 0 => match dest {
+                Zero => vec![Some(CInstruction::JR { src: base })],
+                ReturnAddress => vec![Some(CInstruction::JALR { src: base })],
+                _ => return None,
+            },
+            Instruction::BEQ {
+                src1,
+                src2: Zero,
+                offset,
+            } => {
+                vec![
+                    c(src1)
+                        .zip(CBImmediate::try_from(offset.val()).ok())
+                        .map(|(src, offset)| CInstruction::BEQZ { src, offset }),
+                ]
+            }
+            Instruction::BNE {
+                src1,
+                src2: Zero,
+                offset,
+            } => {
+                vec![
+                    c(src1)
+                        .zip(CBImmediate::try_from(offset.val()).ok())
+                        .map(|(src, offset)| CInstruction::BNEZ { src, offset }),
+                ]
+            }
+            Instruction::LW { dest, base, offset } => vec![
+                CWSPImmediate::try_from(offset.val())
+                    .ok()
+                    .filter(|_| base == StackPointer)
+                    .map(|offset| CInstruction::LWSP { dest, offset }),
+                c(dest)
+                    .zip(c(base))
+                    .zip(CWImmediate::try_from(offset.val()).ok())
+                    .map(|((dest, base), offset)| CInstruction::LW { dest, base, offset }),
+            ],
+            Instruction::LD { dest, base, offset } => vec![
+                CDSPImmediate::try_from(offset.val())
+                    .ok()
+                    .filter(|_| base == StackPointer)
+                    .map(|offset| CInstruction::LDSP { dest, offset }),
+                c(dest)
+                    .zip(c(base))
+                    .zip(CDImmediate::try_from(offset.val()).ok())
+                    .map(|((dest, base), offset)| CInstruction::LD { dest, base, offset }),
+            ],
+            Instruction::SW { src, base, offset } => vec![
+                CSWSPImmediate::try_from(offset.val())
+                    .ok()
+                    .filter(|_| base == StackPointer)
+                    .map(|offset| CInstruction::SWSP { src, offset }),
+                c(src)
+                    .zip(c(base))
+                    .zip(CWImmediate::try_from(offset.val()).ok())
+                    .map(|((src, base), offset)| CInstruction::SW { src, base, offset }),
+            ],
+            Instruction::SD { src, base, offset } => vec![
+                CSDSPImmediate::try_from(offset.val())
+                    .ok()
+                    .filter(|_| base == StackPointer)
+                    .map(|offset| CInstruction::SDSP { src, offset }),
+                c(src)
+                    .zip(c(base))
+                    .zip(CDImmediate::try_from(offset.val()).ok())
+                    .map(|((src, base), offset)| CInstruction::SD { src, base, offset }),
+            ],
+            Instruction::FLW { dest, base, offset } => vec![
+                CWSPImmediate::try_from(offset.val())
+                    .ok()
+                    .filter(|_| base == StackPointer)
+                    .map(|offset| CInstruction::FLWSP { dest, offset }),
+                cf(dest)
+                    .zip(c(base))
+                    .zip(CWImmediate::try_from(offset.val()).ok())
+                    .map(|((dest, base), offset)| CInstruction::FLW { dest, base, offset }),
+            ],
+            Instruction::FSW { src, base, offset } => vec![
+                CSWSPImmediate::try_from(offset.val())
+                    .ok()
+                    .filter(|_| base == StackPointer)
+                    .map(|offset| CInstruction::FSWSP { src, offset }),
+                cf(src)
+                    .zip(c(base))
+                    .zip(CWImmediate::try_from(offset.val()).ok())
+                    .map(|((src, base), offset)| CInstruction::FSW { src, base, offset }),
+            ],
+            Instruction::EBREAK => vec![Some(CInstruction::EBREAK)],
+            _ => return None,
+        };
+        let config = DecoderConfig {
+            xlen,
+            ..Default::default()
+        };
+        candidates.into_iter().flatten().find(|candidate| {
+            CInstruction::decode_with(CInstruction::encode(candidate), &config).as_ref()
+                == Ok(candidate)
+                && candidate.try_expand().as_ref() == Ok(instruction)
+        })
+    }
+
     /// Encodes a `CInstruction` into a `u16`.
+    // zero fields are kept so that every encoding lists all of its fields
+    #[allow(clippy::identity_op)]
     pub fn encode(instruction: &CInstruction) -> u16 {
         match instruction {
             CInstruction::ADDI4SPN { dest, imm } => 0b000 << 13 | imm.to_u16() | dest.rs2(),
@@ -604,6 +1226,7 @@ impl CInstruction {
             CInstruction::SD { src, base, offset } => {
                 0b111 << 13 | offset.to_u16() | base.rs1() | src.rs2()
             }
+            CInstruction::NOP => 0b000 << 13 | 0b01,
             CInstruction::ADDI { dest, imm } => {
                 0b000 << 13 | imm.to_u16() | dest.rd() as u16 | 0b01
             }
@@ -664,6 +1287,12 @@ impl CInstruction {
             CInstruction::ADD { dest, src } => {
                 0b100 << 13 | 0b1 << 12 | dest.rd() as u16 | (src.rd() >> 5) as u16 | 0b10
             }
+            CInstruction::NTLP1 => 0b100 << 13 | 0b1 << 12 | 2 << 2 | 0b10,
+            CInstruction::NTLPALL => 0b100 << 13 | 0b1 << 12 | 3 << 2 | 0b10,
+            CInstruction::NTLS1 => 0b100 << 13 | 0b1 << 12 | 4 << 2 | 0b10,
+            CInstruction::NTLALL => 0b100 << 13 | 0b1 << 12 | 5 << 2 | 0b10,
+            CInstruction::SSPUSH => 0b011 << 13 | 1 << 7 | 0b01,
+            CInstruction::SSPOPCHK => 0b011 << 13 | 5 << 7 | 0b01,
             CInstruction::FSDSP { src, offset } => {
                 0b101 << 13 | offset.to_u16() | (src.rd() >> 5) as u16 | 0b10
             }
@@ -673,6 +1302,100 @@ impl CInstruction {
             CInstruction::SDSP { src, offset } => {
                 0b111 << 13 | offset.to_u16() | (src.rd() >> 5) as u16 | 0b10
             }
+            CInstruction::FLW { dest, base, offset } => {
+                0b011 << 13 | offset.to_u16() | base.rs1() | dest.rs2()
+            }
+            CInstruction::FSW { src, base, offset } => {
+                0b111 << 13 | offset.to_u16() | base.rs1() | src.rs2()
+            }
+            CInstruction::JAL { offset } => 0b001 << 13 | offset.to_u16() | 0b01,
+            CInstruction::FLWSP { dest, offset } => {
+                0b011 << 13 | offset.to_u16() | dest.rd() as u16 | 0b10
+            }
+            CInstruction::FSWSP { src, offset } => {
+                0b111 << 13 | offset.to_u16() | (src.rd() >> 5) as u16 | 0b10
+            }
+            CInstruction::CMPUSH { rlist, stack_adj } => {
+                0b101 << 13 | 0b11000 << 8 | rlist.rlist() << 4 | stack_adj.to_u16() | 0b10
+            }
+            CInstruction::CMPOP { rlist, stack_adj } => {
+                0b101 << 13 | 0b11010 << 8 | rlist.rlist() << 4 | stack_adj.to_u16() | 0b10
+            }
+            CInstruction::CMPOPRETZ { rlist, stack_adj } => {
+                0b101 << 13 | 0b11100 << 8 | rlist.rlist() << 4 | stack_adj.to_u16() | 0b10
+            }
+            CInstruction::CMPOPRET { rlist, stack_adj } => {
+                0b101 << 13 | 0b11110 << 8 | rlist.rlist() << 4 | stack_adj.to_u16() | 0b10
+            }
+            CInstruction::CMMVSA01 { dest1, dest2 } => {
+                0b101 << 13 | 0b011 << 10 | dest1.rs1() | 0b01 << 5 | dest2.rs2() | 0b10
+            }
+            CInstruction::CMMVA01S { src1, src2 } => {
+                0b101 << 13 | 0b011 << 10 | src1.rs1() | 0b11 << 5 | src2.rs2() | 0b10
+            }
         }
     }
 }
+
+/// A `CInstruction` together with the encoding it was decoded from, see `DecodedInstruction`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DecodedCInstruction {
+    pub raw: u16,
+    pub instruction: CInstruction,
+    /// The bits of the raw encoding that are not part of the instruction, which `encode` keeps.
+    pub ignored: u16,
+}
+
+impl DecodedCInstruction {
+    /// Decodes a u16 with the default configuration, keeping the encoding.
+    pub fn decode(raw: u16) -> Result<DecodedCInstruction, DecodeError> {
+        DecodedCInstruction::decode_with(raw, &DecoderConfig::default())
+    }
+
+    /// Decodes a u16 using the given configuration, keeping the encoding.
+    pub fn decode_with(
+        raw: u16,
+        config: &DecoderConfig,
+    ) -> Result<DecodedCInstruction, DecodeError> {
+        let instruction = CInstruction::decode_with(raw, config)?;
+        Ok(DecodedCInstruction {
+            raw,
+            instruction,
+            ignored: raw ^ CInstruction::encode(&instruction),
+        })
+    }
+
+    /// Decodes a u16 so that `encode` reproduces it exactly, see `DecodedInstruction::decode_lossless`.
+    pub fn decode_lossless(
+        raw: u16,
+        config: &DecoderConfig,
+    ) -> Result<DecodedCInstruction, DecodeError> {
+        let config = DecoderConfig {
+            strict: false,
+            reserved_rounding_modes: true,
+            ..*config
+        };
+        DecodedCInstruction::decode_with(raw, &config)
+    }
+
+    /// Encodes the instruction with the ignored bits of the raw encoding.
+    pub fn encode(&self) -> u16 {
+        CInstruction::encode(&self.instruction) | self.ignored
+    }
+
+    /// Returns the encoding as little endian bytes, as it is stored in memory.
+    pub fn bytes(&self) -> [u8; 2] {
+        self.raw.to_le_bytes()
+    }
+
+    /// Returns whether encoding the instruction gives back the raw encoding.
+    pub fn is_canonical(&self) -> bool {
+        CInstruction::encode(&self.instruction) == self.raw
+    }
+}
+
+impl Display for DecodedCInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.instruction.fmt(f)
+    }
+}
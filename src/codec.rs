@@ -0,0 +1,166 @@
+//! A codec that holds the configuration of one target, for decoding, assembling and disassembling its instructions.
+
+use std::fmt::{Debug, Formatter};
+
+use crate::assembly::assemble_line_with;
+use crate::config::{AssemblerConfig, DecoderConfig, FormatOptions, IsaString, Profile};
+use crate::custom::{CUSTOM_0, CUSTOM_1, CustomHandler, CustomInstruction};
+use crate::error::DecodeError;
+use crate::instruction::{
+    DecodedInst, DecodedInstruction, Inst, Instruction, decode_one_with,
+    disassemble_instruction_with, instruction_length,
+};
+
+/// A handler with the mask and pattern of the words it decodes.
+#[derive(Clone, Copy)]
+struct Custom {
+    mask: u32,
+    pattern: u32,
+    handler: &'static dyn CustomHandler,
+}
+
+/// Decodes, assembles and disassembles the instructions of one target, given by its XLEN and extensions.
+///
+/// The free functions (`Instruction::decode`, `assemble_line`, `disassemble_instruction`) use the default
+/// configurations, which are RV64IMAFC with Zicsr, Zifencei and the compressed loads and stores of D. A codec keeps
+/// its configuration for every call instead:
+///
+/// - `config` gives the XLEN, the extensions and the strictness of decoding, and assembling accepts the same XLEN and
+///   extensions.
+/// - `format` gives the options of disassembling.
+/// - Words in the custom-0 and custom-1 major opcodes that match a registered handler are decoded by it as
+///   `Instruction::Custom`.
+#[derive(Clone, Default)]
+pub struct Codec {
+    pub config: DecoderConfig,
+    pub format: FormatOptions,
+    custom: Vec<Custom>,
+}
+
+impl Codec {
+    pub fn new(config: DecoderConfig) -> Codec {
+        Codec {
+            config,
+            format: FormatOptions::default(),
+            custom: Vec::new(),
+        }
+    }
+
+    /// A codec for the XLEN and extensions of an ISA string.
+    pub fn isa(isa: &IsaString) -> Codec {
+        Codec::new(DecoderConfig::isa(isa))
+    }
+
+    /// A codec for the extensions mandated by a profile.
+    pub fn profile(profile: Profile) -> Codec {
+        Codec::new(DecoderConfig::profile(profile))
+    }
+
+    /// The configuration of assembling, which accepts the XLEN and extensions that are decoded.
+    pub fn assembler_config(&self) -> AssemblerConfig {
+        AssemblerConfig {
+            xlen: self.config.xlen,
+            extensions: self.config.extensions,
+        }
+    }
+
+    /// Decodes the words with `word & mask == pattern` with a handler, see `CustomHandler`.
+    ///
+    /// The pattern must be in the custom-0 or custom-1 major opcode, which the mask must cover. Handlers registered
+    /// earlier are tried first.
+    pub fn register_custom(
+        &mut self,
+        mask: u32,
+        pattern: u32,
+        handler: &'static dyn CustomHandler,
+    ) -> Result<(), String> {
+        if pattern & !mask != 0 {
+            return Err(format!(
+                "the pattern {pattern:#010x} has bits outside of the mask {mask:#010x}"
+            ));
+        }
+        if mask & 0b111_1111 != 0b111_1111
+            || ![CUSTOM_0, CUSTOM_1].contains(&(pattern & 0b111_1111))
+        {
+            return Err(format!(
+                "custom instructions must be in the custom-0 or custom-1 major opcode, got the pattern {pattern:#010x} with the mask {mask:#010x}"
+            ));
+        }
+        self.custom.push(Custom {
+            mask,
+            pattern,
+            handler,
+        });
+        Ok(())
+    }
+
+    /// Decodes a word with the registered handler that matches it, `None` if there is none.
+    fn decode_custom(&self, word: u32) -> Option<Result<Instruction, DecodeError>> {
+        let custom = self
+            .custom
+            .iter()
+            .find(|custom| word & custom.mask == custom.pattern)?;
+        Some(
+            custom
+                .handler
+                .decode(word, self.config.xlen)
+                .map(|data| {
+                    Instruction::Custom(CustomInstruction {
+                        handler: custom.handler,
+                        data,
+                    })
+                })
+                .map_err(|message| DecodeError::full(word, message)),
+        )
+    }
+
+    /// Decodes a 32-bit instruction, see `Instruction::decode_with`.
+    pub fn decode(&self, word: u32) -> Result<Instruction, DecodeError> {
+        self.decode_custom(word)
+            .unwrap_or_else(|| Instruction::decode_with(word, &self.config))
+    }
+
+    /// Decodes the instruction at the start of a little endian byte slice, see `decode_one_with`.
+    pub fn decode_one(&self, bytes: &[u8]) -> Result<(DecodedInst, usize), DecodeError> {
+        if bytes.first().copied().and_then(instruction_length) == Some(4)
+            && let Some(word) = bytes.get(..4)
+        {
+            let raw = u32::from_le_bytes([word[0], word[1], word[2], word[3]]);
+            if let Some(decoded) = self.decode_custom(raw) {
+                let instruction = decoded.map_err(|error| error.at(0))?;
+                let decoded = DecodedInstruction {
+                    raw,
+                    instruction,
+                    ignored: 0,
+                };
+                return Ok((DecodedInst::Full(decoded), 4));
+            }
+        }
+        decode_one_with(bytes, &self.config)
+    }
+
+    /// Assembles a line of assembly, see `assemble_line_with`.
+    pub fn assemble_line(&self, line: &str) -> Result<Inst, String> {
+        assemble_line_with(line, &self.assembler_config())
+    }
+
+    /// Disassembles an instruction with the formatting options.
+    pub fn disassemble(&self, instruction: &Instruction) -> String {
+        disassemble_instruction_with(instruction, &self.format)
+    }
+}
+
+impl Debug for Codec {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let custom: Vec<_> = self
+            .custom
+            .iter()
+            .map(|custom| format!("{:#010x}/{:#010x}", custom.pattern, custom.mask))
+            .collect();
+        f.debug_struct("Codec")
+            .field("config", &self.config)
+            .field("format", &self.format)
+            .field("custom", &custom)
+            .finish()
+    }
+}
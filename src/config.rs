@@ -0,0 +1,961 @@
+use std::fmt::{Display, Formatter};
+
+/// The width of the integer registers (XLEN).
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Xlen {
+    Rv32,
+    #[default]
+    Rv64,
+    /// The draft RV128I base ISA, which adds lq, sq, ldu and the doubleword arithmetic (addid, sllid, addd and
+    /// others). The compressed quad loads and stores are not supported.
+    #[cfg(feature = "rv128")]
+    Rv128,
+}
+
+impl Xlen {
+    /// The number of bits in an integer register.
+    pub fn bits(self) -> u32 {
+        match self {
+            Xlen::Rv32 => 32,
+            Xlen::Rv64 => 64,
+            #[cfg(feature = "rv128")]
+            Xlen::Rv128 => 128,
+        }
+    }
+}
+
+/// The extensions of the base integer ISA that instructions may be from.
+///
+/// The default enables the extensions of `rv64gc` that this crate supports. The others are disabled, including Zilsd
+/// and Zclsd, which only exist on RV32 and take over the encodings of ld, sd and the compressed float loads and stores
+/// there, and the vendor extensions, which take over custom opcodes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct Extensions {
+    /// The embedded base ISA (RV32E and RV64E) in place of I, which only has the integer registers x0-x15.
+    pub e: bool,
+    pub m: bool,
+    pub a: bool,
+    /// Byte and halfword AMOs, including amocas.b and amocas.h, which also require Zacas.
+    pub zabha: bool,
+    /// Load-acquire and store-release (lw.aq, sd.rl and others), which also requires the `zalasr` feature. They do
+    /// not require A.
+    pub zalasr: bool,
+    pub f: bool,
+    /// Only the compressed loads and stores of double precision floats are supported.
+    pub d: bool,
+    /// Quad precision floats, which are held in the float registers like the other precisions.
+    pub q: bool,
+    /// Conversions between single precision and bfloat16, with the loads, stores and moves of half precision values.
+    pub zfbfmin: bool,
+    /// The float instructions operate on the integer registers, without the float loads, stores and moves. Zfinx
+    /// replaces F, so when both are enabled the loads and stores are decoded as with F.
+    pub zfinx: bool,
+    pub c: bool,
+    pub zicsr: bool,
+    pub zifencei: bool,
+    /// Cache block writeback and invalidation (cbo.clean, cbo.flush and cbo.inval).
+    pub zicbom: bool,
+    /// Zeroing of cache blocks (cbo.zero).
+    pub zicboz: bool,
+    /// Prefetch hints, which are decoded as ori to x0 when this is disabled.
+    pub zicbop: bool,
+    /// The pause hint, which is decoded as fence w,0 when this is disabled.
+    pub zihintpause: bool,
+    /// Non-temporal locality hints (ntl.* and c.ntl.*), which are decoded as add and c.add to x0 when this is
+    /// disabled.
+    pub zihintntl: bool,
+    /// Landing pads for indirect jumps (lpad), which are decoded as auipc to x0 when this is disabled.
+    pub zicfilp: bool,
+    /// Shadow stacks (sspush, sspopchk, ssrdp, ssamoswap and c.sspush and c.sspopchk). sspush, sspopchk and ssrdp
+    /// take the encodings of may-be-operations of Zimop, which this crate does not support otherwise.
+    pub zicfiss: bool,
+    /// The bit manipulation instructions of scalar crypto (andn, rol, rori, rev8, pack, brev8, zip and others).
+    pub zbkb: bool,
+    /// Carry-less multiplication (clmul and clmulh).
+    pub zbkc: bool,
+    /// Crossbar permutations (xperm4 and xperm8).
+    pub zbkx: bool,
+    /// The vector extension.
+    pub v: bool,
+    /// Half precision elements in the vector float instructions, which have no encodings of their own.
+    pub zvfh: bool,
+    /// Vector conversions between single precision and bfloat16 (vfncvtbf16.f.f.w and vfwcvtbf16.f.f.v).
+    pub zvfbfmin: bool,
+    /// Vector widening bfloat16 multiply-add (vfwmaccbf16).
+    pub zvfbfwma: bool,
+    /// Vector bit manipulation (vandn, vbrev, vclz, vrol, vror, vwsll and others), which includes Zvkb.
+    pub zvbb: bool,
+    /// Vector carry-less multiplication (vclmul and vclmulh).
+    pub zvbc: bool,
+    /// Vector GCM and GMAC (vghsh and vgmul).
+    pub zvkg: bool,
+    /// Vector AES encryption, decryption and key schedule.
+    pub zvkned: bool,
+    /// Vector SHA-2 compression and message schedule.
+    pub zvknha: bool,
+    /// Vector SM4 rounds and key schedule.
+    pub zvksed: bool,
+    /// Vector SM3 compression and message expansion.
+    pub zvksh: bool,
+    /// AES encryption (aes32es*, aes64es* and the key schedule).
+    pub zkne: bool,
+    /// AES decryption (aes32ds*, aes64ds* and the key schedule).
+    pub zknd: bool,
+    /// The sigma and sum functions of SHA2-256 and SHA2-512.
+    pub zknh: bool,
+    /// ld and sd of even-odd register pairs on RV32.
+    pub zilsd: bool,
+    /// c.ld, c.sd, c.ldsp and c.sdsp of register pairs on RV32, in place of c.flw, c.fsw, c.flwsp and c.fswsp.
+    pub zclsd: bool,
+    /// Push, pop and register moves for function prologues and epilogues (cm.push, cm.popret, cm.mvsa01 and
+    /// others), which take over the encodings of c.fsdsp.
+    pub zcmp: bool,
+    /// The draft packed SIMD instructions, which also requires the `p` feature. They take over the OP-VE major opcode
+    /// of the vector crypto instructions.
+    pub p: bool,
+    /// The custom instructions of Andes cores, which also requires the `xandesperf` feature.
+    pub xandesperf: bool,
+    /// The address generation instruction of T-Head cores (th.addsl), which also requires the `xthead` feature, as
+    /// do the other XThead extensions.
+    pub xtheadba: bool,
+    /// The bit manipulation instructions of T-Head cores (th.ext, th.rev, th.ff1 and others).
+    pub xtheadbb: bool,
+    /// The cache management instructions of T-Head cores (th.dcache.cva and others).
+    pub xtheadcmo: bool,
+    /// The conditional moves of T-Head cores (th.mveqz and th.mvnez).
+    pub xtheadcondmov: bool,
+    /// The loads and stores of register pairs of T-Head cores (th.ldd, th.sdd and others).
+    pub xtheadmempair: bool,
+}
+
+impl Default for Extensions {
+    fn default() -> Self {
+        Extensions {
+            e: false,
+            m: true,
+            a: true,
+            zabha: false,
+            zalasr: false,
+            f: true,
+            d: true,
+            q: false,
+            zfbfmin: false,
+            zfinx: false,
+            c: true,
+            zicsr: true,
+            zifencei: true,
+            zicbom: false,
+            zicboz: false,
+            zicbop: false,
+            zihintpause: false,
+            zihintntl: false,
+            zicfilp: false,
+            zicfiss: false,
+            zbkb: false,
+            zbkc: false,
+            zbkx: false,
+            v: false,
+            zvfh: false,
+            zvfbfmin: false,
+            zvfbfwma: false,
+            zvbb: false,
+            zvbc: false,
+            zvkg: false,
+            zvkned: false,
+            zvknha: false,
+            zvksed: false,
+            zvksh: false,
+            zkne: false,
+            zknd: false,
+            zknh: false,
+            zilsd: false,
+            zclsd: false,
+            zcmp: false,
+            p: false,
+            xandesperf: false,
+            xtheadba: false,
+            xtheadbb: false,
+            xtheadcmo: false,
+            xtheadcondmov: false,
+            xtheadmempair: false,
+        }
+    }
+}
+
+impl Extensions {
+    /// Enables or disables an extension by its name in an ISA string (`m` or `zicsr`).
+    ///
+    /// Enabling an extension also enables the extensions it depends on (`d` enables `f` and `zicsr`) and disabling one
+    /// also disables the extensions that depend on it. Returns false for extensions that this crate does not support,
+    /// which are left alone.
+    pub fn set(&mut self, name: &str, enabled: bool) -> bool {
+        match name {
+            "e" => self.e = enabled,
+            "m" => self.m = enabled,
+            "a" => self.a = enabled,
+            "zabha" => self.zabha = enabled,
+            "zalasr" => self.zalasr = enabled,
+            "f" => self.f = enabled,
+            "d" => self.d = enabled,
+            "q" => self.q = enabled,
+            "zfbfmin" => self.zfbfmin = enabled,
+            "zfinx" => self.zfinx = enabled,
+            "c" => self.c = enabled,
+            "zicsr" => self.zicsr = enabled,
+            "zifencei" => self.zifencei = enabled,
+            "zicbom" => self.zicbom = enabled,
+            "zicboz" => self.zicboz = enabled,
+            "zicbop" => self.zicbop = enabled,
+            "zihintpause" => self.zihintpause = enabled,
+            "zihintntl" => self.zihintntl = enabled,
+            "zicfilp" => self.zicfilp = enabled,
+            "zicfiss" => self.zicfiss = enabled,
+            "zbkb" => self.zbkb = enabled,
+            "zbkc" => self.zbkc = enabled,
+            "zbkx" => self.zbkx = enabled,
+            "v" => self.v = enabled,
+            "zvfh" => self.zvfh = enabled,
+            "zvfbfmin" => self.zvfbfmin = enabled,
+            "zvfbfwma" => self.zvfbfwma = enabled,
+            "zvbb" => self.zvbb = enabled,
+            "zvbc" => self.zvbc = enabled,
+            "zvkg" => self.zvkg = enabled,
+            "zvkned" => self.zvkned = enabled,
+            "zvknha" => self.zvknha = enabled,
+            "zvksed" => self.zvksed = enabled,
+            "zvksh" => self.zvksh = enabled,
+            "zkne" => self.zkne = enabled,
+            "zknd" => self.zknd = enabled,
+            "zknh" => self.zknh = enabled,
+            "zilsd" => self.zilsd = enabled,
+            "zclsd" => self.zclsd = enabled,
+            "zcmp" => self.zcmp = enabled,
+            "p" => self.p = enabled,
+            "xandesperf" => self.xandesperf = enabled,
+            "xtheadba" => self.xtheadba = enabled,
+            "xtheadbb" => self.xtheadbb = enabled,
+            "xtheadcmo" => self.xtheadcmo = enabled,
+            "xtheadcondmov" => self.xtheadcondmov = enabled,
+            "xtheadmempair" => self.xtheadmempair = enabled,
+            _ => return false,
+        }
+        if enabled {
+            self.zvfbfmin |= self.zvfbfwma;
+            self.zfbfmin |= self.zvfbfwma;
+            self.v |= self.zvfh
+                || self.zvfbfmin
+                || self.zvbb
+                || self.zvbc
+                || self.zvkg
+                || self.zvkned
+                || self.zvknha
+                || self.zvksed
+                || self.zvksh;
+            self.d |= self.q || self.v;
+            self.f |= self.d || self.zfbfmin;
+            self.zicsr |= self.f || self.zfinx || self.zicfiss;
+            self.a |= self.zabha;
+            self.zilsd |= self.zclsd;
+            self.c |= self.zcmp;
+        } else {
+            self.f &= self.zicsr;
+            self.zfinx &= self.zicsr;
+            self.zicfiss &= self.zicsr;
+            self.d &= self.f;
+            self.q &= self.d;
+            self.v &= self.d;
+            self.zfbfmin &= self.f;
+            self.zvfh &= self.v;
+            self.zvfbfmin &= self.v;
+            self.zvfbfwma &= self.zvfbfmin && self.zfbfmin;
+            self.zvbb &= self.v;
+            self.zvbc &= self.v;
+            self.zvkg &= self.v;
+            self.zvkned &= self.v;
+            self.zvknha &= self.v;
+            self.zvksed &= self.v;
+            self.zvksh &= self.v;
+            self.zabha &= self.a;
+            self.zclsd &= self.zilsd;
+            self.zcmp &= self.c;
+        }
+        true
+    }
+
+    /// Only the base integer ISA.
+    pub fn none() -> Extensions {
+        Extensions {
+            e: false,
+            m: false,
+            a: false,
+            zabha: false,
+            zalasr: false,
+            f: false,
+            d: false,
+            q: false,
+            zfbfmin: false,
+            zfinx: false,
+            c: false,
+            zicsr: false,
+            zifencei: false,
+            zicbom: false,
+            zicboz: false,
+            zicbop: false,
+            zihintpause: false,
+            zihintntl: false,
+            zicfilp: false,
+            zicfiss: false,
+            zbkb: false,
+            zbkc: false,
+            zbkx: false,
+            v: false,
+            zvfh: false,
+            zvfbfmin: false,
+            zvfbfwma: false,
+            zvbb: false,
+            zvbc: false,
+            zvkg: false,
+            zvkned: false,
+            zvknha: false,
+            zvksed: false,
+            zvksh: false,
+            zkne: false,
+            zknd: false,
+            zknh: false,
+            zilsd: false,
+            zclsd: false,
+            zcmp: false,
+            p: false,
+            xandesperf: false,
+            xtheadba: false,
+            xtheadbb: false,
+            xtheadcmo: false,
+            xtheadcondmov: false,
+            xtheadmempair: false,
+        }
+    }
+
+    /// Returns the name of the extension of a 32-bit encoding if it is not enabled.
+    pub(crate) fn missing(&self, word: u32, xlen: Xlen) -> Option<&'static str> {
+        let funct3 = (word >> 12) & 0b111;
+        let (enabled, name) = match word & 0x7f {
+            0x03 | 0x23 if funct3 == 0b011 && xlen == Xlen::Rv32 => (self.zilsd, "Zilsd"),
+            0x33 | 0x3b if word >> 25 == 1 => (self.m, "M"),
+            0x33 | 0x3b if word >> 25 == 0b000_0100 => (self.zbkb, "Zbkb"),
+            0x13 if matches!(word >> 20, 0x687 | 0x08f) && funct3 & 0b11 == 0b01 => {
+                (self.zbkb, "Zbkb")
+            }
+            // andn, orn and xnor are the inverted forms of and, or and xor
+            0x33 if word >> 25 == 0b010_0000 && matches!(funct3, 0b100 | 0b110 | 0b111) => {
+                (self.zbkb, "Zbkb")
+            }
+            0x33 | 0x3b if word >> 25 == 0b011_0000 => (self.zbkb, "Zbkb"),
+            0x13 if funct3 == 0b101
+                && (word >> 26 == 0b01_1000 || matches!(word >> 20, 0x698 | 0x6b8)) =>
+            {
+                (self.zbkb, "Zbkb")
+            }
+            0x1b if funct3 == 0b101 && word >> 25 == 0b011_0000 => (self.zbkb, "Zbkb"),
+            0x33 if word >> 25 == 0b000_0101 => (self.zbkc, "Zbkc"),
+            0x33 if word >> 25 == 0b001_0100 => (self.zbkx, "Zbkx"),
+            // the upper two bits of funct7 are the byte select of the RV32 AES instructions
+            0x33 if matches!(word >> 25 & 0b1_1111, 0b1_0001 | 0b1_0011) => (self.zkne, "Zkne"),
+            0x33 if matches!(word >> 25 & 0b1_1111, 0b1_0101 | 0b1_0111) => (self.zknd, "Zknd"),
+            0x33 if matches!(word >> 25, 0b001_1001 | 0b001_1011) => (self.zkne, "Zkne"),
+            0x33 if matches!(word >> 25, 0b001_1101 | 0b001_1111) => (self.zknd, "Zknd"),
+            0x33 if word >> 25 == 0b011_1111 => (self.zkne || self.zknd, "Zkne or Zknd"),
+            0x33 if word >> 28 == 0b0101 => (self.zknh, "Zknh"),
+            0x13 if funct3 == 0b001 && word >> 25 == 0b000_1000 => (self.zknh, "Zknh"),
+            0x13 if funct3 == 0b001 && word >> 20 == 0x300 => (self.zknd, "Zknd"),
+            0x13 if funct3 == 0b001 && word >> 24 == 0b0011_0001 => {
+                (self.zkne || self.zknd, "Zkne or Zknd")
+            }
+            0x2f if cfg!(feature = "zalasr") && matches!(word >> 27, 0b00110 | 0b00111) => {
+                (self.zalasr, "Zalasr")
+            }
+            // ssamoswap does not require A either
+            0x2f if word >> 27 == 0b01001 => (self.zicfiss, "Zicfiss"),
+            0x2f if funct3 & 0b110 == 0 => (self.zabha, "Zabha"),
+            0x2f => (self.a, "A"),
+            0x07 | 0x27 if funct3 == 0b100 => (self.q, "Q"),
+            0x07 | 0x27 if funct3 == 0b001 => (self.zfbfmin, "Zfbfmin"),
+            0x07 | 0x27 if matches!(funct3, 0b000 | 0b101 | 0b110 | 0b111) => (self.v, "V"),
+            0x43 | 0x47 | 0x4b | 0x4f | 0x53 if (word >> 25) & 0b11 == 0b11 => (self.q, "Q"),
+            // fcvt.s.q and fcvt.d.q have the fmt of the result and the fmt of Q in rs2
+            0x53 if matches!(word >> 25, 0b010_0000 | 0b010_0001)
+                && (word >> 20) & 0b1_1111 == 0b0_0011 =>
+            {
+                (self.q, "Q")
+            }
+            // the half precision moves and fcvt.bf16.s have the fmt of H, fcvt.s.bf16 the fmt of S
+            0x53 if (word >> 25) & 0b11 == 0b10
+                || (word >> 25 == 0b010_0000 && (word >> 20) & 0b1_1111 == 0b0_0110) =>
+            {
+                (self.zfbfmin, "Zfbfmin")
+            }
+            // fmv.x.w and fmv.w.x, which Zfinx does not need
+            0x53 if word >> 25 == 0b111_1000 || (word >> 25 == 0b111_0000 && funct3 == 0) => {
+                (self.f, "F")
+            }
+            0x07 | 0x27 => (self.f, "F"),
+            0x43 | 0x47 | 0x4b | 0x4f | 0x53 => (self.f || self.zfinx, "F or Zfinx"),
+            0x73 if funct3 == 0b100 => (self.zicfiss, "Zicfiss"),
+            0x73 if funct3 != 0 => (self.zicsr, "Zicsr"),
+            0x0f if funct3 == 1 => (self.zifencei, "Zifencei"),
+            #[cfg(feature = "rv128")]
+            0x0f if funct3 == 2 && xlen == Xlen::Rv128 => (true, ""),
+            0x0f if funct3 == 2 && word >> 20 == 0x004 => (self.zicboz, "Zicboz"),
+            0x0f if funct3 == 2 => (self.zicbom, "Zicbom"),
+            0x57 if matches!(funct3, 0b000 | 0b011 | 0b100)
+                && matches!(word >> 26, 0b000001 | 0b010100 | 0b010101 | 0b110101) =>
+            {
+                (self.zvbb, "Zvbb")
+            }
+            0x57 if funct3 == 0b010
+                && word >> 26 == 0b010010
+                && matches!((word >> 15) & 0b1_1111, 0b01000..=0b01110) =>
+            {
+                (self.zvbb, "Zvbb")
+            }
+            0x57 if matches!(funct3, 0b010 | 0b110)
+                && matches!(word >> 26, 0b001100 | 0b001101) =>
+            {
+                (self.zvbc, "Zvbc")
+            }
+            0x57 if funct3 == 0b001
+                && word >> 26 == 0b010010
+                && matches!((word >> 15) & 0b1_1111, 0b01101 | 0b11101) =>
+            {
+                (self.zvfbfmin, "Zvfbfmin")
+            }
+            0x57 if matches!(funct3, 0b001 | 0b101) && word >> 26 == 0b111011 => {
+                (self.zvfbfwma, "Zvfbfwma")
+            }
+            0x57 => (self.v, "V"),
+            // OP-P, which is decoded as P when it is enabled
+            0x77 if cfg!(feature = "p") && self.p => (true, ""),
+            // the vector crypto instructions of OP-VE, where vs1 selects the operation of some funct6
+            0x77 => match (word >> 26, (word >> 15) & 0b1_1111) {
+                (0b101000 | 0b101001, 0b10000) | (0b100001, _) => (self.zvksed, "Zvksed"),
+                (0b101000, 0b10001) | (0b101100, _) => (self.zvkg, "Zvkg"),
+                (0b101101..=0b101111, _) => (self.zvknha, "Zvknha"),
+                (0b100000 | 0b101011, _) => (self.zvksh, "Zvksh"),
+                _ => (self.zvkned, "Zvkned"),
+            },
+            0x5b if xlen.bits() < 128 => (self.xandesperf, "XAndesPerf"),
+            // the custom-0 opcode of the T-Head instructions
+            0x0b if cfg!(feature = "xthead") => match funct3 {
+                0b000 => (self.xtheadcmo, "XTheadCmo"),
+                0b001 if word >> 27 == 0 => (self.xtheadba, "XTheadBa"),
+                0b001 if matches!(word >> 25, 0b0100000 | 0b0100001) => {
+                    (self.xtheadcondmov, "XTheadCondMov")
+                }
+                0b001..=0b011 => (self.xtheadbb, "XTheadBb"),
+                0b100 | 0b101 => (self.xtheadmempair, "XTheadMemPair"),
+                _ => (true, ""),
+            },
+            _ => (true, ""),
+        };
+        (!enabled).then_some(name)
+    }
+
+    /// Returns the name of the extension of a 16-bit encoding if it is not enabled.
+    pub(crate) fn missing_compressed(&self, half: u16, xlen: Xlen) -> Option<&'static str> {
+        if !self.c {
+            return Some("C");
+        }
+        // c.fld, c.fsd, c.fldsp and c.fsdsp, and c.flw, c.fsw, c.flwsp and c.fswsp on RV32 without Zclsd
+        let funct3 = half >> 13;
+        let (enabled, name) = match (half & 0b11, funct3) {
+            (0b10, 0b101) if self.zcmp => (true, ""),
+            (0b00 | 0b10, 0b001 | 0b101) => (self.d, "D"),
+            (0b00 | 0b10, 0b011 | 0b111) if xlen == Xlen::Rv32 && !self.zclsd => (self.f, "F"),
+            _ => (true, ""),
+        };
+        (!enabled).then_some(name)
+    }
+}
+
+/// Skips a version (`2`, `2p1`) at the start of a string.
+fn skip_version(str: &str) -> &str {
+    let rest = str.trim_start_matches(|c: char| c.is_ascii_digit());
+    match rest.strip_prefix('p') {
+        Some(minor)
+            if rest.len() < str.len() && minor.starts_with(|c: char| c.is_ascii_digit()) =>
+        {
+            minor.trim_start_matches(|c: char| c.is_ascii_digit())
+        }
+        _ => rest,
+    }
+}
+
+/// Removes a version (`2`, `2p1`) from the end of an extension name.
+fn strip_version(str: &str) -> &str {
+    let rest = str.trim_end_matches(|c: char| c.is_ascii_digit());
+    match rest.strip_suffix('p') {
+        Some(major) if rest.len() < str.len() && major.ends_with(|c: char| c.is_ascii_digit()) => {
+            major.trim_end_matches(|c: char| c.is_ascii_digit())
+        }
+        _ => rest,
+    }
+}
+
+/// The order of the single-letter extensions in a canonical ISA string, which multi-letter extensions are also
+/// grouped by (`zicsr` comes before `zba`).
+const CANONICAL_ORDER: &str = "imafdqlcbkjtpvnh";
+
+/// The extensions that `g` stands for.
+const GENERAL: [&str; 6] = ["m", "a", "f", "d", "zicsr", "zifencei"];
+
+/// The position of an extension in a canonical ISA string: single letters, then the multi-letter extensions starting
+/// with z, s and x, each grouped by the single-letter extension they relate to and then sorted alphabetically.
+fn canonical_position(name: &str) -> (usize, usize, &str) {
+    let letter_position = |c: Option<char>| {
+        c.and_then(|c| CANONICAL_ORDER.find(c))
+            .unwrap_or(CANONICAL_ORDER.len())
+    };
+    let mut chars = name.chars();
+    let first = chars.next();
+    if name.len() == 1 {
+        return (0, letter_position(first), name);
+    }
+    let prefix = match first {
+        Some('z') => 1,
+        Some('s') => 2,
+        _ => 3,
+    };
+    (prefix, letter_position(chars.next()), name)
+}
+
+/// An ISA string such as `rv64imafdc_zicsr_zifencei` or `rv32gc`, as used by `-march` and the arch attribute.
+///
+/// Parsing accepts versions after extensions (`rv64i2p1_m2p0`), which are dropped, and `g`, which stands for
+/// `imafd_zicsr_zifencei`. Extensions imply the ones they depend on (`d` implies `f`, which implies `zicsr`). The
+/// extensions that this crate does not support are kept by name, so that displaying the ISA string gives the canonical
+/// form of the original (`rv64imafdc_zicsr_zifencei_zba_zbb` for `rv64gc_zbb_zba`).
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct IsaString {
+    pub xlen: Xlen,
+    pub extensions: Extensions,
+    /// The names of the extensions that this crate does not support, in canonical order.
+    pub unsupported: Vec<String>,
+}
+
+impl IsaString {
+    fn enable(&mut self, name: &str) {
+        if name == "g" {
+            for name in GENERAL {
+                self.extensions.set(name, true);
+            }
+        } else if !self.extensions.set(name, true) && !self.unsupported.iter().any(|u| u == name) {
+            self.unsupported.push(name.to_owned());
+        }
+    }
+}
+
+impl TryFrom<&str> for IsaString {
+    type Error = String;
+
+    fn try_from(isa: &str) -> Result<Self, Self::Error> {
+        let lower = isa.to_lowercase();
+        let (xlen, rest) = if let Some(rest) = lower.strip_prefix("rv32") {
+            (Xlen::Rv32, rest)
+        } else if let Some(rest) = lower.strip_prefix("rv64") {
+            (Xlen::Rv64, rest)
+        } else {
+            return Err(format!("ISA string {isa} must start with rv32 or rv64"));
+        };
+        let mut parts = rest.split('_');
+        let letters = parts.next().unwrap();
+        let mut parsed = IsaString {
+            xlen,
+            extensions: Extensions::none(),
+            unsupported: Vec::new(),
+        };
+        match letters.chars().next() {
+            Some('i') => {}
+            Some('g') => parsed.enable("g"),
+            Some('e') => parsed.extensions.e = true,
+            _ => return Err(format!("ISA string {isa} must have the base ISA i, e or g")),
+        }
+        let mut rest = &letters[1..];
+        loop {
+            // skip the version of the base ISA or the previous extension
+            rest = skip_version(rest);
+            match rest.chars().next() {
+                Some(c) if c.is_ascii_lowercase() => parsed.enable(&rest[..1]),
+                Some(c) => return Err(format!("invalid character {c:?} in ISA string {isa}")),
+                None => break,
+            }
+            rest = &rest[1..];
+        }
+        for part in parts {
+            let name = strip_version(part);
+            if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric()) {
+                return Err(format!("invalid extension {part} in ISA string {isa}"));
+            }
+            parsed.enable(name);
+        }
+        parsed
+            .unsupported
+            .sort_by(|a, b| canonical_position(a).cmp(&canonical_position(b)));
+        Ok(parsed)
+    }
+}
+
+/// Displays the canonical form of the ISA string, with every extension spelled out (`rv64imafdc_zicsr_zifencei`).
+impl Display for IsaString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let e = &self.extensions;
+        let mut names: Vec<&str> = [
+            ("m", e.m),
+            ("a", e.a),
+            ("zabha", e.zabha),
+            ("zalasr", e.zalasr),
+            ("f", e.f),
+            ("d", e.d),
+            ("q", e.q),
+            ("zfbfmin", e.zfbfmin),
+            ("zfinx", e.zfinx),
+            ("c", e.c),
+            ("zicsr", e.zicsr),
+            ("zifencei", e.zifencei),
+            ("zicbom", e.zicbom),
+            ("zicboz", e.zicboz),
+            ("zicbop", e.zicbop),
+            ("zihintpause", e.zihintpause),
+            ("zihintntl", e.zihintntl),
+            ("zicfilp", e.zicfilp),
+            ("zicfiss", e.zicfiss),
+            ("zbkb", e.zbkb),
+            ("zbkc", e.zbkc),
+            ("zbkx", e.zbkx),
+            ("v", e.v),
+            ("zvfh", e.zvfh),
+            ("zvfbfmin", e.zvfbfmin),
+            ("zvfbfwma", e.zvfbfwma),
+            ("zvbb", e.zvbb),
+            ("zvbc", e.zvbc),
+            ("zvkg", e.zvkg),
+            ("zvkned", e.zvkned),
+            ("zvknha", e.zvknha),
+            ("zvksed", e.zvksed),
+            ("zvksh", e.zvksh),
+            ("zkne", e.zkne),
+            ("zknd", e.zknd),
+            ("zknh", e.zknh),
+            ("zilsd", e.zilsd),
+            ("zclsd", e.zclsd),
+            ("zcmp", e.zcmp),
+            ("p", e.p),
+            ("xandesperf", e.xandesperf),
+            ("xtheadba", e.xtheadba),
+            ("xtheadbb", e.xtheadbb),
+            ("xtheadcmo", e.xtheadcmo),
+            ("xtheadcondmov", e.xtheadcondmov),
+            ("xtheadmempair", e.xtheadmempair),
+        ]
+        .into_iter()
+        .filter(|(_, enabled)| *enabled)
+        .map(|(name, _)| name)
+        .collect();
+        names.extend(self.unsupported.iter().map(String::as_str));
+        names.sort_by_key(|name| canonical_position(name));
+        let base = if e.e { 'e' } else { 'i' };
+        write!(f, "rv{}{base}", self.xlen.bits())?;
+        // single letters come first and are not separated
+        for name in names {
+            if name.len() > 1 {
+                f.write_str("_")?;
+            }
+            f.write_str(name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses an ISA string such as `rv64imafdc_zicsr_zifencei` or `rv32gc` into the XLEN and the supported extensions,
+/// see `IsaString`.
+pub fn parse_isa_string(isa: &str) -> Result<(Xlen, Extensions), String> {
+    let parsed = IsaString::try_from(isa)?;
+    Ok((parsed.xlen, parsed.extensions))
+}
+
+/// The ratified RISC-V profiles of the unprivileged ISA.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Profile {
+    Rvi20U32,
+    Rvi20U64,
+    Rva20U64,
+    Rva22U64,
+    Rva23U64,
+}
+
+impl Profile {
+    pub fn xlen(self) -> Xlen {
+        match self {
+            Profile::Rvi20U32 => Xlen::Rv32,
+            _ => Xlen::Rv64,
+        }
+    }
+
+    /// The mandatory extensions of the profile, including the many that this crate does not support.
+    pub fn mandatory(self) -> &'static [&'static str] {
+        const RVA20: &[&str] = &[
+            "M", "A", "F", "D", "C", "Zicsr", "Zicntr", "Ziccif", "Ziccrse", "Ziccamoa", "Za128rs",
+            "Zicclsm",
+        ];
+        const RVA22: &[&str] = &[
+            "M",
+            "A",
+            "F",
+            "D",
+            "C",
+            "Zicsr",
+            "Zicntr",
+            "Zihpm",
+            "Ziccif",
+            "Ziccrse",
+            "Ziccamoa",
+            "Zicclsm",
+            "Za64rs",
+            "Zihintpause",
+            "Zba",
+            "Zbb",
+            "Zbs",
+            "Zic64b",
+            "Zicbom",
+            "Zicbop",
+            "Zicboz",
+            "Zfhmin",
+            "Zkt",
+        ];
+        const RVA23: &[&str] = &[
+            "M",
+            "A",
+            "F",
+            "D",
+            "C",
+            "B",
+            "Zicsr",
+            "Zicntr",
+            "Zihpm",
+            "Ziccif",
+            "Ziccrse",
+            "Ziccamoa",
+            "Zicclsm",
+            "Za64rs",
+            "Zihintpause",
+            "Zic64b",
+            "Zicbom",
+            "Zicbop",
+            "Zicboz",
+            "Zfhmin",
+            "Zkt",
+            "V",
+            "Zvfhmin",
+            "Zvbb",
+            "Zvkt",
+            "Zihintntl",
+            "Zicond",
+            "Zimop",
+            "Zcmop",
+            "Zcb",
+            "Zfa",
+            "Zawrs",
+            "Supm",
+        ];
+        match self {
+            Profile::Rvi20U32 | Profile::Rvi20U64 => &[],
+            Profile::Rva20U64 => RVA20,
+            Profile::Rva22U64 => RVA22,
+            Profile::Rva23U64 => RVA23,
+        }
+    }
+
+    /// The supported extensions that the profile mandates, all others are disabled.
+    pub fn extensions(self) -> Extensions {
+        let mandatory = self.mandatory();
+        let has = |name| mandatory.contains(&name);
+        Extensions {
+            e: false,
+            m: has("M"),
+            a: has("A"),
+            zabha: has("Zabha"),
+            zalasr: has("Zalasr"),
+            f: has("F"),
+            d: has("D"),
+            q: has("Q"),
+            zfbfmin: has("Zfbfmin"),
+            zfinx: has("Zfinx"),
+            c: has("C"),
+            zicsr: has("Zicsr"),
+            zifencei: has("Zifencei"),
+            zicbom: has("Zicbom"),
+            zicboz: has("Zicboz"),
+            zicbop: has("Zicbop"),
+            zihintpause: has("Zihintpause"),
+            zihintntl: has("Zihintntl"),
+            zicfilp: has("Zicfilp"),
+            zicfiss: has("Zicfiss"),
+            zbkb: has("Zbkb"),
+            zbkc: has("Zbkc"),
+            zbkx: has("Zbkx"),
+            v: has("V"),
+            zvfh: has("Zvfh"),
+            zvfbfmin: has("Zvfbfmin"),
+            zvfbfwma: has("Zvfbfwma"),
+            zvbb: has("Zvbb"),
+            zvbc: has("Zvbc"),
+            zvkg: has("Zvkg"),
+            zvkned: has("Zvkned"),
+            zvknha: has("Zvknha"),
+            zvksed: has("Zvksed"),
+            zvksh: has("Zvksh"),
+            zkne: has("Zkne"),
+            zknd: has("Zknd"),
+            zknh: has("Zknh"),
+            zilsd: has("Zilsd"),
+            zclsd: has("Zclsd"),
+            zcmp: has("Zcmp"),
+            p: false,
+            xandesperf: false,
+            xtheadba: false,
+            xtheadbb: false,
+            xtheadcmo: false,
+            xtheadcondmov: false,
+            xtheadmempair: false,
+        }
+    }
+}
+
+/// Options controlling how machine code is decoded.
+///
+/// The default configuration decodes RV64IMAFC with Zicsr, Zifencei and the compressed loads and stores of D, with
+/// strict reserved bit checking.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DecoderConfig {
+    pub xlen: Xlen,
+    /// Reject encodings with reserved fields that are not zero (such as the rs2 field of lr.w or fsqrt.s,
+    /// the rd and rs1 fields of fence, or reserved fence fm values).
+    ///
+    /// When disabled, these fields are ignored as the ISA manual requires of implementations,
+    /// which makes the decoder act as a permissive disassembler instead of a validator.
+    pub strict: bool,
+    /// Decode the reserved rounding modes (0b101 and 0b110) as `RoundingMode::Reserved` instead of
+    /// reporting an error, for tools that need to inspect such encodings.
+    pub reserved_rounding_modes: bool,
+    /// The extensions to decode, instructions of other extensions are rejected.
+    pub extensions: Extensions,
+}
+
+impl Default for DecoderConfig {
+    fn default() -> Self {
+        DecoderConfig {
+            xlen: Xlen::default(),
+            strict: true,
+            reserved_rounding_modes: false,
+            extensions: Extensions::default(),
+        }
+    }
+}
+
+impl DecoderConfig {
+    /// Decodes the XLEN and extensions of an ISA string.
+    pub fn isa(isa: &IsaString) -> DecoderConfig {
+        DecoderConfig {
+            xlen: isa.xlen,
+            extensions: isa.extensions,
+            ..Default::default()
+        }
+    }
+
+    /// Decodes the extensions mandated by a profile.
+    pub fn profile(profile: Profile) -> DecoderConfig {
+        DecoderConfig {
+            xlen: profile.xlen(),
+            extensions: profile.extensions(),
+            ..Default::default()
+        }
+    }
+}
+
+/// Options controlling which instructions the assembler accepts.
+///
+/// The default configuration assembles RV64IMAFC with Zicsr, Zifencei and the compressed loads and stores of D.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct AssemblerConfig {
+    pub xlen: Xlen,
+    /// The extensions to accept instructions from.
+    pub extensions: Extensions,
+}
+
+impl AssemblerConfig {
+    /// Assembles the XLEN and extensions of an ISA string.
+    pub fn isa(isa: &IsaString) -> AssemblerConfig {
+        AssemblerConfig {
+            xlen: isa.xlen,
+            extensions: isa.extensions,
+        }
+    }
+
+    /// Assembles the extensions mandated by a profile.
+    pub fn profile(profile: Profile) -> AssemblerConfig {
+        AssemblerConfig {
+            xlen: profile.xlen(),
+            extensions: profile.extensions(),
+        }
+    }
+}
+
+/// Options controlling how instructions are displayed.
+///
+/// The default options match the output of this crate's `Display` implementations.
+#[derive(Debug, PartialEq, Clone, Copy, Default)]
+pub struct FormatOptions {
+    /// Print the immediates of lui and auipc as 20-bit hex values (`lui a0,0xfffff`) like objdump.
+    pub hex_u_immediates: bool,
+    /// How immediates and offsets are printed.
+    pub immediate_style: ImmediateStyle,
+    /// How registers are named.
+    pub register_style: RegisterStyle,
+    /// Print instructions as their common pseudo-instructions (`mv a0,a1`, `ret`, `beqz a0,8`) like objdump.
+    pub pseudo_instructions: bool,
+    /// Print the address of lr, sc and the AMOs in parentheses after the source (`amoadd.w a0,a2,(a1)`) like objdump.
+    pub parenthesized_atomics: bool,
+    /// Pad mnemonics with spaces to this width so that the operands of consecutive lines line up (`addi    a0,a0,1`),
+    /// longer mnemonics are followed by a single space.
+    pub mnemonic_width: Option<usize>,
+    /// Print the rounding mode as a last operand (`fadd.s fa0,fa1,fa2,rtz`) like objdump instead of as a suffix of the
+    /// mnemonic (`fadd.s.rtz fa0,fa1,fa2`). The dynamic rounding mode is never printed.
+    pub rounding_mode_operand: bool,
+    /// Print float registers as the integer register of the same number (`fadd.s a0,a1,a2`), for code that runs
+    /// with Zfinx.
+    pub zfinx: bool,
+}
+
+/// The names used to display registers.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum RegisterStyle {
+    /// ABI names (`a0`, `fa0`).
+    #[default]
+    Abi,
+    /// ABI names, except that x8 is called `fp` instead of `s0`.
+    AbiFramePointer,
+    /// Architectural numbers (`x10`, `f10`).
+    Numeric,
+}
+
+/// The number format used to display immediates.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum ImmediateStyle {
+    /// Signed decimal (`-16`, `16`).
+    #[default]
+    Decimal,
+    /// Signed hexadecimal (`-0x10`, `0x10`).
+    Hex,
+    /// Signed decimal with an explicit sign on positive values (`-16`, `+16`).
+    Signed,
+    /// The raw bits of the immediate as an unsigned decimal (`-16` in a 12 bit immediate is `4080`).
+    Unsigned,
+}
@@ -0,0 +1,351 @@
+//! Comparison of the decoder against the riscv-opcodes database.
+//!
+//! The [riscv-opcodes](https://github.com/riscv/riscv-opcodes) repository describes every instruction of an extension
+//! in a file named after it (`rv_i`, `rv64_m`, `rv32_c_f`) with one line per instruction, giving its name, operands
+//! and fixed bit fields:
+//!
+//! ```text
+//! add     rd rs1 rs2 31..25=0  14..12=0 6..2=0x0C 1..0=3
+//! ```
+//!
+//! A coverage report decodes encodings of each instruction in the database to find the instructions that are
+//! missing or decoded as something else, and decodes the whole space of fixed fields to find encodings that are
+//! accepted but not in the database.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::{Display, Formatter};
+use std::path::Path;
+
+use crate::cinstruction::CInstruction;
+use crate::config::{DecoderConfig, Xlen};
+use crate::instruction::Instruction;
+
+/// An instruction of the opcode database.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct OpcodeEntry {
+    /// The mnemonic, with the underscores of the database replaced by dots (`fcvt.w.s`).
+    pub name: String,
+    /// The name of the file the instruction is defined in (`rv64_i`).
+    pub extension: String,
+    /// The base ISA the instruction exists in, `None` if it is in both RV32 and RV64.
+    pub xlen: Option<Xlen>,
+    /// The bits that are fixed in every encoding.
+    pub mask: u32,
+    /// The value of the fixed bits.
+    pub matches: u32,
+    /// The mnemonic of the instruction this is a pseudo-instruction of, pseudo-instructions are only used to accept
+    /// the mnemonics of encodings and are not required to be supported.
+    pub pseudo_of: Option<String>,
+}
+
+impl OpcodeEntry {
+    /// Whether the instruction has a 16-bit encoding.
+    pub fn compressed(&self) -> bool {
+        self.matches & 0b11 != 0b11
+    }
+
+    /// Whether an encoding is an encoding of this instruction.
+    pub fn is_match(&self, word: u32) -> bool {
+        word & self.mask == self.matches
+    }
+
+    /// Whether the instruction exists in a base ISA.
+    pub fn in_xlen(&self, xlen: Xlen) -> bool {
+        self.xlen.is_none_or(|x| x == xlen)
+    }
+}
+
+/// Parses a bit field value, which may be decimal, `0x` prefixed hexadecimal or `0b` prefixed binary.
+fn parse_value(str: &str) -> Option<u32> {
+    if let Some(hex) = str.strip_prefix("0x") {
+        u32::from_str_radix(hex, 16).ok()
+    } else if let Some(binary) = str.strip_prefix("0b") {
+        u32::from_str_radix(binary, 2).ok()
+    } else {
+        str.parse().ok()
+    }
+}
+
+/// Adds a fixed bit field (`14..12=0` or `12=1`) to a mask and match, fields with the value `ignore` are skipped.
+fn add_field(field: &str, mask: &mut u32, matches: &mut u32) -> Option<()> {
+    let (range, value) = field.split_once('=')?;
+    if value == "ignore" {
+        return Some(());
+    }
+    let (high, low) = match range.split_once("..") {
+        Some((high, low)) => (high.parse::<u32>().ok()?, low.parse::<u32>().ok()?),
+        None => (range.parse().ok()?, range.parse().ok()?),
+    };
+    if high < low || high > 31 {
+        return None;
+    }
+    let width = high - low + 1;
+    let value = parse_value(value)?;
+    if width < 32 && value >> width != 0 {
+        return None;
+    }
+    let field_mask = (u32::MAX >> (32 - width)) << low;
+    if *mask & field_mask != 0 {
+        return None;
+    }
+    *mask |= field_mask;
+    *matches |= value << low;
+    Some(())
+}
+
+/// Parses the file of an extension in the opcode database.
+///
+/// `$import` lines are skipped, the imported instructions are expected to be read from the file that defines them.
+pub fn parse_opcodes(extension: &str, text: &str) -> Result<Vec<OpcodeEntry>, String> {
+    let xlen = if extension.starts_with("rv32_") {
+        Some(Xlen::Rv32)
+    } else if extension.starts_with("rv64_") {
+        Some(Xlen::Rv64)
+    } else {
+        None
+    };
+    let mut entries = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.split('#').next().unwrap();
+        let mut tokens = line.split_whitespace();
+        let Some(first) = tokens.next() else {
+            continue;
+        };
+        let (name, pseudo_of) = match first {
+            "$import" => continue,
+            "$pseudo_op" => {
+                let base = tokens.next().and_then(|t| t.split_once("::"));
+                let name = tokens.next();
+                match (base, name) {
+                    (Some((_, base)), Some(name)) => (name, Some(base.replace('_', "."))),
+                    _ => return Err(format!("{extension}:{}: invalid pseudo-op", number + 1)),
+                }
+            }
+            name => (name, None),
+        };
+        let mut mask = 0;
+        let mut matches = 0;
+        for field in tokens.filter(|t| t.contains('=')) {
+            add_field(field, &mut mask, &mut matches)
+                .ok_or_else(|| format!("{extension}:{}: invalid bit field {field}", number + 1))?;
+        }
+        entries.push(OpcodeEntry {
+            name: name.replace('_', "."),
+            extension: extension.to_owned(),
+            xlen,
+            mask,
+            matches,
+            pseudo_of,
+        });
+    }
+    Ok(entries)
+}
+
+/// Reads the extension files of a directory of the opcode database (`extensions` in riscv-opcodes).
+///
+/// Only files named `rv_*`, `rv32_*` and `rv64_*` are read, subdirectories such as `unratified` are not.
+pub fn load_opcodes(dir: &Path) -> Result<Vec<OpcodeEntry>, String> {
+    let read_error =
+        |path: &Path, e: std::io::Error| format!("unable to read {}: {e}", path.display());
+    let mut files = Vec::new();
+    for entry in std::fs::read_dir(dir).map_err(|e| read_error(dir, e))? {
+        let path = entry.map_err(|e| read_error(dir, e))?.path();
+        let Some(name) = path.file_name().and_then(|n| n.to_str()) else {
+            continue;
+        };
+        if path.is_file()
+            && ["rv_", "rv32_", "rv64_"]
+                .iter()
+                .any(|p| name.starts_with(p))
+        {
+            files.push((name.to_owned(), path));
+        }
+    }
+    files.sort();
+    let mut entries = Vec::new();
+    for (name, path) in files {
+        let text = std::fs::read_to_string(&path).map_err(|e| read_error(&path, e))?;
+        entries.extend(parse_opcodes(&name, &text)?);
+    }
+    Ok(entries)
+}
+
+/// An instruction that is decoded as a different instruction.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct CoverageMismatch {
+    pub name: String,
+    pub encoding: u32,
+    /// The disassembly of the encoding.
+    pub decoded: String,
+}
+
+impl Display for CoverageMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} ({:#010x}) decoded as {}",
+            self.name, self.encoding, self.decoded
+        )
+    }
+}
+
+/// The coverage of the instructions of one extension file.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ExtensionCoverage {
+    pub supported: Vec<String>,
+    /// Instructions none of whose encodings are decoded.
+    pub missing: Vec<String>,
+    pub mismatched: Vec<CoverageMismatch>,
+}
+
+/// The coverage of the decoder compared to the opcode database.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct CoverageReport {
+    /// The coverage of each extension file, by name.
+    pub extensions: BTreeMap<String, ExtensionCoverage>,
+    /// The disassembly of one encoding of each mnemonic that is decoded from encodings not in the database.
+    pub extra: Vec<String>,
+}
+
+impl Display for CoverageReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (name, coverage) in &self.extensions {
+            let total =
+                coverage.supported.len() + coverage.missing.len() + coverage.mismatched.len();
+            writeln!(f, "{name}: {}/{total} supported", coverage.supported.len())?;
+            if !coverage.missing.is_empty() {
+                writeln!(f, "    missing: {}", coverage.missing.join(", "))?;
+            }
+            for mismatch in &coverage.mismatched {
+                writeln!(f, "    mismatched: {mismatch}")?;
+            }
+        }
+        if !self.extra.is_empty() {
+            writeln!(f, "extra: {}", self.extra.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Patterns for the bits of an encoding that are not fixed, so that operands that must not be zero (or must be)
+/// are covered by at least one of the encodings tried.
+const FILLS: [u32; 8] = [
+    0,
+    u32::MAX,
+    0x5555_5555,
+    0xaaaa_aaaa,
+    0x3333_3333,
+    0xcccc_cccc,
+    0x0f0f_0f0f,
+    0xf0f0_f0f0,
+];
+
+/// Decodes an encoding of the given size, returning its disassembly.
+fn disassemble(word: u32, compressed: bool, config: &DecoderConfig) -> Option<String> {
+    if compressed {
+        CInstruction::decode_with(word as u16, config)
+            .ok()
+            .map(|c| c.to_string())
+    } else {
+        Instruction::decode_with(word, config)
+            .ok()
+            .map(|i| i.to_string())
+    }
+}
+
+/// Returns the mnemonic of a disassembled instruction without its ordering or rounding mode suffix.
+fn mnemonic(text: &str) -> &str {
+    let mnemonic = text.split(' ').next().unwrap();
+    [
+        ".aqrl", ".aq", ".rl", ".rne", ".rtz", ".rdn", ".rup", ".rmm", ".dyn",
+    ]
+    .iter()
+    .find_map(|s| mnemonic.strip_suffix(s))
+    .unwrap_or(mnemonic)
+}
+
+/// Compares the instructions decoded with a configuration against the opcode database.
+///
+/// Only the instructions of the configured base ISA are compared, so RV32 and RV64 need separate reports. The
+/// extensions enabled in the configuration are not taken into account, instructions of disabled extensions are
+/// reported as missing.
+pub fn coverage(entries: &[OpcodeEntry], config: &DecoderConfig) -> CoverageReport {
+    let entries: Vec<&OpcodeEntry> = entries.iter().filter(|e| e.in_xlen(config.xlen)).collect();
+    // every 32-bit encoding fixes the opcode, so the entries that can match an encoding are found by its opcode
+    let mut by_opcode: BTreeMap<u32, Vec<&OpcodeEntry>> = BTreeMap::new();
+    for entry in &entries {
+        let key = if entry.compressed() {
+            entry.matches & 0b11
+        } else {
+            entry.matches & 0x7f
+        };
+        by_opcode.entry(key).or_default().push(entry);
+    }
+    let accepted = |word: u32, compressed: bool, mnemonic: &str| {
+        let key = if compressed { word & 0b11 } else { word & 0x7f };
+        by_opcode
+            .get(&key)
+            .is_some_and(|e| e.iter().any(|e| e.is_match(word) && e.name == mnemonic))
+    };
+
+    let mut report = CoverageReport::default();
+    for entry in entries.iter().filter(|e| e.pseudo_of.is_none()) {
+        let coverage = report
+            .extensions
+            .entry(entry.extension.clone())
+            .or_default();
+        let mut mismatch = None;
+        let mut supported = false;
+        for fill in FILLS {
+            let word = entry.matches | (fill & !entry.mask);
+            let Some(text) = disassemble(word, entry.compressed(), config) else {
+                continue;
+            };
+            let name = mnemonic(&text);
+            let own = name == entry.name
+                || entries.iter().any(|e| {
+                    e.pseudo_of.as_ref() == Some(&entry.name) && e.name == name && e.is_match(word)
+                });
+            if own {
+                supported = true;
+                break;
+            }
+            if mismatch.is_none() && !accepted(word, entry.compressed(), name) {
+                mismatch = Some(CoverageMismatch {
+                    name: entry.name.clone(),
+                    encoding: word,
+                    decoded: text,
+                });
+            }
+        }
+        match (supported, mismatch) {
+            (true, _) => coverage.supported.push(entry.name.clone()),
+            (false, Some(mismatch)) => coverage.mismatched.push(mismatch),
+            (false, None) => coverage.missing.push(entry.name.clone()),
+        }
+    }
+
+    // the opcode, funct3 and bits 31..20 (funct7 and rs2, or the immediate) select every 32-bit instruction
+    let words = (0..1 << 20).map(|i: u32| {
+        let opcode = (i & 0x1f) << 2 | 0b11;
+        let funct3 = (i >> 5) & 0b111;
+        opcode | funct3 << 12 | (i >> 8) << 20
+    });
+    let mut seen = BTreeSet::new();
+    let mut check = |word: u32, compressed: bool| {
+        if let Some(text) = disassemble(word, compressed, config) {
+            let name = mnemonic(&text).to_owned();
+            if !seen.contains(&name) && !accepted(word, compressed, &name) {
+                seen.insert(name);
+                report.extra.push(text);
+            }
+        }
+    };
+    for word in words {
+        check(word, false);
+    }
+    for half in (0..=u16::MAX).filter(|h| h & 0b11 != 0b11) {
+        check(half as u32, true);
+    }
+    report
+}
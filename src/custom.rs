@@ -0,0 +1,96 @@
+//! Instructions of custom extensions that are decoded outside of this crate.
+//!
+//! The custom-0 and custom-1 major opcodes are left to vendors, and this crate only decodes the XThead extensions in
+//! custom-0. A `CustomHandler` decodes, encodes and formats the instructions of another such extension, and
+//! registering it with `Codec::register_custom` makes the codec decode them as `Instruction::Custom`, taking
+//! precedence over XThead.
+
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::{Hash, Hasher};
+use std::panic::RefUnwindSafe;
+
+use crate::config::Xlen;
+
+/// The custom-0 major opcode.
+pub const CUSTOM_0: u32 = 0b0001011;
+/// The custom-1 major opcode.
+pub const CUSTOM_1: u32 = 0b0101011;
+
+/// Decodes, encodes and formats the instructions of a custom extension.
+///
+/// A handler keeps what it decodes from an encoding in a `u64`, which is given back to `encode` and `fmt`. It is
+/// shared by the instructions it decodes, so it must live for `'static` (a `static` item or a leaked box), and is
+/// `Sync` and `RefUnwindSafe` so that instructions can be sent between threads and used across `catch_unwind`.
+pub trait CustomHandler: Sync + RefUnwindSafe {
+    /// Decodes a word that matches the mask the handler was registered with, or explains why it is not an
+    /// instruction.
+    fn decode(&self, word: u32, xlen: Xlen) -> Result<u64, String>;
+
+    /// Encodes the data of an instruction decoded by `decode`.
+    fn encode(&self, data: u64) -> u32;
+
+    /// Displays the data of an instruction decoded by `decode` (`cust.add a0,a1,a2`).
+    fn fmt(&self, data: u64, f: &mut Formatter<'_>) -> std::fmt::Result;
+}
+
+/// An instruction decoded by a `CustomHandler`, with the data that the handler keeps.
+///
+/// Custom instructions are compared, ordered and hashed by their encoding and data, not by their handler.
+#[derive(Clone, Copy)]
+pub struct CustomInstruction {
+    pub handler: &'static dyn CustomHandler,
+    pub data: u64,
+}
+
+impl CustomInstruction {
+    pub fn encode(&self) -> u32 {
+        self.handler.encode(self.data)
+    }
+
+    fn key(&self) -> (u32, u64) {
+        (self.encode(), self.data)
+    }
+}
+
+impl PartialEq for CustomInstruction {
+    fn eq(&self, other: &Self) -> bool {
+        self.key() == other.key()
+    }
+}
+
+impl Eq for CustomInstruction {}
+
+impl PartialOrd for CustomInstruction {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for CustomInstruction {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+impl Hash for CustomInstruction {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.key().hash(state);
+    }
+}
+
+impl Debug for CustomInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CustomInstruction")
+            .field("encoding", &format_args!("{:#010x}", self.encode()))
+            .field("data", &self.data)
+            .field("display", &format_args!("{self}"))
+            .finish()
+    }
+}
+
+impl Display for CustomInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        self.handler.fmt(self.data, f)
+    }
+}
@@ -0,0 +1,267 @@
+//! Differential testing of the assembler against an external toolchain.
+//!
+//! Random encodings are decoded and disassembled to get valid instruction strings, which are then assembled by both
+//! this crate and an external assembler (llvm-mc or gcc) so that the encodings can be compared.
+
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::process::{Command, Stdio};
+
+use crate::assembly::assemble_line;
+use crate::cinstruction::CInstruction;
+use crate::config::DecoderConfig;
+use crate::elf::Elf;
+use crate::instruction::Instruction;
+
+/// An external assembler that reads assembly from stdin and writes an object file to the path following `-o`.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ExternalAssembler {
+    pub program: PathBuf,
+    pub args: Vec<String>,
+}
+
+impl ExternalAssembler {
+    /// llvm-mc assembling RV64IMAFC.
+    pub fn llvm_mc(program: impl Into<PathBuf>) -> ExternalAssembler {
+        ExternalAssembler {
+            program: program.into(),
+            args: [
+                "--triple=riscv64",
+                "-mattr=+m,+a,+f,+c",
+                "-filetype=obj",
+                "-",
+            ]
+            .map(str::to_owned)
+            .to_vec(),
+        }
+    }
+
+    /// A RISC-V gcc assembling RV64IMAFC.
+    pub fn gcc(program: impl Into<PathBuf>) -> ExternalAssembler {
+        ExternalAssembler {
+            program: program.into(),
+            args: [
+                "-march=rv64imafc_zicsr_zifencei",
+                "-mabi=lp64",
+                "-c",
+                "-x",
+                "assembler",
+                "-",
+            ]
+            .map(str::to_owned)
+            .to_vec(),
+        }
+    }
+
+    /// Assembles a source file, returning the contents of its `.text` section and the error output.
+    fn run(&self, source: &str) -> Result<(Option<Vec<u8>>, String), String> {
+        let output =
+            std::env::temp_dir().join(format!("riscv-codec-difftest-{}.o", std::process::id()));
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .arg("-o")
+            .arg(&output)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("unable to run {}: {e}", self.program.display()))?;
+        std::io::Write::write_all(child.stdin.as_mut().unwrap(), source.as_bytes())
+            .map_err(|e| format!("unable to write to {}: {e}", self.program.display()))?;
+        let result = child.wait_with_output().map_err(|e| e.to_string())?;
+        let stderr = String::from_utf8_lossy(&result.stderr).into_owned();
+        if !result.status.success() {
+            return Ok((None, stderr));
+        }
+        let data = std::fs::read(&output)
+            .map_err(|e| format!("unable to read {}: {e}", output.display()))?;
+        let _ = std::fs::remove_file(&output);
+        let elf = Elf::parse(&data)?;
+        let text = match elf.section(".text") {
+            Some(section) => elf.section_data(section)?.to_vec(),
+            None => Vec::new(),
+        };
+        Ok((Some(text), stderr))
+    }
+}
+
+/// An instruction that assembled differently, or was only accepted by one of the assemblers.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Mismatch {
+    /// The instruction in the syntax of this crate.
+    pub text: String,
+    pub ours: Result<u32, String>,
+    pub theirs: Result<u32, String>,
+}
+
+impl Display for Mismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let show = |result: &Result<u32, String>| match result {
+            Ok(encoding) => format!("{encoding:#010x}"),
+            Err(e) => format!("error ({})", e.trim()),
+        };
+        write!(
+            f,
+            "{}: ours {}, theirs {}",
+            self.text,
+            show(&self.ours),
+            show(&self.theirs)
+        )
+    }
+}
+
+/// A xorshift generator, so that runs can be reproduced from a seed.
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+}
+
+/// Generates `count` random instructions by decoding random encodings, either 32-bit or compressed.
+pub fn random_instructions(
+    seed: u64,
+    count: usize,
+    compressed: bool,
+    config: &DecoderConfig,
+) -> Vec<String> {
+    let mut rng = Rng(seed.max(1));
+    let mut instructions = Vec::new();
+    while instructions.len() < count {
+        let word = rng.next() as u32;
+        let text = if compressed {
+            let half = word as u16;
+            match half & 0b11 {
+                0b11 => None,
+                _ => CInstruction::decode_with(half, config)
+                    .ok()
+                    .map(|c| c.to_string()),
+            }
+        } else {
+            Instruction::decode_with(word | 0b11, config)
+                .ok()
+                .map(|i| i.to_string())
+        };
+        instructions.extend(text);
+    }
+    instructions
+}
+
+/// Converts the syntax of this crate to the standard syntax accepted by GNU and LLVM assemblers.
+///
+/// Atomics take the address register in parentheses after the source (`amoadd.w a0,a2,(a1)`), rounding modes are an
+/// operand instead of a suffix (`fcvt.w.s a0,fa0,rtz`), the immediates of lui and auipc are unsigned and fence.tso has
+/// no operands.
+pub fn standard_syntax(text: &str) -> String {
+    let (mnemonic, operands) = text.split_once(' ').unwrap_or((text, ""));
+    let operands: Vec<&str> = operands.split(',').collect();
+    if mnemonic.starts_with("lr.") {
+        return format!("{mnemonic} {},({})", operands[0], operands[1]);
+    }
+    if mnemonic.starts_with("sc.") || mnemonic.starts_with("amo") {
+        return format!(
+            "{mnemonic} {},{},({})",
+            operands[0], operands[2], operands[1]
+        );
+    }
+    if mnemonic == "fence.tso" {
+        return mnemonic.to_owned();
+    }
+    if (mnemonic == "lui" || mnemonic == "auipc")
+        && let Ok(imm) = operands[1].parse::<i32>()
+    {
+        return format!("{mnemonic} {},{}", operands[0], imm & 0xfffff);
+    }
+    if let Some((base, rm)) = mnemonic.rsplit_once('.')
+        && mnemonic.starts_with('f')
+        && ["rne", "rtz", "rdn", "rup", "rmm", "dyn"].contains(&rm)
+    {
+        return format!("{base} {},{rm}", operands.join(","));
+    }
+    text.to_owned()
+}
+
+/// Returns the line numbers that an assembler reported errors for, with their messages.
+fn error_lines(stderr: &str) -> Vec<(usize, String)> {
+    stderr
+        .lines()
+        .filter_map(|line| {
+            let number = line.split(':').find_map(|field| field.parse().ok())?;
+            Some((number, line.to_owned()))
+        })
+        .collect()
+}
+
+/// Assembles instructions with this crate and an external assembler, returning the instructions whose encodings
+/// differ.
+///
+/// All instructions must have the same length, `compressed` selects whether the external assembler may use
+/// compressed encodings.
+pub fn compare(
+    assembler: &ExternalAssembler,
+    instructions: &[String],
+    compressed: bool,
+) -> Result<Vec<Mismatch>, String> {
+    let ours: Vec<Result<u32, String>> = instructions
+        .iter()
+        .map(|text| Ok(assemble_line(text)?.encode()))
+        .collect();
+
+    // the first line is an option, so instruction n is on line n + 2
+    let option = if compressed {
+        ".option rvc"
+    } else {
+        ".option norvc"
+    };
+    let mut lines: Vec<String> = instructions
+        .iter()
+        .map(|text| standard_syntax(text))
+        .collect();
+    let source = |lines: &[String]| format!("{option}\n{}\n", lines.join("\n"));
+    let mut theirs: Vec<Option<Result<u32, String>>> = vec![None; instructions.len()];
+    let (mut text, stderr) = assembler.run(&source(&lines))?;
+    if text.is_none() {
+        // assemble again without the rejected instructions
+        for (number, message) in error_lines(&stderr) {
+            if let Some(index) = number.checked_sub(2).filter(|i| *i < lines.len()) {
+                theirs[index].get_or_insert(Err(message));
+                lines[index].clear();
+            }
+        }
+        text = assembler.run(&source(&lines))?.0;
+    }
+    let text = text.ok_or_else(|| format!("{} failed: {stderr}", assembler.program.display()))?;
+    let length = if compressed { 2 } else { 4 };
+    let mut encodings = text.chunks(length).map(|chunk| {
+        let mut word = [0; 4];
+        word[..chunk.len()].copy_from_slice(chunk);
+        u32::from_le_bytes(word)
+    });
+    for result in theirs.iter_mut().filter(|result| result.is_none()) {
+        let encoding = encodings
+            .next()
+            .ok_or("the external assembler produced too little code")?;
+        *result = Some(Ok(encoding));
+    }
+
+    Ok(instructions
+        .iter()
+        .zip(ours)
+        .zip(theirs)
+        .filter_map(|((text, ours), theirs)| {
+            let theirs = theirs.unwrap();
+            match (&ours, &theirs) {
+                (Ok(a), Ok(b)) if a == b => None,
+                _ => Some(Mismatch {
+                    text: text.clone(),
+                    ours,
+                    theirs,
+                }),
+            }
+        })
+        .collect())
+}
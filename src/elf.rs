@@ -0,0 +1,797 @@
+//! A minimal reader for little endian ELF files and their DWARF line tables.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+
+use crate::analysis::{Decoded, Symbol, find_frames, listing};
+use crate::config::DecoderConfig;
+
+/// Reads little endian values from a byte slice.
+struct Reader<'a> {
+    data: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn new(data: &'a [u8], position: usize) -> Self {
+        Reader { data, position }
+    }
+
+    fn bytes(&mut self, length: usize) -> Result<&'a [u8], String> {
+        let end = self
+            .position
+            .checked_add(length)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| format!("unexpected end of data at offset {:#x}", self.position))?;
+        let bytes = &self.data[self.position..end];
+        self.position = end;
+        Ok(bytes)
+    }
+
+    /// Reads an unsigned little endian value of `size` bytes (at most 8).
+    fn uint(&mut self, size: usize) -> Result<u64, String> {
+        Ok(self
+            .bytes(size)?
+            .iter()
+            .rev()
+            .fold(0, |value, byte| (value << 8) | *byte as u64))
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.bytes(1)?[0])
+    }
+
+    fn u16(&mut self) -> Result<u16, String> {
+        Ok(self.uint(2)? as u16)
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(self.uint(4)? as u32)
+    }
+
+    fn uleb128(&mut self) -> Result<u64, String> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= ((byte & 0x7f) as u64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn sleb128(&mut self) -> Result<i64, String> {
+        let mut value = 0;
+        let mut shift = 0;
+        loop {
+            let byte = self.u8()?;
+            if shift < 64 {
+                value |= ((byte & 0x7f) as i64) << shift;
+            }
+            shift += 7;
+            if byte & 0x80 == 0 {
+                if shift < 64 && byte & 0x40 != 0 {
+                    value |= -1 << shift;
+                }
+                return Ok(value);
+            }
+        }
+    }
+
+    /// Reads a null terminated string.
+    fn string(&mut self) -> Result<String, String> {
+        let rest = &self.data[self.position.min(self.data.len())..];
+        let length = rest
+            .iter()
+            .position(|b| *b == 0)
+            .ok_or("unterminated string")?;
+        let string = String::from_utf8_lossy(&rest[..length]).into_owned();
+        self.position += length + 1;
+        Ok(string)
+    }
+}
+
+/// Reads the null terminated string at an offset of a string table.
+fn string_at(table: &[u8], offset: u64) -> Result<String, String> {
+    let mut reader = Reader::new(table, offset as usize);
+    if offset as usize >= table.len() {
+        return Err(format!("string offset {offset:#x} is out of bounds"));
+    }
+    reader.string()
+}
+
+/// A section header.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Section {
+    pub name: String,
+    /// The section type (`sh_type`).
+    pub kind: u32,
+    pub flags: u64,
+    pub address: u64,
+    pub offset: u64,
+    pub size: u64,
+    pub link: u32,
+    /// The section that a relocation section applies to (`sh_info`).
+    pub info: u32,
+    /// The alignment of the address in bytes (`sh_addralign`).
+    pub align: u64,
+}
+
+impl Section {
+    /// Whether the section contains instructions (`SHF_EXECINSTR`).
+    pub fn is_code(&self) -> bool {
+        self.flags & 0x4 != 0
+    }
+}
+
+/// A parsed ELF file.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Elf<'a> {
+    pub data: &'a [u8],
+    /// Whether this is an ELF64 (rather than ELF32) file.
+    pub is_64: bool,
+    /// The object file type (`e_type`), `ET_REL` for relocatable objects.
+    pub file_type: u16,
+    pub machine: u16,
+    pub entry: u64,
+    pub sections: Vec<Section>,
+}
+
+/// The `e_machine` value of RISC-V.
+pub const EM_RISCV: u16 = 243;
+
+/// The `e_type` value of relocatable objects, in which every section starts at address 0.
+pub const ET_REL: u16 = 1;
+
+const SHT_SYMTAB: u32 = 2;
+const SHT_RELA: u32 = 4;
+const SHT_NOBITS: u32 = 8;
+
+const R_RISCV_NONE: u64 = 0;
+const R_RISCV_32: u64 = 1;
+const R_RISCV_64: u64 = 2;
+const R_RISCV_ADD8: u64 = 33;
+const R_RISCV_ADD64: u64 = 36;
+const R_RISCV_SUB8: u64 = 37;
+const R_RISCV_SUB64: u64 = 40;
+const R_RISCV_RELAX: u64 = 51;
+
+impl<'a> Elf<'a> {
+    /// Parses the header and section headers of a little endian ELF file.
+    pub fn parse(data: &'a [u8]) -> Result<Elf<'a>, String> {
+        if data.len() < 16 || &data[0..4] != b"\x7fELF" {
+            return Err("not an ELF file".to_owned());
+        }
+        let is_64 = match data[4] {
+            1 => false,
+            2 => true,
+            class => return Err(format!("unknown ELF class {class}")),
+        };
+        if data[5] != 1 {
+            return Err("only little endian ELF files are supported".to_owned());
+        }
+        let word = if is_64 { 8 } else { 4 };
+        let mut reader = Reader::new(data, 16);
+        let file_type = reader.u16()?;
+        let machine = reader.u16()?;
+        reader.u32()?;
+        let entry = reader.uint(word)?;
+        reader.uint(word)?;
+        let section_offset = reader.uint(word)?;
+        // flags, header size, program header size and count
+        reader.u32()?;
+        reader.bytes(6)?;
+        let section_size = reader.u16()? as u64;
+        let section_count = reader.u16()? as u64;
+        let names_index = reader.u16()? as u64;
+
+        let mut sections = Vec::new();
+        let mut names = Vec::new();
+        for i in 0..section_count {
+            let mut reader = Reader::new(data, (section_offset + i * section_size) as usize);
+            names.push(reader.u32()?);
+            let kind = reader.u32()?;
+            let flags = reader.uint(word)?;
+            let address = reader.uint(word)?;
+            let offset = reader.uint(word)?;
+            let size = reader.uint(word)?;
+            let link = reader.u32()?;
+            let info = reader.u32()?;
+            let align = reader.uint(word)?;
+            sections.push(Section {
+                name: String::new(),
+                kind,
+                flags,
+                address,
+                offset,
+                size,
+                link,
+                info,
+                align,
+            });
+        }
+        let mut elf = Elf {
+            data,
+            is_64,
+            file_type,
+            machine,
+            entry,
+            sections,
+        };
+        if let Some(names_section) = elf.sections.get(names_index as usize) {
+            let table = elf.section_data(names_section)?;
+            for (section, name) in elf.sections.iter_mut().zip(names) {
+                section.name = string_at(table, name as u64)?;
+            }
+        }
+        Ok(elf)
+    }
+
+    /// Returns the first section with a name.
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    /// Returns the contents of a section, which is empty for sections without data such as .bss.
+    pub fn section_data(&self, section: &Section) -> Result<&'a [u8], String> {
+        if section.kind == SHT_NOBITS {
+            return Ok(&[]);
+        }
+        self.data
+            .get(section.offset as usize..(section.offset + section.size) as usize)
+            .ok_or_else(|| format!("section {} is out of bounds", section.name))
+    }
+
+    /// Returns the named functions and labels in code sections from the symbol table, sorted by address.
+    ///
+    /// Mapping symbols (`$x`, `$d`) and local labels (`.L`) are skipped.
+    pub fn symbols(&self) -> Result<Vec<Symbol>, String> {
+        let mut symbols: Vec<Symbol> = self
+            .section_symbols()?
+            .into_iter()
+            .map(|(_, symbol)| symbol)
+            .collect();
+        symbols.dedup();
+        Ok(symbols)
+    }
+
+    /// The symbols of `symbols` with the index of the section they are in, which tells apart the symbols of the code
+    /// sections of relocatable objects that all start at address 0.
+    fn section_symbols(&self) -> Result<Vec<(usize, Symbol)>, String> {
+        let mut symbols = Vec::new();
+        for table in self.sections.iter().filter(|s| s.kind == SHT_SYMTAB) {
+            let strings = self
+                .sections
+                .get(table.link as usize)
+                .ok_or("symbol table without string table")?;
+            let strings = self.section_data(strings)?;
+            let data = self.section_data(table)?;
+            let entry_size = if self.is_64 { 24 } else { 16 };
+            for entry in data.chunks_exact(entry_size) {
+                let (name, info, section, address) = self.read_symbol(entry)?;
+                // functions, or labels without a type, in code sections
+                let kind = info & 0xf;
+                let in_code = self
+                    .sections
+                    .get(section as usize)
+                    .is_some_and(Section::is_code);
+                if !in_code || (kind != 0 && kind != 2) {
+                    continue;
+                }
+                let name = string_at(strings, name as u64)?;
+                if name.is_empty() || name.starts_with('$') || name.starts_with(".L") {
+                    continue;
+                }
+                symbols.push((section as usize, Symbol { address, name }));
+            }
+        }
+        symbols.sort_by_key(|(_, s)| s.address);
+        symbols.dedup();
+        Ok(symbols)
+    }
+
+    /// Reads the name offset, `st_info`, section index and value of a symbol table entry.
+    fn read_symbol(&self, entry: &[u8]) -> Result<(u32, u8, u16, u64), String> {
+        let mut reader = Reader::new(entry, 0);
+        let name = reader.u32()?;
+        if self.is_64 {
+            let info = reader.u8()?;
+            reader.u8()?;
+            let section = reader.u16()?;
+            Ok((name, info, section, reader.uint(8)?))
+        } else {
+            let address = reader.uint(4)?;
+            reader.u32()?;
+            let info = reader.u8()?;
+            reader.u8()?;
+            Ok((name, info, reader.u16()?, address))
+        }
+    }
+
+    /// Returns the contents of a section with the relocations of a relocatable object applied, along with the
+    /// section that the symbol of each absolute (`R_RISCV_32` or `R_RISCV_64`) relocation is in, by offset.
+    ///
+    /// Debug sections of relocatable objects refer to strings and code through relocations, and hold zeros until
+    /// they are applied. The sections of other files are returned unchanged.
+    fn relocated_data(&self, index: usize) -> Result<(Vec<u8>, BTreeMap<usize, usize>), String> {
+        let section = self
+            .sections
+            .get(index)
+            .ok_or("section index is out of bounds")?;
+        let mut data = self.section_data(section)?.to_vec();
+        let mut targets = BTreeMap::new();
+        if self.file_type != ET_REL {
+            return Ok((data, targets));
+        }
+        let word = if self.is_64 { 8 } else { 4 };
+        for relocations in self
+            .sections
+            .iter()
+            .filter(|s| s.kind == SHT_RELA && s.info as usize == index)
+        {
+            let symbols = self
+                .sections
+                .get(relocations.link as usize)
+                .ok_or("relocation section without symbol table")?;
+            let symbols = self.section_data(symbols)?;
+            let symbol_size = if self.is_64 { 24 } else { 16 };
+            for entry in self.section_data(relocations)?.chunks_exact(3 * word) {
+                let mut reader = Reader::new(entry, 0);
+                let offset = reader.uint(word)? as usize;
+                let info = reader.uint(word)?;
+                let addend = match reader.uint(word)? {
+                    addend if self.is_64 => addend,
+                    addend => addend as u32 as i32 as u64,
+                };
+                let (symbol, kind) = if self.is_64 {
+                    (info >> 32, info & 0xffff_ffff)
+                } else {
+                    (info >> 8, info & 0xff)
+                };
+                let start = symbol as usize * symbol_size;
+                let entry = symbols
+                    .get(start..start + symbol_size)
+                    .ok_or_else(|| format!("relocation symbol {symbol} is out of bounds"))?;
+                let (_, _, symbol_section, value) = self.read_symbol(entry)?;
+                let value = value.wrapping_add(addend);
+                // the size of the relocated value and the value it is replaced with
+                let size = match kind {
+                    R_RISCV_NONE | R_RISCV_RELAX => continue,
+                    R_RISCV_32 => 4,
+                    R_RISCV_64 => 8,
+                    R_RISCV_ADD8..=R_RISCV_ADD64 => 1 << (kind - R_RISCV_ADD8),
+                    R_RISCV_SUB8..=R_RISCV_SUB64 => 1 << (kind - R_RISCV_SUB8),
+                    kind => {
+                        return Err(format!(
+                            "unsupported relocation type {kind} in {}",
+                            relocations.name
+                        ));
+                    }
+                };
+                let bytes = data
+                    .get_mut(offset..offset.saturating_add(size))
+                    .ok_or_else(|| {
+                        format!(
+                            "relocation offset {offset:#x} is out of bounds of {}",
+                            section.name
+                        )
+                    })?;
+                let current = Reader::new(bytes, 0).uint(size)?;
+                let value = match kind {
+                    R_RISCV_ADD8..=R_RISCV_ADD64 => current.wrapping_add(value),
+                    R_RISCV_SUB8..=R_RISCV_SUB64 => current.wrapping_sub(value),
+                    _ => {
+                        targets.insert(offset, symbol_section as usize);
+                        value
+                    }
+                };
+                bytes.copy_from_slice(&value.to_le_bytes()[..size]);
+            }
+        }
+        Ok((data, targets))
+    }
+
+    /// Decodes the line number programs in `.debug_line` into rows sorted by address.
+    pub fn line_table(&self) -> Result<Vec<LineRow>, String> {
+        let Some(index) = self.sections.iter().position(|s| s.name == ".debug_line") else {
+            return Ok(Vec::new());
+        };
+        let (data, targets) = self.relocated_data(index)?;
+        let line_strings = match self.section(".debug_line_str") {
+            Some(s) => self.section_data(s)?,
+            None => &[],
+        };
+        let strings = match self.section(".debug_str") {
+            Some(s) => self.section_data(s)?,
+            None => &[],
+        };
+        let mut rows = Vec::new();
+        let mut reader = Reader::new(&data, 0);
+        while reader.position < data.len() {
+            parse_line_program(&mut reader, &targets, line_strings, strings, &mut rows)?;
+        }
+        // a sequence can start where another ends, so end rows come first
+        rows.sort_by_key(|r: &LineRow| (r.address, !r.end_sequence));
+        Ok(rows)
+    }
+}
+
+/// A row of a DWARF line table.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct LineRow {
+    pub address: u64,
+    pub file: String,
+    pub line: u64,
+    /// Whether this row marks the first address after a sequence of instructions.
+    pub end_sequence: bool,
+    /// The index of the section that the address is in, for relocatable objects in which every code section starts
+    /// at address 0.
+    pub section: Option<usize>,
+}
+
+const DW_FORM_BLOCK: u64 = 0x09;
+const DW_FORM_DATA1: u64 = 0x0b;
+const DW_FORM_DATA2: u64 = 0x05;
+const DW_FORM_DATA4: u64 = 0x06;
+const DW_FORM_DATA8: u64 = 0x07;
+const DW_FORM_DATA16: u64 = 0x1e;
+const DW_FORM_LINE_STRP: u64 = 0x1f;
+const DW_FORM_STRING: u64 = 0x08;
+const DW_FORM_STRP: u64 = 0x0e;
+const DW_FORM_UDATA: u64 = 0x0f;
+
+const DW_LNCT_PATH: u64 = 1;
+const DW_LNCT_DIRECTORY_INDEX: u64 = 2;
+
+/// The value of an attribute of a directory or file entry, only strings and unsigned numbers are kept.
+enum FormValue {
+    String(String),
+    Number(u64),
+    Other,
+}
+
+fn read_form(
+    reader: &mut Reader,
+    form: u64,
+    offset_size: usize,
+    line_strings: &[u8],
+    strings: &[u8],
+) -> Result<FormValue, String> {
+    Ok(match form {
+        DW_FORM_STRING => FormValue::String(reader.string()?),
+        DW_FORM_LINE_STRP => FormValue::String(string_at(line_strings, reader.uint(offset_size)?)?),
+        DW_FORM_STRP => FormValue::String(string_at(strings, reader.uint(offset_size)?)?),
+        DW_FORM_UDATA => FormValue::Number(reader.uleb128()?),
+        DW_FORM_DATA1 => FormValue::Number(reader.uint(1)?),
+        DW_FORM_DATA2 => FormValue::Number(reader.uint(2)?),
+        DW_FORM_DATA4 => FormValue::Number(reader.uint(4)?),
+        DW_FORM_DATA8 => FormValue::Number(reader.uint(8)?),
+        DW_FORM_DATA16 => {
+            reader.bytes(16)?;
+            FormValue::Other
+        }
+        DW_FORM_BLOCK => {
+            let length = reader.uleb128()?;
+            reader.bytes(length as usize)?;
+            FormValue::Other
+        }
+        form => {
+            return Err(format!(
+                "unsupported DWARF form {form:#x} in line table header"
+            ));
+        }
+    })
+}
+
+/// Reads the DWARF 5 directory or file name table as (path, directory index) pairs.
+fn read_entries(
+    reader: &mut Reader,
+    offset_size: usize,
+    line_strings: &[u8],
+    strings: &[u8],
+) -> Result<Vec<(String, u64)>, String> {
+    let format_count = reader.u8()?;
+    let mut format = Vec::new();
+    for _ in 0..format_count {
+        format.push((reader.uleb128()?, reader.uleb128()?));
+    }
+    let count = reader.uleb128()?;
+    let mut entries = Vec::new();
+    for _ in 0..count {
+        let mut path = String::new();
+        let mut directory = 0;
+        for (content, form) in &format {
+            match (
+                *content,
+                read_form(reader, *form, offset_size, line_strings, strings)?,
+            ) {
+                (DW_LNCT_PATH, FormValue::String(s)) => path = s,
+                (DW_LNCT_DIRECTORY_INDEX, FormValue::Number(n)) => directory = n,
+                _ => {}
+            }
+        }
+        entries.push((path, directory));
+    }
+    Ok(entries)
+}
+
+fn join_path(directory: &str, file: &str) -> String {
+    if directory.is_empty() || file.starts_with('/') {
+        file.to_owned()
+    } else {
+        format!("{directory}/{file}")
+    }
+}
+
+/// Parses one line number program, appending its rows. `targets` gives the section of each relocated address.
+fn parse_line_program(
+    reader: &mut Reader,
+    targets: &BTreeMap<usize, usize>,
+    line_strings: &[u8],
+    strings: &[u8],
+    rows: &mut Vec<LineRow>,
+) -> Result<(), String> {
+    let (length, offset_size) = match reader.u32()? {
+        0xffff_ffff => (reader.uint(8)?, 8),
+        length => (length as u64, 4),
+    };
+    let end = reader.position + length as usize;
+    let version = reader.u16()?;
+    if !(2..=5).contains(&version) {
+        return Err(format!("unsupported DWARF line table version {version}"));
+    }
+    let mut address_size = 8;
+    if version >= 5 {
+        address_size = reader.u8()? as usize;
+        reader.u8()?;
+    }
+    let header_length = reader.uint(offset_size)?;
+    let program_start = reader.position + header_length as usize;
+    let minimum_instruction_length = reader.u8()? as u64;
+    if version >= 4 {
+        reader.u8()?;
+    }
+    let default_is_stmt = reader.u8()? != 0;
+    let line_base = reader.u8()? as i8 as i64;
+    let line_range = reader.u8()? as u64;
+    let opcode_base = reader.u8()?;
+    let mut standard_lengths = Vec::new();
+    for _ in 1..opcode_base {
+        standard_lengths.push(reader.u8()?);
+    }
+    if line_range == 0 {
+        return Err("line table has a line range of zero".to_owned());
+    }
+
+    // file names are indexed from 0 in DWARF 5 and from 1 before
+    let mut files: Vec<String> = Vec::new();
+    if version >= 5 {
+        let directories = read_entries(reader, offset_size, line_strings, strings)?;
+        for (path, directory) in read_entries(reader, offset_size, line_strings, strings)? {
+            let directory = directories
+                .get(directory as usize)
+                .map(|(d, _)| d.as_str())
+                .unwrap_or("");
+            files.push(join_path(directory, &path));
+        }
+    } else {
+        let mut directories = vec![String::new()];
+        loop {
+            let directory = reader.string()?;
+            if directory.is_empty() {
+                break;
+            }
+            directories.push(directory);
+        }
+        files.push(String::new());
+        loop {
+            let path = reader.string()?;
+            if path.is_empty() {
+                break;
+            }
+            let directory = reader.uleb128()?;
+            reader.uleb128()?;
+            reader.uleb128()?;
+            let directory = directories
+                .get(directory as usize)
+                .map(String::as_str)
+                .unwrap_or("");
+            files.push(join_path(directory, &path));
+        }
+    }
+    let file_name = |files: &Vec<String>, index: u64| {
+        files
+            .get(index as usize)
+            .cloned()
+            .unwrap_or_else(|| format!("<file {index}>"))
+    };
+
+    reader.position = program_start;
+    let mut address = 0u64;
+    let mut section = None;
+    let mut file = 1;
+    let mut line = 1u64;
+    let mut is_stmt = default_is_stmt;
+    let mut emit =
+        |address: u64, section: Option<usize>, file: u64, line: u64, end_sequence: bool| {
+            rows.push(LineRow {
+                address,
+                file: file_name(&files, file),
+                line,
+                end_sequence,
+                section,
+            })
+        };
+    while reader.position < end {
+        let opcode = reader.u8()?;
+        if opcode >= opcode_base {
+            let adjusted = (opcode - opcode_base) as u64;
+            address = address.wrapping_add(adjusted / line_range * minimum_instruction_length);
+            line = line.wrapping_add((line_base + (adjusted % line_range) as i64) as u64);
+            if is_stmt {
+                emit(address, section, file, line, false);
+            }
+            continue;
+        }
+        match opcode {
+            0 => {
+                let length = reader.uleb128()? as usize;
+                let start = reader.position;
+                match reader.u8()? {
+                    // end_sequence
+                    1 => {
+                        emit(address, section, file, line, true);
+                        address = 0;
+                        section = None;
+                        file = 1;
+                        line = 1;
+                        is_stmt = default_is_stmt;
+                    }
+                    // set_address
+                    2 => {
+                        section = targets.get(&reader.position).copied();
+                        address = reader.uint(address_size.min(length - 1))?;
+                    }
+                    _ => {}
+                }
+                reader.position = start + length;
+            }
+            // copy
+            1 => {
+                if is_stmt {
+                    emit(address, section, file, line, false);
+                }
+            }
+            // advance_pc
+            2 => address = address.wrapping_add(reader.uleb128()? * minimum_instruction_length),
+            // advance_line
+            3 => line = line.wrapping_add(reader.sleb128()? as u64),
+            // set_file
+            4 => file = reader.uleb128()?,
+            // negate_stmt
+            6 => is_stmt = !is_stmt,
+            // const_add_pc
+            8 => {
+                address = address.wrapping_add(
+                    (255 - opcode_base) as u64 / line_range * minimum_instruction_length,
+                )
+            }
+            // fixed_advance_pc
+            9 => address = address.wrapping_add(reader.u16()? as u64),
+            // opcodes without effects on the rows we keep, skipping their operands
+            _ => {
+                for _ in 0..standard_lengths[opcode as usize - 1] {
+                    reader.uleb128()?;
+                }
+            }
+        }
+    }
+    reader.position = end;
+    Ok(())
+}
+
+/// Options for `disassemble_elf`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct ListingOptions {
+    /// Print a `file:line` annotation from the DWARF line table whenever the source line changes (`objdump -dl`).
+    pub lines: bool,
+    /// Print the source line after each annotation when the source file can be read.
+    pub source: bool,
+    /// Annotate the instructions that allocate and free stack frames (`# prologue, frame=32`).
+    pub frames: bool,
+}
+
+/// Disassembles the code sections of an ELF file in the style of objdump.
+pub fn disassemble_elf(elf: &Elf, options: &ListingOptions) -> Result<String, String> {
+    let config = DecoderConfig {
+        xlen: if elf.is_64 {
+            crate::config::Xlen::Rv64
+        } else {
+            crate::config::Xlen::Rv32
+        },
+        ..Default::default()
+    };
+    let symbols = elf.section_symbols()?;
+    let rows = if options.lines || options.source {
+        elf.line_table()?
+    } else {
+        Vec::new()
+    };
+    let mut sources: BTreeMap<String, Option<Vec<String>>> = BTreeMap::new();
+    let mut out = String::new();
+    let mut last_line = None;
+    for (index, section) in elf.sections.iter().enumerate().filter(|(_, s)| s.is_code()) {
+        let data = elf.section_data(section)?;
+        let rows: Vec<&LineRow> = rows
+            .iter()
+            .filter(|r| r.section.is_none_or(|s| s == index))
+            .collect();
+        writeln!(out, "\nDisassembly of section {}:", section.name).unwrap();
+        let entries = listing(data, section.address, &config);
+        let frames = if options.frames {
+            let code: Vec<Decoded> = entries
+                .iter()
+                .filter_map(|e| {
+                    Some(Decoded {
+                        address: e.address,
+                        length: e.length,
+                        instruction: e.instruction?,
+                    })
+                })
+                .collect();
+            find_frames(&code)
+        } else {
+            BTreeMap::new()
+        };
+        for entry in entries {
+            let address = entry.address;
+            for (_, symbol) in symbols
+                .iter()
+                .filter(|(s, symbol)| *s == index && symbol.address == address)
+            {
+                writeln!(out, "\n{address:016x} <{}>:", symbol.name).unwrap();
+            }
+            // the closest row at or before the address, unless it ends a sequence
+            let row = rows[..rows.partition_point(|r| r.address <= address)]
+                .last()
+                .filter(|r| !r.end_sequence);
+            if let Some(row) = row {
+                let key = (row.file.clone(), row.line);
+                if last_line.as_ref() != Some(&key) {
+                    writeln!(out, "{}:{}", row.file, row.line).unwrap();
+                    if options.source {
+                        let lines = sources.entry(row.file.clone()).or_insert_with(|| {
+                            std::fs::read_to_string(&row.file)
+                                .ok()
+                                .map(|s| s.lines().map(str::to_owned).collect())
+                        });
+                        if let Some(text) = lines
+                            .as_ref()
+                            .and_then(|l| l.get((row.line as usize).wrapping_sub(1)))
+                        {
+                            writeln!(out, "{text}").unwrap();
+                        }
+                    }
+                    last_line = Some(key);
+                }
+            }
+            write!(
+                out,
+                "{:8x}:\t{}          \t{}",
+                entry.address,
+                entry.raw_hex(),
+                entry.text
+            )
+            .unwrap();
+            match frames.get(&address) {
+                Some(marker) => writeln!(out, "\t# {marker}").unwrap(),
+                None => out.push('\n'),
+            }
+        }
+    }
+    Ok(out)
+}
@@ -0,0 +1,353 @@
+//! Generation of machine code from typed instructions, for JITs and emulators that produce code at run time.
+//!
+//! A `CodeBuffer` holds instructions in the order they are emitted. Branches, jumps and auipc sequences may target
+//! labels, which are bound to a position in the buffer before or after the instructions that use them, and their
+//! offsets are filled in by `finish`. With compression enabled, `finish` replaces the instructions that have a
+//! compressed form, including branches and jumps whose final offsets fit, and lays out the code again until the
+//! offsets settle.
+
+use crate::cinstruction::CInstruction;
+use crate::config::Xlen;
+use crate::immediates::{BImmediate, IImmediate, JImmediate, SImmediate, UImmediate};
+use crate::instruction::{Inst, Instruction};
+use crate::register::IRegister;
+
+/// A position in a `CodeBuffer`, created by `CodeBuffer::label` and bound by `CodeBuffer::bind`.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Label(usize);
+
+/// How the offset of an emitted instruction is filled in.
+#[derive(Debug, Clone, Copy)]
+enum Fixup {
+    /// A branch or jal to a label.
+    Target(Label),
+    /// The auipc of a PC-relative sequence, which holds the upper 20 bits of the offset of a label.
+    PcrelHi(Label),
+    /// The instruction after the auipc at an index, which holds the lower 12 bits of the offset.
+    PcrelLo(Label, usize),
+}
+
+/// A buffer of instructions with labels, see the module documentation.
+#[derive(Debug, Clone)]
+pub struct CodeBuffer {
+    xlen: Xlen,
+    compress: bool,
+    instructions: Vec<(Instruction, Option<Fixup>)>,
+    /// The index of the instruction each label is bound before.
+    labels: Vec<Option<usize>>,
+}
+
+/// Returns a branch or jal with its offset replaced.
+fn with_offset(instruction: Instruction, offset: i64) -> Result<Instruction, String> {
+    let b = || BImmediate::try_from(offset);
+    Ok(match instruction {
+        Instruction::JAL { dest, .. } => Instruction::JAL {
+            dest,
+            offset: JImmediate::try_from(offset)?,
+        },
+        Instruction::BEQ { src1, src2, .. } => Instruction::BEQ {
+            src1,
+            src2,
+            offset: b()?,
+        },
+        Instruction::BNE { src1, src2, .. } => Instruction::BNE {
+            src1,
+            src2,
+            offset: b()?,
+        },
+        Instruction::BLT { src1, src2, .. } => Instruction::BLT {
+            src1,
+            src2,
+            offset: b()?,
+        },
+        Instruction::BGE { src1, src2, .. } => Instruction::BGE {
+            src1,
+            src2,
+            offset: b()?,
+        },
+        Instruction::BLTU { src1, src2, .. } => Instruction::BLTU {
+            src1,
+            src2,
+            offset: b()?,
+        },
+        Instruction::BGEU { src1, src2, .. } => Instruction::BGEU {
+            src1,
+            src2,
+            offset: b()?,
+        },
+        i => return Err(format!("{i} is not a branch or jal")),
+    })
+}
+
+/// Returns an instruction that follows an auipc with its 12-bit immediate replaced, if it uses the result of the
+/// auipc as its base.
+fn with_low_offset(instruction: Instruction, base: IRegister, lo: i64) -> Option<Instruction> {
+    let i = || IImmediate::try_from(lo).ok();
+    let s = || SImmediate::try_from(lo).ok();
+    Some(match instruction {
+        Instruction::ADDI { dest, src, .. } if src == base => Instruction::ADDI {
+            dest,
+            src,
+            imm: i()?,
+        },
+        Instruction::JALR { dest, base: b, .. } if b == base => Instruction::JALR {
+            dest,
+            base,
+            offset: i()?,
+        },
+        Instruction::LB { dest, base: b, .. } if b == base => Instruction::LB {
+            dest,
+            base,
+            offset: i()?,
+        },
+        Instruction::LH { dest, base: b, .. } if b == base => Instruction::LH {
+            dest,
+            base,
+            offset: i()?,
+        },
+        Instruction::LW { dest, base: b, .. } if b == base => Instruction::LW {
+            dest,
+            base,
+            offset: i()?,
+        },
+        Instruction::LD { dest, base: b, .. } if b == base => Instruction::LD {
+            dest,
+            base,
+            offset: i()?,
+        },
+        Instruction::LBU { dest, base: b, .. } if b == base => Instruction::LBU {
+            dest,
+            base,
+            offset: i()?,
+        },
+        Instruction::LHU { dest, base: b, .. } if b == base => Instruction::LHU {
+            dest,
+            base,
+            offset: i()?,
+        },
+        Instruction::LWU { dest, base: b, .. } if b == base => Instruction::LWU {
+            dest,
+            base,
+            offset: i()?,
+        },
+        Instruction::FLW { dest, base: b, .. } if b == base => Instruction::FLW {
+            dest,
+            base,
+            offset: i()?,
+        },
+        Instruction::FLQ { dest, base: b, .. } if b == base => Instruction::FLQ {
+            dest,
+            base,
+            offset: i()?,
+        },
+        Instruction::SB { src, base: b, .. } if b == base => Instruction::SB {
+            src,
+            base,
+            offset: s()?,
+        },
+        Instruction::SH { src, base: b, .. } if b == base => Instruction::SH {
+            src,
+            base,
+            offset: s()?,
+        },
+        Instruction::SW { src, base: b, .. } if b == base => Instruction::SW {
+            src,
+            base,
+            offset: s()?,
+        },
+        Instruction::SD { src, base: b, .. } if b == base => Instruction::SD {
+            src,
+            base,
+            offset: s()?,
+        },
+        Instruction::FSW { src, base: b, .. } if b == base => Instruction::FSW {
+            src,
+            base,
+            offset: s()?,
+        },
+        Instruction::FSQ { src, base: b, .. } if b == base => Instruction::FSQ {
+            src,
+            base,
+            offset: s()?,
+        },
+        _ => return None,
+    })
+}
+
+impl CodeBuffer {
+    pub fn new(xlen: Xlen) -> CodeBuffer {
+        CodeBuffer {
+            xlen,
+            compress: false,
+            instructions: Vec::new(),
+            labels: Vec::new(),
+        }
+    }
+
+    /// Enables replacing instructions with their compressed forms in `finish`, which requires the C extension.
+    pub fn compress(&mut self, enabled: bool) {
+        self.compress = enabled;
+    }
+
+    /// The number of instructions emitted, counting each instruction of a PC-relative sequence.
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        self.instructions.len()
+    }
+
+    /// Appends an instruction.
+    pub fn emit(&mut self, instruction: Instruction) {
+        self.instructions.push((instruction, None));
+    }
+
+    /// Creates a label that is not bound to a position yet.
+    pub fn label(&mut self) -> Label {
+        self.labels.push(None);
+        Label(self.labels.len() - 1)
+    }
+
+    /// Binds a label to the position of the next instruction emitted.
+    pub fn bind(&mut self, label: Label) -> Result<(), String> {
+        let position = &mut self.labels[label.0];
+        if position.is_some() {
+            return Err(format!("label {} is already bound", label.0));
+        }
+        *position = Some(self.instructions.len());
+        Ok(())
+    }
+
+    /// Appends a conditional branch or jal to a label, the offset of the instruction is replaced.
+    pub fn branch(&mut self, instruction: Instruction, target: Label) -> Result<(), String> {
+        with_offset(instruction, 0)?;
+        self.instructions
+            .push((instruction, Some(Fixup::Target(target))));
+        Ok(())
+    }
+
+    /// Appends `auipc dest` followed by an instruction that uses dest as its base (addi, jalr, a load or a store),
+    /// together addressing a label anywhere within 2GiB (`la`, `call` and PC-relative loads and stores). The immediate
+    /// of the instruction is replaced.
+    pub fn pc_relative(
+        &mut self,
+        dest: IRegister,
+        instruction: Instruction,
+        target: Label,
+    ) -> Result<(), String> {
+        if with_low_offset(instruction, dest, 0).is_none() {
+            return Err(format!(
+                "{instruction} can not follow an auipc of {dest}, it must be an addi, jalr, load or store based on it"
+            ));
+        }
+        let auipc = Instruction::AUIPC {
+            dest,
+            imm: UImmediate::try_from(0)?,
+        };
+        let index = self.instructions.len();
+        self.instructions
+            .push((auipc, Some(Fixup::PcrelHi(target))));
+        self.instructions
+            .push((instruction, Some(Fixup::PcrelLo(target, index))));
+        Ok(())
+    }
+
+    /// Returns the offset of a label from the start of the code, given the size of each instruction.
+    fn offset(&self, label: Label, sizes: &[u64]) -> Result<i64, String> {
+        let index =
+            self.labels[label.0].ok_or_else(|| format!("label {} is never bound", label.0))?;
+        Ok(sizes[..index].iter().sum::<u64>() as i64)
+    }
+
+    /// Fills in the offset of the instruction at an index, given the size of each instruction.
+    fn resolve(&self, index: usize, sizes: &[u64]) -> Result<Instruction, String> {
+        let (instruction, fixup) = self.instructions[index];
+        let address = |index: usize| sizes[..index].iter().sum::<u64>() as i64;
+        let pcrel = |label, auipc| -> Result<(i64, i64), String> {
+            let offset = self.offset(label, sizes)? - address(auipc);
+            let hi = (offset + 0x800) >> 12;
+            Ok((hi, offset - (hi << 12)))
+        };
+        match fixup {
+            None => Ok(instruction),
+            Some(Fixup::Target(label)) => {
+                with_offset(instruction, self.offset(label, sizes)? - address(index))
+            }
+            Some(Fixup::PcrelHi(label)) => {
+                let (hi, _) = pcrel(label, index)?;
+                let Instruction::AUIPC { dest, .. } = instruction else {
+                    unreachable!()
+                };
+                Ok(Instruction::AUIPC {
+                    dest,
+                    imm: UImmediate::try_from(hi)
+                        .map_err(|_| format!("label {} is out of range of auipc", label.0))?,
+                })
+            }
+            Some(Fixup::PcrelLo(label, auipc)) => {
+                let (_, lo) = pcrel(label, auipc)?;
+                let Instruction::AUIPC { dest, .. } = self.instructions[auipc].0 else {
+                    unreachable!()
+                };
+                Ok(with_low_offset(instruction, dest, lo).unwrap())
+            }
+        }
+    }
+
+    /// Returns the compressed form of the instruction at an index, if compression is enabled and it has one. The
+    /// instructions of PC-relative sequences are never compressed.
+    fn compressed(&self, index: usize, sizes: &[u64]) -> Result<Option<CInstruction>, String> {
+        match self.instructions[index].1 {
+            _ if !self.compress => Ok(None),
+            Some(Fixup::PcrelHi(_) | Fixup::PcrelLo(..)) => Ok(None),
+            _ => Ok(CInstruction::compress(
+                &self.resolve(index, sizes)?,
+                self.xlen,
+            )),
+        }
+    }
+
+    /// Fills in the offsets of branches, jumps and PC-relative sequences and encodes the code.
+    ///
+    /// Instructions start out compressed where they can be and are widened until every offset fits in its compressed
+    /// form, so that the sizes only grow and the layout settles. Branches and jumps that are out of range, and labels
+    /// that are used but never bound, are errors.
+    pub fn finish(&self) -> Result<Vec<u8>, String> {
+        let mut sizes: Vec<u64> = self
+            .instructions
+            .iter()
+            .map(|(instruction, fixup)| {
+                let candidate = match fixup {
+                    Some(Fixup::Target(_)) => with_offset(*instruction, 0).ok(),
+                    Some(_) => None,
+                    None => Some(*instruction),
+                };
+                match candidate {
+                    Some(i) if self.compress && CInstruction::compress(&i, self.xlen).is_some() => {
+                        2
+                    }
+                    _ => 4,
+                }
+            })
+            .collect();
+        loop {
+            let mut changed = false;
+            for index in 0..self.instructions.len() {
+                if sizes[index] == 2 && !matches!(self.compressed(index, &sizes), Ok(Some(_))) {
+                    sizes[index] = 4;
+                    changed = true;
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        let mut bytes = Vec::new();
+        for index in 0..self.instructions.len() {
+            let inst = match sizes[index] {
+                2 => Inst::Compressed(self.compressed(index, &sizes)?.unwrap()),
+                _ => Inst::Full(self.resolve(index, &sizes)?),
+            };
+            bytes.extend(inst.encode_bytes());
+        }
+        Ok(bytes)
+    }
+}
@@ -0,0 +1,114 @@
+//! The error returned when an instruction can not be decoded.
+
+use std::fmt::{Display, Formatter};
+
+/// A failure to decode an instruction.
+///
+/// Along with the message the error keeps the encoding, so that the opcode and function fields the decoder looked at
+/// can be reported, and the offset of the encoding when decoding a slice of bytes.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct DecodeError {
+    /// The encoding, only the low 16 bits are used for compressed instructions. For truncated input or unsupported
+    /// longer instructions this holds the bytes that were available, up to 4.
+    pub raw: u32,
+    /// The length of the encoding in bytes as given by its low bits, 0 when there were no bytes to decode.
+    pub length: usize,
+    /// The offset of the encoding in the decoded slice, see `decode_one` and `decode_all`.
+    pub offset: Option<usize>,
+    pub message: String,
+}
+
+impl DecodeError {
+    /// An error decoding a 32-bit encoding.
+    pub(crate) fn full(raw: u32, message: String) -> DecodeError {
+        DecodeError {
+            raw,
+            length: 4,
+            offset: None,
+            message,
+        }
+    }
+
+    /// An error decoding a compressed encoding.
+    pub(crate) fn compressed(raw: u16, message: String) -> DecodeError {
+        DecodeError {
+            raw: raw as u32,
+            length: 2,
+            offset: None,
+            message,
+        }
+    }
+
+    /// An error decoding the start of a slice that does not hold a supported instruction.
+    pub(crate) fn bytes(bytes: &[u8], length: usize, message: String) -> DecodeError {
+        let mut raw = [0; 4];
+        let available = bytes.len().min(4);
+        raw[..available].copy_from_slice(&bytes[..available]);
+        DecodeError {
+            raw: u32::from_le_bytes(raw),
+            length,
+            offset: None,
+            message,
+        }
+    }
+
+    /// Records the offset of the encoding in the decoded slice.
+    pub(crate) fn at(self, offset: usize) -> DecodeError {
+        DecodeError {
+            offset: Some(offset),
+            ..self
+        }
+    }
+
+    /// The major opcode (bits 6 to 0), or the quadrant (bits 1 and 0) of a compressed encoding.
+    pub fn opcode(&self) -> u32 {
+        if self.length == 2 {
+            self.raw & 0b11
+        } else {
+            self.raw & 0b111_1111
+        }
+    }
+
+    /// Bits 14 to 12, or bits 15 to 13 of a compressed encoding.
+    pub fn funct3(&self) -> u32 {
+        if self.length == 2 {
+            (self.raw >> 13) & 0b111
+        } else {
+            (self.raw >> 12) & 0b111
+        }
+    }
+
+    /// Bits 31 to 25, which compressed encodings do not have.
+    pub fn funct7(&self) -> Option<u32> {
+        (self.length == 4).then_some(self.raw >> 25)
+    }
+
+    /// Describes the error with its offset and the fields of the encoding, for reports from bulk disassembly.
+    pub fn report(&self) -> String {
+        let offset = self.offset.map(|o| format!("{o:#x}: ")).unwrap_or_default();
+        let mut fields = if self.length == 2 {
+            format!("{:04x}: quadrant {:#04b}", self.raw, self.opcode())
+        } else {
+            format!("{:08x}: opcode {:#09b}", self.raw, self.opcode())
+        };
+        fields += &format!(", funct3 {:#05b}", self.funct3());
+        if let Some(funct7) = self.funct7() {
+            fields += &format!(", funct7 {funct7:#09b}");
+        }
+        format!("{offset}{} ({fields})", self.message)
+    }
+}
+
+impl Display for DecodeError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+impl From<DecodeError> for String {
+    fn from(error: DecodeError) -> String {
+        error.message
+    }
+}
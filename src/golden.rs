@@ -0,0 +1,138 @@
+//! Comparison of listings against golden objdump output.
+//!
+//! A corpus is a directory of ELF files, each with the expected disassembly next to it in a file with an added
+//! `.objdump` extension (`hello` and `hello.objdump`). The output of either GNU objdump or llvm-objdump can be used.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+use std::path::{Path, PathBuf};
+
+use crate::elf::{Elf, ListingOptions, disassemble_elf};
+
+/// An instruction that is listed differently from the expected output.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ListingMismatch {
+    pub address: u64,
+    /// The expected encoding and instruction, normalized.
+    pub expected: String,
+    /// The encoding and instruction listed at the address, `None` if there is none.
+    pub actual: Option<String>,
+}
+
+impl Display for ListingMismatch {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{:#x}: expected {}, got {}",
+            self.address,
+            self.expected,
+            self.actual.as_deref().unwrap_or("nothing")
+        )
+    }
+}
+
+/// Parses the instruction lines of a listing into the encoding and normalized text at each address.
+///
+/// Encodings printed as separate bytes (llvm-objdump) are combined into a little endian word, and the text drops
+/// comments and `<symbol>` annotations and has no whitespace after commas.
+fn parse_listing(listing: &str) -> BTreeMap<u64, (String, String)> {
+    let mut instructions = BTreeMap::new();
+    for line in listing.lines() {
+        let Some((address, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let Ok(address) = u64::from_str_radix(address.trim(), 16) else {
+            continue;
+        };
+        let Some((raw, text)) = rest.trim_start().split_once('\t') else {
+            continue;
+        };
+        let bytes: Vec<&str> = raw.split_whitespace().collect();
+        let raw = match bytes[..] {
+            [word] => word.to_owned(),
+            _ if bytes.iter().all(|b| b.len() == 2) => bytes.iter().rev().copied().collect(),
+            _ => continue,
+        };
+        if raw.is_empty() || !raw.chars().all(|c| c.is_ascii_hexdigit()) {
+            continue;
+        }
+        let text = text.split('#').next().unwrap();
+        let text = match text.find(" <") {
+            Some(index) => &text[..index],
+            None => text,
+        };
+        let text = text
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace(", ", ",");
+        instructions.insert(address, (raw, text));
+    }
+    instructions
+}
+
+/// Compares a listing against the expected objdump output, instruction by instruction.
+///
+/// Encodings must always match. Differences in the text of instructions whose expected mnemonic is in `allowlist`
+/// are ignored, which covers known intentional differences such as objdump printing pseudo-instructions or absolute
+/// branch targets.
+pub fn compare_listing(expected: &str, actual: &str, allowlist: &[&str]) -> Vec<ListingMismatch> {
+    let actual = parse_listing(actual);
+    parse_listing(expected)
+        .into_iter()
+        .filter_map(|(address, (raw, text))| {
+            let found = actual.get(&address);
+            let allowed = || {
+                let mnemonic = text.split(' ').next().unwrap();
+                allowlist.contains(&mnemonic)
+            };
+            match found {
+                Some((r, t)) if *r == raw && (*t == text || allowed()) => None,
+                _ => Some(ListingMismatch {
+                    address,
+                    expected: format!("{raw} {text}"),
+                    actual: found.map(|(r, t)| format!("{r} {t}")),
+                }),
+            }
+        })
+        .collect()
+}
+
+/// Checks every ELF file in a corpus directory against its expected objdump output.
+///
+/// Returns the mismatches of each file that has any, files without a `.objdump` file are skipped.
+pub fn check_corpus(
+    dir: &Path,
+    options: &ListingOptions,
+    allowlist: &[&str],
+) -> Result<Vec<(PathBuf, Vec<ListingMismatch>)>, String> {
+    let read_error =
+        |path: &Path, e: std::io::Error| format!("unable to read {}: {e}", path.display());
+    let mut paths = std::fs::read_dir(dir)
+        .map_err(|e| read_error(dir, e))?
+        .map(|entry| entry.map(|e| e.path()))
+        .collect::<Result<Vec<PathBuf>, _>>()
+        .map_err(|e| read_error(dir, e))?;
+    paths.sort();
+    let mut results = Vec::new();
+    for path in paths {
+        if path.extension().is_some_and(|e| e == "objdump") {
+            continue;
+        }
+        let mut golden = path.clone().into_os_string();
+        golden.push(".objdump");
+        let golden = PathBuf::from(golden);
+        if !golden.is_file() {
+            continue;
+        }
+        let expected = std::fs::read_to_string(&golden).map_err(|e| read_error(&golden, e))?;
+        let data = std::fs::read(&path).map_err(|e| read_error(&path, e))?;
+        let elf = Elf::parse(&data).map_err(|e| format!("{}: {e}", path.display()))?;
+        let actual = disassemble_elf(&elf, options)?;
+        let mismatches = compare_listing(&expected, &actual, allowlist);
+        if !mismatches.is_empty() {
+            results.push((path, mismatches));
+        }
+    }
+    Ok(results)
+}
@@ -0,0 +1,84 @@
+//! HTML disassembly listings.
+
+use std::fmt::Write;
+
+use crate::analysis::{Symbol, describe, listing};
+use crate::config::DecoderConfig;
+
+/// Escapes the characters with a special meaning in HTML.
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Disassembles a region of code into a standalone HTML page.
+///
+/// Each instruction is a table row with its address, encoding, mnemonic and operands. Rows are anchored by
+/// address (`#x1000`) so that the targets of jumps and branches can link to them, and each symbol starts a new
+/// heading.
+pub fn html_listing(bytes: &[u8], base: u64, symbols: &[Symbol], config: &DecoderConfig) -> String {
+    let mut symbols = symbols.to_vec();
+    symbols.sort_by_key(|s| s.address);
+    let mut out = String::from(
+        "<!DOCTYPE html>
+<html>
+<head>
+<meta charset=\"utf-8\">
+<title>Disassembly</title>
+<style>
+body { font-family: monospace; }
+td { padding: 0 1em 0 0; }
+.address, .bytes { color: #666; }
+</style>
+</head>
+<body>
+",
+    );
+    let mut in_table = false;
+    for entry in listing(bytes, base, config) {
+        for symbol in symbols.iter().filter(|s| s.address == entry.address) {
+            if in_table {
+                out.push_str("</table>\n");
+                in_table = false;
+            }
+            let name = escape(&symbol.name);
+            writeln!(out, "<h2 id=\"{name}\">{name}</h2>").unwrap();
+        }
+        if !in_table {
+            out.push_str("<table>\n");
+            in_table = true;
+        }
+        let (mnemonic, operands) = entry
+            .text
+            .split_once(' ')
+            .unwrap_or((entry.text.as_str(), ""));
+        let target = entry
+            .instruction
+            .and_then(|i| i.branch_target(entry.address))
+            .map(|target| {
+                let label = match describe(&symbols, target) {
+                    Some(name) => format!("{target:x} &lt;{}&gt;", escape(&name)),
+                    None => format!("{target:x}"),
+                };
+                format!("<a href=\"#x{target:x}\">{label}</a>")
+            })
+            .unwrap_or_default();
+        writeln!(
+            out,
+            "<tr id=\"x{address:x}\"><td class=\"address\">{address:x}</td><td class=\"bytes\">{bytes}</td>\
+<td class=\"mnemonic\">{mnemonic}</td><td class=\"operands\">{operands}</td><td class=\"target\">{target}</td></tr>",
+            address = entry.address,
+            bytes = entry.raw_hex().trim_end(),
+            mnemonic = escape(mnemonic),
+            operands = escape(operands),
+        )
+        .unwrap();
+    }
+    if in_table {
+        out.push_str("</table>\n");
+    }
+    out.push_str("</body>\n</html>\n");
+    out
+}
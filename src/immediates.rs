@@ -1,6 +1,57 @@
+use crate::assembly::parse_int;
+use crate::config::{ImmediateStyle, Xlen};
+use crate::register::RegisterList;
 use riscv_codec_proc_macros::make_immediate;
 use std::fmt::{Display, Formatter};
 
+/// Operations shared by every kind of immediate.
+///
+/// This allows generic code to work with any immediate without knowing its layout.
+pub trait Immediate: Sized + Copy + Display {
+    /// The number of bits in the immediate value (not the number of bits stored in the instruction).
+    const BITS: u32;
+    /// The smallest value of the immediate.
+    const MIN: i64;
+    /// The largest value of the immediate that meets its alignment.
+    const MAX: i64;
+
+    /// Extracts the immediate from an instruction. Compressed immediates use the low 16 bits.
+    fn decode(instruction: u32) -> Self;
+
+    /// Constructs the immediate, checking its range and alignment.
+    fn try_new(value: i64) -> Result<Self, String>;
+
+    /// The value of the immediate.
+    fn value(self) -> i64;
+
+    /// The bits of the immediate positioned as they are in the instruction.
+    fn encode(self) -> u32;
+
+    /// Returns a value that displays this immediate in the given style.
+    fn display_with(self, style: ImmediateStyle) -> ImmediateDisplay<Self> {
+        ImmediateDisplay { imm: self, style }
+    }
+}
+
+/// Displays an immediate using an `ImmediateStyle`, see `Immediate::display_with`.
+pub struct ImmediateDisplay<T: Immediate> {
+    imm: T,
+    style: ImmediateStyle,
+}
+
+impl<T: Immediate> Display for ImmediateDisplay<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let value = self.imm.value();
+        match self.style {
+            ImmediateStyle::Decimal => write!(f, "{value}"),
+            ImmediateStyle::Hex if value < 0 => write!(f, "-{:#x}", -value),
+            ImmediateStyle::Hex => write!(f, "{value:#x}"),
+            ImmediateStyle::Signed => write!(f, "{value:+}"),
+            ImmediateStyle::Unsigned => write!(f, "{}", value & ((1 << T::BITS) - 1)),
+        }
+    }
+}
+
 // name signed compressed (imm_pos size instr pos)+
 make_immediate!(IImmediate true false (0 12 20));
 make_immediate!(SImmediate true false (0 5 7) (5 7 25));
@@ -22,6 +73,299 @@ make_immediate!(CWSPImmediate false true (6 2 2) (2 3 4) (5 1 12));
 make_immediate!(CSDSPImmediate false true (6 3 7) (3 3 10));
 make_immediate!(CSWSPImmediate false true (6 2 7) (2 4 9));
 make_immediate!(C16SPImmediate true true (5 1 2) (7 2 3) (6 1 5) (4 1 6) (9 1 12));
+// nzimm[17:12] of c.lui, stored in the same units as UImmediate
+make_immediate!(CLUIImmediate true true (0 5 2) (5 1 12));
 
 make_immediate!(CSR false false (0 12 20));
 make_immediate!(CSRImmediate false false (0 5 15));
+
+// the offset of the Zicbop prefetches, which is a multiple of 32 in the upper bits of an I-type immediate
+make_immediate!(PrefetchImmediate true false (5 7 25));
+
+// the label of the Zicfilp landing pads, which is compared with the upper 20 bits of x7
+make_immediate!(LpadImmediate false false (0 20 12));
+
+// scalar crypto, the byte select of the RV32 AES instructions and the round number of aes64ks1i
+make_immediate!(Bs false false (0 2 30));
+make_immediate!(Rnum false false (0 4 20));
+
+// vector, the signed and unsigned immediates of the .vi instructions and the 6-bit rotate amount of vror.vi
+make_immediate!(VImmediate true false (0 5 15));
+make_immediate!(VUImmediate false false (0 5 15));
+make_immediate!(VShamt false false (0 5 15) (5 1 26));
+
+// P, the shift amount or clip width of the immediate forms, of which the 8 and 16-bit forms only use 3 and 4 bits
+#[cfg(feature = "p")]
+make_immediate!(PImmediate false false (0 5 20));
+
+// XAndesPerf, see the andes module
+#[cfg(feature = "xandesperf")]
+make_immediate!(NdsBImmediate true false (1 4 8) (5 5 25) (10 1 31));
+#[cfg(feature = "xandesperf")]
+make_immediate!(NdsBitImmediate false false (0 5 20) (5 1 7));
+#[cfg(feature = "xandesperf")]
+make_immediate!(NdsCImmediate false false (0 5 20) (5 1 7) (6 1 30));
+#[cfg(feature = "xandesperf")]
+make_immediate!(NdsMsb false false (0 6 26));
+#[cfg(feature = "xandesperf")]
+make_immediate!(NdsLsb false false (0 6 20));
+
+// XThead, see the thead module, the shift of th.addsl and the offset of the pairs of XTheadMemPair
+#[cfg(feature = "xthead")]
+make_immediate!(ThImm2 false false (0 2 25));
+#[cfg(feature = "xthead")]
+make_immediate!(ThMsb false false (0 6 26));
+#[cfg(feature = "xthead")]
+make_immediate!(ThLsb false false (0 6 20));
+
+/// The number of bytes that the Zcmp push and pop instructions move the stack pointer by.
+///
+/// This is the stack size of the register list, which depends on XLEN, plus 0, 16, 32 or 48 bytes given by the 2-bit
+/// spimm field. Push instructions move the stack pointer down and display the adjustment as negative.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct StackAdjustment {
+    value: u32,
+    spimm: u8,
+}
+
+impl StackAdjustment {
+    /// Constructs the adjustment of a register list, checking that it can be encoded on `xlen`.
+    pub fn new(rlist: RegisterList, value: i64, xlen: Xlen) -> Result<StackAdjustment, String> {
+        let base = rlist.stack_size(xlen) as i64;
+        let extra = value - base;
+        if !(0..=48).contains(&extra) || extra % 16 != 0 {
+            return Err(format!(
+                "stack adjustment of {rlist} must be {base}, {}, {} or {}, got {value}",
+                base + 16,
+                base + 32,
+                base + 48
+            ));
+        }
+        Ok(StackAdjustment::from_spimm(rlist, extra as u16 / 16, xlen))
+    }
+
+    /// Constructs the adjustment of a register list from the spimm field, which must fit in 2 bits.
+    pub fn from_spimm(rlist: RegisterList, spimm: u16, xlen: Xlen) -> StackAdjustment {
+        assert!(spimm < 4, "spimm must fit in 2 bits, got {spimm}");
+        StackAdjustment {
+            value: rlist.stack_size(xlen) + spimm as u32 * 16,
+            spimm: spimm as u8,
+        }
+    }
+
+    /// The number of bytes.
+    pub fn val(self) -> i64 {
+        self.value as i64
+    }
+
+    /// The spimm field positioned as it is in the instruction.
+    pub fn to_u16(self) -> u16 {
+        (self.spimm as u16) << 2
+    }
+}
+
+impl Display for StackAdjustment {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        write!(f, "{}", self.value)
+    }
+}
+
+/// The register group multiplier of a `VType`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum Lmul {
+    MF8,
+    MF4,
+    MF2,
+    M1,
+    M2,
+    M4,
+    M8,
+}
+
+impl Lmul {
+    /// The encoding of the vlmul field.
+    pub fn to_u32(self) -> u32 {
+        match self {
+            Lmul::M1 => 0b000,
+            Lmul::M2 => 0b001,
+            Lmul::M4 => 0b010,
+            Lmul::M8 => 0b011,
+            Lmul::MF8 => 0b101,
+            Lmul::MF4 => 0b110,
+            Lmul::MF2 => 0b111,
+        }
+    }
+}
+
+impl Display for Lmul {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let name = match self {
+            Lmul::MF8 => "mf8",
+            Lmul::MF4 => "mf4",
+            Lmul::MF2 => "mf2",
+            Lmul::M1 => "m1",
+            Lmul::M2 => "m2",
+            Lmul::M4 => "m4",
+            Lmul::M8 => "m8",
+        };
+        f.write_str(name)
+    }
+}
+
+/// The vector type set by vsetvli and vsetivli, which is the immediate of those instructions.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct VType {
+    /// The selected element width in bits (8, 16, 32 or 64).
+    pub sew: u32,
+    pub lmul: Lmul,
+    /// Tail agnostic, otherwise tail undisturbed.
+    pub ta: bool,
+    /// Mask agnostic, otherwise mask undisturbed.
+    pub ma: bool,
+}
+
+impl VType {
+    /// Decodes the immediate of vsetvli (11 bits) or vsetivli (10 bits).
+    pub fn from_u32(bits: u32) -> Result<VType, String> {
+        if bits >> 8 != 0 {
+            return Err(format!("reserved vtype bits set: {bits:#x}"));
+        }
+        let lmul = match bits & 0b111 {
+            0b000 => Lmul::M1,
+            0b001 => Lmul::M2,
+            0b010 => Lmul::M4,
+            0b011 => Lmul::M8,
+            0b101 => Lmul::MF8,
+            0b110 => Lmul::MF4,
+            0b111 => Lmul::MF2,
+            _ => return Err("reserved vlmul: 0b100".to_owned()),
+        };
+        let sew = match (bits >> 3) & 0b111 {
+            x @ 0b000..=0b011 => 8 << x,
+            x => return Err(format!("reserved vsew: {x:#05b}")),
+        };
+        Ok(VType {
+            sew,
+            lmul,
+            ta: bits & 0b100_0000 != 0,
+            ma: bits & 0b1000_0000 != 0,
+        })
+    }
+
+    /// Encodes the vector type as the immediate of vsetvli or vsetivli.
+    pub fn to_u32(self) -> u32 {
+        (self.ma as u32) << 7
+            | (self.ta as u32) << 6
+            | self.sew.trailing_zeros().saturating_sub(3) << 3
+            | self.lmul.to_u32()
+    }
+
+    /// Parses the assembly syntax of a vector type (`e32,m1,ta,ma`), the policies default to undisturbed if they are
+    /// left out.
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(str: &str) -> Result<VType, String> {
+        let mut fields = str.split(',').map(str::trim);
+        let sew = match fields.next() {
+            Some("e8") => 8,
+            Some("e16") => 16,
+            Some("e32") => 32,
+            Some("e64") => 64,
+            _ => {
+                return Err(format!(
+                    "invalid vtype {str}, expected an element width e8-e64"
+                ));
+            }
+        };
+        let lmul = match fields.next() {
+            Some("mf8") => Lmul::MF8,
+            Some("mf4") => Lmul::MF4,
+            Some("mf2") => Lmul::MF2,
+            Some("m1") => Lmul::M1,
+            Some("m2") => Lmul::M2,
+            Some("m4") => Lmul::M4,
+            Some("m8") => Lmul::M8,
+            _ => return Err(format!("invalid vtype {str}, expected an lmul mf8-m8")),
+        };
+        let mut vtype = VType {
+            sew,
+            lmul,
+            ta: false,
+            ma: false,
+        };
+        let ta = fields.next();
+        let ma = fields.next();
+        match ta {
+            Some("ta") => vtype.ta = true,
+            Some("tu") | None => {}
+            Some(x) => return Err(format!("invalid vtype {str}, unknown tail policy {x}")),
+        }
+        match ma {
+            Some("ma") => vtype.ma = true,
+            Some("mu") | None => {}
+            Some(x) => return Err(format!("invalid vtype {str}, unknown mask policy {x}")),
+        }
+        if fields.next().is_some() {
+            return Err(format!("invalid vtype {str}, too many fields"));
+        }
+        Ok(vtype)
+    }
+
+    /// The number of elements in a register group for a vector register length in bits.
+    pub fn vlmax(self, vlen: u32) -> u32 {
+        let elements = vlen / self.sew;
+        match self.lmul {
+            Lmul::MF8 => elements / 8,
+            Lmul::MF4 => elements / 4,
+            Lmul::MF2 => elements / 2,
+            Lmul::M1 => elements,
+            Lmul::M2 => elements * 2,
+            Lmul::M4 => elements * 4,
+            Lmul::M8 => elements * 8,
+        }
+    }
+}
+
+impl Display for VType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        let ta = if self.ta { "ta" } else { "tu" };
+        let ma = if self.ma { "ma" } else { "mu" };
+        write!(f, "e{},{},{ta},{ma}", self.sew, self.lmul)
+    }
+}
+
+/// The immediate of vsetvli and vsetivli, which keeps its bits if the vector type is reserved.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum VTypeImmediate {
+    Valid(VType),
+    /// A reserved vsew or vlmul or reserved bits that are set, which is written as a number (`0x1d0`).
+    Reserved(u32),
+}
+
+impl VTypeImmediate {
+    /// Encodes the immediate of vsetvli or vsetivli.
+    pub fn to_u32(self) -> u32 {
+        match self {
+            VTypeImmediate::Valid(vtype) => vtype.to_u32(),
+            VTypeImmediate::Reserved(bits) => bits,
+        }
+    }
+
+    /// Parses a vector type (`e32,m1,ta,ma`) or its encoding as a number of at most `bits` bits (`0xd0` or `0x1d0`).
+    pub fn from_str(str: &str, bits: u32) -> Result<VTypeImmediate, String> {
+        match parse_int(str.trim()) {
+            Ok(x) if (0..1 << bits).contains(&x) => Ok(VType::from_u32(x as u32)
+                .map_or(VTypeImmediate::Reserved(x as u32), VTypeImmediate::Valid)),
+            Ok(x) => Err(format!("vtype {x:#x} does not fit in {bits} bits")),
+            Err(_) => VType::from_str(str).map(VTypeImmediate::Valid),
+        }
+    }
+}
+
+impl Display for VTypeImmediate {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            VTypeImmediate::Valid(vtype) => vtype.fmt(f),
+            VTypeImmediate::Reserved(bits) => write!(f, "{bits:#x}"),
+        }
+    }
+}
@@ -1,24 +1,40 @@
+#[cfg(feature = "xandesperf")]
+use crate::andes::{self, AndesInstruction};
+use crate::cinstruction::{CInstruction, DecodedCInstruction};
+use crate::config::{
+    AssemblerConfig, DecoderConfig, FormatOptions, ImmediateStyle, RegisterStyle, Xlen,
+};
+use crate::custom::CustomInstruction;
+use crate::error::DecodeError;
 use crate::immediates::{
-    BImmediate, CSR, CSRImmediate, JImmediate, SImmediate, Shamt, ShamtW, UImmediate,
+    BImmediate, Bs, CSR, CSRImmediate, Immediate, JImmediate, LpadImmediate, PrefetchImmediate,
+    Rnum, SImmediate, Shamt, ShamtW, UImmediate,
 };
 use crate::register::{FRegister, IRegister};
+#[cfg(feature = "p")]
+use crate::simd::{self, SimdInstruction};
+#[cfg(feature = "xthead")]
+use crate::thead::{self, TheadInstruction};
+use crate::vector::VectorInstruction;
 use crate::{immediates::IImmediate, opcode::Opcode};
 use std::fmt::{Display, Formatter};
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub enum RoundingMode {
     /// round to nearest, ties to even
-    RNE = 0b000,
+    RNE,
     /// round towards zero
-    RTZ = 0b001,
+    RTZ,
     /// round down
-    RDN = 0b010,
+    RDN,
     /// round up
-    RUP = 0b011,
+    RUP,
     /// round to nearest, ties to max magnitude
-    RMM = 0b100,
+    RMM,
     /// use rounding mode in fcsr
-    DYN = 0b111,
+    DYN,
+    /// one of the reserved encodings (0b101 or 0b110), only produced when the decoder is configured to keep them
+    Reserved(u8),
 }
 
 impl Display for RoundingMode {
@@ -30,6 +46,7 @@ impl Display for RoundingMode {
             RoundingMode::RUP => write!(f, "rup"),
             RoundingMode::RMM => write!(f, "rmm"),
             RoundingMode::DYN => write!(f, "dyn"),
+            RoundingMode::Reserved(x) => write!(f, "reserved{x}"),
         }
     }
 }
@@ -43,9 +60,41 @@ impl RoundingMode {
             0b011 => Ok(RoundingMode::RUP),
             0b100 => Ok(RoundingMode::RMM),
             0b111 => Ok(RoundingMode::DYN),
+            0b101 | 0b110 => Err(format!("reserved rounding mode: {x:#05b}")),
             _ => Err("attempted to create invalid rounding mode".to_owned()),
         }
     }
+
+    /// The suffix of the mnemonic for the rounding mode (`.rtz`), empty if it is printed as an operand or dynamic.
+    pub(crate) fn suffix(&self, options: &FormatOptions) -> String {
+        match self {
+            RoundingMode::DYN => String::new(),
+            _ if options.rounding_mode_operand => String::new(),
+            _ => format!(".{self}"),
+        }
+    }
+
+    /// The last operand for the rounding mode (`,rtz`), empty if it is printed as a suffix or dynamic.
+    pub(crate) fn operand(&self, options: &FormatOptions) -> String {
+        match self {
+            RoundingMode::DYN => String::new(),
+            _ if options.rounding_mode_operand => format!(",{self}"),
+            _ => String::new(),
+        }
+    }
+
+    /// Decodes the rm field of an instruction, keeping reserved values if the configuration allows it.
+    fn decode(x: u32, config: &DecoderConfig) -> Result<RoundingMode, String> {
+        match x {
+            0b101 | 0b110 if config.reserved_rounding_modes => Ok(RoundingMode::Reserved(x as u8)),
+            _ => RoundingMode::from_int(x),
+        }
+    }
+
+    /// Parses the name of a rounding mode or its encoding as a number (`rtz` or `1`).
+    ///
+    /// The reserved encodings are only accepted in the form they are displayed in (`reserved5`).
+    #[allow(clippy::should_implement_trait)]
     pub fn from_str(x: &str) -> Result<RoundingMode, String> {
         match x {
             "rne" => Ok(RoundingMode::RNE),
@@ -54,16 +103,32 @@ impl RoundingMode {
             "rup" => Ok(RoundingMode::RUP),
             "rmm" => Ok(RoundingMode::RMM),
             "dyn" => Ok(RoundingMode::DYN),
-            _ => Err("attempted to create invalid rounding mode".to_owned()),
+            "reserved5" => Ok(RoundingMode::Reserved(0b101)),
+            "reserved6" => Ok(RoundingMode::Reserved(0b110)),
+            _ => match x.parse::<u32>() {
+                Ok(x) => RoundingMode::from_int(x),
+                Err(_) => Err("attempted to create invalid rounding mode".to_owned()),
+            },
         }
     }
 
     pub fn to_u32(self) -> u32 {
-        return (self as u32) << 12;
+        let x = match self {
+            RoundingMode::RNE => 0b000,
+            RoundingMode::RTZ => 0b001,
+            RoundingMode::RDN => 0b010,
+            RoundingMode::RUP => 0b011,
+            RoundingMode::RMM => 0b100,
+            RoundingMode::DYN => 0b111,
+            RoundingMode::Reserved(x) => x as u32 & 0b111,
+        };
+        x << 12
     }
 }
 
-#[derive(Debug, PartialEq)]
+/// Instructions are ordered by the declaration order of their variants and then by their operands, registers are
+/// ordered by number and immediates by value.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub enum Instruction {
     //
     // Instructions from RV32I
@@ -353,6 +418,93 @@ pub enum Instruction {
         src2: IRegister,
     },
     //
+    // Instructions in the draft RV128I base ISA
+    //
+    /// Load Quadword
+    #[cfg(feature = "rv128")]
+    LQ {
+        dest: IRegister,
+        base: IRegister,
+        offset: IImmediate,
+    },
+    /// Load Doubleword Unsigned
+    #[cfg(feature = "rv128")]
+    LDU {
+        dest: IRegister,
+        base: IRegister,
+        offset: IImmediate,
+    },
+    /// Store Quadword
+    #[cfg(feature = "rv128")]
+    SQ {
+        src: IRegister,
+        base: IRegister,
+        offset: SImmediate,
+    },
+    /// Add Immediate (doubleword)
+    #[cfg(feature = "rv128")]
+    ADDID {
+        dest: IRegister,
+        src: IRegister,
+        imm: IImmediate,
+    },
+    /// Left Shift Immediate (doubleword)
+    #[cfg(feature = "rv128")]
+    SLLID {
+        dest: IRegister,
+        src: IRegister,
+        shamt: Shamt,
+    },
+    /// Logical Right Shift Immediate (doubleword)
+    #[cfg(feature = "rv128")]
+    SRLID {
+        dest: IRegister,
+        src: IRegister,
+        shamt: Shamt,
+    },
+    /// Arithmetic Right Shift Immediate (doubleword)
+    #[cfg(feature = "rv128")]
+    SRAID {
+        dest: IRegister,
+        src: IRegister,
+        shamt: Shamt,
+    },
+    /// Add (doubleword)
+    #[cfg(feature = "rv128")]
+    ADDD {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Subtract (doubleword)
+    #[cfg(feature = "rv128")]
+    SUBD {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Left Shift (doubleword)
+    #[cfg(feature = "rv128")]
+    SLLD {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Logical Right Shift (doubleword)
+    #[cfg(feature = "rv128")]
+    SRLD {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Arithmetic Right Shift (doubleword)
+    #[cfg(feature = "rv128")]
+    SRAD {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    //
     // Instructions In M Extension
     //
     /// Multiply
@@ -592,6 +744,150 @@ pub enum Instruction {
         rl: bool,
     },
     //
+    // Instructions In Zabha Extension
+    //
+    AMOSWAPB {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOADDB {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOXORB {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOANDB {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOORB {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOMINB {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOMAXB {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOMINUB {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOMAXUB {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOSWAPH {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOADDH {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOXORH {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOANDH {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOORH {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOMINH {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOMAXH {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOMINUH {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOMAXUH {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    /// Compare and swap a byte, which also requires Zacas. `dest` holds the expected value and receives the old value.
+    AMOCASB {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    AMOCASH {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    //
     // Instructions in F Extension
     //
     FLW {
@@ -757,54 +1053,774 @@ pub enum Instruction {
         rm: RoundingMode,
     },
     //
-    // Instructions in Zicsr Extension
+    // Instructions in Q Extension
     //
-    CSRRW {
-        dest: IRegister,
-        src: IRegister,
-        csr: CSR,
+    FLQ {
+        dest: FRegister,
+        base: IRegister,
+        offset: IImmediate,
     },
-    CSRRS {
-        dest: IRegister,
-        src: IRegister,
-        csr: CSR,
+    FSQ {
+        base: IRegister,
+        src: FRegister,
+        offset: SImmediate,
     },
-    CSRRC {
-        dest: IRegister,
-        src: IRegister,
-        csr: CSR,
+    FMADDQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+        src3: FRegister,
+        rm: RoundingMode,
     },
-    CSRRWI {
-        dest: IRegister,
-        imm: CSRImmediate,
-        csr: CSR,
+    FMSUBQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+        src3: FRegister,
+        rm: RoundingMode,
     },
-    CSRRSI {
-        dest: IRegister,
-        imm: CSRImmediate,
-        csr: CSR,
+    FNMSUBQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+        src3: FRegister,
+        rm: RoundingMode,
     },
-    CSRRCI {
-        dest: IRegister,
-        imm: CSRImmediate,
-        csr: CSR,
+    FNMADDQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+        src3: FRegister,
+        rm: RoundingMode,
     },
-    //
-    // Instructions in Zifencei Extension
-    //
-    FENCEI,
-}
-
-fn aq_rl_suffix(aq: &bool, rl: &bool) -> &'static str {
-    match (aq, rl) {
-        (true, true) => ".aqrl",
-        (true, false) => ".aq",
-        (false, true) => ".rl",
-        (false, false) => "",
-    }
-}
-
-/// puts the aquire bit in the correct location
+    FADDQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+        rm: RoundingMode,
+    },
+    FSUBQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+        rm: RoundingMode,
+    },
+    FMULQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+        rm: RoundingMode,
+    },
+    FDIVQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+        rm: RoundingMode,
+    },
+    FSQRTQ {
+        dest: FRegister,
+        src: FRegister,
+        rm: RoundingMode,
+    },
+    FSGNJQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+    },
+    FSGNJNQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+    },
+    FSGNJXQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+    },
+    FMINQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+    },
+    FMAXQ {
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+    },
+    /// Convert quad to single precision
+    FCVTSQ {
+        dest: FRegister,
+        src: FRegister,
+        rm: RoundingMode,
+    },
+    /// Convert single to quad precision, which is exact
+    FCVTQS {
+        dest: FRegister,
+        src: FRegister,
+        rm: RoundingMode,
+    },
+    /// Convert quad to double precision
+    FCVTDQ {
+        dest: FRegister,
+        src: FRegister,
+        rm: RoundingMode,
+    },
+    /// Convert double to quad precision, which is exact
+    FCVTQD {
+        dest: FRegister,
+        src: FRegister,
+        rm: RoundingMode,
+    },
+    FEQQ {
+        dest: IRegister,
+        src1: FRegister,
+        src2: FRegister,
+    },
+    FLTQ {
+        dest: IRegister,
+        src1: FRegister,
+        src2: FRegister,
+    },
+    FLEQ {
+        dest: IRegister,
+        src1: FRegister,
+        src2: FRegister,
+    },
+    FCLASSQ {
+        dest: IRegister,
+        src: FRegister,
+    },
+    FCVTWQ {
+        dest: IRegister,
+        src: FRegister,
+        rm: RoundingMode,
+    },
+    FCVTWUQ {
+        dest: IRegister,
+        src: FRegister,
+        rm: RoundingMode,
+    },
+    FCVTQW {
+        dest: FRegister,
+        src: IRegister,
+        rm: RoundingMode,
+    },
+    FCVTQWU {
+        dest: FRegister,
+        src: IRegister,
+        rm: RoundingMode,
+    },
+    //
+    // Instructions in Q Extension (RV64)
+    //
+    FCVTLQ {
+        dest: IRegister,
+        src: FRegister,
+        rm: RoundingMode,
+    },
+    FCVTLUQ {
+        dest: IRegister,
+        src: FRegister,
+        rm: RoundingMode,
+    },
+    FCVTQL {
+        dest: FRegister,
+        src: IRegister,
+        rm: RoundingMode,
+    },
+    FCVTQLU {
+        dest: FRegister,
+        src: IRegister,
+        rm: RoundingMode,
+    },
+    //
+    // Instructions in Zfbfmin Extension
+    //
+    FLH {
+        dest: FRegister,
+        base: IRegister,
+        offset: IImmediate,
+    },
+    FSH {
+        base: IRegister,
+        src: FRegister,
+        offset: SImmediate,
+    },
+    FMVXH {
+        dest: IRegister,
+        src: FRegister,
+    },
+    FMVHX {
+        dest: FRegister,
+        src: IRegister,
+    },
+    /// Convert single precision to bfloat16
+    FCVTBF16S {
+        dest: FRegister,
+        src: FRegister,
+        rm: RoundingMode,
+    },
+    /// Convert bfloat16 to single precision, which is exact
+    FCVTSBF16 {
+        dest: FRegister,
+        src: FRegister,
+        rm: RoundingMode,
+    },
+    //
+    // Instructions in Zicsr Extension
+    //
+    CSRRW {
+        dest: IRegister,
+        src: IRegister,
+        csr: CSR,
+    },
+    CSRRS {
+        dest: IRegister,
+        src: IRegister,
+        csr: CSR,
+    },
+    CSRRC {
+        dest: IRegister,
+        src: IRegister,
+        csr: CSR,
+    },
+    CSRRWI {
+        dest: IRegister,
+        imm: CSRImmediate,
+        csr: CSR,
+    },
+    CSRRSI {
+        dest: IRegister,
+        imm: CSRImmediate,
+        csr: CSR,
+    },
+    CSRRCI {
+        dest: IRegister,
+        imm: CSRImmediate,
+        csr: CSR,
+    },
+    //
+    // Instructions in Zifencei Extension
+    //
+    FENCEI,
+    //
+    // Instructions in Zicbom Extension
+    //
+    /// Write a cache block back to memory
+    CBOCLEAN {
+        base: IRegister,
+    },
+    /// Write a cache block back to memory and invalidate it
+    CBOFLUSH {
+        base: IRegister,
+    },
+    /// Invalidate a cache block, which may discard writes to it
+    CBOINVAL {
+        base: IRegister,
+    },
+    //
+    // Instructions in Zicboz Extension
+    //
+    /// Zero a cache block
+    CBOZERO {
+        base: IRegister,
+    },
+    //
+    // Instructions in Zicbop Extension, which are hints in the encoding of ori with a destination of x0
+    //
+    /// Prefetch a cache block for an instruction fetch
+    PREFETCHI {
+        base: IRegister,
+        offset: PrefetchImmediate,
+    },
+    /// Prefetch a cache block for a read
+    PREFETCHR {
+        base: IRegister,
+        offset: PrefetchImmediate,
+    },
+    /// Prefetch a cache block for a write
+    PREFETCHW {
+        base: IRegister,
+        offset: PrefetchImmediate,
+    },
+    //
+    // Instructions in Zihintpause Extension, a hint in the encoding of fence w,0
+    //
+    /// Pause Hint
+    PAUSE,
+    //
+    // Instructions in Zihintntl Extension, hints in the encoding of add with a destination and first source of x0
+    //
+    /// Non-temporal locality hint for the innermost private cache
+    NTLP1,
+    /// Non-temporal locality hint for all private caches
+    NTLPALL,
+    /// Non-temporal locality hint for the innermost shared cache
+    NTLS1,
+    /// Non-temporal locality hint for all caches
+    NTLALL,
+    //
+    // Instructions in Zicfilp Extension, a hint in the encoding of auipc with a destination of x0
+    //
+    /// Landing pad, the target of an indirect jump, which checks its label against x7
+    LPAD {
+        label: LpadImmediate,
+    },
+    //
+    // Instructions in Zicfiss Extension, of which all but ssamoswap use the encodings of Zimop
+    //
+    /// Push ra or t0 to the shadow stack
+    SSPUSH {
+        src: IRegister,
+    },
+    /// Pop the shadow stack and check that it matches ra or t0
+    SSPOPCHK {
+        src: IRegister,
+    },
+    /// Read the shadow stack pointer
+    SSRDP {
+        dest: IRegister,
+    },
+    /// Atomic swap with the shadow stack
+    SSAMOSWAPW {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    SSAMOSWAPD {
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        aq: bool,
+        rl: bool,
+    },
+    //
+    // Privileged Instructions
+    //
+    /// Supervisor Trap Return
+    SRET,
+    /// Machine Trap Return
+    MRET,
+    /// Resumable Non-Maskable Interrupt Return (Smrnmi)
+    MNRET,
+    /// Debug Mode Return (Sdext)
+    DRET,
+    /// Wait for Interrupt
+    WFI,
+    /// Supervisor Memory-Management Fence, x0 for either operand applies the fence to every address or address space
+    SFENCEVMA {
+        vaddr: IRegister,
+        asid: IRegister,
+    },
+    //
+    // Instructions in Zbkb Extension
+    //
+    /// Pack low halves of registers
+    PACK {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Pack low bytes of registers
+    PACKH {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Reverse bits in each byte
+    BREV8 {
+        dest: IRegister,
+        src: IRegister,
+    },
+    /// AND with inverted operand
+    ANDN {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// OR with inverted operand
+    ORN {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Exclusive NOR
+    XNOR {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Rotate left (register)
+    ROL {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Rotate right (register)
+    ROR {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Rotate right (immediate)
+    RORI {
+        dest: IRegister,
+        src: IRegister,
+        shamt: Shamt,
+    },
+    //
+    // Instructions in Zbkb Extension (RV32)
+    //
+    /// Interleave the bits of the low and high halves
+    ZIP {
+        dest: IRegister,
+        src: IRegister,
+    },
+    /// Deinterleave the bits into the low and high halves
+    UNZIP {
+        dest: IRegister,
+        src: IRegister,
+    },
+    /// Byte-reverse register, which has a different encoding on RV64
+    REV8RV32 {
+        dest: IRegister,
+        src: IRegister,
+    },
+    //
+    // Instructions in Zbkb Extension (RV64)
+    //
+    /// Pack low 16-bits of registers (word)
+    PACKW {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Rotate left word (register)
+    ROLW {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Rotate right word (register)
+    RORW {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Rotate right word (immediate)
+    RORIW {
+        dest: IRegister,
+        src: IRegister,
+        shamt: ShamtW,
+    },
+    /// Byte-reverse register
+    REV8 {
+        dest: IRegister,
+        src: IRegister,
+    },
+    //
+    // Instructions in Zbkc Extension
+    //
+    /// Carry-less Multiply (low bits)
+    CLMUL {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Carry-less Multiply (high bits)
+    CLMULH {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    //
+    // Instructions in Zbkx Extension
+    //
+    /// Crossbar Permutation (nibbles)
+    XPERM4 {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Crossbar Permutation (bytes)
+    XPERM8 {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    //
+    // Instructions in Zkne Extension (RV32)
+    //
+    /// AES final round encryption
+    AES32ESI {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+        bs: Bs,
+    },
+    /// AES middle round encryption
+    AES32ESMI {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+        bs: Bs,
+    },
+    //
+    // Instructions in Zknd Extension (RV32)
+    //
+    /// AES final round decryption
+    AES32DSI {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+        bs: Bs,
+    },
+    /// AES middle round decryption
+    AES32DSMI {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+        bs: Bs,
+    },
+    //
+    // Instructions in Zkne Extension (RV64)
+    //
+    /// AES final round encryption
+    AES64ES {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// AES middle round encryption
+    AES64ESM {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    //
+    // Instructions in Zknd Extension (RV64)
+    //
+    /// AES final round decryption
+    AES64DS {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// AES middle round decryption
+    AES64DSM {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// AES decryption key schedule inverse MixColumns
+    AES64IM {
+        dest: IRegister,
+        src: IRegister,
+    },
+    //
+    // Instructions in Zkne and Zknd Extensions (RV64)
+    //
+    /// AES key schedule (first step)
+    AES64KS1I {
+        dest: IRegister,
+        src: IRegister,
+        rnum: Rnum,
+    },
+    /// AES key schedule (second step)
+    AES64KS2 {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    //
+    // Instructions in Zknh Extension
+    //
+    /// SHA2-256 sigma0
+    SHA256SIG0 {
+        dest: IRegister,
+        src: IRegister,
+    },
+    /// SHA2-256 sigma1
+    SHA256SIG1 {
+        dest: IRegister,
+        src: IRegister,
+    },
+    /// SHA2-256 sum0
+    SHA256SUM0 {
+        dest: IRegister,
+        src: IRegister,
+    },
+    /// SHA2-256 sum1
+    SHA256SUM1 {
+        dest: IRegister,
+        src: IRegister,
+    },
+    //
+    // Instructions in Zknh Extension (RV32)
+    //
+    /// SHA2-512 sum0 (low and high halves)
+    SHA512SUM0R {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// SHA2-512 sum1 (low and high halves)
+    SHA512SUM1R {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// SHA2-512 sigma0 (low half)
+    SHA512SIG0L {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// SHA2-512 sigma0 (high half)
+    SHA512SIG0H {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// SHA2-512 sigma1 (low half)
+    SHA512SIG1L {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// SHA2-512 sigma1 (high half)
+    SHA512SIG1H {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    //
+    // Instructions in Zknh Extension (RV64)
+    //
+    /// SHA2-512 sigma0
+    SHA512SIG0 {
+        dest: IRegister,
+        src: IRegister,
+    },
+    /// SHA2-512 sigma1
+    SHA512SIG1 {
+        dest: IRegister,
+        src: IRegister,
+    },
+    /// SHA2-512 sum0
+    SHA512SUM0 {
+        dest: IRegister,
+        src: IRegister,
+    },
+    /// SHA2-512 sum1
+    SHA512SUM1 {
+        dest: IRegister,
+        src: IRegister,
+    },
+    //
+    // Instructions in the Zalasr proposal, loads always acquire and stores always release
+    //
+    #[cfg(feature = "zalasr")]
+    LBAQ {
+        dest: IRegister,
+        addr: IRegister,
+        rl: bool,
+    },
+    #[cfg(feature = "zalasr")]
+    LHAQ {
+        dest: IRegister,
+        addr: IRegister,
+        rl: bool,
+    },
+    #[cfg(feature = "zalasr")]
+    LWAQ {
+        dest: IRegister,
+        addr: IRegister,
+        rl: bool,
+    },
+    #[cfg(feature = "zalasr")]
+    LDAQ {
+        dest: IRegister,
+        addr: IRegister,
+        rl: bool,
+    },
+    #[cfg(feature = "zalasr")]
+    SBRL {
+        src: IRegister,
+        addr: IRegister,
+        aq: bool,
+    },
+    #[cfg(feature = "zalasr")]
+    SHRL {
+        src: IRegister,
+        addr: IRegister,
+        aq: bool,
+    },
+    #[cfg(feature = "zalasr")]
+    SWRL {
+        src: IRegister,
+        addr: IRegister,
+        aq: bool,
+    },
+    #[cfg(feature = "zalasr")]
+    SDRL {
+        src: IRegister,
+        addr: IRegister,
+        aq: bool,
+    },
+    //
+    // V extension, see the vector module
+    //
+    Vector(VectorInstruction),
+    //
+    // The draft P extension, see the simd module
+    //
+    #[cfg(feature = "p")]
+    P(SimdInstruction),
+    //
+    // Vendor extensions
+    //
+    #[cfg(feature = "xandesperf")]
+    XAndesPerf(AndesInstruction),
+    #[cfg(feature = "xthead")]
+    XThead(TheadInstruction),
+    /// An instruction of a custom extension, decoded by a handler registered with `Codec::register_custom`
+    Custom(CustomInstruction),
+}
+
+fn aq_rl_suffix(aq: &bool, rl: &bool) -> &'static str {
+    match (aq, rl) {
+        (true, true) => ".aqrl",
+        (true, false) => ".aq",
+        (false, true) => ".rl",
+        (false, false) => "",
+    }
+}
+
+/// Formats the operands of lr, sc, the AMOs and the Zalasr loads and stores, with the address in parentheses after
+/// the source if `options.parenthesized_atomics` is set.
+fn atomic_operands(
+    dest: IRegister,
+    addr: IRegister,
+    src: Option<IRegister>,
+    options: &FormatOptions,
+) -> String {
+    let dest = dest.display_with(options.register_style);
+    let addr = addr.display_with(options.register_style);
+    match (
+        src.map(|src| src.display_with(options.register_style)),
+        options.parenthesized_atomics,
+    ) {
+        (Some(src), false) => format!("{dest},{addr},{src}"),
+        (Some(src), true) => format!("{dest},{src},({addr})"),
+        (None, false) => format!("{dest},{addr}"),
+        (None, true) => format!("{dest},({addr})"),
+    }
+}
+
+/// puts the aquire bit in the correct location
 fn aqb(aq: bool) -> u32 {
     if aq { 1 << 26 } else { 0 }
 }
@@ -814,263 +1830,1434 @@ fn rlb(rl: bool) -> u32 {
     if rl { 1 << 25 } else { 0 }
 }
 
+/// Formats a U-type immediate according to the given options.
+fn fmt_u_immediate(imm: &UImmediate, options: &FormatOptions) -> String {
+    if options.hex_u_immediates {
+        format!("{:#x}", imm.as_u32())
+    } else {
+        format!("{}", imm.display_with(options.immediate_style))
+    }
+}
+
 impl Display for Instruction {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.fmt_with(f, &FormatOptions::default())
+    }
+}
+
+/// Displays an `Instruction` using a set of `FormatOptions`, see `Instruction::display_with`.
+pub struct InstructionDisplay<'a> {
+    instruction: &'a Instruction,
+    options: &'a FormatOptions,
+}
+
+impl Display for InstructionDisplay<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if let Some(width) = self.options.mnemonic_width {
+            let unpadded = FormatOptions {
+                mnemonic_width: None,
+                ..*self.options
+            };
+            let text = self.instruction.display_with(&unpadded).to_string();
+            return match text.split_once(' ') {
+                Some((mnemonic, operands)) => {
+                    write!(f, "{:<width$}{operands}", format!("{mnemonic} "))
+                }
+                None => f.write_str(&text),
+            };
+        }
+        if self.options.pseudo_instructions
+            && let Some(pseudo) = self.instruction.pseudo(self.options)
+        {
+            return f.write_str(&pseudo);
+        }
+        self.instruction.fmt_with(f, self.options)
+    }
+}
+
+impl Instruction {
+    /// Returns a value that displays this instruction using the given options.
+    pub fn display_with<'a>(&'a self, options: &'a FormatOptions) -> InstructionDisplay<'a> {
+        InstructionDisplay {
+            instruction: self,
+            options,
+        }
+    }
+
+    /// Returns the target of a direct jump or branch located at `address`.
+    pub fn branch_target(&self, address: u64) -> Option<u64> {
+        match self {
+            Instruction::JAL { offset, .. } => Some(address.wrapping_add(offset.val() as u64)),
+            Instruction::BEQ { offset, .. }
+            | Instruction::BNE { offset, .. }
+            | Instruction::BLT { offset, .. }
+            | Instruction::BGE { offset, .. }
+            | Instruction::BLTU { offset, .. }
+            | Instruction::BGEU { offset, .. } => Some(address.wrapping_add(offset.val() as u64)),
+            #[cfg(feature = "xandesperf")]
+            Instruction::XAndesPerf(i) => i.branch_target(address),
+            _ => None,
+        }
+    }
+
+    /// Whether the instruction only exists on RV128, such as lq, ldu and addid.
+    #[cfg(feature = "rv128")]
+    pub fn is_rv128_only(&self) -> bool {
+        matches!(
+            self,
+            Instruction::LQ { .. }
+                | Instruction::LDU { .. }
+                | Instruction::SQ { .. }
+                | Instruction::ADDID { .. }
+                | Instruction::SLLID { .. }
+                | Instruction::SRLID { .. }
+                | Instruction::SRAID { .. }
+                | Instruction::ADDD { .. }
+                | Instruction::SUBD { .. }
+                | Instruction::SLLD { .. }
+                | Instruction::SRLD { .. }
+                | Instruction::SRAD { .. }
+        )
+    }
+
+    /// Whether the instruction only exists on RV64, such as addiw, lwu, the doubleword atomics and the conversions
+    /// from and to 64-bit integers. ld and sd are not included, since Zilsd adds them to RV32.
+    pub fn is_rv64_only(&self) -> bool {
+        matches!(
+            self,
+            Instruction::LWU { .. }
+                | Instruction::ADDIW { .. }
+                | Instruction::SLLIW { .. }
+                | Instruction::SRLIW { .. }
+                | Instruction::SRAIW { .. }
+                | Instruction::ADDW { .. }
+                | Instruction::SUBW { .. }
+                | Instruction::SLLW { .. }
+                | Instruction::SRLW { .. }
+                | Instruction::SRAW { .. }
+                | Instruction::MULW { .. }
+                | Instruction::DIVW { .. }
+                | Instruction::DIVUW { .. }
+                | Instruction::REMW { .. }
+                | Instruction::REMUW { .. }
+                | Instruction::LRD { .. }
+                | Instruction::SCD { .. }
+                | Instruction::AMOSWAPD { .. }
+                | Instruction::AMOADDD { .. }
+                | Instruction::AMOXORD { .. }
+                | Instruction::AMOANDD { .. }
+                | Instruction::AMOORD { .. }
+                | Instruction::AMOMIND { .. }
+                | Instruction::AMOMAXD { .. }
+                | Instruction::AMOMINUD { .. }
+                | Instruction::AMOMAXUD { .. }
+                | Instruction::SSAMOSWAPD { .. }
+                | Instruction::FCVTLS { .. }
+                | Instruction::FCVTLUS { .. }
+                | Instruction::FCVTSL { .. }
+                | Instruction::FCVTSLU { .. }
+                | Instruction::FCVTLQ { .. }
+                | Instruction::FCVTLUQ { .. }
+                | Instruction::FCVTQL { .. }
+                | Instruction::FCVTQLU { .. }
+                | Instruction::PACKW { .. }
+                | Instruction::ROLW { .. }
+                | Instruction::RORW { .. }
+                | Instruction::RORIW { .. }
+                | Instruction::REV8 { .. }
+                | Instruction::AES64ES { .. }
+                | Instruction::AES64ESM { .. }
+                | Instruction::AES64DS { .. }
+                | Instruction::AES64DSM { .. }
+                | Instruction::AES64IM { .. }
+                | Instruction::AES64KS1I { .. }
+                | Instruction::AES64KS2 { .. }
+                | Instruction::SHA512SIG0 { .. }
+                | Instruction::SHA512SIG1 { .. }
+                | Instruction::SHA512SUM0 { .. }
+                | Instruction::SHA512SUM1 { .. }
+        )
+    }
+
+    /// Returns the first integer register of the instruction that RVE does not have (x16-x31). With `zfinx` the float
+    /// registers are integer registers as well.
+    pub fn rve_register(&self, zfinx: bool) -> Option<IRegister> {
+        let options = FormatOptions {
+            register_style: RegisterStyle::Numeric,
+            zfinx,
+            ..Default::default()
+        };
+        self.display_with(&options)
+            .to_string()
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .filter_map(|token| token.strip_prefix('x')?.parse::<u32>().ok())
+            .find(|&number| number >= 16)
+            .map(IRegister::from_int)
+    }
+
+    /// Returns the pseudo-instruction an instruction is commonly written as, such as `mv`, `li`, `ret` or `beqz`.
+    fn pseudo(&self, options: &FormatOptions) -> Option<String> {
+        use IRegister::{ReturnAddress as Ra, Zero};
+        let r = options.register_style;
+        let i = options.immediate_style;
+        Some(match *self {
+            Instruction::ADDI {
+                dest: Zero,
+                src: Zero,
+                imm,
+            } if imm.val() == 0 => "nop".to_owned(),
+            Instruction::ADDI {
+                dest,
+                src: Zero,
+                imm,
+            } => format!("li {},{}", dest.display_with(r), imm.display_with(i)),
+            Instruction::ADDI { dest, src, imm } if imm.val() == 0 => {
+                format!("mv {},{}", dest.display_with(r), src.display_with(r))
+            }
+            Instruction::XORI { dest, src, imm } if imm.val() == -1 => {
+                format!("not {},{}", dest.display_with(r), src.display_with(r))
+            }
+            Instruction::SUB {
+                dest,
+                src1: Zero,
+                src2,
+            } => format!("neg {},{}", dest.display_with(r), src2.display_with(r)),
+            Instruction::SUBW {
+                dest,
+                src1: Zero,
+                src2,
+            } => format!("negw {},{}", dest.display_with(r), src2.display_with(r)),
+            Instruction::ADDIW { dest, src, imm } if imm.val() == 0 => {
+                format!("sext.w {},{}", dest.display_with(r), src.display_with(r))
+            }
+            Instruction::SLTIU { dest, src, imm } if imm.val() == 1 => {
+                format!("seqz {},{}", dest.display_with(r), src.display_with(r))
+            }
+            Instruction::SLTU {
+                dest,
+                src1: Zero,
+                src2,
+            } => format!("snez {},{}", dest.display_with(r), src2.display_with(r)),
+            Instruction::SLT {
+                dest,
+                src1,
+                src2: Zero,
+            } => format!("sltz {},{}", dest.display_with(r), src1.display_with(r)),
+            Instruction::SLT {
+                dest,
+                src1: Zero,
+                src2,
+            } => format!("sgtz {},{}", dest.display_with(r), src2.display_with(r)),
+            Instruction::BEQ {
+                src1,
+                src2: Zero,
+                offset,
+            } => format!("beqz {},{}", src1.display_with(r), offset.display_with(i)),
+            Instruction::BNE {
+                src1,
+                src2: Zero,
+                offset,
+            } => format!("bnez {},{}", src1.display_with(r), offset.display_with(i)),
+            Instruction::BGE {
+                src1: Zero,
+                src2,
+                offset,
+            } => format!("blez {},{}", src2.display_with(r), offset.display_with(i)),
+            Instruction::BGE {
+                src1,
+                src2: Zero,
+                offset,
+            } => format!("bgez {},{}", src1.display_with(r), offset.display_with(i)),
+            Instruction::BLT {
+                src1,
+                src2: Zero,
+                offset,
+            } => format!("bltz {},{}", src1.display_with(r), offset.display_with(i)),
+            Instruction::BLT {
+                src1: Zero,
+                src2,
+                offset,
+            } => format!("bgtz {},{}", src2.display_with(r), offset.display_with(i)),
+            Instruction::JAL { dest: Zero, offset } => format!("j {}", offset.display_with(i)),
+            Instruction::JAL { dest: Ra, offset } => format!("jal {}", offset.display_with(i)),
+            Instruction::JALR {
+                dest: Zero,
+                base: Ra,
+                offset,
+            } if offset.val() == 0 => "ret".to_owned(),
+            Instruction::JALR {
+                dest: Zero,
+                base,
+                offset,
+            } if offset.val() == 0 => format!("jr {}", base.display_with(r)),
+            Instruction::JALR {
+                dest: Ra,
+                base,
+                offset,
+            } if offset.val() == 0 => format!("jalr {}", base.display_with(r)),
+            Instruction::FSGNJS { dest, src1, src2 } if src1 == src2 => {
+                format!(
+                    "fmv.s {},{}",
+                    dest.display_with_options(options),
+                    src1.display_with_options(options)
+                )
+            }
+            Instruction::FSGNJNS { dest, src1, src2 } if src1 == src2 => {
+                format!(
+                    "fneg.s {},{}",
+                    dest.display_with_options(options),
+                    src1.display_with_options(options)
+                )
+            }
+            Instruction::FSGNJXS { dest, src1, src2 } if src1 == src2 => {
+                format!(
+                    "fabs.s {},{}",
+                    dest.display_with_options(options),
+                    src1.display_with_options(options)
+                )
+            }
+            Instruction::SFENCEVMA {
+                vaddr: Zero,
+                asid: Zero,
+            } => "sfence.vma".to_owned(),
+            Instruction::SFENCEVMA { vaddr, asid: Zero } => {
+                format!("sfence.vma {}", vaddr.display_with(r))
+            }
+            _ => return None,
+        })
+    }
+
+    fn fmt_with(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &FormatOptions,
+    ) -> Result<(), std::fmt::Error> {
         match self {
-            Instruction::LUI { dest, imm } => write!(f, "lui {dest},{imm}"),
-            Instruction::AUIPC { dest, imm } => write!(f, "auipc {dest},{imm}"),
-            Instruction::JAL { dest, offset } => write!(f, "jal {dest},{offset}"),
-            Instruction::JALR { dest, base, offset } => write!(f, "jalr {dest},{offset}({base})"),
-            Instruction::BEQ { src1, src2, offset } => write!(f, "beq {src1},{src2},{offset}"),
-            Instruction::BNE { src1, src2, offset } => write!(f, "bne {src1},{src2},{offset}"),
-            Instruction::BLT { src1, src2, offset } => write!(f, "blt {src1},{src2},{offset}"),
-            Instruction::BGE { src1, src2, offset } => write!(f, "bge {src1},{src2},{offset}"),
-            Instruction::BLTU { src1, src2, offset } => write!(f, "bltu {src1},{src2},{offset}"),
-            Instruction::BGEU { src1, src2, offset } => write!(f, "bgeu {src1},{src2},{offset}"),
-            Instruction::LB { dest, base, offset } => write!(f, "lb {dest},{offset}({base})"),
-            Instruction::LH { dest, base, offset } => write!(f, "lh {dest},{offset}({base})"),
-            Instruction::LW { dest, base, offset } => write!(f, "lw {dest},{offset}({base})"),
-            Instruction::LBU { dest, base, offset } => write!(f, "lbu {dest},{offset}({base})"),
-            Instruction::LHU { dest, base, offset } => write!(f, "lhu {dest},{offset}({base})"),
-            Instruction::SB { src, base, offset } => write!(f, "sb {src},{offset}({base})"),
-            Instruction::SH { src, base, offset } => write!(f, "sh {src},{offset}({base})"),
-            Instruction::SW { src, base, offset } => write!(f, "sw {src},{offset}({base})"),
-            Instruction::ADDI { dest, src, imm } => write!(f, "addi {dest},{src},{imm}"),
-            Instruction::SLTI { dest, src, imm } => write!(f, "slti {dest},{src},{imm}"),
-            Instruction::SLTIU { dest, src, imm } => write!(f, "sltiu {dest},{src},{imm}"),
-            Instruction::XORI { dest, src, imm } => write!(f, "xori {dest},{src},{imm}"),
-            Instruction::ORI { dest, src, imm } => write!(f, "ori {dest},{src},{imm}"),
-            Instruction::ANDI { dest, src, imm } => write!(f, "andi {dest},{src},{imm}"),
-            Instruction::SLLI { dest, src, shamt } => write!(f, "slli {dest},{src},{shamt}"),
-            Instruction::SRLI { dest, src, shamt } => write!(f, "srli {dest},{src},{shamt}"),
-            Instruction::SRAI { dest, src, shamt } => write!(f, "srai {dest},{src},{shamt}"),
-            Instruction::ADD { dest, src1, src2 } => write!(f, "add {dest},{src1},{src2}"),
-            Instruction::SUB { dest, src1, src2 } => write!(f, "sub {dest},{src1},{src2}"),
-            Instruction::SLL { dest, src1, src2 } => write!(f, "sll {dest},{src1},{src2}"),
-            Instruction::SLT { dest, src1, src2 } => write!(f, "slt {dest},{src1},{src2}"),
-            Instruction::SLTU { dest, src1, src2 } => write!(f, "sltu {dest},{src1},{src2}"),
-            Instruction::XOR { dest, src1, src2 } => write!(f, "xor {dest},{src1},{src2}"),
-            Instruction::SRL { dest, src1, src2 } => write!(f, "srl {dest},{src1},{src2}"),
-            Instruction::SRA { dest, src1, src2 } => write!(f, "sra {dest},{src1},{src2}"),
-            Instruction::OR { dest, src1, src2 } => write!(f, "or {dest},{src1},{src2}"),
-            Instruction::AND { dest, src1, src2 } => write!(f, "and {dest},{src1},{src2}"),
+            Instruction::LUI { dest, imm } => {
+                write!(
+                    f,
+                    "lui {dest},{}",
+                    fmt_u_immediate(imm, options),
+                    dest = dest.display_with(options.register_style)
+                )
+            }
+            Instruction::AUIPC { dest, imm } => {
+                write!(
+                    f,
+                    "auipc {dest},{}",
+                    fmt_u_immediate(imm, options),
+                    dest = dest.display_with(options.register_style)
+                )
+            }
+            Instruction::JAL { dest, offset } => write!(
+                f,
+                "jal {dest},{offset}",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style)
+            ),
+            Instruction::JALR { dest, base, offset } => write!(
+                f,
+                "jalr {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::BEQ { src1, src2, offset } => write!(
+                f,
+                "beq {src1},{src2},{offset}",
+                offset = offset.display_with(options.immediate_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::BNE { src1, src2, offset } => write!(
+                f,
+                "bne {src1},{src2},{offset}",
+                offset = offset.display_with(options.immediate_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::BLT { src1, src2, offset } => write!(
+                f,
+                "blt {src1},{src2},{offset}",
+                offset = offset.display_with(options.immediate_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::BGE { src1, src2, offset } => write!(
+                f,
+                "bge {src1},{src2},{offset}",
+                offset = offset.display_with(options.immediate_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::BLTU { src1, src2, offset } => write!(
+                f,
+                "bltu {src1},{src2},{offset}",
+                offset = offset.display_with(options.immediate_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::BGEU { src1, src2, offset } => write!(
+                f,
+                "bgeu {src1},{src2},{offset}",
+                offset = offset.display_with(options.immediate_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::LB { dest, base, offset } => write!(
+                f,
+                "lb {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::LH { dest, base, offset } => write!(
+                f,
+                "lh {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::LW { dest, base, offset } => write!(
+                f,
+                "lw {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::LBU { dest, base, offset } => write!(
+                f,
+                "lbu {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::LHU { dest, base, offset } => write!(
+                f,
+                "lhu {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::SB { src, base, offset } => write!(
+                f,
+                "sb {src},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                src = src.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::SH { src, base, offset } => write!(
+                f,
+                "sh {src},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                src = src.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::SW { src, base, offset } => write!(
+                f,
+                "sw {src},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                src = src.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::ADDI { dest, src, imm } => write!(
+                f,
+                "addi {dest},{src},{imm}",
+                imm = imm.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SLTI { dest, src, imm } => write!(
+                f,
+                "slti {dest},{src},{imm}",
+                imm = imm.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SLTIU { dest, src, imm } => write!(
+                f,
+                "sltiu {dest},{src},{imm}",
+                imm = imm.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::XORI { dest, src, imm } => write!(
+                f,
+                "xori {dest},{src},{imm}",
+                imm = imm.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::ORI { dest, src, imm } => write!(
+                f,
+                "ori {dest},{src},{imm}",
+                imm = imm.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::ANDI { dest, src, imm } => write!(
+                f,
+                "andi {dest},{src},{imm}",
+                imm = imm.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SLLI { dest, src, shamt } => write!(
+                f,
+                "slli {dest},{src},{shamt}",
+                shamt = shamt.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SRLI { dest, src, shamt } => write!(
+                f,
+                "srli {dest},{src},{shamt}",
+                shamt = shamt.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SRAI { dest, src, shamt } => write!(
+                f,
+                "srai {dest},{src},{shamt}",
+                shamt = shamt.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::ADD { dest, src1, src2 } => write!(
+                f,
+                "add {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SUB { dest, src1, src2 } => write!(
+                f,
+                "sub {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SLL { dest, src1, src2 } => write!(
+                f,
+                "sll {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SLT { dest, src1, src2 } => write!(
+                f,
+                "slt {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SLTU { dest, src1, src2 } => write!(
+                f,
+                "sltu {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::XOR { dest, src1, src2 } => write!(
+                f,
+                "xor {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SRL { dest, src1, src2 } => write!(
+                f,
+                "srl {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SRA { dest, src1, src2 } => write!(
+                f,
+                "sra {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::OR { dest, src1, src2 } => write!(
+                f,
+                "or {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::AND { dest, src1, src2 } => write!(
+                f,
+                "and {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
             Instruction::FENCE { .. } => write!(f, "{}", self.fmt_fence()),
             Instruction::ECALL => write!(f, "ecall"),
             Instruction::EBREAK => write!(f, "ebreak"),
-            Instruction::LWU { dest, base, offset } => write!(f, "lwu {dest},{offset}({base})"),
-            Instruction::LD { dest, base, offset } => write!(f, "ld {dest},{offset}({base})"),
-            Instruction::SD { src, base, offset } => write!(f, "sd {src},{offset}({base})"),
-            Instruction::ADDIW { dest, src, imm } => write!(f, "addiw {dest},{src},{imm}"),
-            Instruction::SLLIW { dest, src, shamt } => write!(f, "slliw {dest},{src},{shamt}"),
-            Instruction::SRLIW { dest, src, shamt } => write!(f, "srliw {dest},{src},{shamt}"),
-            Instruction::SRAIW { dest, src, shamt } => write!(f, "sraiw {dest},{src},{shamt}"),
-            Instruction::ADDW { dest, src1, src2 } => write!(f, "addw {dest},{src1},{src2}"),
-            Instruction::SUBW { dest, src1, src2 } => write!(f, "subw {dest},{src1},{src2}"),
-            Instruction::SLLW { dest, src1, src2 } => write!(f, "sllw {dest},{src1},{src2}"),
-            Instruction::SRLW { dest, src1, src2 } => write!(f, "srlw {dest},{src1},{src2}"),
-            Instruction::SRAW { dest, src1, src2 } => write!(f, "sraw {dest},{src1},{src2}"),
-            Instruction::MUL { dest, src1, src2 } => write!(f, "mul {dest},{src1},{src2}"),
-            Instruction::MULH { dest, src1, src2 } => write!(f, "mulh {dest},{src1},{src2}"),
-            Instruction::MULHSU { dest, src1, src2 } => write!(f, "mulhsu {dest},{src1},{src2}"),
-            Instruction::MULHU { dest, src1, src2 } => write!(f, "mulhu {dest},{src1},{src2}"),
-            Instruction::DIV { dest, src1, src2 } => write!(f, "div {dest},{src1},{src2}"),
-            Instruction::DIVU { dest, src1, src2 } => write!(f, "divu {dest},{src1},{src2}"),
-            Instruction::REM { dest, src1, src2 } => write!(f, "rem {dest},{src1},{src2}"),
-            Instruction::REMU { dest, src1, src2 } => write!(f, "remu {dest},{src1},{src2}"),
-            Instruction::MULW { dest, src1, src2 } => write!(f, "mulw {dest},{src1},{src2}"),
-            Instruction::DIVW { dest, src1, src2 } => write!(f, "divw {dest},{src1},{src2}"),
-            Instruction::DIVUW { dest, src1, src2 } => write!(f, "divuw {dest},{src1},{src2}"),
-            Instruction::REMW { dest, src1, src2 } => write!(f, "remw {dest},{src1},{src2}"),
-            Instruction::REMUW { dest, src1, src2 } => write!(f, "remuw {dest},{src1},{src2}"),
+            Instruction::LWU { dest, base, offset } => write!(
+                f,
+                "lwu {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::LD { dest, base, offset } => write!(
+                f,
+                "ld {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::SD { src, base, offset } => write!(
+                f,
+                "sd {src},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                src = src.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::ADDIW { dest, src, imm } => write!(
+                f,
+                "addiw {dest},{src},{imm}",
+                imm = imm.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SLLIW { dest, src, shamt } => write!(
+                f,
+                "slliw {dest},{src},{shamt}",
+                shamt = shamt.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SRLIW { dest, src, shamt } => write!(
+                f,
+                "srliw {dest},{src},{shamt}",
+                shamt = shamt.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SRAIW { dest, src, shamt } => write!(
+                f,
+                "sraiw {dest},{src},{shamt}",
+                shamt = shamt.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::ADDW { dest, src1, src2 } => write!(
+                f,
+                "addw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SUBW { dest, src1, src2 } => write!(
+                f,
+                "subw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SLLW { dest, src1, src2 } => write!(
+                f,
+                "sllw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SRLW { dest, src1, src2 } => write!(
+                f,
+                "srlw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SRAW { dest, src1, src2 } => write!(
+                f,
+                "sraw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::LQ { dest, base, offset } => write!(
+                f,
+                "lq {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::LDU { dest, base, offset } => write!(
+                f,
+                "ldu {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::SQ { src, base, offset } => write!(
+                f,
+                "sq {src},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                src = src.display_with(options.register_style),
+                base = base.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::ADDID { dest, src, imm } => write!(
+                f,
+                "addid {dest},{src},{imm}",
+                imm = imm.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::SLLID { dest, src, shamt } => write!(
+                f,
+                "sllid {dest},{src},{shamt}",
+                shamt = shamt.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::SRLID { dest, src, shamt } => write!(
+                f,
+                "srlid {dest},{src},{shamt}",
+                shamt = shamt.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::SRAID { dest, src, shamt } => write!(
+                f,
+                "sraid {dest},{src},{shamt}",
+                shamt = shamt.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::ADDD { dest, src1, src2 } => write!(
+                f,
+                "addd {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::SUBD { dest, src1, src2 } => write!(
+                f,
+                "subd {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::SLLD { dest, src1, src2 } => write!(
+                f,
+                "slld {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::SRLD { dest, src1, src2 } => write!(
+                f,
+                "srld {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            #[cfg(feature = "rv128")]
+            Instruction::SRAD { dest, src1, src2 } => write!(
+                f,
+                "srad {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::MUL { dest, src1, src2 } => write!(
+                f,
+                "mul {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::MULH { dest, src1, src2 } => write!(
+                f,
+                "mulh {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::MULHSU { dest, src1, src2 } => write!(
+                f,
+                "mulhsu {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::MULHU { dest, src1, src2 } => write!(
+                f,
+                "mulhu {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::DIV { dest, src1, src2 } => write!(
+                f,
+                "div {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::DIVU { dest, src1, src2 } => write!(
+                f,
+                "divu {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::REM { dest, src1, src2 } => write!(
+                f,
+                "rem {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::REMU { dest, src1, src2 } => write!(
+                f,
+                "remu {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::MULW { dest, src1, src2 } => write!(
+                f,
+                "mulw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::DIVW { dest, src1, src2 } => write!(
+                f,
+                "divw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::DIVUW { dest, src1, src2 } => write!(
+                f,
+                "divuw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::REMW { dest, src1, src2 } => write!(
+                f,
+                "remw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::REMUW { dest, src1, src2 } => write!(
+                f,
+                "remuw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
             Instruction::LRW { dest, addr, aq, rl } => {
-                write!(f, "lr.w{} {dest},{addr}", aq_rl_suffix(aq, rl))
+                write!(
+                    f,
+                    "lr.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, None, options)
+                )
+            }
+            Instruction::SCW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "sc.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOSWAPW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amoswap.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOADDW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amoadd.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOXORW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amoxor.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOANDW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amoand.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOORW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amoor.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+
+            Instruction::AMOMINW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amomin.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOMAXW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amomax.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOMINUW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amominu.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOMAXUW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amomaxu.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::LRD { dest, addr, aq, rl } => {
+                write!(
+                    f,
+                    "lr.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, None, options)
+                )
+            }
+            Instruction::SCD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "sc.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOSWAPD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amoswap.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOADDD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amoadd.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOXORD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amoxor.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOANDD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amoand.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOORD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amoor.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
             }
-            Instruction::SCW {
+            Instruction::AMOMIND {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "sc.w{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
+                write!(
+                    f,
+                    "amomin.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
             }
-            Instruction::AMOSWAPW {
+            Instruction::AMOMAXD {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amoswap.w{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
+                write!(
+                    f,
+                    "amomax.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
             }
-            Instruction::AMOADDW {
+            Instruction::AMOMINUD {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amoadd.w{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
+                write!(
+                    f,
+                    "amominu.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
             }
-            Instruction::AMOXORW {
+            Instruction::AMOMAXUD {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amoxor.w{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOANDW {
+                write!(
+                    f,
+                    "amomaxu.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOSWAPB {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amoand.w{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOORW {
+                write!(
+                    f,
+                    "amoswap.b{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOADDB {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amoor.w{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-
-            Instruction::AMOMINW {
+                write!(
+                    f,
+                    "amoadd.b{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOXORB {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amomin.w{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOMAXW {
+                write!(
+                    f,
+                    "amoxor.b{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOANDB {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amomax.w{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOMINUW {
+                write!(
+                    f,
+                    "amoand.b{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOORB {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amominu.w{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOMAXUW {
+                write!(
+                    f,
+                    "amoor.b{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOMINB {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amomaxu.w{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::LRD { dest, addr, aq, rl } => {
-                write!(f, "lr.d{} {dest},{addr}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::SCD {
+                write!(
+                    f,
+                    "amomin.b{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOMAXB {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "sc.d{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOSWAPD {
+                write!(
+                    f,
+                    "amomax.b{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOMINUB {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amoswap.d{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOADDD {
+                write!(
+                    f,
+                    "amominu.b{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOMAXUB {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amoadd.d{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOXORD {
+                write!(
+                    f,
+                    "amomaxu.b{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOSWAPH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amoxor.d{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOANDD {
+                write!(
+                    f,
+                    "amoswap.h{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOADDH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amoand.d{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOORD {
+                write!(
+                    f,
+                    "amoadd.h{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOXORH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amoor.d{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOMIND {
+                write!(
+                    f,
+                    "amoxor.h{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOANDH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amomin.d{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOMAXD {
+                write!(
+                    f,
+                    "amoand.h{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOORH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amomax.d{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOMINUD {
+                write!(
+                    f,
+                    "amoor.h{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOMINH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amominu.d{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::AMOMAXUD {
+                write!(
+                    f,
+                    "amomin.h{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOMAXH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                write!(f, "amomaxu.d{} {dest},{addr},{src}", aq_rl_suffix(aq, rl))
-            }
-            Instruction::FLW { dest, base, offset } => write!(f, "flw {dest},{offset}({base})"),
-            Instruction::FSW { base, src, offset } => write!(f, "fsw {src},{offset}({base})"),
+                write!(
+                    f,
+                    "amomax.h{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOMINUH {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amominu.h{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOMAXUH {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amomaxu.h{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOCASB {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amocas.b{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::AMOCASH {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "amocas.h{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::FLW { dest, base, offset } => write!(
+                f,
+                "flw {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with_options(options),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::FSW { base, src, offset } => write!(
+                f,
+                "fsw {src},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                src = src.display_with_options(options),
+                base = base.display_with(options.register_style)
+            ),
             Instruction::FMADDS {
                 dest,
                 src1,
@@ -1078,7 +3265,16 @@ impl Display for Instruction {
                 src3,
                 rm,
             } => {
-                write!(f, "fmadd.s.{rm} {dest},{src1},{src2},{src3}")
+                write!(
+                    f,
+                    "fmadd.s{suffix} {dest},{src1},{src2},{src3}{operand}",
+                    dest = dest.display_with_options(options),
+                    src1 = src1.display_with_options(options),
+                    src2 = src2.display_with_options(options),
+                    src3 = src3.display_with_options(options),
+                    suffix = rm.suffix(options),
+                    operand = rm.operand(options)
+                )
             }
             Instruction::FMSUBS {
                 dest,
@@ -1087,7 +3283,16 @@ impl Display for Instruction {
                 src3,
                 rm,
             } => {
-                write!(f, "fmsub.s.{rm} {dest},{src1},{src2},{src3}")
+                write!(
+                    f,
+                    "fmsub.s{suffix} {dest},{src1},{src2},{src3}{operand}",
+                    dest = dest.display_with_options(options),
+                    src1 = src1.display_with_options(options),
+                    src2 = src2.display_with_options(options),
+                    src3 = src3.display_with_options(options),
+                    suffix = rm.suffix(options),
+                    operand = rm.operand(options)
+                )
             }
             Instruction::FNMSUBS {
                 dest,
@@ -1096,7 +3301,16 @@ impl Display for Instruction {
                 src3,
                 rm,
             } => {
-                write!(f, "fnmsub.s.{rm} {dest},{src1},{src2},{src3}")
+                write!(
+                    f,
+                    "fnmsub.s{suffix} {dest},{src1},{src2},{src3}{operand}",
+                    dest = dest.display_with_options(options),
+                    src1 = src1.display_with_options(options),
+                    src2 = src2.display_with_options(options),
+                    src3 = src3.display_with_options(options),
+                    suffix = rm.suffix(options),
+                    operand = rm.operand(options)
+                )
             }
             Instruction::FNMADDS {
                 dest,
@@ -1105,59 +3319,1088 @@ impl Display for Instruction {
                 src3,
                 rm,
             } => {
-                write!(f, "fnmadd.s.{rm} {dest},{src1},{src2},{src3}")
+                write!(
+                    f,
+                    "fnmadd.s{suffix} {dest},{src1},{src2},{src3}{operand}",
+                    dest = dest.display_with_options(options),
+                    src1 = src1.display_with_options(options),
+                    src2 = src2.display_with_options(options),
+                    src3 = src3.display_with_options(options),
+                    suffix = rm.suffix(options),
+                    operand = rm.operand(options)
+                )
             }
             Instruction::FADDS {
                 dest,
                 src1,
                 src2,
                 rm,
-            } => write!(f, "fadd.s.{rm} {dest},{src1},{src2}"),
+            } => write!(
+                f,
+                "fadd.s{suffix} {dest},{src1},{src2}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
             Instruction::FSUBS {
                 dest,
                 src1,
                 src2,
                 rm,
-            } => write!(f, "fsub.s.{rm} {dest},{src1},{src2}"),
+            } => write!(
+                f,
+                "fsub.s{suffix} {dest},{src1},{src2}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
             Instruction::FMULS {
                 dest,
                 src1,
                 src2,
                 rm,
-            } => write!(f, "fmul.s.{rm} {dest},{src1},{src2}"),
+            } => write!(
+                f,
+                "fmul.s{suffix} {dest},{src1},{src2}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
             Instruction::FDIVS {
                 dest,
                 src1,
                 src2,
                 rm,
-            } => write!(f, "fdiv.s.{rm} {dest},{src1},{src2}"),
-            Instruction::FSQRTS { dest, src, rm } => write!(f, "fsqrt.s.{rm} {dest},{src}"),
-            Instruction::FSGNJS { dest, src1, src2 } => write!(f, "fsgnj.s {dest},{src1},{src2}"),
-            Instruction::FSGNJNS { dest, src1, src2 } => write!(f, "fsgnjn.s {dest},{src1},{src2}"),
-            Instruction::FSGNJXS { dest, src1, src2 } => write!(f, "fsgnjx.s {dest},{src1},{src2}"),
-            Instruction::FMINS { dest, src1, src2 } => write!(f, "fmin.s {dest},{src1},{src2}"),
-            Instruction::FMAXS { dest, src1, src2 } => write!(f, "fmax.s {dest},{src1},{src2}"),
-            Instruction::FCVTWS { dest, src, rm } => write!(f, "fcvt.w.s.{rm} {dest},{src}"),
-            Instruction::FCVTWUS { dest, src, rm } => write!(f, "fcvt.wu.s.{rm} {dest},{src}"),
-            Instruction::FMVXW { dest, src } => write!(f, "fmv.x.w {dest},{src}"),
-            Instruction::FEQS { dest, src1, src2 } => write!(f, "feq.s {dest},{src1},{src2}"),
-            Instruction::FLTS { dest, src1, src2 } => write!(f, "flt.s {dest},{src1},{src2}"),
-            Instruction::FLES { dest, src1, src2 } => write!(f, "fle.s {dest},{src1},{src2}"),
-            Instruction::FCLASSS { dest, src } => write!(f, "fclass.s {dest},{src}"),
-            Instruction::FCVTSW { dest, src, rm } => write!(f, "fcvt.s.w.{rm} {dest},{src}"),
-            Instruction::FCVTSWU { dest, src, rm } => write!(f, "fcvt.s.wu.{rm} {dest},{src}"),
-            Instruction::FMVWX { dest, src } => write!(f, "fmv.w.x {dest},{src}"),
-            Instruction::FCVTLS { dest, src, rm } => write!(f, "fcvt.l.s.{rm} {dest},{src}"),
-            Instruction::FCVTLUS { dest, src, rm } => write!(f, "fcvt.lu.s.{rm} {dest},{src}"),
-            Instruction::FCVTSL { dest, src, rm } => write!(f, "fcvt.s.l.{rm} {dest},{src}"),
-            Instruction::FCVTSLU { dest, src, rm } => write!(f, "fcvt.s.lu.{rm} {dest},{src}"),
-            Instruction::CSRRW { dest, src, csr } => write!(f, "csrrw {dest},{csr},{src}"),
-            Instruction::CSRRS { dest, src, csr } => write!(f, "csrrs {dest},{csr},{src}"),
-            Instruction::CSRRC { dest, src, csr } => write!(f, "csrrc {dest},{csr},{src}"),
-            Instruction::CSRRWI { dest, imm, csr } => write!(f, "csrrwi {dest},{csr},{imm}"),
-            Instruction::CSRRSI { dest, imm, csr } => write!(f, "csrrsi {dest},{csr},{imm}"),
-            Instruction::CSRRCI { dest, imm, csr } => write!(f, "csrrci {dest},{csr},{imm}"),
+            } => write!(
+                f,
+                "fdiv.s{suffix} {dest},{src1},{src2}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FSQRTS { dest, src, rm } => write!(
+                f,
+                "fsqrt.s{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FSGNJS { dest, src1, src2 } => write!(
+                f,
+                "fsgnj.s {dest},{src1},{src2}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FSGNJNS { dest, src1, src2 } => write!(
+                f,
+                "fsgnjn.s {dest},{src1},{src2}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FSGNJXS { dest, src1, src2 } => write!(
+                f,
+                "fsgnjx.s {dest},{src1},{src2}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FMINS { dest, src1, src2 } => write!(
+                f,
+                "fmin.s {dest},{src1},{src2}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FMAXS { dest, src1, src2 } => write!(
+                f,
+                "fmax.s {dest},{src1},{src2}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FCVTWS { dest, src, rm } => write!(
+                f,
+                "fcvt.w.s{suffix} {dest},{src}{operand}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTWUS { dest, src, rm } => write!(
+                f,
+                "fcvt.wu.s{suffix} {dest},{src}{operand}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FMVXW { dest, src } => write!(
+                f,
+                "fmv.x.w {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options)
+            ),
+            Instruction::FEQS { dest, src1, src2 } => write!(
+                f,
+                "feq.s {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FLTS { dest, src1, src2 } => write!(
+                f,
+                "flt.s {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FLES { dest, src1, src2 } => write!(
+                f,
+                "fle.s {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FCLASSS { dest, src } => write!(
+                f,
+                "fclass.s {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options)
+            ),
+            Instruction::FCVTSW { dest, src, rm } => write!(
+                f,
+                "fcvt.s.w{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with(options.register_style),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTSWU { dest, src, rm } => write!(
+                f,
+                "fcvt.s.wu{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with(options.register_style),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FMVWX { dest, src } => write!(
+                f,
+                "fmv.w.x {dest},{src}",
+                dest = dest.display_with_options(options),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::FCVTLS { dest, src, rm } => write!(
+                f,
+                "fcvt.l.s{suffix} {dest},{src}{operand}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTLUS { dest, src, rm } => write!(
+                f,
+                "fcvt.lu.s{suffix} {dest},{src}{operand}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTSL { dest, src, rm } => write!(
+                f,
+                "fcvt.s.l{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with(options.register_style),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTSLU { dest, src, rm } => write!(
+                f,
+                "fcvt.s.lu{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with(options.register_style),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FLQ { dest, base, offset } => write!(
+                f,
+                "flq {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with_options(options),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::FSQ { base, src, offset } => write!(
+                f,
+                "fsq {src},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                src = src.display_with_options(options),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::FMADDQ {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => write!(
+                f,
+                "fmadd.q{suffix} {dest},{src1},{src2},{src3}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                src3 = src3.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FMSUBQ {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => write!(
+                f,
+                "fmsub.q{suffix} {dest},{src1},{src2},{src3}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                src3 = src3.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FNMSUBQ {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => write!(
+                f,
+                "fnmsub.q{suffix} {dest},{src1},{src2},{src3}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                src3 = src3.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FNMADDQ {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => write!(
+                f,
+                "fnmadd.q{suffix} {dest},{src1},{src2},{src3}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                src3 = src3.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FADDQ {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => write!(
+                f,
+                "fadd.q{suffix} {dest},{src1},{src2}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FSUBQ {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => write!(
+                f,
+                "fsub.q{suffix} {dest},{src1},{src2}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FMULQ {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => write!(
+                f,
+                "fmul.q{suffix} {dest},{src1},{src2}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FDIVQ {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => write!(
+                f,
+                "fdiv.q{suffix} {dest},{src1},{src2}{operand}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FSQRTQ { dest, src, rm } => write!(
+                f,
+                "fsqrt.q{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FSGNJQ { dest, src1, src2 } => write!(
+                f,
+                "fsgnj.q {dest},{src1},{src2}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FSGNJNQ { dest, src1, src2 } => write!(
+                f,
+                "fsgnjn.q {dest},{src1},{src2}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FSGNJXQ { dest, src1, src2 } => write!(
+                f,
+                "fsgnjx.q {dest},{src1},{src2}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FMINQ { dest, src1, src2 } => write!(
+                f,
+                "fmin.q {dest},{src1},{src2}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FMAXQ { dest, src1, src2 } => write!(
+                f,
+                "fmax.q {dest},{src1},{src2}",
+                dest = dest.display_with_options(options),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FCVTSQ { dest, src, rm } => write!(
+                f,
+                "fcvt.s.q{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTQS { dest, src, rm } => write!(
+                f,
+                "fcvt.q.s{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTDQ { dest, src, rm } => write!(
+                f,
+                "fcvt.d.q{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTQD { dest, src, rm } => write!(
+                f,
+                "fcvt.q.d{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FEQQ { dest, src1, src2 } => write!(
+                f,
+                "feq.q {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FLTQ { dest, src1, src2 } => write!(
+                f,
+                "flt.q {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FLEQ { dest, src1, src2 } => write!(
+                f,
+                "fle.q {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with_options(options),
+                src2 = src2.display_with_options(options)
+            ),
+            Instruction::FCLASSQ { dest, src } => write!(
+                f,
+                "fclass.q {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options)
+            ),
+            Instruction::FCVTWQ { dest, src, rm } => write!(
+                f,
+                "fcvt.w.q{suffix} {dest},{src}{operand}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTWUQ { dest, src, rm } => write!(
+                f,
+                "fcvt.wu.q{suffix} {dest},{src}{operand}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTQW { dest, src, rm } => write!(
+                f,
+                "fcvt.q.w{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with(options.register_style),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTQWU { dest, src, rm } => write!(
+                f,
+                "fcvt.q.wu{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with(options.register_style),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTLQ { dest, src, rm } => write!(
+                f,
+                "fcvt.l.q{suffix} {dest},{src}{operand}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTLUQ { dest, src, rm } => write!(
+                f,
+                "fcvt.lu.q{suffix} {dest},{src}{operand}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTQL { dest, src, rm } => write!(
+                f,
+                "fcvt.q.l{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with(options.register_style),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTQLU { dest, src, rm } => write!(
+                f,
+                "fcvt.q.lu{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with(options.register_style),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FLH { dest, base, offset } => write!(
+                f,
+                "flh {dest},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                dest = dest.display_with_options(options),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::FSH { base, src, offset } => write!(
+                f,
+                "fsh {src},{offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                src = src.display_with_options(options),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::FMVXH { dest, src } => write!(
+                f,
+                "fmv.x.h {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with_options(options)
+            ),
+            Instruction::FMVHX { dest, src } => write!(
+                f,
+                "fmv.h.x {dest},{src}",
+                dest = dest.display_with_options(options),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::FCVTBF16S { dest, src, rm } => write!(
+                f,
+                "fcvt.bf16.s{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            Instruction::FCVTSBF16 { dest, src, rm } => write!(
+                f,
+                "fcvt.s.bf16{suffix} {dest},{src}{operand}",
+                dest = dest.display_with_options(options),
+                src = src.display_with_options(options),
+                suffix = rm.suffix(options),
+                operand = rm.operand(options)
+            ),
+            // CSRs are not named, their numbers are shown in hex as toolchains do for unknown CSRs
+            Instruction::CSRRW { dest, src, csr } => write!(
+                f,
+                "csrrw {dest},{csr},{src}",
+                csr = csr.display_with(ImmediateStyle::Hex),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::CSRRS { dest, src, csr } => write!(
+                f,
+                "csrrs {dest},{csr},{src}",
+                csr = csr.display_with(ImmediateStyle::Hex),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::CSRRC { dest, src, csr } => write!(
+                f,
+                "csrrc {dest},{csr},{src}",
+                csr = csr.display_with(ImmediateStyle::Hex),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::CSRRWI { dest, imm, csr } => write!(
+                f,
+                "csrrwi {dest},{csr},{imm}",
+                csr = csr.display_with(ImmediateStyle::Hex),
+                imm = imm.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style)
+            ),
+            Instruction::CSRRSI { dest, imm, csr } => write!(
+                f,
+                "csrrsi {dest},{csr},{imm}",
+                csr = csr.display_with(ImmediateStyle::Hex),
+                imm = imm.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style)
+            ),
+            Instruction::CSRRCI { dest, imm, csr } => write!(
+                f,
+                "csrrci {dest},{csr},{imm}",
+                csr = csr.display_with(ImmediateStyle::Hex),
+                imm = imm.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style)
+            ),
             Instruction::FENCEI => write!(f, "fence.i"),
+            Instruction::CBOCLEAN { base } => write!(
+                f,
+                "cbo.clean ({base})",
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::CBOFLUSH { base } => write!(
+                f,
+                "cbo.flush ({base})",
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::CBOINVAL { base } => write!(
+                f,
+                "cbo.inval ({base})",
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::CBOZERO { base } => write!(
+                f,
+                "cbo.zero ({base})",
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::PREFETCHI { base, offset } => write!(
+                f,
+                "prefetch.i {offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::PREFETCHR { base, offset } => write!(
+                f,
+                "prefetch.r {offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::PREFETCHW { base, offset } => write!(
+                f,
+                "prefetch.w {offset}({base})",
+                offset = offset.display_with(options.immediate_style),
+                base = base.display_with(options.register_style)
+            ),
+            Instruction::PAUSE => write!(f, "pause"),
+            Instruction::NTLP1 => write!(f, "ntl.p1"),
+            Instruction::NTLPALL => write!(f, "ntl.pall"),
+            Instruction::NTLS1 => write!(f, "ntl.s1"),
+            Instruction::NTLALL => write!(f, "ntl.all"),
+            Instruction::LPAD { label } => write!(f, "lpad {label}"),
+            Instruction::SSPUSH { src } => {
+                write!(f, "sspush {}", src.display_with(options.register_style))
+            }
+            Instruction::SSPOPCHK { src } => {
+                write!(f, "sspopchk {}", src.display_with(options.register_style))
+            }
+            Instruction::SSRDP { dest } => {
+                write!(f, "ssrdp {}", dest.display_with(options.register_style))
+            }
+            Instruction::SSAMOSWAPW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "ssamoswap.w{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::SSAMOSWAPD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                write!(
+                    f,
+                    "ssamoswap.d{} {}",
+                    aq_rl_suffix(aq, rl),
+                    atomic_operands(*dest, *addr, Some(*src), options)
+                )
+            }
+            Instruction::SRET => write!(f, "sret"),
+            Instruction::MRET => write!(f, "mret"),
+            Instruction::MNRET => write!(f, "mnret"),
+            Instruction::DRET => write!(f, "dret"),
+            Instruction::WFI => write!(f, "wfi"),
+            Instruction::SFENCEVMA { vaddr, asid } => write!(
+                f,
+                "sfence.vma {vaddr},{asid}",
+                vaddr = vaddr.display_with(options.register_style),
+                asid = asid.display_with(options.register_style)
+            ),
+            Instruction::PACK { dest, src1, src2 } => write!(
+                f,
+                "pack {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::PACKH { dest, src1, src2 } => write!(
+                f,
+                "packh {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::PACKW { dest, src1, src2 } => write!(
+                f,
+                "packw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::CLMUL { dest, src1, src2 } => write!(
+                f,
+                "clmul {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::CLMULH { dest, src1, src2 } => write!(
+                f,
+                "clmulh {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::XPERM4 { dest, src1, src2 } => write!(
+                f,
+                "xperm4 {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::XPERM8 { dest, src1, src2 } => write!(
+                f,
+                "xperm8 {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::BREV8 { dest, src } => write!(
+                f,
+                "brev8 {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::ZIP { dest, src } => write!(
+                f,
+                "zip {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::UNZIP { dest, src } => write!(
+                f,
+                "unzip {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::ANDN { dest, src1, src2 } => write!(
+                f,
+                "andn {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::ORN { dest, src1, src2 } => write!(
+                f,
+                "orn {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::XNOR { dest, src1, src2 } => write!(
+                f,
+                "xnor {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::ROL { dest, src1, src2 } => write!(
+                f,
+                "rol {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::ROR { dest, src1, src2 } => write!(
+                f,
+                "ror {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::RORI { dest, src, shamt } => write!(
+                f,
+                "rori {dest},{src},{shamt}",
+                shamt = shamt.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::ROLW { dest, src1, src2 } => write!(
+                f,
+                "rolw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::RORW { dest, src1, src2 } => write!(
+                f,
+                "rorw {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::RORIW { dest, src, shamt } => write!(
+                f,
+                "roriw {dest},{src},{shamt}",
+                shamt = shamt.display_with(options.immediate_style),
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::REV8 { dest, src } => write!(
+                f,
+                "rev8 {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::REV8RV32 { dest, src } => write!(
+                f,
+                "rev8 {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::AES32ESI {
+                dest,
+                src1,
+                src2,
+                bs,
+            } => write!(
+                f,
+                "aes32esi {dest},{src1},{src2},{bs}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::AES32ESMI {
+                dest,
+                src1,
+                src2,
+                bs,
+            } => write!(
+                f,
+                "aes32esmi {dest},{src1},{src2},{bs}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::AES32DSI {
+                dest,
+                src1,
+                src2,
+                bs,
+            } => write!(
+                f,
+                "aes32dsi {dest},{src1},{src2},{bs}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::AES32DSMI {
+                dest,
+                src1,
+                src2,
+                bs,
+            } => write!(
+                f,
+                "aes32dsmi {dest},{src1},{src2},{bs}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::AES64ES { dest, src1, src2 } => write!(
+                f,
+                "aes64es {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::AES64ESM { dest, src1, src2 } => write!(
+                f,
+                "aes64esm {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::AES64DS { dest, src1, src2 } => write!(
+                f,
+                "aes64ds {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::AES64DSM { dest, src1, src2 } => write!(
+                f,
+                "aes64dsm {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::AES64KS2 { dest, src1, src2 } => write!(
+                f,
+                "aes64ks2 {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SHA512SUM0R { dest, src1, src2 } => write!(
+                f,
+                "sha512sum0r {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SHA512SUM1R { dest, src1, src2 } => write!(
+                f,
+                "sha512sum1r {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SHA512SIG0L { dest, src1, src2 } => write!(
+                f,
+                "sha512sig0l {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SHA512SIG0H { dest, src1, src2 } => write!(
+                f,
+                "sha512sig0h {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SHA512SIG1L { dest, src1, src2 } => write!(
+                f,
+                "sha512sig1l {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::SHA512SIG1H { dest, src1, src2 } => write!(
+                f,
+                "sha512sig1h {dest},{src1},{src2}",
+                dest = dest.display_with(options.register_style),
+                src1 = src1.display_with(options.register_style),
+                src2 = src2.display_with(options.register_style)
+            ),
+            Instruction::AES64IM { dest, src } => write!(
+                f,
+                "aes64im {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SHA256SIG0 { dest, src } => write!(
+                f,
+                "sha256sig0 {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SHA256SIG1 { dest, src } => write!(
+                f,
+                "sha256sig1 {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SHA256SUM0 { dest, src } => write!(
+                f,
+                "sha256sum0 {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SHA256SUM1 { dest, src } => write!(
+                f,
+                "sha256sum1 {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SHA512SIG0 { dest, src } => write!(
+                f,
+                "sha512sig0 {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SHA512SIG1 { dest, src } => write!(
+                f,
+                "sha512sig1 {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SHA512SUM0 { dest, src } => write!(
+                f,
+                "sha512sum0 {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::SHA512SUM1 { dest, src } => write!(
+                f,
+                "sha512sum1 {dest},{src}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            Instruction::AES64KS1I { dest, src, rnum } => write!(
+                f,
+                "aes64ks1i {dest},{src},{rnum}",
+                dest = dest.display_with(options.register_style),
+                src = src.display_with(options.register_style)
+            ),
+            #[cfg(feature = "zalasr")]
+            Instruction::LBAQ { dest, addr, rl } => write!(
+                f,
+                "lb{} {}",
+                aq_rl_suffix(&true, rl),
+                atomic_operands(*dest, *addr, None, options)
+            ),
+            #[cfg(feature = "zalasr")]
+            Instruction::LHAQ { dest, addr, rl } => write!(
+                f,
+                "lh{} {}",
+                aq_rl_suffix(&true, rl),
+                atomic_operands(*dest, *addr, None, options)
+            ),
+            #[cfg(feature = "zalasr")]
+            Instruction::LWAQ { dest, addr, rl } => write!(
+                f,
+                "lw{} {}",
+                aq_rl_suffix(&true, rl),
+                atomic_operands(*dest, *addr, None, options)
+            ),
+            #[cfg(feature = "zalasr")]
+            Instruction::LDAQ { dest, addr, rl } => write!(
+                f,
+                "ld{} {}",
+                aq_rl_suffix(&true, rl),
+                atomic_operands(*dest, *addr, None, options)
+            ),
+            #[cfg(feature = "zalasr")]
+            Instruction::SBRL { src, addr, aq } => write!(
+                f,
+                "sb{} {}",
+                aq_rl_suffix(aq, &true),
+                atomic_operands(*src, *addr, None, options)
+            ),
+            #[cfg(feature = "zalasr")]
+            Instruction::SHRL { src, addr, aq } => write!(
+                f,
+                "sh{} {}",
+                aq_rl_suffix(aq, &true),
+                atomic_operands(*src, *addr, None, options)
+            ),
+            #[cfg(feature = "zalasr")]
+            Instruction::SWRL { src, addr, aq } => write!(
+                f,
+                "sw{} {}",
+                aq_rl_suffix(aq, &true),
+                atomic_operands(*src, *addr, None, options)
+            ),
+            #[cfg(feature = "zalasr")]
+            Instruction::SDRL { src, addr, aq } => write!(
+                f,
+                "sd{} {}",
+                aq_rl_suffix(aq, &true),
+                atomic_operands(*src, *addr, None, options)
+            ),
+            Instruction::Vector(i) => i.fmt_with(f, options),
+            #[cfg(feature = "p")]
+            Instruction::P(i) => i.fmt_with(f, options),
+            #[cfg(feature = "xandesperf")]
+            Instruction::XAndesPerf(i) => i.fmt_with(f, options),
+            #[cfg(feature = "xthead")]
+            Instruction::XThead(i) => i.fmt_with(f, options),
+            Instruction::Custom(i) => write!(f, "{i}"),
         }
     }
 }
@@ -1190,7 +4433,67 @@ impl Instruction {
     }
 
     /// Constructs an `Instruction` from it's machine code representation.
-    pub fn decode(instruction: u32) -> Result<Instruction, String> {
+    pub fn decode(instruction: u32) -> Result<Instruction, DecodeError> {
+        Instruction::decode_with(instruction, &DecoderConfig::default())
+    }
+
+    /// Constructs an `Instruction` from the little endian bytes it is stored as in memory.
+    pub fn from_le_bytes(
+        bytes: [u8; 4],
+        config: &DecoderConfig,
+    ) -> Result<Instruction, DecodeError> {
+        Instruction::decode_with(u32::from_le_bytes(bytes), config)
+    }
+
+    /// Constructs an `Instruction` from it's machine code representation using the given configuration.
+    ///
+    /// Instructions of extensions that are not enabled in the configuration are rejected, as are the instructions that
+    /// only exist on RV64 (addiw, lwu) when decoding for RV32, the ones of RV128 on other bases and the ones using
+    /// x16-x31 with RVE. The HINTs of extensions that are not enabled (prefetch.i or lpad) are decoded as the
+    /// instruction whose encoding they use (ori or auipc).
+    pub fn decode_with(
+        instruction: u32,
+        config: &DecoderConfig,
+    ) -> Result<Instruction, DecodeError> {
+        let decoded = Instruction::decode_fields(instruction, config)
+            .map_err(|message| DecodeError::full(instruction, message))?;
+        #[cfg(feature = "rv128")]
+        if config.xlen != Xlen::Rv128 && decoded.is_rv128_only() {
+            return Err(DecodeError::full(
+                instruction,
+                format!("unsupported instruction {decoded}, which is only available on RV128"),
+            ));
+        }
+        if config.xlen == Xlen::Rv32 && decoded.is_rv64_only() {
+            return Err(DecodeError::full(
+                instruction,
+                format!("unsupported instruction {decoded}, which is only available on RV64"),
+            ));
+        }
+        if config.extensions.e
+            && let Some(register) = decoded.rve_register(config.extensions.zfinx)
+        {
+            return Err(DecodeError::full(
+                instruction,
+                format!(
+                    "unsupported instruction {decoded}, which uses {register} but RVE only has x0-x15"
+                ),
+            ));
+        }
+        match config.extensions.missing(instruction, config.xlen) {
+            Some(extension) => Err(DecodeError::full(
+                instruction,
+                format!(
+                    "unsupported instruction {decoded}, which requires the {extension} extension"
+                ),
+            )),
+            None => Ok(decoded),
+        }
+    }
+
+    // the unusual bit groupings are used to separate the shamt bit from func7
+    #[allow(clippy::unusual_byte_groupings)]
+    fn decode_fields(instruction: u32, config: &DecoderConfig) -> Result<Instruction, String> {
         let opcode = Opcode::from_int(instruction & 0b111_1111);
 
         let func3 = (instruction >> 12) & 0b111;
@@ -1221,6 +4524,26 @@ impl Instruction {
         let aq: bool = ((instruction >> 26) & 0b1) == 0b1;
         let rl: bool = ((instruction >> 25) & 0b1) == 0b1;
 
+        // ld and sd access even-odd register pairs on RV32 with Zilsd
+        let pairs = config.xlen == Xlen::Rv32 && config.extensions.zilsd;
+
+        // OP-P is the OP-VE opcode of the vector crypto instructions
+        #[cfg(feature = "p")]
+        if instruction & 0b111_1111 == simd::OPCODE && config.extensions.p {
+            return SimdInstruction::decode(instruction, config.xlen).map(Instruction::P);
+        }
+
+        // the custom-2 opcode of the Andes instructions is OP-IMM-64 on RV128
+        #[cfg(feature = "xandesperf")]
+        if instruction & 0b111_1111 == andes::OPCODE && config.xlen.bits() < 128 {
+            return AndesInstruction::decode(instruction, config.xlen).map(Instruction::XAndesPerf);
+        }
+
+        #[cfg(feature = "xthead")]
+        if instruction & 0b111_1111 == thead::OPCODE {
+            return TheadInstruction::decode(instruction, config.xlen).map(Instruction::XThead);
+        }
+
         match opcode {
             Opcode::Load => match func3 {
                 0b000 => Ok(Instruction::LB {
@@ -1238,6 +4561,9 @@ impl Instruction {
                     base: rs1,
                     offset: i_immediate,
                 }),
+                0b011 if pairs && rd.number() % 2 == 1 => Err(format!(
+                    "ld on RV32 accesses a register pair and must use an even register, got {rd}"
+                )),
                 0b011 => Ok(Instruction::LD {
                     dest: rd,
                     base: rs1,
@@ -1258,9 +4584,22 @@ impl Instruction {
                     base: rs1,
                     offset: i_immediate,
                 }),
+                #[cfg(feature = "rv128")]
+                0b111 => Ok(Instruction::LDU {
+                    dest: rd,
+                    base: rs1,
+                    offset: i_immediate,
+                }),
+                #[cfg(not(feature = "rv128"))]
                 0b111 => Err("Invalid load func3".to_owned()),
                 _ => unreachable!(),
             },
+            // lpad is auipc to x0 without Zicfilp
+            Opcode::Auipc if config.extensions.zicfilp && rd == IRegister::Zero => {
+                Ok(Instruction::LPAD {
+                    label: LpadImmediate::from_u32(instruction),
+                })
+            }
             Opcode::Auipc => Ok(Instruction::AUIPC {
                 dest: rd,
                 imm: u_immediate,
@@ -1281,11 +4620,20 @@ impl Instruction {
                     base: rs1,
                     offset: s_immediate,
                 }),
+                0b011 if pairs && rs2.number() % 2 == 1 => Err(format!(
+                    "sd on RV32 accesses a register pair and must use an even register, got {rs2}"
+                )),
                 0b011 => Ok(Instruction::SD {
                     src: rs2,
                     base: rs1,
                     offset: s_immediate,
                 }),
+                #[cfg(feature = "rv128")]
+                0b100 => Ok(Instruction::SQ {
+                    src: rs2,
+                    base: rs1,
+                    offset: s_immediate,
+                }),
                 x => Err(format!("invalid store func3: {}", x)),
             },
             Opcode::Lui => Ok(Instruction::LUI {
@@ -1293,6 +4641,20 @@ impl Instruction {
                 imm: u_immediate,
             }),
             Opcode::Op => match (func7, func3) {
+                // the ntl hints are add to x0 without Zihintntl
+                (0b000_0000, 0b000)
+                    if config.extensions.zihintntl
+                        && rd == IRegister::Zero
+                        && rs1 == IRegister::Zero
+                        && (2..=5).contains(&(rs2 as u32)) =>
+                {
+                    Ok(match rs2 {
+                        IRegister::StackPointer => Instruction::NTLP1,
+                        IRegister::GlobalPointer => Instruction::NTLPALL,
+                        IRegister::ThreadPointer => Instruction::NTLS1,
+                        _ => Instruction::NTLALL,
+                    })
+                }
                 (0b000_0000, 0b000) => Ok(Instruction::ADD {
                     dest: rd,
                     src1: rs1,
@@ -1333,52 +4695,195 @@ impl Instruction {
                     src1: rs1,
                     src2: rs2,
                 }),
-                (0b010_0000, 0b000) => Ok(Instruction::SUB {
+                (0b010_0000, 0b000) => Ok(Instruction::SUB {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b010_0000, 0b101) => Ok(Instruction::SRA {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0001, 0b000) => Ok(Instruction::MUL {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0001, 0b001) => Ok(Instruction::MULH {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0001, 0b010) => Ok(Instruction::MULHSU {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0001, 0b011) => Ok(Instruction::MULHU {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0001, 0b100) => Ok(Instruction::DIV {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0001, 0b101) => Ok(Instruction::DIVU {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0001, 0b110) => Ok(Instruction::REM {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0001, 0b111) => Ok(Instruction::REMU {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0100, 0b100) => Ok(Instruction::PACK {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0100, 0b111) => Ok(Instruction::PACKH {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0101, 0b001) => Ok(Instruction::CLMUL {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000_0101, 0b011) => Ok(Instruction::CLMULH {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b001_0100, 0b010) => Ok(Instruction::XPERM4 {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b001_0100, 0b100) => Ok(Instruction::XPERM8 {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b010_0000, 0b111) => Ok(Instruction::ANDN {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b010_0000, 0b110) => Ok(Instruction::ORN {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b010_0000, 0b100) => Ok(Instruction::XNOR {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b011_0000, 0b001) => Ok(Instruction::ROL {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b011_0000, 0b101) => Ok(Instruction::ROR {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                // the upper two bits of func7 are the byte select of the RV32 AES instructions
+                (f, 0b000)
+                    if config.xlen == Xlen::Rv32
+                        && matches!(f & 0b1_1111, 0b1_0001 | 0b1_0011 | 0b1_0101 | 0b1_0111) =>
+                {
+                    let bs = Bs::from_u32(instruction);
+                    Ok(match f & 0b1_1111 {
+                        0b1_0001 => Instruction::AES32ESI {
+                            dest: rd,
+                            src1: rs1,
+                            src2: rs2,
+                            bs,
+                        },
+                        0b1_0011 => Instruction::AES32ESMI {
+                            dest: rd,
+                            src1: rs1,
+                            src2: rs2,
+                            bs,
+                        },
+                        0b1_0101 => Instruction::AES32DSI {
+                            dest: rd,
+                            src1: rs1,
+                            src2: rs2,
+                            bs,
+                        },
+                        _ => Instruction::AES32DSMI {
+                            dest: rd,
+                            src1: rs1,
+                            src2: rs2,
+                            bs,
+                        },
+                    })
+                }
+                (0b010_1000, 0b000) if config.xlen == Xlen::Rv32 => Ok(Instruction::SHA512SUM0R {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b010_1001, 0b000) if config.xlen == Xlen::Rv32 => Ok(Instruction::SHA512SUM1R {
                     dest: rd,
                     src1: rs1,
                     src2: rs2,
                 }),
-                (0b010_0000, 0b101) => Ok(Instruction::SRA {
+                (0b010_1010, 0b000) if config.xlen == Xlen::Rv32 => Ok(Instruction::SHA512SIG0L {
                     dest: rd,
                     src1: rs1,
                     src2: rs2,
                 }),
-                (0b000_0001, 0b000) => Ok(Instruction::MUL {
+                (0b010_1110, 0b000) if config.xlen == Xlen::Rv32 => Ok(Instruction::SHA512SIG0H {
                     dest: rd,
                     src1: rs1,
                     src2: rs2,
                 }),
-                (0b000_0001, 0b001) => Ok(Instruction::MULH {
+                (0b010_1011, 0b000) if config.xlen == Xlen::Rv32 => Ok(Instruction::SHA512SIG1L {
                     dest: rd,
                     src1: rs1,
                     src2: rs2,
                 }),
-                (0b000_0001, 0b010) => Ok(Instruction::MULHSU {
+                (0b010_1111, 0b000) if config.xlen == Xlen::Rv32 => Ok(Instruction::SHA512SIG1H {
                     dest: rd,
                     src1: rs1,
                     src2: rs2,
                 }),
-                (0b000_0001, 0b011) => Ok(Instruction::MULHU {
+                (0b001_1001, 0b000) if config.xlen == Xlen::Rv64 => Ok(Instruction::AES64ES {
                     dest: rd,
                     src1: rs1,
                     src2: rs2,
                 }),
-                (0b000_0001, 0b100) => Ok(Instruction::DIV {
+                (0b001_1011, 0b000) if config.xlen == Xlen::Rv64 => Ok(Instruction::AES64ESM {
                     dest: rd,
                     src1: rs1,
                     src2: rs2,
                 }),
-                (0b000_0001, 0b101) => Ok(Instruction::DIVU {
+                (0b001_1101, 0b000) if config.xlen == Xlen::Rv64 => Ok(Instruction::AES64DS {
                     dest: rd,
                     src1: rs1,
                     src2: rs2,
                 }),
-                (0b000_0001, 0b110) => Ok(Instruction::REM {
+                (0b001_1111, 0b000) if config.xlen == Xlen::Rv64 => Ok(Instruction::AES64DSM {
                     dest: rd,
                     src1: rs1,
                     src2: rs2,
                 }),
-                (0b000_0001, 0b111) => Ok(Instruction::REMU {
+                (0b011_1111, 0b000) if config.xlen == Xlen::Rv64 => Ok(Instruction::AES64KS2 {
                     dest: rd,
                     src1: rs1,
                     src2: rs2,
@@ -1406,7 +4911,7 @@ impl Instruction {
                     src1: rs1,
                     src2: rs2,
                 }),
-                (0b100, 0b0000_001) => Ok(Instruction::DIVW {
+                (0b100, 0b000_0001) => Ok(Instruction::DIVW {
                     dest: rd,
                     src1: rs1,
                     src2: rs2,
@@ -1436,6 +4941,21 @@ impl Instruction {
                     src1: rs1,
                     src2: rs2,
                 }),
+                (0b100, 0b000_0100) => Ok(Instruction::PACKW {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b001, 0b011_0000) => Ok(Instruction::ROLW {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b101, 0b011_0000) => Ok(Instruction::RORW {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
                 _ => Err(format!("unknown Op32. func3: {}, func7: {}", func3, func7)),
             },
             Opcode::OpImm => match func3 {
@@ -1444,6 +4964,64 @@ impl Instruction {
                     src: rs1,
                     imm: i_immediate,
                 }),
+                // the unary scalar crypto instructions are encoded as shifts with a fixed shamt
+                0b001 if func7 == 0b000_1000 => match (instruction >> 20) & 0b1_1111 {
+                    0b00010 => Ok(Instruction::SHA256SIG0 { dest: rd, src: rs1 }),
+                    0b00011 => Ok(Instruction::SHA256SIG1 { dest: rd, src: rs1 }),
+                    0b00000 => Ok(Instruction::SHA256SUM0 { dest: rd, src: rs1 }),
+                    0b00001 => Ok(Instruction::SHA256SUM1 { dest: rd, src: rs1 }),
+                    0b00110 if config.xlen == Xlen::Rv64 => {
+                        Ok(Instruction::SHA512SIG0 { dest: rd, src: rs1 })
+                    }
+                    0b00111 if config.xlen == Xlen::Rv64 => {
+                        Ok(Instruction::SHA512SIG1 { dest: rd, src: rs1 })
+                    }
+                    0b00100 if config.xlen == Xlen::Rv64 => {
+                        Ok(Instruction::SHA512SUM0 { dest: rd, src: rs1 })
+                    }
+                    0b00101 if config.xlen == Xlen::Rv64 => {
+                        Ok(Instruction::SHA512SUM1 { dest: rd, src: rs1 })
+                    }
+                    x => Err(format!(
+                        "unknown OpImm. func3: 0b001, func7: 0b000_1000, rs2: {x}"
+                    )),
+                },
+                0b001 if config.xlen == Xlen::Rv64 && instruction >> 20 == 0x300 => {
+                    Ok(Instruction::AES64IM { dest: rd, src: rs1 })
+                }
+                0b001 if config.xlen == Xlen::Rv64 && instruction >> 24 == 0b0011_0001 => {
+                    let rnum = Rnum::from_u32(instruction);
+                    if rnum.val() > 0xa {
+                        Err(format!(
+                            "aes64ks1i round number must be at most 10, got {rnum}"
+                        ))
+                    } else {
+                        Ok(Instruction::AES64KS1I {
+                            dest: rd,
+                            src: rs1,
+                            rnum,
+                        })
+                    }
+                }
+                0b101 if instruction >> 20 == 0x687 => {
+                    Ok(Instruction::BREV8 { dest: rd, src: rs1 })
+                }
+                0b001 if config.xlen == Xlen::Rv32 && instruction >> 20 == 0x08f => {
+                    Ok(Instruction::ZIP { dest: rd, src: rs1 })
+                }
+                0b101 if config.xlen == Xlen::Rv32 && instruction >> 20 == 0x08f => {
+                    Ok(Instruction::UNZIP { dest: rd, src: rs1 })
+                }
+                0b101 if config.xlen == Xlen::Rv32 && instruction >> 20 == 0x698 => {
+                    Ok(Instruction::REV8RV32 { dest: rd, src: rs1 })
+                }
+                0b101 if config.xlen == Xlen::Rv64 && instruction >> 20 == 0x6b8 => {
+                    Ok(Instruction::REV8 { dest: rd, src: rs1 })
+                }
+                // shamt[5] is reserved on RV32
+                0b001 | 0b101 if config.xlen == Xlen::Rv32 && shamt.val() >= 32 => {
+                    Err(format!("shamt must be less than 32 on RV32, got {shamt}"))
+                }
                 // SLLi requires special handling because shamt uses the bottom bit of func7
                 0b001 => match func7 | 0b1 {
                     0b000000_1 => Ok(Instruction::SLLI {
@@ -1480,8 +5058,27 @@ impl Instruction {
                         src: rs1,
                         shamt,
                     }),
+                    0b011000_1 => Ok(Instruction::RORI {
+                        dest: rd,
+                        src: rs1,
+                        shamt,
+                    }),
                     _ => Err(format!("unknown OpImm. func3: {}, func7: {}", func3, func7)),
                 },
+                // the prefetches are hints that are ori to x0 without Zicbop
+                0b110 if config.extensions.zicbop && rd == IRegister::Zero => {
+                    let offset = PrefetchImmediate::from_u32(instruction);
+                    match (instruction >> 20) & 0b1_1111 {
+                        0b00000 => Ok(Instruction::PREFETCHI { base: rs1, offset }),
+                        0b00001 => Ok(Instruction::PREFETCHR { base: rs1, offset }),
+                        0b00011 => Ok(Instruction::PREFETCHW { base: rs1, offset }),
+                        _ => Ok(Instruction::ORI {
+                            dest: rd,
+                            src: rs1,
+                            imm: i_immediate,
+                        }),
+                    }
+                }
                 0b110 => Ok(Instruction::ORI {
                     dest: rd,
                     src: rs1,
@@ -1500,6 +5097,9 @@ impl Instruction {
                     src: rs1,
                     imm: i_immediate,
                 }),
+                0b001 if config.strict && func7 != 0 => {
+                    Err(format!("reserved slliw func7: {func7}"))
+                }
                 0b001 => Ok(Instruction::SLLIW {
                     dest: rd,
                     src: rs1,
@@ -1516,10 +5116,18 @@ impl Instruction {
                         src: rs1,
                         shamt: shamtw,
                     }),
+                    0b011_0000 => Ok(Instruction::RORIW {
+                        dest: rd,
+                        src: rs1,
+                        shamt: shamtw,
+                    }),
                     x => Err(format!("unknown OpImm32(101) func7: {}", x).to_owned()),
                 },
                 x => Err(format!("unkown OpImm32 func3: {}", x).to_owned()),
             },
+            Opcode::Jalr if config.strict && func3 != 0 => {
+                Err(format!("reserved jalr func3: {func3}"))
+            }
             Opcode::Jalr => Ok(Instruction::JALR {
                 dest: rd,
                 base: rs1,
@@ -1550,242 +5158,505 @@ impl Instruction {
                     src2: rs2,
                     offset: b_immediate,
                 }),
-                0b110 => Ok(Instruction::BLTU {
-                    src1: rs1,
-                    src2: rs2,
-                    offset: b_immediate,
+                0b110 => Ok(Instruction::BLTU {
+                    src1: rs1,
+                    src2: rs2,
+                    offset: b_immediate,
+                }),
+                0b111 => Ok(Instruction::BGEU {
+                    src1: rs1,
+                    src2: rs2,
+                    offset: b_immediate,
+                }),
+                x => Err(format!("invalid branch func3: {x}").to_owned()),
+            },
+            Opcode::MiscMem => match func3 {
+                0b000 => {
+                    let fm = ((instruction >> 28) & 0b1111) as u8;
+                    let ops = ((instruction >> 20) & 0xFF) as u8;
+                    if config.strict && (rd != IRegister::Zero || rs1 != IRegister::Zero) {
+                        // technicially, we are supposed to ignore these fields
+                        Err("reserved register fields not set to zero".to_owned())
+                    } else if config.strict && fm != 0 && fm != 0b1000 {
+                        Err(format!("reserved fence FM: {fm}").to_owned())
+                    } else if config.strict && fm == 0b1000 && ops != 0b0011_0011 {
+                        Err("fence.tso must be rw,rw".to_owned())
+                    } else if config.extensions.zihintpause && instruction == 0x0100000f {
+                        // pause is fence w,0 without Zihintpause
+                        Ok(Instruction::PAUSE)
+                    } else {
+                        Ok(Instruction::FENCE { rd, rs1, ops, fm })
+                    }
+                }
+                0b001 => {
+                    if config.strict && (rd != IRegister::Zero || rs1 != IRegister::Zero) {
+                        // technicially, we are supposed to ignore these fields
+                        Err("reserved register fields not set to zero".to_owned())
+                    } else if config.strict && instruction >> 20 != 0 {
+                        Err("reserved register fields not set to zero".to_owned())
+                    } else {
+                        Ok(Instruction::FENCEI)
+                    }
+                }
+                // lq takes the encoding of the cache block operations on RV128
+                #[cfg(feature = "rv128")]
+                0b010 if config.xlen == Xlen::Rv128 => Ok(Instruction::LQ {
+                    dest: rd,
+                    base: rs1,
+                    offset: i_immediate,
+                }),
+                0b010 => {
+                    if config.strict && rd != IRegister::Zero {
+                        Err("reserved register fields not set to zero".to_owned())
+                    } else {
+                        match instruction >> 20 {
+                            0x000 => Ok(Instruction::CBOINVAL { base: rs1 }),
+                            0x001 => Ok(Instruction::CBOCLEAN { base: rs1 }),
+                            0x002 => Ok(Instruction::CBOFLUSH { base: rs1 }),
+                            0x004 => Ok(Instruction::CBOZERO { base: rs1 }),
+                            x => Err(format!("unknown cache block operation: {x:#x}")),
+                        }
+                    }
+                }
+                x => Err(format!("unknown fence func3: {x}")),
+            },
+            Opcode::AMO => match (func3, func7 >> 2) {
+                (0b010, 0b00010) => {
+                    if config.strict && rs2 != IRegister::Zero {
+                        Err("LR.W expects rs2 to be 0".to_owned())
+                    } else {
+                        Ok(Instruction::LRW {
+                            dest: rd,
+                            addr: rs1,
+                            aq,
+                            rl,
+                        })
+                    }
+                }
+                (0b011, 0b00010) => {
+                    if config.strict && rs2 != IRegister::Zero {
+                        Err("LR.D expects rs2 to be 0".to_owned())
+                    } else {
+                        Ok(Instruction::LRD {
+                            dest: rd,
+                            addr: rs1,
+                            aq,
+                            rl,
+                        })
+                    }
+                }
+                (0b010, 0b00011) => Ok(Instruction::SCW {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b011, 0b00011) => Ok(Instruction::SCD {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b010, 0b01001) if config.extensions.zicfiss => Ok(Instruction::SSAMOSWAPW {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b011, 0b01001) if config.extensions.zicfiss => Ok(Instruction::SSAMOSWAPD {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b010, 0b00001) => Ok(Instruction::AMOSWAPW {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b011, 0b00001) => Ok(Instruction::AMOSWAPD {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b010, 0b00000) => Ok(Instruction::AMOADDW {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b011, 0b00000) => Ok(Instruction::AMOADDD {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b010, 0b00100) => Ok(Instruction::AMOXORW {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b011, 0b00100) => Ok(Instruction::AMOXORD {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b010, 0b01100) => Ok(Instruction::AMOANDW {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b011, 0b01100) => Ok(Instruction::AMOANDD {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b010, 0b01000) => Ok(Instruction::AMOORW {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b011, 0b01000) => Ok(Instruction::AMOORD {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b010, 0b10000) => Ok(Instruction::AMOMINW {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b011, 0b10000) => Ok(Instruction::AMOMIND {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b010, 0b10100) => Ok(Instruction::AMOMAXW {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b011, 0b10100) => Ok(Instruction::AMOMAXD {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b010, 0b11000) => Ok(Instruction::AMOMINUW {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b011, 0b11000) => Ok(Instruction::AMOMINUD {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
+                }),
+                (0b010, 0b11100) => Ok(Instruction::AMOMAXUW {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
                 }),
-                0b111 => Ok(Instruction::BGEU {
-                    src1: rs1,
-                    src2: rs2,
-                    offset: b_immediate,
+                (0b011, 0b11100) => Ok(Instruction::AMOMAXUD {
+                    dest: rd,
+                    addr: rs1,
+                    src: rs2,
+                    aq,
+                    rl,
                 }),
-                x => Err(format!("invalid branch func3: {x}").to_owned()),
-            },
-            Opcode::MiscMem => match func3 {
-                0b000 => {
-                    if rd != IRegister::Zero || rs1 != IRegister::Zero {
-                        // technicially, we are supposed to ignore these fields
-                        Err("reserved register fields not set to zero".to_owned())
-                    } else {
-                        let fm = ((instruction >> 28) & 0b1111) as u8;
-                        if fm != 0 && fm != 0b1000 {
-                            Err(format!("reserved fence FM: {fm}").to_owned())
-                        } else if fm == 0b1000 && ((instruction >> 20) & 0xFF) != 0b0011_0011 {
-                            Err("fence.tso must be rw,rw".to_owned())
-                        } else {
-                            Ok(Instruction::FENCE {
-                                rd,
-                                rs1,
-                                ops: ((instruction >> 20) & 0xFF) as u8,
-                                fm: ((instruction >> 28) & 0b1111) as u8,
-                            })
-                        }
-                    }
-                }
-                0b001 => {
-                    if rd != IRegister::Zero || rs1 != IRegister::Zero {
-                        // technicially, we are supposed to ignore these fields
-                        Err("reserved register fields not set to zero".to_owned())
-                    } else {
-                        let func12 = instruction >> 20;
-                        if func12 != 0 {
-                            Err("reserved register fields not set to zero".to_owned())
-                        } else {
-                            Ok(Instruction::FENCEI)
-                        }
-                    }
-                }
-                x => Err(format!("unknown fence func3: {x}")),
-            },
-            Opcode::AMO => match (func3, func7 >> 2) {
-                (0b010, 0b00010) => {
-                    if rs2 != IRegister::Zero {
-                        Err("LR.W expects rs2 to be 0".to_owned())
-                    } else {
-                        Ok(Instruction::LRW {
-                            dest: rd,
-                            addr: rs1,
-                            aq,
-                            rl,
-                        })
-                    }
-                }
-                (0b011, 0b00010) => {
-                    if rs2 != IRegister::Zero {
-                        Err("LR.D expects rs2 to be 0".to_owned())
-                    } else {
-                        Ok(Instruction::LRD {
-                            dest: rd,
-                            addr: rs1,
-                            aq,
-                            rl,
-                        })
-                    }
-                }
-                (0b010, 0b00011) => Ok(Instruction::SCW {
+                (0b000, 0b00001) => Ok(Instruction::AMOSWAPB {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b011, 0b00011) => Ok(Instruction::SCD {
+                (0b000, 0b00000) => Ok(Instruction::AMOADDB {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b010, 0b00001) => Ok(Instruction::AMOSWAPW {
+                (0b000, 0b00100) => Ok(Instruction::AMOXORB {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b011, 0b00001) => Ok(Instruction::AMOSWAPD {
+                (0b000, 0b01100) => Ok(Instruction::AMOANDB {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b010, 0b00000) => Ok(Instruction::AMOADDW {
+                (0b000, 0b01000) => Ok(Instruction::AMOORB {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b011, 0b00000) => Ok(Instruction::AMOADDD {
+                (0b000, 0b10000) => Ok(Instruction::AMOMINB {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b010, 0b00100) => Ok(Instruction::AMOXORW {
+                (0b000, 0b10100) => Ok(Instruction::AMOMAXB {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b011, 0b00100) => Ok(Instruction::AMOXORD {
+                (0b000, 0b11000) => Ok(Instruction::AMOMINUB {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b010, 0b01100) => Ok(Instruction::AMOANDW {
+                (0b000, 0b11100) => Ok(Instruction::AMOMAXUB {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b011, 0b01100) => Ok(Instruction::AMOANDD {
+                (0b001, 0b00001) => Ok(Instruction::AMOSWAPH {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b010, 0b01000) => Ok(Instruction::AMOORW {
+                (0b001, 0b00000) => Ok(Instruction::AMOADDH {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b011, 0b01000) => Ok(Instruction::AMOORD {
+                (0b001, 0b00100) => Ok(Instruction::AMOXORH {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b010, 0b10000) => Ok(Instruction::AMOMINW {
+                (0b001, 0b01100) => Ok(Instruction::AMOANDH {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b011, 0b10000) => Ok(Instruction::AMOMIND {
+                (0b001, 0b01000) => Ok(Instruction::AMOORH {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b010, 0b10100) => Ok(Instruction::AMOMAXW {
+                (0b001, 0b10000) => Ok(Instruction::AMOMINH {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b011, 0b10100) => Ok(Instruction::AMOMAXD {
+                (0b001, 0b10100) => Ok(Instruction::AMOMAXH {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b010, 0b11000) => Ok(Instruction::AMOMINUW {
+                (0b001, 0b11000) => Ok(Instruction::AMOMINUH {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b011, 0b11000) => Ok(Instruction::AMOMINUD {
+                (0b001, 0b11100) => Ok(Instruction::AMOMAXUH {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b010, 0b11100) => Ok(Instruction::AMOMAXUW {
+                (0b000, 0b00101) => Ok(Instruction::AMOCASB {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
-                (0b011, 0b11100) => Ok(Instruction::AMOMAXUD {
+                (0b001, 0b00101) => Ok(Instruction::AMOCASH {
                     dest: rd,
                     addr: rs1,
                     src: rs2,
                     aq,
                     rl,
                 }),
+                #[cfg(feature = "zalasr")]
+                (0b011, 0b00110 | 0b00111) if config.xlen == Xlen::Rv32 => {
+                    Err("ld.aq and sd.rl are only available on RV64".to_owned())
+                }
+                #[cfg(feature = "zalasr")]
+                (0b000..=0b011, 0b00110) if !aq => {
+                    Err("Zalasr loads must have the aq bit set".to_owned())
+                }
+                #[cfg(feature = "zalasr")]
+                (0b000..=0b011, 0b00110) if config.strict && rs2 != IRegister::Zero => {
+                    Err("Zalasr loads expect rs2 to be 0".to_owned())
+                }
+                #[cfg(feature = "zalasr")]
+                (0b000, 0b00110) => Ok(Instruction::LBAQ {
+                    dest: rd,
+                    addr: rs1,
+                    rl,
+                }),
+                #[cfg(feature = "zalasr")]
+                (0b001, 0b00110) => Ok(Instruction::LHAQ {
+                    dest: rd,
+                    addr: rs1,
+                    rl,
+                }),
+                #[cfg(feature = "zalasr")]
+                (0b010, 0b00110) => Ok(Instruction::LWAQ {
+                    dest: rd,
+                    addr: rs1,
+                    rl,
+                }),
+                #[cfg(feature = "zalasr")]
+                (0b011, 0b00110) => Ok(Instruction::LDAQ {
+                    dest: rd,
+                    addr: rs1,
+                    rl,
+                }),
+                #[cfg(feature = "zalasr")]
+                (0b000..=0b011, 0b00111) if !rl => {
+                    Err("Zalasr stores must have the rl bit set".to_owned())
+                }
+                #[cfg(feature = "zalasr")]
+                (0b000..=0b011, 0b00111) if config.strict && rd != IRegister::Zero => {
+                    Err("Zalasr stores expect rd to be 0".to_owned())
+                }
+                #[cfg(feature = "zalasr")]
+                (0b000, 0b00111) => Ok(Instruction::SBRL {
+                    src: rs2,
+                    addr: rs1,
+                    aq,
+                }),
+                #[cfg(feature = "zalasr")]
+                (0b001, 0b00111) => Ok(Instruction::SHRL {
+                    src: rs2,
+                    addr: rs1,
+                    aq,
+                }),
+                #[cfg(feature = "zalasr")]
+                (0b010, 0b00111) => Ok(Instruction::SWRL {
+                    src: rs2,
+                    addr: rs1,
+                    aq,
+                }),
+                #[cfg(feature = "zalasr")]
+                (0b011, 0b00111) => Ok(Instruction::SDRL {
+                    src: rs2,
+                    addr: rs1,
+                    aq,
+                }),
                 _ => Err(format!("unknown AMO. func3: {func3}, func7: {func7}")),
             },
             Opcode::LoadFp => {
-                println!("{i_immediate}, {:b}", instruction);
-                if func3 == 0b010 {
+                if func3 == 0b001 {
+                    Ok(Instruction::FLH {
+                        dest: frd,
+                        base: rs1,
+                        offset: i_immediate,
+                    })
+                } else if func3 == 0b010 {
                     Ok(Instruction::FLW {
                         dest: frd,
                         base: rs1,
                         offset: i_immediate,
                     })
+                } else if func3 == 0b100 {
+                    Ok(Instruction::FLQ {
+                        dest: frd,
+                        base: rs1,
+                        offset: i_immediate,
+                    })
+                } else if matches!(func3, 0b000 | 0b101 | 0b110 | 0b111) {
+                    VectorInstruction::decode_with(instruction, config).map(Instruction::Vector)
                 } else {
                     Err(format!("unknown func3: {func3} in opcode LoadFp"))
                 }
             }
             Opcode::StoreFp => {
-                if func3 == 0b010 {
+                if func3 == 0b001 {
+                    Ok(Instruction::FSH {
+                        base: rs1,
+                        src: frs2,
+                        offset: s_immediate,
+                    })
+                } else if func3 == 0b010 {
                     Ok(Instruction::FSW {
                         base: rs1,
                         src: frs2,
                         offset: s_immediate,
                     })
+                } else if func3 == 0b100 {
+                    Ok(Instruction::FSQ {
+                        base: rs1,
+                        src: frs2,
+                        offset: s_immediate,
+                    })
+                } else if matches!(func3, 0b000 | 0b101 | 0b110 | 0b111) {
+                    VectorInstruction::decode_with(instruction, config).map(Instruction::Vector)
                 } else {
-                    Err(format!("unknown func3: {func3} in opcode LoadFp"))
+                    Err(format!("unknown func3: {func3} in opcode StoreFp"))
                 }
             }
             Opcode::OpFp => match func7 {
@@ -1793,31 +5664,37 @@ impl Instruction {
                     dest: frd,
                     src1: frs1,
                     src2: frs2,
-                    rm: RoundingMode::from_int(func3)?,
+                    rm: RoundingMode::decode(func3, config)?,
                 }),
                 0b000_0100 => Ok(Instruction::FSUBS {
                     dest: frd,
                     src1: frs1,
                     src2: frs2,
-                    rm: RoundingMode::from_int(func3)?,
+                    rm: RoundingMode::decode(func3, config)?,
                 }),
                 0b000_1000 => Ok(Instruction::FMULS {
                     dest: frd,
                     src1: frs1,
                     src2: frs2,
-                    rm: RoundingMode::from_int(func3)?,
+                    rm: RoundingMode::decode(func3, config)?,
                 }),
                 0b000_1100 => Ok(Instruction::FDIVS {
                     dest: frd,
                     src1: frs1,
                     src2: frs2,
-                    rm: RoundingMode::from_int(func3)?,
-                }),
-                0b010_1100 => Ok(Instruction::FSQRTS {
-                    dest: frd,
-                    src: frs1,
-                    rm: RoundingMode::from_int(func3)?,
+                    rm: RoundingMode::decode(func3, config)?,
                 }),
+                0b010_1100 => {
+                    if config.strict && rs2 != IRegister::Zero {
+                        Err("FSQRT.S expects rs2 to be 0".to_owned())
+                    } else {
+                        Ok(Instruction::FSQRTS {
+                            dest: frd,
+                            src: frs1,
+                            rm: RoundingMode::decode(func3, config)?,
+                        })
+                    }
+                }
                 0b001_0000 => match func3 {
                     0b000 => Ok(Instruction::FSGNJS {
                         dest: frd,
@@ -1871,22 +5748,22 @@ impl Instruction {
                     0b0_0000 => Ok(Instruction::FCVTWS {
                         dest: rd,
                         src: frs1,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
                     }),
                     0b0_0001 => Ok(Instruction::FCVTWUS {
                         dest: rd,
                         src: frs1,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
                     }),
                     0b0_0010 => Ok(Instruction::FCVTLS {
                         dest: rd,
                         src: frs1,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
                     }),
                     0b0_0011 => Ok(Instruction::FCVTLUS {
                         dest: rd,
                         src: frs1,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
                     }),
                     x => Err(format!("unknown OpFp func7=0b001_0100 rs2: {}", x)),
                 },
@@ -1894,27 +5771,27 @@ impl Instruction {
                     0b0_0000 => Ok(Instruction::FCVTSW {
                         dest: frd,
                         src: rs1,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
                     }),
                     0b0_0001 => Ok(Instruction::FCVTSWU {
                         dest: frd,
                         src: rs1,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
                     }),
                     0b0_0010 => Ok(Instruction::FCVTSL {
                         dest: frd,
                         src: rs1,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
                     }),
                     0b0_0011 => Ok(Instruction::FCVTSLU {
                         dest: frd,
                         src: rs1,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
                     }),
                     x => Err(format!("unknown OpFp func7=0b001_0100 rs2: {}", x)),
                 },
                 0b111_0000 => {
-                    if (instruction >> 20) & 0b1_1111 == 0 {
+                    if !config.strict || (instruction >> 20) & 0b1_1111 == 0 {
                         if func3 == 0 {
                             Ok(Instruction::FMVXW {
                                 dest: rd,
@@ -1940,7 +5817,7 @@ impl Instruction {
                     }
                 }
                 0b111_1000 => {
-                    if (instruction >> 20) & 0b1_1111 == 0 {
+                    if !config.strict || (instruction >> 20) & 0b1_1111 == 0 {
                         if func3 == 0 {
                             Ok(Instruction::FMVWX {
                                 dest: frd,
@@ -1960,9 +5837,263 @@ impl Instruction {
                         ))
                     }
                 }
+                0b000_0011 => Ok(Instruction::FADDQ {
+                    dest: frd,
+                    src1: frs1,
+                    src2: frs2,
+                    rm: RoundingMode::decode(func3, config)?,
+                }),
+                0b000_0111 => Ok(Instruction::FSUBQ {
+                    dest: frd,
+                    src1: frs1,
+                    src2: frs2,
+                    rm: RoundingMode::decode(func3, config)?,
+                }),
+                0b000_1011 => Ok(Instruction::FMULQ {
+                    dest: frd,
+                    src1: frs1,
+                    src2: frs2,
+                    rm: RoundingMode::decode(func3, config)?,
+                }),
+                0b000_1111 => Ok(Instruction::FDIVQ {
+                    dest: frd,
+                    src1: frs1,
+                    src2: frs2,
+                    rm: RoundingMode::decode(func3, config)?,
+                }),
+                0b010_1111 => {
+                    if config.strict && rs2 != IRegister::Zero {
+                        Err("FSQRT.Q expects rs2 to be 0".to_owned())
+                    } else {
+                        Ok(Instruction::FSQRTQ {
+                            dest: frd,
+                            src: frs1,
+                            rm: RoundingMode::decode(func3, config)?,
+                        })
+                    }
+                }
+                0b001_0011 => match func3 {
+                    0b000 => Ok(Instruction::FSGNJQ {
+                        dest: frd,
+                        src1: frs1,
+                        src2: frs2,
+                    }),
+                    0b001 => Ok(Instruction::FSGNJNQ {
+                        dest: frd,
+                        src1: frs1,
+                        src2: frs2,
+                    }),
+                    0b010 => Ok(Instruction::FSGNJXQ {
+                        dest: frd,
+                        src1: frs1,
+                        src2: frs2,
+                    }),
+                    x => Err(format!("unknown OpFp func7=0b001_0011 func3: {x}")),
+                },
+                0b001_0111 => match func3 {
+                    0b000 => Ok(Instruction::FMINQ {
+                        dest: frd,
+                        src1: frs1,
+                        src2: frs2,
+                    }),
+                    0b001 => Ok(Instruction::FMAXQ {
+                        dest: frd,
+                        src1: frs1,
+                        src2: frs2,
+                    }),
+                    x => Err(format!("unknown OpFp func7=0b001_0111 func3: {x}")),
+                },
+                0b010_0000 => match (instruction >> 20) & 0b1_1111 {
+                    0b0_0011 => Ok(Instruction::FCVTSQ {
+                        dest: frd,
+                        src: frs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    0b0_0110 => Ok(Instruction::FCVTSBF16 {
+                        dest: frd,
+                        src: frs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    x => Err(format!("unknown OpFp func7=0b010_0000 rs2: {x}")),
+                },
+                0b010_0010 => match (instruction >> 20) & 0b1_1111 {
+                    0b0_1000 => Ok(Instruction::FCVTBF16S {
+                        dest: frd,
+                        src: frs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    x => Err(format!("unknown OpFp func7=0b010_0010 rs2: {x}")),
+                },
+                0b111_0010
+                    if func3 == 0 && (!config.strict || (instruction >> 20) & 0b1_1111 == 0) =>
+                {
+                    Ok(Instruction::FMVXH {
+                        dest: rd,
+                        src: frs1,
+                    })
+                }
+                0b111_1010
+                    if func3 == 0 && (!config.strict || (instruction >> 20) & 0b1_1111 == 0) =>
+                {
+                    Ok(Instruction::FMVHX {
+                        dest: frd,
+                        src: rs1,
+                    })
+                }
+                0b010_0011 => match (instruction >> 20) & 0b1_1111 {
+                    0b0_0000 => Ok(Instruction::FCVTQS {
+                        dest: frd,
+                        src: frs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    0b0_0001 => Ok(Instruction::FCVTQD {
+                        dest: frd,
+                        src: frs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    x => Err(format!("unknown OpFp func7=0b010_0011 rs2: {x}")),
+                },
+                0b010_0001 => match (instruction >> 20) & 0b1_1111 {
+                    0b0_0011 => Ok(Instruction::FCVTDQ {
+                        dest: frd,
+                        src: frs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    x => Err(format!("unknown OpFp func7=0b010_0001 rs2: {x}")),
+                },
+                0b101_0011 => match func3 {
+                    0b010 => Ok(Instruction::FEQQ {
+                        dest: rd,
+                        src1: frs1,
+                        src2: frs2,
+                    }),
+                    0b001 => Ok(Instruction::FLTQ {
+                        dest: rd,
+                        src1: frs1,
+                        src2: frs2,
+                    }),
+                    0b000 => Ok(Instruction::FLEQ {
+                        dest: rd,
+                        src1: frs1,
+                        src2: frs2,
+                    }),
+                    x => Err(format!("unknown OpFp func7=0b101_0011 func3: {x}")),
+                },
+                0b111_0011 if func3 == 0b001 => {
+                    if config.strict && rs2 != IRegister::Zero {
+                        Err("FCLASS.Q expects rs2 to be 0".to_owned())
+                    } else {
+                        Ok(Instruction::FCLASSQ {
+                            dest: rd,
+                            src: frs1,
+                        })
+                    }
+                }
+                0b110_0011 => match (instruction >> 20) & 0b1_1111 {
+                    0b0_0000 => Ok(Instruction::FCVTWQ {
+                        dest: rd,
+                        src: frs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    0b0_0001 => Ok(Instruction::FCVTWUQ {
+                        dest: rd,
+                        src: frs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    0b0_0010 => Ok(Instruction::FCVTLQ {
+                        dest: rd,
+                        src: frs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    0b0_0011 => Ok(Instruction::FCVTLUQ {
+                        dest: rd,
+                        src: frs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    x => Err(format!("unknown OpFp func7=0b110_0011 rs2: {x}")),
+                },
+                0b110_1011 => match (instruction >> 20) & 0b1_1111 {
+                    0b0_0000 => Ok(Instruction::FCVTQW {
+                        dest: frd,
+                        src: rs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    0b0_0001 => Ok(Instruction::FCVTQWU {
+                        dest: frd,
+                        src: rs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    0b0_0010 => Ok(Instruction::FCVTQL {
+                        dest: frd,
+                        src: rs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    0b0_0011 => Ok(Instruction::FCVTQLU {
+                        dest: frd,
+                        src: rs1,
+                        rm: RoundingMode::decode(func3, config)?,
+                    }),
+                    x => Err(format!("unknown OpFp func7=0b110_1011 rs2: {x}")),
+                },
                 x => Err(format!("Unknown OpFp func7: {x}")),
             },
+            #[cfg(feature = "rv128")]
+            Opcode::OpImm64 => match func3 {
+                0b000 => Ok(Instruction::ADDID {
+                    dest: rd,
+                    src: rs1,
+                    imm: i_immediate,
+                }),
+                0b001 if func7 >> 1 == 0 => Ok(Instruction::SLLID {
+                    dest: rd,
+                    src: rs1,
+                    shamt,
+                }),
+                0b101 if func7 >> 1 == 0 => Ok(Instruction::SRLID {
+                    dest: rd,
+                    src: rs1,
+                    shamt,
+                }),
+                0b101 if func7 >> 1 == 0b010_000 => Ok(Instruction::SRAID {
+                    dest: rd,
+                    src: rs1,
+                    shamt,
+                }),
+                _ => Err(format!("unknown OpImm64. func3: {func3}, func7: {func7}")),
+            },
+            #[cfg(feature = "rv128")]
+            Opcode::Op64 => match (func3, func7) {
+                (0b000, 0b000_0000) => Ok(Instruction::ADDD {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b000, 0b010_0000) => Ok(Instruction::SUBD {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b001, 0b000_0000) => Ok(Instruction::SLLD {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b101, 0b000_0000) => Ok(Instruction::SRLD {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                (0b101, 0b010_0000) => Ok(Instruction::SRAD {
+                    dest: rd,
+                    src1: rs1,
+                    src2: rs2,
+                }),
+                _ => Err(format!("unknown Op64. func3: {func3}, func7: {func7}")),
+            },
             Opcode::Reserved => Err("instruction uses reserved opcode".to_owned()),
+            Opcode::OpV | Opcode::OpVe => {
+                VectorInstruction::decode_with(instruction, config).map(Instruction::Vector)
+            }
             Opcode::Madd => {
                 if func7 & 0b11 == 0 {
                     Ok(Instruction::FMADDS {
@@ -1970,7 +6101,15 @@ impl Instruction {
                         src1: frs1,
                         src2: frs2,
                         src3: frs3,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
+                    })
+                } else if func7 & 0b11 == 0b11 {
+                    Ok(Instruction::FMADDQ {
+                        dest: frd,
+                        src1: frs1,
+                        src2: frs2,
+                        src3: frs3,
+                        rm: RoundingMode::decode(func3, config)?,
                     })
                 } else {
                     Err(format!(
@@ -1986,7 +6125,15 @@ impl Instruction {
                         src1: frs1,
                         src2: frs2,
                         src3: frs3,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
+                    })
+                } else if func7 & 0b11 == 0b11 {
+                    Ok(Instruction::FMSUBQ {
+                        dest: frd,
+                        src1: frs1,
+                        src2: frs2,
+                        src3: frs3,
+                        rm: RoundingMode::decode(func3, config)?,
                     })
                 } else {
                     Err(format!(
@@ -2002,7 +6149,15 @@ impl Instruction {
                         src1: frs1,
                         src2: frs2,
                         src3: frs3,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
+                    })
+                } else if func7 & 0b11 == 0b11 {
+                    Ok(Instruction::FNMSUBQ {
+                        dest: frd,
+                        src1: frs1,
+                        src2: frs2,
+                        src3: frs3,
+                        rm: RoundingMode::decode(func3, config)?,
                     })
                 } else {
                     Err(format!(
@@ -2018,7 +6173,15 @@ impl Instruction {
                         src1: frs1,
                         src2: frs2,
                         src3: frs3,
-                        rm: RoundingMode::from_int(func3)?,
+                        rm: RoundingMode::decode(func3, config)?,
+                    })
+                } else if func7 & 0b11 == 0b11 {
+                    Ok(Instruction::FNMADDQ {
+                        dest: frd,
+                        src1: frs1,
+                        src2: frs2,
+                        src3: frs3,
+                        rm: RoundingMode::decode(func3, config)?,
                     })
                 } else {
                     Err(format!(
@@ -2028,7 +6191,32 @@ impl Instruction {
                 }
             }
             Opcode::System => match func3 {
-                0b000 => Err("Reserved func3 in Opcode SYSTEM".to_owned()),
+                0b000 if func7 == 0b000_1001 => {
+                    if config.strict && rd != IRegister::Zero {
+                        Err("reserved register fields not set to zero".to_owned())
+                    } else {
+                        Ok(Instruction::SFENCEVMA {
+                            vaddr: rs1,
+                            asid: rs2,
+                        })
+                    }
+                }
+                0b000 => {
+                    if config.strict && (rd != IRegister::Zero || rs1 != IRegister::Zero) {
+                        Err("reserved register fields not set to zero".to_owned())
+                    } else {
+                        match instruction >> 20 {
+                            0x000 => Ok(Instruction::ECALL),
+                            0x001 => Ok(Instruction::EBREAK),
+                            0x102 => Ok(Instruction::SRET),
+                            0x302 => Ok(Instruction::MRET),
+                            0x702 => Ok(Instruction::MNRET),
+                            0x7b2 => Ok(Instruction::DRET),
+                            0x105 => Ok(Instruction::WFI),
+                            x => Err(format!("unknown SYSTEM instruction: {x:#05x}")),
+                        }
+                    }
+                }
                 0b001 => Ok(Instruction::CSRRW {
                     dest: rd,
                     src: rs1,
@@ -2044,6 +6232,33 @@ impl Instruction {
                     src: rs1,
                     csr: CSR::from_u32(instruction),
                 }),
+                // the shadow stack instructions of Zicfiss are may-be-operations of Zimop, which uses this func3
+                0b100 if config.extensions.zicfiss => {
+                    // sspush and sspopchk only take the link registers
+                    let link = [IRegister::ReturnAddress, IRegister::T0];
+                    if func7 == 0b110_0111
+                        && rd == IRegister::Zero
+                        && rs1 == IRegister::Zero
+                        && link.contains(&rs2)
+                    {
+                        Ok(Instruction::SSPUSH { src: rs2 })
+                    } else if instruction >> 20 == 0xcdc
+                        && rd == IRegister::Zero
+                        && link.contains(&rs1)
+                    {
+                        Ok(Instruction::SSPOPCHK { src: rs1 })
+                    } else if instruction >> 20 == 0xcdc
+                        && rs1 == IRegister::Zero
+                        && rd != IRegister::Zero
+                    {
+                        Ok(Instruction::SSRDP { dest: rd })
+                    } else {
+                        Err(
+                            "unknown may-be-operation, only those of Zicfiss are supported"
+                                .to_owned(),
+                        )
+                    }
+                }
                 0b100 => Err("Reserved func3 in Opcode SYSTEM".to_owned()),
                 0b101 => Ok(Instruction::CSRRWI {
                     dest: rd,
@@ -2065,6 +6280,8 @@ impl Instruction {
         }
     }
 
+    // zero fields are kept so that every encoding lists all of its fields
+    #[allow(clippy::identity_op)]
     pub fn encode(instruction: &Instruction) -> u32 {
         match instruction {
             Instruction::LUI { dest, imm } => imm.to_u32() | dest.rd() | 0b0110111,
@@ -2213,71 +6430,432 @@ impl Instruction {
             Instruction::SRAW { dest, src1, src2 } => {
                 0b0100000 << 25 | src2.rs2() | src1.rs1() | 0b101 << 12 | dest.rd() | 0b0111011
             }
+            #[cfg(feature = "rv128")]
+            Instruction::LQ { dest, base, offset } => {
+                offset.to_u32() | base.rs1() | 0b010 << 12 | dest.rd() | 0b0001111
+            }
+            #[cfg(feature = "rv128")]
+            Instruction::LDU { dest, base, offset } => {
+                offset.to_u32() | base.rs1() | 0b111 << 12 | dest.rd() | 0b0000011
+            }
+            #[cfg(feature = "rv128")]
+            Instruction::SQ { src, base, offset } => {
+                offset.to_u32() | src.rs2() | base.rs1() | 0b100 << 12 | 0b0100011
+            }
+            #[cfg(feature = "rv128")]
+            Instruction::ADDID { dest, src, imm } => {
+                imm.to_u32() | src.rs1() | 0b000 << 12 | dest.rd() | 0b1011011
+            }
+            #[cfg(feature = "rv128")]
+            Instruction::SLLID { dest, src, shamt } => {
+                shamt.to_u32() | src.rs1() | 0b001 << 12 | dest.rd() | 0b1011011
+            }
+            #[cfg(feature = "rv128")]
+            Instruction::SRLID { dest, src, shamt } => {
+                shamt.to_u32() | src.rs1() | 0b101 << 12 | dest.rd() | 0b1011011
+            }
+            #[cfg(feature = "rv128")]
+            Instruction::SRAID { dest, src, shamt } => {
+                0b0100000 << 25 | shamt.to_u32() | src.rs1() | 0b101 << 12 | dest.rd() | 0b1011011
+            }
+            #[cfg(feature = "rv128")]
+            Instruction::ADDD { dest, src1, src2 } => {
+                src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b1111011
+            }
+            #[cfg(feature = "rv128")]
+            Instruction::SUBD { dest, src1, src2 } => {
+                0b0100000 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b1111011
+            }
+            #[cfg(feature = "rv128")]
+            Instruction::SLLD { dest, src1, src2 } => {
+                src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b1111011
+            }
+            #[cfg(feature = "rv128")]
+            Instruction::SRLD { dest, src1, src2 } => {
+                src2.rs2() | src1.rs1() | 0b101 << 12 | dest.rd() | 0b1111011
+            }
+            #[cfg(feature = "rv128")]
+            Instruction::SRAD { dest, src1, src2 } => {
+                0b0100000 << 25 | src2.rs2() | src1.rs1() | 0b101 << 12 | dest.rd() | 0b1111011
+            }
             Instruction::MUL { dest, src1, src2 } => {
                 0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b0110011
             }
             Instruction::MULH { dest, src1, src2 } => {
                 0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b0110011
             }
-            Instruction::MULHSU { dest, src1, src2 } => {
-                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b010 << 12 | dest.rd() | 0b0110011
+            Instruction::MULHSU { dest, src1, src2 } => {
+                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b010 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::MULHU { dest, src1, src2 } => {
+                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b011 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::DIV { dest, src1, src2 } => {
+                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b100 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::DIVU { dest, src1, src2 } => {
+                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b101 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::REM { dest, src1, src2 } => {
+                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b110 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::REMU { dest, src1, src2 } => {
+                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b111 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::MULW { dest, src1, src2 } => {
+                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b0111011
+            }
+            Instruction::DIVW { dest, src1, src2 } => {
+                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b100 << 12 | dest.rd() | 0b0111011
+            }
+            Instruction::DIVUW { dest, src1, src2 } => {
+                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b101 << 12 | dest.rd() | 0b0111011
+            }
+            Instruction::REMW { dest, src1, src2 } => {
+                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b110 << 12 | dest.rd() | 0b0111011
+            }
+            Instruction::REMUW { dest, src1, src2 } => {
+                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b111 << 12 | dest.rd() | 0b0111011
+            }
+            Instruction::LRW { dest, addr, aq, rl } => {
+                0b00010 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            Instruction::SCW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b00011 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            Instruction::AMOSWAPW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b00001 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            Instruction::AMOADDW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b00000 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            Instruction::AMOXORW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b00100 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            Instruction::AMOANDW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b01100 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            Instruction::AMOORW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b01000 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            Instruction::AMOMINW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b10000 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            Instruction::AMOMAXW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b10100 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            Instruction::AMOMINUW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b11000 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::MULHU { dest, src1, src2 } => {
-                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b011 << 12 | dest.rd() | 0b0110011
+            Instruction::AMOMAXUW {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b11100 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::DIV { dest, src1, src2 } => {
-                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b100 << 12 | dest.rd() | 0b0110011
+            Instruction::LRD { dest, addr, aq, rl } => {
+                0b00010 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | addr.rs1()
+                    | 0b011 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::DIVU { dest, src1, src2 } => {
-                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b101 << 12 | dest.rd() | 0b0110011
+            Instruction::SCD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b00011 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b011 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::REM { dest, src1, src2 } => {
-                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b110 << 12 | dest.rd() | 0b0110011
+            Instruction::AMOSWAPD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b00001 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b011 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::REMU { dest, src1, src2 } => {
-                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b111 << 12 | dest.rd() | 0b0110011
+            Instruction::AMOADDD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b00000 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b011 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::MULW { dest, src1, src2 } => {
-                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b0111011
+            Instruction::AMOXORD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b00100 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b011 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::DIVW { dest, src1, src2 } => {
-                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b100 << 12 | dest.rd() | 0b0111011
+            Instruction::AMOANDD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b01100 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b011 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::DIVUW { dest, src1, src2 } => {
-                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b101 << 12 | dest.rd() | 0b0111011
+            Instruction::AMOORD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b01000 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b011 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::REMW { dest, src1, src2 } => {
-                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b110 << 12 | dest.rd() | 0b0111011
+            Instruction::AMOMIND {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b10000 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b011 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::REMUW { dest, src1, src2 } => {
-                0b0000001 << 25 | src2.rs2() | src1.rs1() | 0b111 << 12 | dest.rd() | 0b0111011
+            Instruction::AMOMAXD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b10100 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b011 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::LRW { dest, addr, aq, rl } => {
-                0b00010 << 27
+            Instruction::AMOMINUD {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b11000 << 27
                     | aqb(*aq)
                     | rlb(*rl)
+                    | src.rs2()
                     | addr.rs1()
-                    | 0b010 << 12
+                    | 0b011 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::SCW {
+            Instruction::AMOMAXUD {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                0b00011 << 27
+                0b11100 << 27
                     | aqb(*aq)
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b010 << 12
+                    | 0b011 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOSWAPW {
+            Instruction::AMOSWAPB {
                 dest,
                 addr,
                 src,
@@ -2289,11 +6867,11 @@ impl Instruction {
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b010 << 12
+                    | 0b000 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOADDW {
+            Instruction::AMOADDB {
                 dest,
                 addr,
                 src,
@@ -2305,11 +6883,11 @@ impl Instruction {
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b010 << 12
+                    | 0b000 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOXORW {
+            Instruction::AMOXORB {
                 dest,
                 addr,
                 src,
@@ -2321,11 +6899,11 @@ impl Instruction {
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b010 << 12
+                    | 0b000 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOANDW {
+            Instruction::AMOANDB {
                 dest,
                 addr,
                 src,
@@ -2337,11 +6915,11 @@ impl Instruction {
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b010 << 12
+                    | 0b000 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOORW {
+            Instruction::AMOORB {
                 dest,
                 addr,
                 src,
@@ -2353,11 +6931,11 @@ impl Instruction {
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b010 << 12
+                    | 0b000 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOMINW {
+            Instruction::AMOMINB {
                 dest,
                 addr,
                 src,
@@ -2369,11 +6947,11 @@ impl Instruction {
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b010 << 12
+                    | 0b000 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOMAXW {
+            Instruction::AMOMAXB {
                 dest,
                 addr,
                 src,
@@ -2385,11 +6963,11 @@ impl Instruction {
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b010 << 12
+                    | 0b000 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOMINUW {
+            Instruction::AMOMINUB {
                 dest,
                 addr,
                 src,
@@ -2401,11 +6979,11 @@ impl Instruction {
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b010 << 12
+                    | 0b000 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOMAXUW {
+            Instruction::AMOMAXUB {
                 dest,
                 addr,
                 src,
@@ -2417,171 +6995,538 @@ impl Instruction {
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b010 << 12
+                    | 0b000 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::LRD { dest, addr, aq, rl } => {
-                0b00010 << 27
+            Instruction::AMOSWAPH {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b00001 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b001 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            Instruction::AMOADDH {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b00000 << 27
+                    | aqb(*aq)
+                    | rlb(*rl)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b001 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            Instruction::AMOXORH {
+                dest,
+                addr,
+                src,
+                aq,
+                rl,
+            } => {
+                0b00100 << 27
                     | aqb(*aq)
                     | rlb(*rl)
+                    | src.rs2()
                     | addr.rs1()
-                    | 0b011 << 12
+                    | 0b001 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::SCD {
+            Instruction::AMOANDH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                0b00011 << 27
+                0b01100 << 27
                     | aqb(*aq)
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b011 << 12
+                    | 0b001 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOSWAPD {
+            Instruction::AMOORH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                0b00001 << 27
+                0b01000 << 27
                     | aqb(*aq)
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b011 << 12
+                    | 0b001 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOADDD {
+            Instruction::AMOMINH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                0b00000 << 27
+                0b10000 << 27
                     | aqb(*aq)
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b011 << 12
+                    | 0b001 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOXORD {
+            Instruction::AMOMAXH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                0b00100 << 27
+                0b10100 << 27
                     | aqb(*aq)
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b011 << 12
+                    | 0b001 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOANDD {
+            Instruction::AMOMINUH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                0b01100 << 27
+                0b11000 << 27
                     | aqb(*aq)
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b011 << 12
+                    | 0b001 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOORD {
+            Instruction::AMOMAXUH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                0b01000 << 27
+                0b11100 << 27
                     | aqb(*aq)
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b011 << 12
+                    | 0b001 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOMIND {
+            Instruction::AMOCASB {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                0b10000 << 27
+                0b00101 << 27
                     | aqb(*aq)
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b011 << 12
+                    | 0b000 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOMAXD {
+            Instruction::AMOCASH {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                0b10100 << 27
+                0b00101 << 27
                     | aqb(*aq)
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b011 << 12
+                    | 0b001 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOMINUD {
+            Instruction::FLW { dest, base, offset } => {
+                offset.to_u32() | base.rs1() | 0b010 << 12 | dest.rd() | 0b0000111
+            }
+            Instruction::FSW { base, src, offset } => {
+                offset.to_u32() | src.rs2() | base.rs1() | 0b010 << 12 | 0b0100111
+            }
+            Instruction::FMADDS {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => src3.rs3() | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1000011,
+            Instruction::FMSUBS {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => src3.rs3() | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1000111,
+            Instruction::FNMSUBS {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => src3.rs3() | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1001011,
+            Instruction::FNMADDS {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => src3.rs3() | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1001111,
+            Instruction::FADDS {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => 0b0000000 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
+            Instruction::FSUBS {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => 0b0000100 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
+            Instruction::FMULS {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => 0b0001000 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
+            Instruction::FDIVS {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => 0b0001100 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
+            Instruction::FSQRTS { dest, src, rm } => {
+                0b0101100 << 25 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FSGNJS { dest, src1, src2 } => {
+                0b0010000 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b1010011
+            }
+
+            Instruction::FSGNJNS { dest, src1, src2 } => {
+                0b0010000 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FSGNJXS { dest, src1, src2 } => {
+                0b0010000 << 25 | src2.rs2() | src1.rs1() | 0b010 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FMINS { dest, src1, src2 } => {
+                0b0010100 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FMAXS { dest, src1, src2 } => {
+                0b0010100 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTWS { dest, src, rm } => {
+                0b1100000 << 25 | 0b00000 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTWUS { dest, src, rm } => {
+                0b1100000 << 25 | 0b00001 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FMVXW { dest, src } => 0b1110000 << 25 | src.rs1() | dest.rd() | 0b1010011,
+            Instruction::FEQS { dest, src1, src2 } => {
+                0b1010000 << 25 | src2.rs2() | src1.rs1() | 0b010 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FLTS { dest, src1, src2 } => {
+                0b1010000 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FLES { dest, src1, src2 } => {
+                0b1010000 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FCLASSS { dest, src } => {
+                0b1110000 << 25 | src.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTSW { dest, src, rm } => {
+                0b1101000 << 25 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTSWU { dest, src, rm } => {
+                0b1101000 << 25 | 0b00001 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FMVWX { dest, src } => 0b1111000 << 25 | src.rs1() | dest.rd() | 0b1010011,
+            Instruction::FCVTLS { dest, src, rm } => {
+                0b1100000 << 25 | 0b00010 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTLUS { dest, src, rm } => {
+                0b1100000 << 25 | 0b00011 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTSL { dest, src, rm } => {
+                0b1101000 << 25 | 0b00010 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTSLU { dest, src, rm } => {
+                0b1101000 << 25 | 0b00011 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FLQ { dest, base, offset } => {
+                offset.to_u32() | base.rs1() | 0b100 << 12 | dest.rd() | 0b0000111
+            }
+            Instruction::FSQ { base, src, offset } => {
+                offset.to_u32() | src.rs2() | base.rs1() | 0b100 << 12 | 0b0100111
+            }
+            Instruction::FMADDQ {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => {
+                src3.rs3()
+                    | 0b11 << 25
+                    | src2.rs2()
+                    | src1.rs1()
+                    | rm.to_u32()
+                    | dest.rd()
+                    | 0b1000011
+            }
+            Instruction::FMSUBQ {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => {
+                src3.rs3()
+                    | 0b11 << 25
+                    | src2.rs2()
+                    | src1.rs1()
+                    | rm.to_u32()
+                    | dest.rd()
+                    | 0b1000111
+            }
+            Instruction::FNMSUBQ {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => {
+                src3.rs3()
+                    | 0b11 << 25
+                    | src2.rs2()
+                    | src1.rs1()
+                    | rm.to_u32()
+                    | dest.rd()
+                    | 0b1001011
+            }
+            Instruction::FNMADDQ {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => {
+                src3.rs3()
+                    | 0b11 << 25
+                    | src2.rs2()
+                    | src1.rs1()
+                    | rm.to_u32()
+                    | dest.rd()
+                    | 0b1001111
+            }
+            Instruction::FADDQ {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => 0b0000011 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
+            Instruction::FSUBQ {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => 0b0000111 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
+            Instruction::FMULQ {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => 0b0001011 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
+            Instruction::FDIVQ {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => 0b0001111 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
+            Instruction::FSQRTQ { dest, src, rm } => {
+                0b0101111 << 25 | 0b00000 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FSGNJQ { dest, src1, src2 } => {
+                0b0010011 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FSGNJNQ { dest, src1, src2 } => {
+                0b0010011 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FSGNJXQ { dest, src1, src2 } => {
+                0b0010011 << 25 | src2.rs2() | src1.rs1() | 0b010 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FMINQ { dest, src1, src2 } => {
+                0b0010111 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FMAXQ { dest, src1, src2 } => {
+                0b0010111 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTSQ { dest, src, rm } => {
+                0b0100000 << 25 | 0b00011 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTQS { dest, src, rm } => {
+                0b0100011 << 25 | 0b00000 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTDQ { dest, src, rm } => {
+                0b0100001 << 25 | 0b00011 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTQD { dest, src, rm } => {
+                0b0100011 << 25 | 0b00001 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FEQQ { dest, src1, src2 } => {
+                0b1010011 << 25 | src2.rs2() | src1.rs1() | 0b010 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FLTQ { dest, src1, src2 } => {
+                0b1010011 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FLEQ { dest, src1, src2 } => {
+                0b1010011 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FCLASSQ { dest, src } => {
+                0b1110011 << 25 | src.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTWQ { dest, src, rm } => {
+                0b1100011 << 25 | 0b00000 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTWUQ { dest, src, rm } => {
+                0b1100011 << 25 | 0b00001 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTQW { dest, src, rm } => {
+                0b1101011 << 25 | 0b00000 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTQWU { dest, src, rm } => {
+                0b1101011 << 25 | 0b00001 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTLQ { dest, src, rm } => {
+                0b1100011 << 25 | 0b00010 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTLUQ { dest, src, rm } => {
+                0b1100011 << 25 | 0b00011 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTQL { dest, src, rm } => {
+                0b1101011 << 25 | 0b00010 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTQLU { dest, src, rm } => {
+                0b1101011 << 25 | 0b00011 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FLH { dest, base, offset } => {
+                offset.to_u32() | base.rs1() | 0b001 << 12 | dest.rd() | 0b0000111
+            }
+            Instruction::FSH { base, src, offset } => {
+                offset.to_u32() | src.rs2() | base.rs1() | 0b001 << 12 | 0b0100111
+            }
+            Instruction::FMVXH { dest, src } => 0b1110010 << 25 | src.rs1() | dest.rd() | 0b1010011,
+            Instruction::FMVHX { dest, src } => 0b1111010 << 25 | src.rs1() | dest.rd() | 0b1010011,
+            Instruction::FCVTBF16S { dest, src, rm } => {
+                0b0100010 << 25 | 0b01000 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::FCVTSBF16 { dest, src, rm } => {
+                0b0100000 << 25 | 0b00110 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            }
+            Instruction::CSRRW { dest, src, csr } => {
+                csr.to_u32() | src.rs1() | 0b001 << 12 | dest.rd() | 0b1110011
+            }
+            Instruction::CSRRS { dest, src, csr } => {
+                csr.to_u32() | src.rs1() | 0b010 << 12 | dest.rd() | 0b1110011
+            }
+            Instruction::CSRRC { dest, src, csr } => {
+                csr.to_u32() | src.rs1() | 0b011 << 12 | dest.rd() | 0b1110011
+            }
+            Instruction::CSRRWI { dest, imm, csr } => {
+                csr.to_u32() | imm.to_u32() | 0b101 << 12 | dest.rd() | 0b1110011
+            }
+            Instruction::CSRRSI { dest, imm, csr } => {
+                csr.to_u32() | imm.to_u32() | 0b110 << 12 | dest.rd() | 0b1110011
+            }
+            Instruction::CSRRCI { dest, imm, csr } => {
+                csr.to_u32() | imm.to_u32() | 0b111 << 12 | dest.rd() | 0b1110011
+            }
+            Instruction::FENCEI => 0b001 << 12 | 0b0001111,
+            Instruction::CBOCLEAN { base } => 0x001 << 20 | base.rs1() | 0b010 << 12 | 0b0001111,
+            Instruction::CBOFLUSH { base } => 0x002 << 20 | base.rs1() | 0b010 << 12 | 0b0001111,
+            Instruction::CBOINVAL { base } => 0x000 << 20 | base.rs1() | 0b010 << 12 | 0b0001111,
+            Instruction::CBOZERO { base } => 0x004 << 20 | base.rs1() | 0b010 << 12 | 0b0001111,
+            Instruction::PREFETCHI { base, offset } => {
+                offset.to_u32() | 0b00000 << 20 | base.rs1() | 0b110 << 12 | 0b0010011
+            }
+            Instruction::PREFETCHR { base, offset } => {
+                offset.to_u32() | 0b00001 << 20 | base.rs1() | 0b110 << 12 | 0b0010011
+            }
+            Instruction::PREFETCHW { base, offset } => {
+                offset.to_u32() | 0b00011 << 20 | base.rs1() | 0b110 << 12 | 0b0010011
+            }
+            // fence w,0
+            Instruction::PAUSE => 0b0001 << 24 | 0b0001111,
+            // add x0,x0,x2 to add x0,x0,x5
+            Instruction::NTLP1 => IRegister::StackPointer.rs2() | 0b0110011,
+            Instruction::NTLPALL => IRegister::GlobalPointer.rs2() | 0b0110011,
+            Instruction::NTLS1 => IRegister::ThreadPointer.rs2() | 0b0110011,
+            Instruction::NTLALL => IRegister::T0.rs2() | 0b0110011,
+            Instruction::LPAD { label } => label.to_u32() | 0b0010111,
+            Instruction::SSPUSH { src } => 0b110_0111 << 25 | src.rs2() | 0b100 << 12 | 0b1110011,
+            Instruction::SSPOPCHK { src } => 0xcdc << 20 | src.rs1() | 0b100 << 12 | 0b1110011,
+            Instruction::SSRDP { dest } => 0xcdc << 20 | 0b100 << 12 | dest.rd() | 0b1110011,
+            Instruction::SSAMOSWAPW {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                0b11000 << 27
+                0b01001 << 27
                     | aqb(*aq)
                     | rlb(*rl)
                     | src.rs2()
                     | addr.rs1()
-                    | 0b011 << 12
+                    | 0b010 << 12
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::AMOMAXUD {
+            Instruction::SSAMOSWAPD {
                 dest,
                 addr,
                 src,
                 aq,
                 rl,
             } => {
-                0b11100 << 27
+                0b01001 << 27
                     | aqb(*aq)
                     | rlb(*rl)
                     | src.rs2()
@@ -2590,145 +7535,597 @@ impl Instruction {
                     | dest.rd()
                     | 0b0101111
             }
-            Instruction::FLW { dest, base, offset } => {
-                offset.to_u32() | base.rs1() | 0b010 << 12 | dest.rd() | 0b0000111
+            Instruction::SRET => 0x102 << 20 | 0b1110011,
+            Instruction::MRET => 0x302 << 20 | 0b1110011,
+            Instruction::MNRET => 0x702 << 20 | 0b1110011,
+            Instruction::DRET => 0x7b2 << 20 | 0b1110011,
+            Instruction::WFI => 0x105 << 20 | 0b1110011,
+            Instruction::SFENCEVMA { vaddr, asid } => {
+                0b0001001 << 25 | asid.rs2() | vaddr.rs1() | 0b1110011
+            }
+            Instruction::PACK { dest, src1, src2 } => {
+                0b0000100 << 25 | src2.rs2() | src1.rs1() | 0b100 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::PACKH { dest, src1, src2 } => {
+                0b0000100 << 25 | src2.rs2() | src1.rs1() | 0b111 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::PACKW { dest, src1, src2 } => {
+                0b0000100 << 25 | src2.rs2() | src1.rs1() | 0b100 << 12 | dest.rd() | 0b0111011
+            }
+            Instruction::CLMUL { dest, src1, src2 } => {
+                0b0000101 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::CLMULH { dest, src1, src2 } => {
+                0b0000101 << 25 | src2.rs2() | src1.rs1() | 0b011 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::XPERM4 { dest, src1, src2 } => {
+                0b0010100 << 25 | src2.rs2() | src1.rs1() | 0b010 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::XPERM8 { dest, src1, src2 } => {
+                0b0010100 << 25 | src2.rs2() | src1.rs1() | 0b100 << 12 | dest.rd() | 0b0110011
             }
-            Instruction::FSW { base, src, offset } => {
-                offset.to_u32() | src.rs2() | base.rs1() | 0b010 << 12 | 0b0100111
+            Instruction::BREV8 { dest, src } => {
+                0x687 << 20 | src.rs1() | 0b101 << 12 | dest.rd() | 0b0010011
             }
-            Instruction::FMADDS {
-                dest,
-                src1,
-                src2,
-                src3,
-                rm,
-            } => src3.rs3() | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1000011,
-            Instruction::FMSUBS {
-                dest,
-                src1,
-                src2,
-                src3,
-                rm,
-            } => src3.rs3() | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1000111,
-            Instruction::FNMSUBS {
-                dest,
-                src1,
-                src2,
-                src3,
-                rm,
-            } => src3.rs3() | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1001011,
-            Instruction::FNMADDS {
-                dest,
-                src1,
-                src2,
-                src3,
-                rm,
-            } => src3.rs3() | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1001111,
-            Instruction::FADDS {
+            Instruction::ZIP { dest, src } => {
+                0x08f << 20 | src.rs1() | 0b001 << 12 | dest.rd() | 0b0010011
+            }
+            Instruction::UNZIP { dest, src } => {
+                0x08f << 20 | src.rs1() | 0b101 << 12 | dest.rd() | 0b0010011
+            }
+            Instruction::ANDN { dest, src1, src2 } => {
+                0b0100000 << 25 | src2.rs2() | src1.rs1() | 0b111 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::ORN { dest, src1, src2 } => {
+                0b0100000 << 25 | src2.rs2() | src1.rs1() | 0b110 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::XNOR { dest, src1, src2 } => {
+                0b0100000 << 25 | src2.rs2() | src1.rs1() | 0b100 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::ROL { dest, src1, src2 } => {
+                0b0110000 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::ROR { dest, src1, src2 } => {
+                0b0110000 << 25 | src2.rs2() | src1.rs1() | 0b101 << 12 | dest.rd() | 0b0110011
+            }
+            Instruction::RORI { dest, src, shamt } => {
+                0b011000 << 26 | shamt.to_u32() | src.rs1() | 0b101 << 12 | dest.rd() | 0b0010011
+            }
+            Instruction::ROLW { dest, src1, src2 } => {
+                0b0110000 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b0111011
+            }
+            Instruction::RORW { dest, src1, src2 } => {
+                0b0110000 << 25 | src2.rs2() | src1.rs1() | 0b101 << 12 | dest.rd() | 0b0111011
+            }
+            Instruction::RORIW { dest, src, shamt } => {
+                0b0110000 << 25 | shamt.to_u32() | src.rs1() | 0b101 << 12 | dest.rd() | 0b0011011
+            }
+            Instruction::REV8 { dest, src } => {
+                0x6b8 << 20 | src.rs1() | 0b101 << 12 | dest.rd() | 0b0010011
+            }
+            Instruction::REV8RV32 { dest, src } => {
+                0x698 << 20 | src.rs1() | 0b101 << 12 | dest.rd() | 0b0010011
+            }
+            Instruction::AES32ESI {
                 dest,
                 src1,
                 src2,
-                rm,
-            } => 0b0000000 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
-            Instruction::FSUBS {
+                bs,
+            } => bs.to_u32() | 0b10001 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011,
+            Instruction::AES32ESMI {
                 dest,
                 src1,
                 src2,
-                rm,
-            } => 0b0000100 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
-            Instruction::FMULS {
+                bs,
+            } => bs.to_u32() | 0b10011 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011,
+            Instruction::AES32DSI {
                 dest,
                 src1,
                 src2,
-                rm,
-            } => 0b0001000 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
-            Instruction::FDIVS {
+                bs,
+            } => bs.to_u32() | 0b10101 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011,
+            Instruction::AES32DSMI {
                 dest,
                 src1,
                 src2,
-                rm,
-            } => 0b0001100 << 25 | src2.rs2() | src1.rs1() | rm.to_u32() | dest.rd() | 0b1010011,
-            Instruction::FSQRTS { dest, src, rm } => {
-                0b0101100 << 25 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+                bs,
+            } => bs.to_u32() | 0b10111 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011,
+            Instruction::AES64ES { dest, src1, src2 } => {
+                0b0011001 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011
             }
-            Instruction::FSGNJS { dest, src1, src2 } => {
-                0b0010000 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b1010011
+            Instruction::AES64ESM { dest, src1, src2 } => {
+                0b0011011 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011
             }
-
-            Instruction::FSGNJNS { dest, src1, src2 } => {
-                0b0010000 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            Instruction::AES64DS { dest, src1, src2 } => {
+                0b0011101 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011
             }
-            Instruction::FSGNJXS { dest, src1, src2 } => {
-                0b0010000 << 25 | src2.rs2() | src1.rs1() | 0b010 << 12 | dest.rd() | 0b1010011
+            Instruction::AES64DSM { dest, src1, src2 } => {
+                0b0011111 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011
             }
-            Instruction::FMINS { dest, src1, src2 } => {
-                0b0010100 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b1010011
+            Instruction::AES64KS2 { dest, src1, src2 } => {
+                0b0111111 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011
             }
-            Instruction::FMAXS { dest, src1, src2 } => {
-                0b0010100 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            Instruction::SHA512SUM0R { dest, src1, src2 } => {
+                0b0101000 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011
             }
-            Instruction::FCVTWS { dest, src, rm } => {
-                0b1100000 << 25 | 0b00000 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            Instruction::SHA512SUM1R { dest, src1, src2 } => {
+                0b0101001 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011
             }
-            Instruction::FCVTWUS { dest, src, rm } => {
-                0b1100000 << 25 | 0b00001 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            Instruction::SHA512SIG0L { dest, src1, src2 } => {
+                0b0101010 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011
             }
-            Instruction::FMVXW { dest, src } => 0b1110000 << 25 | src.rs1() | dest.rd() | 0b1010011,
-            Instruction::FEQS { dest, src1, src2 } => {
-                0b1010000 << 25 | src2.rs2() | src1.rs1() | 0b010 << 12 | dest.rd() | 0b1010011
+            Instruction::SHA512SIG0H { dest, src1, src2 } => {
+                0b0101110 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011
             }
-            Instruction::FLTS { dest, src1, src2 } => {
-                0b1010000 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            Instruction::SHA512SIG1L { dest, src1, src2 } => {
+                0b0101011 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011
             }
-            Instruction::FLES { dest, src1, src2 } => {
-                0b1010000 << 25 | src2.rs2() | src1.rs1() | 0b000 << 12 | dest.rd() | 0b1010011
+            Instruction::SHA512SIG1H { dest, src1, src2 } => {
+                0b0101111 << 25 | src2.rs2() | src1.rs1() | dest.rd() | 0b0110011
             }
-            Instruction::FCLASSS { dest, src } => {
-                0b1110000 << 25 | src.rs1() | 0b001 << 12 | dest.rd() | 0b1010011
+            Instruction::AES64IM { dest, src } => {
+                0x300 << 20 | src.rs1() | 0b001 << 12 | dest.rd() | 0b0010011
             }
-            Instruction::FCVTSW { dest, src, rm } => {
-                0b1101000 << 25 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            Instruction::SHA256SIG0 { dest, src } => {
+                0x102 << 20 | src.rs1() | 0b001 << 12 | dest.rd() | 0b0010011
             }
-            Instruction::FCVTSWU { dest, src, rm } => {
-                0b1101000 << 25 | 0b00001 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            Instruction::SHA256SIG1 { dest, src } => {
+                0x103 << 20 | src.rs1() | 0b001 << 12 | dest.rd() | 0b0010011
             }
-            Instruction::FMVWX { dest, src } => 0b1111000 << 25 | src.rs1() | dest.rd() | 0b1010011,
-            Instruction::FCVTLS { dest, src, rm } => {
-                0b1100000 << 25 | 0b00010 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            Instruction::SHA256SUM0 { dest, src } => {
+                0x100 << 20 | src.rs1() | 0b001 << 12 | dest.rd() | 0b0010011
             }
-            Instruction::FCVTLUS { dest, src, rm } => {
-                0b1100000 << 25 | 0b00011 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            Instruction::SHA256SUM1 { dest, src } => {
+                0x101 << 20 | src.rs1() | 0b001 << 12 | dest.rd() | 0b0010011
             }
-            Instruction::FCVTSL { dest, src, rm } => {
-                0b1101000 << 25 | 0b00010 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            Instruction::SHA512SIG0 { dest, src } => {
+                0x106 << 20 | src.rs1() | 0b001 << 12 | dest.rd() | 0b0010011
             }
-            Instruction::FCVTSLU { dest, src, rm } => {
-                0b1101000 << 25 | 0b00011 << 20 | src.rs1() | rm.to_u32() | dest.rd() | 0b1010011
+            Instruction::SHA512SIG1 { dest, src } => {
+                0x107 << 20 | src.rs1() | 0b001 << 12 | dest.rd() | 0b0010011
             }
-            Instruction::CSRRW { dest, src, csr } => {
-                csr.to_u32() | src.rs1() | 0b001 << 12 | dest.rd() | 0b1110011
+            Instruction::SHA512SUM0 { dest, src } => {
+                0x104 << 20 | src.rs1() | 0b001 << 12 | dest.rd() | 0b0010011
             }
-            Instruction::CSRRS { dest, src, csr } => {
-                csr.to_u32() | src.rs1() | 0b010 << 12 | dest.rd() | 0b1110011
+            Instruction::SHA512SUM1 { dest, src } => {
+                0x105 << 20 | src.rs1() | 0b001 << 12 | dest.rd() | 0b0010011
             }
-            Instruction::CSRRC { dest, src, csr } => {
-                csr.to_u32() | src.rs1() | 0b011 << 12 | dest.rd() | 0b1110011
+            Instruction::AES64KS1I { dest, src, rnum } => {
+                0b0011_0001 << 24 | rnum.to_u32() | src.rs1() | 0b001 << 12 | dest.rd() | 0b0010011
             }
-            Instruction::CSRRWI { dest, imm, csr } => {
-                csr.to_u32() | imm.to_u32() | 0b101 << 12 | dest.rd() | 0b1110011
+            #[cfg(feature = "zalasr")]
+            Instruction::LBAQ { dest, addr, rl } => {
+                0b00110 << 27
+                    | aqb(true)
+                    | rlb(*rl)
+                    | addr.rs1()
+                    | 0b000 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::CSRRSI { dest, imm, csr } => {
-                csr.to_u32() | imm.to_u32() | 0b110 << 12 | dest.rd() | 0b1110011
+            #[cfg(feature = "zalasr")]
+            Instruction::LHAQ { dest, addr, rl } => {
+                0b00110 << 27
+                    | aqb(true)
+                    | rlb(*rl)
+                    | addr.rs1()
+                    | 0b001 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::CSRRCI { dest, imm, csr } => {
-                csr.to_u32() | imm.to_u32() | 0b111 << 12 | dest.rd() | 0b1110011
+            #[cfg(feature = "zalasr")]
+            Instruction::LWAQ { dest, addr, rl } => {
+                0b00110 << 27
+                    | aqb(true)
+                    | rlb(*rl)
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | dest.rd()
+                    | 0b0101111
             }
-            Instruction::FENCEI => 0b001 << 12 | 0b0001111,
+            #[cfg(feature = "zalasr")]
+            Instruction::LDAQ { dest, addr, rl } => {
+                0b00110 << 27
+                    | aqb(true)
+                    | rlb(*rl)
+                    | addr.rs1()
+                    | 0b011 << 12
+                    | dest.rd()
+                    | 0b0101111
+            }
+            #[cfg(feature = "zalasr")]
+            Instruction::SBRL { src, addr, aq } => {
+                0b00111 << 27
+                    | aqb(*aq)
+                    | rlb(true)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b000 << 12
+                    | 0b0101111
+            }
+            #[cfg(feature = "zalasr")]
+            Instruction::SHRL { src, addr, aq } => {
+                0b00111 << 27
+                    | aqb(*aq)
+                    | rlb(true)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b001 << 12
+                    | 0b0101111
+            }
+            #[cfg(feature = "zalasr")]
+            Instruction::SWRL { src, addr, aq } => {
+                0b00111 << 27
+                    | aqb(*aq)
+                    | rlb(true)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b010 << 12
+                    | 0b0101111
+            }
+            #[cfg(feature = "zalasr")]
+            Instruction::SDRL { src, addr, aq } => {
+                0b00111 << 27
+                    | aqb(*aq)
+                    | rlb(true)
+                    | src.rs2()
+                    | addr.rs1()
+                    | 0b011 << 12
+                    | 0b0101111
+            }
+            Instruction::Vector(i) => i.encode(),
+            #[cfg(feature = "p")]
+            Instruction::P(i) => i.encode(),
+            #[cfg(feature = "xandesperf")]
+            Instruction::XAndesPerf(i) => i.encode(),
+            #[cfg(feature = "xthead")]
+            Instruction::XThead(i) => i.encode(),
+            Instruction::Custom(i) => i.encode(),
+        }
+    }
+
+    /// Encodes an instruction with its compressed encoding when it has one that `config` enables, and with its
+    /// 32-bit encoding otherwise.
+    ///
+    /// Returns the little endian bytes and the number of them used, which is 2 or 4.
+    pub fn encode_preferring_compressed(
+        instruction: &Instruction,
+        config: &AssemblerConfig,
+    ) -> ([u8; 4], usize) {
+        let compressed = CInstruction::compress(instruction, config.xlen).filter(|c| {
+            config
+                .extensions
+                .missing_compressed(CInstruction::encode(c), config.xlen)
+                .is_none()
+        });
+        match compressed {
+            Some(c) => ((CInstruction::encode(&c) as u32).to_le_bytes(), 2),
+            None => (Instruction::encode(instruction).to_le_bytes(), 4),
+        }
+    }
+}
+
+/// An `Instruction` together with the encoding it was decoded from.
+///
+/// Lenient decoding accepts encodings with fields that `Instruction::encode` does not reproduce, keeping the raw word
+/// allows these to be shown and re-emitted exactly.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct DecodedInstruction {
+    pub raw: u32,
+    pub instruction: Instruction,
+    /// The bits of the raw word that are not part of the instruction (such as the rd and rs1 fields of fence.i),
+    /// which `encode` keeps.
+    pub ignored: u32,
+}
+
+impl DecodedInstruction {
+    /// Decodes a word with the default configuration, keeping the word.
+    pub fn decode(raw: u32) -> Result<DecodedInstruction, DecodeError> {
+        DecodedInstruction::decode_with(raw, &DecoderConfig::default())
+    }
+
+    /// Decodes a word using the given configuration, keeping the word.
+    pub fn decode_with(
+        raw: u32,
+        config: &DecoderConfig,
+    ) -> Result<DecodedInstruction, DecodeError> {
+        let instruction = Instruction::decode_with(raw, config)?;
+        Ok(DecodedInstruction {
+            raw,
+            instruction,
+            ignored: raw ^ Instruction::encode(&instruction),
+        })
+    }
+
+    /// Decodes a word so that `encode` reproduces it exactly.
+    ///
+    /// Reserved fields are ignored and reserved rounding modes are kept regardless of the configuration, so every
+    /// valid encoding is accepted.
+    pub fn decode_lossless(
+        raw: u32,
+        config: &DecoderConfig,
+    ) -> Result<DecodedInstruction, DecodeError> {
+        let config = DecoderConfig {
+            strict: false,
+            reserved_rounding_modes: true,
+            ..*config
+        };
+        DecodedInstruction::decode_with(raw, &config)
+    }
+
+    /// Encodes the instruction with the ignored bits of the raw word.
+    pub fn encode(&self) -> u32 {
+        Instruction::encode(&self.instruction) | self.ignored
+    }
+
+    /// Returns the encoding as little endian bytes, as it is stored in memory.
+    pub fn bytes(&self) -> [u8; 4] {
+        self.raw.to_le_bytes()
+    }
+
+    /// Returns whether encoding the instruction gives back the raw word, encodings with reserved fields set are not
+    /// canonical.
+    pub fn is_canonical(&self) -> bool {
+        Instruction::encode(&self.instruction) == self.raw
+    }
+}
+
+impl Display for DecodedInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.instruction.fmt(f)
+    }
+}
+
+/// A 32-bit or compressed instruction.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum Inst {
+    Full(Instruction),
+    Compressed(CInstruction),
+}
+
+impl Inst {
+    /// The length of the encoding in bytes (2 or 4).
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        match self {
+            Inst::Full(_) => 4,
+            Inst::Compressed(_) => 2,
+        }
+    }
+
+    /// The encoding, only the low 16 bits are used for compressed instructions.
+    pub fn encode(&self) -> u32 {
+        match self {
+            Inst::Full(i) => Instruction::encode(i),
+            Inst::Compressed(c) => CInstruction::encode(c) as u32,
+        }
+    }
+
+    /// The encoding as the little endian bytes it is stored as in memory.
+    pub fn encode_bytes(&self) -> Vec<u8> {
+        self.encode().to_le_bytes()[..self.len()].to_vec()
+    }
+
+    /// Returns the 32-bit instruction, `None` for compressed instructions.
+    pub fn full(&self) -> Option<Instruction> {
+        match self {
+            Inst::Full(i) => Some(*i),
+            Inst::Compressed(_) => None,
+        }
+    }
+
+    /// Returns the compressed instruction, `None` for 32-bit instructions.
+    pub fn compressed(&self) -> Option<CInstruction> {
+        match self {
+            Inst::Full(_) => None,
+            Inst::Compressed(c) => Some(*c),
+        }
+    }
+
+    /// Returns the 32-bit instruction, with compressed instructions expanded.
+    pub fn expand(&self) -> Result<Instruction, String> {
+        match self {
+            Inst::Full(i) => Ok(*i),
+            Inst::Compressed(c) => c.try_expand(),
+        }
+    }
+
+    /// Returns the 32-bit instruction, panicking for compressed instructions. See `full` for a non-panicking version.
+    pub fn i(self) -> Instruction {
+        self.full()
+            .unwrap_or_else(|| panic!("i called on compressed instruction {self}"))
+    }
+
+    /// Returns the compressed instruction, panicking for 32-bit instructions. See `compressed` for a non-panicking
+    /// version.
+    pub fn c(self) -> CInstruction {
+        self.compressed()
+            .unwrap_or_else(|| panic!("c called on regular instruction {self}"))
+    }
+}
+
+impl Display for Inst {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            Inst::Full(i) => i.fmt(f),
+            Inst::Compressed(c) => c.fmt(f),
+        }
+    }
+}
+
+impl From<Instruction> for Inst {
+    fn from(instruction: Instruction) -> Self {
+        Inst::Full(instruction)
+    }
+}
+
+impl From<CInstruction> for Inst {
+    fn from(instruction: CInstruction) -> Self {
+        Inst::Compressed(instruction)
+    }
+}
+
+/// A 32-bit or compressed instruction together with its encoding, as decoded by `decode_one`.
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum DecodedInst {
+    Full(DecodedInstruction),
+    Compressed(DecodedCInstruction),
+}
+
+impl DecodedInst {
+    /// The length of the encoding in bytes (2 or 4).
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        match self {
+            DecodedInst::Full(_) => 4,
+            DecodedInst::Compressed(_) => 2,
+        }
+    }
+
+    /// The encoding, only the low 16 bits are used for compressed instructions.
+    pub fn raw(&self) -> u32 {
+        match self {
+            DecodedInst::Full(i) => i.raw,
+            DecodedInst::Compressed(c) => c.raw as u32,
+        }
+    }
+
+    /// The decoded instruction without its encoding.
+    pub fn inst(&self) -> Inst {
+        match self {
+            DecodedInst::Full(i) => Inst::Full(i.instruction),
+            DecodedInst::Compressed(c) => Inst::Compressed(c.instruction),
+        }
+    }
+}
+
+impl Display for DecodedInst {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self {
+            DecodedInst::Full(i) => i.fmt(f),
+            DecodedInst::Compressed(c) => c.fmt(f),
         }
     }
 }
 
+/// Returns the length in bytes of the instruction starting with the given byte, which is the lowest byte of the
+/// encoding.
+///
+/// Returns `None` for encodings of 80 bits or longer, whose length is given by the second byte.
+pub fn instruction_length(first_byte: u8) -> Option<usize> {
+    if first_byte & 0b11 != 0b11 {
+        Some(2)
+    } else if first_byte & 0b1_1100 != 0b1_1100 {
+        Some(4)
+    } else if first_byte & 0b10_0000 == 0 {
+        Some(6)
+    } else if first_byte & 0b100_0000 == 0 {
+        Some(8)
+    } else {
+        None
+    }
+}
+
+/// Decodes the instruction at the start of `bytes` with the default configuration, see `decode_one_with`.
+pub fn decode_one(bytes: &[u8]) -> Result<(DecodedInst, usize), DecodeError> {
+    decode_one_with(bytes, &DecoderConfig::default())
+}
+
+/// Decodes the instruction at the start of a little endian byte slice, returning it with the number of bytes it
+/// takes (2 or 4).
+///
+/// Instructions longer than 32 bits are not supported, and it is an error if the slice ends before the instruction.
+pub fn decode_one_with(
+    bytes: &[u8],
+    config: &DecoderConfig,
+) -> Result<(DecodedInst, usize), DecodeError> {
+    let Some(&first) = bytes.first() else {
+        return Err(DecodeError::bytes(
+            bytes,
+            0,
+            "no bytes to decode".to_owned(),
+        ));
+    };
+    let unsupported =
+        |length: usize, message: String| DecodeError::bytes(bytes, length, message).at(0);
+    let truncated = |length: usize| {
+        unsupported(
+            length,
+            format!("the instruction needs {length} bytes, got {}", bytes.len()),
+        )
+    };
+    let decoded = match instruction_length(first) {
+        Some(2) => {
+            let half = bytes.get(..2).ok_or_else(|| truncated(2))?;
+            DecodedInst::Compressed(
+                DecodedCInstruction::decode_with(u16::from_le_bytes([half[0], half[1]]), config)
+                    .map_err(|error| error.at(0))?,
+            )
+        }
+        Some(4) => {
+            let word = bytes.get(..4).ok_or_else(|| truncated(4))?;
+            DecodedInst::Full(
+                DecodedInstruction::decode_with(
+                    u32::from_le_bytes([word[0], word[1], word[2], word[3]]),
+                    config,
+                )
+                .map_err(|error| error.at(0))?,
+            )
+        }
+        Some(length) => {
+            let message = format!("{}-bit instructions are not supported", length * 8);
+            return Err(unsupported(length, message));
+        }
+        None => {
+            let message = "instructions of 80 bits or more are not supported".to_owned();
+            return Err(unsupported(10, message));
+        }
+    };
+    let length = decoded.len();
+    Ok((decoded, length))
+}
+
+/// Decodes every instruction of a little endian byte slice with the default configuration, see `decode_all_with`.
+pub fn decode_all(
+    bytes: &[u8],
+) -> impl Iterator<Item = Result<(usize, DecodedInst), DecodeError>> + '_ {
+    decode_all_with(bytes, &DecoderConfig::default())
+}
+
+/// Decodes every instruction of a little endian byte slice, returning each with its offset in the slice.
+///
+/// After an encoding that can not be decoded, decoding continues at the next 16-bit boundary and the error records
+/// the offset of the encoding.
+pub fn decode_all_with<'a>(
+    bytes: &'a [u8],
+    config: &DecoderConfig,
+) -> impl Iterator<Item = Result<(usize, DecodedInst), DecodeError>> + use<'a> {
+    let config = *config;
+    let mut offset = 0;
+    std::iter::from_fn(move || {
+        let start = offset;
+        if start >= bytes.len() {
+            return None;
+        }
+        Some(match decode_one_with(&bytes[start..], &config) {
+            Ok((instruction, length)) => {
+                offset += length;
+                Ok((start, instruction))
+            }
+            Err(error) => {
+                offset += 2;
+                Err(error.at(start))
+            }
+        })
+    })
+}
+
 /// Disassembles an instruction.
 pub fn disassemble_instruction(instruction: &Instruction) -> String {
     format!("{}", instruction)
 }
+
+/// Disassembles an instruction using the given formatting options.
+pub fn disassemble_instruction_with(instruction: &Instruction, options: &FormatOptions) -> String {
+    format!("{}", instruction.display_with(options))
+}
@@ -0,0 +1,1287 @@
+//! A simple reference interpreter for RV64IMAF.
+//!
+//! The interpreter executes decoded instructions against a register file and a flat byte-addressed memory.
+//! It is intended as a correctness oracle for the encoder and decoder (and for emulator authors), not as a fast
+//! or complete emulator: there are no privilege levels, interrupts or virtual memory, only the floating point CSRs
+//! are implemented, floating point arithmetic always rounds to nearest-even and the accrued exception flags are not
+//! tracked.
+
+use crate::cinstruction::CInstruction;
+use crate::instruction::{Instruction, RoundingMode};
+use crate::register::{FRegister, IRegister};
+
+/// The canonical NaN produced by floating point operations.
+const CANONICAL_NAN: u32 = 0x7fc0_0000;
+/// The size in bytes of the blocks that cbo.zero zeroes.
+const CACHE_BLOCK_SIZE: u64 = 64;
+
+/// A flat little endian memory covering the addresses `base..base + bytes.len()`.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Memory {
+    pub base: u64,
+    pub bytes: Vec<u8>,
+}
+
+impl Memory {
+    /// Creates a zeroed memory of `size` bytes starting at `base`.
+    pub fn new(base: u64, size: usize) -> Self {
+        Memory {
+            base,
+            bytes: vec![0; size],
+        }
+    }
+
+    /// Returns the index of the first byte of an access, checking that the whole access is in bounds.
+    fn index(&self, address: u64, size: usize) -> Result<usize, String> {
+        match address.checked_sub(self.base) {
+            Some(offset) if offset.saturating_add(size as u64) <= self.bytes.len() as u64 => {
+                Ok(offset as usize)
+            }
+            _ => Err(format!(
+                "memory access of {size} bytes at {address:#x} is out of bounds"
+            )),
+        }
+    }
+
+    /// Loads a zero extended little endian value of `size` bytes (at most 8).
+    pub fn load(&self, address: u64, size: usize) -> Result<u64, String> {
+        let index = self.index(address, size)?;
+        Ok(self.bytes[index..index + size]
+            .iter()
+            .rev()
+            .fold(0, |value, byte| (value << 8) | *byte as u64))
+    }
+
+    /// Stores the low `size` bytes (at most 8) of a value in little endian order.
+    pub fn store(&mut self, address: u64, size: usize, value: u64) -> Result<(), String> {
+        let index = self.index(address, size)?;
+        for (i, byte) in self.bytes[index..index + size].iter_mut().enumerate() {
+            *byte = (value >> (i * 8)) as u8;
+        }
+        Ok(())
+    }
+
+    /// Copies a slice of bytes into memory.
+    pub fn write_bytes(&mut self, address: u64, bytes: &[u8]) -> Result<(), String> {
+        let index = self.index(address, bytes.len())?;
+        self.bytes[index..index + bytes.len()].copy_from_slice(bytes);
+        Ok(())
+    }
+}
+
+/// The result of executing an instruction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Event {
+    /// The instruction completed normally.
+    Continue,
+    /// An ecall was executed, the environment should handle it before continuing.
+    Ecall,
+    /// An ebreak was executed.
+    Ebreak,
+}
+
+/// A single hardware thread with its own registers and memory.
+#[derive(Debug, PartialEq, Clone)]
+pub struct Hart {
+    /// The integer registers, writes to x0 are ignored.
+    pub x: [u64; 32],
+    /// The single precision float registers.
+    pub f: [u32; 32],
+    pub pc: u64,
+    /// The floating point control and status register (frm and fflags).
+    pub fcsr: u32,
+    pub memory: Memory,
+    /// The address reserved by the last lr.w or lr.d.
+    reservation: Option<u64>,
+}
+
+impl Hart {
+    /// Creates a hart with zeroed registers that starts executing at the base of its memory.
+    pub fn new(memory: Memory) -> Self {
+        Hart {
+            x: [0; 32],
+            f: [0; 32],
+            pc: memory.base,
+            fcsr: 0,
+            memory,
+            reservation: None,
+        }
+    }
+
+    /// Reads an integer register.
+    pub fn read(&self, register: IRegister) -> u64 {
+        self.x[register.number() as usize]
+    }
+
+    /// Writes an integer register, writes to x0 are ignored.
+    pub fn write(&mut self, register: IRegister, value: u64) {
+        if register != IRegister::Zero {
+            self.x[register.number() as usize] = value;
+        }
+    }
+
+    /// Reads a float register.
+    pub fn read_f(&self, register: FRegister) -> f32 {
+        f32::from_bits(self.f[u32::from(register) as usize])
+    }
+
+    /// Writes a float register, replacing NaNs with the canonical NaN.
+    pub fn write_f(&mut self, register: FRegister, value: f32) {
+        self.f[u32::from(register) as usize] = if value.is_nan() {
+            CANONICAL_NAN
+        } else {
+            value.to_bits()
+        };
+    }
+
+    /// Fetches, decodes and executes the instruction at the program counter.
+    pub fn step(&mut self) -> Result<Event, String> {
+        let low = self.memory.load(self.pc, 2)? as u16;
+        if low & 0b11 == 0b11 {
+            let word = self.memory.load(self.pc, 4)? as u32;
+            let instruction = Instruction::decode(word)?;
+            self.execute(&instruction, 4)
+        } else {
+            let instruction = CInstruction::decode(low)?.try_expand()?;
+            self.execute(&instruction, 2)
+        }
+    }
+
+    /// Runs until an ecall or ebreak is executed, or `limit` instructions have been executed.
+    pub fn run(&mut self, limit: usize) -> Result<Event, String> {
+        for _ in 0..limit {
+            match self.step()? {
+                Event::Continue => {}
+                event => return Ok(event),
+            }
+        }
+        Err(format!("no ecall or ebreak within {limit} instructions"))
+    }
+
+    /// Executes an instruction of `length` bytes located at the program counter.
+    ///
+    /// Afterwards the program counter points to the next instruction, including after an ecall or ebreak.
+    pub fn execute(&mut self, instruction: &Instruction, length: u64) -> Result<Event, String> {
+        let pc = self.pc;
+        let mut next = pc.wrapping_add(length);
+        match *instruction {
+            Instruction::LUI { dest, imm } => self.write(dest, (imm.val() << 12) as u64),
+            Instruction::AUIPC { dest, imm } => {
+                self.write(dest, pc.wrapping_add((imm.val() << 12) as u64))
+            }
+            Instruction::JAL { dest, offset } => {
+                self.write(dest, next);
+                next = pc.wrapping_add(offset.val() as u64);
+            }
+            Instruction::JALR { dest, base, offset } => {
+                let target = self.read(base).wrapping_add(offset.val() as u64) & !1;
+                self.write(dest, next);
+                next = target;
+            }
+            Instruction::BEQ { src1, src2, offset } => {
+                if self.read(src1) == self.read(src2) {
+                    next = pc.wrapping_add(offset.val() as u64);
+                }
+            }
+            Instruction::BNE { src1, src2, offset } => {
+                if self.read(src1) != self.read(src2) {
+                    next = pc.wrapping_add(offset.val() as u64);
+                }
+            }
+            Instruction::BLT { src1, src2, offset } => {
+                if (self.read(src1) as i64) < self.read(src2) as i64 {
+                    next = pc.wrapping_add(offset.val() as u64);
+                }
+            }
+            Instruction::BGE { src1, src2, offset } => {
+                if self.read(src1) as i64 >= self.read(src2) as i64 {
+                    next = pc.wrapping_add(offset.val() as u64);
+                }
+            }
+            Instruction::BLTU { src1, src2, offset } => {
+                if self.read(src1) < self.read(src2) {
+                    next = pc.wrapping_add(offset.val() as u64);
+                }
+            }
+            Instruction::BGEU { src1, src2, offset } => {
+                if self.read(src1) >= self.read(src2) {
+                    next = pc.wrapping_add(offset.val() as u64);
+                }
+            }
+            Instruction::LB { dest, base, offset } => {
+                let value = self.load(base, offset.val(), 1)? as i8 as u64;
+                self.write(dest, value);
+            }
+            Instruction::LH { dest, base, offset } => {
+                let value = self.load(base, offset.val(), 2)? as i16 as u64;
+                self.write(dest, value);
+            }
+            Instruction::LW { dest, base, offset } => {
+                let value = self.load(base, offset.val(), 4)? as i32 as u64;
+                self.write(dest, value);
+            }
+            Instruction::LBU { dest, base, offset } => {
+                let value = self.load(base, offset.val(), 1)?;
+                self.write(dest, value);
+            }
+            Instruction::LHU { dest, base, offset } => {
+                let value = self.load(base, offset.val(), 2)?;
+                self.write(dest, value);
+            }
+            Instruction::LWU { dest, base, offset } => {
+                let value = self.load(base, offset.val(), 4)?;
+                self.write(dest, value);
+            }
+            Instruction::LD { dest, base, offset } => {
+                let value = self.load(base, offset.val(), 8)?;
+                self.write(dest, value);
+            }
+            Instruction::SB { src, base, offset } => self.store(base, offset.val(), 1, src)?,
+            Instruction::SH { src, base, offset } => self.store(base, offset.val(), 2, src)?,
+            Instruction::SW { src, base, offset } => self.store(base, offset.val(), 4, src)?,
+            Instruction::SD { src, base, offset } => self.store(base, offset.val(), 8, src)?,
+            Instruction::ADDI { dest, src, imm } => {
+                self.write(dest, self.read(src).wrapping_add(imm.val() as u64))
+            }
+            Instruction::SLTI { dest, src, imm } => {
+                self.write(dest, ((self.read(src) as i64) < imm.val()) as u64)
+            }
+            Instruction::SLTIU { dest, src, imm } => {
+                self.write(dest, (self.read(src) < imm.val() as u64) as u64)
+            }
+            Instruction::XORI { dest, src, imm } => {
+                self.write(dest, self.read(src) ^ imm.val() as u64)
+            }
+            Instruction::ORI { dest, src, imm } => {
+                self.write(dest, self.read(src) | imm.val() as u64)
+            }
+            Instruction::ANDI { dest, src, imm } => {
+                self.write(dest, self.read(src) & imm.val() as u64)
+            }
+            Instruction::SLLI { dest, src, shamt } => {
+                self.write(dest, self.read(src) << shamt.val())
+            }
+            Instruction::SRLI { dest, src, shamt } => {
+                self.write(dest, self.read(src) >> shamt.val())
+            }
+            Instruction::SRAI { dest, src, shamt } => {
+                self.write(dest, ((self.read(src) as i64) >> shamt.val()) as u64)
+            }
+            Instruction::ADD { dest, src1, src2 } => {
+                self.write(dest, self.read(src1).wrapping_add(self.read(src2)))
+            }
+            Instruction::SUB { dest, src1, src2 } => {
+                self.write(dest, self.read(src1).wrapping_sub(self.read(src2)))
+            }
+            Instruction::SLL { dest, src1, src2 } => {
+                self.write(dest, self.read(src1) << (self.read(src2) & 0b11_1111))
+            }
+            Instruction::SLT { dest, src1, src2 } => self.write(
+                dest,
+                ((self.read(src1) as i64) < self.read(src2) as i64) as u64,
+            ),
+            Instruction::SLTU { dest, src1, src2 } => {
+                self.write(dest, (self.read(src1) < self.read(src2)) as u64)
+            }
+            Instruction::XOR { dest, src1, src2 } => {
+                self.write(dest, self.read(src1) ^ self.read(src2))
+            }
+            Instruction::SRL { dest, src1, src2 } => {
+                self.write(dest, self.read(src1) >> (self.read(src2) & 0b11_1111))
+            }
+            Instruction::SRA { dest, src1, src2 } => self.write(
+                dest,
+                ((self.read(src1) as i64) >> (self.read(src2) & 0b11_1111)) as u64,
+            ),
+            Instruction::OR { dest, src1, src2 } => {
+                self.write(dest, self.read(src1) | self.read(src2))
+            }
+            Instruction::AND { dest, src1, src2 } => {
+                self.write(dest, self.read(src1) & self.read(src2))
+            }
+            // memory is always coherent in this interpreter
+            Instruction::FENCE { .. } | Instruction::FENCEI => {}
+            // there are no caches to manage or prefetch into
+            Instruction::CBOCLEAN { .. }
+            | Instruction::CBOFLUSH { .. }
+            | Instruction::CBOINVAL { .. }
+            | Instruction::PREFETCHI { .. }
+            | Instruction::PREFETCHR { .. }
+            | Instruction::PREFETCHW { .. } => {}
+            // hints that only affect performance
+            Instruction::PAUSE
+            | Instruction::NTLP1
+            | Instruction::NTLPALL
+            | Instruction::NTLS1
+            | Instruction::NTLALL => {}
+            // landing pads are not enforced, so lpad does nothing
+            Instruction::LPAD { .. } => {}
+            Instruction::CBOZERO { base } => {
+                let block = self.read(base) & !(CACHE_BLOCK_SIZE - 1);
+                for address in (block..block + CACHE_BLOCK_SIZE).step_by(8) {
+                    self.memory.store(address, 8, 0)?;
+                }
+            }
+            Instruction::PACK { dest, src1, src2 } => {
+                self.write(dest, self.read(src1) as u32 as u64 | self.read(src2) << 32)
+            }
+            Instruction::PACKH { dest, src1, src2 } => self.write(
+                dest,
+                (self.read(src1) & 0xff) | (self.read(src2) & 0xff) << 8,
+            ),
+            Instruction::PACKW { dest, src1, src2 } => self.write_w(
+                dest,
+                (self.read(src1) as u16 as u32 | (self.read(src2) as u16 as u32) << 16) as i32,
+            ),
+            Instruction::BREV8 { dest, src } => self.write(
+                dest,
+                u64::from_le_bytes(self.read(src).to_le_bytes().map(u8::reverse_bits)),
+            ),
+            Instruction::CLMUL { dest, src1, src2 } => {
+                self.write(dest, clmul(self.read(src1), self.read(src2)) as u64)
+            }
+            Instruction::CLMULH { dest, src1, src2 } => {
+                self.write(dest, (clmul(self.read(src1), self.read(src2)) >> 64) as u64)
+            }
+            Instruction::XPERM4 { dest, src1, src2 } => {
+                self.write(dest, xperm(self.read(src1), self.read(src2), 4))
+            }
+            Instruction::XPERM8 { dest, src1, src2 } => {
+                self.write(dest, xperm(self.read(src1), self.read(src2), 8))
+            }
+            Instruction::ANDN { dest, src1, src2 } => {
+                self.write(dest, self.read(src1) & !self.read(src2))
+            }
+            Instruction::ORN { dest, src1, src2 } => {
+                self.write(dest, self.read(src1) | !self.read(src2))
+            }
+            Instruction::XNOR { dest, src1, src2 } => {
+                self.write(dest, !(self.read(src1) ^ self.read(src2)))
+            }
+            Instruction::ROL { dest, src1, src2 } => self.write(
+                dest,
+                self.read(src1)
+                    .rotate_left(self.read(src2) as u32 & 0b11_1111),
+            ),
+            Instruction::ROR { dest, src1, src2 } => self.write(
+                dest,
+                self.read(src1)
+                    .rotate_right(self.read(src2) as u32 & 0b11_1111),
+            ),
+            Instruction::RORI { dest, src, shamt } => {
+                self.write(dest, self.read(src).rotate_right(shamt.val() as u32))
+            }
+            Instruction::ROLW { dest, src1, src2 } => self.write_w(
+                dest,
+                (self.read(src1) as u32).rotate_left(self.read(src2) as u32 & 0b1_1111) as i32,
+            ),
+            Instruction::RORW { dest, src1, src2 } => self.write_w(
+                dest,
+                (self.read(src1) as u32).rotate_right(self.read(src2) as u32 & 0b1_1111) as i32,
+            ),
+            Instruction::RORIW { dest, src, shamt } => self.write_w(
+                dest,
+                (self.read(src) as u32).rotate_right(shamt.val() as u32) as i32,
+            ),
+            Instruction::REV8 { dest, src } => self.write(dest, self.read(src).swap_bytes()),
+            Instruction::SHA256SIG0 { dest, src } => {
+                let x = self.read(src) as u32;
+                self.write_w(
+                    dest,
+                    (x.rotate_right(7) ^ x.rotate_right(18) ^ x >> 3) as i32,
+                )
+            }
+            Instruction::SHA256SIG1 { dest, src } => {
+                let x = self.read(src) as u32;
+                self.write_w(
+                    dest,
+                    (x.rotate_right(17) ^ x.rotate_right(19) ^ x >> 10) as i32,
+                )
+            }
+            Instruction::SHA256SUM0 { dest, src } => {
+                let x = self.read(src) as u32;
+                self.write_w(
+                    dest,
+                    (x.rotate_right(2) ^ x.rotate_right(13) ^ x.rotate_right(22)) as i32,
+                )
+            }
+            Instruction::SHA256SUM1 { dest, src } => {
+                let x = self.read(src) as u32;
+                self.write_w(
+                    dest,
+                    (x.rotate_right(6) ^ x.rotate_right(11) ^ x.rotate_right(25)) as i32,
+                )
+            }
+            Instruction::SHA512SIG0 { dest, src } => {
+                let x = self.read(src);
+                self.write(dest, x.rotate_right(1) ^ x.rotate_right(8) ^ x >> 7)
+            }
+            Instruction::SHA512SIG1 { dest, src } => {
+                let x = self.read(src);
+                self.write(dest, x.rotate_right(19) ^ x.rotate_right(61) ^ x >> 6)
+            }
+            Instruction::SHA512SUM0 { dest, src } => {
+                let x = self.read(src);
+                self.write(
+                    dest,
+                    x.rotate_right(28) ^ x.rotate_right(34) ^ x.rotate_right(39),
+                )
+            }
+            Instruction::SHA512SUM1 { dest, src } => {
+                let x = self.read(src);
+                self.write(
+                    dest,
+                    x.rotate_right(14) ^ x.rotate_right(18) ^ x.rotate_right(41),
+                )
+            }
+            // only exist on RV32, or need the AES tables
+            Instruction::ZIP { .. }
+            | Instruction::UNZIP { .. }
+            | Instruction::REV8RV32 { .. }
+            | Instruction::AES32ESI { .. }
+            | Instruction::AES32ESMI { .. }
+            | Instruction::AES32DSI { .. }
+            | Instruction::AES32DSMI { .. }
+            | Instruction::SHA512SUM0R { .. }
+            | Instruction::SHA512SUM1R { .. }
+            | Instruction::SHA512SIG0L { .. }
+            | Instruction::SHA512SIG0H { .. }
+            | Instruction::SHA512SIG1L { .. }
+            | Instruction::SHA512SIG1H { .. }
+            | Instruction::AES64ES { .. }
+            | Instruction::AES64ESM { .. }
+            | Instruction::AES64DS { .. }
+            | Instruction::AES64DSM { .. }
+            | Instruction::AES64IM { .. }
+            | Instruction::AES64KS1I { .. }
+            | Instruction::AES64KS2 { .. } => {
+                return Err(format!("unsupported instruction {instruction}"));
+            }
+            Instruction::ECALL => {
+                self.pc = next;
+                return Ok(Event::Ecall);
+            }
+            Instruction::EBREAK => {
+                self.pc = next;
+                return Ok(Event::Ebreak);
+            }
+            // there are no interrupts to wait for or address translation to synchronize
+            Instruction::WFI | Instruction::SFENCEVMA { .. } => {}
+            Instruction::SRET | Instruction::MRET | Instruction::MNRET | Instruction::DRET => {
+                return Err(format!(
+                    "unsupported instruction {instruction}, there are no privilege levels"
+                ));
+            }
+            Instruction::ADDIW { dest, src, imm } => {
+                self.write_w(dest, (self.read(src) as i32).wrapping_add(imm.val() as i32))
+            }
+            Instruction::SLLIW { dest, src, shamt } => {
+                self.write_w(dest, (self.read(src) as i32) << shamt.val())
+            }
+            Instruction::SRLIW { dest, src, shamt } => {
+                self.write_w(dest, ((self.read(src) as u32) >> shamt.val()) as i32)
+            }
+            Instruction::SRAIW { dest, src, shamt } => {
+                self.write_w(dest, (self.read(src) as i32) >> shamt.val())
+            }
+            Instruction::ADDW { dest, src1, src2 } => self.write_w(
+                dest,
+                (self.read(src1) as i32).wrapping_add(self.read(src2) as i32),
+            ),
+            Instruction::SUBW { dest, src1, src2 } => self.write_w(
+                dest,
+                (self.read(src1) as i32).wrapping_sub(self.read(src2) as i32),
+            ),
+            Instruction::SLLW { dest, src1, src2 } => self.write_w(
+                dest,
+                (self.read(src1) as i32) << (self.read(src2) & 0b1_1111),
+            ),
+            Instruction::SRLW { dest, src1, src2 } => self.write_w(
+                dest,
+                ((self.read(src1) as u32) >> (self.read(src2) & 0b1_1111)) as i32,
+            ),
+            Instruction::SRAW { dest, src1, src2 } => self.write_w(
+                dest,
+                (self.read(src1) as i32) >> (self.read(src2) & 0b1_1111),
+            ),
+            Instruction::MUL { dest, src1, src2 } => {
+                self.write(dest, self.read(src1).wrapping_mul(self.read(src2)))
+            }
+            Instruction::MULH { dest, src1, src2 } => {
+                let product = self.read(src1) as i64 as i128 * self.read(src2) as i64 as i128;
+                self.write(dest, (product >> 64) as u64);
+            }
+            Instruction::MULHSU { dest, src1, src2 } => {
+                let product = self.read(src1) as i64 as i128 * self.read(src2) as i128;
+                self.write(dest, (product >> 64) as u64);
+            }
+            Instruction::MULHU { dest, src1, src2 } => {
+                let product = self.read(src1) as u128 * self.read(src2) as u128;
+                self.write(dest, (product >> 64) as u64);
+            }
+            Instruction::DIV { dest, src1, src2 } => {
+                let (a, b) = (self.read(src1) as i64, self.read(src2) as i64);
+                self.write(dest, if b == 0 { -1 } else { a.wrapping_div(b) } as u64);
+            }
+            Instruction::DIVU { dest, src1, src2 } => {
+                let (a, b) = (self.read(src1), self.read(src2));
+                self.write(dest, a.checked_div(b).unwrap_or(u64::MAX));
+            }
+            Instruction::REM { dest, src1, src2 } => {
+                let (a, b) = (self.read(src1) as i64, self.read(src2) as i64);
+                self.write(dest, if b == 0 { a } else { a.wrapping_rem(b) } as u64);
+            }
+            Instruction::REMU { dest, src1, src2 } => {
+                let (a, b) = (self.read(src1), self.read(src2));
+                self.write(dest, a.checked_rem(b).unwrap_or(a));
+            }
+            Instruction::MULW { dest, src1, src2 } => self.write_w(
+                dest,
+                (self.read(src1) as i32).wrapping_mul(self.read(src2) as i32),
+            ),
+            Instruction::DIVW { dest, src1, src2 } => {
+                let (a, b) = (self.read(src1) as i32, self.read(src2) as i32);
+                self.write_w(dest, if b == 0 { -1 } else { a.wrapping_div(b) });
+            }
+            Instruction::DIVUW { dest, src1, src2 } => {
+                let (a, b) = (self.read(src1) as u32, self.read(src2) as u32);
+                self.write_w(dest, a.checked_div(b).unwrap_or(u32::MAX) as i32);
+            }
+            Instruction::REMW { dest, src1, src2 } => {
+                let (a, b) = (self.read(src1) as i32, self.read(src2) as i32);
+                self.write_w(dest, if b == 0 { a } else { a.wrapping_rem(b) });
+            }
+            Instruction::REMUW { dest, src1, src2 } => {
+                let (a, b) = (self.read(src1) as u32, self.read(src2) as u32);
+                self.write_w(dest, a.checked_rem(b).unwrap_or(a) as i32);
+            }
+            #[cfg(feature = "zalasr")]
+            Instruction::LBAQ { dest, addr, .. } => {
+                let value = self.load(addr, 0, 1)? as i8 as u64;
+                self.write(dest, value);
+            }
+            #[cfg(feature = "zalasr")]
+            Instruction::LHAQ { dest, addr, .. } => {
+                let value = self.load(addr, 0, 2)? as i16 as u64;
+                self.write(dest, value);
+            }
+            #[cfg(feature = "zalasr")]
+            Instruction::LWAQ { dest, addr, .. } => {
+                let value = self.load(addr, 0, 4)? as i32 as u64;
+                self.write(dest, value);
+            }
+            #[cfg(feature = "zalasr")]
+            Instruction::LDAQ { dest, addr, .. } => {
+                let value = self.load(addr, 0, 8)?;
+                self.write(dest, value);
+            }
+            #[cfg(feature = "zalasr")]
+            Instruction::SBRL { src, addr, .. } => self.store(addr, 0, 1, src)?,
+            #[cfg(feature = "zalasr")]
+            Instruction::SHRL { src, addr, .. } => self.store(addr, 0, 2, src)?,
+            #[cfg(feature = "zalasr")]
+            Instruction::SWRL { src, addr, .. } => self.store(addr, 0, 4, src)?,
+            #[cfg(feature = "zalasr")]
+            Instruction::SDRL { src, addr, .. } => self.store(addr, 0, 8, src)?,
+            // the interpreter has 64-bit registers
+            #[cfg(feature = "rv128")]
+            Instruction::LQ { .. }
+            | Instruction::LDU { .. }
+            | Instruction::SQ { .. }
+            | Instruction::ADDID { .. }
+            | Instruction::SLLID { .. }
+            | Instruction::SRLID { .. }
+            | Instruction::SRAID { .. }
+            | Instruction::ADDD { .. }
+            | Instruction::SUBD { .. }
+            | Instruction::SLLD { .. }
+            | Instruction::SRLD { .. }
+            | Instruction::SRAD { .. } => {
+                return Err(format!("unsupported instruction {instruction}"));
+            }
+            // there is no shadow stack
+            Instruction::SSPUSH { .. }
+            | Instruction::SSPOPCHK { .. }
+            | Instruction::SSRDP { .. }
+            | Instruction::SSAMOSWAPW { .. }
+            | Instruction::SSAMOSWAPD { .. } => {
+                return Err(format!("unsupported instruction {instruction}"));
+            }
+            Instruction::Vector(i) => return Err(format!("unsupported instruction {i}")),
+            #[cfg(feature = "p")]
+            Instruction::P(i) => return Err(format!("unsupported instruction {i}")),
+            #[cfg(feature = "xandesperf")]
+            Instruction::XAndesPerf(i) => return Err(format!("unsupported instruction {i}")),
+            #[cfg(feature = "xthead")]
+            Instruction::XThead(i) => return Err(format!("unsupported instruction {i}")),
+            Instruction::Custom(i) => return Err(format!("unsupported instruction {i}")),
+            Instruction::LRW { dest, addr, .. } => {
+                let address = self.atomic_address(addr, 4)?;
+                let value = self.memory.load(address, 4)? as i32 as u64;
+                self.reservation = Some(address);
+                self.write(dest, value);
+            }
+            Instruction::LRD { dest, addr, .. } => {
+                let address = self.atomic_address(addr, 8)?;
+                let value = self.memory.load(address, 8)?;
+                self.reservation = Some(address);
+                self.write(dest, value);
+            }
+            Instruction::SCW {
+                dest, addr, src, ..
+            } => self.store_conditional(dest, addr, src, 4)?,
+            Instruction::SCD {
+                dest, addr, src, ..
+            } => self.store_conditional(dest, addr, src, 8)?,
+            Instruction::AMOSWAPW {
+                dest, addr, src, ..
+            } => self.amo_w(dest, addr, src, |_, b| b)?,
+            Instruction::AMOADDW {
+                dest, addr, src, ..
+            } => self.amo_w(dest, addr, src, |a, b| a.wrapping_add(b))?,
+            Instruction::AMOXORW {
+                dest, addr, src, ..
+            } => self.amo_w(dest, addr, src, |a, b| a ^ b)?,
+            Instruction::AMOANDW {
+                dest, addr, src, ..
+            } => self.amo_w(dest, addr, src, |a, b| a & b)?,
+            Instruction::AMOORW {
+                dest, addr, src, ..
+            } => self.amo_w(dest, addr, src, |a, b| a | b)?,
+            Instruction::AMOMINW {
+                dest, addr, src, ..
+            } => self.amo_w(dest, addr, src, i32::min)?,
+            Instruction::AMOMAXW {
+                dest, addr, src, ..
+            } => self.amo_w(dest, addr, src, i32::max)?,
+            Instruction::AMOMINUW {
+                dest, addr, src, ..
+            } => self.amo_w(dest, addr, src, |a, b| (a as u32).min(b as u32) as i32)?,
+            Instruction::AMOMAXUW {
+                dest, addr, src, ..
+            } => self.amo_w(dest, addr, src, |a, b| (a as u32).max(b as u32) as i32)?,
+            Instruction::AMOSWAPD {
+                dest, addr, src, ..
+            } => self.amo_d(dest, addr, src, |_, b| b)?,
+            Instruction::AMOADDD {
+                dest, addr, src, ..
+            } => self.amo_d(dest, addr, src, |a, b| a.wrapping_add(b))?,
+            Instruction::AMOXORD {
+                dest, addr, src, ..
+            } => self.amo_d(dest, addr, src, |a, b| a ^ b)?,
+            Instruction::AMOANDD {
+                dest, addr, src, ..
+            } => self.amo_d(dest, addr, src, |a, b| a & b)?,
+            Instruction::AMOORD {
+                dest, addr, src, ..
+            } => self.amo_d(dest, addr, src, |a, b| a | b)?,
+            Instruction::AMOMIND {
+                dest, addr, src, ..
+            } => self.amo_d(dest, addr, src, |a, b| (a as i64).min(b as i64) as u64)?,
+            Instruction::AMOMAXD {
+                dest, addr, src, ..
+            } => self.amo_d(dest, addr, src, |a, b| (a as i64).max(b as i64) as u64)?,
+            Instruction::AMOMINUD {
+                dest, addr, src, ..
+            } => self.amo_d(dest, addr, src, u64::min)?,
+            Instruction::AMOMAXUD {
+                dest, addr, src, ..
+            } => self.amo_d(dest, addr, src, u64::max)?,
+            Instruction::AMOSWAPB {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 1, |_, b| b)?,
+            Instruction::AMOADDB {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 1, |a, b| a.wrapping_add(b))?,
+            Instruction::AMOXORB {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 1, |a, b| a ^ b)?,
+            Instruction::AMOANDB {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 1, |a, b| a & b)?,
+            Instruction::AMOORB {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 1, |a, b| a | b)?,
+            Instruction::AMOMINB {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 1, |a, b| (a as i64).min(b as i64) as u64)?,
+            Instruction::AMOMAXB {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 1, |a, b| (a as i64).max(b as i64) as u64)?,
+            Instruction::AMOMINUB {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 1, u64::min)?,
+            Instruction::AMOMAXUB {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 1, u64::max)?,
+            Instruction::AMOSWAPH {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 2, |_, b| b)?,
+            Instruction::AMOADDH {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 2, |a, b| a.wrapping_add(b))?,
+            Instruction::AMOXORH {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 2, |a, b| a ^ b)?,
+            Instruction::AMOANDH {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 2, |a, b| a & b)?,
+            Instruction::AMOORH {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 2, |a, b| a | b)?,
+            Instruction::AMOMINH {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 2, |a, b| (a as i64).min(b as i64) as u64)?,
+            Instruction::AMOMAXH {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 2, |a, b| (a as i64).max(b as i64) as u64)?,
+            Instruction::AMOMINUH {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 2, u64::min)?,
+            Instruction::AMOMAXUH {
+                dest, addr, src, ..
+            } => self.amo_narrow(dest, addr, src, 2, u64::max)?,
+            Instruction::AMOCASB {
+                dest, addr, src, ..
+            } => self.amo_cas(dest, addr, src, 1)?,
+            Instruction::AMOCASH {
+                dest, addr, src, ..
+            } => self.amo_cas(dest, addr, src, 2)?,
+            Instruction::FLW { dest, base, offset } => {
+                let value = self.load(base, offset.val(), 4)? as u32;
+                // loads do not canonicalize NaNs
+                self.f[u32::from(dest) as usize] = value;
+            }
+            Instruction::FSW { base, src, offset } => {
+                let address = self.read(base).wrapping_add(offset.val() as u64);
+                self.memory
+                    .store(address, 4, self.f[u32::from(src) as usize] as u64)?;
+            }
+            Instruction::FMADDS {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => {
+                self.rounding_mode(rm)?;
+                let (a, b, c) = (self.read_f(src1), self.read_f(src2), self.read_f(src3));
+                self.write_f(dest, a.mul_add(b, c));
+            }
+            Instruction::FMSUBS {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => {
+                self.rounding_mode(rm)?;
+                let (a, b, c) = (self.read_f(src1), self.read_f(src2), self.read_f(src3));
+                self.write_f(dest, a.mul_add(b, -c));
+            }
+            Instruction::FNMSUBS {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => {
+                self.rounding_mode(rm)?;
+                let (a, b, c) = (self.read_f(src1), self.read_f(src2), self.read_f(src3));
+                self.write_f(dest, (-a).mul_add(b, c));
+            }
+            Instruction::FNMADDS {
+                dest,
+                src1,
+                src2,
+                src3,
+                rm,
+            } => {
+                self.rounding_mode(rm)?;
+                let (a, b, c) = (self.read_f(src1), self.read_f(src2), self.read_f(src3));
+                self.write_f(dest, (-a).mul_add(b, -c));
+            }
+            Instruction::FADDS {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => {
+                self.rounding_mode(rm)?;
+                self.write_f(dest, self.read_f(src1) + self.read_f(src2));
+            }
+            Instruction::FSUBS {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => {
+                self.rounding_mode(rm)?;
+                self.write_f(dest, self.read_f(src1) - self.read_f(src2));
+            }
+            Instruction::FMULS {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => {
+                self.rounding_mode(rm)?;
+                self.write_f(dest, self.read_f(src1) * self.read_f(src2));
+            }
+            Instruction::FDIVS {
+                dest,
+                src1,
+                src2,
+                rm,
+            } => {
+                self.rounding_mode(rm)?;
+                self.write_f(dest, self.read_f(src1) / self.read_f(src2));
+            }
+            Instruction::FSQRTS { dest, src, rm } => {
+                self.rounding_mode(rm)?;
+                self.write_f(dest, self.read_f(src).sqrt());
+            }
+            Instruction::FSGNJS { dest, src1, src2 } => {
+                self.sign_inject(dest, src1, src2, |_, b| b)
+            }
+            Instruction::FSGNJNS { dest, src1, src2 } => {
+                self.sign_inject(dest, src1, src2, |_, b| !b)
+            }
+            Instruction::FSGNJXS { dest, src1, src2 } => {
+                self.sign_inject(dest, src1, src2, |a, b| a ^ b)
+            }
+            Instruction::FMINS { dest, src1, src2 } => {
+                let (a, b) = (self.read_f(src1), self.read_f(src2));
+                // -0.0 is considered smaller than 0.0
+                let min = match a == b {
+                    true if a.is_sign_negative() => a,
+                    true => b,
+                    false => a.min(b),
+                };
+                self.write_f(dest, min);
+            }
+            Instruction::FMAXS { dest, src1, src2 } => {
+                let (a, b) = (self.read_f(src1), self.read_f(src2));
+                let max = match a == b {
+                    true if a.is_sign_positive() => a,
+                    true => b,
+                    false => a.max(b),
+                };
+                self.write_f(dest, max);
+            }
+            Instruction::FCVTWS { dest, src, rm } => {
+                let value = self.round(self.read_f(src), rm)?;
+                let int = if value.is_nan() {
+                    i32::MAX
+                } else {
+                    value as i32
+                };
+                self.write_w(dest, int);
+            }
+            Instruction::FCVTWUS { dest, src, rm } => {
+                let value = self.round(self.read_f(src), rm)?;
+                let int = if value.is_nan() {
+                    u32::MAX
+                } else {
+                    value as u32
+                };
+                self.write_w(dest, int as i32);
+            }
+            Instruction::FCVTLS { dest, src, rm } => {
+                let value = self.round(self.read_f(src), rm)?;
+                let int = if value.is_nan() {
+                    i64::MAX
+                } else {
+                    value as i64
+                };
+                self.write(dest, int as u64);
+            }
+            Instruction::FCVTLUS { dest, src, rm } => {
+                let value = self.round(self.read_f(src), rm)?;
+                let int = if value.is_nan() {
+                    u64::MAX
+                } else {
+                    value as u64
+                };
+                self.write(dest, int);
+            }
+            Instruction::FMVXW { dest, src } => {
+                self.write_w(dest, self.f[u32::from(src) as usize] as i32)
+            }
+            Instruction::FEQS { dest, src1, src2 } => {
+                self.write(dest, (self.read_f(src1) == self.read_f(src2)) as u64)
+            }
+            Instruction::FLTS { dest, src1, src2 } => {
+                self.write(dest, (self.read_f(src1) < self.read_f(src2)) as u64)
+            }
+            Instruction::FLES { dest, src1, src2 } => {
+                self.write(dest, (self.read_f(src1) <= self.read_f(src2)) as u64)
+            }
+            Instruction::FCLASSS { dest, src } => {
+                self.write(dest, classify(self.f[u32::from(src) as usize]))
+            }
+            Instruction::FCVTSW { dest, src, rm } => {
+                self.rounding_mode(rm)?;
+                self.write_f(dest, self.read(src) as i32 as f32);
+            }
+            Instruction::FCVTSWU { dest, src, rm } => {
+                self.rounding_mode(rm)?;
+                self.write_f(dest, self.read(src) as u32 as f32);
+            }
+            Instruction::FCVTSL { dest, src, rm } => {
+                self.rounding_mode(rm)?;
+                self.write_f(dest, self.read(src) as i64 as f32);
+            }
+            Instruction::FCVTSLU { dest, src, rm } => {
+                self.rounding_mode(rm)?;
+                self.write_f(dest, self.read(src) as f32);
+            }
+            Instruction::FMVWX { dest, src } => {
+                self.f[u32::from(dest) as usize] = self.read(src) as u32
+            }
+            // half precision values are NaN-boxed in the single precision registers
+            Instruction::FLH { dest, base, offset } => {
+                let value = self.load(base, offset.val(), 2)? as u32;
+                self.f[u32::from(dest) as usize] = 0xffff_0000 | value;
+            }
+            Instruction::FSH { base, src, offset } => {
+                let address = self.read(base).wrapping_add(offset.val() as u64);
+                self.memory
+                    .store(address, 2, self.f[u32::from(src) as usize] as u64)?;
+            }
+            Instruction::FMVXH { dest, src } => {
+                self.write(dest, self.f[u32::from(src) as usize] as i16 as u64)
+            }
+            Instruction::FMVHX { dest, src } => {
+                self.f[u32::from(dest) as usize] = 0xffff_0000 | self.read(src) as u16 as u32
+            }
+            Instruction::FCVTBF16S { dest, src, rm } => {
+                let bf16 = round_bf16(self.read_f(src), self.rounding_mode(rm)?);
+                self.f[u32::from(dest) as usize] = 0xffff_0000 | bf16 as u32;
+            }
+            Instruction::FCVTSBF16 { dest, src, rm } => {
+                self.rounding_mode(rm)?;
+                let bits = self.f[u32::from(src) as usize];
+                // a value that is not properly NaN-boxed is read as the canonical NaN
+                let value = if bits >> 16 == 0xffff {
+                    f32::from_bits(bits << 16)
+                } else {
+                    f32::NAN
+                };
+                self.write_f(dest, value);
+            }
+            // the floating point registers are only single precision
+            Instruction::FLQ { .. }
+            | Instruction::FSQ { .. }
+            | Instruction::FMADDQ { .. }
+            | Instruction::FMSUBQ { .. }
+            | Instruction::FNMSUBQ { .. }
+            | Instruction::FNMADDQ { .. }
+            | Instruction::FADDQ { .. }
+            | Instruction::FSUBQ { .. }
+            | Instruction::FMULQ { .. }
+            | Instruction::FDIVQ { .. }
+            | Instruction::FSQRTQ { .. }
+            | Instruction::FSGNJQ { .. }
+            | Instruction::FSGNJNQ { .. }
+            | Instruction::FSGNJXQ { .. }
+            | Instruction::FMINQ { .. }
+            | Instruction::FMAXQ { .. }
+            | Instruction::FCVTSQ { .. }
+            | Instruction::FCVTQS { .. }
+            | Instruction::FCVTDQ { .. }
+            | Instruction::FCVTQD { .. }
+            | Instruction::FEQQ { .. }
+            | Instruction::FLTQ { .. }
+            | Instruction::FLEQ { .. }
+            | Instruction::FCLASSQ { .. }
+            | Instruction::FCVTWQ { .. }
+            | Instruction::FCVTWUQ { .. }
+            | Instruction::FCVTQW { .. }
+            | Instruction::FCVTQWU { .. }
+            | Instruction::FCVTLQ { .. }
+            | Instruction::FCVTLUQ { .. }
+            | Instruction::FCVTQL { .. }
+            | Instruction::FCVTQLU { .. } => {
+                return Err(format!("unsupported instruction {instruction}"));
+            }
+            Instruction::CSRRW { dest, src, csr } => {
+                let value = self.read(src);
+                let old = self.read_csr(csr.val())?;
+                self.write_csr(csr.val(), value)?;
+                self.write(dest, old);
+            }
+            Instruction::CSRRS { dest, src, csr } => {
+                let old = self.read_csr(csr.val())?;
+                if src != IRegister::Zero {
+                    self.write_csr(csr.val(), old | self.read(src))?;
+                }
+                self.write(dest, old);
+            }
+            Instruction::CSRRC { dest, src, csr } => {
+                let old = self.read_csr(csr.val())?;
+                if src != IRegister::Zero {
+                    self.write_csr(csr.val(), old & !self.read(src))?;
+                }
+                self.write(dest, old);
+            }
+            Instruction::CSRRWI { dest, imm, csr } => {
+                let old = self.read_csr(csr.val())?;
+                self.write_csr(csr.val(), imm.val() as u64)?;
+                self.write(dest, old);
+            }
+            Instruction::CSRRSI { dest, imm, csr } => {
+                let old = self.read_csr(csr.val())?;
+                if imm.val() != 0 {
+                    self.write_csr(csr.val(), old | imm.val() as u64)?;
+                }
+                self.write(dest, old);
+            }
+            Instruction::CSRRCI { dest, imm, csr } => {
+                let old = self.read_csr(csr.val())?;
+                if imm.val() != 0 {
+                    self.write_csr(csr.val(), old & !(imm.val() as u64))?;
+                }
+                self.write(dest, old);
+            }
+        }
+        self.pc = next;
+        Ok(Event::Continue)
+    }
+
+    /// Writes the sign extension of a 32-bit result.
+    fn write_w(&mut self, register: IRegister, value: i32) {
+        self.write(register, value as i64 as u64);
+    }
+
+    fn load(&self, base: IRegister, offset: i64, size: usize) -> Result<u64, String> {
+        self.memory
+            .load(self.read(base).wrapping_add(offset as u64), size)
+    }
+
+    fn store(
+        &mut self,
+        base: IRegister,
+        offset: i64,
+        size: usize,
+        src: IRegister,
+    ) -> Result<(), String> {
+        let address = self.read(base).wrapping_add(offset as u64);
+        self.memory.store(address, size, self.read(src))
+    }
+
+    /// Returns the address of an atomic memory operation, which must be naturally aligned.
+    fn atomic_address(&self, addr: IRegister, size: u64) -> Result<u64, String> {
+        let address = self.read(addr);
+        if !address.is_multiple_of(size) {
+            Err(format!("misaligned atomic access at {address:#x}"))
+        } else {
+            Ok(address)
+        }
+    }
+
+    fn store_conditional(
+        &mut self,
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        size: usize,
+    ) -> Result<(), String> {
+        let address = self.atomic_address(addr, size as u64)?;
+        if self.reservation.take() == Some(address) {
+            self.memory.store(address, size, self.read(src))?;
+            self.write(dest, 0);
+        } else {
+            self.write(dest, 1);
+        }
+        Ok(())
+    }
+
+    fn amo_w(
+        &mut self,
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        op: impl Fn(i32, i32) -> i32,
+    ) -> Result<(), String> {
+        let address = self.atomic_address(addr, 4)?;
+        let old = self.memory.load(address, 4)? as i32;
+        self.memory
+            .store(address, 4, op(old, self.read(src) as i32) as u32 as u64)?;
+        self.write_w(dest, old);
+        Ok(())
+    }
+
+    fn amo_d(
+        &mut self,
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        op: impl Fn(u64, u64) -> u64,
+    ) -> Result<(), String> {
+        let address = self.atomic_address(addr, 8)?;
+        let old = self.memory.load(address, 8)?;
+        self.memory.store(address, 8, op(old, self.read(src)))?;
+        self.write(dest, old);
+        Ok(())
+    }
+
+    /// The byte and halfword AMOs of Zabha. The operands are sign extended, which keeps their unsigned order, so the
+    /// operations of `amo_d` can be used.
+    fn amo_narrow(
+        &mut self,
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        size: usize,
+        op: impl Fn(u64, u64) -> u64,
+    ) -> Result<(), String> {
+        let sext = |value: u64| {
+            let shift = 64 - 8 * size as u32;
+            ((value << shift) as i64 >> shift) as u64
+        };
+        let address = self.atomic_address(addr, size as u64)?;
+        let old = sext(self.memory.load(address, size)?);
+        self.memory
+            .store(address, size, op(old, sext(self.read(src))))?;
+        self.write(dest, old);
+        Ok(())
+    }
+
+    /// amocas.b and amocas.h, which store `src` if memory holds the low bits of `dest`.
+    fn amo_cas(
+        &mut self,
+        dest: IRegister,
+        addr: IRegister,
+        src: IRegister,
+        size: usize,
+    ) -> Result<(), String> {
+        let shift = 64 - 8 * size as u32;
+        let address = self.atomic_address(addr, size as u64)?;
+        let old = self.memory.load(address, size)?;
+        if old == self.read(dest) << shift >> shift {
+            self.memory.store(address, size, self.read(src))?;
+        }
+        self.write(dest, ((old << shift) as i64 >> shift) as u64);
+        Ok(())
+    }
+
+    fn sign_inject(
+        &mut self,
+        dest: FRegister,
+        src1: FRegister,
+        src2: FRegister,
+        sign: impl Fn(bool, bool) -> bool,
+    ) {
+        let a = self.f[u32::from(src1) as usize];
+        let b = self.f[u32::from(src2) as usize];
+        let negative = sign(a >> 31 == 1, b >> 31 == 1);
+        self.f[u32::from(dest) as usize] = (a & 0x7fff_ffff) | ((negative as u32) << 31);
+    }
+
+    /// Resolves the dynamic rounding mode, returning an error for reserved rounding modes.
+    fn rounding_mode(&self, rm: RoundingMode) -> Result<RoundingMode, String> {
+        let rm = match rm {
+            RoundingMode::DYN => RoundingMode::from_int((self.fcsr >> 5) & 0b111)?,
+            rm => rm,
+        };
+        match rm {
+            RoundingMode::DYN | RoundingMode::Reserved(_) => {
+                Err(format!("invalid rounding mode {rm}"))
+            }
+            rm => Ok(rm),
+        }
+    }
+
+    /// Rounds a float to an integral value using a rounding mode.
+    fn round(&self, value: f32, rm: RoundingMode) -> Result<f32, String> {
+        Ok(match self.rounding_mode(rm)? {
+            RoundingMode::RNE => value.round_ties_even(),
+            RoundingMode::RTZ => value.trunc(),
+            RoundingMode::RDN => value.floor(),
+            RoundingMode::RUP => value.ceil(),
+            RoundingMode::RMM => value.round(),
+            RoundingMode::DYN | RoundingMode::Reserved(_) => unreachable!(),
+        })
+    }
+
+    fn read_csr(&self, csr: i64) -> Result<u64, String> {
+        match csr {
+            0x001 => Ok((self.fcsr & 0b1_1111) as u64),
+            0x002 => Ok(((self.fcsr >> 5) & 0b111) as u64),
+            0x003 => Ok((self.fcsr & 0xff) as u64),
+            _ => Err(format!("unsupported csr {csr:#x}")),
+        }
+    }
+
+    fn write_csr(&mut self, csr: i64, value: u64) -> Result<(), String> {
+        let value = value as u32;
+        self.fcsr = match csr {
+            0x001 => (self.fcsr & !0b1_1111) | (value & 0b1_1111),
+            0x002 => (self.fcsr & 0b1_1111) | ((value & 0b111) << 5),
+            0x003 => value & 0xff,
+            _ => return Err(format!("unsupported csr {csr:#x}")),
+        };
+        Ok(())
+    }
+}
+
+/// Rounds a single precision value to bfloat16, which keeps its exponent and the top 7 bits of its mantissa.
+fn round_bf16(value: f32, rm: RoundingMode) -> u16 {
+    if value.is_nan() {
+        return (CANONICAL_NAN >> 16) as u16;
+    }
+    let bits = value.to_bits();
+    let (upper, lower) = ((bits >> 16) as u16, bits & 0xffff);
+    let negative = bits >> 31 == 1;
+    let away = lower != 0
+        && match rm {
+            RoundingMode::RNE => lower > 0x8000 || (lower == 0x8000 && upper & 1 == 1),
+            RoundingMode::RTZ => false,
+            RoundingMode::RDN => negative,
+            RoundingMode::RUP => !negative,
+            RoundingMode::RMM => lower >= 0x8000,
+            RoundingMode::DYN | RoundingMode::Reserved(_) => unreachable!(),
+        };
+    // a carry out of the mantissa increments the exponent, which rounds the largest values to infinity
+    upper + away as u16
+}
+
+/// The full carry-less product of two registers.
+fn clmul(a: u64, b: u64) -> u128 {
+    (0..64)
+        .filter(|i| b >> i & 1 == 1)
+        .fold(0, |product, i| product ^ (a as u128) << i)
+}
+
+/// Replaces each element of `indices` of a width of bits with the element of `table` it selects, or 0 if it is out of
+/// range.
+fn xperm(table: u64, indices: u64, width: u32) -> u64 {
+    let mask = (1 << width) - 1;
+    (0..64).step_by(width as usize).fold(0, |result, position| {
+        let index = (indices >> position & mask) as u32 * width;
+        let element = if index < 64 { table >> index & mask } else { 0 };
+        result | element << position
+    })
+}
+
+/// Computes the result of fclass.s for the bits of a float.
+fn classify(bits: u32) -> u64 {
+    let value = f32::from_bits(bits);
+    let negative = bits >> 31 == 1;
+    let bit = if value.is_nan() {
+        // quiet NaNs have the most significant bit of the mantissa set
+        if bits & 0x0040_0000 != 0 { 9 } else { 8 }
+    } else if value.is_infinite() {
+        if negative { 0 } else { 7 }
+    } else if value == 0.0 {
+        if negative { 3 } else { 4 }
+    } else if value.is_subnormal() {
+        if negative { 2 } else { 5 }
+    } else if negative {
+        1
+    } else {
+        6
+    };
+    1 << bit
+}
@@ -1,6 +1,35 @@
+pub mod analysis;
+#[cfg(feature = "xandesperf")]
+pub mod andes;
 pub mod assembly;
 pub mod cinstruction;
+pub mod codec;
+pub mod config;
+pub mod coverage;
+pub mod custom;
+#[cfg(feature = "difftest")]
+pub mod difftest;
+#[cfg(feature = "elf")]
+pub mod elf;
+pub mod emit;
+pub mod error;
+#[cfg(feature = "elf")]
+pub mod golden;
+pub mod html;
 pub mod immediates;
 pub mod instruction;
+#[cfg(feature = "interp")]
+pub mod interp;
 pub mod opcode;
+pub mod packed;
+pub mod program;
 pub mod register;
+pub mod scan;
+pub mod semantics;
+#[cfg(feature = "p")]
+pub mod simd;
+#[cfg(feature = "xthead")]
+pub mod thead;
+pub mod timing;
+pub mod trace;
+pub mod vector;
@@ -0,0 +1,195 @@
+use std::process::ExitCode;
+
+use riscv_codec::analysis::{DiffOptions, decode_region, diff, from_be_words};
+use riscv_codec::config::{AssemblerConfig, DecoderConfig, FormatOptions, Xlen};
+use riscv_codec::coverage::{coverage, load_opcodes};
+use riscv_codec::html::html_listing;
+use riscv_codec::program::assemble_program;
+use riscv_codec::trace::{format_trace, parse_trace};
+
+const USAGE: &str = "usage:
+    riscv-codec assemble [--rv32] SOURCE OBJECT
+        assemble a program into a relocatable ELF file
+    riscv-codec coverage [--rv32] OPCODES
+        compare the decoder against the extensions directory of riscv-opcodes
+    riscv-codec diff [--base ADDRESS] [--big-endian] [--ignore-registers] OLD NEW
+        compare two raw binary images instruction by instruction
+    riscv-codec disassemble [--lines] [--source] [--frames] ELF
+        disassemble the code sections of an ELF file, optionally annotated with source lines and stack frames
+    riscv-codec html [--base ADDRESS] [--big-endian] IMAGE
+        disassemble a raw binary image into an HTML page
+    riscv-codec trace TRACE
+        decode a trace of pc,instruction pairs or a spike log into an execution listing
+
+--big-endian reads images as dumps of big endian 32-bit words";
+
+/// Reads a raw binary image, converting it to little endian if it is a dump of big endian words.
+fn read_image(path: &str, big_endian: bool) -> Result<Vec<u8>, String> {
+    let data = std::fs::read(path).map_err(|e| format!("unable to read {path}: {e}"))?;
+    if big_endian {
+        from_be_words(&data).map_err(|e| format!("{path}: {e}"))
+    } else {
+        Ok(data)
+    }
+}
+
+/// Parses a decimal or `0x` prefixed hexadecimal address.
+fn parse_address(str: &str) -> Result<u64, String> {
+    match str.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => str.parse(),
+    }
+    .map_err(|e| format!("invalid address {str}: {e}"))
+}
+
+fn assemble_command(args: &[String]) -> Result<(), String> {
+    let mut config = AssemblerConfig::default();
+    let mut files = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--rv32" => config.xlen = Xlen::Rv32,
+            _ => files.push(arg),
+        }
+    }
+    let [source, object] = files[..] else {
+        return Err(USAGE.to_owned());
+    };
+    let text =
+        std::fs::read_to_string(source).map_err(|e| format!("unable to read {source}: {e}"))?;
+    let program = assemble_program(&text, &config).map_err(|e| format!("{source}: {e}"))?;
+    std::fs::write(object, program.to_elf()).map_err(|e| format!("unable to write {object}: {e}"))
+}
+
+fn coverage_command(args: &[String]) -> Result<(), String> {
+    let mut config = DecoderConfig::default();
+    let mut dirs = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--rv32" => config.xlen = Xlen::Rv32,
+            _ => dirs.push(arg),
+        }
+    }
+    let [dir] = dirs[..] else {
+        return Err(USAGE.to_owned());
+    };
+    let entries = load_opcodes(dir.as_ref())?;
+    print!("{}", coverage(&entries, &config));
+    Ok(())
+}
+
+fn diff_command(args: &[String]) -> Result<(), String> {
+    let mut base = 0;
+    let mut big_endian = false;
+    let mut options = DiffOptions::default();
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--base" => {
+                base = parse_address(args.next().ok_or("--base requires an address")?)?;
+            }
+            "--big-endian" => big_endian = true,
+            "--ignore-registers" => options.ignore_registers = true,
+            _ => files.push(arg),
+        }
+    }
+    let [old, new] = files[..] else {
+        return Err(USAGE.to_owned());
+    };
+    let config = DecoderConfig::default();
+    let old = decode_region(&read_image(old, big_endian)?, base, &config);
+    let new = decode_region(&read_image(new, big_endian)?, base, &config);
+    for function in diff(&old, &new, &[], &options) {
+        print!("{function}");
+    }
+    Ok(())
+}
+
+fn html_command(args: &[String]) -> Result<(), String> {
+    let mut base = 0;
+    let mut big_endian = false;
+    let mut files = Vec::new();
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--base" => {
+                base = parse_address(args.next().ok_or("--base requires an address")?)?;
+            }
+            "--big-endian" => big_endian = true,
+            _ => files.push(arg),
+        }
+    }
+    let [path] = files[..] else {
+        return Err(USAGE.to_owned());
+    };
+    let data = read_image(path, big_endian)?;
+    print!(
+        "{}",
+        html_listing(&data, base, &[], &DecoderConfig::default())
+    );
+    Ok(())
+}
+
+fn trace_command(args: &[String]) -> Result<(), String> {
+    let [path] = args else {
+        return Err(USAGE.to_owned());
+    };
+    let text = std::fs::read_to_string(path).map_err(|e| format!("unable to read {path}: {e}"))?;
+    let options = FormatOptions {
+        pseudo_instructions: true,
+        ..Default::default()
+    };
+    print!(
+        "{}",
+        format_trace(
+            &parse_trace(&text)?,
+            &[],
+            &DecoderConfig::default(),
+            &options
+        )
+    );
+    Ok(())
+}
+
+#[cfg(feature = "elf")]
+fn disassemble_command(args: &[String]) -> Result<(), String> {
+    use riscv_codec::elf::{Elf, ListingOptions, disassemble_elf};
+
+    let mut options = ListingOptions::default();
+    let mut files = Vec::new();
+    for arg in args {
+        match arg.as_str() {
+            "--lines" => options.lines = true,
+            "--source" => options.source = true,
+            "--frames" => options.frames = true,
+            _ => files.push(arg),
+        }
+    }
+    let [path] = files[..] else {
+        return Err(USAGE.to_owned());
+    };
+    let data = std::fs::read(path).map_err(|e| format!("unable to read {path}: {e}"))?;
+    print!("{}", disassemble_elf(&Elf::parse(&data)?, &options)?);
+    Ok(())
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let result = match args.first().map(String::as_str) {
+        Some("assemble") => assemble_command(&args[1..]),
+        Some("coverage") => coverage_command(&args[1..]),
+        Some("diff") => diff_command(&args[1..]),
+        Some("html") => html_command(&args[1..]),
+        Some("trace") => trace_command(&args[1..]),
+        #[cfg(feature = "elf")]
+        Some("disassemble") => disassemble_command(&args[1..]),
+        _ => Err(USAGE.to_owned()),
+    };
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("{e}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -24,6 +24,12 @@ pub enum Opcode {
     Nmsub = 0b10_010_11,
     Nmadd = 0b10_011_11,
     System = 0b11_100_11,
+    OpV = 0b10_101_11,
+    OpVe = 0b11_101_11,
+    #[cfg(feature = "rv128")]
+    OpImm64 = 0b10_110_11,
+    #[cfg(feature = "rv128")]
+    Op64 = 0b11_110_11,
     Reserved = 0,
 }
 
@@ -55,6 +61,12 @@ impl Opcode {
             0b00_001_11 => Self::LoadFp,
             0b01_001_11 => Self::StoreFp,
             0b11_100_11 => Self::System,
+            0b10_101_11 => Self::OpV,
+            0b11_101_11 => Self::OpVe,
+            #[cfg(feature = "rv128")]
+            0b10_110_11 => Self::OpImm64,
+            #[cfg(feature = "rv128")]
+            0b11_110_11 => Self::Op64,
             _ => Self::Reserved,
         }
     }
@@ -0,0 +1,136 @@
+//! A compact representation of decoded instructions for holding large traces in memory.
+//!
+//! `Instruction` is an enum with operands for every instruction and takes several times the space of its encoding.
+//! `PackedInst` keeps the encoding along with the length and execution class, which are what trace processing needs
+//! most often, and decodes the full instruction again on demand.
+
+use crate::cinstruction::CInstruction;
+use crate::config::{DecoderConfig, Extensions, Xlen};
+use crate::error::DecodeError;
+use crate::instruction::Instruction;
+use crate::timing::ExecClass;
+
+const COMPRESSED: u8 = 1 << 0;
+const RV32: u8 = 1 << 1;
+const ZCLSD: u8 = 1 << 2;
+#[cfg(feature = "rv128")]
+const RV128: u8 = 1 << 3;
+const P: u8 = 1 << 4;
+const ZICFILP: u8 = 1 << 5;
+
+/// A validated instruction encoding with its cached length and execution class, in 8 bytes.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub struct PackedInst {
+    raw: u32,
+    class: ExecClass,
+    /// The parts of the decoder configuration that change the meaning of an encoding.
+    flags: u8,
+}
+
+impl PackedInst {
+    /// Decodes an instruction with the default configuration, see `decode_with`.
+    pub fn decode(raw: u32) -> Result<PackedInst, DecodeError> {
+        PackedInst::decode_with(raw, &DecoderConfig::default())
+    }
+
+    /// Decodes an instruction, which is compressed if the low two bits of `raw` are not `0b11` (in which case only the
+    /// low 16 bits are kept).
+    pub fn decode_with(raw: u32, config: &DecoderConfig) -> Result<PackedInst, DecodeError> {
+        let mut flags = 0;
+        if config.xlen == Xlen::Rv32 {
+            flags |= RV32;
+        }
+        if config.extensions.zclsd {
+            flags |= ZCLSD;
+        }
+        #[cfg(feature = "rv128")]
+        if config.xlen == Xlen::Rv128 {
+            flags |= RV128;
+        }
+        if config.extensions.p {
+            flags |= P;
+        }
+        if config.extensions.zicfilp {
+            flags |= ZICFILP;
+        }
+        let (raw, instruction) = if raw & 0b11 == 0b11 {
+            (raw, Instruction::decode_with(raw, config)?)
+        } else {
+            flags |= COMPRESSED;
+            let half = raw as u16;
+            let instruction = CInstruction::decode_expanded_with(half, config)?;
+            (half as u32, instruction)
+        };
+        Ok(PackedInst {
+            raw,
+            class: instruction.exec_class(),
+            flags,
+        })
+    }
+
+    /// The encoding, only the low 16 bits are used for compressed instructions.
+    pub fn raw(&self) -> u32 {
+        self.raw
+    }
+
+    pub fn is_compressed(&self) -> bool {
+        self.flags & COMPRESSED != 0
+    }
+
+    /// The length of the encoding in bytes (2 or 4).
+    #[allow(clippy::len_without_is_empty)]
+    pub fn len(&self) -> usize {
+        if self.is_compressed() { 2 } else { 4 }
+    }
+
+    pub fn exec_class(&self) -> ExecClass {
+        self.class
+    }
+
+    /// The configuration to decode the encoding with again.
+    ///
+    /// The encoding was accepted when it was packed, so a permissive configuration gives the same instruction as long as
+    /// it agrees on the XLEN, on Zclsd, on P and on Zicfilp, which are the only options that change the meaning of an
+    /// encoding (Zicfiss only takes encodings that are otherwise rejected).
+    fn config(&self) -> DecoderConfig {
+        let rv32 = self.flags & RV32 != 0;
+        let xlen = if rv32 { Xlen::Rv32 } else { Xlen::Rv64 };
+        #[cfg(feature = "rv128")]
+        let xlen = if self.flags & RV128 != 0 {
+            Xlen::Rv128
+        } else {
+            xlen
+        };
+        DecoderConfig {
+            xlen,
+            strict: false,
+            reserved_rounding_modes: true,
+            extensions: Extensions {
+                zilsd: rv32,
+                zclsd: self.flags & ZCLSD != 0,
+                p: self.flags & P != 0,
+                zicfilp: self.flags & ZICFILP != 0,
+                zicfiss: true,
+                zalasr: true,
+                xandesperf: true,
+                xtheadba: true,
+                xtheadbb: true,
+                xtheadcmo: true,
+                xtheadcondmov: true,
+                xtheadmempair: true,
+                ..Extensions::default()
+            },
+        }
+    }
+
+    /// Decodes the full instruction, compressed instructions are expanded.
+    pub fn expand(&self) -> Instruction {
+        let config = self.config();
+        let instruction = if self.is_compressed() {
+            CInstruction::decode_expanded_with(self.raw as u16, &config)
+        } else {
+            Instruction::decode_with(self.raw, &config)
+        };
+        instruction.expect("packed instructions were decoded before")
+    }
+}
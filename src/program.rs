@@ -0,0 +1,1376 @@
+//! Assembly of whole programs, with labels, data directives and symbols.
+//!
+//! Each line holds any number of labels (`loop:`) followed by an instruction or a directive, and `#` starts a comment.
+//! Code and data are assembled into .text unless another section is selected with `.data`, `.rodata`, `.bss` or
+//! `.section`. Branches and jumps may target labels in the same section, other targets are left to the linker as
+//! relocations. Memory operands may use a symbol as the offset (`lw a0,value+4(gp)`), which is the offset of the symbol
+//! in its section, and `%hi(symbol+addend)` and `%lo(symbol+addend)` are left to the linker as relocations, as are
+//! the PC-relative, GOT and TLS operators (`%pcrel_hi`, `%pcrel_lo`, `%got_pcrel_hi`, `%tls_ie_pcrel_hi`,
+//! `%tls_gd_pcrel_hi`, `%tprel_hi`, `%tprel_lo` and the fourth operand `%tprel_add` of add). `call`, `tail` and `la`
+//! take a symbol as well as an offset, and a symbol outside the section is left to the linker as an `R_RISCV_CALL_PLT`
+//! relocation or as a pair of `R_RISCV_PCREL_HI20` and `R_RISCV_PCREL_LO12_I` relocations.
+//! `.org offset` and `. = offset` pad the current section to an offset from its start, and `.attribute arch, "rv32imac"`
+//! selects the XLEN and extensions for the rest of the program. `.option arch, +zifencei, -c` enables and disables
+//! extensions, `.option rvc` and `.option norvc` are short for `+c` and `-c`, and `.option push` and `.option pop`
+//! save and restore the extensions around them. `li rd,=value` loads a constant or the address of a
+//! symbol from a literal pool, which is placed at the next `.pool` (or `.ltorg`) in the section or at its end. The
+//! result can be written out as a relocatable ELF file.
+
+use crate::assembly::{assemble_line_expanded_with, assemble_line_with};
+use crate::config::{AssemblerConfig, Extensions, Xlen, parse_isa_string};
+
+/// The binding of a symbol, which controls whether it is visible to the linker.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub enum Binding {
+    /// Only visible within the program, the default for labels.
+    #[default]
+    Local,
+    /// Visible to other programs (`.globl`).
+    Global,
+    /// Visible to other programs, but may be overridden by a global symbol and may be left undefined (`.weak`).
+    Weak,
+}
+
+/// A symbol of an assembled program.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ProgramSymbol {
+    pub name: String,
+    /// The index of the section the symbol is defined in, `None` for undefined symbols.
+    pub section: Option<usize>,
+    /// The offset of the symbol within its section.
+    pub value: u64,
+    pub binding: Binding,
+}
+
+/// The kind of a relocation.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RelocationKind {
+    /// The offset of a conditional branch (`R_RISCV_BRANCH`).
+    Branch,
+    /// The offset of jal (`R_RISCV_JAL`).
+    Jal,
+    /// The offset of c.beqz and c.bnez (`R_RISCV_RVC_BRANCH`).
+    RvcBranch,
+    /// The offset of c.j and c.jal (`R_RISCV_RVC_JUMP`).
+    RvcJump,
+    /// The offset of the auipc and jalr pair of call and tail (`R_RISCV_CALL_PLT`).
+    CallPlt,
+    /// The upper 20 bits of an address, for `%hi` (`R_RISCV_HI20`).
+    Hi20,
+    /// The lower 12 bits of an address in an I-type immediate, for `%lo` (`R_RISCV_LO12_I`).
+    Lo12I,
+    /// The lower 12 bits of an address in the immediate of a store, for `%lo` (`R_RISCV_LO12_S`).
+    Lo12S,
+    /// The upper 20 bits of a PC-relative offset, for `%pcrel_hi` (`R_RISCV_PCREL_HI20`).
+    PcrelHi20,
+    /// The lower 12 bits of the PC-relative offset computed by the auipc at the symbol, in an I-type immediate, for
+    /// `%pcrel_lo` (`R_RISCV_PCREL_LO12_I`).
+    PcrelLo12I,
+    /// The same in the immediate of a store (`R_RISCV_PCREL_LO12_S`).
+    PcrelLo12S,
+    /// The upper 20 bits of the PC-relative offset of the GOT entry of a symbol, for `%got_pcrel_hi`
+    /// (`R_RISCV_GOT_HI20`).
+    GotHi20,
+    /// The upper 20 bits of the PC-relative offset of the GOT entry holding the TLS offset of a symbol, for
+    /// `%tls_ie_pcrel_hi` (`R_RISCV_TLS_GOT_HI20`).
+    TlsGotHi20,
+    /// The upper 20 bits of the PC-relative offset of the GOT entry holding the TLS index of a symbol, for
+    /// `%tls_gd_pcrel_hi` (`R_RISCV_TLS_GD_HI20`).
+    TlsGdHi20,
+    /// The upper 20 bits of the offset of a symbol from the thread pointer, for `%tprel_hi` (`R_RISCV_TPREL_HI20`).
+    TprelHi20,
+    /// The lower 12 bits of the offset from the thread pointer in an I-type immediate, for `%tprel_lo`
+    /// (`R_RISCV_TPREL_LO12_I`).
+    TprelLo12I,
+    /// The same in the immediate of a store (`R_RISCV_TPREL_LO12_S`).
+    TprelLo12S,
+    /// Marks the add of the thread pointer, for `%tprel_add` (`R_RISCV_TPREL_ADD`).
+    TprelAdd,
+    /// A 32-bit address in data, for literal pools on RV32 (`R_RISCV_32`).
+    Abs32,
+    /// A 64-bit address in data, for literal pools on RV64 (`R_RISCV_64`).
+    Abs64,
+}
+
+impl RelocationKind {
+    /// The relocation type number in the RISC-V ELF psABI.
+    pub fn elf_type(&self) -> u32 {
+        match self {
+            RelocationKind::Branch => 16,
+            RelocationKind::Jal => 17,
+            RelocationKind::RvcBranch => 44,
+            RelocationKind::RvcJump => 45,
+            RelocationKind::CallPlt => 19,
+            RelocationKind::Hi20 => 26,
+            RelocationKind::Lo12I => 27,
+            RelocationKind::Lo12S => 28,
+            RelocationKind::PcrelHi20 => 23,
+            RelocationKind::PcrelLo12I => 24,
+            RelocationKind::PcrelLo12S => 25,
+            RelocationKind::GotHi20 => 20,
+            RelocationKind::TlsGotHi20 => 21,
+            RelocationKind::TlsGdHi20 => 22,
+            RelocationKind::TprelHi20 => 29,
+            RelocationKind::TprelLo12I => 30,
+            RelocationKind::TprelLo12S => 31,
+            RelocationKind::TprelAdd => 32,
+            RelocationKind::Abs32 => 1,
+            RelocationKind::Abs64 => 2,
+        }
+    }
+
+    /// The relocation for the `%` operator named `operator` (without the `%`).
+    fn from_operator(operator: &str) -> Option<RelocationKind> {
+        Some(match operator {
+            "hi" => RelocationKind::Hi20,
+            "lo" => RelocationKind::Lo12I,
+            "pcrel_hi" => RelocationKind::PcrelHi20,
+            "pcrel_lo" => RelocationKind::PcrelLo12I,
+            "got_pcrel_hi" => RelocationKind::GotHi20,
+            "tls_ie_pcrel_hi" => RelocationKind::TlsGotHi20,
+            "tls_gd_pcrel_hi" => RelocationKind::TlsGdHi20,
+            "tprel_hi" => RelocationKind::TprelHi20,
+            "tprel_lo" => RelocationKind::TprelLo12I,
+            "tprel_add" => RelocationKind::TprelAdd,
+            _ => return None,
+        })
+    }
+
+    /// The relocation for the same operator in the immediate of a store.
+    fn store(self) -> RelocationKind {
+        match self {
+            RelocationKind::Lo12I => RelocationKind::Lo12S,
+            RelocationKind::PcrelLo12I => RelocationKind::PcrelLo12S,
+            RelocationKind::TprelLo12I => RelocationKind::TprelLo12S,
+            kind => kind,
+        }
+    }
+
+    /// Whether the relocation is written with a `%` operator rather than as a branch target or data.
+    fn is_operator(&self) -> bool {
+        !matches!(
+            self,
+            RelocationKind::Branch
+                | RelocationKind::Jal
+                | RelocationKind::RvcBranch
+                | RelocationKind::RvcJump
+                | RelocationKind::CallPlt
+                | RelocationKind::Abs32
+                | RelocationKind::Abs64
+        )
+    }
+}
+
+/// A reference to a symbol that is resolved by the linker.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Relocation {
+    /// The offset of the instruction within its section.
+    pub offset: u64,
+    pub kind: RelocationKind,
+    pub symbol: String,
+    pub addend: i64,
+}
+
+/// A section of an assembled program.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct ProgramSection {
+    pub name: String,
+    /// The contents, all zero for sections without data.
+    pub data: Vec<u8>,
+    pub relocations: Vec<Relocation>,
+    /// Whether the section holds compressed instructions.
+    pub compressed: bool,
+    /// Whether the section is loaded into memory (`a`).
+    pub allocated: bool,
+    pub writable: bool,
+    pub executable: bool,
+    /// Whether the section takes no space in the file, like .bss (`@nobits`).
+    pub nobits: bool,
+    /// The alignment that the contents need in bytes, raised by literal pools and by data wider than a byte. Code
+    /// sections are aligned to at least 2 bytes.
+    pub align: u64,
+}
+
+impl ProgramSection {
+    /// Creates an empty section, the flags of the standard sections are derived from their names.
+    pub fn new(name: &str) -> ProgramSection {
+        let is = |standard: &str| name == standard || name.starts_with(&format!("{standard}."));
+        ProgramSection {
+            name: name.to_owned(),
+            data: Vec::new(),
+            relocations: Vec::new(),
+            compressed: false,
+            allocated: is(".text")
+                || is(".data")
+                || is(".rodata")
+                || is(".bss")
+                || is(".sdata")
+                || is(".sbss"),
+            writable: is(".data") || is(".bss") || is(".sdata") || is(".sbss"),
+            executable: is(".text"),
+            nobits: is(".bss") || is(".sbss"),
+            align: 1,
+        }
+    }
+}
+
+/// An assembled program.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub struct Program {
+    pub xlen: Xlen,
+    pub sections: Vec<ProgramSection>,
+    /// The labels and declared symbols, in the order they first appear.
+    pub symbols: Vec<ProgramSymbol>,
+}
+
+impl Program {
+    /// Returns the section with a name.
+    pub fn section(&self, name: &str) -> Option<&ProgramSection> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    /// Returns the symbol with a name.
+    pub fn symbol(&self, name: &str) -> Option<&ProgramSymbol> {
+        self.symbols.iter().find(|s| s.name == name)
+    }
+}
+
+/// Whether a string is a valid symbol name.
+fn is_symbol(str: &str) -> bool {
+    let mut chars = str.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '.' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '$')
+}
+
+/// Splits the labels off the start of a line, returning them and the rest of the line.
+fn split_labels(line: &str) -> (Vec<&str>, &str) {
+    let mut labels = Vec::new();
+    let mut rest = line.trim();
+    while let Some((label, after)) = rest.split_once(':') {
+        if !is_symbol(label.trim()) || label.trim() != label.trim_start() {
+            break;
+        }
+        labels.push(label.trim());
+        rest = after.trim();
+    }
+    (labels, rest)
+}
+
+/// Returns the operand of an instruction that may be a label, with the relocation used when it is undefined.
+fn label_operand(mnemonic: &str) -> Option<(usize, RelocationKind)> {
+    match mnemonic {
+        "beq" | "bne" | "blt" | "bge" | "bltu" | "bgeu" => Some((2, RelocationKind::Branch)),
+        "beqz" | "bnez" | "blez" | "bgez" | "bltz" | "bgtz" => Some((1, RelocationKind::Branch)),
+        "jal" => Some((1, RelocationKind::Jal)),
+        "j" => Some((0, RelocationKind::Jal)),
+        "c.beqz" | "c.bnez" => Some((1, RelocationKind::RvcBranch)),
+        "c.j" | "c.jal" => Some((0, RelocationKind::RvcJump)),
+        _ => None,
+    }
+}
+
+/// Parses an integer operand of a data directive.
+fn parse_value(str: &str) -> Result<i64, String> {
+    let str = str.trim();
+    let (negative, digits) = match str.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, str),
+    };
+    let value = match digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        Some(hex) => u64::from_str_radix(hex, 16),
+        None => digits.parse::<u64>(),
+    }
+    .map_err(|_| format!("unable to parse int:{str}"))?;
+    Ok(if negative {
+        (value as i64).wrapping_neg()
+    } else {
+        value as i64
+    })
+}
+
+/// Encodes the values of an integer data directive.
+fn integers(operands: &str, size: usize) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for operand in operands.split(',') {
+        let value = parse_value(operand)?;
+        let bits = size as u32 * 8;
+        // values must fit as either a signed or an unsigned integer
+        if bits < 64 && (value < -(1 << (bits - 1)) || value >= 1 << bits) {
+            return Err(format!("{value} does not fit in {size} bytes"));
+        }
+        bytes.extend(&value.to_le_bytes()[..size]);
+    }
+    Ok(bytes)
+}
+
+/// Parses a symbol with an optional constant added or subtracted (`data+8`), returning the symbol and the constant.
+fn symbol_offset(expression: &str) -> Result<(&str, i64), String> {
+    let expression = expression.trim();
+    let end = expression.find(['+', '-']).unwrap_or(expression.len());
+    let name = expression[..end].trim();
+    if !is_symbol(name) {
+        return Err(format!("expected a symbol, got {expression}"));
+    }
+    let addend = match expression[end..].split_at_checked(1) {
+        Some(("-", value)) => parse_value(value)?.wrapping_neg(),
+        Some((_, value)) => parse_value(value)?,
+        None => 0,
+    };
+    Ok((name, addend))
+}
+
+/// Parses a hex float literal without its `0x` prefix (`1.8p3`), the exponent is optional.
+fn parse_hex_float(str: &str) -> Option<f64> {
+    let (digits, exponent) = match str.split_once(['p', 'P']) {
+        Some((digits, exponent)) => (digits, exponent.parse::<i32>().ok()?),
+        None => (str, 0),
+    };
+    let (integer, fraction) = digits.split_once('.').unwrap_or((digits, ""));
+    if integer.is_empty() && fraction.is_empty() {
+        return None;
+    }
+    let mut mantissa = 0u64;
+    for c in integer.chars().chain(fraction.chars()) {
+        mantissa = mantissa.checked_mul(16)? + c.to_digit(16)? as u64;
+    }
+    let exponent = exponent.checked_sub(4 * fraction.len() as i32)?;
+    // scale in two steps so that the intermediate powers of two are representable
+    let half = exponent / 2;
+    Some(mantissa as f64 * 2f64.powi(half) * 2f64.powi(exponent - half))
+}
+
+/// Returns the IEEE-754 encoding of a float literal, in single or double precision.
+///
+/// Literals are decimal (`1.5`, `1e-3`), hex (`0x1.8p3`), `inf` or `nan`, which is encoded with all bits of the
+/// significand set like LLVM does.
+fn float_bits(str: &str, double: bool) -> Result<u64, String> {
+    let str = str.trim();
+    let (negative, digits) = match str.strip_prefix('-') {
+        Some(digits) => (true, digits),
+        None => (false, str.strip_prefix('+').unwrap_or(str)),
+    };
+    let error = || format!("unable to parse float:{str}");
+    if digits.starts_with(['+', '-']) {
+        return Err(error());
+    }
+    let sign = if negative { -1.0 } else { 1.0 };
+    let bits = if digits.eq_ignore_ascii_case("nan") {
+        if double {
+            u64::MAX >> 1
+        } else {
+            u32::MAX as u64 >> 1
+        }
+    } else if let Some(hex) = digits
+        .strip_prefix("0x")
+        .or_else(|| digits.strip_prefix("0X"))
+    {
+        let value = sign * parse_hex_float(hex).ok_or_else(error)?;
+        if double {
+            value.to_bits()
+        } else {
+            (value as f32).to_bits() as u64
+        }
+    } else if double {
+        (sign * digits.parse::<f64>().map_err(|_| error())?).to_bits()
+    } else {
+        (sign as f32 * digits.parse::<f32>().map_err(|_| error())?).to_bits() as u64
+    };
+    let sign_bit = if double { 1 << 63 } else { 1 << 31 };
+    Ok(if negative { bits | sign_bit } else { bits })
+}
+
+/// Encodes the values of .float or .double.
+fn floats(operands: &str, double: bool) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    for operand in operands.split(',') {
+        let bits = float_bits(operand, double)?;
+        bytes.extend(&bits.to_le_bytes()[..if double { 8 } else { 4 }]);
+    }
+    Ok(bytes)
+}
+
+/// Parses the string operands of .ascii or .asciz, separated by commas.
+///
+/// Adjacent strings (`"a" "b"`) are concatenated into one. Escapes follow GNU as: `\n`, `\t`, `\r`, `\b`, `\f`,
+/// `\v`, `\\` and `\"`, up to three octal digits (`\0`) and `\x` followed by any number of hex digits of which the
+/// low byte is used.
+fn strings(operands: &str) -> Result<Vec<Vec<u8>>, String> {
+    let operands = operands.trim();
+    let expected = || format!("expected a string in double quotes, got {operands}");
+    let mut strings = Vec::new();
+    let mut string = Vec::new();
+    let mut chars = operands.chars().peekable();
+    loop {
+        if chars.next() != Some('"') {
+            return Err(expected());
+        }
+        loop {
+            let c = chars.next().ok_or_else(expected)?;
+            match c {
+                '"' => break,
+                '\\' => {
+                    let escape = chars.next().ok_or_else(expected)?;
+                    let byte = match escape {
+                        'n' => b'\n',
+                        't' => b'\t',
+                        'r' => b'\r',
+                        'b' => 0x08,
+                        'f' => 0x0c,
+                        'v' => 0x0b,
+                        '\\' | '"' => escape as u8,
+                        '0'..='7' => {
+                            let mut value = escape.to_digit(8).unwrap();
+                            for _ in 0..2 {
+                                match chars.peek().and_then(|c| c.to_digit(8)) {
+                                    Some(digit) => {
+                                        value = value * 8 + digit;
+                                        chars.next();
+                                    }
+                                    None => break,
+                                }
+                            }
+                            value as u8
+                        }
+                        'x' => {
+                            let mut value = None;
+                            while let Some(digit) = chars.peek().and_then(|c| c.to_digit(16)) {
+                                value = Some((value.unwrap_or(0) << 4 | digit) & 0xff);
+                                chars.next();
+                            }
+                            value.ok_or("\\x must be followed by hex digits")? as u8
+                        }
+                        _ => return Err(format!("unknown escape sequence \\{escape}")),
+                    };
+                    string.push(byte);
+                }
+                _ => string.extend(c.to_string().bytes()),
+            }
+        }
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        match chars.peek() {
+            None => break,
+            Some('"') => {}
+            Some(',') => {
+                chars.next();
+                strings.push(std::mem::take(&mut string));
+                while chars.next_if(|c| c.is_whitespace()).is_some() {}
+            }
+            Some(_) => return Err(expected()),
+        }
+    }
+    strings.push(string);
+    Ok(strings)
+}
+
+/// The natural alignment of the values of a directive that emits data.
+fn data_alignment(directive: &str) -> u64 {
+    match directive {
+        ".half" | ".2byte" => 2,
+        ".word" | ".4byte" | ".float" | ".single" => 4,
+        ".dword" | ".8byte" | ".double" => 8,
+        _ => 1,
+    }
+}
+
+/// The contents of a directive that emits data, `None` for other directives.
+fn data(directive: &str, operands: &str) -> Result<Option<Vec<u8>>, String> {
+    Ok(Some(match directive {
+        ".byte" => integers(operands, 1)?,
+        ".half" | ".2byte" => integers(operands, 2)?,
+        ".word" | ".4byte" => integers(operands, 4)?,
+        ".dword" | ".8byte" => integers(operands, 8)?,
+        ".float" | ".single" => floats(operands, false)?,
+        ".double" => floats(operands, true)?,
+        ".ascii" => strings(operands)?.concat(),
+        ".asciz" | ".string" => strings(operands)?
+            .into_iter()
+            .flat_map(|mut string| {
+                string.push(0);
+                string
+            })
+            .collect(),
+        ".zero" | ".space" => {
+            let size = parse_value(operands)?;
+            vec![0; usize::try_from(size).map_err(|_| format!("invalid size {size}"))?]
+        }
+        _ => return Ok(None),
+    }))
+}
+
+/// Removes a comment from a line, ignoring `#` inside strings.
+fn strip_comment(line: &str) -> &str {
+    let mut quoted = false;
+    let mut escaped = false;
+    for (i, c) in line.char_indices() {
+        if std::mem::take(&mut escaped) {
+            continue;
+        }
+        match c {
+            '\\' if quoted => escaped = true,
+            '"' => quoted = !quoted,
+            '#' if !quoted => return &line[..i],
+            _ => {}
+        }
+    }
+    line
+}
+
+/// Splits a statement into its mnemonic or directive and its operands.
+fn split_statement(statement: &str) -> (&str, &str) {
+    match statement.split_once(char::is_whitespace) {
+        Some((mnemonic, operands)) => (mnemonic, operands.trim()),
+        None => (statement, ""),
+    }
+}
+
+/// Parses the operands of `.section name[,"flags"[,@type]]`.
+fn parse_section(operands: &str) -> Result<ProgramSection, String> {
+    let operands: Vec<&str> = operands.split(',').map(str::trim).collect();
+    let name = operands[0];
+    if !is_symbol(name) {
+        return Err(format!("invalid section name {name}"));
+    }
+    let mut section = ProgramSection::new(name);
+    if let Some(flags) = operands.get(1) {
+        let flags = flags
+            .strip_prefix('"')
+            .and_then(|f| f.strip_suffix('"'))
+            .ok_or_else(|| format!("section flags must be in double quotes, got {flags}"))?;
+        section.allocated = false;
+        section.writable = false;
+        section.executable = false;
+        for flag in flags.chars() {
+            match flag {
+                'a' => section.allocated = true,
+                'w' => section.writable = true,
+                'x' => section.executable = true,
+                _ => return Err(format!("unknown section flag {flag}")),
+            }
+        }
+    }
+    match operands.get(2).copied() {
+        None => {}
+        Some("@progbits" | "%progbits") => section.nobits = false,
+        Some("@nobits" | "%nobits") => section.nobits = true,
+        Some(kind) => return Err(format!("unknown section type {kind}")),
+    }
+    Ok(section)
+}
+
+/// The program being assembled.
+struct Assembler<'a> {
+    /// The configuration the program is assembled with, which `.attribute arch` replaces.
+    initial: &'a AssemblerConfig,
+    config: AssemblerConfig,
+    /// The extensions saved by `.option push`.
+    pushed: Vec<Extensions>,
+    program: Program,
+    /// The index of the section being assembled into.
+    current: usize,
+    /// The values of literal loads that are not in a pool yet, with the section of the load and the number of the
+    /// load in the program.
+    literals: Vec<(usize, String, usize)>,
+    /// The number of literal loads assembled in this pass.
+    loads: usize,
+    /// The offset of the pool entry of each literal load, known after the first pass.
+    pool_offsets: Vec<u64>,
+    /// The number of `.Lpcrel_hi` labels placed on the auipc of la in this pass.
+    pcrel_labels: usize,
+}
+
+impl Assembler<'_> {
+    /// Returns the index of a symbol, adding it as an undefined local symbol if it does not exist yet.
+    fn symbol(&mut self, name: &str) -> &mut ProgramSymbol {
+        let symbols = &mut self.program.symbols;
+        let index = match symbols.iter().position(|s| s.name == name) {
+            Some(index) => index,
+            None => {
+                symbols.push(ProgramSymbol {
+                    name: name.to_owned(),
+                    section: None,
+                    value: 0,
+                    binding: Binding::Local,
+                });
+                symbols.len() - 1
+            }
+        };
+        &mut symbols[index]
+    }
+
+    /// Handles a directive that sets the binding of symbols.
+    fn bind(&mut self, operands: &str, binding: Binding) -> Result<(), String> {
+        for name in operands.split(',').map(str::trim) {
+            if !is_symbol(name) {
+                return Err(format!("invalid symbol name {name}"));
+            }
+            self.symbol(name).binding = binding;
+        }
+        Ok(())
+    }
+
+    /// Evaluates an offset in the current section: numbers, `.` and labels defined in the section, added or
+    /// subtracted.
+    fn offset_expression(&self, expression: &str) -> Result<u64, String> {
+        let current = self.program.sections[self.current].data.len() as i64;
+        let mut value = 0i64;
+        let mut rest = expression.trim();
+        let mut sign = 1;
+        loop {
+            let end = rest.find(['+', '-']).unwrap_or(rest.len());
+            let term = rest[..end].trim();
+            let term = match term {
+                "" => return Err(format!("invalid expression {expression}")),
+                "." => current,
+                _ if is_symbol(term) => match self.program.symbol(term) {
+                    Some(symbol) if symbol.section == Some(self.current) => symbol.value as i64,
+                    _ => return Err(format!("{term} is not defined earlier in this section")),
+                },
+                _ => parse_value(term)?,
+            };
+            value = value.wrapping_add(sign * term);
+            match rest[end..].chars().next() {
+                None => break,
+                Some(operator) => {
+                    sign = if operator == '-' { -1 } else { 1 };
+                    rest = &rest[end + 1..];
+                }
+            }
+        }
+        u64::try_from(value).map_err(|_| format!("{expression} is a negative offset"))
+    }
+
+    /// Handles `.attribute tag, value`. The arch attribute configures the assembler for the rest of the program, the
+    /// other attributes of the RISC-V psABI are accepted and ignored.
+    fn attribute(&mut self, operands: &str) -> Result<(), String> {
+        let (tag, value) = operands
+            .split_once(',')
+            .ok_or_else(|| format!("expected a tag and a value, got {operands}"))?;
+        match tag.trim() {
+            "arch" | "Tag_RISCV_arch" | "5" => {
+                let isa = strings(value)?.concat();
+                let (xlen, extensions) = parse_isa_string(&String::from_utf8_lossy(&isa))?;
+                let assembled = self.program.sections.iter().any(|s| !s.data.is_empty());
+                if xlen != self.program.xlen && assembled {
+                    return Err(format!(
+                        "the arch attribute can not change the XLEN to {} after code or data",
+                        xlen.bits()
+                    ));
+                }
+                self.config = AssemblerConfig { xlen, extensions };
+                self.program.xlen = xlen;
+            }
+            "stack_align" | "unaligned_access" | "priv_spec" | "priv_spec_minor"
+            | "priv_spec_revision" | "atomic_abi" | "x3_reg_usage" => {}
+            tag if tag.parse::<u32>().is_ok() => {}
+            tag => return Err(format!("unknown attribute {tag}")),
+        }
+        Ok(())
+    }
+
+    /// Handles `.option`, which changes the extensions instructions are assembled with. The options for relaxation and
+    /// position independent code do not change the output and are accepted and ignored.
+    fn option(&mut self, operands: &str) -> Result<(), String> {
+        let (option, rest) = operands.split_once(',').unwrap_or((operands, ""));
+        match option.trim() {
+            "push" => self.pushed.push(self.config.extensions),
+            "pop" => {
+                self.config.extensions =
+                    self.pushed.pop().ok_or("there is no .option push to pop")?;
+            }
+            "rvc" => {
+                self.config.extensions.set("c", true);
+            }
+            "norvc" => {
+                self.config.extensions.set("c", false);
+            }
+            "arch" => {
+                for change in rest.split(',').map(str::trim) {
+                    if let Some(name) = change.strip_prefix('+') {
+                        self.config.extensions.set(&name.to_lowercase(), true);
+                    } else if let Some(name) = change.strip_prefix('-') {
+                        self.config.extensions.set(&name.to_lowercase(), false);
+                    } else {
+                        let (xlen, extensions) = parse_isa_string(change)?;
+                        if xlen != self.config.xlen {
+                            return Err(format!(
+                                ".option arch can not change the XLEN to {}",
+                                xlen.bits()
+                            ));
+                        }
+                        self.config.extensions = extensions;
+                    }
+                }
+            }
+            "pic" | "nopic" | "relax" | "norelax" => {}
+            option => return Err(format!("unknown option {option}")),
+        }
+        Ok(())
+    }
+
+    /// Pads the current section up to an offset, for `.org offset[,fill]` and `. = offset`.
+    fn org(&mut self, operands: &str) -> Result<(), String> {
+        let (offset, fill) = match operands.split_once(',') {
+            Some((offset, fill)) => (offset, parse_value(fill)?),
+            None => (operands, 0),
+        };
+        let offset = self.offset_expression(offset)?;
+        let fill = u8::try_from(fill).map_err(|_| format!("{fill} does not fit in a byte"))?;
+        let section = &mut self.program.sections[self.current];
+        let current = section.data.len() as u64;
+        if offset < current {
+            return Err(format!(
+                "can not move the location counter backwards from {current:#x} to {offset:#x}"
+            ));
+        }
+        if section.nobits && fill != 0 {
+            return Err(format!("{} can only hold zeros", section.name));
+        }
+        section.data.resize(offset as usize, fill);
+        Ok(())
+    }
+
+    /// Assembles `li dest,=value` as a pc-relative load from the entry of the value in a literal pool.
+    ///
+    /// During the first pass the pool is not placed yet, so the load is assembled with an offset of 0.
+    fn literal_load(&mut self, dest: &str, value: &str, offset: u64) -> Result<Vec<u8>, String> {
+        let load = match self.config.xlen {
+            Xlen::Rv32 => "lw",
+            Xlen::Rv64 => "ld",
+            #[cfg(feature = "rv128")]
+            Xlen::Rv128 => return Err("literal pools are not supported on RV128".to_owned()),
+        };
+        self.literals
+            .push((self.current, value.to_owned(), self.loads));
+        let target = self.pool_offsets.get(self.loads).copied().unwrap_or(offset);
+        self.loads += 1;
+        let distance = target.wrapping_sub(offset) as i64;
+        let hi = distance.wrapping_add(0x800) >> 12;
+        let lo = distance - (hi << 12);
+        let mut bytes =
+            assemble_line_with(&format!("auipc {dest},{hi}"), &self.config)?.encode_bytes();
+        bytes.extend(
+            assemble_line_with(&format!("{load} {dest},{lo}({dest})"), &self.config)?
+                .encode_bytes(),
+        );
+        Ok(bytes)
+    }
+
+    /// Places the literals loaded in the current section in a pool at the current offset, aligned to the size of an
+    /// address. Loads of the same value share an entry.
+    fn pool(&mut self, final_pass: bool) -> Result<(), String> {
+        let (literals, rest) = std::mem::take(&mut self.literals)
+            .into_iter()
+            .partition::<Vec<_>, _>(|(section, ..)| *section == self.current);
+        self.literals = rest;
+        let size = self.config.xlen.bits() as usize / 8;
+        let mut entries: Vec<(String, u64)> = Vec::new();
+        for (_, value, load) in literals {
+            let entry = match entries.iter().find(|(v, _)| *v == value) {
+                Some((_, entry)) => *entry,
+                None => {
+                    let section = &mut self.program.sections[self.current];
+                    section.align = section.align.max(size as u64);
+                    let data = &mut section.data;
+                    data.resize(data.len().next_multiple_of(size), 0);
+                    let entry = data.len() as u64;
+                    match parse_value(&value) {
+                        Ok(_) => data.extend(integers(&value, size)?),
+                        Err(_) => {
+                            let (name, addend) = symbol_offset(&value)?;
+                            data.extend(vec![0; size]);
+                            if final_pass {
+                                self.symbol(name);
+                            }
+                            let kind = match self.config.xlen {
+                                Xlen::Rv32 => RelocationKind::Abs32,
+                                Xlen::Rv64 => RelocationKind::Abs64,
+                                // literal_load rejects the pools on RV128
+                                #[cfg(feature = "rv128")]
+                                Xlen::Rv128 => unreachable!(),
+                            };
+                            self.program.sections[self.current]
+                                .relocations
+                                .push(Relocation {
+                                    offset: entry,
+                                    kind,
+                                    symbol: name.to_owned(),
+                                    addend,
+                                });
+                        }
+                    }
+                    entries.push((value, entry));
+                    entry
+                }
+            };
+            if self.pool_offsets.len() <= load {
+                self.pool_offsets.resize(load + 1, 0);
+            }
+            self.pool_offsets[load] = entry;
+        }
+        Ok(())
+    }
+
+    /// Assembles an instruction, resolving a label operand if it has one in the same section.
+    ///
+    /// During the first pass labels may not be defined yet, they are assembled as 0 since only the size matters.
+    fn instruction(
+        &mut self,
+        mnemonic: &str,
+        operands: &str,
+        offset: u64,
+        final_pass: bool,
+    ) -> Result<(Vec<u8>, Vec<Relocation>), String> {
+        let mut operands: Vec<String> = if operands.is_empty() {
+            Vec::new()
+        } else {
+            operands.split(',').map(|o| o.trim().to_owned()).collect()
+        };
+        if mnemonic == "li"
+            && let [dest, value] = &operands[..]
+            && let Some(value) = value.strip_prefix('=')
+        {
+            return Ok((self.literal_load(dest, value.trim(), offset)?, Vec::new()));
+        }
+        if matches!(mnemonic, "call" | "tail" | "la")
+            && let Some(target) = operands.last()
+            && parse_value(target).is_err()
+        {
+            return self.pc_relative_symbol(mnemonic, &operands, offset, final_pass);
+        }
+        let mut relocation = None;
+        if let Some((index, kind)) = label_operand(mnemonic)
+            && let Some(operand) = operands.get(index)
+            && is_symbol(operand)
+        {
+            let name = operand.clone();
+            let target = self
+                .program
+                .symbols
+                .iter()
+                .find(|s| s.name == name && s.section == Some(self.current))
+                .map(|s| s.value);
+            operands[index] = match target {
+                Some(target) => (target.wrapping_sub(offset) as i64).to_string(),
+                None => {
+                    if final_pass {
+                        self.symbol(&name);
+                    }
+                    relocation = Some(Relocation {
+                        offset,
+                        kind,
+                        symbol: name,
+                        addend: 0,
+                    });
+                    "0".to_owned()
+                }
+            };
+        }
+        for operand in operands.iter_mut() {
+            if let Some(rest) = operand.strip_prefix('%') {
+                // %hi(symbol+addend), %lo(symbol+addend) and the like, optionally followed by a base register
+                let (operator, rest) = rest
+                    .split_once('(')
+                    .ok_or_else(|| format!("invalid operand {operand}"))?;
+                let kind = RelocationKind::from_operator(operator.trim())
+                    .ok_or_else(|| format!("unknown relocation operator %{operator}"))?;
+                let (expression, base) = rest
+                    .split_once(')')
+                    .ok_or_else(|| format!("missing ) in {operand}"))?;
+                let (name, addend) = symbol_offset(expression)?;
+                if final_pass {
+                    self.symbol(name);
+                }
+                relocation = Some(Relocation {
+                    offset,
+                    kind,
+                    symbol: name.to_owned(),
+                    addend,
+                });
+                // %tprel_add only marks the add of the thread pointer and is not an operand of the instruction
+                *operand = if kind == RelocationKind::TprelAdd {
+                    String::new()
+                } else {
+                    format!("0{base}")
+                };
+            } else if let Some((expression, base)) = operand.split_once('(')
+                && !expression.trim().is_empty()
+                && parse_value(expression).is_err()
+            {
+                // a symbolic offset from a base register, which is the offset of the symbol in its section
+                let (name, addend) = symbol_offset(expression)?;
+                let value = match self.program.symbol(name) {
+                    Some(symbol) if symbol.section.is_some() => symbol.value as i64,
+                    _ if final_pass => return Err(format!("{name} is not defined")),
+                    _ => 0,
+                };
+                *operand = format!("{}({base}", value.wrapping_add(addend));
+            }
+        }
+        if let Some(index) = operands.iter().position(String::is_empty) {
+            if mnemonic != "add" || index != 3 || operands.len() != 4 {
+                return Err(format!(
+                    "%tprel_add can only be the fourth operand of add, not of {mnemonic}"
+                ));
+            }
+            operands.pop();
+        }
+        let line = format!("{mnemonic} {}", operands.join(","));
+        let bytes: Vec<u8> = assemble_line_expanded_with(line.trim_end(), &self.config)?
+            .iter()
+            .flat_map(|inst| inst.encode_bytes())
+            .collect();
+        if let Some(relocation) = &mut relocation
+            && relocation.kind.is_operator()
+        {
+            if bytes.len() == 2 {
+                return Err(format!(
+                    "{mnemonic} is compressed and can not take a relocation operator"
+                ));
+            }
+            // STORE and STORE-FP
+            if matches!(bytes[0] & 0x7f, 0x23 | 0x27) {
+                relocation.kind = relocation.kind.store();
+            }
+        }
+        Ok((bytes, relocation.into_iter().collect()))
+    }
+
+    /// Assembles call, tail or la with a symbol as the target, optionally with an addend.
+    ///
+    /// A label in the same section is resolved to its offset. Other symbols are left to the linker, as an
+    /// `R_RISCV_CALL_PLT` relocation of the auipc and jalr of call and tail, or as an `R_RISCV_PCREL_HI20` relocation
+    /// of the auipc of la and an `R_RISCV_PCREL_LO12_I` relocation of its addi, which refers to a `.Lpcrel_hi` label
+    /// placed on the auipc.
+    fn pc_relative_symbol(
+        &mut self,
+        mnemonic: &str,
+        operands: &[String],
+        offset: u64,
+        final_pass: bool,
+    ) -> Result<(Vec<u8>, Vec<Relocation>), String> {
+        let (name, addend) = symbol_offset(&operands[operands.len() - 1])?;
+        let target = self
+            .program
+            .symbols
+            .iter()
+            .find(|s| s.name == name && s.section == Some(self.current))
+            .map(|s| s.value);
+        let distance = match target {
+            Some(target) => (target.wrapping_sub(offset) as i64).wrapping_add(addend),
+            None => 0,
+        };
+        let mut operands = operands.to_vec();
+        *operands.last_mut().unwrap() = distance.to_string();
+        let bytes: Vec<u8> = assemble_line_expanded_with(
+            &format!("{mnemonic} {}", operands.join(",")),
+            &self.config,
+        )?
+        .iter()
+        .flat_map(|inst| inst.encode_bytes())
+        .collect();
+        // the first pass only needs the size, and may not know the label yet
+        if target.is_some() || !final_pass {
+            return Ok((bytes, Vec::new()));
+        }
+        self.symbol(name);
+        let relocation = |offset, kind, symbol: &str, addend| Relocation {
+            offset,
+            kind,
+            symbol: symbol.to_owned(),
+            addend,
+        };
+        if mnemonic != "la" {
+            return Ok((
+                bytes,
+                vec![relocation(offset, RelocationKind::CallPlt, name, addend)],
+            ));
+        }
+        let label = format!(".Lpcrel_hi{}", self.pcrel_labels);
+        self.pcrel_labels += 1;
+        let current = self.current;
+        let symbol = self.symbol(&label);
+        symbol.section = Some(current);
+        symbol.value = offset;
+        Ok((
+            bytes,
+            vec![
+                relocation(offset, RelocationKind::PcrelHi20, name, addend),
+                relocation(offset + 4, RelocationKind::PcrelLo12I, &label, 0),
+            ],
+        ))
+    }
+
+    /// Assembles the program. The first pass finds the offsets of labels and the second emits the program.
+    fn pass(&mut self, lines: &[&str], final_pass: bool) -> Result<(), String> {
+        self.program.sections = vec![ProgramSection::new(".text")];
+        self.current = 0;
+        self.config = *self.initial;
+        self.pushed.clear();
+        self.program.xlen = self.initial.xlen;
+        self.literals.clear();
+        self.loads = 0;
+        self.pcrel_labels = 0;
+        for (number, line) in lines.iter().enumerate() {
+            let error = |e: String| format!("line {}: {e}", number + 1);
+            let (labels, statement) = split_labels(strip_comment(line));
+            for label in labels {
+                let offset = self.program.sections[self.current].data.len() as u64;
+                let current = self.current;
+                let symbol = self.symbol(label);
+                if !final_pass && symbol.section.is_some() {
+                    return Err(error(format!("{label} is already defined")));
+                }
+                symbol.section = Some(current);
+                symbol.value = offset;
+            }
+            if statement.is_empty() {
+                continue;
+            }
+            if let Some(offset) = statement
+                .strip_prefix('.')
+                .and_then(|s| s.trim_start().strip_prefix('='))
+            {
+                self.org(offset).map_err(error)?;
+                continue;
+            }
+            let (mnemonic, operands) = split_statement(statement);
+            let offset = self.program.sections[self.current].data.len() as u64;
+            match mnemonic {
+                ".globl" | ".global" => self.bind(operands, Binding::Global).map_err(error)?,
+                ".local" => self.bind(operands, Binding::Local).map_err(error)?,
+                ".weak" => self.bind(operands, Binding::Weak).map_err(error)?,
+                ".text" | ".data" | ".rodata" | ".bss" => {
+                    self.switch(ProgramSection::new(mnemonic));
+                }
+                ".section" => self.switch(parse_section(operands).map_err(error)?),
+                ".org" => self.org(operands).map_err(error)?,
+                ".attribute" => self.attribute(operands).map_err(error)?,
+                ".option" => self.option(operands).map_err(error)?,
+                ".pool" | ".ltorg" => self.pool(final_pass).map_err(error)?,
+                directive if directive.starts_with('.') => {
+                    let section = &mut self.program.sections[self.current];
+                    match data(directive, operands) {
+                        Ok(Some(bytes)) if section.nobits && bytes.iter().any(|b| *b != 0) => {
+                            return Err(error(format!(
+                                "{} can only hold zeros, use .zero",
+                                section.name
+                            )));
+                        }
+                        Ok(Some(bytes)) => {
+                            section.align = section.align.max(data_alignment(directive));
+                            section.data.extend(bytes);
+                        }
+                        Ok(None) => return Err(error(format!("unknown directive {directive}"))),
+                        Err(e) => return Err(error(e)),
+                    }
+                }
+                _ => {
+                    if self.program.sections[self.current].nobits {
+                        return Err(error(format!(
+                            "{} can not hold instructions",
+                            self.program.sections[self.current].name
+                        )));
+                    }
+                    let (bytes, relocation) = self
+                        .instruction(mnemonic, operands, offset, final_pass)
+                        .map_err(error)?;
+                    let section = &mut self.program.sections[self.current];
+                    section.compressed |= bytes.len() == 2;
+                    section.data.extend(bytes);
+                    section.relocations.extend(relocation);
+                }
+            }
+        }
+        // the literals that are left go at the end of their sections
+        while let Some((section, ..)) = self.literals.first() {
+            self.current = *section;
+            self.pool(final_pass).map_err(|e| {
+                format!(
+                    "literal pool of {}: {e}",
+                    self.program.sections[self.current].name
+                )
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Continues assembling into a section, which keeps its contents and flags if it was used before.
+    fn switch(&mut self, section: ProgramSection) {
+        let sections = &mut self.program.sections;
+        self.current = match sections.iter().position(|s| s.name == section.name) {
+            Some(index) => index,
+            None => {
+                sections.push(section);
+                sections.len() - 1
+            }
+        };
+    }
+}
+
+/// Assembles a program.
+///
+/// Symbols that are referenced but not defined become undefined global symbols, like in the GNU assembler.
+pub fn assemble_program(source: &str, config: &AssemblerConfig) -> Result<Program, String> {
+    let lines: Vec<&str> = source.lines().collect();
+    let mut assembler = Assembler {
+        initial: config,
+        config: *config,
+        pushed: Vec::new(),
+        program: Program {
+            xlen: config.xlen,
+            sections: Vec::new(),
+            symbols: Vec::new(),
+        },
+        current: 0,
+        literals: Vec::new(),
+        loads: 0,
+        pool_offsets: Vec::new(),
+        pcrel_labels: 0,
+    };
+    assembler.pass(&lines, false)?;
+    assembler.pass(&lines, true)?;
+    for symbol in &mut assembler.program.symbols {
+        if symbol.section.is_none() && symbol.binding == Binding::Local {
+            symbol.binding = Binding::Global;
+        }
+    }
+    Ok(assembler.program)
+}
+
+const SHT_PROGBITS: u32 = 1;
+const SHT_SYMTAB: u32 = 2;
+const SHT_STRTAB: u32 = 3;
+const SHT_RELA: u32 = 4;
+const SHT_NOBITS: u32 = 8;
+const SHF_WRITE: u64 = 0x1;
+const SHF_ALLOC: u64 = 0x2;
+const SHF_EXECINSTR: u64 = 0x4;
+const SHF_INFO_LINK: u64 = 0x40;
+const EM_RISCV: u16 = 243;
+/// The `e_flags` bit marking that compressed instructions are used.
+const EF_RISCV_RVC: u32 = 0x1;
+
+/// A section header of an ELF file being written.
+struct SectionHeader {
+    name: u32,
+    kind: u32,
+    flags: u64,
+    offset: u64,
+    size: u64,
+    link: u32,
+    info: u32,
+    align: u64,
+    entry_size: u64,
+}
+
+/// Adds a string to a string table, returning its offset.
+fn add_string(table: &mut Vec<u8>, string: &str) -> u32 {
+    let offset = table.len() as u32;
+    table.extend(string.as_bytes());
+    table.push(0);
+    offset
+}
+
+impl Program {
+    /// Writes the program as a little endian relocatable ELF file, ELF32 for RV32 and ELF64 otherwise.
+    ///
+    /// Local symbols come before global and weak symbols in the symbol table as the format requires.
+    pub fn to_elf(&self) -> Vec<u8> {
+        let is_64 = self.xlen != Xlen::Rv32;
+        let word = if is_64 { 8 } else { 4 };
+        let uint = |out: &mut Vec<u8>, value: u64| out.extend(&value.to_le_bytes()[..word]);
+
+        // sections are laid out as null, the program's sections, their relocations, then the tables
+        let mut order: Vec<usize> = (0..self.symbols.len()).collect();
+        order.sort_by_key(|i| self.symbols[*i].binding != Binding::Local);
+        let first_global = order
+            .iter()
+            .position(|i| self.symbols[*i].binding != Binding::Local)
+            .unwrap_or(order.len())
+            + 1;
+        let mut strings = vec![0];
+        let mut symtab = vec![0; if is_64 { 24 } else { 16 }];
+        for &i in &order {
+            let symbol = &self.symbols[i];
+            let name = add_string(&mut strings, &symbol.name);
+            let binding = match symbol.binding {
+                Binding::Local => 0,
+                Binding::Global => 1,
+                Binding::Weak => 2,
+            };
+            let index = symbol.section.map_or(0, |s| s as u16 + 1);
+            symtab.extend(name.to_le_bytes());
+            if is_64 {
+                symtab.extend([binding << 4, 0]);
+                symtab.extend(index.to_le_bytes());
+                uint(&mut symtab, symbol.value);
+                uint(&mut symtab, 0);
+            } else {
+                uint(&mut symtab, symbol.value);
+                uint(&mut symtab, 0);
+                symtab.extend([binding << 4, 0]);
+                symtab.extend(index.to_le_bytes());
+            }
+        }
+        let symbol_index = |name: &str| {
+            order
+                .iter()
+                .position(|i| self.symbols[*i].name == name)
+                .map_or(0, |i| i as u64 + 1)
+        };
+
+        let mut names = vec![0];
+        let mut contents: Vec<Vec<u8>> = Vec::new();
+        let mut headers: Vec<SectionHeader> = Vec::new();
+        let relocated: Vec<usize> = (0..self.sections.len())
+            .filter(|i| !self.sections[*i].relocations.is_empty())
+            .collect();
+        let symtab_index = (1 + self.sections.len() + relocated.len()) as u32;
+        for section in &self.sections {
+            let flag = |set: bool, flag: u64| if set { flag } else { 0 };
+            headers.push(SectionHeader {
+                name: add_string(&mut names, &section.name),
+                kind: if section.nobits {
+                    SHT_NOBITS
+                } else {
+                    SHT_PROGBITS
+                },
+                flags: flag(section.allocated, SHF_ALLOC)
+                    | flag(section.writable, SHF_WRITE)
+                    | flag(section.executable, SHF_EXECINSTR),
+                offset: 0,
+                size: section.data.len() as u64,
+                link: 0,
+                info: 0,
+                align: section.align.max(if section.executable { 2 } else { 1 }),
+                entry_size: 0,
+            });
+            contents.push(if section.nobits {
+                Vec::new()
+            } else {
+                section.data.clone()
+            });
+        }
+        for &i in &relocated {
+            let section = &self.sections[i];
+            let mut rela = Vec::new();
+            for relocation in &section.relocations {
+                let symbol = symbol_index(&relocation.symbol);
+                let kind = relocation.kind.elf_type() as u64;
+                uint(&mut rela, relocation.offset);
+                uint(
+                    &mut rela,
+                    if is_64 {
+                        symbol << 32 | kind
+                    } else {
+                        symbol << 8 | kind
+                    },
+                );
+                uint(&mut rela, relocation.addend as u64);
+            }
+            headers.push(SectionHeader {
+                name: add_string(&mut names, &format!(".rela{}", section.name)),
+                kind: SHT_RELA,
+                flags: SHF_INFO_LINK,
+                offset: 0,
+                size: rela.len() as u64,
+                link: symtab_index,
+                info: i as u32 + 1,
+                align: word as u64,
+                entry_size: 3 * word as u64,
+            });
+            contents.push(rela);
+        }
+        headers.push(SectionHeader {
+            name: add_string(&mut names, ".symtab"),
+            kind: SHT_SYMTAB,
+            flags: 0,
+            offset: 0,
+            size: symtab.len() as u64,
+            link: symtab_index + 1,
+            info: first_global as u32,
+            align: word as u64,
+            entry_size: if is_64 { 24 } else { 16 },
+        });
+        contents.push(symtab);
+        headers.push(SectionHeader {
+            name: add_string(&mut names, ".strtab"),
+            kind: SHT_STRTAB,
+            flags: 0,
+            offset: 0,
+            size: strings.len() as u64,
+            link: 0,
+            info: 0,
+            align: 1,
+            entry_size: 0,
+        });
+        contents.push(strings);
+        let names_name = add_string(&mut names, ".shstrtab");
+        headers.push(SectionHeader {
+            name: names_name,
+            kind: SHT_STRTAB,
+            flags: 0,
+            offset: 0,
+            size: names.len() as u64,
+            link: 0,
+            info: 0,
+            align: 1,
+            entry_size: 0,
+        });
+        contents.push(names);
+
+        let header_size = if is_64 { 64 } else { 52 };
+        let mut out = vec![0; header_size];
+        for (header, data) in headers.iter_mut().zip(&contents) {
+            while !out.len().is_multiple_of(header.align.max(1) as usize) {
+                out.push(0);
+            }
+            header.offset = out.len() as u64;
+            out.extend(data);
+        }
+        while !out.len().is_multiple_of(word) {
+            out.push(0);
+        }
+        let section_headers = out.len() as u64;
+        out.extend(vec![0; if is_64 { 64 } else { 40 }]);
+        for header in &headers {
+            out.extend(header.name.to_le_bytes());
+            out.extend(header.kind.to_le_bytes());
+            uint(&mut out, header.flags);
+            uint(&mut out, 0);
+            uint(&mut out, header.offset);
+            uint(&mut out, header.size);
+            out.extend(header.link.to_le_bytes());
+            out.extend(header.info.to_le_bytes());
+            uint(&mut out, header.align);
+            uint(&mut out, header.entry_size);
+        }
+
+        let mut header = Vec::new();
+        header.extend(b"\x7fELF");
+        header.extend([if is_64 { 2 } else { 1 }, 1, 1, 0]);
+        header.extend([0; 8]);
+        // a relocatable file
+        header.extend(1u16.to_le_bytes());
+        header.extend(EM_RISCV.to_le_bytes());
+        header.extend(1u32.to_le_bytes());
+        // entry and program header offset
+        uint(&mut header, 0);
+        uint(&mut header, 0);
+        uint(&mut header, section_headers);
+        let compressed = self.sections.iter().any(|s| s.compressed);
+        header.extend((if compressed { EF_RISCV_RVC } else { 0 }).to_le_bytes());
+        header.extend((header_size as u16).to_le_bytes());
+        header.extend([0; 4]);
+        header.extend((if is_64 { 64u16 } else { 40 }).to_le_bytes());
+        header.extend((headers.len() as u16 + 1).to_le_bytes());
+        header.extend((headers.len() as u16).to_le_bytes());
+        out[..header_size].copy_from_slice(&header);
+        out
+    }
+}
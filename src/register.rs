@@ -1,6 +1,37 @@
+use crate::config::{FormatOptions, RegisterStyle, Xlen};
 use std::fmt::{Display, Formatter};
+use std::ops::RangeInclusive;
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+/// The register file that a register belongs to.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum RegisterKind {
+    Integer,
+    Float,
+    Vector,
+}
+
+/// Operations shared by every kind of register.
+///
+/// This allows generic code to work with any register without matching on its type.
+pub trait RegisterClass: Copy + Display {
+    /// The ABI name of the register.
+    fn name(self) -> &'static str;
+
+    /// The architectural number of the register (x0-x31, f0-f31 or v0-v31), also for compressed registers.
+    fn number(self) -> u8;
+
+    /// The register file the register belongs to.
+    fn kind(self) -> RegisterKind;
+}
+
+/// Displays a register using a `RegisterStyle`, see `IRegister::display_with` and `FRegister::display_with`.
+pub struct RegisterDisplay<T> {
+    register: T,
+    style: RegisterStyle,
+    zfinx: bool,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub enum IRegister {
     Zero = 0,
     ReturnAddress = 1,
@@ -37,89 +68,177 @@ pub enum IRegister {
     T6 = 31,
 }
 
+impl RegisterClass for IRegister {
+    fn name(self) -> &'static str {
+        match self {
+            IRegister::Zero => "zero",
+            IRegister::ReturnAddress => "ra",
+            IRegister::StackPointer => "sp",
+            IRegister::GlobalPointer => "gp",
+            IRegister::ThreadPointer => "tp",
+            IRegister::T0 => "t0",
+            IRegister::T1 => "t1",
+            IRegister::T2 => "t2",
+            IRegister::FramePointer => "s0",
+            IRegister::S1 => "s1",
+            IRegister::A0 => "a0",
+            IRegister::A1 => "a1",
+            IRegister::A2 => "a2",
+            IRegister::A3 => "a3",
+            IRegister::A4 => "a4",
+            IRegister::A5 => "a5",
+            IRegister::A6 => "a6",
+            IRegister::A7 => "a7",
+            IRegister::S2 => "s2",
+            IRegister::S3 => "s3",
+            IRegister::S4 => "s4",
+            IRegister::S5 => "s5",
+            IRegister::S6 => "s6",
+            IRegister::S7 => "s7",
+            IRegister::S8 => "s8",
+            IRegister::S9 => "s9",
+            IRegister::S10 => "s10",
+            IRegister::S11 => "s11",
+            IRegister::T3 => "t3",
+            IRegister::T4 => "t4",
+            IRegister::T5 => "t5",
+            IRegister::T6 => "t6",
+        }
+    }
+
+    fn number(self) -> u8 {
+        self as u8
+    }
+
+    fn kind(self) -> RegisterKind {
+        RegisterKind::Integer
+    }
+}
+
 impl Display for IRegister {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(
-            f,
-            "{}",
-            match self {
-                IRegister::Zero => "zero",
-                IRegister::ReturnAddress => "ra",
-                IRegister::StackPointer => "sp",
-                IRegister::GlobalPointer => "gp",
-                IRegister::ThreadPointer => "tp",
-                IRegister::T0 => "t0",
-                IRegister::T1 => "t1",
-                IRegister::T2 => "t2",
-                IRegister::FramePointer => "s0",
-                IRegister::S1 => "s1",
-                IRegister::A0 => "a0",
-                IRegister::A1 => "a1",
-                IRegister::A2 => "a2",
-                IRegister::A3 => "a3",
-                IRegister::A4 => "a4",
-                IRegister::A5 => "a5",
-                IRegister::A6 => "a6",
-                IRegister::A7 => "a7",
-                IRegister::S2 => "s2",
-                IRegister::S3 => "s3",
-                IRegister::S4 => "s4",
-                IRegister::S5 => "s5",
-                IRegister::S6 => "s6",
-                IRegister::S7 => "s7",
-                IRegister::S8 => "s8",
-                IRegister::S9 => "s9",
-                IRegister::S10 => "s10",
-                IRegister::S11 => "s11",
-                IRegister::T3 => "t3",
-                IRegister::T4 => "t4",
-                IRegister::T5 => "t5",
-                IRegister::T6 => "t6",
+        f.write_str(self.name())
+    }
+}
+
+impl Display for RegisterDisplay<IRegister> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self.style {
+            RegisterStyle::AbiFramePointer if self.register == IRegister::FramePointer => {
+                write!(f, "fp")
             }
-        )
+            RegisterStyle::Abi | RegisterStyle::AbiFramePointer => write!(f, "{}", self.register),
+            RegisterStyle::Numeric => write!(f, "x{}", self.register.number()),
+        }
     }
 }
 
-impl Into<u32> for IRegister {
-    fn into(self) -> u32 {
-        match self {
-            Self::Zero => 0,
-            Self::ReturnAddress => 1,
-            Self::StackPointer => 2,
-            Self::GlobalPointer => 3,
-            Self::ThreadPointer => 4,
-            Self::T0 => 5,
-            Self::T1 => 6,
-            Self::T2 => 7,
-            Self::FramePointer => 8,
-            Self::S1 => 9,
-            Self::A0 => 10,
-            Self::A1 => 11,
-            Self::A2 => 12,
-            Self::A3 => 13,
-            Self::A4 => 14,
-            Self::A5 => 15,
-            Self::A6 => 16,
-            Self::A7 => 17,
-            Self::S2 => 18,
-            Self::S3 => 19,
-            Self::S4 => 20,
-            Self::S5 => 21,
-            Self::S6 => 22,
-            Self::S7 => 23,
-            Self::S8 => 24,
-            Self::S9 => 25,
-            Self::S10 => 26,
-            Self::S11 => 27,
-            Self::T3 => 28,
-            Self::T4 => 29,
-            Self::T5 => 30,
-            Self::T6 => 31,
+impl From<IRegister> for u32 {
+    fn from(value: IRegister) -> Self {
+        match value {
+            IRegister::Zero => 0,
+            IRegister::ReturnAddress => 1,
+            IRegister::StackPointer => 2,
+            IRegister::GlobalPointer => 3,
+            IRegister::ThreadPointer => 4,
+            IRegister::T0 => 5,
+            IRegister::T1 => 6,
+            IRegister::T2 => 7,
+            IRegister::FramePointer => 8,
+            IRegister::S1 => 9,
+            IRegister::A0 => 10,
+            IRegister::A1 => 11,
+            IRegister::A2 => 12,
+            IRegister::A3 => 13,
+            IRegister::A4 => 14,
+            IRegister::A5 => 15,
+            IRegister::A6 => 16,
+            IRegister::A7 => 17,
+            IRegister::S2 => 18,
+            IRegister::S3 => 19,
+            IRegister::S4 => 20,
+            IRegister::S5 => 21,
+            IRegister::S6 => 22,
+            IRegister::S7 => 23,
+            IRegister::S8 => 24,
+            IRegister::S9 => 25,
+            IRegister::S10 => 26,
+            IRegister::S11 => 27,
+            IRegister::T3 => 28,
+            IRegister::T4 => 29,
+            IRegister::T5 => 30,
+            IRegister::T6 => 31,
+        }
+    }
+}
+
+impl TryFrom<u8> for IRegister {
+    type Error = String;
+
+    fn try_from(value: u8) -> Result<Self, Self::Error> {
+        if value < 32 {
+            Ok(IRegister::from_int(value as u32))
+        } else {
+            Err(format!(
+                "converted invalid integer to integer register {}",
+                value
+            ))
         }
     }
 }
 
 impl IRegister {
+    /// Every integer register, ordered by number.
+    pub const ALL: [IRegister; 32] = [
+        IRegister::Zero,
+        IRegister::ReturnAddress,
+        IRegister::StackPointer,
+        IRegister::GlobalPointer,
+        IRegister::ThreadPointer,
+        IRegister::T0,
+        IRegister::T1,
+        IRegister::T2,
+        IRegister::FramePointer,
+        IRegister::S1,
+        IRegister::A0,
+        IRegister::A1,
+        IRegister::A2,
+        IRegister::A3,
+        IRegister::A4,
+        IRegister::A5,
+        IRegister::A6,
+        IRegister::A7,
+        IRegister::S2,
+        IRegister::S3,
+        IRegister::S4,
+        IRegister::S5,
+        IRegister::S6,
+        IRegister::S7,
+        IRegister::S8,
+        IRegister::S9,
+        IRegister::S10,
+        IRegister::S11,
+        IRegister::T3,
+        IRegister::T4,
+        IRegister::T5,
+        IRegister::T6,
+    ];
+
+    /// Iterates over every integer register, ordered by number.
+    pub fn iter() -> impl Iterator<Item = IRegister> {
+        IRegister::ALL.into_iter()
+    }
+
+    /// Iterates over the registers of an inclusive range, such as `IRegister::A0..=IRegister::A7`.
+    pub fn range(range: RangeInclusive<IRegister>) -> impl Iterator<Item = IRegister> {
+        IRegister::iter().filter(move |register| range.contains(register))
+    }
+
+    /// The number of this register (x0-x31).
+    pub fn number(self) -> u8 {
+        RegisterClass::number(self)
+    }
+
     pub fn from_int(int: u32) -> Self {
         match int {
             0 => Self::Zero,
@@ -193,25 +312,43 @@ impl IRegister {
             "t4" => Ok(Self::T4),
             "t5" => Ok(Self::T5),
             "t6" => Ok(Self::T6),
-            x => Err(format!("converted invalid str to integer register {}", x)),
+            x => match x.strip_prefix('x') {
+                // numeric names (x0-x31)
+                Some(n) if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => {
+                    match n.parse::<u32>() {
+                        Ok(n) if n < 32 => Ok(IRegister::from_int(n)),
+                        _ => Err(format!("converted invalid str to integer register {}", x)),
+                    }
+                }
+                _ => Err(format!("converted invalid str to integer register {}", x)),
+            },
+        }
+    }
+
+    /// Returns a value that displays this register in the given style.
+    pub fn display_with(self, style: RegisterStyle) -> RegisterDisplay<IRegister> {
+        RegisterDisplay {
+            register: self,
+            style,
+            zfinx: false,
         }
     }
 
     pub fn rd(self) -> u32 {
         let v: u32 = self.into();
-        return v << 7;
+        v << 7
     }
     pub fn rs1(self) -> u32 {
         let v: u32 = self.into();
-        return v << 15;
+        v << 15
     }
     pub fn rs2(self) -> u32 {
         let v: u32 = self.into();
-        return v << 20;
+        v << 20
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub enum FRegister {
     FT0 = 0,
     FT1 = 1,
@@ -247,46 +384,70 @@ pub enum FRegister {
     FT11 = 31,
 }
 
+impl RegisterClass for FRegister {
+    fn name(self) -> &'static str {
+        match self {
+            FRegister::FT0 => "ft0",
+            FRegister::FT1 => "ft1",
+            FRegister::FT2 => "ft2",
+            FRegister::FT3 => "ft3",
+            FRegister::FT4 => "ft4",
+            FRegister::FT5 => "ft5",
+            FRegister::FT6 => "ft6",
+            FRegister::FT7 => "ft7",
+            FRegister::FS0 => "fs0",
+            FRegister::FS1 => "fs1",
+            FRegister::FA0 => "fa0",
+            FRegister::FA1 => "fa1",
+            FRegister::FA2 => "fa2",
+            FRegister::FA3 => "fa3",
+            FRegister::FA4 => "fa4",
+            FRegister::FA5 => "fa5",
+            FRegister::FA6 => "fa6",
+            FRegister::FA7 => "fa7",
+            FRegister::FS2 => "fs2",
+            FRegister::FS3 => "fs3",
+            FRegister::FS4 => "fs4",
+            FRegister::FS5 => "fs5",
+            FRegister::FS6 => "fs6",
+            FRegister::FS7 => "fs7",
+            FRegister::FS8 => "fs8",
+            FRegister::FS9 => "fs9",
+            FRegister::FS10 => "fs10",
+            FRegister::FS11 => "fs11",
+            FRegister::FT8 => "ft8",
+            FRegister::FT9 => "ft9",
+            FRegister::FT10 => "ft10",
+            FRegister::FT11 => "ft11",
+        }
+    }
+
+    fn number(self) -> u8 {
+        u32::from(self) as u8
+    }
+
+    fn kind(self) -> RegisterKind {
+        RegisterKind::Float
+    }
+}
+
 impl Display for FRegister {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(
-            f,
-            "{}",
-            match self {
-                FRegister::FT0 => "ft0",
-                FRegister::FT1 => "ft1",
-                FRegister::FT2 => "ft2",
-                FRegister::FT3 => "ft3",
-                FRegister::FT4 => "ft4",
-                FRegister::FT5 => "ft5",
-                FRegister::FT6 => "ft6",
-                FRegister::FT7 => "ft7",
-                FRegister::FS0 => "fs0",
-                FRegister::FS1 => "fs1",
-                FRegister::FA0 => "fa0",
-                FRegister::FA1 => "fa1",
-                FRegister::FA2 => "fa2",
-                FRegister::FA3 => "fa3",
-                FRegister::FA4 => "fa4",
-                FRegister::FA5 => "fa5",
-                FRegister::FA6 => "fa6",
-                FRegister::FA7 => "fa7",
-                FRegister::FS2 => "fs2",
-                FRegister::FS3 => "fs3",
-                FRegister::FS4 => "fs4",
-                FRegister::FS5 => "fs5",
-                FRegister::FS6 => "fs6",
-                FRegister::FS7 => "fs7",
-                FRegister::FS8 => "fs8",
-                FRegister::FS9 => "fs9",
-                FRegister::FS10 => "fs10",
-                FRegister::FS11 => "fs11",
-                FRegister::FT8 => "ft8",
-                FRegister::FT9 => "ft9",
-                FRegister::FT10 => "ft10",
-                FRegister::FT11 => "ft11",
-            }
-        )
+        f.write_str(self.name())
+    }
+}
+
+impl Display for RegisterDisplay<FRegister> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        if self.zfinx {
+            return IRegister::from_int(self.register.number().into())
+                .display_with(self.style)
+                .fmt(f);
+        }
+        match self.style {
+            RegisterStyle::Abi | RegisterStyle::AbiFramePointer => write!(f, "{}", self.register),
+            RegisterStyle::Numeric => write!(f, "f{}", self.register.number()),
+        }
     }
 }
 
@@ -369,14 +530,21 @@ impl TryFrom<&str> for FRegister {
             "ft9" => Ok(Self::FT9),
             "ft10" => Ok(Self::FT10),
             "ft11" => Ok(Self::FT11),
-            x => Err(format!("converted invalid str to float register {}", x)),
+            x => match x.strip_prefix('f') {
+                // numeric names (f0-f31)
+                Some(n) if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => {
+                    FRegister::try_from(n.parse::<u32>().unwrap_or(u32::MAX))
+                        .map_err(|_| format!("converted invalid str to float register {}", x))
+                }
+                _ => Err(format!("converted invalid str to float register {}", x)),
+            },
         }
     }
 }
 
-impl Into<u32> for FRegister {
-    fn into(self) -> u32 {
-        match self {
+impl From<FRegister> for u32 {
+    fn from(value: FRegister) -> Self {
+        match value {
             FRegister::FT0 => 0,
             FRegister::FT1 => 1,
             FRegister::FT2 => 2,
@@ -414,26 +582,237 @@ impl Into<u32> for FRegister {
 }
 
 impl FRegister {
+    /// Iterates over every float register, ordered by number.
+    pub fn iter() -> impl Iterator<Item = FRegister> {
+        (0..32).map(|number| FRegister::try_from(number).unwrap())
+    }
+
+    /// Iterates over the registers of an inclusive range, such as `FRegister::FA0..=FRegister::FA7`.
+    pub fn range(range: RangeInclusive<FRegister>) -> impl Iterator<Item = FRegister> {
+        FRegister::iter().filter(move |register| range.contains(register))
+    }
+
+    /// Returns a value that displays this register in the given style.
+    pub fn display_with(self, style: RegisterStyle) -> RegisterDisplay<FRegister> {
+        RegisterDisplay {
+            register: self,
+            style,
+            zfinx: false,
+        }
+    }
+
+    /// Returns a value that displays this register with the given options, as the integer register of the same
+    /// number when `options.zfinx` is set.
+    pub fn display_with_options(self, options: &FormatOptions) -> RegisterDisplay<FRegister> {
+        RegisterDisplay {
+            register: self,
+            style: options.register_style,
+            zfinx: options.zfinx,
+        }
+    }
+
     pub fn rd(self) -> u32 {
         let v: u32 = self.into();
-        return v << 7;
+        v << 7
     }
     pub fn rs1(self) -> u32 {
         let v: u32 = self.into();
-        return v << 15;
+        v << 15
     }
     pub fn rs2(self) -> u32 {
         let v: u32 = self.into();
-        return v << 20;
+        v << 20
     }
     pub fn rs3(self) -> u32 {
         let v: u32 = self.into();
-        return v << 27;
+        v << 27
+    }
+}
+
+/// A vector register, which holds a register group of several registers when LMUL is greater than 1.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum VRegister {
+    V0 = 0,
+    V1 = 1,
+    V2 = 2,
+    V3 = 3,
+    V4 = 4,
+    V5 = 5,
+    V6 = 6,
+    V7 = 7,
+    V8 = 8,
+    V9 = 9,
+    V10 = 10,
+    V11 = 11,
+    V12 = 12,
+    V13 = 13,
+    V14 = 14,
+    V15 = 15,
+    V16 = 16,
+    V17 = 17,
+    V18 = 18,
+    V19 = 19,
+    V20 = 20,
+    V21 = 21,
+    V22 = 22,
+    V23 = 23,
+    V24 = 24,
+    V25 = 25,
+    V26 = 26,
+    V27 = 27,
+    V28 = 28,
+    V29 = 29,
+    V30 = 30,
+    V31 = 31,
+}
+
+impl RegisterClass for VRegister {
+    fn name(self) -> &'static str {
+        match self {
+            VRegister::V0 => "v0",
+            VRegister::V1 => "v1",
+            VRegister::V2 => "v2",
+            VRegister::V3 => "v3",
+            VRegister::V4 => "v4",
+            VRegister::V5 => "v5",
+            VRegister::V6 => "v6",
+            VRegister::V7 => "v7",
+            VRegister::V8 => "v8",
+            VRegister::V9 => "v9",
+            VRegister::V10 => "v10",
+            VRegister::V11 => "v11",
+            VRegister::V12 => "v12",
+            VRegister::V13 => "v13",
+            VRegister::V14 => "v14",
+            VRegister::V15 => "v15",
+            VRegister::V16 => "v16",
+            VRegister::V17 => "v17",
+            VRegister::V18 => "v18",
+            VRegister::V19 => "v19",
+            VRegister::V20 => "v20",
+            VRegister::V21 => "v21",
+            VRegister::V22 => "v22",
+            VRegister::V23 => "v23",
+            VRegister::V24 => "v24",
+            VRegister::V25 => "v25",
+            VRegister::V26 => "v26",
+            VRegister::V27 => "v27",
+            VRegister::V28 => "v28",
+            VRegister::V29 => "v29",
+            VRegister::V30 => "v30",
+            VRegister::V31 => "v31",
+        }
+    }
+
+    fn number(self) -> u8 {
+        self as u8
+    }
+
+    fn kind(self) -> RegisterKind {
+        RegisterKind::Vector
+    }
+}
+
+impl Display for VRegister {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.write_str(self.name())
+    }
+}
+
+impl TryFrom<u32> for VRegister {
+    type Error = String;
+
+    fn try_from(value: u32) -> Result<Self, Self::Error> {
+        VRegister::ALL
+            .get(value as usize)
+            .copied()
+            .ok_or_else(|| format!("converted invalid integer to vector register {value}"))
+    }
+}
+
+impl TryFrom<&str> for VRegister {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.strip_prefix('v') {
+            Some(n) if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) => {
+                VRegister::try_from(n.parse::<u32>().unwrap_or(u32::MAX))
+                    .map_err(|_| format!("converted invalid str to vector register {value}"))
+            }
+            _ => Err(format!("converted invalid str to vector register {value}")),
+        }
+    }
+}
+
+impl From<VRegister> for u32 {
+    fn from(value: VRegister) -> Self {
+        value as u32
+    }
+}
+
+impl VRegister {
+    /// Every vector register, ordered by number.
+    pub const ALL: [VRegister; 32] = [
+        VRegister::V0,
+        VRegister::V1,
+        VRegister::V2,
+        VRegister::V3,
+        VRegister::V4,
+        VRegister::V5,
+        VRegister::V6,
+        VRegister::V7,
+        VRegister::V8,
+        VRegister::V9,
+        VRegister::V10,
+        VRegister::V11,
+        VRegister::V12,
+        VRegister::V13,
+        VRegister::V14,
+        VRegister::V15,
+        VRegister::V16,
+        VRegister::V17,
+        VRegister::V18,
+        VRegister::V19,
+        VRegister::V20,
+        VRegister::V21,
+        VRegister::V22,
+        VRegister::V23,
+        VRegister::V24,
+        VRegister::V25,
+        VRegister::V26,
+        VRegister::V27,
+        VRegister::V28,
+        VRegister::V29,
+        VRegister::V30,
+        VRegister::V31,
+    ];
+
+    /// Iterates over every vector register, ordered by number.
+    pub fn iter() -> impl Iterator<Item = VRegister> {
+        VRegister::ALL.into_iter()
+    }
+
+    /// Iterates over the registers of an inclusive range, such as `VRegister::V8..=VRegister::V15`.
+    pub fn range(range: RangeInclusive<VRegister>) -> impl Iterator<Item = VRegister> {
+        VRegister::iter().filter(move |register| range.contains(register))
+    }
+
+    pub fn rd(self) -> u32 {
+        let v: u32 = self.into();
+        v << 7
+    }
+    pub fn rs1(self) -> u32 {
+        let v: u32 = self.into();
+        v << 15
+    }
+    pub fn rs2(self) -> u32 {
+        let v: u32 = self.into();
+        v << 20
     }
 }
 
 /// One of the limited set of registers available in compressed instructions
-#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub enum CIRegister {
     FramePointer,
     S1,
@@ -478,10 +857,14 @@ impl TryFrom<&str> for CIRegister {
             "a3" => Ok(Self::A3),
             "a4" => Ok(Self::A4),
             "a5" => Ok(Self::A5),
-            x => Err(format!(
-                "converted invalid str to integer register in compressed instruction: {}",
-                x
-            )),
+            x => match IRegister::from_string(x).map(u32::from) {
+                // numeric names (x8-x15)
+                Ok(n) if (8..16).contains(&n) => Ok(CIRegister::from((n - 8) as u16)),
+                _ => Err(format!(
+                    "converted invalid str to integer register in compressed instruction: {}",
+                    x
+                )),
+            },
         }
     }
 }
@@ -501,34 +884,64 @@ impl CIRegister {
     }
 
     pub fn rs2(&self) -> u16 {
-        return (*self as u16) << 2;
+        (*self as u16) << 2
     }
 
     pub fn rs1(&self) -> u16 {
-        return (*self as u16) << 7;
+        (*self as u16) << 7
+    }
+}
+
+impl From<CIRegister> for IRegister {
+    fn from(value: CIRegister) -> Self {
+        value.expand()
+    }
+}
+
+impl TryFrom<IRegister> for CIRegister {
+    type Error = String;
+
+    fn try_from(value: IRegister) -> Result<Self, Self::Error> {
+        match u32::from(value) {
+            n @ 8..=15 => Ok(CIRegister::from((n - 8) as u16)),
+            _ => Err(format!(
+                "integer register {value} is not available in compressed instructions"
+            )),
+        }
+    }
+}
+
+impl RegisterClass for CIRegister {
+    fn name(self) -> &'static str {
+        match self {
+            CIRegister::FramePointer => "s0",
+            CIRegister::S1 => "s1",
+            CIRegister::A0 => "a0",
+            CIRegister::A1 => "a1",
+            CIRegister::A2 => "a2",
+            CIRegister::A3 => "a3",
+            CIRegister::A4 => "a4",
+            CIRegister::A5 => "a5",
+        }
+    }
+
+    fn number(self) -> u8 {
+        self as u8 + 8
+    }
+
+    fn kind(self) -> RegisterKind {
+        RegisterKind::Integer
     }
 }
 
 impl Display for CIRegister {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(
-            f,
-            "{}",
-            match self {
-                CIRegister::FramePointer => "s0",
-                CIRegister::S1 => "s1",
-                CIRegister::A0 => "a0",
-                CIRegister::A1 => "a1",
-                CIRegister::A2 => "a2",
-                CIRegister::A3 => "a3",
-                CIRegister::A4 => "a4",
-                CIRegister::A5 => "a5",
-            }
-        )
+        f.write_str(self.name())
     }
 }
 
-#[derive(Debug, PartialEq, Clone, Copy)]
+/// One of the limited set of float registers (f8-f15) available in compressed instructions
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
 pub enum CFRegister {
     FS0,
     FS1,
@@ -574,10 +987,14 @@ impl TryFrom<&str> for CFRegister {
             "fa3" => Ok(Self::FA3),
             "fa4" => Ok(Self::FA4),
             "fa5" => Ok(Self::FA5),
-            x => Err(format!(
-                "converted invalid str to float register in compressed instruction {}",
-                x
-            )),
+            x => match FRegister::try_from(x).map(u32::from) {
+                // numeric names (f8-f15)
+                Ok(n) if (8..16).contains(&n) => CFRegister::try_from((n - 8) as u16),
+                _ => Err(format!(
+                    "converted invalid str to float register in compressed instruction {}",
+                    x
+                )),
+            },
         }
     }
 }
@@ -597,29 +1014,295 @@ impl CFRegister {
     }
 
     pub fn rs2(&self) -> u16 {
-        return (*self as u16) << 2;
+        (*self as u16) << 2
     }
 
     pub fn rs1(&self) -> u16 {
-        return (*self as u16) << 7;
+        (*self as u16) << 7
+    }
+}
+
+impl From<CFRegister> for FRegister {
+    fn from(value: CFRegister) -> Self {
+        value.expand()
+    }
+}
+
+impl TryFrom<FRegister> for CFRegister {
+    type Error = String;
+
+    fn try_from(value: FRegister) -> Result<Self, Self::Error> {
+        match u32::from(value) {
+            n @ 8..=15 => CFRegister::try_from((n - 8) as u16),
+            _ => Err(format!(
+                "float register {value} is not available in compressed instructions"
+            )),
+        }
+    }
+}
+
+impl RegisterClass for CFRegister {
+    fn name(self) -> &'static str {
+        match self {
+            CFRegister::FS0 => "fs0",
+            CFRegister::FS1 => "fs1",
+            CFRegister::FA0 => "fa0",
+            CFRegister::FA1 => "fa1",
+            CFRegister::FA2 => "fa2",
+            CFRegister::FA3 => "fa3",
+            CFRegister::FA4 => "fa4",
+            CFRegister::FA5 => "fa5",
+        }
+    }
+
+    fn number(self) -> u8 {
+        self as u8 + 8
+    }
+
+    fn kind(self) -> RegisterKind {
+        RegisterKind::Float
     }
 }
 
 impl Display for CFRegister {
     fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
-        write!(
-            f,
-            "{}",
-            match self {
-                CFRegister::FS0 => "fs0",
-                CFRegister::FS1 => "fs1",
-                CFRegister::FA0 => "fa0",
-                CFRegister::FA1 => "fa1",
-                CFRegister::FA2 => "fa2",
-                CFRegister::FA3 => "fa3",
-                CFRegister::FA4 => "fa4",
-                CFRegister::FA5 => "fa5",
+        f.write_str(self.name())
+    }
+}
+
+/// One of the saved registers (s0-s7) that cm.mvsa01 and cm.mva01s move to and from the argument registers
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum CSRegister {
+    S0,
+    S1,
+    S2,
+    S3,
+    S4,
+    S5,
+    S6,
+    S7,
+}
+
+impl From<u16> for CSRegister {
+    fn from(value: u16) -> Self {
+        match value {
+            0 => Self::S0,
+            1 => Self::S1,
+            2 => Self::S2,
+            3 => Self::S3,
+            4 => Self::S4,
+            5 => Self::S5,
+            6 => Self::S6,
+            7 => Self::S7,
+            x => panic!(
+                "converted invalid integer to saved register in compressed instruction: {}",
+                x
+            ),
+        }
+    }
+}
+
+impl TryFrom<&str> for CSRegister {
+    type Error = String;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        IRegister::from_string(value)
+            .and_then(CSRegister::try_from)
+            .map_err(|_| {
+                format!(
+                    "converted invalid str to saved register in compressed instruction: {value}"
+                )
+            })
+    }
+}
+
+impl CSRegister {
+    pub fn expand(&self) -> IRegister {
+        match self {
+            CSRegister::S0 => IRegister::FramePointer,
+            CSRegister::S1 => IRegister::S1,
+            CSRegister::S2 => IRegister::S2,
+            CSRegister::S3 => IRegister::S3,
+            CSRegister::S4 => IRegister::S4,
+            CSRegister::S5 => IRegister::S5,
+            CSRegister::S6 => IRegister::S6,
+            CSRegister::S7 => IRegister::S7,
+        }
+    }
+
+    pub fn rs2(&self) -> u16 {
+        (*self as u16) << 2
+    }
+
+    pub fn rs1(&self) -> u16 {
+        (*self as u16) << 7
+    }
+}
+
+impl From<CSRegister> for IRegister {
+    fn from(value: CSRegister) -> Self {
+        value.expand()
+    }
+}
+
+impl TryFrom<IRegister> for CSRegister {
+    type Error = String;
+
+    fn try_from(value: IRegister) -> Result<Self, Self::Error> {
+        match u32::from(value) {
+            n @ 8..=9 => Ok(CSRegister::from((n - 8) as u16)),
+            n @ 18..=23 => Ok(CSRegister::from((n - 16) as u16)),
+            _ => Err(format!(
+                "integer register {value} is not one of the saved registers s0-s7"
+            )),
+        }
+    }
+}
+
+impl RegisterClass for CSRegister {
+    fn name(self) -> &'static str {
+        self.expand().name()
+    }
+
+    fn number(self) -> u8 {
+        self.expand().number()
+    }
+
+    fn kind(self) -> RegisterKind {
+        RegisterKind::Integer
+    }
+}
+
+impl Display for CSRegister {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        f.write_str(self.name())
+    }
+}
+
+/// The registers saved and restored by the Zcmp push and pop instructions, which are always ra followed by s0 up to
+/// some saved register (`{ra, s0-s3}`).
+///
+/// The list is stored as its 4-bit rlist encoding, where 4 is `{ra}` and 15 is `{ra, s0-s11}`. There is no encoding
+/// for `{ra, s0-s10}`.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub struct RegisterList {
+    rlist: u8,
+}
+
+impl RegisterList {
+    /// The registers that a list may hold, in order.
+    const ORDER: [IRegister; 13] = [
+        IRegister::ReturnAddress,
+        IRegister::FramePointer,
+        IRegister::S1,
+        IRegister::S2,
+        IRegister::S3,
+        IRegister::S4,
+        IRegister::S5,
+        IRegister::S6,
+        IRegister::S7,
+        IRegister::S8,
+        IRegister::S9,
+        IRegister::S10,
+        IRegister::S11,
+    ];
+
+    /// The 4-bit rlist field of the encoding.
+    pub fn rlist(self) -> u16 {
+        self.rlist as u16
+    }
+
+    /// The number of registers in the list.
+    pub fn count(self) -> usize {
+        match self.rlist {
+            15 => 13,
+            rlist => rlist as usize - 3,
+        }
+    }
+
+    /// The registers in the list, ra first.
+    pub fn registers(self) -> impl Iterator<Item = IRegister> {
+        RegisterList::ORDER.into_iter().take(self.count())
+    }
+
+    /// The number of bytes the registers take on the stack on `xlen`, rounded up to the 16-byte stack alignment.
+    pub fn stack_size(self, xlen: Xlen) -> u32 {
+        (self.count() as u32 * xlen.bits() / 8).next_multiple_of(16)
+    }
+}
+
+impl TryFrom<u16> for RegisterList {
+    type Error = String;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        match value {
+            4..=15 => Ok(RegisterList { rlist: value as u8 }),
+            x => Err(format!("reserved register list encoding {x}")),
+        }
+    }
+}
+
+impl TryFrom<&str> for RegisterList {
+    type Error = String;
+
+    /// Parses a register list in braces, with ABI names (`{ra, s0-s3}`) or numeric names (`{x1, x8-x9, x18-x19}`).
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let invalid = |reason: String| format!("invalid register list {value}, {reason}");
+        let inner = value
+            .trim()
+            .strip_prefix('{')
+            .and_then(|v| v.strip_suffix('}'))
+            .ok_or_else(|| invalid("expected registers in braces".to_owned()))?;
+        // the position of each register in the order of the list
+        let position = |name: &str| {
+            let register = IRegister::from_string(name.trim()).map_err(invalid)?;
+            RegisterList::ORDER
+                .iter()
+                .position(|r| *r == register)
+                .ok_or_else(|| invalid(format!("{register} can not be saved by a register list")))
+        };
+        let mut count = 0;
+        for part in inner.split(',') {
+            let (first, last) = match part.split_once('-') {
+                Some((first, last)) => (position(first)?, position(last)?),
+                None => (position(part)?, position(part)?),
+            };
+            // numeric ranges can not skip over registers (x8-x18 includes the argument registers)
+            let skips = last > first
+                && (RegisterList::ORDER[last].number() - RegisterList::ORDER[first].number())
+                    as usize
+                    != last - first;
+            if part.trim().starts_with('x') && skips {
+                return Err(invalid(format!(
+                    "{} includes registers that can not be saved",
+                    part.trim()
+                )));
             }
-        )
+            if first != count || last < first {
+                return Err(invalid(
+                    "expected ra followed by s0 up to a saved register".to_owned(),
+                ));
+            }
+            count = last + 1;
+        }
+        match count {
+            12 => Err(invalid(
+                "s10 can only be saved together with s11".to_owned(),
+            )),
+            13 => Ok(RegisterList { rlist: 15 }),
+            _ => Ok(RegisterList {
+                rlist: count as u8 + 3,
+            }),
+        }
+    }
+}
+
+impl Display for RegisterList {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        match self.count() {
+            1 => write!(f, "{{ra}}"),
+            2 => write!(f, "{{ra, s0}}"),
+            count => write!(f, "{{ra, s0-s{}}}", count - 2),
+        }
     }
 }
@@ -0,0 +1,158 @@
+//! Exhaustive scans of the encoding space, for checking the decoder against the specification.
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+use crate::cinstruction::CInstruction;
+use crate::config::DecoderConfig;
+use crate::error::DecodeError;
+use crate::instruction::Instruction;
+use crate::opcode::Opcode;
+
+/// How the decoder handles an encoding.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Classification {
+    Decoded,
+    /// The encoding matches an instruction, but a field holds a value the specification reserves.
+    Reserved,
+    /// No instruction has the opcode and function fields of the encoding.
+    Invalid,
+}
+
+impl Classification {
+    /// Classifies the result of decoding.
+    ///
+    /// Decoding errors are not typed, errors for unknown or invalid function fields and for extensions that are not
+    /// enabled are invalid and all others (reserved values, registers that must not be x0, immediates that must not
+    /// be zero) are reserved.
+    fn of<T>(result: &Result<T, DecodeError>) -> Classification {
+        match result {
+            Ok(_) => Classification::Decoded,
+            Err(e) => {
+                let e = e.message.to_lowercase();
+                if ["unknown", "unkown", "invalid", "unsupported"]
+                    .iter()
+                    .any(|prefix| e.starts_with(prefix))
+                {
+                    Classification::Invalid
+                } else {
+                    Classification::Reserved
+                }
+            }
+        }
+    }
+}
+
+/// Classifies a 32-bit encoding.
+pub fn classify(word: u32, config: &DecoderConfig) -> Classification {
+    Classification::of(&Instruction::decode_with(word, config))
+}
+
+/// Classifies a 16-bit encoding.
+pub fn classify_compressed(half: u16, config: &DecoderConfig) -> Classification {
+    Classification::of(&CInstruction::decode_with(half, config))
+}
+
+/// The number of encodings of each classification.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct Counts {
+    pub decoded: u64,
+    pub reserved: u64,
+    pub invalid: u64,
+}
+
+impl Counts {
+    fn add(&mut self, classification: Classification) {
+        match classification {
+            Classification::Decoded => self.decoded += 1,
+            Classification::Reserved => self.reserved += 1,
+            Classification::Invalid => self.invalid += 1,
+        }
+    }
+
+    pub fn total(&self) -> u64 {
+        self.decoded + self.reserved + self.invalid
+    }
+}
+
+/// The classification of a scanned encoding space by opcode.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct ScanSummary {
+    /// Whether 16-bit encodings were scanned.
+    pub compressed: bool,
+    /// The counts of each opcode, which is bits 6:0 of 32-bit encodings and funct3 followed by the quadrant (bits
+    /// 15:13 and 1:0) of 16-bit encodings.
+    pub opcodes: BTreeMap<u8, Counts>,
+}
+
+impl ScanSummary {
+    /// Returns the counts over all opcodes.
+    pub fn total(&self) -> Counts {
+        let mut total = Counts::default();
+        for counts in self.opcodes.values() {
+            total.decoded += counts.decoded;
+            total.reserved += counts.reserved;
+            total.invalid += counts.invalid;
+        }
+        total
+    }
+}
+
+/// Prints a table with one line per opcode.
+impl Display for ScanSummary {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{:<20} {:>10} {:>10} {:>10}",
+            "opcode", "decoded", "reserved", "invalid"
+        )?;
+        for (opcode, counts) in &self.opcodes {
+            let name = if self.compressed {
+                format!("q{} funct3={:03b}", opcode & 0b11, opcode >> 2)
+            } else {
+                format!("{:07b} {:?}", opcode, Opcode::from_int(*opcode as u32))
+            };
+            writeln!(
+                f,
+                "{name:<20} {:>10} {:>10} {:>10}",
+                counts.decoded, counts.reserved, counts.invalid
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Classifies 32-bit encodings, words that are not 32-bit encodings (the low bits are not `11`) are skipped.
+pub fn scan_words(words: impl IntoIterator<Item = u32>, config: &DecoderConfig) -> ScanSummary {
+    let mut summary = ScanSummary::default();
+    for word in words.into_iter().filter(|w| w & 0b11 == 0b11) {
+        summary
+            .opcodes
+            .entry((word & 0b111_1111) as u8)
+            .or_default()
+            .add(classify(word, config));
+    }
+    summary
+}
+
+/// Classifies all 2^30 32-bit encodings, this decodes over a billion words.
+pub fn scan_all_words(config: &DecoderConfig) -> ScanSummary {
+    scan_words((0..1u32 << 30).map(|w| w << 2 | 0b11), config)
+}
+
+/// Classifies all 49152 16-bit encodings.
+pub fn scan_compressed(config: &DecoderConfig) -> ScanSummary {
+    let mut summary = ScanSummary {
+        compressed: true,
+        ..Default::default()
+    };
+    for half in (0..=u16::MAX).filter(|h| h & 0b11 != 0b11) {
+        let opcode = ((half >> 11) & 0b11100 | half & 0b11) as u8;
+        summary
+            .opcodes
+            .entry(opcode)
+            .or_default()
+            .add(classify_compressed(half, config));
+    }
+    summary
+}
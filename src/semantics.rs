@@ -0,0 +1,709 @@
+//! A declarative description of what instructions do.
+//!
+//! `Instruction::semantics` describes an instruction as a list of effects whose expressions all read the state from
+//! before the instruction executes, so the order of effects does not matter. This makes the description usable by
+//! lifters and symbolic execution tools without a semantics table of their own.
+
+use crate::instruction::Instruction;
+use crate::register::IRegister;
+
+/// The width of a memory access or of a value being extended.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub enum Width {
+    Byte,
+    Half,
+    Word,
+    Double,
+}
+
+impl Width {
+    /// The number of bits in this width.
+    pub fn bits(self) -> u32 {
+        match self {
+            Width::Byte => 8,
+            Width::Half => 16,
+            Width::Word => 32,
+            Width::Double => 64,
+        }
+    }
+}
+
+/// A 64-bit value computed from the state before an instruction executes.
+///
+/// Arithmetic wraps, shift amounts are not masked (the masking is part of the expression) and division follows the
+/// RISC-V rules for division by zero and overflow.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Expr {
+    /// A sign extended constant.
+    Imm(i64),
+    /// The value of an integer register, x0 always reads as zero.
+    Read(IRegister),
+    /// The address of the instruction.
+    Pc,
+    /// A zero extended value loaded from memory.
+    Load(Width, Box<Expr>),
+    /// Sign extends the low bits of a value.
+    SignExtend(Width, Box<Expr>),
+    /// Zero extends the low bits of a value.
+    ZeroExtend(Width, Box<Expr>),
+    Add(Box<Expr>, Box<Expr>),
+    Sub(Box<Expr>, Box<Expr>),
+    And(Box<Expr>, Box<Expr>),
+    Or(Box<Expr>, Box<Expr>),
+    Xor(Box<Expr>, Box<Expr>),
+    /// Shift left.
+    Shl(Box<Expr>, Box<Expr>),
+    /// Logical shift right.
+    Shr(Box<Expr>, Box<Expr>),
+    /// Arithmetic shift right.
+    Sra(Box<Expr>, Box<Expr>),
+    /// 1 if the values are equal, otherwise 0.
+    Eq(Box<Expr>, Box<Expr>),
+    /// 1 if the values are not equal, otherwise 0.
+    Ne(Box<Expr>, Box<Expr>),
+    /// 1 if the first value is less than the second as signed integers, otherwise 0.
+    Lt(Box<Expr>, Box<Expr>),
+    /// 1 if the first value is less than the second as unsigned integers, otherwise 0.
+    Ltu(Box<Expr>, Box<Expr>),
+    /// 1 if the first value is greater than or equal to the second as signed integers, otherwise 0.
+    Ge(Box<Expr>, Box<Expr>),
+    /// 1 if the first value is greater than or equal to the second as unsigned integers, otherwise 0.
+    Geu(Box<Expr>, Box<Expr>),
+    Mul(Box<Expr>, Box<Expr>),
+    /// The upper 64 bits of the signed 128-bit product.
+    MulH(Box<Expr>, Box<Expr>),
+    /// The upper 64 bits of the 128-bit product of a signed and an unsigned value.
+    MulHSU(Box<Expr>, Box<Expr>),
+    /// The upper 64 bits of the unsigned 128-bit product.
+    MulHU(Box<Expr>, Box<Expr>),
+    /// Signed division, dividing by zero gives -1.
+    Div(Box<Expr>, Box<Expr>),
+    /// Unsigned division, dividing by zero gives the maximum value.
+    DivU(Box<Expr>, Box<Expr>),
+    /// Signed remainder, dividing by zero gives the dividend.
+    Rem(Box<Expr>, Box<Expr>),
+    /// Unsigned remainder, dividing by zero gives the dividend.
+    RemU(Box<Expr>, Box<Expr>),
+    Min(Box<Expr>, Box<Expr>),
+    Max(Box<Expr>, Box<Expr>),
+    MinU(Box<Expr>, Box<Expr>),
+    MaxU(Box<Expr>, Box<Expr>),
+}
+
+/// A change to the state made by an instruction.
+#[derive(Debug, PartialEq, Eq, Clone)]
+pub enum Effect {
+    /// Writes an integer register, writes to x0 are discarded.
+    Write(IRegister, Expr),
+    /// Stores the low bits of a value to memory.
+    Store {
+        width: Width,
+        addr: Expr,
+        value: Expr,
+    },
+    /// Sets the program counter, otherwise execution continues with the next instruction.
+    Jump(Expr),
+    /// Performs the effect if the condition is not zero.
+    If(Expr, Box<Effect>),
+    /// Registers a reservation on an address (lr).
+    Reserve(Width, Expr),
+    /// Stores a value if the reservation on the address is still valid and writes 0 to `dest` on success or a
+    /// non-zero value on failure (sc). The reservation is always released.
+    StoreConditional {
+        dest: IRegister,
+        width: Width,
+        addr: Expr,
+        value: Expr,
+    },
+    /// Orders memory accesses, the sets use the bit layout of the fence instruction (iorw).
+    Fence { predecessor: u8, successor: u8 },
+    /// Synchronizes the instruction and data streams.
+    FenceI,
+    /// Requests a service from the execution environment.
+    Ecall,
+    /// Returns control to a debugger.
+    Ebreak,
+}
+
+fn imm(value: i64) -> Expr {
+    Expr::Imm(value)
+}
+
+fn read(register: IRegister) -> Expr {
+    Expr::Read(register)
+}
+
+fn boxed(expr: Expr) -> Box<Expr> {
+    Box::new(expr)
+}
+
+fn sext(width: Width, expr: Expr) -> Expr {
+    Expr::SignExtend(width, boxed(expr))
+}
+
+fn zext(width: Width, expr: Expr) -> Expr {
+    Expr::ZeroExtend(width, boxed(expr))
+}
+
+fn add(a: Expr, b: Expr) -> Expr {
+    Expr::Add(boxed(a), boxed(b))
+}
+
+fn and(a: Expr, b: Expr) -> Expr {
+    Expr::And(boxed(a), boxed(b))
+}
+
+fn not(a: Expr) -> Expr {
+    Expr::Xor(boxed(a), boxed(imm(-1)))
+}
+
+fn negate(a: Expr) -> Expr {
+    Expr::Sub(boxed(imm(0)), boxed(a))
+}
+
+/// Rotates a value right by an amount masked to its width (`0b11_1111` or `0b1_1111`). The bits of a word above the
+/// low 32 are meaningless.
+fn rotate_right(value: Expr, amount: Expr, mask: i64) -> Expr {
+    let amount = and(amount, imm(mask));
+    let inverse = and(negate(amount.clone()), imm(mask));
+    Expr::Or(
+        boxed(Expr::Shr(boxed(value.clone()), boxed(amount))),
+        boxed(Expr::Shl(boxed(value), boxed(inverse))),
+    )
+}
+
+fn load(width: Width, addr: Expr) -> Expr {
+    Expr::Load(width, boxed(addr))
+}
+
+/// The effective address of a load or store.
+fn address(base: IRegister, offset: i64) -> Expr {
+    add(read(base), imm(offset))
+}
+
+/// The address of the next instruction.
+fn next() -> Expr {
+    add(Expr::Pc, imm(4))
+}
+
+/// Writes the result of a binary operation.
+fn op(dest: IRegister, f: fn(Box<Expr>, Box<Expr>) -> Expr, a: Expr, b: Expr) -> Vec<Effect> {
+    vec![Effect::Write(dest, f(boxed(a), boxed(b)))]
+}
+
+/// Writes the sign extension of a 32-bit result.
+fn op_w(dest: IRegister, f: fn(Box<Expr>, Box<Expr>) -> Expr, a: Expr, b: Expr) -> Vec<Effect> {
+    vec![Effect::Write(
+        dest,
+        sext(Width::Word, f(boxed(a), boxed(b))),
+    )]
+}
+
+fn branch(
+    condition: fn(Box<Expr>, Box<Expr>) -> Expr,
+    src1: IRegister,
+    src2: IRegister,
+    offset: i64,
+) -> Vec<Effect> {
+    vec![Effect::If(
+        condition(boxed(read(src1)), boxed(read(src2))),
+        Box::new(Effect::Jump(add(Expr::Pc, imm(offset)))),
+    )]
+}
+
+fn load_effect(
+    dest: IRegister,
+    width: Width,
+    signed: bool,
+    base: IRegister,
+    offset: i64,
+) -> Vec<Effect> {
+    let value = load(width, address(base, offset));
+    vec![Effect::Write(
+        dest,
+        if signed && width != Width::Double {
+            sext(width, value)
+        } else {
+            value
+        },
+    )]
+}
+
+fn store_effect(width: Width, src: IRegister, base: IRegister, offset: i64) -> Vec<Effect> {
+    vec![Effect::Store {
+        width,
+        addr: address(base, offset),
+        value: read(src),
+    }]
+}
+
+/// A read-modify-write of memory that writes the old value to `dest`.
+///
+/// The operands of 32-bit operations are extended with `extend` so that min and max compare them correctly.
+fn amo(
+    dest: IRegister,
+    width: Width,
+    addr: IRegister,
+    f: fn(Box<Expr>, Box<Expr>) -> Expr,
+    extend: fn(Width, Expr) -> Expr,
+    src: IRegister,
+) -> Vec<Effect> {
+    let (old, operand) = match width {
+        Width::Double => (load(width, read(addr)), read(src)),
+        _ => (
+            extend(width, load(width, read(addr))),
+            extend(width, read(src)),
+        ),
+    };
+    vec![
+        Effect::Write(dest, sign_extend_narrow(width, load(width, read(addr)))),
+        Effect::Store {
+            width,
+            addr: read(addr),
+            value: f(boxed(old), boxed(operand)),
+        },
+    ]
+}
+
+/// Sign extends values loaded by atomics narrower than XLEN.
+fn sign_extend_narrow(width: Width, value: Expr) -> Expr {
+    match width {
+        Width::Double => value,
+        _ => sext(width, value),
+    }
+}
+
+/// Replaces the memory value with the source register.
+fn swap(dest: IRegister, width: Width, addr: IRegister, src: IRegister) -> Vec<Effect> {
+    let old = load(width, read(addr));
+    vec![
+        Effect::Write(dest, sign_extend_narrow(width, old)),
+        Effect::Store {
+            width,
+            addr: read(addr),
+            value: read(src),
+        },
+    ]
+}
+
+/// Stores the source register if memory holds the expected value in `dest` (amocas).
+fn compare_and_swap(dest: IRegister, width: Width, addr: IRegister, src: IRegister) -> Vec<Effect> {
+    let old = load(width, read(addr));
+    vec![
+        Effect::Write(dest, sign_extend_narrow(width, old.clone())),
+        Effect::If(
+            Expr::Eq(boxed(old), boxed(zext(width, read(dest)))),
+            Box::new(Effect::Store {
+                width,
+                addr: read(addr),
+                value: read(src),
+            }),
+        ),
+    ]
+}
+
+fn load_reserved(dest: IRegister, width: Width, addr: IRegister) -> Vec<Effect> {
+    let value = load(width, read(addr));
+    vec![
+        Effect::Write(dest, sign_extend_narrow(width, value)),
+        Effect::Reserve(width, read(addr)),
+    ]
+}
+
+fn store_conditional(
+    dest: IRegister,
+    width: Width,
+    addr: IRegister,
+    src: IRegister,
+) -> Vec<Effect> {
+    vec![Effect::StoreConditional {
+        dest,
+        width,
+        addr: read(addr),
+        value: read(src),
+    }]
+}
+
+impl Instruction {
+    /// Describes the effects of this instruction on the integer registers, memory and program counter.
+    ///
+    /// Returns `None` for instructions that are not described yet (the float and Zicsr extensions and scalar crypto
+    /// other than pack, the inverted logic operations and the rotations).
+    /// The memory ordering bits of atomic instructions are not part of the description.
+    pub fn semantics(&self) -> Option<Vec<Effect>> {
+        Some(match *self {
+            Instruction::LUI { dest, imm: i } => vec![Effect::Write(dest, imm(i.val() << 12))],
+            Instruction::AUIPC { dest, imm: i } => {
+                vec![Effect::Write(dest, add(Expr::Pc, imm(i.val() << 12)))]
+            }
+            Instruction::JAL { dest, offset } => vec![
+                Effect::Write(dest, next()),
+                Effect::Jump(add(Expr::Pc, imm(offset.val()))),
+            ],
+            Instruction::JALR { dest, base, offset } => vec![
+                Effect::Write(dest, next()),
+                Effect::Jump(and(address(base, offset.val()), imm(!1))),
+            ],
+            Instruction::BEQ { src1, src2, offset } => branch(Expr::Eq, src1, src2, offset.val()),
+            Instruction::BNE { src1, src2, offset } => branch(Expr::Ne, src1, src2, offset.val()),
+            Instruction::BLT { src1, src2, offset } => branch(Expr::Lt, src1, src2, offset.val()),
+            Instruction::BGE { src1, src2, offset } => branch(Expr::Ge, src1, src2, offset.val()),
+            Instruction::BLTU { src1, src2, offset } => branch(Expr::Ltu, src1, src2, offset.val()),
+            Instruction::BGEU { src1, src2, offset } => branch(Expr::Geu, src1, src2, offset.val()),
+            Instruction::LB { dest, base, offset } => {
+                load_effect(dest, Width::Byte, true, base, offset.val())
+            }
+            Instruction::LH { dest, base, offset } => {
+                load_effect(dest, Width::Half, true, base, offset.val())
+            }
+            Instruction::LW { dest, base, offset } => {
+                load_effect(dest, Width::Word, true, base, offset.val())
+            }
+            Instruction::LD { dest, base, offset } => {
+                load_effect(dest, Width::Double, true, base, offset.val())
+            }
+            Instruction::LBU { dest, base, offset } => {
+                load_effect(dest, Width::Byte, false, base, offset.val())
+            }
+            Instruction::LHU { dest, base, offset } => {
+                load_effect(dest, Width::Half, false, base, offset.val())
+            }
+            Instruction::LWU { dest, base, offset } => {
+                load_effect(dest, Width::Word, false, base, offset.val())
+            }
+            Instruction::SB { src, base, offset } => {
+                store_effect(Width::Byte, src, base, offset.val())
+            }
+            Instruction::SH { src, base, offset } => {
+                store_effect(Width::Half, src, base, offset.val())
+            }
+            Instruction::SW { src, base, offset } => {
+                store_effect(Width::Word, src, base, offset.val())
+            }
+            Instruction::SD { src, base, offset } => {
+                store_effect(Width::Double, src, base, offset.val())
+            }
+            Instruction::ADDI { dest, src, imm: i } => op(dest, Expr::Add, read(src), imm(i.val())),
+            Instruction::SLTI { dest, src, imm: i } => op(dest, Expr::Lt, read(src), imm(i.val())),
+            Instruction::SLTIU { dest, src, imm: i } => {
+                op(dest, Expr::Ltu, read(src), imm(i.val()))
+            }
+            Instruction::XORI { dest, src, imm: i } => op(dest, Expr::Xor, read(src), imm(i.val())),
+            Instruction::ORI { dest, src, imm: i } => op(dest, Expr::Or, read(src), imm(i.val())),
+            Instruction::ANDI { dest, src, imm: i } => op(dest, Expr::And, read(src), imm(i.val())),
+            Instruction::SLLI { dest, src, shamt } => {
+                op(dest, Expr::Shl, read(src), imm(shamt.val()))
+            }
+            Instruction::SRLI { dest, src, shamt } => {
+                op(dest, Expr::Shr, read(src), imm(shamt.val()))
+            }
+            Instruction::SRAI { dest, src, shamt } => {
+                op(dest, Expr::Sra, read(src), imm(shamt.val()))
+            }
+            Instruction::ADD { dest, src1, src2 } => op(dest, Expr::Add, read(src1), read(src2)),
+            Instruction::SUB { dest, src1, src2 } => op(dest, Expr::Sub, read(src1), read(src2)),
+            Instruction::SLL { dest, src1, src2 } => {
+                op(dest, Expr::Shl, read(src1), and(read(src2), imm(0b11_1111)))
+            }
+            Instruction::SLT { dest, src1, src2 } => op(dest, Expr::Lt, read(src1), read(src2)),
+            Instruction::SLTU { dest, src1, src2 } => op(dest, Expr::Ltu, read(src1), read(src2)),
+            Instruction::XOR { dest, src1, src2 } => op(dest, Expr::Xor, read(src1), read(src2)),
+            Instruction::SRL { dest, src1, src2 } => {
+                op(dest, Expr::Shr, read(src1), and(read(src2), imm(0b11_1111)))
+            }
+            Instruction::SRA { dest, src1, src2 } => {
+                op(dest, Expr::Sra, read(src1), and(read(src2), imm(0b11_1111)))
+            }
+            Instruction::OR { dest, src1, src2 } => op(dest, Expr::Or, read(src1), read(src2)),
+            Instruction::AND { dest, src1, src2 } => op(dest, Expr::And, read(src1), read(src2)),
+            Instruction::FENCE { ops, .. } => vec![Effect::Fence {
+                predecessor: ops >> 4,
+                successor: ops & 0b1111,
+            }],
+            Instruction::FENCEI => vec![Effect::FenceI],
+            // hints have no architectural effect
+            Instruction::PREFETCHI { .. }
+            | Instruction::PREFETCHR { .. }
+            | Instruction::PREFETCHW { .. }
+            | Instruction::PAUSE
+            | Instruction::NTLP1
+            | Instruction::NTLPALL
+            | Instruction::NTLS1
+            | Instruction::NTLALL => vec![],
+            Instruction::ECALL => vec![Effect::Ecall],
+            Instruction::EBREAK => vec![Effect::Ebreak],
+            Instruction::ADDIW { dest, src, imm: i } => {
+                op_w(dest, Expr::Add, read(src), imm(i.val()))
+            }
+            Instruction::SLLIW { dest, src, shamt } => {
+                op_w(dest, Expr::Shl, read(src), imm(shamt.val()))
+            }
+            Instruction::SRLIW { dest, src, shamt } => op_w(
+                dest,
+                Expr::Shr,
+                zext(Width::Word, read(src)),
+                imm(shamt.val()),
+            ),
+            Instruction::SRAIW { dest, src, shamt } => op(
+                dest,
+                Expr::Sra,
+                sext(Width::Word, read(src)),
+                imm(shamt.val()),
+            ),
+            Instruction::ADDW { dest, src1, src2 } => op_w(dest, Expr::Add, read(src1), read(src2)),
+            Instruction::SUBW { dest, src1, src2 } => op_w(dest, Expr::Sub, read(src1), read(src2)),
+            Instruction::SLLW { dest, src1, src2 } => {
+                op_w(dest, Expr::Shl, read(src1), and(read(src2), imm(0b1_1111)))
+            }
+            Instruction::SRLW { dest, src1, src2 } => op_w(
+                dest,
+                Expr::Shr,
+                zext(Width::Word, read(src1)),
+                and(read(src2), imm(0b1_1111)),
+            ),
+            Instruction::SRAW { dest, src1, src2 } => op(
+                dest,
+                Expr::Sra,
+                sext(Width::Word, read(src1)),
+                and(read(src2), imm(0b1_1111)),
+            ),
+            Instruction::MUL { dest, src1, src2 } => op(dest, Expr::Mul, read(src1), read(src2)),
+            Instruction::MULH { dest, src1, src2 } => op(dest, Expr::MulH, read(src1), read(src2)),
+            Instruction::MULHSU { dest, src1, src2 } => {
+                op(dest, Expr::MulHSU, read(src1), read(src2))
+            }
+            Instruction::MULHU { dest, src1, src2 } => {
+                op(dest, Expr::MulHU, read(src1), read(src2))
+            }
+            Instruction::DIV { dest, src1, src2 } => op(dest, Expr::Div, read(src1), read(src2)),
+            Instruction::DIVU { dest, src1, src2 } => op(dest, Expr::DivU, read(src1), read(src2)),
+            Instruction::REM { dest, src1, src2 } => op(dest, Expr::Rem, read(src1), read(src2)),
+            Instruction::REMU { dest, src1, src2 } => op(dest, Expr::RemU, read(src1), read(src2)),
+            Instruction::MULW { dest, src1, src2 } => op_w(dest, Expr::Mul, read(src1), read(src2)),
+            Instruction::DIVW { dest, src1, src2 } => op_w(
+                dest,
+                Expr::Div,
+                sext(Width::Word, read(src1)),
+                sext(Width::Word, read(src2)),
+            ),
+            Instruction::DIVUW { dest, src1, src2 } => op_w(
+                dest,
+                Expr::DivU,
+                zext(Width::Word, read(src1)),
+                zext(Width::Word, read(src2)),
+            ),
+            Instruction::REMW { dest, src1, src2 } => op_w(
+                dest,
+                Expr::Rem,
+                sext(Width::Word, read(src1)),
+                sext(Width::Word, read(src2)),
+            ),
+            Instruction::REMUW { dest, src1, src2 } => op_w(
+                dest,
+                Expr::RemU,
+                zext(Width::Word, read(src1)),
+                zext(Width::Word, read(src2)),
+            ),
+            Instruction::PACK { dest, src1, src2 } => op(
+                dest,
+                Expr::Or,
+                zext(Width::Word, read(src1)),
+                Expr::Shl(boxed(read(src2)), boxed(imm(32))),
+            ),
+            Instruction::PACKH { dest, src1, src2 } => op(
+                dest,
+                Expr::Or,
+                zext(Width::Byte, read(src1)),
+                Expr::Shl(boxed(zext(Width::Byte, read(src2))), boxed(imm(8))),
+            ),
+            Instruction::PACKW { dest, src1, src2 } => op_w(
+                dest,
+                Expr::Or,
+                zext(Width::Half, read(src1)),
+                Expr::Shl(boxed(zext(Width::Half, read(src2))), boxed(imm(16))),
+            ),
+            Instruction::ANDN { dest, src1, src2 } => {
+                op(dest, Expr::And, read(src1), not(read(src2)))
+            }
+            Instruction::ORN { dest, src1, src2 } => {
+                op(dest, Expr::Or, read(src1), not(read(src2)))
+            }
+            Instruction::XNOR { dest, src1, src2 } => vec![Effect::Write(
+                dest,
+                not(Expr::Xor(boxed(read(src1)), boxed(read(src2)))),
+            )],
+            Instruction::ROL { dest, src1, src2 } => vec![Effect::Write(
+                dest,
+                rotate_right(read(src1), negate(read(src2)), 0b11_1111),
+            )],
+            Instruction::ROR { dest, src1, src2 } => vec![Effect::Write(
+                dest,
+                rotate_right(read(src1), read(src2), 0b11_1111),
+            )],
+            Instruction::RORI { dest, src, shamt } => vec![Effect::Write(
+                dest,
+                rotate_right(read(src), imm(shamt.val()), 0b11_1111),
+            )],
+            Instruction::ROLW { dest, src1, src2 } => vec![Effect::Write(
+                dest,
+                sext(
+                    Width::Word,
+                    rotate_right(zext(Width::Word, read(src1)), negate(read(src2)), 0b1_1111),
+                ),
+            )],
+            Instruction::RORW { dest, src1, src2 } => vec![Effect::Write(
+                dest,
+                sext(
+                    Width::Word,
+                    rotate_right(zext(Width::Word, read(src1)), read(src2), 0b1_1111),
+                ),
+            )],
+            Instruction::RORIW { dest, src, shamt } => vec![Effect::Write(
+                dest,
+                sext(
+                    Width::Word,
+                    rotate_right(zext(Width::Word, read(src)), imm(shamt.val()), 0b1_1111),
+                ),
+            )],
+            #[cfg(feature = "zalasr")]
+            Instruction::LBAQ { dest, addr, .. } => load_effect(dest, Width::Byte, true, addr, 0),
+            #[cfg(feature = "zalasr")]
+            Instruction::LHAQ { dest, addr, .. } => load_effect(dest, Width::Half, true, addr, 0),
+            #[cfg(feature = "zalasr")]
+            Instruction::LWAQ { dest, addr, .. } => load_effect(dest, Width::Word, true, addr, 0),
+            #[cfg(feature = "zalasr")]
+            Instruction::LDAQ { dest, addr, .. } => load_effect(dest, Width::Double, true, addr, 0),
+            #[cfg(feature = "zalasr")]
+            Instruction::SBRL { src, addr, .. } => store_effect(Width::Byte, src, addr, 0),
+            #[cfg(feature = "zalasr")]
+            Instruction::SHRL { src, addr, .. } => store_effect(Width::Half, src, addr, 0),
+            #[cfg(feature = "zalasr")]
+            Instruction::SWRL { src, addr, .. } => store_effect(Width::Word, src, addr, 0),
+            #[cfg(feature = "zalasr")]
+            Instruction::SDRL { src, addr, .. } => store_effect(Width::Double, src, addr, 0),
+            Instruction::LRW { dest, addr, .. } => load_reserved(dest, Width::Word, addr),
+            Instruction::LRD { dest, addr, .. } => load_reserved(dest, Width::Double, addr),
+            Instruction::SCW {
+                dest, addr, src, ..
+            } => store_conditional(dest, Width::Word, addr, src),
+            Instruction::SCD {
+                dest, addr, src, ..
+            } => store_conditional(dest, Width::Double, addr, src),
+            Instruction::AMOSWAPW {
+                dest, addr, src, ..
+            } => swap(dest, Width::Word, addr, src),
+            Instruction::AMOADDW {
+                dest, addr, src, ..
+            } => amo(dest, Width::Word, addr, Expr::Add, sext, src),
+            Instruction::AMOXORW {
+                dest, addr, src, ..
+            } => amo(dest, Width::Word, addr, Expr::Xor, sext, src),
+            Instruction::AMOANDW {
+                dest, addr, src, ..
+            } => amo(dest, Width::Word, addr, Expr::And, sext, src),
+            Instruction::AMOORW {
+                dest, addr, src, ..
+            } => amo(dest, Width::Word, addr, Expr::Or, sext, src),
+            Instruction::AMOMINW {
+                dest, addr, src, ..
+            } => amo(dest, Width::Word, addr, Expr::Min, sext, src),
+            Instruction::AMOMAXW {
+                dest, addr, src, ..
+            } => amo(dest, Width::Word, addr, Expr::Max, sext, src),
+            Instruction::AMOMINUW {
+                dest, addr, src, ..
+            } => amo(dest, Width::Word, addr, Expr::MinU, zext, src),
+            Instruction::AMOMAXUW {
+                dest, addr, src, ..
+            } => amo(dest, Width::Word, addr, Expr::MaxU, zext, src),
+            Instruction::AMOSWAPD {
+                dest, addr, src, ..
+            } => swap(dest, Width::Double, addr, src),
+            Instruction::AMOADDD {
+                dest, addr, src, ..
+            } => amo(dest, Width::Double, addr, Expr::Add, sext, src),
+            Instruction::AMOXORD {
+                dest, addr, src, ..
+            } => amo(dest, Width::Double, addr, Expr::Xor, sext, src),
+            Instruction::AMOANDD {
+                dest, addr, src, ..
+            } => amo(dest, Width::Double, addr, Expr::And, sext, src),
+            Instruction::AMOORD {
+                dest, addr, src, ..
+            } => amo(dest, Width::Double, addr, Expr::Or, sext, src),
+            Instruction::AMOMIND {
+                dest, addr, src, ..
+            } => amo(dest, Width::Double, addr, Expr::Min, sext, src),
+            Instruction::AMOMAXD {
+                dest, addr, src, ..
+            } => amo(dest, Width::Double, addr, Expr::Max, sext, src),
+            Instruction::AMOMINUD {
+                dest, addr, src, ..
+            } => amo(dest, Width::Double, addr, Expr::MinU, zext, src),
+            Instruction::AMOMAXUD {
+                dest, addr, src, ..
+            } => amo(dest, Width::Double, addr, Expr::MaxU, zext, src),
+            Instruction::AMOSWAPB {
+                dest, addr, src, ..
+            } => swap(dest, Width::Byte, addr, src),
+            Instruction::AMOADDB {
+                dest, addr, src, ..
+            } => amo(dest, Width::Byte, addr, Expr::Add, sext, src),
+            Instruction::AMOXORB {
+                dest, addr, src, ..
+            } => amo(dest, Width::Byte, addr, Expr::Xor, sext, src),
+            Instruction::AMOANDB {
+                dest, addr, src, ..
+            } => amo(dest, Width::Byte, addr, Expr::And, sext, src),
+            Instruction::AMOORB {
+                dest, addr, src, ..
+            } => amo(dest, Width::Byte, addr, Expr::Or, sext, src),
+            Instruction::AMOMINB {
+                dest, addr, src, ..
+            } => amo(dest, Width::Byte, addr, Expr::Min, sext, src),
+            Instruction::AMOMAXB {
+                dest, addr, src, ..
+            } => amo(dest, Width::Byte, addr, Expr::Max, sext, src),
+            Instruction::AMOMINUB {
+                dest, addr, src, ..
+            } => amo(dest, Width::Byte, addr, Expr::MinU, zext, src),
+            Instruction::AMOMAXUB {
+                dest, addr, src, ..
+            } => amo(dest, Width::Byte, addr, Expr::MaxU, zext, src),
+            Instruction::AMOSWAPH {
+                dest, addr, src, ..
+            } => swap(dest, Width::Half, addr, src),
+            Instruction::AMOADDH {
+                dest, addr, src, ..
+            } => amo(dest, Width::Half, addr, Expr::Add, sext, src),
+            Instruction::AMOXORH {
+                dest, addr, src, ..
+            } => amo(dest, Width::Half, addr, Expr::Xor, sext, src),
+            Instruction::AMOANDH {
+                dest, addr, src, ..
+            } => amo(dest, Width::Half, addr, Expr::And, sext, src),
+            Instruction::AMOORH {
+                dest, addr, src, ..
+            } => amo(dest, Width::Half, addr, Expr::Or, sext, src),
+            Instruction::AMOMINH {
+                dest, addr, src, ..
+            } => amo(dest, Width::Half, addr, Expr::Min, sext, src),
+            Instruction::AMOMAXH {
+                dest, addr, src, ..
+            } => amo(dest, Width::Half, addr, Expr::Max, sext, src),
+            Instruction::AMOMINUH {
+                dest, addr, src, ..
+            } => amo(dest, Width::Half, addr, Expr::MinU, zext, src),
+            Instruction::AMOMAXUH {
+                dest, addr, src, ..
+            } => amo(dest, Width::Half, addr, Expr::MaxU, zext, src),
+            Instruction::AMOCASB {
+                dest, addr, src, ..
+            } => compare_and_swap(dest, Width::Byte, addr, src),
+            Instruction::AMOCASH {
+                dest, addr, src, ..
+            } => compare_and_swap(dest, Width::Half, addr, src),
+            _ => return None,
+        })
+    }
+}
@@ -0,0 +1,499 @@
+//! The draft P extension, packed SIMD instructions that operate on the 8 and 16-bit elements of the integer registers.
+//!
+//! Every instruction is in the OP-P major opcode, which is the OP-VE major opcode of the vector crypto instructions, so
+//! it is only decoded as P when `Extensions::p` is enabled (and the `p` feature is). The instructions here are the
+//! SIMD data processing instructions of version 0.9 of the draft: the 8 and 16-bit additions, subtractions, shifts,
+//! comparisons, multiplies, minimums, maximums and clips. On RV32 the 8 and 16-bit multiplies write their 64-bit
+//! result to an even-odd register pair, named by the even register. The 64-bit and partial SIMD instructions are not
+//! supported.
+
+use std::fmt::{Display, Formatter};
+
+use crate::assembly::parse_int;
+use crate::config::{FormatOptions, Xlen};
+use crate::immediates::{Immediate, PImmediate};
+use crate::register::IRegister;
+
+/// The OP-P major opcode.
+pub(crate) const OPCODE: u32 = 0b1110111;
+
+/// The operations of `SimdInstruction::Binary`, named by their mnemonic (`SRA16U` is `sra16.u`).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum SimdOp {
+    ADD16,
+    RADD16,
+    URADD16,
+    KADD16,
+    UKADD16,
+    SUB16,
+    RSUB16,
+    URSUB16,
+    KSUB16,
+    UKSUB16,
+    CRAS16,
+    RCRAS16,
+    URCRAS16,
+    KCRAS16,
+    UKCRAS16,
+    CRSA16,
+    RCRSA16,
+    URCRSA16,
+    KCRSA16,
+    UKCRSA16,
+    ADD8,
+    RADD8,
+    URADD8,
+    KADD8,
+    UKADD8,
+    SUB8,
+    RSUB8,
+    URSUB8,
+    KSUB8,
+    UKSUB8,
+    SRA16,
+    SRA16U,
+    SRL16,
+    SRL16U,
+    SLL16,
+    KSLL16,
+    KSLRA16,
+    KSLRA16U,
+    SRA8,
+    SRA8U,
+    SRL8,
+    SRL8U,
+    SLL8,
+    KSLL8,
+    KSLRA8,
+    KSLRA8U,
+    CMPEQ16,
+    SCMPLT16,
+    SCMPLE16,
+    UCMPLT16,
+    UCMPLE16,
+    CMPEQ8,
+    SCMPLT8,
+    SCMPLE8,
+    UCMPLT8,
+    UCMPLE8,
+    SMUL16,
+    SMULX16,
+    UMUL16,
+    UMULX16,
+    SMUL8,
+    SMULX8,
+    UMUL8,
+    UMULX8,
+    KHM16,
+    KHMX16,
+    KHM8,
+    KHMX8,
+    SMIN16,
+    UMIN16,
+    SMAX16,
+    UMAX16,
+    SMIN8,
+    UMIN8,
+    SMAX8,
+    UMAX8,
+}
+
+/// The mnemonic and funct7 of each register-register operation, which all have a funct3 of 0.
+#[rustfmt::skip]
+const OPS: [(SimdOp, &str, u32); 76] = [
+    (SimdOp::ADD16, "add16", 0b0100000),
+    (SimdOp::RADD16, "radd16", 0b0000000),
+    (SimdOp::URADD16, "uradd16", 0b0010000),
+    (SimdOp::KADD16, "kadd16", 0b0001000),
+    (SimdOp::UKADD16, "ukadd16", 0b0011000),
+    (SimdOp::SUB16, "sub16", 0b0100001),
+    (SimdOp::RSUB16, "rsub16", 0b0000001),
+    (SimdOp::URSUB16, "ursub16", 0b0010001),
+    (SimdOp::KSUB16, "ksub16", 0b0001001),
+    (SimdOp::UKSUB16, "uksub16", 0b0011001),
+    (SimdOp::CRAS16, "cras16", 0b0100010),
+    (SimdOp::RCRAS16, "rcras16", 0b0000010),
+    (SimdOp::URCRAS16, "urcras16", 0b0010010),
+    (SimdOp::KCRAS16, "kcras16", 0b0001010),
+    (SimdOp::UKCRAS16, "ukcras16", 0b0011010),
+    (SimdOp::CRSA16, "crsa16", 0b0100011),
+    (SimdOp::RCRSA16, "rcrsa16", 0b0000011),
+    (SimdOp::URCRSA16, "urcrsa16", 0b0010011),
+    (SimdOp::KCRSA16, "kcrsa16", 0b0001011),
+    (SimdOp::UKCRSA16, "ukcrsa16", 0b0011011),
+    (SimdOp::ADD8, "add8", 0b0100100),
+    (SimdOp::RADD8, "radd8", 0b0000100),
+    (SimdOp::URADD8, "uradd8", 0b0010100),
+    (SimdOp::KADD8, "kadd8", 0b0001100),
+    (SimdOp::UKADD8, "ukadd8", 0b0011100),
+    (SimdOp::SUB8, "sub8", 0b0100101),
+    (SimdOp::RSUB8, "rsub8", 0b0000101),
+    (SimdOp::URSUB8, "ursub8", 0b0010101),
+    (SimdOp::KSUB8, "ksub8", 0b0001101),
+    (SimdOp::UKSUB8, "uksub8", 0b0011101),
+    (SimdOp::SRA16, "sra16", 0b0101000),
+    (SimdOp::SRA16U, "sra16.u", 0b0110000),
+    (SimdOp::SRL16, "srl16", 0b0101001),
+    (SimdOp::SRL16U, "srl16.u", 0b0110001),
+    (SimdOp::SLL16, "sll16", 0b0101010),
+    (SimdOp::KSLL16, "ksll16", 0b0110010),
+    (SimdOp::KSLRA16, "kslra16", 0b0101011),
+    (SimdOp::KSLRA16U, "kslra16.u", 0b0110011),
+    (SimdOp::SRA8, "sra8", 0b0101100),
+    (SimdOp::SRA8U, "sra8.u", 0b0110100),
+    (SimdOp::SRL8, "srl8", 0b0101101),
+    (SimdOp::SRL8U, "srl8.u", 0b0110101),
+    (SimdOp::SLL8, "sll8", 0b0101110),
+    (SimdOp::KSLL8, "ksll8", 0b0110110),
+    (SimdOp::KSLRA8, "kslra8", 0b0101111),
+    (SimdOp::KSLRA8U, "kslra8.u", 0b0110111),
+    (SimdOp::CMPEQ16, "cmpeq16", 0b0100110),
+    (SimdOp::SCMPLT16, "scmplt16", 0b0000110),
+    (SimdOp::SCMPLE16, "scmple16", 0b0001110),
+    (SimdOp::UCMPLT16, "ucmplt16", 0b0010110),
+    (SimdOp::UCMPLE16, "ucmple16", 0b0011110),
+    (SimdOp::CMPEQ8, "cmpeq8", 0b0100111),
+    (SimdOp::SCMPLT8, "scmplt8", 0b0000111),
+    (SimdOp::SCMPLE8, "scmple8", 0b0001111),
+    (SimdOp::UCMPLT8, "ucmplt8", 0b0010111),
+    (SimdOp::UCMPLE8, "ucmple8", 0b0011111),
+    (SimdOp::SMUL16, "smul16", 0b1010000),
+    (SimdOp::SMULX16, "smulx16", 0b1010001),
+    (SimdOp::UMUL16, "umul16", 0b1011000),
+    (SimdOp::UMULX16, "umulx16", 0b1011001),
+    (SimdOp::SMUL8, "smul8", 0b1010100),
+    (SimdOp::SMULX8, "smulx8", 0b1010101),
+    (SimdOp::UMUL8, "umul8", 0b1011100),
+    (SimdOp::UMULX8, "umulx8", 0b1011101),
+    (SimdOp::KHM16, "khm16", 0b1000011),
+    (SimdOp::KHMX16, "khmx16", 0b1001011),
+    (SimdOp::KHM8, "khm8", 0b1000111),
+    (SimdOp::KHMX8, "khmx8", 0b1001111),
+    (SimdOp::SMIN16, "smin16", 0b1000000),
+    (SimdOp::UMIN16, "umin16", 0b1001000),
+    (SimdOp::SMAX16, "smax16", 0b1000001),
+    (SimdOp::UMAX16, "umax16", 0b1001001),
+    (SimdOp::SMIN8, "smin8", 0b1000100),
+    (SimdOp::UMIN8, "umin8", 0b1001100),
+    (SimdOp::SMAX8, "smax8", 0b1000101),
+    (SimdOp::UMAX8, "umax8", 0b1001101),
+];
+
+/// The operations of `SimdInstruction::Unary`, which have a single source.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum SimdUnaryOp {
+    KABS16,
+    KABS8,
+    CLRS16,
+    CLZ16,
+    CLRS8,
+    CLZ8,
+    CLRS32,
+    CLZ32,
+}
+
+/// The mnemonic, funct7 and rs2 field of each unary operation.
+#[rustfmt::skip]
+const UNARY_OPS: [(SimdUnaryOp, &str, u32, u32); 8] = [
+    (SimdUnaryOp::KABS16, "kabs16", 0b1010110, 0b10001),
+    (SimdUnaryOp::KABS8, "kabs8", 0b1010110, 0b10000),
+    (SimdUnaryOp::CLRS16, "clrs16", 0b1010111, 0b01000),
+    (SimdUnaryOp::CLZ16, "clz16", 0b1010111, 0b01001),
+    (SimdUnaryOp::CLRS8, "clrs8", 0b1010111, 0b00000),
+    (SimdUnaryOp::CLZ8, "clz8", 0b1010111, 0b00001),
+    (SimdUnaryOp::CLRS32, "clrs32", 0b1010111, 0b11000),
+    (SimdUnaryOp::CLZ32, "clz32", 0b1010111, 0b11001),
+];
+
+/// The operations of `SimdInstruction::Immediate`, the shifts by a constant and the clips to a number of bits.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum SimdImmOp {
+    SRAI16,
+    SRAI16U,
+    SRLI16,
+    SRLI16U,
+    SLLI16,
+    KSLLI16,
+    SRAI8,
+    SRAI8U,
+    SRLI8,
+    SRLI8U,
+    SLLI8,
+    KSLLI8,
+    SCLIP16,
+    UCLIP16,
+    SCLIP8,
+    UCLIP8,
+    SCLIP32,
+    UCLIP32,
+}
+
+/// The mnemonic of each immediate operation, the bits above its immediate and the width of its immediate, which is
+/// 3 bits for the 8-bit elements, 4 bits for the 16-bit elements and 5 bits for the 32-bit elements.
+// the unusual bit groupings separate the funct7 from the bits above the immediate
+#[rustfmt::skip]
+#[allow(clippy::unusual_byte_groupings)]
+const IMM_OPS: [(SimdImmOp, &str, u32, u32); 18] = [
+    (SimdImmOp::SRAI16, "srai16", 0b0111000_0, 4),
+    (SimdImmOp::SRAI16U, "srai16.u", 0b0111000_1, 4),
+    (SimdImmOp::SRLI16, "srli16", 0b0111001_0, 4),
+    (SimdImmOp::SRLI16U, "srli16.u", 0b0111001_1, 4),
+    (SimdImmOp::SLLI16, "slli16", 0b0111010_0, 4),
+    (SimdImmOp::KSLLI16, "kslli16", 0b0111010_1, 4),
+    (SimdImmOp::SRAI8, "srai8", 0b0111100_00, 3),
+    (SimdImmOp::SRAI8U, "srai8.u", 0b0111100_01, 3),
+    (SimdImmOp::SRLI8, "srli8", 0b0111101_00, 3),
+    (SimdImmOp::SRLI8U, "srli8.u", 0b0111101_01, 3),
+    (SimdImmOp::SLLI8, "slli8", 0b0111110_00, 3),
+    (SimdImmOp::KSLLI8, "kslli8", 0b0111110_01, 3),
+    (SimdImmOp::SCLIP16, "sclip16", 0b1000010_0, 4),
+    (SimdImmOp::UCLIP16, "uclip16", 0b1000010_1, 4),
+    (SimdImmOp::SCLIP8, "sclip8", 0b1000110_00, 3),
+    (SimdImmOp::UCLIP8, "uclip8", 0b1000110_10, 3),
+    (SimdImmOp::SCLIP32, "sclip32", 0b1110010, 5),
+    (SimdImmOp::UCLIP32, "uclip32", 0b1111010, 5),
+];
+
+impl SimdOp {
+    pub fn mnemonic(self) -> &'static str {
+        OPS.iter().find(|(op, ..)| *op == self).unwrap().1
+    }
+
+    /// Whether the operation writes a 64-bit result, which takes an even-odd register pair on RV32.
+    pub fn is_widening(self) -> bool {
+        matches!(
+            self,
+            SimdOp::SMUL16
+                | SimdOp::SMULX16
+                | SimdOp::UMUL16
+                | SimdOp::UMULX16
+                | SimdOp::SMUL8
+                | SimdOp::SMULX8
+                | SimdOp::UMUL8
+                | SimdOp::UMULX8
+        )
+    }
+}
+
+impl SimdUnaryOp {
+    pub fn mnemonic(self) -> &'static str {
+        UNARY_OPS.iter().find(|(op, ..)| *op == self).unwrap().1
+    }
+}
+
+impl SimdImmOp {
+    pub fn mnemonic(self) -> &'static str {
+        IMM_OPS.iter().find(|(op, ..)| *op == self).unwrap().1
+    }
+
+    /// The number of bits of the immediate.
+    pub fn width(self) -> u32 {
+        IMM_OPS.iter().find(|(op, ..)| *op == self).unwrap().3
+    }
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum SimdInstruction {
+    /// `add16 rd,rs1,rs2`
+    Binary {
+        op: SimdOp,
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// `kabs16 rd,rs1`
+    Unary {
+        op: SimdUnaryOp,
+        dest: IRegister,
+        src: IRegister,
+    },
+    /// `srai16 rd,rs1,imm`, where the immediate is a shift amount or the number of bits to clip to.
+    Immediate {
+        op: SimdImmOp,
+        dest: IRegister,
+        src: IRegister,
+        imm: PImmediate,
+    },
+}
+
+impl SimdInstruction {
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            SimdInstruction::Binary { op, .. } => op.mnemonic(),
+            SimdInstruction::Unary { op, .. } => op.mnemonic(),
+            SimdInstruction::Immediate { op, .. } => op.mnemonic(),
+        }
+    }
+
+    /// Whether the instruction multiplies, which are the widening multiplies and the Q15 and Q7 multiplies (khm16).
+    pub fn is_multiply(&self) -> bool {
+        matches!(
+            self,
+            SimdInstruction::Binary { op, .. } if op.is_widening()
+                || matches!(op, SimdOp::KHM16 | SimdOp::KHMX16 | SimdOp::KHM8 | SimdOp::KHMX8)
+        )
+    }
+
+    /// Returns true for the mnemonics of the instructions.
+    pub(crate) fn is_mnemonic(mnemonic: &str) -> bool {
+        OPS.iter().any(|(_, m, _)| *m == mnemonic)
+            || UNARY_OPS.iter().any(|(_, m, ..)| *m == mnemonic)
+            || IMM_OPS.iter().any(|(_, m, ..)| *m == mnemonic)
+    }
+
+    /// Decodes an instruction in the OP-P major opcode.
+    pub fn decode(instruction: u32, xlen: Xlen) -> Result<Self, String> {
+        let funct3 = (instruction >> 12) & 0b111;
+        let funct7 = instruction >> 25;
+        let rd = IRegister::from_int((instruction >> 7) & 0b1_1111);
+        let rs1 = IRegister::from_int((instruction >> 15) & 0b1_1111);
+        let rs2 = (instruction >> 20) & 0b1_1111;
+        if funct3 != 0 {
+            return Err(format!("unknown P funct3: {funct3:#05b}"));
+        }
+        if let Some((op, ..)) = OPS.iter().find(|(_, _, f)| *f == funct7) {
+            if xlen == Xlen::Rv32 && op.is_widening() && rd.number() % 2 == 1 {
+                return Err(format!(
+                    "{} on RV32 writes a register pair and must use an even register, got {rd}",
+                    op.mnemonic()
+                ));
+            }
+            return Ok(SimdInstruction::Binary {
+                op: *op,
+                dest: rd,
+                src1: rs1,
+                src2: IRegister::from_int(rs2),
+            });
+        }
+        if let Some((op, ..)) = UNARY_OPS
+            .iter()
+            .find(|(_, _, f, r)| *f == funct7 && *r == rs2)
+        {
+            return Ok(SimdInstruction::Unary {
+                op: *op,
+                dest: rd,
+                src: rs1,
+            });
+        }
+        if let Some((op, _, _, width)) = IMM_OPS
+            .iter()
+            .find(|(_, _, high, width)| instruction >> (20 + width) == *high)
+        {
+            let imm = (instruction >> 20) & ((1 << width) - 1);
+            return Ok(SimdInstruction::Immediate {
+                op: *op,
+                dest: rd,
+                src: rs1,
+                imm: PImmediate::try_from(imm as i64).unwrap(),
+            });
+        }
+        Err(format!("unknown P funct7: {funct7:#09b}"))
+    }
+
+    pub fn encode(&self) -> u32 {
+        match self {
+            SimdInstruction::Binary {
+                op,
+                dest,
+                src1,
+                src2,
+            } => {
+                let (.., funct7) = OPS.iter().find(|(o, ..)| o == op).unwrap();
+                funct7 << 25 | src2.rs2() | src1.rs1() | dest.rd() | OPCODE
+            }
+            SimdInstruction::Unary { op, dest, src } => {
+                let (.., funct7, rs2) = UNARY_OPS.iter().find(|(o, ..)| o == op).unwrap();
+                funct7 << 25 | rs2 << 20 | src.rs1() | dest.rd() | OPCODE
+            }
+            SimdInstruction::Immediate { op, dest, src, imm } => {
+                let (.., high, width) = IMM_OPS.iter().find(|(o, ..)| o == op).unwrap();
+                high << (20 + width) | imm.to_u32() | src.rs1() | dest.rd() | OPCODE
+            }
+        }
+    }
+
+    /// Assembles an instruction from its full mnemonic (`srai16.u`).
+    pub(crate) fn assemble(mnemonic: &str, operands: &[&str], xlen: Xlen) -> Result<Self, String> {
+        let register = |str: &str| IRegister::from_string(str);
+        let instruction = if let Some((op, ..)) = OPS.iter().find(|(_, m, _)| *m == mnemonic) {
+            let [dest, src1, src2] = operands else {
+                return Err(format!("{mnemonic} requires 3 operands"));
+            };
+            SimdInstruction::Binary {
+                op: *op,
+                dest: register(dest)?,
+                src1: register(src1)?,
+                src2: register(src2)?,
+            }
+        } else if let Some((op, ..)) = UNARY_OPS.iter().find(|(_, m, ..)| *m == mnemonic) {
+            let [dest, src] = operands else {
+                return Err(format!("{mnemonic} requires 2 operands"));
+            };
+            SimdInstruction::Unary {
+                op: *op,
+                dest: register(dest)?,
+                src: register(src)?,
+            }
+        } else if let Some((op, _, _, width)) = IMM_OPS.iter().find(|(_, m, ..)| *m == mnemonic) {
+            let [dest, src, imm] = operands else {
+                return Err(format!("{mnemonic} requires 3 operands"));
+            };
+            let imm = parse_int(imm)?;
+            if !(0..1 << width).contains(&imm) {
+                return Err(format!(
+                    "the immediate of {mnemonic} must be between 0 and {}, got {imm}",
+                    (1 << width) - 1
+                ));
+            }
+            SimdInstruction::Immediate {
+                op: *op,
+                dest: register(dest)?,
+                src: register(src)?,
+                imm: PImmediate::try_from(imm)?,
+            }
+        } else {
+            return Err(format!("unknown mnemonic: {mnemonic}"));
+        };
+        // check the register pairs of RV32 by decoding
+        SimdInstruction::decode(instruction.encode(), xlen)
+    }
+
+    pub(crate) fn fmt_with(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &FormatOptions,
+    ) -> Result<(), std::fmt::Error> {
+        let mnemonic = self.mnemonic();
+        let style = options.register_style;
+        match self {
+            SimdInstruction::Binary {
+                dest, src1, src2, ..
+            } => write!(
+                f,
+                "{mnemonic} {dest},{src1},{src2}",
+                dest = dest.display_with(style),
+                src1 = src1.display_with(style),
+                src2 = src2.display_with(style)
+            ),
+            SimdInstruction::Unary { dest, src, .. } => write!(
+                f,
+                "{mnemonic} {dest},{src}",
+                dest = dest.display_with(style),
+                src = src.display_with(style)
+            ),
+            SimdInstruction::Immediate { dest, src, imm, .. } => write!(
+                f,
+                "{mnemonic} {dest},{src},{imm}",
+                dest = dest.display_with(style),
+                src = src.display_with(style),
+                imm = imm.display_with(options.immediate_style)
+            ),
+        }
+    }
+}
+
+impl Display for SimdInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.fmt_with(f, &FormatOptions::default())
+    }
+}
@@ -0,0 +1,836 @@
+//! The XThead vendor extensions of T-Head cores such as the C906 and C910.
+//!
+//! XTheadBa, XTheadBb, XTheadCmo, XTheadCondMov and XTheadMemPair are supported. Every instruction is in the custom-0
+//! major opcode and is written with the `th.` prefix used by GNU and LLVM (`th.addsl a0,a1,a2,1`). Decoding custom-0
+//! as XThead requires both the `xthead` feature and the `Extensions` field of each extension, since other vendors use
+//! the same opcode.
+
+use std::fmt::{Display, Formatter};
+
+use crate::assembly::parse_int;
+use crate::config::{FormatOptions, Xlen};
+use crate::immediates::{Shamt, ShamtW, ThImm2, ThLsb, ThMsb};
+use crate::register::IRegister;
+
+/// The custom-0 major opcode.
+pub(crate) const OPCODE: u32 = 0b0001011;
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum TheadInstruction {
+    //
+    // XTheadBa
+    //
+    /// `src1 + (src2 << shift)`
+    ADDSL {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+        shift: ThImm2,
+    },
+    //
+    // XTheadBb
+    //
+    /// Rotate right by a constant.
+    SRRI {
+        dest: IRegister,
+        src: IRegister,
+        shamt: Shamt,
+    },
+    /// Rotate the low word right by a constant and sign extend, RV64 only
+    SRRIW {
+        dest: IRegister,
+        src: IRegister,
+        shamt: ShamtW,
+    },
+    /// Extract bits `msb` to `lsb`, sign extending.
+    EXT {
+        dest: IRegister,
+        src: IRegister,
+        msb: ThMsb,
+        lsb: ThLsb,
+    },
+    /// Extract bits `msb` to `lsb`, zero extending.
+    EXTU {
+        dest: IRegister,
+        src: IRegister,
+        msb: ThMsb,
+        lsb: ThLsb,
+    },
+    /// Count the leading ones.
+    FF0 { dest: IRegister, src: IRegister },
+    /// Count the leading zeros.
+    FF1 { dest: IRegister, src: IRegister },
+    /// Reverse the bytes.
+    REV { dest: IRegister, src: IRegister },
+    /// Reverse the bytes of the low word and sign extend, RV64 only
+    REVW { dest: IRegister, src: IRegister },
+    /// Set the bytes that are zero to 0xff and the others to zero.
+    TSTNBZ { dest: IRegister, src: IRegister },
+    /// Test bit `bit`, giving 1 if it is set.
+    TST {
+        dest: IRegister,
+        src: IRegister,
+        bit: Shamt,
+    },
+    //
+    // XTheadCondMov
+    //
+    /// Move `src1` to `dest` if `src2` is zero.
+    MVEQZ {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    /// Move `src1` to `dest` if `src2` is not zero.
+    MVNEZ {
+        dest: IRegister,
+        src1: IRegister,
+        src2: IRegister,
+    },
+    //
+    // XTheadMemPair, which access two consecutive values at `base + (offset << 3)` for words and
+    // `base + (offset << 4)` for doublewords
+    //
+    /// Load two words, sign extending.
+    LWD {
+        dest1: IRegister,
+        dest2: IRegister,
+        base: IRegister,
+        offset: ThImm2,
+    },
+    /// Load two words, zero extending, RV64 only
+    LWUD {
+        dest1: IRegister,
+        dest2: IRegister,
+        base: IRegister,
+        offset: ThImm2,
+    },
+    /// Load two doublewords, RV64 only
+    LDD {
+        dest1: IRegister,
+        dest2: IRegister,
+        base: IRegister,
+        offset: ThImm2,
+    },
+    /// Store two words.
+    SWD {
+        src1: IRegister,
+        src2: IRegister,
+        base: IRegister,
+        offset: ThImm2,
+    },
+    /// Store two doublewords, RV64 only
+    SDD {
+        src1: IRegister,
+        src2: IRegister,
+        base: IRegister,
+        offset: ThImm2,
+    },
+    //
+    // XTheadCmo
+    //
+    /// Manage the cache line at an address, or at a set and way, given by `src`.
+    CACHELINE { op: CacheLineOp, src: IRegister },
+    /// Manage a whole cache.
+    CACHE { op: CacheOp },
+}
+
+/// The XTheadCmo operations on a cache line, whose names say the cache, the operation (clean, invalidate or both) and
+/// how the line is given (virtual or physical address, or set and way).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum CacheLineOp {
+    DCACHECSW,
+    DCACHEISW,
+    DCACHECISW,
+    /// Clean to the L1 cache.
+    DCACHECVAL1,
+    DCACHECVA,
+    DCACHEIVA,
+    DCACHECIVA,
+    /// Clean to the L1 cache.
+    DCACHECPAL1,
+    DCACHECPA,
+    DCACHEIPA,
+    DCACHECIPA,
+    ICACHEIVA,
+    ICACHEIPA,
+}
+
+/// The XTheadCmo operations on a whole cache.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum CacheOp {
+    DCACHECALL,
+    DCACHEIALL,
+    DCACHECIALL,
+    ICACHEIALL,
+    /// Invalidate the instruction caches of every core.
+    ICACHEIALLS,
+    L2CACHECALL,
+    L2CACHEIALL,
+    L2CACHECIALL,
+}
+
+/// The cache line operations with their mnemonic without the `th.` prefix and the rs2 field that selects them.
+const CACHE_LINE_OPS: [(CacheLineOp, &str, u32); 13] = [
+    (CacheLineOp::DCACHECSW, "dcache.csw", 0b00001),
+    (CacheLineOp::DCACHEISW, "dcache.isw", 0b00010),
+    (CacheLineOp::DCACHECISW, "dcache.cisw", 0b00011),
+    (CacheLineOp::DCACHECVAL1, "dcache.cval1", 0b00100),
+    (CacheLineOp::DCACHECVA, "dcache.cva", 0b00101),
+    (CacheLineOp::DCACHEIVA, "dcache.iva", 0b00110),
+    (CacheLineOp::DCACHECIVA, "dcache.civa", 0b00111),
+    (CacheLineOp::DCACHECPAL1, "dcache.cpal1", 0b01000),
+    (CacheLineOp::DCACHECPA, "dcache.cpa", 0b01001),
+    (CacheLineOp::DCACHEIPA, "dcache.ipa", 0b01010),
+    (CacheLineOp::DCACHECIPA, "dcache.cipa", 0b01011),
+    (CacheLineOp::ICACHEIVA, "icache.iva", 0b10000),
+    (CacheLineOp::ICACHEIPA, "icache.ipa", 0b11000),
+];
+
+/// The whole cache operations with their mnemonic without the `th.` prefix and the rs2 field that selects them.
+const CACHE_OPS: [(CacheOp, &str, u32); 8] = [
+    (CacheOp::DCACHECALL, "dcache.call", 0b00001),
+    (CacheOp::DCACHEIALL, "dcache.iall", 0b00010),
+    (CacheOp::DCACHECIALL, "dcache.ciall", 0b00011),
+    (CacheOp::ICACHEIALL, "icache.iall", 0b10000),
+    (CacheOp::ICACHEIALLS, "icache.ialls", 0b10001),
+    (CacheOp::L2CACHECALL, "l2cache.call", 0b10101),
+    (CacheOp::L2CACHEIALL, "l2cache.iall", 0b10110),
+    (CacheOp::L2CACHECIALL, "l2cache.ciall", 0b10111),
+];
+
+/// The funct7 of the XTheadBb instructions with one register operand, which all have a funct3 of 1 and rs2 of 0.
+const UNARY_FUNCT7: [(&str, u32); 5] = [
+    ("ff0", 0b1000010),
+    ("ff1", 0b1000011),
+    ("rev", 0b1000001),
+    ("revw", 0b1001000),
+    ("tstnbz", 0b1000000),
+];
+
+/// The funct5 of the XTheadMemPair instructions, which have a funct3 of 4 for loads and 5 for stores.
+const PAIR_FUNCT5: [(&str, u32); 5] = [
+    ("lwd", 0b11100),
+    ("lwud", 0b11110),
+    ("ldd", 0b11111),
+    ("swd", 0b11100),
+    ("sdd", 0b11111),
+];
+
+impl TheadInstruction {
+    /// Constructs an instruction with one register operand from its mnemonic without the `th.` prefix.
+    fn unary(name: &str, dest: IRegister, src: IRegister) -> Option<Self> {
+        Some(match name {
+            "ff0" => TheadInstruction::FF0 { dest, src },
+            "ff1" => TheadInstruction::FF1 { dest, src },
+            "rev" => TheadInstruction::REV { dest, src },
+            "revw" => TheadInstruction::REVW { dest, src },
+            "tstnbz" => TheadInstruction::TSTNBZ { dest, src },
+            _ => return None,
+        })
+    }
+
+    /// Constructs a load or store of a pair from its mnemonic without the `th.` prefix.
+    fn pair(
+        name: &str,
+        first: IRegister,
+        second: IRegister,
+        base: IRegister,
+        offset: ThImm2,
+    ) -> Option<Self> {
+        let (dest1, dest2, src1, src2) = (first, second, first, second);
+        Some(match name {
+            "lwd" => TheadInstruction::LWD {
+                dest1,
+                dest2,
+                base,
+                offset,
+            },
+            "lwud" => TheadInstruction::LWUD {
+                dest1,
+                dest2,
+                base,
+                offset,
+            },
+            "ldd" => TheadInstruction::LDD {
+                dest1,
+                dest2,
+                base,
+                offset,
+            },
+            "swd" => TheadInstruction::SWD {
+                src1,
+                src2,
+                base,
+                offset,
+            },
+            "sdd" => TheadInstruction::SDD {
+                src1,
+                src2,
+                base,
+                offset,
+            },
+            _ => return None,
+        })
+    }
+
+    /// The mnemonic, without the `th.` prefix.
+    pub fn mnemonic(&self) -> &'static str {
+        match self {
+            TheadInstruction::ADDSL { .. } => "addsl",
+            TheadInstruction::SRRI { .. } => "srri",
+            TheadInstruction::SRRIW { .. } => "srriw",
+            TheadInstruction::EXT { .. } => "ext",
+            TheadInstruction::EXTU { .. } => "extu",
+            TheadInstruction::FF0 { .. } => "ff0",
+            TheadInstruction::FF1 { .. } => "ff1",
+            TheadInstruction::REV { .. } => "rev",
+            TheadInstruction::REVW { .. } => "revw",
+            TheadInstruction::TSTNBZ { .. } => "tstnbz",
+            TheadInstruction::TST { .. } => "tst",
+            TheadInstruction::MVEQZ { .. } => "mveqz",
+            TheadInstruction::MVNEZ { .. } => "mvnez",
+            TheadInstruction::LWD { .. } => "lwd",
+            TheadInstruction::LWUD { .. } => "lwud",
+            TheadInstruction::LDD { .. } => "ldd",
+            TheadInstruction::SWD { .. } => "swd",
+            TheadInstruction::SDD { .. } => "sdd",
+            TheadInstruction::CACHELINE { op, .. } => {
+                CACHE_LINE_OPS.iter().find(|(o, ..)| o == op).unwrap().1
+            }
+            TheadInstruction::CACHE { op } => CACHE_OPS.iter().find(|(o, ..)| o == op).unwrap().1,
+        }
+    }
+
+    /// Returns true for the loads of XTheadMemPair.
+    pub fn is_load(&self) -> bool {
+        matches!(
+            self,
+            TheadInstruction::LWD { .. }
+                | TheadInstruction::LWUD { .. }
+                | TheadInstruction::LDD { .. }
+        )
+    }
+
+    /// Returns true for the stores of XTheadMemPair.
+    pub fn is_store(&self) -> bool {
+        matches!(
+            self,
+            TheadInstruction::SWD { .. } | TheadInstruction::SDD { .. }
+        )
+    }
+
+    /// Returns true for the cache management instructions of XTheadCmo.
+    pub fn is_cache_management(&self) -> bool {
+        matches!(
+            self,
+            TheadInstruction::CACHELINE { .. } | TheadInstruction::CACHE { .. }
+        )
+    }
+
+    /// Decodes an instruction in the custom-0 major opcode.
+    pub fn decode(instruction: u32, xlen: Xlen) -> Result<Self, String> {
+        let funct3 = (instruction >> 12) & 0b111;
+        let funct7 = instruction >> 25;
+        let rd = IRegister::from_int((instruction >> 7) & 0b1_1111);
+        let rs1 = IRegister::from_int((instruction >> 15) & 0b1_1111);
+        let rs2 = IRegister::from_int((instruction >> 20) & 0b1_1111);
+        let rv32 = xlen == Xlen::Rv32;
+        let i = match funct3 {
+            0b000 => {
+                let funct5 = (instruction >> 20) & 0b1_1111;
+                if rd != IRegister::Zero {
+                    return Err(format!(
+                        "the XTheadCmo instructions must not write a register, got {rd}"
+                    ));
+                }
+                match funct7 {
+                    0b0000001 => TheadInstruction::CACHELINE {
+                        op: CACHE_LINE_OPS
+                            .iter()
+                            .find(|(.., f)| *f == funct5)
+                            .ok_or_else(|| format!("unknown XTheadCmo operation: {funct5:#07b}"))?
+                            .0,
+                        src: rs1,
+                    },
+                    0b0000000 if rs1 == IRegister::Zero => TheadInstruction::CACHE {
+                        op: CACHE_OPS
+                            .iter()
+                            .find(|(.., f)| *f == funct5)
+                            .ok_or_else(|| format!("unknown XTheadCmo operation: {funct5:#07b}"))?
+                            .0,
+                    },
+                    0b0000000 => {
+                        return Err(format!(
+                            "the XTheadCmo operations on a whole cache take no register, got {rs1}"
+                        ));
+                    }
+                    _ => return Err(format!("unknown XTheadCmo funct7: {funct7:#09b}")),
+                }
+            }
+            0b001 if funct7 >> 2 == 0 => TheadInstruction::ADDSL {
+                dest: rd,
+                src1: rs1,
+                src2: rs2,
+                shift: ThImm2::from_u32(instruction),
+            },
+            0b001 if funct7 >> 1 == 0b000100 => TheadInstruction::SRRI {
+                dest: rd,
+                src: rs1,
+                shamt: Shamt::from_u32(instruction),
+            },
+            0b001 if funct7 == 0b0001010 => TheadInstruction::SRRIW {
+                dest: rd,
+                src: rs1,
+                shamt: ShamtW::from_u32(instruction),
+            },
+            0b001 if funct7 >> 1 == 0b100010 => TheadInstruction::TST {
+                dest: rd,
+                src: rs1,
+                bit: Shamt::from_u32(instruction),
+            },
+            0b001 if funct7 == 0b0100000 => TheadInstruction::MVEQZ {
+                dest: rd,
+                src1: rs1,
+                src2: rs2,
+            },
+            0b001 if funct7 == 0b0100001 => TheadInstruction::MVNEZ {
+                dest: rd,
+                src1: rs1,
+                src2: rs2,
+            },
+            0b001 => {
+                let name = UNARY_FUNCT7
+                    .iter()
+                    .find(|(_, f)| *f == funct7)
+                    .map(|(name, _)| *name)
+                    .ok_or_else(|| format!("unknown XTheadBb funct7: {funct7:#09b}"))?;
+                if rs2 != IRegister::Zero {
+                    return Err(format!("th.{name} takes one register, got rs2 of {rs2}"));
+                }
+                TheadInstruction::unary(name, rd, rs1).unwrap()
+            }
+            0b010 | 0b011 => {
+                let msb = ThMsb::from_u32(instruction);
+                let lsb = ThLsb::from_u32(instruction);
+                if rv32 && (msb.val() >= 32 || lsb.val() >= 32) {
+                    return Err(format!(
+                        "the bit positions of th.ext and th.extu must be less than 32 on RV32, got {msb} and {lsb}"
+                    ));
+                }
+                if funct3 == 0b010 {
+                    TheadInstruction::EXT {
+                        dest: rd,
+                        src: rs1,
+                        msb,
+                        lsb,
+                    }
+                } else {
+                    TheadInstruction::EXTU {
+                        dest: rd,
+                        src: rs1,
+                        msb,
+                        lsb,
+                    }
+                }
+            }
+            0b100 | 0b101 => {
+                let funct5 = instruction >> 27;
+                let names = if funct3 == 0b100 {
+                    &PAIR_FUNCT5[..3]
+                } else {
+                    &PAIR_FUNCT5[3..]
+                };
+                let name = names
+                    .iter()
+                    .find(|(_, f)| *f == funct5)
+                    .map(|(name, _)| *name)
+                    .ok_or_else(|| format!("unknown XTheadMemPair funct5: {funct5:#07b}"))?;
+                let instruction =
+                    TheadInstruction::pair(name, rd, rs2, rs1, ThImm2::from_u32(instruction))
+                        .unwrap();
+                if instruction.is_load() && (rd == rs2 || rd == rs1 || rs2 == rs1) {
+                    return Err(format!(
+                        "th.{name} must load two registers that differ from each other and from the base, got {rd}, {rs2} and {rs1}"
+                    ));
+                }
+                instruction
+            }
+            _ => return Err(format!("unknown XThead funct3: {funct3:#05b}")),
+        };
+        match i {
+            TheadInstruction::SRRI { shamt: bits, .. }
+            | TheadInstruction::TST { bit: bits, .. }
+                if rv32 && bits.val() >= 32 =>
+            {
+                Err(format!(
+                    "the immediate of th.{} must be less than 32 on RV32, got {bits}",
+                    i.mnemonic()
+                ))
+            }
+            TheadInstruction::SRRIW { .. }
+            | TheadInstruction::REVW { .. }
+            | TheadInstruction::LWUD { .. }
+            | TheadInstruction::LDD { .. }
+            | TheadInstruction::SDD { .. }
+                if rv32 =>
+            {
+                Err(format!("th.{} is only available on RV64", i.mnemonic()))
+            }
+            _ => Ok(i),
+        }
+    }
+
+    // zero fields are kept so that every encoding lists all of its fields
+    #[allow(clippy::identity_op)]
+    pub fn encode(&self) -> u32 {
+        let unary = |dest: &IRegister, src: &IRegister| {
+            let (_, funct7) = UNARY_FUNCT7
+                .iter()
+                .find(|(name, _)| *name == self.mnemonic())
+                .unwrap();
+            funct7 << 25 | src.rs1() | 0b001 << 12 | dest.rd() | OPCODE
+        };
+        let pair = |funct3: u32, first: &IRegister, second: &IRegister, base: &IRegister| {
+            let (_, funct5) = PAIR_FUNCT5
+                .iter()
+                .find(|(name, _)| *name == self.mnemonic())
+                .unwrap();
+            funct5 << 27 | second.rs2() | base.rs1() | funct3 << 12 | first.rd() | OPCODE
+        };
+        match self {
+            TheadInstruction::ADDSL {
+                dest,
+                src1,
+                src2,
+                shift,
+            } => shift.to_u32() | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | OPCODE,
+            TheadInstruction::SRRI { dest, src, shamt } => {
+                0b000100 << 26 | shamt.to_u32() | src.rs1() | 0b001 << 12 | dest.rd() | OPCODE
+            }
+            TheadInstruction::SRRIW { dest, src, shamt } => {
+                0b0001010 << 25 | shamt.to_u32() | src.rs1() | 0b001 << 12 | dest.rd() | OPCODE
+            }
+            TheadInstruction::TST { dest, src, bit } => {
+                0b100010 << 26 | bit.to_u32() | src.rs1() | 0b001 << 12 | dest.rd() | OPCODE
+            }
+            TheadInstruction::EXT {
+                dest,
+                src,
+                msb,
+                lsb,
+            } => msb.to_u32() | lsb.to_u32() | src.rs1() | 0b010 << 12 | dest.rd() | OPCODE,
+            TheadInstruction::EXTU {
+                dest,
+                src,
+                msb,
+                lsb,
+            } => msb.to_u32() | lsb.to_u32() | src.rs1() | 0b011 << 12 | dest.rd() | OPCODE,
+            TheadInstruction::FF0 { dest, src }
+            | TheadInstruction::FF1 { dest, src }
+            | TheadInstruction::REV { dest, src }
+            | TheadInstruction::REVW { dest, src }
+            | TheadInstruction::TSTNBZ { dest, src } => unary(dest, src),
+            TheadInstruction::MVEQZ { dest, src1, src2 } => {
+                0b0100000 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | OPCODE
+            }
+            TheadInstruction::MVNEZ { dest, src1, src2 } => {
+                0b0100001 << 25 | src2.rs2() | src1.rs1() | 0b001 << 12 | dest.rd() | OPCODE
+            }
+            TheadInstruction::LWD {
+                dest1,
+                dest2,
+                base,
+                offset,
+            }
+            | TheadInstruction::LWUD {
+                dest1,
+                dest2,
+                base,
+                offset,
+            }
+            | TheadInstruction::LDD {
+                dest1,
+                dest2,
+                base,
+                offset,
+            } => offset.to_u32() | pair(0b100, dest1, dest2, base),
+            TheadInstruction::SWD {
+                src1,
+                src2,
+                base,
+                offset,
+            }
+            | TheadInstruction::SDD {
+                src1,
+                src2,
+                base,
+                offset,
+            } => offset.to_u32() | pair(0b101, src1, src2, base),
+            TheadInstruction::CACHELINE { op, src } => {
+                let (.., funct5) = CACHE_LINE_OPS.iter().find(|(o, ..)| o == op).unwrap();
+                0b0000001 << 25 | funct5 << 20 | src.rs1() | 0b000 << 12 | OPCODE
+            }
+            TheadInstruction::CACHE { op } => {
+                let (.., funct5) = CACHE_OPS.iter().find(|(o, ..)| o == op).unwrap();
+                0b0000000 << 25 | funct5 << 20 | 0b000 << 12 | OPCODE
+            }
+        }
+    }
+
+    /// The amount that the offset of a load or store of a pair is shifted by, 3 for words and 4 for doublewords.
+    fn pair_shift(name: &str) -> i64 {
+        if matches!(name, "ldd" | "sdd") { 4 } else { 3 }
+    }
+
+    /// Assembles an instruction from its mnemonic without the `th.` prefix, split at the dots.
+    pub(crate) fn assemble(
+        mnemonics: &[&str],
+        operands: &[&str],
+        xlen: Xlen,
+    ) -> Result<Self, String> {
+        let name = mnemonics.join(".");
+        let register = |str: &str| IRegister::from_string(str);
+        let instruction = match (name.as_str(), operands) {
+            ("addsl", [dest, src1, src2, shift]) => TheadInstruction::ADDSL {
+                dest: register(dest)?,
+                src1: register(src1)?,
+                src2: register(src2)?,
+                shift: ThImm2::try_from(parse_int(shift)?)?,
+            },
+            ("srri", [dest, src, shamt]) => TheadInstruction::SRRI {
+                dest: register(dest)?,
+                src: register(src)?,
+                shamt: Shamt::try_from(parse_int(shamt)?)?,
+            },
+            ("srriw", [dest, src, shamt]) => TheadInstruction::SRRIW {
+                dest: register(dest)?,
+                src: register(src)?,
+                shamt: ShamtW::try_from(parse_int(shamt)?)?,
+            },
+            ("tst", [dest, src, bit]) => TheadInstruction::TST {
+                dest: register(dest)?,
+                src: register(src)?,
+                bit: Shamt::try_from(parse_int(bit)?)?,
+            },
+            ("ext" | "extu", [dest, src, msb, lsb]) => {
+                let dest = register(dest)?;
+                let src = register(src)?;
+                let msb = ThMsb::try_from(parse_int(msb)?)?;
+                let lsb = ThLsb::try_from(parse_int(lsb)?)?;
+                if name == "ext" {
+                    TheadInstruction::EXT {
+                        dest,
+                        src,
+                        msb,
+                        lsb,
+                    }
+                } else {
+                    TheadInstruction::EXTU {
+                        dest,
+                        src,
+                        msb,
+                        lsb,
+                    }
+                }
+            }
+            ("mveqz" | "mvnez", [dest, src1, src2]) => {
+                let dest = register(dest)?;
+                let src1 = register(src1)?;
+                let src2 = register(src2)?;
+                if name == "mveqz" {
+                    TheadInstruction::MVEQZ { dest, src1, src2 }
+                } else {
+                    TheadInstruction::MVNEZ { dest, src1, src2 }
+                }
+            }
+            (_, [dest, src]) if UNARY_FUNCT7.iter().any(|(n, _)| *n == name) => {
+                TheadInstruction::unary(&name, register(dest)?, register(src)?).unwrap()
+            }
+            (_, [first, second, base, offset, shift])
+                if PAIR_FUNCT5.iter().any(|(n, _)| *n == name) =>
+            {
+                let base = base
+                    .strip_prefix('(')
+                    .and_then(|base| base.strip_suffix(')'))
+                    .ok_or_else(|| {
+                        format!("the base of th.{name} must be in parentheses, got {base}")
+                    })?;
+                let expected = TheadInstruction::pair_shift(&name);
+                if parse_int(shift)? != expected {
+                    return Err(format!(
+                        "the last operand of th.{name} must be {expected}, got {shift}"
+                    ));
+                }
+                TheadInstruction::pair(
+                    &name,
+                    register(first)?,
+                    register(second)?,
+                    register(base)?,
+                    ThImm2::try_from(parse_int(offset)?)?,
+                )
+                .unwrap()
+            }
+            (_, [src]) if CACHE_LINE_OPS.iter().any(|(_, n, _)| *n == name) => {
+                TheadInstruction::CACHELINE {
+                    op: CACHE_LINE_OPS
+                        .iter()
+                        .find(|(_, n, _)| *n == name)
+                        .unwrap()
+                        .0,
+                    src: register(src)?,
+                }
+            }
+            (_, []) if CACHE_OPS.iter().any(|(_, n, _)| *n == name) => TheadInstruction::CACHE {
+                op: CACHE_OPS.iter().find(|(_, n, _)| *n == name).unwrap().0,
+            },
+            ("addsl" | "ext" | "extu", _) => {
+                return Err(format!("th.{name} requires 4 operands"));
+            }
+            ("srri" | "srriw" | "tst" | "mveqz" | "mvnez", _) => {
+                return Err(format!("th.{name} requires 3 operands"));
+            }
+            _ if UNARY_FUNCT7.iter().any(|(n, _)| *n == name) => {
+                return Err(format!("th.{name} requires 2 operands"));
+            }
+            _ if PAIR_FUNCT5.iter().any(|(n, _)| *n == name) => {
+                return Err(format!("th.{name} requires 5 operands"));
+            }
+            _ if CACHE_LINE_OPS.iter().any(|(_, n, _)| *n == name) => {
+                return Err(format!("th.{name} requires 1 operand"));
+            }
+            _ if CACHE_OPS.iter().any(|(_, n, _)| *n == name) => {
+                return Err(format!("th.{name} takes no operands"));
+            }
+            _ => return Err(format!("unknown mnemonic: th.{name}")),
+        };
+        // check the restrictions of RV32 and of the pairs by decoding
+        TheadInstruction::decode(instruction.encode(), xlen)
+    }
+
+    pub(crate) fn fmt_with(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &FormatOptions,
+    ) -> Result<(), std::fmt::Error> {
+        let mnemonic = self.mnemonic();
+        let style = options.register_style;
+        match self {
+            TheadInstruction::ADDSL {
+                dest,
+                src1,
+                src2,
+                shift,
+            } => write!(
+                f,
+                "th.{mnemonic} {dest},{src1},{src2},{shift}",
+                dest = dest.display_with(style),
+                src1 = src1.display_with(style),
+                src2 = src2.display_with(style)
+            ),
+            TheadInstruction::SRRI {
+                dest,
+                src,
+                shamt: imm,
+            }
+            | TheadInstruction::TST {
+                dest,
+                src,
+                bit: imm,
+            } => write!(
+                f,
+                "th.{mnemonic} {dest},{src},{imm}",
+                dest = dest.display_with(style),
+                src = src.display_with(style)
+            ),
+            TheadInstruction::SRRIW { dest, src, shamt } => write!(
+                f,
+                "th.{mnemonic} {dest},{src},{shamt}",
+                dest = dest.display_with(style),
+                src = src.display_with(style)
+            ),
+            TheadInstruction::EXT {
+                dest,
+                src,
+                msb,
+                lsb,
+            }
+            | TheadInstruction::EXTU {
+                dest,
+                src,
+                msb,
+                lsb,
+            } => write!(
+                f,
+                "th.{mnemonic} {dest},{src},{msb},{lsb}",
+                dest = dest.display_with(style),
+                src = src.display_with(style)
+            ),
+            TheadInstruction::FF0 { dest, src }
+            | TheadInstruction::FF1 { dest, src }
+            | TheadInstruction::REV { dest, src }
+            | TheadInstruction::REVW { dest, src }
+            | TheadInstruction::TSTNBZ { dest, src } => write!(
+                f,
+                "th.{mnemonic} {dest},{src}",
+                dest = dest.display_with(style),
+                src = src.display_with(style)
+            ),
+            TheadInstruction::MVEQZ { dest, src1, src2 }
+            | TheadInstruction::MVNEZ { dest, src1, src2 } => write!(
+                f,
+                "th.{mnemonic} {dest},{src1},{src2}",
+                dest = dest.display_with(style),
+                src1 = src1.display_with(style),
+                src2 = src2.display_with(style)
+            ),
+            TheadInstruction::LWD {
+                dest1: first,
+                dest2: second,
+                base,
+                offset,
+            }
+            | TheadInstruction::LWUD {
+                dest1: first,
+                dest2: second,
+                base,
+                offset,
+            }
+            | TheadInstruction::LDD {
+                dest1: first,
+                dest2: second,
+                base,
+                offset,
+            }
+            | TheadInstruction::SWD {
+                src1: first,
+                src2: second,
+                base,
+                offset,
+            }
+            | TheadInstruction::SDD {
+                src1: first,
+                src2: second,
+                base,
+                offset,
+            } => write!(
+                f,
+                "th.{mnemonic} {first},{second},({base}),{offset},{shift}",
+                first = first.display_with(style),
+                second = second.display_with(style),
+                base = base.display_with(style),
+                shift = TheadInstruction::pair_shift(mnemonic)
+            ),
+            TheadInstruction::CACHELINE { src, .. } => {
+                write!(f, "th.{mnemonic} {}", src.display_with(style))
+            }
+            TheadInstruction::CACHE { .. } => write!(f, "th.{mnemonic}"),
+        }
+    }
+}
+
+impl Display for TheadInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.fmt_with(f, &FormatOptions::default())
+    }
+}
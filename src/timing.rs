@@ -0,0 +1,266 @@
+//! Instruction classes for simple pipeline models.
+//!
+//! `Instruction::exec_class` groups instructions by the functional unit that would execute them, so that
+//! cycle-approximate simulators and schedulers can be built on the decoder without a table of their own. Latencies
+//! are rough figures for a single-issue in-order core and are meant as defaults to be overridden.
+
+use crate::instruction::Instruction;
+
+/// The functional unit class of an instruction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Hash)]
+pub enum ExecClass {
+    /// Integer arithmetic, logic, shifts, comparisons and lui/auipc.
+    Alu,
+    /// Integer multiplication.
+    Mul,
+    /// Integer division and remainder.
+    Div,
+    /// Integer and float loads.
+    Load,
+    /// Integer and float stores.
+    Store,
+    /// Conditional branches and jumps.
+    Branch,
+    /// Float arithmetic, conversions, comparisons and moves.
+    Fpu,
+    /// Control and status register accesses.
+    Csr,
+    /// Load-reserved, store-conditional and AMOs.
+    Amo,
+    /// Fences, pause, cache block management, environment calls, breakpoints and the privileged instructions.
+    System,
+    /// Vector arithmetic.
+    Vector,
+}
+
+impl ExecClass {
+    /// A rough number of cycles until the result of an instruction of this class can be used.
+    pub fn latency(self) -> u32 {
+        match self {
+            ExecClass::Alu | ExecClass::Branch | ExecClass::Store | ExecClass::System => 1,
+            ExecClass::Load => 2,
+            ExecClass::Mul => 3,
+            ExecClass::Fpu | ExecClass::Vector => 4,
+            ExecClass::Csr | ExecClass::Amo => 5,
+            // division takes a variable number of cycles which this is the typical worst case of
+            ExecClass::Div => 34,
+        }
+    }
+}
+
+impl Instruction {
+    /// Returns the functional unit class of this instruction.
+    pub fn exec_class(&self) -> ExecClass {
+        match self {
+            Instruction::MUL { .. }
+            | Instruction::MULH { .. }
+            | Instruction::MULHSU { .. }
+            | Instruction::MULHU { .. }
+            | Instruction::MULW { .. } => ExecClass::Mul,
+            Instruction::DIV { .. }
+            | Instruction::DIVU { .. }
+            | Instruction::REM { .. }
+            | Instruction::REMU { .. }
+            | Instruction::DIVW { .. }
+            | Instruction::DIVUW { .. }
+            | Instruction::REMW { .. }
+            | Instruction::REMUW { .. } => ExecClass::Div,
+            Instruction::LB { .. }
+            | Instruction::LH { .. }
+            | Instruction::LW { .. }
+            | Instruction::LBU { .. }
+            | Instruction::LHU { .. }
+            | Instruction::LWU { .. }
+            | Instruction::LD { .. }
+            | Instruction::FLH { .. }
+            | Instruction::FLW { .. }
+            | Instruction::FLQ { .. } => ExecClass::Load,
+            Instruction::SB { .. }
+            | Instruction::SH { .. }
+            | Instruction::SW { .. }
+            | Instruction::SD { .. }
+            | Instruction::FSH { .. }
+            | Instruction::FSW { .. }
+            | Instruction::FSQ { .. }
+            | Instruction::CBOZERO { .. } => ExecClass::Store,
+            Instruction::JAL { .. }
+            | Instruction::JALR { .. }
+            | Instruction::BEQ { .. }
+            | Instruction::BNE { .. }
+            | Instruction::BLT { .. }
+            | Instruction::BGE { .. }
+            | Instruction::BLTU { .. }
+            | Instruction::BGEU { .. } => ExecClass::Branch,
+            Instruction::FMADDS { .. }
+            | Instruction::FMSUBS { .. }
+            | Instruction::FNMSUBS { .. }
+            | Instruction::FNMADDS { .. }
+            | Instruction::FADDS { .. }
+            | Instruction::FSUBS { .. }
+            | Instruction::FMULS { .. }
+            | Instruction::FDIVS { .. }
+            | Instruction::FSQRTS { .. }
+            | Instruction::FSGNJS { .. }
+            | Instruction::FSGNJNS { .. }
+            | Instruction::FSGNJXS { .. }
+            | Instruction::FMINS { .. }
+            | Instruction::FMAXS { .. }
+            | Instruction::FCVTWS { .. }
+            | Instruction::FCVTWUS { .. }
+            | Instruction::FMVXW { .. }
+            | Instruction::FEQS { .. }
+            | Instruction::FLTS { .. }
+            | Instruction::FLES { .. }
+            | Instruction::FCLASSS { .. }
+            | Instruction::FCVTSW { .. }
+            | Instruction::FCVTSWU { .. }
+            | Instruction::FMVWX { .. }
+            | Instruction::FCVTLS { .. }
+            | Instruction::FCVTLUS { .. }
+            | Instruction::FCVTSL { .. }
+            | Instruction::FCVTSLU { .. }
+            | Instruction::FMADDQ { .. }
+            | Instruction::FMSUBQ { .. }
+            | Instruction::FNMSUBQ { .. }
+            | Instruction::FNMADDQ { .. }
+            | Instruction::FADDQ { .. }
+            | Instruction::FSUBQ { .. }
+            | Instruction::FMULQ { .. }
+            | Instruction::FDIVQ { .. }
+            | Instruction::FSQRTQ { .. }
+            | Instruction::FSGNJQ { .. }
+            | Instruction::FSGNJNQ { .. }
+            | Instruction::FSGNJXQ { .. }
+            | Instruction::FMINQ { .. }
+            | Instruction::FMAXQ { .. }
+            | Instruction::FCVTSQ { .. }
+            | Instruction::FCVTQS { .. }
+            | Instruction::FCVTDQ { .. }
+            | Instruction::FCVTQD { .. }
+            | Instruction::FEQQ { .. }
+            | Instruction::FLTQ { .. }
+            | Instruction::FLEQ { .. }
+            | Instruction::FCLASSQ { .. }
+            | Instruction::FCVTWQ { .. }
+            | Instruction::FCVTWUQ { .. }
+            | Instruction::FCVTQW { .. }
+            | Instruction::FCVTQWU { .. }
+            | Instruction::FCVTLQ { .. }
+            | Instruction::FCVTLUQ { .. }
+            | Instruction::FCVTQL { .. }
+            | Instruction::FCVTQLU { .. }
+            | Instruction::FMVXH { .. }
+            | Instruction::FMVHX { .. }
+            | Instruction::FCVTBF16S { .. }
+            | Instruction::FCVTSBF16 { .. } => ExecClass::Fpu,
+            #[cfg(feature = "rv128")]
+            Instruction::LQ { .. } | Instruction::LDU { .. } => ExecClass::Load,
+            #[cfg(feature = "rv128")]
+            Instruction::SQ { .. } => ExecClass::Store,
+            #[cfg(feature = "zalasr")]
+            Instruction::LBAQ { .. }
+            | Instruction::LHAQ { .. }
+            | Instruction::LWAQ { .. }
+            | Instruction::LDAQ { .. } => ExecClass::Load,
+            #[cfg(feature = "zalasr")]
+            Instruction::SBRL { .. }
+            | Instruction::SHRL { .. }
+            | Instruction::SWRL { .. }
+            | Instruction::SDRL { .. } => ExecClass::Store,
+            Instruction::CSRRW { .. }
+            | Instruction::CSRRS { .. }
+            | Instruction::CSRRC { .. }
+            | Instruction::CSRRWI { .. }
+            | Instruction::CSRRSI { .. }
+            | Instruction::CSRRCI { .. }
+            | Instruction::SSRDP { .. } => ExecClass::Csr,
+            // the shadow stack is in memory
+            Instruction::SSPUSH { .. } => ExecClass::Store,
+            Instruction::SSPOPCHK { .. } => ExecClass::Load,
+            Instruction::LRW { .. }
+            | Instruction::SCW { .. }
+            | Instruction::AMOSWAPW { .. }
+            | Instruction::AMOADDW { .. }
+            | Instruction::AMOXORW { .. }
+            | Instruction::AMOANDW { .. }
+            | Instruction::AMOORW { .. }
+            | Instruction::AMOMINW { .. }
+            | Instruction::AMOMAXW { .. }
+            | Instruction::AMOMINUW { .. }
+            | Instruction::AMOMAXUW { .. }
+            | Instruction::LRD { .. }
+            | Instruction::SCD { .. }
+            | Instruction::AMOSWAPD { .. }
+            | Instruction::AMOADDD { .. }
+            | Instruction::AMOXORD { .. }
+            | Instruction::AMOANDD { .. }
+            | Instruction::AMOORD { .. }
+            | Instruction::AMOMIND { .. }
+            | Instruction::AMOMAXD { .. }
+            | Instruction::AMOMINUD { .. }
+            | Instruction::AMOMAXUD { .. }
+            | Instruction::AMOSWAPB { .. }
+            | Instruction::AMOADDB { .. }
+            | Instruction::AMOXORB { .. }
+            | Instruction::AMOANDB { .. }
+            | Instruction::AMOORB { .. }
+            | Instruction::AMOMINB { .. }
+            | Instruction::AMOMAXB { .. }
+            | Instruction::AMOMINUB { .. }
+            | Instruction::AMOMAXUB { .. }
+            | Instruction::AMOSWAPH { .. }
+            | Instruction::AMOADDH { .. }
+            | Instruction::AMOXORH { .. }
+            | Instruction::AMOANDH { .. }
+            | Instruction::AMOORH { .. }
+            | Instruction::AMOMINH { .. }
+            | Instruction::AMOMAXH { .. }
+            | Instruction::AMOMINUH { .. }
+            | Instruction::AMOMAXUH { .. }
+            | Instruction::AMOCASB { .. }
+            | Instruction::AMOCASH { .. }
+            | Instruction::SSAMOSWAPW { .. }
+            | Instruction::SSAMOSWAPD { .. } => ExecClass::Amo,
+            Instruction::FENCE { .. }
+            | Instruction::FENCEI
+            | Instruction::PAUSE
+            | Instruction::CBOCLEAN { .. }
+            | Instruction::CBOFLUSH { .. }
+            | Instruction::CBOINVAL { .. }
+            | Instruction::ECALL
+            | Instruction::EBREAK
+            | Instruction::SRET
+            | Instruction::MRET
+            | Instruction::MNRET
+            | Instruction::DRET
+            | Instruction::WFI
+            | Instruction::SFENCEVMA { .. } => ExecClass::System,
+            Instruction::Vector(i) if i.is_store() => ExecClass::Store,
+            Instruction::Vector(i) if i.base().is_some() => ExecClass::Load,
+            Instruction::Vector(_) => ExecClass::Vector,
+            #[cfg(feature = "p")]
+            Instruction::P(i) if i.is_multiply() => ExecClass::Mul,
+            #[cfg(feature = "xandesperf")]
+            Instruction::XAndesPerf(i) if i.is_branch() => ExecClass::Branch,
+            #[cfg(feature = "xthead")]
+            Instruction::XThead(i) if i.is_load() => ExecClass::Load,
+            #[cfg(feature = "xthead")]
+            Instruction::XThead(i) if i.is_store() => ExecClass::Store,
+            #[cfg(feature = "xthead")]
+            Instruction::XThead(i) if i.is_cache_management() => ExecClass::System,
+            _ => ExecClass::Alu,
+        }
+    }
+
+    /// A rough number of cycles until the result of this instruction can be used, the latency of its class except
+    /// for float division and square root, which take as long as integer division.
+    pub fn latency(&self) -> u32 {
+        match self {
+            Instruction::FDIVS { .. }
+            | Instruction::FSQRTS { .. }
+            | Instruction::FDIVQ { .. }
+            | Instruction::FSQRTQ { .. } => ExecClass::Div.latency(),
+            _ => self.exec_class().latency(),
+        }
+    }
+}
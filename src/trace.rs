@@ -0,0 +1,217 @@
+//! Instruction traces from emulators and simulators.
+
+use std::fmt::Write;
+
+use crate::analysis::{Symbol, describe};
+use crate::cinstruction::CInstruction;
+use crate::config::{DecoderConfig, FormatOptions};
+use crate::error::DecodeError;
+use crate::instruction::Instruction;
+
+/// One executed instruction.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct TraceEntry {
+    pub pc: u64,
+    /// The encoding, only the low 16 bits are used for compressed instructions.
+    pub raw: u32,
+}
+
+/// Parses a hex number with an optional `0x` prefix.
+fn parse_hex(str: &str) -> Option<u64> {
+    let str = str.trim();
+    u64::from_str_radix(str.strip_prefix("0x").unwrap_or(str), 16).ok()
+}
+
+/// Parses a line of a spike log (`core   0: 0x0000000080000000 (0x00000297) auipc t0,0x0`), the commit log
+/// format with a privilege level before the pc is also accepted.
+fn parse_spike(line: &str) -> Option<TraceEntry> {
+    let (_, rest) = line.split_once(':')?;
+    let mut tokens = rest.split_whitespace();
+    let pc = tokens.find(|t| t.starts_with("0x"))?;
+    let raw = tokens.next()?.strip_prefix('(')?.strip_suffix(')')?;
+    Some(TraceEntry {
+        pc: parse_hex(pc)?,
+        raw: parse_hex(raw)? as u32,
+    })
+}
+
+/// Parses a trace of executed instructions.
+///
+/// Each line is either a `pc,raw` pair of hex numbers or a line of a spike log starting with `core`. Empty lines,
+/// `#` comments, a CSV header and spike lines without an instruction (such as `>>>>  main`) are skipped.
+pub fn parse_trace(text: &str) -> Result<Vec<TraceEntry>, String> {
+    let mut entries = Vec::new();
+    for (number, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if line.starts_with("core") {
+            entries.extend(parse_spike(line));
+            continue;
+        }
+        let entry = line.split_once(',').and_then(|(pc, raw)| {
+            Some(TraceEntry {
+                pc: parse_hex(pc)?,
+                raw: parse_hex(raw.split(',').next()?)? as u32,
+            })
+        });
+        match entry {
+            Some(entry) => entries.push(entry),
+            None if number == 0 => {}
+            None => {
+                return Err(format!(
+                    "invalid trace entry on line {}: {line}",
+                    number + 1
+                ));
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Decodes an entry, returning the length of the encoding and the instruction with compressed instructions
+/// expanded.
+fn decode(raw: u32, config: &DecoderConfig) -> (u8, Result<Instruction, DecodeError>) {
+    if raw & 0b11 == 0b11 {
+        (4, Instruction::decode_with(raw, config))
+    } else {
+        (2, CInstruction::decode_expanded_with(raw as u16, config))
+    }
+}
+
+/// Formats a trace as an execution listing.
+///
+/// Each entry is decoded and displayed with `options`, so enabling `pseudo_instructions` folds pseudo-instructions.
+/// Jumps and branches are annotated with their target and the closest symbol, and conditional branches with
+/// whether they were taken, which is known from the pc of the next entry.
+pub fn format_trace(
+    entries: &[TraceEntry],
+    symbols: &[Symbol],
+    config: &DecoderConfig,
+    options: &FormatOptions,
+) -> String {
+    let mut out = String::new();
+    for (i, entry) in entries.iter().enumerate() {
+        let next = entries.get(i + 1).map(|e| e.pc);
+        let (length, instruction) = decode(entry.raw, config);
+        let raw = match length {
+            2 => format!("{:04x}    ", entry.raw & 0xffff),
+            _ => format!("{:08x}", entry.raw),
+        };
+        let instruction = match instruction {
+            Ok(instruction) => instruction,
+            Err(_) => {
+                let directive = format!(".{length}byte {:#x}", entry.raw);
+                writeln!(out, "{:8x}:\t{raw}\t{directive}", entry.pc).unwrap();
+                continue;
+            }
+        };
+        write!(
+            out,
+            "{:8x}:\t{raw}\t{}",
+            entry.pc,
+            instruction.display_with(options)
+        )
+        .unwrap();
+        let (target, conditional) = match instruction {
+            Instruction::JAL { .. } => (instruction.branch_target(entry.pc), false),
+            Instruction::JALR { .. } => (next, false),
+            _ => (instruction.branch_target(entry.pc), true),
+        };
+        if let Some(target) = target {
+            write!(out, "\t# {target:x}").unwrap();
+            if let Some(name) = describe(symbols, target) {
+                write!(out, " <{name}>").unwrap();
+            }
+            // a branch to the next instruction is never distinguishable from one that is not taken
+            let fallthrough = entry.pc.wrapping_add(length as u64);
+            if let Some(next) = next
+                && conditional
+                && target != fallthrough
+            {
+                let taken = if next == target { "taken" } else { "not taken" };
+                write!(out, " ({taken})").unwrap();
+            }
+        }
+        out.push('\n');
+    }
+    out
+}
+
+/// A compressed branch trace, as produced by trace encoders such as E-Trace.
+///
+/// Only the information that can not be inferred from the program is recorded: whether each conditional branch was
+/// taken and the target of each indirect jump, in execution order.
+#[derive(Debug, PartialEq, Eq, Clone, Default)]
+pub struct BranchTrace {
+    pub taken: Vec<bool>,
+    pub targets: Vec<u64>,
+}
+
+/// Reconstructs the instructions executed from `start` given an image of the code starting at `base` and a branch
+/// trace.
+///
+/// Reconstruction ends at the first branch or indirect jump after the trace is used up. It is an error if the trace
+/// runs out of one kind of entry while entries of the other kind remain, if execution leaves the image, or if more
+/// than `limit` instructions are executed.
+pub fn reconstruct(
+    image: &[u8],
+    base: u64,
+    start: u64,
+    trace: &BranchTrace,
+    config: &DecoderConfig,
+    limit: usize,
+) -> Result<Vec<TraceEntry>, String> {
+    let mut taken = trace.taken.iter();
+    let mut targets = trace.targets.iter();
+    let mut entries = Vec::new();
+    let mut pc = start;
+    for _ in 0..limit {
+        let offset =
+            pc.checked_sub(base)
+                .filter(|offset| offset + 2 <= image.len() as u64)
+                .ok_or_else(|| format!("pc {pc:#x} is outside the image"))? as usize;
+        let low = u16::from_le_bytes([image[offset], image[offset + 1]]);
+        let raw = if low & 0b11 == 0b11 {
+            let high = image
+                .get(offset + 2..offset + 4)
+                .ok_or_else(|| format!("pc {pc:#x} is outside the image"))?;
+            low as u32 | (u16::from_le_bytes([high[0], high[1]]) as u32) << 16
+        } else {
+            low as u32
+        };
+        let (length, instruction) = decode(raw, config);
+        let instruction = instruction.map_err(|e| format!("unable to decode {pc:#x}: {e}"))?;
+        let next = match instruction {
+            Instruction::JAL { .. } => instruction.branch_target(pc),
+            Instruction::JALR { .. } => match targets.next() {
+                Some(target) => Some(*target),
+                None if taken.len() == 0 => return Ok(entries),
+                None => {
+                    return Err(format!(
+                        "no indirect target for {pc:#x} with {} branches left",
+                        taken.len()
+                    ));
+                }
+            },
+            _ => match instruction.branch_target(pc) {
+                Some(target) => match taken.next() {
+                    Some(true) => Some(target),
+                    Some(false) => None,
+                    None if targets.len() == 0 => return Ok(entries),
+                    None => {
+                        return Err(format!(
+                            "no branch outcome for {pc:#x} with {} indirect targets left",
+                            targets.len()
+                        ));
+                    }
+                },
+                None => None,
+            },
+        };
+        entries.push(TraceEntry { pc, raw });
+        pc = next.unwrap_or(pc.wrapping_add(length as u64));
+    }
+    Err(format!("the trace did not end within {limit} instructions"))
+}
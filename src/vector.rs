@@ -0,0 +1,1798 @@
+//! The vector extension (V).
+//!
+//! The arithmetic instructions are in the OP-V major opcode, where funct3 selects the kind of operands and funct6 the
+//! operation. The operations are listed in tables of their funct6 in each of the three groups that share funct6
+//! values (OPI, OPM and OPF), together with the operand forms they exist in. Operands are printed the same way
+//! whatever the element width and register group multiplier, since those are only known at run time from vtype.
+//! The bit manipulation and carry-less multiply instructions of the vector crypto extensions (Zvbb and Zvbc) are in
+//! those groups, the rest are in the OP-VE major opcode and are listed in the same tables as a fourth group.
+//! The configuration instructions (vsetvli, vsetivli and vsetvl) use the last funct3 of OP-V. The loads and stores
+//! share the LOAD-FP and STORE-FP major opcodes with the scalar float loads and stores, and use the width values
+//! those leave free. Masked instructions are written with a trailing `v0.t` operand.
+
+use std::fmt::{Display, Formatter};
+
+use crate::assembly::{parse_address_expression, parse_int};
+use crate::config::{DecoderConfig, FormatOptions};
+use crate::immediates::{Immediate, VImmediate, VShamt, VType, VTypeImmediate, VUImmediate};
+use crate::register::{FRegister, IRegister, RegisterClass, VRegister};
+
+/// The OP-V major opcode.
+pub(crate) const OPCODE: u32 = 0b1010111;
+/// The LOAD-FP major opcode.
+const LOAD_OPCODE: u32 = 0b0000111;
+/// The STORE-FP major opcode.
+const STORE_OPCODE: u32 = 0b0100111;
+/// The OP-VE major opcode.
+const CRYPTO_OPCODE: u32 = 0b1110111;
+
+/// The groups of OP-V instructions, each of which has its own funct6 values.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Group {
+    /// Integer operations (OPIVV, OPIVX and OPIVI).
+    Opi,
+    /// Integer operations of the multiply and mask unit (OPMVV and OPMVX).
+    Opm,
+    /// Float operations (OPFVV and OPFVF).
+    Opf,
+    /// The vector crypto operations in OP-VE, which always have the funct3 of OPMVV and can not be masked.
+    Opve,
+}
+
+impl Group {
+    /// The major opcode of the operations in the group.
+    fn opcode(self) -> u32 {
+        match self {
+            Group::Opve => CRYPTO_OPCODE,
+            _ => OPCODE,
+        }
+    }
+}
+
+/// How the mnemonic suffix and the order of the operands of an operation are formed.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+enum Shape {
+    /// `vadd.vv vd,vs2,vs1`
+    Normal,
+    /// The first source is twice as wide as the second (`vwadd.wv`, `vnsrl.wi`).
+    Wide,
+    /// Multiply-adds, which list the sources the other way around (`vmacc.vv vd,vs1,vs2`).
+    MultiplyAdd,
+    /// `vredsum.vs vd,vs2,vs1`
+    Reduction,
+    /// `vmand.mm vd,vs2,vs1`
+    Mask,
+    /// `vcompress.vm vd,vs2,vs1`
+    Compress,
+    /// Operations that take v0 as a carry or a selector, which are encoded like masked instructions
+    /// (`vadc.vvm vd,vs2,vs1,v0`).
+    Carry,
+}
+
+/// The operations of `VectorInstruction::Binary`, named by their mnemonic without the suffix. The operations that are
+/// written with a `.w` suffix are separate from the ones that are not (`VWADD` is `vwadd.vv` and `VWADDW` is
+/// `vwadd.wv`), and so are the ones with a carry in (`VMADCM` is `vmadc.vvm`).
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum VectorOp {
+    VADD,
+    VANDN,
+    VSUB,
+    VRSUB,
+    VMINU,
+    VMIN,
+    VMAXU,
+    VMAX,
+    VAND,
+    VOR,
+    VXOR,
+    VRGATHER,
+    VRGATHEREI16,
+    VSLIDEUP,
+    VSLIDEDOWN,
+    VADC,
+    VMADC,
+    VMADCM,
+    VSBC,
+    VMSBC,
+    VMSBCM,
+    VROR,
+    VROL,
+    VMERGE,
+    VMSEQ,
+    VMSNE,
+    VMSLTU,
+    VMSLT,
+    VMSLEU,
+    VMSLE,
+    VMSGTU,
+    VMSGT,
+    VSADDU,
+    VSADD,
+    VSSUBU,
+    VSSUB,
+    VSLL,
+    VSMUL,
+    VSRL,
+    VSRA,
+    VSSRL,
+    VSSRA,
+    VNSRL,
+    VNSRA,
+    VNCLIPU,
+    VNCLIP,
+    VWREDSUMU,
+    VWREDSUM,
+    VWSLL,
+    VREDSUM,
+    VREDAND,
+    VREDOR,
+    VREDXOR,
+    VREDMINU,
+    VREDMIN,
+    VREDMAXU,
+    VREDMAX,
+    VAADDU,
+    VAADD,
+    VASUBU,
+    VASUB,
+    VCLMUL,
+    VCLMULH,
+    VSLIDE1UP,
+    VSLIDE1DOWN,
+    VCOMPRESS,
+    VMANDN,
+    VMAND,
+    VMOR,
+    VMXOR,
+    VMORN,
+    VMNAND,
+    VMNOR,
+    VMXNOR,
+    VDIVU,
+    VDIV,
+    VREMU,
+    VREM,
+    VMULHU,
+    VMUL,
+    VMULHSU,
+    VMULH,
+    VMADD,
+    VNMSUB,
+    VMACC,
+    VNMSAC,
+    VWADDU,
+    VWADD,
+    VWSUBU,
+    VWSUB,
+    VWADDUW,
+    VWADDW,
+    VWSUBUW,
+    VWSUBW,
+    VWMULU,
+    VWMULSU,
+    VWMUL,
+    VWMACCU,
+    VWMACC,
+    VWMACCUS,
+    VWMACCSU,
+    VFADD,
+    VFREDUSUM,
+    VFSUB,
+    VFREDOSUM,
+    VFMIN,
+    VFREDMIN,
+    VFMAX,
+    VFREDMAX,
+    VFSGNJ,
+    VFSGNJN,
+    VFSGNJX,
+    VFSLIDE1UP,
+    VFSLIDE1DOWN,
+    VFMERGE,
+    VMFEQ,
+    VMFLE,
+    VMFLT,
+    VMFNE,
+    VMFGT,
+    VMFGE,
+    VFDIV,
+    VFRDIV,
+    VFMUL,
+    VFRSUB,
+    VFMADD,
+    VFNMADD,
+    VFMSUB,
+    VFNMSUB,
+    VFMACC,
+    VFNMACC,
+    VFMSAC,
+    VFNMSAC,
+    VFWADD,
+    VFWREDUSUM,
+    VFWSUB,
+    VFWREDOSUM,
+    VFWADDW,
+    VFWSUBW,
+    VFWMUL,
+    VFWMACCBF16,
+    VFWMACC,
+    VFWNMACC,
+    VFWMSAC,
+    VFWNMSAC,
+    VSM3ME,
+    VSM4K,
+    VAESKF1,
+    VAESKF2,
+    VSM3C,
+    VGHSH,
+    VSHA2MS,
+    VSHA2CH,
+    VSHA2CL,
+}
+
+/// The mnemonic, group, funct6, operand forms and shape of each operation. The forms are `v`, `x` and `f` for a
+/// vector, integer or float register, `i` or `u` for a signed or unsigned 5-bit immediate and `r` for the 6-bit
+/// rotate amount of vror.
+#[rustfmt::skip]
+const OPS: [(VectorOp, &str, Group, u32, &str, Shape); 154] = [
+    (VectorOp::VADD, "vadd", Group::Opi, 0b000000, "vxi", Shape::Normal),
+    (VectorOp::VANDN, "vandn", Group::Opi, 0b000001, "vx", Shape::Normal),
+    (VectorOp::VSUB, "vsub", Group::Opi, 0b000010, "vx", Shape::Normal),
+    (VectorOp::VRSUB, "vrsub", Group::Opi, 0b000011, "xi", Shape::Normal),
+    (VectorOp::VMINU, "vminu", Group::Opi, 0b000100, "vx", Shape::Normal),
+    (VectorOp::VMIN, "vmin", Group::Opi, 0b000101, "vx", Shape::Normal),
+    (VectorOp::VMAXU, "vmaxu", Group::Opi, 0b000110, "vx", Shape::Normal),
+    (VectorOp::VMAX, "vmax", Group::Opi, 0b000111, "vx", Shape::Normal),
+    (VectorOp::VAND, "vand", Group::Opi, 0b001001, "vxi", Shape::Normal),
+    (VectorOp::VOR, "vor", Group::Opi, 0b001010, "vxi", Shape::Normal),
+    (VectorOp::VXOR, "vxor", Group::Opi, 0b001011, "vxi", Shape::Normal),
+    (VectorOp::VRGATHER, "vrgather", Group::Opi, 0b001100, "vxu", Shape::Normal),
+    (VectorOp::VRGATHEREI16, "vrgatherei16", Group::Opi, 0b001110, "v", Shape::Normal),
+    (VectorOp::VSLIDEUP, "vslideup", Group::Opi, 0b001110, "xu", Shape::Normal),
+    (VectorOp::VSLIDEDOWN, "vslidedown", Group::Opi, 0b001111, "xu", Shape::Normal),
+    (VectorOp::VADC, "vadc", Group::Opi, 0b010000, "vxi", Shape::Carry),
+    (VectorOp::VMADC, "vmadc", Group::Opi, 0b010001, "vxi", Shape::Normal),
+    (VectorOp::VMADCM, "vmadc", Group::Opi, 0b010001, "vxi", Shape::Carry),
+    (VectorOp::VSBC, "vsbc", Group::Opi, 0b010010, "vx", Shape::Carry),
+    (VectorOp::VMSBC, "vmsbc", Group::Opi, 0b010011, "vx", Shape::Normal),
+    (VectorOp::VMSBCM, "vmsbc", Group::Opi, 0b010011, "vx", Shape::Carry),
+    (VectorOp::VROR, "vror", Group::Opi, 0b010100, "vxr", Shape::Normal),
+    (VectorOp::VROL, "vrol", Group::Opi, 0b010101, "vx", Shape::Normal),
+    (VectorOp::VMERGE, "vmerge", Group::Opi, 0b010111, "vxi", Shape::Carry),
+    (VectorOp::VMSEQ, "vmseq", Group::Opi, 0b011000, "vxi", Shape::Normal),
+    (VectorOp::VMSNE, "vmsne", Group::Opi, 0b011001, "vxi", Shape::Normal),
+    (VectorOp::VMSLTU, "vmsltu", Group::Opi, 0b011010, "vx", Shape::Normal),
+    (VectorOp::VMSLT, "vmslt", Group::Opi, 0b011011, "vx", Shape::Normal),
+    (VectorOp::VMSLEU, "vmsleu", Group::Opi, 0b011100, "vxi", Shape::Normal),
+    (VectorOp::VMSLE, "vmsle", Group::Opi, 0b011101, "vxi", Shape::Normal),
+    (VectorOp::VMSGTU, "vmsgtu", Group::Opi, 0b011110, "xi", Shape::Normal),
+    (VectorOp::VMSGT, "vmsgt", Group::Opi, 0b011111, "xi", Shape::Normal),
+    (VectorOp::VSADDU, "vsaddu", Group::Opi, 0b100000, "vxi", Shape::Normal),
+    (VectorOp::VSADD, "vsadd", Group::Opi, 0b100001, "vxi", Shape::Normal),
+    (VectorOp::VSSUBU, "vssubu", Group::Opi, 0b100010, "vx", Shape::Normal),
+    (VectorOp::VSSUB, "vssub", Group::Opi, 0b100011, "vx", Shape::Normal),
+    (VectorOp::VSLL, "vsll", Group::Opi, 0b100101, "vxu", Shape::Normal),
+    (VectorOp::VSMUL, "vsmul", Group::Opi, 0b100111, "vx", Shape::Normal),
+    (VectorOp::VSRL, "vsrl", Group::Opi, 0b101000, "vxu", Shape::Normal),
+    (VectorOp::VSRA, "vsra", Group::Opi, 0b101001, "vxu", Shape::Normal),
+    (VectorOp::VSSRL, "vssrl", Group::Opi, 0b101010, "vxu", Shape::Normal),
+    (VectorOp::VSSRA, "vssra", Group::Opi, 0b101011, "vxu", Shape::Normal),
+    (VectorOp::VNSRL, "vnsrl", Group::Opi, 0b101100, "vxu", Shape::Wide),
+    (VectorOp::VNSRA, "vnsra", Group::Opi, 0b101101, "vxu", Shape::Wide),
+    (VectorOp::VNCLIPU, "vnclipu", Group::Opi, 0b101110, "vxu", Shape::Wide),
+    (VectorOp::VNCLIP, "vnclip", Group::Opi, 0b101111, "vxu", Shape::Wide),
+    (VectorOp::VWREDSUMU, "vwredsumu", Group::Opi, 0b110000, "v", Shape::Reduction),
+    (VectorOp::VWREDSUM, "vwredsum", Group::Opi, 0b110001, "v", Shape::Reduction),
+    (VectorOp::VWSLL, "vwsll", Group::Opi, 0b110101, "vxu", Shape::Normal),
+    (VectorOp::VREDSUM, "vredsum", Group::Opm, 0b000000, "v", Shape::Reduction),
+    (VectorOp::VREDAND, "vredand", Group::Opm, 0b000001, "v", Shape::Reduction),
+    (VectorOp::VREDOR, "vredor", Group::Opm, 0b000010, "v", Shape::Reduction),
+    (VectorOp::VREDXOR, "vredxor", Group::Opm, 0b000011, "v", Shape::Reduction),
+    (VectorOp::VREDMINU, "vredminu", Group::Opm, 0b000100, "v", Shape::Reduction),
+    (VectorOp::VREDMIN, "vredmin", Group::Opm, 0b000101, "v", Shape::Reduction),
+    (VectorOp::VREDMAXU, "vredmaxu", Group::Opm, 0b000110, "v", Shape::Reduction),
+    (VectorOp::VREDMAX, "vredmax", Group::Opm, 0b000111, "v", Shape::Reduction),
+    (VectorOp::VAADDU, "vaaddu", Group::Opm, 0b001000, "vx", Shape::Normal),
+    (VectorOp::VAADD, "vaadd", Group::Opm, 0b001001, "vx", Shape::Normal),
+    (VectorOp::VASUBU, "vasubu", Group::Opm, 0b001010, "vx", Shape::Normal),
+    (VectorOp::VASUB, "vasub", Group::Opm, 0b001011, "vx", Shape::Normal),
+    (VectorOp::VCLMUL, "vclmul", Group::Opm, 0b001100, "vx", Shape::Normal),
+    (VectorOp::VCLMULH, "vclmulh", Group::Opm, 0b001101, "vx", Shape::Normal),
+    (VectorOp::VSLIDE1UP, "vslide1up", Group::Opm, 0b001110, "x", Shape::Normal),
+    (VectorOp::VSLIDE1DOWN, "vslide1down", Group::Opm, 0b001111, "x", Shape::Normal),
+    (VectorOp::VCOMPRESS, "vcompress", Group::Opm, 0b010111, "v", Shape::Compress),
+    (VectorOp::VMANDN, "vmandn", Group::Opm, 0b011000, "v", Shape::Mask),
+    (VectorOp::VMAND, "vmand", Group::Opm, 0b011001, "v", Shape::Mask),
+    (VectorOp::VMOR, "vmor", Group::Opm, 0b011010, "v", Shape::Mask),
+    (VectorOp::VMXOR, "vmxor", Group::Opm, 0b011011, "v", Shape::Mask),
+    (VectorOp::VMORN, "vmorn", Group::Opm, 0b011100, "v", Shape::Mask),
+    (VectorOp::VMNAND, "vmnand", Group::Opm, 0b011101, "v", Shape::Mask),
+    (VectorOp::VMNOR, "vmnor", Group::Opm, 0b011110, "v", Shape::Mask),
+    (VectorOp::VMXNOR, "vmxnor", Group::Opm, 0b011111, "v", Shape::Mask),
+    (VectorOp::VDIVU, "vdivu", Group::Opm, 0b100000, "vx", Shape::Normal),
+    (VectorOp::VDIV, "vdiv", Group::Opm, 0b100001, "vx", Shape::Normal),
+    (VectorOp::VREMU, "vremu", Group::Opm, 0b100010, "vx", Shape::Normal),
+    (VectorOp::VREM, "vrem", Group::Opm, 0b100011, "vx", Shape::Normal),
+    (VectorOp::VMULHU, "vmulhu", Group::Opm, 0b100100, "vx", Shape::Normal),
+    (VectorOp::VMUL, "vmul", Group::Opm, 0b100101, "vx", Shape::Normal),
+    (VectorOp::VMULHSU, "vmulhsu", Group::Opm, 0b100110, "vx", Shape::Normal),
+    (VectorOp::VMULH, "vmulh", Group::Opm, 0b100111, "vx", Shape::Normal),
+    (VectorOp::VMADD, "vmadd", Group::Opm, 0b101001, "vx", Shape::MultiplyAdd),
+    (VectorOp::VNMSUB, "vnmsub", Group::Opm, 0b101011, "vx", Shape::MultiplyAdd),
+    (VectorOp::VMACC, "vmacc", Group::Opm, 0b101101, "vx", Shape::MultiplyAdd),
+    (VectorOp::VNMSAC, "vnmsac", Group::Opm, 0b101111, "vx", Shape::MultiplyAdd),
+    (VectorOp::VWADDU, "vwaddu", Group::Opm, 0b110000, "vx", Shape::Normal),
+    (VectorOp::VWADD, "vwadd", Group::Opm, 0b110001, "vx", Shape::Normal),
+    (VectorOp::VWSUBU, "vwsubu", Group::Opm, 0b110010, "vx", Shape::Normal),
+    (VectorOp::VWSUB, "vwsub", Group::Opm, 0b110011, "vx", Shape::Normal),
+    (VectorOp::VWADDUW, "vwaddu", Group::Opm, 0b110100, "vx", Shape::Wide),
+    (VectorOp::VWADDW, "vwadd", Group::Opm, 0b110101, "vx", Shape::Wide),
+    (VectorOp::VWSUBUW, "vwsubu", Group::Opm, 0b110110, "vx", Shape::Wide),
+    (VectorOp::VWSUBW, "vwsub", Group::Opm, 0b110111, "vx", Shape::Wide),
+    (VectorOp::VWMULU, "vwmulu", Group::Opm, 0b111000, "vx", Shape::Normal),
+    (VectorOp::VWMULSU, "vwmulsu", Group::Opm, 0b111010, "vx", Shape::Normal),
+    (VectorOp::VWMUL, "vwmul", Group::Opm, 0b111011, "vx", Shape::Normal),
+    (VectorOp::VWMACCU, "vwmaccu", Group::Opm, 0b111100, "vx", Shape::MultiplyAdd),
+    (VectorOp::VWMACC, "vwmacc", Group::Opm, 0b111101, "vx", Shape::MultiplyAdd),
+    (VectorOp::VWMACCUS, "vwmaccus", Group::Opm, 0b111110, "x", Shape::MultiplyAdd),
+    (VectorOp::VWMACCSU, "vwmaccsu", Group::Opm, 0b111111, "vx", Shape::MultiplyAdd),
+    (VectorOp::VFADD, "vfadd", Group::Opf, 0b000000, "vf", Shape::Normal),
+    (VectorOp::VFREDUSUM, "vfredusum", Group::Opf, 0b000001, "v", Shape::Reduction),
+    (VectorOp::VFSUB, "vfsub", Group::Opf, 0b000010, "vf", Shape::Normal),
+    (VectorOp::VFREDOSUM, "vfredosum", Group::Opf, 0b000011, "v", Shape::Reduction),
+    (VectorOp::VFMIN, "vfmin", Group::Opf, 0b000100, "vf", Shape::Normal),
+    (VectorOp::VFREDMIN, "vfredmin", Group::Opf, 0b000101, "v", Shape::Reduction),
+    (VectorOp::VFMAX, "vfmax", Group::Opf, 0b000110, "vf", Shape::Normal),
+    (VectorOp::VFREDMAX, "vfredmax", Group::Opf, 0b000111, "v", Shape::Reduction),
+    (VectorOp::VFSGNJ, "vfsgnj", Group::Opf, 0b001000, "vf", Shape::Normal),
+    (VectorOp::VFSGNJN, "vfsgnjn", Group::Opf, 0b001001, "vf", Shape::Normal),
+    (VectorOp::VFSGNJX, "vfsgnjx", Group::Opf, 0b001010, "vf", Shape::Normal),
+    (VectorOp::VFSLIDE1UP, "vfslide1up", Group::Opf, 0b001110, "f", Shape::Normal),
+    (VectorOp::VFSLIDE1DOWN, "vfslide1down", Group::Opf, 0b001111, "f", Shape::Normal),
+    (VectorOp::VFMERGE, "vfmerge", Group::Opf, 0b010111, "f", Shape::Carry),
+    (VectorOp::VMFEQ, "vmfeq", Group::Opf, 0b011000, "vf", Shape::Normal),
+    (VectorOp::VMFLE, "vmfle", Group::Opf, 0b011001, "vf", Shape::Normal),
+    (VectorOp::VMFLT, "vmflt", Group::Opf, 0b011011, "vf", Shape::Normal),
+    (VectorOp::VMFNE, "vmfne", Group::Opf, 0b011100, "vf", Shape::Normal),
+    (VectorOp::VMFGT, "vmfgt", Group::Opf, 0b011101, "f", Shape::Normal),
+    (VectorOp::VMFGE, "vmfge", Group::Opf, 0b011111, "f", Shape::Normal),
+    (VectorOp::VFDIV, "vfdiv", Group::Opf, 0b100000, "vf", Shape::Normal),
+    (VectorOp::VFRDIV, "vfrdiv", Group::Opf, 0b100001, "f", Shape::Normal),
+    (VectorOp::VFMUL, "vfmul", Group::Opf, 0b100100, "vf", Shape::Normal),
+    (VectorOp::VFRSUB, "vfrsub", Group::Opf, 0b100111, "f", Shape::Normal),
+    (VectorOp::VFMADD, "vfmadd", Group::Opf, 0b101000, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFNMADD, "vfnmadd", Group::Opf, 0b101001, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFMSUB, "vfmsub", Group::Opf, 0b101010, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFNMSUB, "vfnmsub", Group::Opf, 0b101011, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFMACC, "vfmacc", Group::Opf, 0b101100, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFNMACC, "vfnmacc", Group::Opf, 0b101101, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFMSAC, "vfmsac", Group::Opf, 0b101110, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFNMSAC, "vfnmsac", Group::Opf, 0b101111, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFWADD, "vfwadd", Group::Opf, 0b110000, "vf", Shape::Normal),
+    (VectorOp::VFWREDUSUM, "vfwredusum", Group::Opf, 0b110001, "v", Shape::Reduction),
+    (VectorOp::VFWSUB, "vfwsub", Group::Opf, 0b110010, "vf", Shape::Normal),
+    (VectorOp::VFWREDOSUM, "vfwredosum", Group::Opf, 0b110011, "v", Shape::Reduction),
+    (VectorOp::VFWADDW, "vfwadd", Group::Opf, 0b110100, "vf", Shape::Wide),
+    (VectorOp::VFWSUBW, "vfwsub", Group::Opf, 0b110110, "vf", Shape::Wide),
+    (VectorOp::VFWMUL, "vfwmul", Group::Opf, 0b111000, "vf", Shape::Normal),
+    (VectorOp::VFWMACCBF16, "vfwmaccbf16", Group::Opf, 0b111011, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFWMACC, "vfwmacc", Group::Opf, 0b111100, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFWNMACC, "vfwnmacc", Group::Opf, 0b111101, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFWMSAC, "vfwmsac", Group::Opf, 0b111110, "vf", Shape::MultiplyAdd),
+    (VectorOp::VFWNMSAC, "vfwnmsac", Group::Opf, 0b111111, "vf", Shape::MultiplyAdd),
+    (VectorOp::VSM3ME, "vsm3me", Group::Opve, 0b100000, "v", Shape::Normal),
+    (VectorOp::VSM4K, "vsm4k", Group::Opve, 0b100001, "u", Shape::Normal),
+    (VectorOp::VAESKF1, "vaeskf1", Group::Opve, 0b100010, "u", Shape::Normal),
+    (VectorOp::VAESKF2, "vaeskf2", Group::Opve, 0b101010, "u", Shape::Normal),
+    (VectorOp::VSM3C, "vsm3c", Group::Opve, 0b101011, "u", Shape::Normal),
+    (VectorOp::VGHSH, "vghsh", Group::Opve, 0b101100, "v", Shape::Normal),
+    (VectorOp::VSHA2MS, "vsha2ms", Group::Opve, 0b101101, "v", Shape::Normal),
+    (VectorOp::VSHA2CH, "vsha2ch", Group::Opve, 0b101110, "v", Shape::Normal),
+    (VectorOp::VSHA2CL, "vsha2cl", Group::Opve, 0b101111, "v", Shape::Normal),
+];
+
+/// The operations of `VectorInstruction::Unary`, named by their full mnemonic.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum VectorUnaryOp {
+    VZEXTVF8,
+    VSEXTVF8,
+    VZEXTVF4,
+    VSEXTVF4,
+    VZEXTVF2,
+    VSEXTVF2,
+    VMSBFM,
+    VMSOFM,
+    VMSIFM,
+    VIOTAM,
+    VBREV8V,
+    VREV8V,
+    VBREVV,
+    VCLZV,
+    VCTZV,
+    VCPOPV,
+    VFCVTXUFV,
+    VFCVTXFV,
+    VFCVTFXUV,
+    VFCVTFXV,
+    VFCVTRTZXUFV,
+    VFCVTRTZXFV,
+    VFWCVTXUFV,
+    VFWCVTXFV,
+    VFWCVTFXUV,
+    VFWCVTFXV,
+    VFWCVTFFV,
+    VFWCVTBF16FFV,
+    VFWCVTRTZXUFV,
+    VFWCVTRTZXFV,
+    VFNCVTXUFW,
+    VFNCVTXFW,
+    VFNCVTFXUW,
+    VFNCVTFXW,
+    VFNCVTFFW,
+    VFNCVTRODFFW,
+    VFNCVTRTZXUFW,
+    VFNCVTRTZXFW,
+    VFNCVTBF16FFW,
+    VFSQRTV,
+    VFRSQRT7V,
+    VFREC7V,
+    VFCLASSV,
+    VAESDMVV,
+    VAESDFVV,
+    VAESEMVV,
+    VAESEFVV,
+    VSM4RVV,
+    VGMULVV,
+    VAESDMVS,
+    VAESDFVS,
+    VAESEMVS,
+    VAESEFVS,
+    VAESZVS,
+    VSM4RVS,
+}
+
+/// The mnemonic, group, funct6 and vs1 field of each unary operation.
+#[rustfmt::skip]
+const UNARY_OPS: [(VectorUnaryOp, &str, Group, u32, u32); 55] = [
+    (VectorUnaryOp::VZEXTVF8, "vzext.vf8", Group::Opm, 0b010010, 0b00010),
+    (VectorUnaryOp::VSEXTVF8, "vsext.vf8", Group::Opm, 0b010010, 0b00011),
+    (VectorUnaryOp::VZEXTVF4, "vzext.vf4", Group::Opm, 0b010010, 0b00100),
+    (VectorUnaryOp::VSEXTVF4, "vsext.vf4", Group::Opm, 0b010010, 0b00101),
+    (VectorUnaryOp::VZEXTVF2, "vzext.vf2", Group::Opm, 0b010010, 0b00110),
+    (VectorUnaryOp::VSEXTVF2, "vsext.vf2", Group::Opm, 0b010010, 0b00111),
+    (VectorUnaryOp::VMSBFM, "vmsbf.m", Group::Opm, 0b010100, 0b00001),
+    (VectorUnaryOp::VMSOFM, "vmsof.m", Group::Opm, 0b010100, 0b00010),
+    (VectorUnaryOp::VMSIFM, "vmsif.m", Group::Opm, 0b010100, 0b00011),
+    (VectorUnaryOp::VIOTAM, "viota.m", Group::Opm, 0b010100, 0b10000),
+    (VectorUnaryOp::VBREV8V, "vbrev8.v", Group::Opm, 0b010010, 0b01000),
+    (VectorUnaryOp::VREV8V, "vrev8.v", Group::Opm, 0b010010, 0b01001),
+    (VectorUnaryOp::VBREVV, "vbrev.v", Group::Opm, 0b010010, 0b01010),
+    (VectorUnaryOp::VCLZV, "vclz.v", Group::Opm, 0b010010, 0b01100),
+    (VectorUnaryOp::VCTZV, "vctz.v", Group::Opm, 0b010010, 0b01101),
+    (VectorUnaryOp::VCPOPV, "vcpop.v", Group::Opm, 0b010010, 0b01110),
+    (VectorUnaryOp::VFCVTXUFV, "vfcvt.xu.f.v", Group::Opf, 0b010010, 0b00000),
+    (VectorUnaryOp::VFCVTXFV, "vfcvt.x.f.v", Group::Opf, 0b010010, 0b00001),
+    (VectorUnaryOp::VFCVTFXUV, "vfcvt.f.xu.v", Group::Opf, 0b010010, 0b00010),
+    (VectorUnaryOp::VFCVTFXV, "vfcvt.f.x.v", Group::Opf, 0b010010, 0b00011),
+    (VectorUnaryOp::VFCVTRTZXUFV, "vfcvt.rtz.xu.f.v", Group::Opf, 0b010010, 0b00110),
+    (VectorUnaryOp::VFCVTRTZXFV, "vfcvt.rtz.x.f.v", Group::Opf, 0b010010, 0b00111),
+    (VectorUnaryOp::VFWCVTXUFV, "vfwcvt.xu.f.v", Group::Opf, 0b010010, 0b01000),
+    (VectorUnaryOp::VFWCVTXFV, "vfwcvt.x.f.v", Group::Opf, 0b010010, 0b01001),
+    (VectorUnaryOp::VFWCVTFXUV, "vfwcvt.f.xu.v", Group::Opf, 0b010010, 0b01010),
+    (VectorUnaryOp::VFWCVTFXV, "vfwcvt.f.x.v", Group::Opf, 0b010010, 0b01011),
+    (VectorUnaryOp::VFWCVTFFV, "vfwcvt.f.f.v", Group::Opf, 0b010010, 0b01100),
+    (VectorUnaryOp::VFWCVTBF16FFV, "vfwcvtbf16.f.f.v", Group::Opf, 0b010010, 0b01101),
+    (VectorUnaryOp::VFWCVTRTZXUFV, "vfwcvt.rtz.xu.f.v", Group::Opf, 0b010010, 0b01110),
+    (VectorUnaryOp::VFWCVTRTZXFV, "vfwcvt.rtz.x.f.v", Group::Opf, 0b010010, 0b01111),
+    (VectorUnaryOp::VFNCVTXUFW, "vfncvt.xu.f.w", Group::Opf, 0b010010, 0b10000),
+    (VectorUnaryOp::VFNCVTXFW, "vfncvt.x.f.w", Group::Opf, 0b010010, 0b10001),
+    (VectorUnaryOp::VFNCVTFXUW, "vfncvt.f.xu.w", Group::Opf, 0b010010, 0b10010),
+    (VectorUnaryOp::VFNCVTFXW, "vfncvt.f.x.w", Group::Opf, 0b010010, 0b10011),
+    (VectorUnaryOp::VFNCVTFFW, "vfncvt.f.f.w", Group::Opf, 0b010010, 0b10100),
+    (VectorUnaryOp::VFNCVTRODFFW, "vfncvt.rod.f.f.w", Group::Opf, 0b010010, 0b10101),
+    (VectorUnaryOp::VFNCVTRTZXUFW, "vfncvt.rtz.xu.f.w", Group::Opf, 0b010010, 0b10110),
+    (VectorUnaryOp::VFNCVTRTZXFW, "vfncvt.rtz.x.f.w", Group::Opf, 0b010010, 0b10111),
+    (VectorUnaryOp::VFNCVTBF16FFW, "vfncvtbf16.f.f.w", Group::Opf, 0b010010, 0b11101),
+    (VectorUnaryOp::VFSQRTV, "vfsqrt.v", Group::Opf, 0b010011, 0b00000),
+    (VectorUnaryOp::VFRSQRT7V, "vfrsqrt7.v", Group::Opf, 0b010011, 0b00100),
+    (VectorUnaryOp::VFREC7V, "vfrec7.v", Group::Opf, 0b010011, 0b00101),
+    (VectorUnaryOp::VFCLASSV, "vfclass.v", Group::Opf, 0b010011, 0b10000),
+    (VectorUnaryOp::VAESDMVV, "vaesdm.vv", Group::Opve, 0b101000, 0b00000),
+    (VectorUnaryOp::VAESDFVV, "vaesdf.vv", Group::Opve, 0b101000, 0b00001),
+    (VectorUnaryOp::VAESEMVV, "vaesem.vv", Group::Opve, 0b101000, 0b00010),
+    (VectorUnaryOp::VAESEFVV, "vaesef.vv", Group::Opve, 0b101000, 0b00011),
+    (VectorUnaryOp::VSM4RVV, "vsm4r.vv", Group::Opve, 0b101000, 0b10000),
+    (VectorUnaryOp::VGMULVV, "vgmul.vv", Group::Opve, 0b101000, 0b10001),
+    (VectorUnaryOp::VAESDMVS, "vaesdm.vs", Group::Opve, 0b101001, 0b00000),
+    (VectorUnaryOp::VAESDFVS, "vaesdf.vs", Group::Opve, 0b101001, 0b00001),
+    (VectorUnaryOp::VAESEMVS, "vaesem.vs", Group::Opve, 0b101001, 0b00010),
+    (VectorUnaryOp::VAESEFVS, "vaesef.vs", Group::Opve, 0b101001, 0b00011),
+    (VectorUnaryOp::VAESZVS, "vaesz.vs", Group::Opve, 0b101001, 0b00111),
+    (VectorUnaryOp::VSM4RVS, "vsm4r.vs", Group::Opve, 0b101001, 0b10000),
+];
+
+/// The operand of an OP-V instruction in place of vs1, which funct3 selects the kind of.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum VOperand {
+    /// `.vv`
+    Vector(VRegister),
+    /// `.vx`
+    Scalar(IRegister),
+    /// `.vi` of most operations.
+    Immediate(VImmediate),
+    /// `.vi` of the shifts, slides and vrgather.
+    UImmediate(VUImmediate),
+    /// `.vi` of vror.
+    Shamt(VShamt),
+    /// `.vf`
+    Float(FRegister),
+}
+
+impl VOperand {
+    /// The letter of the operand form in the tables.
+    fn form(self) -> char {
+        match self {
+            VOperand::Vector(_) => 'v',
+            VOperand::Scalar(_) => 'x',
+            VOperand::Immediate(_) => 'i',
+            VOperand::UImmediate(_) => 'u',
+            VOperand::Shamt(_) => 'r',
+            VOperand::Float(_) => 'f',
+        }
+    }
+
+    /// The funct3 of an instruction with this operand in a group.
+    fn funct3(self, group: Group) -> u32 {
+        match (self, group) {
+            (_, Group::Opve) => 0b010,
+            (VOperand::Vector(_), Group::Opi) => 0b000,
+            (VOperand::Vector(_), Group::Opf) => 0b001,
+            (VOperand::Vector(_), Group::Opm) => 0b010,
+            (VOperand::Immediate(_) | VOperand::UImmediate(_) | VOperand::Shamt(_), _) => 0b011,
+            (VOperand::Scalar(_), Group::Opm) => 0b110,
+            (VOperand::Scalar(_), _) => 0b100,
+            (VOperand::Float(_), _) => 0b101,
+        }
+    }
+
+    /// The bits of the operand positioned in the vs1 field.
+    fn rs1(self) -> u32 {
+        match self {
+            VOperand::Vector(v) => v.rs1(),
+            VOperand::Scalar(x) => x.rs1(),
+            VOperand::Immediate(imm) => imm.to_u32(),
+            VOperand::UImmediate(imm) => imm.to_u32(),
+            VOperand::Shamt(imm) => imm.to_u32(),
+            VOperand::Float(f) => f.rs1(),
+        }
+    }
+
+    /// Parses an operand of a form in the tables.
+    fn parse(form: char, str: &str) -> Result<VOperand, String> {
+        Ok(match form {
+            'v' => VOperand::Vector(VRegister::try_from(str)?),
+            'x' => VOperand::Scalar(IRegister::from_string(str)?),
+            'i' => VOperand::Immediate(VImmediate::try_from(parse_int(str)?)?),
+            'u' => VOperand::UImmediate(VUImmediate::try_from(parse_int(str)?)?),
+            'r' => VOperand::Shamt(VShamt::try_from(parse_int(str)?)?),
+            _ => VOperand::Float(FRegister::try_from(str)?),
+        })
+    }
+
+    fn display_with(self, options: &FormatOptions) -> String {
+        match self {
+            VOperand::Vector(v) => v.to_string(),
+            VOperand::Scalar(x) => x.display_with(options.register_style).to_string(),
+            VOperand::Immediate(imm) => imm.display_with(options.immediate_style).to_string(),
+            VOperand::UImmediate(imm) => imm.display_with(options.immediate_style).to_string(),
+            VOperand::Shamt(imm) => imm.display_with(options.immediate_style).to_string(),
+            VOperand::Float(f) => f.display_with(options.register_style).to_string(),
+        }
+    }
+}
+
+/// Returns true if an operation with the forms of the tables takes an operand of a form.
+fn has_form(forms: &str, form: char) -> bool {
+    match form {
+        'i' | 'u' => forms.contains('i') || forms.contains('u'),
+        _ => forms.contains(form),
+    }
+}
+
+/// The mnemonic suffix of an operation of a shape with an operand of a form.
+fn suffix(shape: Shape, form: char) -> &'static str {
+    match (shape, form) {
+        (Shape::Reduction, _) => "vs",
+        (Shape::Mask, _) => "mm",
+        (Shape::Compress, _) => "vm",
+        (Shape::Carry, 'v') => "vvm",
+        (Shape::Carry, 'x') => "vxm",
+        (Shape::Carry, 'f') => "vfm",
+        (Shape::Carry, _) => "vim",
+        (Shape::Wide, 'v') => "wv",
+        (Shape::Wide, 'x') => "wx",
+        (Shape::Wide, 'f') => "wf",
+        (Shape::Wide, _) => "wi",
+        (_, 'v') => "vv",
+        (_, 'x') => "vx",
+        (_, 'f') => "vf",
+        (_, _) => "vi",
+    }
+}
+
+impl VectorOp {
+    fn entry(self) -> &'static (VectorOp, &'static str, Group, u32, &'static str, Shape) {
+        OPS.iter().find(|entry| entry.0 == self).unwrap()
+    }
+
+    /// The mnemonic of the operation with an operand, including the suffix (`vadd.vx`).
+    pub fn mnemonic(self, src1: VOperand) -> String {
+        let (_, name, _, _, _, shape) = *self.entry();
+        format!("{name}.{}", suffix(shape, src1.form()))
+    }
+
+    /// Returns true if the operation can be masked. The mask operations, vcompress, the carry out of vmadc and
+    /// vmsbc and the OP-VE operations can not, and the operations that take v0 as a carry or selector use the
+    /// encoding of a mask for it.
+    pub fn maskable(self) -> bool {
+        let (_, _, group, _, _, shape) = *self.entry();
+        !matches!(shape, Shape::Mask | Shape::Compress | Shape::Carry)
+            && group != Group::Opve
+            && !matches!(self, VectorOp::VMADC | VectorOp::VMSBC)
+    }
+}
+
+impl VectorUnaryOp {
+    fn entry(self) -> &'static (VectorUnaryOp, &'static str, Group, u32, u32) {
+        UNARY_OPS.iter().find(|entry| entry.0 == self).unwrap()
+    }
+
+    pub fn mnemonic(self) -> &'static str {
+        self.entry().1
+    }
+
+    /// Returns true if the operation can be masked, which all but the OP-VE operations can.
+    pub fn maskable(self) -> bool {
+        self.entry().2 != Group::Opve
+    }
+}
+
+/// How the elements of a vector load or store are addressed.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum VectorAddressing {
+    /// Consecutive elements (`vle32.v`).
+    UnitStride,
+    /// Consecutive elements, where only the first element traps and a fault on a later one shortens vl instead
+    /// (`vle32ff.v`). Loads only.
+    FaultOnlyFirst,
+    /// Elements a number of bytes apart given by a register (`vlse32.v`).
+    Strided(IRegister),
+    /// Elements at the byte offsets in a vector of indices, which are accessed in order if `ordered` (`vloxei32.v`)
+    /// and in any order otherwise (`vluxei32.v`).
+    Indexed { index: VRegister, ordered: bool },
+}
+
+impl VectorAddressing {
+    /// The mnemonic of a load (`l`) or store (`s`) with this addressing (`vlsseg2e32.v`).
+    fn mnemonic(self, kind: char, width: u32, fields: u8) -> String {
+        let (prefix, element) = match self {
+            VectorAddressing::UnitStride | VectorAddressing::FaultOnlyFirst => ("", "e"),
+            VectorAddressing::Strided(_) => ("s", "e"),
+            VectorAddressing::Indexed { ordered: false, .. } => ("ux", "ei"),
+            VectorAddressing::Indexed { ordered: true, .. } => ("ox", "ei"),
+        };
+        let segment = if fields > 1 {
+            format!("seg{fields}")
+        } else {
+            String::new()
+        };
+        let ff = if self == VectorAddressing::FaultOnlyFirst {
+            "ff"
+        } else {
+            ""
+        };
+        format!("v{kind}{prefix}{segment}{element}{width}{ff}.v")
+    }
+
+    /// The mop field and the bits of the lumop, sumop, rs2 or vs2 field.
+    fn encode(self) -> (u32, u32) {
+        match self {
+            VectorAddressing::UnitStride => (0b00, 0b00000 << 20),
+            VectorAddressing::FaultOnlyFirst => (0b00, 0b10000 << 20),
+            VectorAddressing::Strided(stride) => (0b10, stride.rs2()),
+            VectorAddressing::Indexed {
+                index,
+                ordered: false,
+            } => (0b01, index.rs2()),
+            VectorAddressing::Indexed {
+                index,
+                ordered: true,
+            } => (0b11, index.rs2()),
+        }
+    }
+}
+
+/// The width field of a vector load or store of elements of a number of bits.
+fn width_funct3(width: u32) -> u32 {
+    match width {
+        8 => 0b000,
+        16 => 0b101,
+        32 => 0b110,
+        _ => 0b111,
+    }
+}
+
+/// Parses the base of a vector load or store, which is written `(a0)` or `0(a0)`.
+fn parse_base(str: &str) -> Result<IRegister, String> {
+    let (base, offset) = parse_address_expression(str)?;
+    if offset != 0 {
+        return Err(format!(
+            "vector loads and stores do not take an offset, got {str}"
+        ));
+    }
+    Ok(base)
+}
+
+/// Checks that a register of an instruction that accesses groups of 1, 2, 4 or 8 whole registers is the first of a group,
+/// which must be a multiple of the number of registers.
+fn check_group(mnemonic: &str, registers: u8, register: VRegister) -> Result<(), String> {
+    if register.number().is_multiple_of(registers) {
+        Ok(())
+    } else {
+        Err(format!(
+            "the registers of {mnemonic} must be a multiple of {registers}, got {register}"
+        ))
+    }
+}
+
+/// A vector instruction. The instructions with a `masked` field only operate on the elements whose bit is set in v0
+/// if it is true, which is written as a trailing `v0.t` operand.
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Clone, Copy)]
+pub enum VectorInstruction {
+    /// An operation of a vector `src2` and an operand of the form the instruction is written with (`vadd.vx`).
+    Binary {
+        op: VectorOp,
+        dest: VRegister,
+        src2: VRegister,
+        src1: VOperand,
+        masked: bool,
+    },
+    /// An operation of a single vector, which is selected by the vs1 field.
+    Unary {
+        op: VectorUnaryOp,
+        dest: VRegister,
+        src: VRegister,
+        masked: bool,
+    },
+    /// The index of each element.
+    VID { dest: VRegister, masked: bool },
+    /// Moves element 0 to an integer register.
+    VMVXS { dest: IRegister, src: VRegister },
+    /// Counts the set bits of a mask.
+    VCPOPM {
+        dest: IRegister,
+        src: VRegister,
+        masked: bool,
+    },
+    /// The index of the first set bit of a mask, or -1.
+    VFIRSTM {
+        dest: IRegister,
+        src: VRegister,
+        masked: bool,
+    },
+    /// Moves element 0 to a float register.
+    VFMVFS { dest: FRegister, src: VRegister },
+    /// Moves an integer register to element 0.
+    VMVSX { dest: VRegister, src: IRegister },
+    /// Moves a float register to element 0.
+    VFMVSF { dest: VRegister, src: FRegister },
+    /// Copies a vector or splats a scalar to every element (`vmv.v.v`, `vmv.v.x`, `vmv.v.i` or `vfmv.v.f`).
+    VMV { dest: VRegister, src: VOperand },
+    /// Copies 1, 2, 4 or 8 whole registers regardless of vtype (`vmv2r.v`).
+    VMVR {
+        registers: u8,
+        dest: VRegister,
+        src: VRegister,
+    },
+    /// Loads elements of `width` bits, or segments of 1 to 8 `fields` into as many consecutive register groups
+    /// (`vlseg2e32.v`). The width of an indexed load is that of the indices, the elements have the width of vtype.
+    Load {
+        addressing: VectorAddressing,
+        width: u32,
+        fields: u8,
+        dest: VRegister,
+        base: IRegister,
+        masked: bool,
+    },
+    /// Stores elements like `Load` loads them.
+    Store {
+        addressing: VectorAddressing,
+        width: u32,
+        fields: u8,
+        src: VRegister,
+        base: IRegister,
+        masked: bool,
+    },
+    /// Loads 1, 2, 4 or 8 whole registers regardless of vtype, `width` is a hint of the element width (`vl2re32.v`).
+    LoadWhole {
+        registers: u8,
+        width: u32,
+        dest: VRegister,
+        base: IRegister,
+    },
+    /// Stores 1, 2, 4 or 8 whole registers regardless of vtype (`vs2r.v`).
+    StoreWhole {
+        registers: u8,
+        src: VRegister,
+        base: IRegister,
+    },
+    /// Sets vl to at most `avl` elements of a vector type and writes it to `dest`.
+    VSETVLI {
+        dest: IRegister,
+        avl: IRegister,
+        vtype: VTypeImmediate,
+    },
+    /// Sets vl to at most an immediate number of elements of a vector type and writes it to `dest`.
+    VSETIVLI {
+        dest: IRegister,
+        avl: VUImmediate,
+        vtype: VTypeImmediate,
+    },
+    /// Sets vl to at most `avl` elements of the vector type in a register and writes it to `dest`.
+    VSETVL {
+        dest: IRegister,
+        avl: IRegister,
+        vtype: IRegister,
+    },
+    /// Loads a mask of vl bits.
+    VLM { dest: VRegister, base: IRegister },
+    /// Stores a mask of vl bits.
+    VSM { src: VRegister, base: IRegister },
+}
+
+impl VectorInstruction {
+    /// The mnemonic, including its suffixes.
+    pub fn mnemonic(&self) -> String {
+        match self {
+            VectorInstruction::Binary { op, src1, .. } => op.mnemonic(*src1),
+            VectorInstruction::Unary { op, .. } => op.mnemonic().to_owned(),
+            VectorInstruction::VID { .. } => "vid.v".to_owned(),
+            VectorInstruction::VMVXS { .. } => "vmv.x.s".to_owned(),
+            VectorInstruction::VCPOPM { .. } => "vcpop.m".to_owned(),
+            VectorInstruction::VFIRSTM { .. } => "vfirst.m".to_owned(),
+            VectorInstruction::VFMVFS { .. } => "vfmv.f.s".to_owned(),
+            VectorInstruction::VMVSX { .. } => "vmv.s.x".to_owned(),
+            VectorInstruction::VFMVSF { .. } => "vfmv.s.f".to_owned(),
+            VectorInstruction::VMV { src, .. } => match src {
+                VOperand::Vector(_) => "vmv.v.v",
+                VOperand::Scalar(_) => "vmv.v.x",
+                VOperand::Immediate(_) | VOperand::UImmediate(_) | VOperand::Shamt(_) => "vmv.v.i",
+                VOperand::Float(_) => "vfmv.v.f",
+            }
+            .to_owned(),
+            VectorInstruction::VMVR { registers, .. } => format!("vmv{registers}r.v"),
+            VectorInstruction::Load {
+                addressing,
+                width,
+                fields,
+                ..
+            } => addressing.mnemonic('l', *width, *fields),
+            VectorInstruction::Store {
+                addressing,
+                width,
+                fields,
+                ..
+            } => addressing.mnemonic('s', *width, *fields),
+            VectorInstruction::LoadWhole {
+                registers, width, ..
+            } => format!("vl{registers}re{width}.v"),
+            VectorInstruction::StoreWhole { registers, .. } => format!("vs{registers}r.v"),
+            VectorInstruction::VSETVLI { .. } => "vsetvli".to_owned(),
+            VectorInstruction::VSETIVLI { .. } => "vsetivli".to_owned(),
+            VectorInstruction::VSETVL { .. } => "vsetvl".to_owned(),
+            VectorInstruction::VLM { .. } => "vlm.v".to_owned(),
+            VectorInstruction::VSM { .. } => "vsm.v".to_owned(),
+        }
+    }
+
+    /// The base register of a load or store.
+    pub fn base(&self) -> Option<IRegister> {
+        match self {
+            VectorInstruction::Load { base, .. }
+            | VectorInstruction::Store { base, .. }
+            | VectorInstruction::LoadWhole { base, .. }
+            | VectorInstruction::StoreWhole { base, .. }
+            | VectorInstruction::VLM { base, .. }
+            | VectorInstruction::VSM { base, .. } => Some(*base),
+            _ => None,
+        }
+    }
+
+    /// Returns true if the instruction is masked by v0.
+    pub fn is_masked(&self) -> bool {
+        match self {
+            VectorInstruction::Binary { masked, .. }
+            | VectorInstruction::Unary { masked, .. }
+            | VectorInstruction::VID { masked, .. }
+            | VectorInstruction::VCPOPM { masked, .. }
+            | VectorInstruction::VFIRSTM { masked, .. }
+            | VectorInstruction::Load { masked, .. }
+            | VectorInstruction::Store { masked, .. } => *masked,
+            _ => false,
+        }
+    }
+
+    /// Returns true for the stores.
+    pub fn is_store(&self) -> bool {
+        matches!(
+            self,
+            VectorInstruction::Store { .. }
+                | VectorInstruction::StoreWhole { .. }
+                | VectorInstruction::VSM { .. }
+        )
+    }
+
+    /// Decodes an instruction in the OP-V major opcode, or a vector load or store, with the default configuration.
+    pub fn decode(instruction: u32) -> Result<Self, String> {
+        Self::decode_with(instruction, &DecoderConfig::default())
+    }
+
+    /// Decodes an instruction in the OP-V major opcode, or a vector load or store. With `strict`, register groups that
+    /// are not aligned to their size (`vmv2r.v v1,v2`) and reserved vector types are rejected, otherwise the vector
+    /// type of vsetvli and vsetivli is kept as a number.
+    pub fn decode_with(instruction: u32, config: &DecoderConfig) -> Result<Self, String> {
+        let funct6 = instruction >> 26;
+        let funct3 = (instruction >> 12) & 0b111;
+        let field = |shift: u32| (instruction >> shift) & 0b1_1111;
+        let vd = VRegister::try_from(field(7)).unwrap();
+        let vs1 = field(15);
+        let vs2 = VRegister::try_from(field(20)).unwrap();
+        // bit 25 is part of the vtype of vsetvli rather than vm
+        if instruction & 0b111_1111 == OPCODE && funct3 == 0b111 {
+            return Self::decode_config(instruction, config.strict);
+        }
+        let masked = (instruction >> 25) & 1 == 0;
+        match instruction & 0b111_1111 {
+            LOAD_OPCODE => return Self::decode_memory(instruction, false, config.strict),
+            STORE_OPCODE => return Self::decode_memory(instruction, true, config.strict),
+            CRYPTO_OPCODE => return Self::decode_crypto(instruction),
+            _ => {}
+        }
+        let (group, src1) = match funct3 {
+            0b000 => (
+                Group::Opi,
+                VOperand::Vector(VRegister::try_from(vs1).unwrap()),
+            ),
+            0b001 => (
+                Group::Opf,
+                VOperand::Vector(VRegister::try_from(vs1).unwrap()),
+            ),
+            0b010 => (
+                Group::Opm,
+                VOperand::Vector(VRegister::try_from(vs1).unwrap()),
+            ),
+            0b011 => (
+                Group::Opi,
+                VOperand::Immediate(VImmediate::from_u32(instruction)),
+            ),
+            0b100 => (Group::Opi, VOperand::Scalar(IRegister::from_int(vs1))),
+            0b101 => (
+                Group::Opf,
+                VOperand::Float(FRegister::try_from(vs1).unwrap()),
+            ),
+            _ => (Group::Opm, VOperand::Scalar(IRegister::from_int(vs1))),
+        };
+        let unknown = || {
+            format!("unknown vector instruction with funct6 {funct6:#08b} and funct3 {funct3:#05b}")
+        };
+        let x = |shift: u32| IRegister::from_int(field(shift));
+        let f = |shift: u32| FRegister::try_from(field(shift)).unwrap();
+        // the moves between element 0 and the scalar registers and the copies of registers can not be masked
+        let unmasked = |instruction: VectorInstruction| {
+            if masked {
+                Err(format!("{} can not be masked", instruction.mnemonic()))
+            } else {
+                Ok(instruction)
+            }
+        };
+        // the instructions that use the vs1 or vs2 field to select the operation
+        match (group, funct6, src1) {
+            // the sixth bit of the rotate amount of vror.vi is the low bit of funct6
+            (Group::Opi, 0b010100 | 0b010101, VOperand::Immediate(_)) => {
+                Ok(VectorInstruction::Binary {
+                    op: VectorOp::VROR,
+                    dest: vd,
+                    src2: vs2,
+                    src1: VOperand::Shamt(VShamt::from_u32(instruction)),
+                    masked,
+                })
+            }
+            (Group::Opm, 0b010000, VOperand::Vector(_)) => {
+                let (dest, src) = (x(7), vs2);
+                match vs1 {
+                    0b00000 => unmasked(VectorInstruction::VMVXS { dest, src }),
+                    0b10000 => Ok(VectorInstruction::VCPOPM { dest, src, masked }),
+                    0b10001 => Ok(VectorInstruction::VFIRSTM { dest, src, masked }),
+                    _ => Err(unknown()),
+                }
+            }
+            (Group::Opm, 0b010000, VOperand::Scalar(src)) if vs2 == VRegister::V0 => {
+                unmasked(VectorInstruction::VMVSX { dest: vd, src })
+            }
+            (Group::Opf, 0b010000, VOperand::Vector(_)) if vs1 == 0 => {
+                unmasked(VectorInstruction::VFMVFS {
+                    dest: f(7),
+                    src: vs2,
+                })
+            }
+            (Group::Opf, 0b010000, VOperand::Float(src)) if vs2 == VRegister::V0 => {
+                unmasked(VectorInstruction::VFMVSF { dest: vd, src })
+            }
+            (Group::Opm, 0b010100, VOperand::Vector(_)) if vs1 == 0b10001 => {
+                if vs2 != VRegister::V0 {
+                    return Err(format!("vid.v must have a vs2 of v0, got {vs2}"));
+                }
+                Ok(VectorInstruction::VID { dest: vd, masked })
+            }
+            (Group::Opm, 0b010010 | 0b010100, VOperand::Vector(_))
+            | (Group::Opf, 0b010010 | 0b010011, VOperand::Vector(_)) => UNARY_OPS
+                .iter()
+                .find(|(_, _, g, f6, code)| *g == group && *f6 == funct6 && *code == vs1)
+                .map(|(op, ..)| VectorInstruction::Unary {
+                    op: *op,
+                    dest: vd,
+                    src: vs2,
+                    masked,
+                })
+                .ok_or_else(unknown),
+            // vmv.v and vfmv.v.f, which are vmerge and vfmerge when masked
+            (Group::Opi, 0b010111, _) | (Group::Opf, 0b010111, VOperand::Float(_)) if !masked => {
+                if vs2 != VRegister::V0 {
+                    return Err(format!(
+                        "vmv.v and vfmv.v.f must have a vs2 of v0, got {vs2}"
+                    ));
+                }
+                Ok(VectorInstruction::VMV {
+                    dest: vd,
+                    src: src1,
+                })
+            }
+            (Group::Opi, 0b100111, VOperand::Immediate(_)) => match vs1 {
+                0 | 1 | 3 | 7 => {
+                    let registers = vs1 as u8 + 1;
+                    if config.strict {
+                        let mnemonic = format!("vmv{registers}r.v");
+                        check_group(&mnemonic, registers, vd)?;
+                        check_group(&mnemonic, registers, vs2)?;
+                    }
+                    unmasked(VectorInstruction::VMVR {
+                        registers,
+                        dest: vd,
+                        src: vs2,
+                    })
+                }
+                _ => Err(format!(
+                    "whole register moves copy 1, 2, 4 or 8 registers, got {}",
+                    vs1 + 1
+                )),
+            },
+            _ => {
+                // the operations with a carry in share their funct6 with the ones without, but are always masked
+                let (op, .., forms, shape) = OPS
+                    .iter()
+                    .filter(|(_, _, g, f6, forms, _)| {
+                        *g == group && *f6 == funct6 && has_form(forms, src1.form())
+                    })
+                    .min_by_key(|(.., shape)| (*shape == Shape::Carry) != masked)
+                    .ok_or_else(unknown)?;
+                let src1 = match src1 {
+                    VOperand::Immediate(_) if forms.contains('u') => {
+                        VOperand::UImmediate(VUImmediate::from_u32(instruction))
+                    }
+                    src1 => src1,
+                };
+                if *shape == Shape::Carry && !masked {
+                    return Err(format!("{} must have vm clear", op.mnemonic(src1)));
+                }
+                if masked && *shape != Shape::Carry && !op.maskable() {
+                    return Err(format!("{} can not be masked", op.mnemonic(src1)));
+                }
+                Ok(VectorInstruction::Binary {
+                    op: *op,
+                    dest: vd,
+                    src2: vs2,
+                    src1,
+                    masked: masked && *shape != Shape::Carry,
+                })
+            }
+        }
+    }
+
+    /// Decodes an instruction in the OP-VE major opcode of the vector crypto extensions.
+    fn decode_crypto(instruction: u32) -> Result<Self, String> {
+        let funct6 = instruction >> 26;
+        let funct3 = (instruction >> 12) & 0b111;
+        let vd = VRegister::try_from((instruction >> 7) & 0b1_1111).unwrap();
+        let vs1 = (instruction >> 15) & 0b1_1111;
+        let vs2 = VRegister::try_from((instruction >> 20) & 0b1_1111).unwrap();
+        if funct3 != 0b010 {
+            return Err(format!(
+                "unknown vector crypto instruction with funct3 {funct3:#05b}"
+            ));
+        }
+        let decoded = if let Some((op, ..)) = UNARY_OPS
+            .iter()
+            .find(|(_, _, g, f6, code)| *g == Group::Opve && *f6 == funct6 && *code == vs1)
+        {
+            VectorInstruction::Unary {
+                op: *op,
+                dest: vd,
+                src: vs2,
+                masked: false,
+            }
+        } else if let Some((op, .., forms, _)) = OPS
+            .iter()
+            .find(|(_, _, g, f6, ..)| *g == Group::Opve && *f6 == funct6)
+        {
+            let src1 = if forms.contains('u') {
+                VOperand::UImmediate(VUImmediate::from_u32(instruction))
+            } else {
+                VOperand::Vector(VRegister::try_from(vs1).unwrap())
+            };
+            VectorInstruction::Binary {
+                op: *op,
+                dest: vd,
+                src2: vs2,
+                src1,
+                masked: false,
+            }
+        } else {
+            return Err(format!(
+                "unknown vector crypto instruction with funct6 {funct6:#08b} and vs1 {vs1:#07b}"
+            ));
+        };
+        if (instruction >> 25) & 1 == 0 {
+            return Err(format!("{} can not be masked", decoded.mnemonic()));
+        }
+        Ok(decoded)
+    }
+
+    /// Decodes vsetvli, vsetivli or vsetvl, `strict` rejects the reserved vector types instead of keeping their bits.
+    fn decode_config(instruction: u32, strict: bool) -> Result<Self, String> {
+        let dest = IRegister::from_int((instruction >> 7) & 0b1_1111);
+        let rs1 = IRegister::from_int((instruction >> 15) & 0b1_1111);
+        let vtype = |bits: u32| match VType::from_u32(bits) {
+            Ok(vtype) => Ok(VTypeImmediate::Valid(vtype)),
+            Err(_) if !strict => Ok(VTypeImmediate::Reserved(bits)),
+            Err(error) => Err(error),
+        };
+        match instruction >> 30 {
+            0b00 | 0b01 => Ok(VectorInstruction::VSETVLI {
+                dest,
+                avl: rs1,
+                vtype: vtype((instruction >> 20) & 0x7ff)?,
+            }),
+            0b11 => Ok(VectorInstruction::VSETIVLI {
+                dest,
+                avl: VUImmediate::from_u32(instruction),
+                vtype: vtype((instruction >> 20) & 0x3ff)?,
+            }),
+            _ if (instruction >> 25) & 0b11_1111 == 0 => Ok(VectorInstruction::VSETVL {
+                dest,
+                avl: rs1,
+                vtype: IRegister::from_int((instruction >> 20) & 0b1_1111),
+            }),
+            _ => Err(format!(
+                "unknown vector configuration instruction with funct7 {:#09b}",
+                instruction >> 25
+            )),
+        }
+    }
+
+    /// Decodes a vector load or store, `strict` rejects whole register groups that are not aligned to their size.
+    fn decode_memory(instruction: u32, store: bool, strict: bool) -> Result<Self, String> {
+        let masked = (instruction >> 25) & 1 == 0;
+        let fields = (instruction >> 29) as u8 + 1;
+        let mop = (instruction >> 26) & 0b11;
+        let umop = (instruction >> 20) & 0b1_1111;
+        let base = IRegister::from_int((instruction >> 15) & 0b1_1111);
+        let vd = VRegister::try_from((instruction >> 7) & 0b1_1111).unwrap();
+        if (instruction >> 28) & 1 == 1 {
+            return Err("vector loads and stores with mew set are reserved".to_owned());
+        }
+        let width = match (instruction >> 12) & 0b111 {
+            0b000 => 8,
+            0b101 => 16,
+            0b110 => 32,
+            _ => 64,
+        };
+        let addressing = match (mop, umop) {
+            (0b00, 0b00000) => VectorAddressing::UnitStride,
+            (0b00, 0b10000) if !store => VectorAddressing::FaultOnlyFirst,
+            (0b00, 0b01000 | 0b01011) if masked => {
+                return Err("whole register and mask loads and stores can not be masked".to_owned());
+            }
+            (0b00, 0b01000) => {
+                if !matches!(fields, 1 | 2 | 4 | 8) {
+                    return Err(format!(
+                        "whole register loads and stores access 1, 2, 4 or 8 registers, got {fields}"
+                    ));
+                }
+                if store && width != 8 {
+                    return Err(format!(
+                        "whole register stores must have a width of 8, got {width}"
+                    ));
+                }
+                let decoded = if store {
+                    VectorInstruction::StoreWhole {
+                        registers: fields,
+                        src: vd,
+                        base,
+                    }
+                } else {
+                    VectorInstruction::LoadWhole {
+                        registers: fields,
+                        width,
+                        dest: vd,
+                        base,
+                    }
+                };
+                if strict {
+                    check_group(&decoded.mnemonic(), fields, vd)?;
+                }
+                return Ok(decoded);
+            }
+            (0b00, 0b01011) => {
+                if width != 8 || fields != 1 {
+                    return Err("vlm.v and vsm.v must have a width of 8 and one field".to_owned());
+                }
+                return Ok(if store {
+                    VectorInstruction::VSM { src: vd, base }
+                } else {
+                    VectorInstruction::VLM { dest: vd, base }
+                });
+            }
+            (0b00, _) => {
+                return Err(format!(
+                    "unknown unit-stride vector load or store with umop {umop:#07b}"
+                ));
+            }
+            (0b10, _) => VectorAddressing::Strided(IRegister::from_int(umop)),
+            (_, _) => VectorAddressing::Indexed {
+                index: VRegister::try_from(umop).unwrap(),
+                ordered: mop == 0b11,
+            },
+        };
+        Ok(if store {
+            VectorInstruction::Store {
+                addressing,
+                width,
+                fields,
+                src: vd,
+                base,
+                masked,
+            }
+        } else {
+            VectorInstruction::Load {
+                addressing,
+                width,
+                fields,
+                dest: vd,
+                base,
+                masked,
+            }
+        })
+    }
+
+    // zero fields are kept so that every encoding lists all of its fields
+    #[allow(clippy::identity_op)]
+    pub fn encode(&self) -> u32 {
+        let op_v = |funct6: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32| {
+            funct6 << 26 | 1 << 25 | rs2 | rs1 | funct3 << 12 | rd | OPCODE
+        };
+        // the operations of the tables, which are in OP-VE rather than OP-V for the crypto extensions
+        let op_group = |group: Group, funct6: u32, rs2: u32, rs1: u32, funct3: u32, rd: u32| {
+            op_v(funct6, rs2, rs1, funct3, rd) & !0b111_1111 | group.opcode()
+        };
+        let memory = |fields: u8, (mop, rs2): (u32, u32), base: &IRegister, width: u32, vd: u32| {
+            (fields as u32 - 1) << 29
+                | mop << 26
+                | 1 << 25
+                | rs2
+                | base.rs1()
+                | width_funct3(width) << 12
+                | vd
+        };
+        let bits = match self {
+            VectorInstruction::Binary {
+                op,
+                dest,
+                src2,
+                src1,
+                ..
+            } => {
+                let (_, _, group, funct6, _, _) = *op.entry();
+                op_group(
+                    group,
+                    funct6,
+                    src2.rs2(),
+                    src1.rs1(),
+                    src1.funct3(group),
+                    dest.rd(),
+                )
+            }
+            VectorInstruction::Unary { op, dest, src, .. } => {
+                let (_, _, group, funct6, code) = *op.entry();
+                let funct3 = VOperand::Vector(*src).funct3(group);
+                op_group(group, funct6, src.rs2(), code << 15, funct3, dest.rd())
+            }
+            VectorInstruction::VID { dest, .. } => {
+                op_v(0b010100, 0, 0b10001 << 15, 0b010, dest.rd())
+            }
+            VectorInstruction::VMVXS { dest, src } => {
+                op_v(0b010000, src.rs2(), 0b00000 << 15, 0b010, dest.rd())
+            }
+            VectorInstruction::VCPOPM { dest, src, .. } => {
+                op_v(0b010000, src.rs2(), 0b10000 << 15, 0b010, dest.rd())
+            }
+            VectorInstruction::VFIRSTM { dest, src, .. } => {
+                op_v(0b010000, src.rs2(), 0b10001 << 15, 0b010, dest.rd())
+            }
+            VectorInstruction::VFMVFS { dest, src } => {
+                op_v(0b010000, src.rs2(), 0b00000 << 15, 0b001, dest.rd())
+            }
+            VectorInstruction::VMVSX { dest, src } => {
+                op_v(0b010000, 0, src.rs1(), 0b110, dest.rd())
+            }
+            VectorInstruction::VFMVSF { dest, src } => {
+                op_v(0b010000, 0, src.rs1(), 0b101, dest.rd())
+            }
+            VectorInstruction::VMV { dest, src } => {
+                op_v(0b010111, 0, src.rs1(), src.funct3(Group::Opi), dest.rd())
+            }
+            VectorInstruction::VMVR {
+                registers,
+                dest,
+                src,
+            } => op_v(
+                0b100111,
+                src.rs2(),
+                (*registers as u32 - 1) << 15,
+                0b011,
+                dest.rd(),
+            ),
+            VectorInstruction::Load {
+                addressing,
+                width,
+                fields,
+                dest,
+                base,
+                ..
+            } => memory(*fields, addressing.encode(), base, *width, dest.rd()) | LOAD_OPCODE,
+            VectorInstruction::Store {
+                addressing,
+                width,
+                fields,
+                src,
+                base,
+                ..
+            } => memory(*fields, addressing.encode(), base, *width, src.rd()) | STORE_OPCODE,
+            VectorInstruction::LoadWhole {
+                registers,
+                width,
+                dest,
+                base,
+            } => memory(*registers, (0b00, 0b01000 << 20), base, *width, dest.rd()) | LOAD_OPCODE,
+            VectorInstruction::StoreWhole {
+                registers,
+                src,
+                base,
+            } => memory(*registers, (0b00, 0b01000 << 20), base, 8, src.rd()) | STORE_OPCODE,
+            VectorInstruction::VSETVLI { dest, avl, vtype } => {
+                vtype.to_u32() << 20 | avl.rs1() | 0b111 << 12 | dest.rd() | OPCODE
+            }
+            VectorInstruction::VSETIVLI { dest, avl, vtype } => {
+                0b11 << 30 | vtype.to_u32() << 20 | avl.to_u32() | 0b111 << 12 | dest.rd() | OPCODE
+            }
+            VectorInstruction::VSETVL { dest, avl, vtype } => {
+                0b1000000 << 25 | vtype.rs2() | avl.rs1() | 0b111 << 12 | dest.rd() | OPCODE
+            }
+            VectorInstruction::VLM { dest, base } => {
+                memory(1, (0b00, 0b01011 << 20), base, 8, dest.rd()) | LOAD_OPCODE
+            }
+            VectorInstruction::VSM { src, base } => {
+                memory(1, (0b00, 0b01011 << 20), base, 8, src.rd()) | STORE_OPCODE
+            }
+        };
+        // vm is clear in masked instructions and in the ones that take v0 as a carry or selector
+        let carry =
+            matches!(self, VectorInstruction::Binary { op, .. } if op.entry().5 == Shape::Carry);
+        if self.is_masked() || carry {
+            bits & !(1 << 25)
+        } else {
+            bits
+        }
+    }
+
+    /// Assembles a vector load or store, or returns None if the mnemonic is not one.
+    fn assemble_memory(
+        name: &str,
+        operands: &[&str],
+        masked: bool,
+    ) -> Result<Option<Self>, String> {
+        if !name.ends_with(".v") {
+            return Ok(None);
+        }
+        let instruction = match (name, operands) {
+            ("vlm.v", [dest, base]) => VectorInstruction::VLM {
+                dest: VRegister::try_from(*dest)?,
+                base: parse_base(base)?,
+            },
+            ("vsm.v", [src, base]) => VectorInstruction::VSM {
+                src: VRegister::try_from(*src)?,
+                base: parse_base(base)?,
+            },
+            ("vlm.v" | "vsm.v", _) => return Err(format!("{name} requires 2 operands")),
+            _ => {
+                let whole = [1, 2, 4, 8].into_iter().find_map(|registers| {
+                    if name == format!("vs{registers}r.v") {
+                        return Some((registers, None));
+                    }
+                    // vl1r.v is an alias of vl1re8.v
+                    if name == format!("vl{registers}r.v") {
+                        return Some((registers, Some(8)));
+                    }
+                    [8, 16, 32, 64]
+                        .into_iter()
+                        .find(|width| name == format!("vl{registers}re{width}.v"))
+                        .map(|width| (registers, Some(width)))
+                });
+                if let Some((registers, width)) = whole {
+                    let [reg, base] = operands else {
+                        return Err(format!("{name} requires 2 operands"));
+                    };
+                    let (reg, base) = (VRegister::try_from(*reg)?, parse_base(base)?);
+                    check_group(name, registers, reg)?;
+                    return Ok(Some(match width {
+                        Some(width) => VectorInstruction::LoadWhole {
+                            registers,
+                            width,
+                            dest: reg,
+                            base,
+                        },
+                        None => VectorInstruction::StoreWhole {
+                            registers,
+                            src: reg,
+                            base,
+                        },
+                    }));
+                }
+                let addressings = [
+                    VectorAddressing::UnitStride,
+                    VectorAddressing::FaultOnlyFirst,
+                    VectorAddressing::Strided(IRegister::Zero),
+                    VectorAddressing::Indexed {
+                        index: VRegister::V0,
+                        ordered: false,
+                    },
+                    VectorAddressing::Indexed {
+                        index: VRegister::V0,
+                        ordered: true,
+                    },
+                ];
+                let found = ['l', 's'].into_iter().find_map(|kind| {
+                    addressings.into_iter().find_map(|addressing| {
+                        [8, 16, 32, 64].into_iter().find_map(|width| {
+                            (1..=8)
+                                .find(|fields| addressing.mnemonic(kind, width, *fields) == name)
+                                .map(|fields| (kind, addressing, width, fields))
+                        })
+                    })
+                });
+                let Some((kind, addressing, width, fields)) = found else {
+                    return Ok(None);
+                };
+                if kind == 's' && addressing == VectorAddressing::FaultOnlyFirst {
+                    return Ok(None);
+                }
+                let (reg, base, addressing) = match (addressing, operands) {
+                    (
+                        VectorAddressing::UnitStride | VectorAddressing::FaultOnlyFirst,
+                        [reg, base],
+                    ) => (reg, base, addressing),
+                    (VectorAddressing::Strided(_), [reg, base, stride]) => (
+                        reg,
+                        base,
+                        VectorAddressing::Strided(IRegister::from_string(stride)?),
+                    ),
+                    (VectorAddressing::Indexed { ordered, .. }, [reg, base, index]) => (
+                        reg,
+                        base,
+                        VectorAddressing::Indexed {
+                            index: VRegister::try_from(*index)?,
+                            ordered,
+                        },
+                    ),
+                    (VectorAddressing::UnitStride | VectorAddressing::FaultOnlyFirst, _) => {
+                        return Err(format!("{name} requires 2 operands"));
+                    }
+                    _ => return Err(format!("{name} requires 3 operands")),
+                };
+                let (reg, base) = (VRegister::try_from(*reg)?, parse_base(base)?);
+                if kind == 'l' {
+                    VectorInstruction::Load {
+                        addressing,
+                        width,
+                        fields,
+                        dest: reg,
+                        base,
+                        masked,
+                    }
+                } else {
+                    VectorInstruction::Store {
+                        addressing,
+                        width,
+                        fields,
+                        src: reg,
+                        base,
+                        masked,
+                    }
+                }
+            }
+        };
+        Ok(Some(instruction))
+    }
+
+    /// Assembles an instruction from its mnemonic, split at the dots.
+    pub(crate) fn assemble(mnemonics: &[&str], operands: &[&str]) -> Result<Self, String> {
+        let name = mnemonics.join(".");
+        let v = |str: &str| VRegister::try_from(str);
+        let x = |str: &str| IRegister::from_string(str);
+        let f = |str: &str| FRegister::try_from(str);
+        let (operands, masked) = match operands {
+            [operands @ .., "v0.t"] => (operands, true),
+            _ => (operands, false),
+        };
+        let instruction = match (name.as_str(), operands) {
+            ("vid.v", [dest]) => VectorInstruction::VID {
+                dest: v(dest)?,
+                masked,
+            },
+            ("vmv.x.s", [dest, src]) => VectorInstruction::VMVXS {
+                dest: x(dest)?,
+                src: v(src)?,
+            },
+            ("vcpop.m", [dest, src]) => VectorInstruction::VCPOPM {
+                dest: x(dest)?,
+                src: v(src)?,
+                masked,
+            },
+            ("vfirst.m", [dest, src]) => VectorInstruction::VFIRSTM {
+                dest: x(dest)?,
+                src: v(src)?,
+                masked,
+            },
+            ("vfmv.f.s", [dest, src]) => VectorInstruction::VFMVFS {
+                dest: f(dest)?,
+                src: v(src)?,
+            },
+            ("vmv.s.x", [dest, src]) => VectorInstruction::VMVSX {
+                dest: v(dest)?,
+                src: x(src)?,
+            },
+            ("vfmv.s.f", [dest, src]) => VectorInstruction::VFMVSF {
+                dest: v(dest)?,
+                src: f(src)?,
+            },
+            ("vmv.v.v" | "vmv.v.x" | "vmv.v.i" | "vfmv.v.f", [dest, src]) => {
+                let form = name.chars().last().unwrap();
+                VectorInstruction::VMV {
+                    dest: v(dest)?,
+                    src: VOperand::parse(form, src)?,
+                }
+            }
+            ("vmv1r.v" | "vmv2r.v" | "vmv4r.v" | "vmv8r.v", [dest, src]) => {
+                let registers = name[3..4].parse().unwrap();
+                let (dest, src) = (v(dest)?, v(src)?);
+                check_group(&name, registers, dest)?;
+                check_group(&name, registers, src)?;
+                VectorInstruction::VMVR {
+                    registers,
+                    dest,
+                    src,
+                }
+            }
+            ("vsetvli", [dest, avl, vtype @ ..]) if !vtype.is_empty() => {
+                VectorInstruction::VSETVLI {
+                    dest: x(dest)?,
+                    avl: x(avl)?,
+                    vtype: VTypeImmediate::from_str(&vtype.join(","), 11)?,
+                }
+            }
+            ("vsetivli", [dest, avl, vtype @ ..]) if !vtype.is_empty() => {
+                VectorInstruction::VSETIVLI {
+                    dest: x(dest)?,
+                    avl: VUImmediate::try_from(parse_int(avl)?)?,
+                    vtype: VTypeImmediate::from_str(&vtype.join(","), 10)?,
+                }
+            }
+            ("vsetvl", [dest, avl, vtype]) => VectorInstruction::VSETVL {
+                dest: x(dest)?,
+                avl: x(avl)?,
+                vtype: x(vtype)?,
+            },
+            ("vsetvli" | "vsetivli", _) => {
+                return Err(format!("{name} requires a destination, an avl and a vtype"));
+            }
+            ("vsetvl", _) => return Err("vsetvl requires 3 operands".to_owned()),
+            ("vid.v", _) => return Err("vid.v requires 1 operand".to_owned()),
+            (
+                "vmv.x.s" | "vcpop.m" | "vfirst.m" | "vfmv.f.s" | "vmv.s.x" | "vfmv.s.f"
+                | "vmv.v.v" | "vmv.v.x" | "vmv.v.i" | "vfmv.v.f" | "vmv1r.v" | "vmv2r.v"
+                | "vmv4r.v" | "vmv8r.v",
+                _,
+            ) => return Err(format!("{name} requires 2 operands")),
+            _ => {
+                if let Some(instruction) = Self::assemble_memory(&name, operands, masked)? {
+                    instruction
+                } else if let Some((op, ..)) = UNARY_OPS.iter().find(|entry| entry.1 == name) {
+                    let [dest, src] = operands else {
+                        return Err(format!("{name} requires 2 operands"));
+                    };
+                    VectorInstruction::Unary {
+                        op: *op,
+                        dest: v(dest)?,
+                        src: v(src)?,
+                        masked: masked && op.maskable(),
+                    }
+                } else {
+                    let (base, suffix_name) = name
+                        .rsplit_once('.')
+                        .ok_or_else(|| format!("unknown mnemonic: {name}"))?;
+                    let (op, form, shape) = OPS
+                        .iter()
+                        .filter(|entry| entry.1 == base)
+                        .find_map(|(op, _, _, _, forms, shape)| {
+                            let form = forms
+                                .chars()
+                                .find(|form| suffix(*shape, *form) == suffix_name)?;
+                            Some((*op, form, *shape))
+                        })
+                        .ok_or_else(|| format!("unknown mnemonic: {name}"))?;
+                    let operands = match operands {
+                        [operands @ .., "v0"] if shape == Shape::Carry => operands,
+                        _ if shape == Shape::Carry => {
+                            return Err(format!("{name} requires v0 as its last operand"));
+                        }
+                        _ => operands,
+                    };
+                    let [dest, first, second] = operands else {
+                        return Err(format!("{name} requires 3 operands"));
+                    };
+                    let (src2, src1) = if shape == Shape::MultiplyAdd {
+                        (second, first)
+                    } else {
+                        (first, second)
+                    };
+                    VectorInstruction::Binary {
+                        op,
+                        dest: v(dest)?,
+                        src2: v(src2)?,
+                        src1: VOperand::parse(form, src1)?,
+                        masked: masked && op.maskable(),
+                    }
+                }
+            }
+        };
+        if masked && !instruction.is_masked() {
+            return Err(format!("{name} can not be masked"));
+        }
+        Ok(instruction)
+    }
+
+    pub(crate) fn fmt_with(
+        &self,
+        f: &mut Formatter<'_>,
+        options: &FormatOptions,
+    ) -> Result<(), std::fmt::Error> {
+        let mnemonic = self.mnemonic();
+        let style = options.register_style;
+        match self {
+            VectorInstruction::Binary {
+                op,
+                dest,
+                src2,
+                src1,
+                ..
+            } => {
+                let src1 = src1.display_with(options);
+                if op.entry().5 == Shape::MultiplyAdd {
+                    write!(f, "{mnemonic} {dest},{src1},{src2}")
+                } else {
+                    write!(f, "{mnemonic} {dest},{src2},{src1}")
+                }
+            }
+            VectorInstruction::Unary { dest, src, .. }
+            | VectorInstruction::VMVR { dest, src, .. } => write!(f, "{mnemonic} {dest},{src}"),
+            VectorInstruction::VID { dest, .. } => write!(f, "{mnemonic} {dest}"),
+            VectorInstruction::VMVXS { dest, src }
+            | VectorInstruction::VCPOPM { dest, src, .. }
+            | VectorInstruction::VFIRSTM { dest, src, .. } => {
+                write!(f, "{mnemonic} {},{src}", dest.display_with(style))
+            }
+            VectorInstruction::VFMVFS { dest, src } => {
+                write!(f, "{mnemonic} {},{src}", dest.display_with(style))
+            }
+            VectorInstruction::VMVSX { dest, src } => {
+                write!(f, "{mnemonic} {dest},{}", src.display_with(style))
+            }
+            VectorInstruction::VFMVSF { dest, src } => {
+                write!(f, "{mnemonic} {dest},{}", src.display_with(style))
+            }
+            VectorInstruction::VMV { dest, src } => {
+                write!(f, "{mnemonic} {dest},{}", src.display_with(options))
+            }
+            VectorInstruction::Load {
+                addressing,
+                dest: reg,
+                base,
+                ..
+            }
+            | VectorInstruction::Store {
+                addressing,
+                src: reg,
+                base,
+                ..
+            } => {
+                write!(f, "{mnemonic} {reg},({})", base.display_with(style))?;
+                match addressing {
+                    VectorAddressing::Strided(stride) => {
+                        write!(f, ",{}", stride.display_with(style))
+                    }
+                    VectorAddressing::Indexed { index, .. } => write!(f, ",{index}"),
+                    _ => Ok(()),
+                }
+            }
+            VectorInstruction::VSETVLI { dest, avl, vtype } => write!(
+                f,
+                "{mnemonic} {},{},{vtype}",
+                dest.display_with(style),
+                avl.display_with(style)
+            ),
+            VectorInstruction::VSETIVLI { dest, avl, vtype } => write!(
+                f,
+                "{mnemonic} {},{},{vtype}",
+                dest.display_with(style),
+                avl.display_with(options.immediate_style)
+            ),
+            VectorInstruction::VSETVL { dest, avl, vtype } => write!(
+                f,
+                "{mnemonic} {},{},{}",
+                dest.display_with(style),
+                avl.display_with(style),
+                vtype.display_with(style)
+            ),
+            VectorInstruction::LoadWhole {
+                dest: reg, base, ..
+            }
+            | VectorInstruction::StoreWhole { src: reg, base, .. }
+            | VectorInstruction::VLM { dest: reg, base }
+            | VectorInstruction::VSM { src: reg, base } => {
+                write!(f, "{mnemonic} {reg},({})", base.display_with(style))
+            }
+        }?;
+        match self {
+            VectorInstruction::Binary { op, .. } if op.entry().5 == Shape::Carry => {
+                write!(f, ",v0")
+            }
+            _ if self.is_masked() => write!(f, ",v0.t"),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl Display for VectorInstruction {
+    fn fmt(&self, f: &mut Formatter<'_>) -> Result<(), std::fmt::Error> {
+        self.fmt_with(f, &FormatOptions::default())
+    }
+}
@@ -1,5 +1,8 @@
 use riscv_codec::assembly::assemble_line;
-use riscv_codec::instruction::{Instruction, disassemble_instruction};
+use riscv_codec::config::{DecoderConfig, FormatOptions};
+use riscv_codec::instruction::{
+    Instruction, disassemble_instruction, disassemble_instruction_with,
+};
 use riscv_codec::register::IRegister;
 
 #[test]
@@ -615,3 +618,72 @@ fn amo_max_unsigned_doubleword() {
     let i3 = assemble_line(&disassemble_instruction(&i)).unwrap().i();
     assert_eq!(i, i3);
 }
+
+#[test]
+fn load_reserved_nonzero_rs2() {
+    let lenient = DecoderConfig {
+        strict: false,
+        ..Default::default()
+    };
+    // lr.w.aq a0,a1 with rs2 set to a2
+    let bin = 0x14c5a52f;
+    assert!(Instruction::decode(bin).is_err());
+    assert_eq!(
+        Instruction::decode_with(bin, &lenient).unwrap(),
+        Instruction::LRW {
+            dest: IRegister::A0,
+            addr: IRegister::A1,
+            aq: true,
+            rl: false,
+        }
+    );
+}
+
+#[test]
+fn parenthesized_addresses() {
+    let amoadd = Instruction::AMOADDW {
+        dest: IRegister::A0,
+        addr: IRegister::A1,
+        src: IRegister::A2,
+        aq: false,
+        rl: true,
+    };
+    assert_eq!(assemble_line("amoadd.w.rl a0,a2,(a1)").unwrap().i(), amoadd);
+    assert_eq!(
+        assemble_line("amoadd.w.rl a0, a2, 0(a1)").unwrap().i(),
+        amoadd
+    );
+    assert_eq!(assemble_line("amoadd.w.rl a0,a1,a2").unwrap().i(), amoadd);
+    let lr = Instruction::LRD {
+        dest: IRegister::T0,
+        addr: IRegister::StackPointer,
+        aq: false,
+        rl: false,
+    };
+    assert_eq!(assemble_line("lr.d t0,(sp)").unwrap().i(), lr);
+    assert_eq!(assemble_line("lr.d t0,0( sp )").unwrap().i(), lr);
+    let sc = assemble_line("sc.w.aqrl a3,a4,(a5)").unwrap().i();
+    assert_eq!(disassemble_instruction(&sc), "sc.w.aqrl a3,a5,a4");
+    assert_eq!(
+        assemble_line("amoswap.w a0,a2,8(a1)"),
+        Err("the address of amoswap.w can not have an offset, got 8(a1)".to_owned())
+    );
+
+    let options = FormatOptions {
+        parenthesized_atomics: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        disassemble_instruction_with(&amoadd, &options),
+        "amoadd.w.rl a0,a2,(a1)"
+    );
+    assert_eq!(disassemble_instruction_with(&lr, &options), "lr.d t0,(sp)");
+    assert_eq!(
+        disassemble_instruction_with(&sc, &options),
+        "sc.w.aqrl a3,a4,(a5)"
+    );
+    for i in [amoadd, lr, sc] {
+        let text = disassemble_instruction_with(&i, &options);
+        assert_eq!(assemble_line(&text).unwrap().i(), i);
+    }
+}
@@ -0,0 +1,402 @@
+use riscv_codec::{
+    analysis::{
+        CallGraph, DiffOptions, Difference, FrameMarker, Fusion, FusionKind, JumpTable, Symbol,
+        Xref, XrefIndex, XrefKind, decode_region, diff, find_frames, find_fusions,
+        find_jump_tables, from_be_words,
+    },
+    assembly::assemble_line,
+    cinstruction::CInstruction,
+    config::DecoderConfig,
+    instruction::Instruction,
+    register::IRegister,
+};
+
+/// Assembles lines into little endian machine code.
+fn assemble(program: &[&str]) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for line in program {
+        bytes.extend(assemble_line(line).unwrap().encode_bytes());
+    }
+    bytes
+}
+
+#[test]
+fn decode_regions() {
+    let mut bytes = assemble(&["addi a0,a0,1", "c.li a1,3", "add a0,a0,a1"]);
+    // an illegal instruction (all zero halfword) is skipped
+    bytes.extend([0, 0]);
+    bytes.extend(assemble(&["c.mv a0,a1"]));
+    let code = decode_region(&bytes, 0x100, &DecoderConfig::default());
+    let addresses: Vec<(u64, u8)> = code.iter().map(|d| (d.address, d.length)).collect();
+    assert_eq!(
+        addresses,
+        vec![(0x100, 4), (0x104, 2), (0x106, 4), (0x10c, 2)]
+    );
+    assert_eq!(
+        code[1].instruction,
+        assemble_line("c.li a1,3").unwrap().c().expand()
+    );
+}
+
+#[test]
+fn big_endian_words() {
+    let bytes = assemble(&["addi a0,a0,1", "c.li a1,3", "c.mv a0,a1"]);
+    let swapped: Vec<u8> = bytes
+        .chunks(4)
+        .flat_map(|word| word.iter().rev().copied())
+        .collect();
+    assert_eq!(from_be_words(&swapped).unwrap(), bytes);
+    let config = DecoderConfig::default();
+    assert_eq!(
+        decode_region(&from_be_words(&swapped).unwrap(), 0, &config),
+        decode_region(&bytes, 0, &config)
+    );
+    assert!(from_be_words(&swapped[..6]).is_err());
+
+    assert_eq!(
+        Instruction::from_le_bytes(bytes[..4].try_into().unwrap(), &config),
+        Ok(assemble_line("addi a0,a0,1").unwrap().i())
+    );
+    assert_eq!(
+        CInstruction::from_le_bytes([bytes[4], bytes[5]], &config),
+        Ok(assemble_line("c.li a1,3").unwrap().c())
+    );
+}
+
+#[test]
+fn call_graph() {
+    let bytes = assemble(&[
+        // main
+        "jal ra,16",
+        "auipc t1,0",
+        "jalr ra,20(t1)",
+        "jalr zero,0(ra)",
+        // f
+        "jal ra,8",
+        "jalr zero,0(ra)",
+        // g
+        "jalr zero,0(ra)",
+    ]);
+    let code = decode_region(&bytes, 0x1000, &DecoderConfig::default());
+    let symbols = vec![Symbol {
+        address: 0x1000,
+        name: "main".to_owned(),
+    }];
+    let graph = CallGraph::build(&code, &symbols);
+    assert_eq!(
+        graph.callees(0x1000).collect::<Vec<_>>(),
+        vec![0x1010, 0x1018]
+    );
+    assert_eq!(graph.callees(0x1010).collect::<Vec<_>>(), vec![0x1018]);
+    assert_eq!(graph.callees(0x1018).count(), 0);
+    assert_eq!(
+        graph.callers(0x1018).collect::<Vec<_>>(),
+        vec![0x1000, 0x1010]
+    );
+    assert_eq!(
+        graph.to_string(),
+        "main -> 0x1010\nmain -> 0x1018\n0x1010 -> 0x1018\n"
+    );
+}
+
+#[test]
+fn jump_tables() {
+    let bytes = assemble(&[
+        "addi a5,zero,3",
+        "bltu a5,a0,28",
+        "slli a0,a0,2",
+        "lui a5,2",
+        "addi a5,a5,0",
+        "add a0,a0,a5",
+        "lw a0,0(a0)",
+        "jalr zero,0(a0)",
+    ]);
+    let code = decode_region(&bytes, 0x1000, &DecoderConfig::default());
+    let data: Vec<u8> = [0x1100i32, 0x1200, 0x1300, 0x1400]
+        .iter()
+        .flat_map(|x| x.to_le_bytes())
+        .collect();
+    assert_eq!(
+        find_jump_tables(&code, &data, 0x2000),
+        vec![JumpTable {
+            jump: 0x101c,
+            table: 0x2000,
+            entry_size: 4,
+            relative: false,
+            targets: vec![0x1100, 0x1200, 0x1300, 0x1400],
+        }]
+    );
+
+    // position independent tables hold offsets from the table
+    let bytes = assemble(&[
+        "addi a5,zero,2",
+        "bgeu a0,a5,32",
+        "slli a0,a0,2",
+        "auipc a5,1",
+        "addi a5,a5,-12",
+        "add a0,a5,a0",
+        "lw a0,0(a0)",
+        "add a0,a0,a5",
+        "jalr zero,0(a0)",
+    ]);
+    let code = decode_region(&bytes, 0x1000, &DecoderConfig::default());
+    let data: Vec<u8> = [-0xf00i32, -0xe00]
+        .iter()
+        .flat_map(|x| x.to_le_bytes())
+        .collect();
+    assert_eq!(
+        find_jump_tables(&code, &data, 0x2000),
+        vec![JumpTable {
+            jump: 0x1020,
+            table: 0x2000,
+            entry_size: 4,
+            relative: true,
+            targets: vec![0x1100, 0x1200],
+        }]
+    );
+
+    // without a bounds check the size of the table is unknown
+    let bytes = assemble(&[
+        "slli a0,a0,3",
+        "lui a5,2",
+        "add a0,a0,a5",
+        "ld a0,0(a0)",
+        "jalr zero,0(a0)",
+    ]);
+    let code = decode_region(&bytes, 0x1000, &DecoderConfig::default());
+    assert_eq!(find_jump_tables(&code, &data, 0x2000), vec![]);
+}
+
+#[test]
+fn binary_diff() {
+    let old = assemble(&[
+        "addi a0,zero,1",
+        "add a1,a0,a0",
+        "jalr zero,0(ra)",
+        "addi a2,a0,4",
+        "sub a3,a2,a0",
+    ]);
+    let new = assemble(&[
+        "addi a0,zero,1",
+        "add a1,a0,a0",
+        "jalr zero,0(ra)",
+        "addi a4,a0,4",
+        "sub a3,a4,a0",
+    ]);
+    let config = DecoderConfig::default();
+    let old = decode_region(&old, 0x1000, &config);
+    let new = decode_region(&new, 0x1000, &config);
+    let symbols = vec![
+        Symbol {
+            address: 0x1000,
+            name: "f".to_owned(),
+        },
+        Symbol {
+            address: 0x100c,
+            name: "g".to_owned(),
+        },
+    ];
+
+    let diffs = diff(&old, &new, &symbols, &DiffOptions::default());
+    assert_eq!(diffs.len(), 1);
+    assert_eq!(diffs[0].function, Some(symbols[1].clone()));
+    assert_eq!(diffs[0].differences.len(), 2);
+    assert_eq!(
+        diffs[0].to_string(),
+        "g (0x100c):\n  0x100c: -addi a2,a0,4 +addi a4,a0,4\n  0x1010: -sub a3,a2,a0 +sub a3,a4,a0\n"
+    );
+
+    // a2 is consistently renamed to a4
+    let options = DiffOptions {
+        ignore_registers: true,
+    };
+    assert_eq!(diff(&old, &new, &symbols, &options), vec![]);
+
+    // but a renaming must be consistent within a function
+    let new = decode_region(
+        &assemble(&[
+            "addi a0,zero,1",
+            "add a1,a0,a0",
+            "jalr zero,0(ra)",
+            "addi a4,a0,4",
+            "sub a3,a2,a0",
+        ]),
+        0x1000,
+        &config,
+    );
+    let diffs = diff(&old, &new, &symbols, &options);
+    assert_eq!(
+        diffs[0].differences,
+        vec![Difference::Changed {
+            address: 0x1010,
+            old: old[4].instruction,
+            new: new[4].instruction,
+        }]
+    );
+}
+
+#[test]
+fn cross_references() {
+    let bytes = assemble(&[
+        "jal ra,20",
+        "auipc a5,1",
+        "lw a0,-4(a5)",
+        "sw a0,8(a5)",
+        "beq a0,zero,-12",
+        // f
+        "auipc t1,0",
+        "jalr ra,-20(t1)",
+        "auipc t1,0",
+        "jalr zero,-8(t1)",
+    ]);
+    let code = decode_region(&bytes, 0x1000, &DecoderConfig::default());
+    let index = XrefIndex::build(&code);
+    assert_eq!(
+        index.to(0x1014),
+        &[
+            Xref {
+                from: 0x1000,
+                kind: XrefKind::Call
+            },
+            Xref {
+                from: 0x1020,
+                kind: XrefKind::Jump
+            }
+        ]
+    );
+    assert_eq!(index.callers(0x1000).collect::<Vec<_>>(), vec![0x1014]);
+    assert_eq!(
+        index.to_kind(0x1004, XrefKind::Jump).collect::<Vec<_>>(),
+        vec![0x1010]
+    );
+    assert_eq!(
+        index.to(0x2000),
+        &[Xref {
+            from: 0x1008,
+            kind: XrefKind::Load
+        }]
+    );
+    assert_eq!(
+        index.to(0x200c),
+        &[Xref {
+            from: 0x100c,
+            kind: XrefKind::Store
+        }]
+    );
+    assert_eq!(index.to(0x3000), &[]);
+}
+
+#[test]
+fn stack_frames() {
+    let code = assemble(&[
+        "addi sp,sp,-32",
+        "sd ra,24(sp)",
+        "sd s0,16(sp)",
+        "addi s0,sp,32",
+        "ld ra,24(sp)",
+        "ld s0,16(sp)",
+        "addi sp,sp,32",
+        "jalr zero,0(ra)",
+        // a leaf that only uses the stack for locals is not a prologue
+        "c.addi16sp -16",
+        "sw a0,12(sp)",
+        "c.addi16sp 16",
+        "jalr zero,0(ra)",
+        // freeing the frame before a tail call is not an epilogue
+        "addi sp,sp,16",
+        "jal zero,-64",
+    ]);
+    let decoded = decode_region(&code, 0x1000, &DecoderConfig::default());
+    let frames: Vec<(u64, FrameMarker)> = find_frames(&decoded).into_iter().collect();
+    assert_eq!(
+        frames,
+        [
+            (0x1000, FrameMarker::Prologue { frame: 32 }),
+            (0x1018, FrameMarker::Epilogue { frame: 32 }),
+            (0x1026, FrameMarker::Epilogue { frame: 16 }),
+        ]
+    );
+    assert_eq!(frames[0].1.to_string(), "prologue, frame=32");
+}
+
+#[test]
+fn fusions() {
+    let bytes = assemble(&[
+        "lui a0,74565",
+        "addiw a0,a0,1656",
+        "auipc a1,1",
+        "addi a1,a1,-16",
+        "auipc a2,0",
+        "ld a2,8(a2)",
+        "slli a3,a3,3",
+        "add a3,a3,a1",
+        "add a4,a4,a5",
+        "lw a4,0(a4)",
+        "slli a5,a5,32",
+        "srli a5,a5,30",
+        "auipc ra,0",
+        "jalr ra,16(ra)",
+        "auipc t1,0",
+        "jalr zero,-8(t1)",
+    ]);
+    let code = decode_region(&bytes, 0x1000, &DecoderConfig::default());
+    let fusion = |address, kind, dest, value| Fusion {
+        address,
+        kind,
+        dest,
+        value,
+    };
+    assert_eq!(
+        find_fusions(&code),
+        [
+            fusion(
+                0x1000,
+                FusionKind::LoadImmediate,
+                IRegister::A0,
+                Some(0x12345678)
+            ),
+            fusion(
+                0x1008,
+                FusionKind::PcRelativeAddress,
+                IRegister::A1,
+                Some(0x1ff8)
+            ),
+            fusion(0x1010, FusionKind::LoadGlobal, IRegister::A2, Some(0x1018)),
+            fusion(0x1018, FusionKind::IndexedAddress, IRegister::A3, None),
+            fusion(0x1020, FusionKind::IndexedLoad, IRegister::A4, None),
+            fusion(0x1028, FusionKind::ZeroExtend, IRegister::A5, None),
+            fusion(
+                0x1030,
+                FusionKind::FarJump,
+                IRegister::ReturnAddress,
+                Some(0x1040)
+            ),
+            fusion(0x1038, FusionKind::FarJump, IRegister::Zero, Some(0x1030)),
+        ]
+    );
+    assert_eq!(FusionKind::LoadGlobal.to_string(), "load global");
+
+    // the second instruction must consume the result of the first, and pairs do not overlap
+    let bytes = assemble(&[
+        "lui a0,1",
+        "addi a1,a0,1",
+        "lui a0,1",
+        "addi a0,a0,1",
+        "addi a0,a0,1",
+        "slli a0,a0,4",
+        "add a0,a0,a1",
+    ]);
+    let code = decode_region(&bytes, 0, &DecoderConfig::default());
+    let found: Vec<(u64, FusionKind)> = find_fusions(&code)
+        .iter()
+        .map(|f| (f.address, f.kind))
+        .collect();
+    assert_eq!(found, [(8, FusionKind::LoadImmediate)]);
+
+    // instructions separated by an undecodable encoding are not adjacent
+    let mut bytes = assemble(&["lui a0,1"]);
+    bytes.extend([0, 0]);
+    bytes.extend(assemble(&["addi a0,a0,1"]));
+    let code = decode_region(&bytes, 0, &DecoderConfig::default());
+    assert!(find_fusions(&code).is_empty());
+}
@@ -1,8 +1,20 @@
 use riscv_codec::{
-    assembly::assemble_line,
+    assembly::{
+        WarningKind, assemble_line, assemble_line_expanded, assemble_line_expanded_with,
+        assemble_line_with, assemble_line_with_warnings,
+    },
+    cinstruction::CInstruction,
+    config::{
+        AssemblerConfig, DecoderConfig, Extensions, FormatOptions, ImmediateStyle, Profile,
+        RegisterStyle, Xlen,
+    },
+    error::DecodeError,
     immediates::{BImmediate, IImmediate, JImmediate, SImmediate, Shamt, ShamtW, UImmediate},
-    instruction::{Instruction, disassemble_instruction},
-    register::IRegister,
+    instruction::{
+        DecodedInst, DecodedInstruction, Instruction, decode_all, decode_one,
+        disassemble_instruction, disassemble_instruction_with, instruction_length,
+    },
+    register::{FRegister, IRegister},
 };
 
 #[test]
@@ -29,6 +41,41 @@ fn load_upper_immediate() {
     assert_eq!(i, i3);
 }
 
+#[test]
+fn load_upper_immediate_unsigned() {
+    let expected = Instruction::LUI {
+        dest: IRegister::A0,
+        imm: UImmediate::try_from(-1).unwrap(),
+    };
+    let bin = 0xfffff537;
+
+    // the unsigned and sign extended forms assemble to the same instruction
+    assert_eq!(assemble_line("lui a0,0xfffff").unwrap().i(), expected);
+    assert_eq!(assemble_line("lui a0,1048575").unwrap().i(), expected);
+    assert_eq!(assemble_line("lui a0,-1").unwrap().i(), expected);
+    assert!(assemble_line("lui a0,0x100000").is_err());
+    assert!(assemble_line("lui a0,-524289").is_err());
+
+    assert_eq!(Instruction::decode(bin).unwrap(), expected);
+    assert_eq!(Instruction::encode(&expected), bin);
+
+    // hex output matches objdump and assembles back to the same instruction
+    let options = FormatOptions {
+        hex_u_immediates: true,
+        ..Default::default()
+    };
+    let hex = disassemble_instruction_with(&expected, &options);
+    assert_eq!(hex, "lui a0,0xfffff");
+    assert_eq!(assemble_line(&hex).unwrap().i(), expected);
+
+    let auipc = assemble_line("auipc t0,0x80000").unwrap().i();
+    assert_eq!(
+        disassemble_instruction_with(&auipc, &options),
+        "auipc t0,0x80000"
+    );
+    assert_eq!(disassemble_instruction(&auipc), "auipc t0,-524288");
+}
+
 #[test]
 fn add_upper_immediate_to_program_counter() {
     let expected = Instruction::AUIPC {
@@ -703,6 +750,81 @@ fn shift_right_arithmetic_immediate() {
     assert_eq!(i, i3);
 }
 
+#[test]
+fn shift_immediate_rv32() {
+    let rv32 = DecoderConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+    let rv32_asm = AssemblerConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+
+    // slli t1,s0,40 sets shamt[5], which is only legal on RV64
+    let bin = 0x02841313;
+    let expected = Instruction::SLLI {
+        dest: IRegister::T1,
+        src: IRegister::FramePointer,
+        shamt: Shamt::try_from(40).unwrap(),
+    };
+    assert_eq!(Instruction::decode(bin).unwrap(), expected);
+    assert!(Instruction::decode_with(bin, &rv32).is_err());
+    assert_eq!(assemble_line("slli t1,s0,40").unwrap().i(), expected);
+    assert!(assemble_line_with("slli t1,s0,40", &rv32_asm).is_err());
+    assert!(assemble_line_with("srli t1,s0,32", &rv32_asm).is_err());
+    assert!(assemble_line_with("srai t1,s0,32", &rv32_asm).is_err());
+
+    // shift amounts below 32 are fine on RV32
+    assert_eq!(
+        Instruction::decode_with(0x4119d493, &rv32).unwrap(),
+        Instruction::SRAI {
+            dest: IRegister::S1,
+            src: IRegister::S3,
+            shamt: Shamt::try_from(17).unwrap(),
+        }
+    );
+    assert!(assemble_line_with("srli t1,s0,31", &rv32_asm).is_ok());
+}
+
+#[test]
+fn rv64_only_instructions() {
+    let rv32 = DecoderConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+    let rv32_asm = AssemblerConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+    for (bin, line) in [
+        (0x0015051b, "addiw a0,a0,1"),
+        (0x0005e503, "lwu a0,0(a1)"),
+        (0x00c5853b, "addw a0,a1,a2"),
+        (0x02c5853b, "mulw a0,a1,a2"),
+        (0x1005b52f, "lr.d a0,a1"),
+        (0xc0257553, "fcvt.l.s a0,fa0"),
+    ] {
+        let i = Instruction::decode(bin).unwrap();
+        assert!(i.is_rv64_only(), "{line}");
+        assert_eq!(i.to_string(), line);
+        assert_eq!(
+            Instruction::decode_with(bin, &rv32).map_err(String::from),
+            Err(format!(
+                "unsupported instruction {line}, which is only available on RV64"
+            ))
+        );
+        let mnemonic = line.split_once(' ').unwrap().0;
+        assert_eq!(
+            assemble_line_with(line, &rv32_asm).map(|_| ()),
+            Err(format!("{mnemonic} is only available on RV64"))
+        );
+    }
+    // ld and sd are RV32 instructions with Zilsd
+    assert!(!assemble_line("ld a0,0(a1)").unwrap().i().is_rv64_only());
+    assert!(!assemble_line("addi a0,a0,1").unwrap().i().is_rv64_only());
+}
+
 #[test]
 fn add() {
     let expected = Instruction::ADD {
@@ -1301,3 +1423,771 @@ fn fence() {
     let i3 = assemble_line(&disassemble_instruction(&i)).unwrap().i();
     assert_eq!(i, i3);
 }
+
+#[test]
+fn fence_reserved_fields() {
+    let lenient = DecoderConfig {
+        strict: false,
+        ..Default::default()
+    };
+
+    // rd is a0, which is reserved for future finer grained fences
+    let bin = 0x0a50050f;
+    assert!(Instruction::decode(bin).is_err());
+    let i = Instruction::decode_with(bin, &lenient).unwrap();
+    assert_eq!(
+        i,
+        Instruction::FENCE {
+            rd: IRegister::A0,
+            rs1: IRegister::Zero,
+            ops: 0b1010_0101,
+            fm: 0,
+        }
+    );
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // fm = 0b0001 is reserved, implementations treat it as a normal fence
+    let bin = 0x1a50000f;
+    assert!(Instruction::decode(bin).is_err());
+    let i = Instruction::decode_with(bin, &lenient).unwrap();
+    assert_eq!(disassemble_instruction(&i), "fence ir,ow");
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // fence.i with a non-zero immediate
+    assert!(Instruction::decode(0x0010100f).is_err());
+    assert_eq!(
+        Instruction::decode_with(0x0010100f, &lenient).unwrap(),
+        Instruction::FENCEI
+    );
+
+    // jalr ra,0(a0) with func3 set and slliw and srliw a0,a1,3 with func7 bits set
+    assert_eq!(
+        Instruction::decode(0x000510e7).map_err(String::from),
+        Err("reserved jalr func3: 1".to_owned())
+    );
+    assert_eq!(
+        disassemble_instruction(&Instruction::decode_with(0x000510e7, &lenient).unwrap()),
+        "jalr ra,0(a0)"
+    );
+    assert_eq!(
+        Instruction::decode(0x0235951b).map_err(String::from),
+        Err("reserved slliw func7: 1".to_owned())
+    );
+    assert_eq!(
+        disassemble_instruction(&Instruction::decode_with(0x0235951b, &lenient).unwrap()),
+        "slliw a0,a1,3"
+    );
+    assert!(Instruction::decode(0x0235d51b).is_err());
+}
+
+#[test]
+fn immediate_styles() {
+    let addi = assemble_line("addi a0,a1,-16").unwrap().i();
+    let sw = assemble_line("sw a0,16(sp)").unwrap().i();
+    let style = |immediate_style| FormatOptions {
+        immediate_style,
+        ..Default::default()
+    };
+
+    let hex = style(ImmediateStyle::Hex);
+    assert_eq!(
+        disassemble_instruction_with(&addi, &hex),
+        "addi a0,a1,-0x10"
+    );
+    assert_eq!(disassemble_instruction_with(&sw, &hex), "sw a0,0x10(sp)");
+
+    let signed = style(ImmediateStyle::Signed);
+    assert_eq!(
+        disassemble_instruction_with(&addi, &signed),
+        "addi a0,a1,-16"
+    );
+    assert_eq!(disassemble_instruction_with(&sw, &signed), "sw a0,+16(sp)");
+
+    let unsigned = style(ImmediateStyle::Unsigned);
+    assert_eq!(
+        disassemble_instruction_with(&addi, &unsigned),
+        "addi a0,a1,4080"
+    );
+    assert_eq!(disassemble_instruction_with(&sw, &unsigned), "sw a0,16(sp)");
+
+    // signed styles assemble back to the same instruction
+    for options in [hex, signed] {
+        for i in [&addi, &sw] {
+            let text = disassemble_instruction_with(i, &options);
+            assert_eq!(&assemble_line(&text).unwrap().i(), i);
+        }
+    }
+}
+
+#[test]
+fn integer_register_names() {
+    let expected = Instruction::ADD {
+        dest: IRegister::FramePointer,
+        src1: IRegister::Zero,
+        src2: IRegister::T6,
+    };
+    for line in ["add s0,zero,t6", "add fp,x0,x31", "add x8,zero,t6"] {
+        assert_eq!(assemble_line(line).unwrap().i(), expected);
+    }
+    assert!(IRegister::from_string("x32").is_err());
+    assert!(IRegister::from_string("x").is_err());
+
+    let style = |register_style| FormatOptions {
+        register_style,
+        ..Default::default()
+    };
+    assert_eq!(disassemble_instruction(&expected), "add s0,zero,t6");
+    assert_eq!(
+        disassemble_instruction_with(&expected, &style(RegisterStyle::AbiFramePointer)),
+        "add fp,zero,t6"
+    );
+    assert_eq!(
+        disassemble_instruction_with(&expected, &style(RegisterStyle::Numeric)),
+        "add x8,x0,x31"
+    );
+    // every instruction uses the register style
+    for (line, numeric) in [
+        ("addi a0,sp,16", "addi x10,x2,16"),
+        ("jalr ra,0(t0)", "jalr x1,0(x5)"),
+        ("beq a0,a1,8", "beq x10,x11,8"),
+        ("lw s1,4(gp)", "lw x9,4(x3)"),
+        ("sd s2,-8(sp)", "sd x18,-8(x2)"),
+        ("slli a0,a0,3", "slli x10,x10,3"),
+    ] {
+        let i = assemble_line(line).unwrap().i();
+        assert_eq!(
+            disassemble_instruction_with(&i, &style(RegisterStyle::Numeric)),
+            numeric
+        );
+    }
+}
+
+#[test]
+fn integer_register_numbers() {
+    assert_eq!(IRegister::iter().count(), 32);
+    for (i, register) in IRegister::iter().enumerate() {
+        assert_eq!(register.number() as usize, i);
+        assert_eq!(IRegister::try_from(i as u8), Ok(register));
+        assert_eq!(u32::from(register), i as u32);
+    }
+    assert!(IRegister::try_from(32u8).is_err());
+    assert!(IRegister::Zero < IRegister::T6);
+    assert!(IRegister::FramePointer < IRegister::A0);
+    assert_eq!(IRegister::range(IRegister::A0..=IRegister::A7).count(), 8);
+    assert_eq!(
+        FRegister::range(FRegister::FS2..=FRegister::FT11).collect::<Vec<_>>(),
+        FRegister::iter().skip(18).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn instruction_ordering() {
+    let mut instructions: Vec<Instruction> = [
+        "sub a0,a1,a2",
+        "addi a0,a0,2",
+        "addi a0,a0,-1",
+        "lui a0,1",
+        "addi a0,a0,2",
+        "addi a1,a0,0",
+    ]
+    .iter()
+    .map(|line| assemble_line(line).unwrap().i())
+    .collect();
+    instructions.sort();
+    instructions.dedup();
+    let lines: Vec<String> = instructions.iter().map(|i| i.to_string()).collect();
+    // by variant, then by operand in declaration order
+    assert_eq!(
+        lines,
+        [
+            "lui a0,1",
+            "addi a0,a0,-1",
+            "addi a0,a0,2",
+            "addi a1,a0,0",
+            "sub a0,a1,a2"
+        ]
+    );
+}
+
+#[test]
+fn pseudo_instructions() {
+    let options = FormatOptions {
+        pseudo_instructions: true,
+        ..Default::default()
+    };
+    for (line, pseudo) in [
+        ("addi zero,zero,0", "nop"),
+        ("addi a0,zero,-5", "li a0,-5"),
+        ("addi a0,a1,0", "mv a0,a1"),
+        ("xori a0,a1,-1", "not a0,a1"),
+        ("sub a0,zero,a1", "neg a0,a1"),
+        ("addiw a0,a1,0", "sext.w a0,a1"),
+        ("sltiu a0,a1,1", "seqz a0,a1"),
+        ("sltu a0,zero,a1", "snez a0,a1"),
+        ("beq a0,zero,8", "beqz a0,8"),
+        ("bge zero,a0,8", "blez a0,8"),
+        ("blt zero,a0,8", "bgtz a0,8"),
+        ("jal zero,-4", "j -4"),
+        ("jal ra,16", "jal 16"),
+        ("jalr zero,0(ra)", "ret"),
+        ("jalr zero,0(t0)", "jr t0"),
+        ("jalr ra,0(t0)", "jalr t0"),
+        // instructions without a pseudo-instruction are unchanged
+        ("addi a0,a1,1", "addi a0,a1,1"),
+        ("jalr ra,4(t0)", "jalr ra,4(t0)"),
+    ] {
+        let i = assemble_line(line).unwrap().i();
+        assert_eq!(disassemble_instruction_with(&i, &options), pseudo);
+        assert_eq!(disassemble_instruction(&i), line);
+    }
+}
+
+#[test]
+fn unknown_mnemonic_suggestions() {
+    assert_eq!(
+        assemble_line("adll a0,a1,a2").unwrap_err(),
+        "unknown mnemonic: adll, did you mean add, addi, addw, sll?"
+    );
+    assert_eq!(
+        assemble_line("jall ra,16").unwrap_err(),
+        "unknown mnemonic: jall, did you mean jal, jalr?"
+    );
+    assert_eq!(
+        assemble_line("fmvxw a0,fa0").unwrap_err(),
+        "unknown mnemonic: fmvxw, did you mean fmv.x.w?"
+    );
+    assert_eq!(
+        assemble_line("c.lwspp a0,4(sp)").unwrap_err(),
+        "unknown compressed instruction mnemonic: c.lwspp, did you mean c.lwsp?"
+    );
+    // nothing is close enough
+    assert_eq!(
+        assemble_line("xyzzy a0").unwrap_err(),
+        "unknown mnemonic: xyzzy"
+    );
+}
+
+#[test]
+fn operand_syntax_errors() {
+    assert_eq!(
+        assemble_line("lw a0").unwrap_err(),
+        "lw expects: lw rd, offset(rs1), got 1 operand"
+    );
+    assert_eq!(
+        assemble_line("lw a0,x(sp)").unwrap_err(),
+        "lw expects: lw rd, offset(rs1), operand 2 (offset(rs1)) is invalid: unable to parse int:x"
+    );
+    assert_eq!(
+        assemble_line("addi a0,a9,1").unwrap_err(),
+        "addi expects: addi rd, rs1, imm, operand 2 (rs1) is invalid: converted invalid str to integer register a9"
+    );
+    assert_eq!(
+        assemble_line("fadd.s.rtz fa0,fa1,a2").unwrap_err(),
+        "fadd.s.rtz expects: fadd.s fd, fs1, fs2, operand 3 (fs2) is invalid: converted invalid str to float register a2"
+    );
+    assert_eq!(
+        assemble_line("c.lw a0,0(q0)").unwrap_err(),
+        "c.lw expects: c.lw rd', offset(rs1'), operand 2 (offset(rs1')) is invalid: converted invalid str to integer register q0"
+    );
+    assert_eq!(
+        assemble_line("fence.i a0").unwrap_err(),
+        "fence.i expects: fence.i, got 1 operand"
+    );
+    // well formed operands keep the more specific error
+    assert_eq!(
+        assemble_line("addi a0,a1,2048").unwrap_err(),
+        "2048 is out of range for IImmediate (-2048 to 2047)"
+    );
+}
+
+#[test]
+fn assembler_warnings() {
+    let config = AssemblerConfig::default();
+    let kinds = |line: &str| -> Vec<WarningKind> {
+        let (_, warnings) = assemble_line_with_warnings(line, &config).unwrap();
+        warnings.iter().map(|w| w.kind).collect()
+    };
+    // the nop and standard hints are fine
+    for line in [
+        "addi zero,zero,0",
+        "add zero,zero,sp",
+        "ori zero,a0,1",
+        "fence w,r",
+        "c.add zero,t0",
+        "jalr t0,0(a0)",
+        "lui a0,0x7ffff",
+        "beq a0,a1,-4",
+    ] {
+        assert_eq!(kinds(line), [], "{line}");
+    }
+    assert_eq!(kinds("addi zero,a0,1"), [WarningKind::Hint]);
+    assert_eq!(kinds("c.li zero,1"), [WarningKind::Hint]);
+    assert_eq!(kinds("jal a0,8"), [WarningKind::RegisterRole]);
+    assert_eq!(kinds("lw tp,0(sp)"), [WarningKind::RegisterRole]);
+    assert_eq!(kinds("sltiu a0,a1,-1"), [WarningKind::SignExtension]);
+    assert_eq!(kinds("beq a0,a1,-2"), [WarningKind::UnalignedTarget]);
+
+    let (_, warnings) = assemble_line_with_warnings("lui a0,0x80000", &config).unwrap();
+    assert_eq!(
+        warnings[0].to_string(),
+        "warning[W0002]: the immediate of lui is sign-extended to 0xffffffff80000000"
+    );
+    let rv32 = AssemblerConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+    assert!(
+        assemble_line_with_warnings("lui a0,0x80000", &rv32)
+            .unwrap()
+            .1
+            .is_empty()
+    );
+}
+
+#[test]
+fn profiles() {
+    use riscv_codec::cinstruction::CInstruction;
+
+    let mul = 0x02b50533;
+    let fence_i = 0x0000100f;
+    let csrrw = 0x30051573;
+    let rvi20 = DecoderConfig::profile(Profile::Rvi20U64);
+    assert!(Instruction::decode_with(0x00b50533, &rvi20).is_ok());
+    assert_eq!(
+        Instruction::decode_with(mul, &rvi20).map_err(String::from),
+        Err("unsupported instruction mul a0,a0,a1, which requires the M extension".to_owned())
+    );
+    assert!(Instruction::decode_with(csrrw, &rvi20).is_err());
+    assert!(CInstruction::decode_with(0x0505, &rvi20).is_err());
+    assert_eq!(DecoderConfig::profile(Profile::Rvi20U32).xlen, Xlen::Rv32);
+
+    for profile in [Profile::Rva20U64, Profile::Rva22U64, Profile::Rva23U64] {
+        let config = DecoderConfig::profile(profile);
+        assert_eq!(config.xlen, Xlen::Rv64);
+        assert!(Instruction::decode_with(mul, &config).is_ok());
+        assert!(Instruction::decode_with(csrrw, &config).is_ok());
+        assert!(CInstruction::decode_with(0x0505, &config).is_ok());
+        // Zifencei is only mandated by the supervisor profiles
+        assert!(Instruction::decode_with(fence_i, &config).is_err());
+        assert!(profile.mandatory().contains(&"Zicntr"));
+    }
+    assert!(Profile::Rva23U64.mandatory().contains(&"V"));
+    assert!(!Profile::Rva22U64.mandatory().contains(&"V"));
+
+    let rvi20_asm = AssemblerConfig::profile(Profile::Rvi20U64);
+    assert_eq!(
+        assemble_line_with("mul a0,a0,a1", &rvi20_asm),
+        Err("mul requires the M extension".to_owned())
+    );
+    assert!(assemble_line_with("c.addi a0,1", &rvi20_asm).is_err());
+    assert!(assemble_line_with("add a0,a0,a1", &rvi20_asm).is_ok());
+}
+
+#[test]
+fn zilsd_register_pairs() {
+    let zilsd = DecoderConfig {
+        xlen: Xlen::Rv32,
+        extensions: Extensions {
+            zilsd: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let rv32 = DecoderConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+    let zilsd_asm = AssemblerConfig {
+        xlen: Xlen::Rv32,
+        extensions: zilsd.extensions,
+    };
+
+    // ld a2,8(sp) and sd s0,16(a0)
+    for (bin, line) in [(0x00813603, "ld a2,8(sp)"), (0x00853823, "sd s0,16(a0)")] {
+        let i = Instruction::decode_with(bin, &zilsd).unwrap();
+        assert_eq!(i.to_string(), line);
+        assert_eq!(assemble_line_with(line, &zilsd_asm).unwrap().i(), i);
+        assert_eq!(
+            Instruction::decode_with(bin, &rv32).map_err(String::from),
+            Err(format!(
+                "unsupported instruction {line}, which requires the Zilsd extension"
+            ))
+        );
+    }
+    // a destination of x0 discards the loaded values
+    assert!(assemble_line_with("ld zero,0(a0)", &zilsd_asm).is_ok());
+
+    // the register pair must start at an even register
+    let ld = Instruction::encode(&assemble_line("ld a1,0(a0)").unwrap().i());
+    assert_eq!(
+        Instruction::decode_with(ld, &zilsd).map_err(String::from),
+        Err("ld on RV32 accesses a register pair and must use an even register, got a1".to_owned())
+    );
+    assert!(Instruction::decode(ld).is_ok());
+    assert!(assemble_line_with("ld a1,0(a0)", &zilsd_asm).is_err());
+    assert!(assemble_line_with("sd s1,0(a0)", &zilsd_asm).is_err());
+}
+
+#[test]
+fn isa_strings() {
+    use riscv_codec::config::parse_isa_string;
+
+    let all = Extensions::default();
+    assert_eq!(
+        parse_isa_string("rv64imafdc_zicsr_zifencei"),
+        Ok((Xlen::Rv64, all))
+    );
+    assert_eq!(parse_isa_string("RV64GC"), Ok((Xlen::Rv64, all)));
+    assert_eq!(
+        parse_isa_string("rv32i2p1_m2p0_a2p1_c2p0_zicbop1p0_zba"),
+        Ok((
+            Xlen::Rv32,
+            Extensions {
+                m: true,
+                a: true,
+                c: true,
+                zicbop: true,
+                ..Extensions::none()
+            }
+        ))
+    );
+    // zclsd implies zilsd
+    assert_eq!(
+        parse_isa_string("rv32ic_zclsd"),
+        Ok((
+            Xlen::Rv32,
+            Extensions {
+                c: true,
+                zilsd: true,
+                zclsd: true,
+                ..Extensions::none()
+            }
+        ))
+    );
+    // d implies f, which implies zicsr
+    assert_eq!(
+        parse_isa_string("rv64id"),
+        Ok((
+            Xlen::Rv64,
+            Extensions {
+                f: true,
+                d: true,
+                zicsr: true,
+                ..Extensions::none()
+            }
+        ))
+    );
+    for isa in ["rv128i", "rv64", "rv64xm", "rv64i_", "rv64i_z-b", "rv64i,m"] {
+        assert!(parse_isa_string(isa).is_err(), "{isa}");
+    }
+}
+
+#[test]
+fn isa_string_round_trip() {
+    use riscv_codec::config::{AssemblerConfig, IsaString};
+
+    for (isa, canonical) in [
+        ("rv64gc", "rv64imafdc_zicsr_zifencei"),
+        ("RV64GC_ZBB_ZBA", "rv64imafdc_zicsr_zifencei_zba_zbb"),
+        ("rv32i2p1_m2p0_zifencei2p0", "rv32im_zifencei"),
+        ("rv64id", "rv64ifd_zicsr"),
+        ("rv32ic_zclsd", "rv32ic_zilsd_zclsd"),
+        (
+            "rv64iv_xandesperf_sstc_zvbb_zicond",
+            "rv64ifdv_zicond_zicsr_zvbb_sstc_xandesperf",
+        ),
+        ("rv64i", "rv64i"),
+    ] {
+        let parsed = IsaString::try_from(isa).unwrap();
+        assert_eq!(parsed.to_string(), canonical, "{isa}");
+        assert_eq!(IsaString::try_from(canonical), Ok(parsed), "{isa}");
+    }
+
+    let parsed = IsaString::try_from("rv64gc_zba").unwrap();
+    assert_eq!(parsed.unsupported, ["zba"]);
+    assert_eq!(
+        AssemblerConfig::isa(&parsed),
+        AssemblerConfig {
+            xlen: Xlen::Rv64,
+            extensions: Extensions::default(),
+        }
+    );
+    assert_eq!(DecoderConfig::isa(&parsed), DecoderConfig::default());
+    assert!(IsaString::try_from("rv64i_").is_err());
+}
+
+#[test]
+fn raw_encoding() {
+    let decoded = DecodedInstruction::decode(0x00190937).unwrap();
+    assert_eq!(decoded.raw, 0x00190937);
+    assert_eq!(decoded.bytes(), [0x37, 0x09, 0x19, 0x00]);
+    assert_eq!(decoded.to_string(), "lui s2,400");
+    assert!(decoded.is_canonical());
+
+    // fence.i with rd set to ra, which only lenient decoding accepts
+    let lenient = DecoderConfig {
+        strict: false,
+        ..Default::default()
+    };
+    assert!(DecodedInstruction::decode(0x0000108f).is_err());
+    let decoded = DecodedInstruction::decode_with(0x0000108f, &lenient).unwrap();
+    assert_eq!(decoded.instruction, Instruction::FENCEI);
+    assert_eq!(decoded.raw, 0x0000108f);
+    assert_eq!(decoded.ignored, 0x00000080);
+    assert!(!decoded.is_canonical());
+    assert_eq!(Instruction::encode(&decoded.instruction), 0x0000100f);
+    assert_eq!(decoded.encode(), 0x0000108f);
+
+    // lossless decoding also keeps the reserved rounding mode of fadd.s ft0,ft1,ft2
+    let config = DecoderConfig::default();
+    let decoded = DecodedInstruction::decode_lossless(0x0020d053, &config).unwrap();
+    assert_eq!(decoded.ignored, 0);
+    assert_eq!(decoded.encode(), 0x0020d053);
+    assert_eq!(
+        DecodedInstruction::decode_lossless(0x0000108f, &config)
+            .unwrap()
+            .encode(),
+        0x0000108f
+    );
+}
+
+#[test]
+fn malformed_input_errors() {
+    for line in [
+        "lw a0,a0",
+        "sd a0,8",
+        "jalr ra,a0",
+        "c.lw a0,a1",
+        "lr.w",
+        "lr.d.aq a0",
+        "sc.w a0,a1",
+        "amoadd.w",
+        "amoswap.d.aqrl a0,a1",
+        "feq.d a0,fa0,fa1",
+        "fclass.q a0,fa0",
+        "flt.h a0,fa0,fa1",
+    ] {
+        assert!(assemble_line(line).is_err(), "{line}");
+    }
+    assert_eq!(
+        assemble_line("feq.d a0,fa0,fa1"),
+        Err("feq.d is not supported, only single and quad precision are".to_owned())
+    );
+}
+
+#[test]
+fn address_expressions() {
+    // the offset defaults to 0 and whitespace is allowed inside the parentheses
+    for (line, expected) in [
+        ("lw a0,(a1)", "lw a0,0(a1)"),
+        ("sd ra, 8 ( sp )", "sd ra,8(sp)"),
+        ("flw fa0,-4( s0)", "flw fa0,-4(s0)"),
+        ("jalr ra,(t0)", "jalr ra,0(t0)"),
+        ("c.lw a0,(a1)", "c.lw a0,0(a1)"),
+        ("c.sd s0, 16 (a5 )", "c.sd s0,16(a5)"),
+    ] {
+        let i = assemble_line(line).unwrap();
+        assert_eq!(i.to_string(), expected, "{line}");
+    }
+    for line in [
+        "lw a0,8(sp",
+        "lw a0,8 sp)",
+        "lw a0,8(sp)x",
+        "lw a0,((sp))",
+        "c.lw a0,(sp)",
+    ] {
+        assert!(assemble_line(line).is_err(), "{line}");
+    }
+    assert_eq!(
+        assemble_line("lw a0,4(a9)").unwrap_err(),
+        "lw expects: lw rd, offset(rs1), operand 2 (offset(rs1)) is invalid: converted invalid str to integer register a9"
+    );
+}
+
+#[test]
+fn decode_one_instruction() {
+    // lui s2,400 followed by c.li a0,0
+    let bytes = [0x37, 0x09, 0x19, 0x00, 0x01, 0x45];
+    let (decoded, length) = decode_one(&bytes).unwrap();
+    assert_eq!(length, 4);
+    assert!(matches!(decoded, DecodedInst::Full(_)));
+    assert_eq!(decoded.to_string(), "lui s2,400");
+    let (decoded, length) = decode_one(&bytes[length..]).unwrap();
+    assert_eq!((length, decoded.len(), decoded.raw()), (2, 2, 0x4501));
+    assert_eq!(decoded.to_string(), "c.li a0,0");
+
+    assert_eq!(instruction_length(0x37), Some(4));
+    assert_eq!(instruction_length(0x01), Some(2));
+    assert_eq!(instruction_length(0x1f), Some(6));
+    assert_eq!(instruction_length(0x3f), Some(8));
+    assert_eq!(instruction_length(0x7f), None);
+
+    assert!(decode_one(&[]).is_err());
+    assert_eq!(
+        decode_one(&bytes[..3]).map(|(_, length)| length),
+        Err(DecodeError {
+            raw: 0x190937,
+            length: 4,
+            offset: Some(0),
+            message: "the instruction needs 4 bytes, got 3".to_owned(),
+        })
+    );
+    assert!(decode_one(&[0x1f, 0, 0, 0, 0, 0]).is_err());
+}
+
+#[test]
+fn decode_errors() {
+    // an unknown funct7 of OP whose upper half is a reserved compressed encoding, and a truncated lui
+    let bytes = [0x33, 0x05, 0x00, 0x80, 0x37, 0x09, 0x10, 0x00, 0x37, 0x09];
+    let decoded: Vec<_> = decode_all(&bytes).collect();
+    assert_eq!(decoded.len(), 4);
+    let error = decoded[0].as_ref().unwrap_err();
+    assert_eq!(
+        (error.raw, error.length, error.offset),
+        (0x80000533, 4, Some(0))
+    );
+    assert_eq!(
+        (error.opcode(), error.funct3(), error.funct7()),
+        (0b0110011, 0b000, Some(0b1000000))
+    );
+    assert_eq!(
+        error.report(),
+        format!(
+            "0x0: {} (80000533: opcode 0b0110011, funct3 0b000, funct7 0b1000000)",
+            error.message
+        )
+    );
+    // decoding resumes at the next halfword
+    let error = decoded[1].as_ref().unwrap_err();
+    assert_eq!(
+        (error.raw, error.length, error.offset),
+        (0x8000, 2, Some(2))
+    );
+    assert_eq!(
+        (error.opcode(), error.funct3(), error.funct7()),
+        (0b00, 0b100, None)
+    );
+    assert_eq!(decoded[2].as_ref().unwrap().0, 4);
+    assert_eq!(decoded[2].as_ref().unwrap().1.to_string(), "lui s2,256");
+    let error = decoded[3].as_ref().unwrap_err();
+    assert_eq!((error.raw, error.offset), (0x0937, Some(8)));
+    assert_eq!(error.to_string(), "the instruction needs 4 bytes, got 2");
+
+    let error = CInstruction::decode(0x0000).unwrap_err();
+    assert_eq!((error.raw, error.length, error.offset), (0, 2, None));
+    assert_eq!(
+        String::from(error),
+        "the all zero halfword is an illegal instruction"
+    );
+}
+
+#[test]
+fn mnemonic_columns() {
+    let options = FormatOptions {
+        mnemonic_width: Some(8),
+        ..Default::default()
+    };
+    let lines: Vec<String> = [
+        "addi a0,a0,1",
+        "fcvt.w.s.rtz a0,fa0",
+        "fence.i",
+        "add a0,zero,a1",
+    ]
+    .iter()
+    .map(|line| disassemble_instruction_with(&assemble_line(line).unwrap().i(), &options))
+    .collect();
+    assert_eq!(
+        lines,
+        [
+            "addi    a0,a0,1",
+            "fcvt.w.s.rtz a0,fa0",
+            "fence.i",
+            "add     a0,zero,a1"
+        ]
+    );
+    let pseudo = FormatOptions {
+        pseudo_instructions: true,
+        ..options
+    };
+    let mv = assemble_line("addi a0,a1,0").unwrap().i();
+    assert_eq!(disassemble_instruction_with(&mv, &pseudo), "mv      a0,a1");
+    let text = disassemble_instruction_with(&mv, &options);
+    assert_eq!(assemble_line(&text).unwrap().i(), mv);
+}
+
+#[test]
+fn expanded_pseudo_instructions() {
+    let expand = |line: &str, config: &AssemblerConfig| -> Vec<String> {
+        assemble_line_expanded_with(line, config)
+            .unwrap()
+            .iter()
+            .map(|inst| inst.to_string())
+            .collect()
+    };
+    let rv64 = AssemblerConfig::default();
+    let rv32 = AssemblerConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+    assert_eq!(expand("li a0,-5", &rv64), ["addi a0,zero,-5"]);
+    assert_eq!(
+        expand("li a0,0x12345fff", &rv64),
+        ["lui a0,74566", "addiw a0,a0,-1"]
+    );
+    assert_eq!(
+        expand("li a0,0x12345fff", &rv32),
+        ["lui a0,74566", "addi a0,a0,-1"]
+    );
+    assert_eq!(expand("li a0,0x80000000", &rv32), ["lui a0,-524288"]);
+    assert_eq!(expand("li a0,0xffffffff", &rv32), ["addi a0,zero,-1"]);
+    assert_eq!(
+        expand("li a0,0x100000000", &rv64),
+        ["addi a0,zero,1", "slli a0,a0,32"]
+    );
+    assert_eq!(
+        expand("la a1,0x1800", &rv64),
+        ["auipc a1,2", "addi a1,a1,-2048"]
+    );
+    assert_eq!(expand("call -8", &rv64), ["auipc ra,0", "jalr ra,-8(ra)"]);
+    assert_eq!(
+        expand("tail 4096", &rv64),
+        ["auipc t1,1", "jalr zero,0(t1)"]
+    );
+
+    // single instruction pseudo-instructions are what the disassembler folds
+    let options = FormatOptions {
+        pseudo_instructions: true,
+        ..Default::default()
+    };
+    for line in [
+        "nop",
+        "ret",
+        "mv a0,a1",
+        "not a0,a1",
+        "neg a0,a1",
+        "seqz a0,a1",
+        "snez a0,a1",
+        "beqz a0,16",
+        "bgtz a1,-4",
+        "j 8",
+        "jal 8",
+        "jr a5",
+        "jalr a5",
+    ] {
+        let expanded = assemble_line_expanded(line).unwrap();
+        assert_eq!(expanded.len(), 1, "{line}");
+        let full = expanded[0].full().unwrap();
+        assert_eq!(full.display_with(&options).to_string(), line);
+    }
+    // other lines are assembled as is
+    assert_eq!(
+        assemble_line_expanded("c.addi a0,1").unwrap(),
+        [assemble_line("c.addi a0,1").unwrap()]
+    );
+    assert_eq!(
+        assemble_line_expanded("li a0"),
+        Err("li requires 2 operands".to_owned())
+    );
+    assert!(assemble_line_expanded_with("li a0,0x100000000", &rv32).is_err());
+    assert!(assemble_line_expanded("call 0x80000000").is_err());
+}
@@ -1,10 +1,15 @@
-use riscv_codec::assembly::assemble_line;
-use riscv_codec::cinstruction::CInstruction;
+use riscv_codec::assembly::{assemble_line, assemble_line_with};
+use riscv_codec::cinstruction::{CInstruction, DecodedCInstruction};
+use riscv_codec::config::{AssemblerConfig, DecoderConfig, Extensions, Xlen};
 use riscv_codec::immediates::{
     C16SPImmediate, CBImmediate, CDImmediate, CDSPImmediate, CIImmediate, CJImmediate,
-    CSDSPImmediate, CSWSPImmediate, CShamt, CWImmediate, CWSPImmediate, CWideImmediate,
+    CLUIImmediate, CSDSPImmediate, CSWSPImmediate, CShamt, CWImmediate, CWSPImmediate,
+    CWideImmediate, UImmediate,
+};
+use riscv_codec::instruction::{Inst, Instruction, decode_one};
+use riscv_codec::register::{
+    CFRegister, CIRegister, FRegister, IRegister, RegisterClass, RegisterKind,
 };
-use riscv_codec::register::{CFRegister, CIRegister, FRegister, IRegister};
 
 #[test]
 fn add_4_immediate_stack_pointer() {
@@ -300,7 +305,7 @@ fn add_16_immediate_stack_pointer() {
 fn load_upper_immediate() {
     let expected = CInstruction::LUI {
         dest: IRegister::S9,
-        imm: CIImmediate::try_from(24).unwrap(),
+        imm: CLUIImmediate::try_from(24).unwrap(),
     };
     let bin = 0x6ce1;
 
@@ -320,6 +325,19 @@ fn load_upper_immediate() {
     println!("{}", CInstruction::disassemble(&i));
     let i3 = assemble_line(&CInstruction::disassemble(&i)).unwrap().c();
     assert_eq!(i, i3);
+
+    // check expansion
+    assert_eq!(
+        i.expand(),
+        Instruction::LUI {
+            dest: IRegister::S9,
+            imm: UImmediate::try_from(24).unwrap(),
+        }
+    );
+
+    // the zero immediate is reserved
+    assert!(CInstruction::decode(0x6c81).is_err());
+    assert!(assemble_line("c.lui s9,0").is_err());
 }
 
 #[test]
@@ -884,6 +902,37 @@ fn environment_break() {
     assert_eq!(i, i3);
 }
 
+#[test]
+fn no_operation() {
+    let expected = CInstruction::NOP;
+    let bin = 0x0001;
+
+    // check assembler
+    let i = assemble_line("c.nop").unwrap().c();
+    assert_eq!(i, expected);
+
+    // check decoder
+    let i2 = CInstruction::decode(bin).unwrap();
+    assert_eq!(i2, expected);
+
+    // check encoder
+    let b = CInstruction::encode(&i);
+    assert_eq!(b, bin);
+
+    // check disassembler
+    assert_eq!(CInstruction::disassemble(&i), "c.nop");
+    let i3 = assemble_line(&CInstruction::disassemble(&i)).unwrap().c();
+    assert_eq!(i, i3);
+
+    // c.addi x0,0 is the same encoding
+    let addi = assemble_line("c.addi zero,0").unwrap().c();
+    assert_eq!(CInstruction::encode(&addi), bin);
+    assert_eq!(
+        CInstruction::decode(CInstruction::encode(&addi)),
+        Ok(CInstruction::NOP)
+    );
+}
+
 #[test]
 fn jump_and_link_register() {
     let expected = CInstruction::JALR { src: IRegister::S5 };
@@ -1010,3 +1059,396 @@ fn store_double_stack_pointer() {
     let i3 = assemble_line(&CInstruction::disassemble(&i)).unwrap().c();
     assert_eq!(i, i3);
 }
+
+#[test]
+fn non_compressible_operands() {
+    assert_eq!(
+        assemble_line("c.lw t3,4(a0)"),
+        Err("c.lw requires a0-a5/s0-s1, got t3 (use lw instead)".to_owned())
+    );
+    assert_eq!(
+        assemble_line("c.sub x10,x9").unwrap().c(),
+        assemble_line("c.sub a0,s1").unwrap().c()
+    );
+    assert_eq!(
+        assemble_line("c.sub a0,t0"),
+        Err("c.sub requires a0-a5/s0-s1, got t0 (use sub instead)".to_owned())
+    );
+    assert_eq!(
+        assemble_line("c.fld ft0,8(a0)"),
+        Err("c.fld requires fa0-fa5/fs0-fs1, got ft0 (use fld instead)".to_owned())
+    );
+    assert_eq!(
+        assemble_line("c.lw a0,6(a1)"),
+        Err(
+            "c.lw cannot encode 6: 6 is not a multiple of 4 for CWImmediate (use lw instead)"
+                .to_owned()
+        )
+    );
+    assert_eq!(
+        assemble_line("c.li a0,32"),
+        Err("c.li cannot encode 32: 32 is out of range for CIImmediate (-32 to 31) (use addi instead)".to_owned())
+    );
+    assert_eq!(
+        assemble_line("c.lwsp a0,256"),
+        Err("c.lwsp cannot encode 256: 256 is out of range for CWSPImmediate (0 to 252) (use lw instead)".to_owned())
+    );
+}
+
+#[test]
+fn compressed_register_conversions() {
+    for n in 0..8u16 {
+        let c = CFRegister::try_from(n).unwrap();
+        let f = FRegister::from(c);
+        assert_eq!(u32::from(f), n as u32 + 8);
+        assert_eq!(CFRegister::try_from(f), Ok(c));
+        assert_eq!(c.rs2(), n << 2);
+
+        let c = CIRegister::from(n);
+        let i = IRegister::from(c);
+        assert_eq!(u32::from(i), n as u32 + 8);
+        assert_eq!(CIRegister::try_from(i), Ok(c));
+    }
+    assert!(CFRegister::try_from(FRegister::FT0).is_err());
+    assert!(CFRegister::try_from(FRegister::FA6).is_err());
+    assert!(CIRegister::try_from(IRegister::T2).is_err());
+}
+
+#[test]
+fn register_class() {
+    fn describe<R: RegisterClass>(register: R) -> (&'static str, u8, RegisterKind) {
+        (register.name(), register.number(), register.kind())
+    }
+    assert_eq!(describe(IRegister::A0), ("a0", 10, RegisterKind::Integer));
+    assert_eq!(describe(CIRegister::A0), ("a0", 10, RegisterKind::Integer));
+    assert_eq!(describe(FRegister::FA0), ("fa0", 10, RegisterKind::Float));
+    assert_eq!(describe(CFRegister::FA0), ("fa0", 10, RegisterKind::Float));
+}
+
+#[test]
+fn xlen_dependent_encodings() {
+    let rv32 = DecoderConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+    let rv32_asm = AssemblerConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+
+    // c.jal on RV32, c.addiw on RV64
+    let bin = 0x3ffd;
+    let jal = CInstruction::JAL {
+        offset: CJImmediate::try_from(-2).unwrap(),
+    };
+    assert_eq!(CInstruction::decode_with(bin, &rv32).unwrap(), jal);
+    assert_eq!(
+        CInstruction::decode(bin).unwrap(),
+        CInstruction::ADDIW {
+            dest: IRegister::T6,
+            imm: CIImmediate::try_from(-1).unwrap(),
+        }
+    );
+    assert_eq!(CInstruction::encode(&jal), bin);
+    assert_eq!(assemble_line_with("c.jal -2", &rv32_asm).unwrap().c(), jal);
+    assert!(assemble_line("c.jal -2").is_err());
+    assert!(assemble_line_with("c.addiw t6,-1", &rv32_asm).is_err());
+
+    // c.flw on RV32, c.ld on RV64
+    let bin = 0x61c8;
+    let flw = CInstruction::FLW {
+        dest: CFRegister::FA0,
+        base: CIRegister::A1,
+        offset: CWImmediate::try_from(4).unwrap(),
+    };
+    assert_eq!(CInstruction::decode_with(bin, &rv32).unwrap(), flw);
+    assert_eq!(CInstruction::disassemble(&flw), "c.flw fa0,4(a1)");
+    assert_eq!(
+        CInstruction::disassemble(&CInstruction::decode(bin).unwrap()),
+        "c.ld a0,128(a1)"
+    );
+    assert_eq!(CInstruction::encode(&flw), bin);
+    assert_eq!(
+        assemble_line_with("c.flw fa0,4(a1)", &rv32_asm)
+            .unwrap()
+            .c(),
+        flw
+    );
+
+    // the stack pointer relative forms round trip on RV32
+    for line in ["c.fsw fs1,8(a5)", "c.flwsp ft3,16", "c.fswsp fs11,252"] {
+        let i = assemble_line_with(line, &rv32_asm).unwrap().c();
+        assert_eq!(
+            CInstruction::decode_with(CInstruction::encode(&i), &rv32).unwrap(),
+            i
+        );
+        assert_eq!(CInstruction::disassemble(&i), line);
+        assert!(assemble_line(line).is_err());
+    }
+
+    // shift amounts and the word arithmetic instructions are restricted on RV32
+    let slli = CInstruction::encode(&assemble_line("c.slli a0,32").unwrap().c());
+    assert!(CInstruction::decode_with(slli, &rv32).is_err());
+    assert!(assemble_line_with("c.slli a0,32", &rv32_asm).is_err());
+    let subw = CInstruction::encode(&assemble_line("c.subw a0,a1").unwrap().c());
+    assert!(CInstruction::decode_with(subw, &rv32).is_err());
+}
+
+#[test]
+fn zclsd_register_pairs() {
+    let extensions = Extensions {
+        zclsd: true,
+        zilsd: true,
+        ..Default::default()
+    };
+    let zclsd = DecoderConfig {
+        xlen: Xlen::Rv32,
+        extensions,
+        ..Default::default()
+    };
+    let zclsd_asm = AssemblerConfig {
+        xlen: Xlen::Rv32,
+        extensions,
+    };
+    let rv32_asm = AssemblerConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+
+    // the encoding of c.flw is c.ld with Zclsd
+    let ld = CInstruction::decode_with(0x61c8, &zclsd).unwrap();
+    assert_eq!(
+        ld,
+        CInstruction::LD {
+            dest: CIRegister::A0,
+            base: CIRegister::A1,
+            offset: CDImmediate::try_from(128).unwrap(),
+        }
+    );
+    assert_eq!(
+        assemble_line_with("c.ld a0,128(a1)", &zclsd_asm)
+            .unwrap()
+            .c(),
+        ld
+    );
+    assert!(assemble_line_with("c.ld a0,128(a1)", &rv32_asm).is_err());
+    assert!(assemble_line_with("c.flw fa0,4(a1)", &zclsd_asm).is_err());
+
+    for line in ["c.sd s0,8(a5)", "c.ldsp a2,16", "c.sdsp zero,248"] {
+        let i = assemble_line_with(line, &zclsd_asm).unwrap().c();
+        assert_eq!(
+            CInstruction::decode_with(CInstruction::encode(&i), &zclsd).unwrap(),
+            i
+        );
+        assert_eq!(i.to_string(), line);
+    }
+
+    // the register pair must start at an even register, c.ldsp must not load into x0
+    for line in [
+        "c.ld a1,0(a0)",
+        "c.sd s1,0(a0)",
+        "c.ldsp t0,8",
+        "c.sdsp ra,8",
+    ] {
+        let rv64 = assemble_line(line).unwrap().c();
+        assert!(
+            CInstruction::decode_with(CInstruction::encode(&rv64), &zclsd).is_err(),
+            "{line}"
+        );
+        assert!(assemble_line_with(line, &zclsd_asm).is_err(), "{line}");
+    }
+    assert!(CInstruction::decode_with(0x6002, &zclsd).is_err());
+}
+
+#[test]
+fn hints() {
+    // c.addi a0,0; c.addi zero,1; c.li zero,5; c.mv zero,a0; c.slli a0,0
+    for bin in [0x0501, 0x0005, 0x4015, 0x802a, 0x0502] {
+        assert!(CInstruction::decode(bin).unwrap().is_hint(), "{bin:#06x}");
+    }
+    // c.addi a0,1; c.nop
+    for bin in [0x0505, 0x0001] {
+        assert!(!CInstruction::decode(bin).unwrap().is_hint(), "{bin:#06x}");
+    }
+    // reserved encodings that are not hints: c.jr zero; c.lwsp zero,0; c.addi16sp 0
+    for bin in [0x8002, 0x4002, 0x6101] {
+        assert!(CInstruction::decode(bin).is_err(), "{bin:#06x}");
+    }
+}
+
+#[test]
+fn reserved_encodings() {
+    let cases = [
+        (0x0000, "the all zero halfword is an illegal instruction"),
+        (0x0010, "c.addi4spn immediate must not be zero"),
+        (0x6101, "c.addi16sp immediate must not be zero"),
+        (0x6501, "c.lui immediate must not be zero"),
+        (0x8002, "c.jr must not jump to x0"),
+        (0x4002, "c.lwsp and c.ldsp must not load into x0"),
+        (0x2005, "c.addiw must not write to x0"),
+        (0x8000, "reserved opcode in C instruction"),
+    ];
+    for (bin, error) in cases {
+        assert_eq!(
+            CInstruction::decode(bin).map_err(|e| e.message),
+            Err(error.to_owned()),
+            "{bin:#06x}"
+        );
+    }
+}
+
+#[test]
+fn reserved_operands() {
+    // operands that give a reserved encoding or the encoding of another instruction
+    for (line, error) in [
+        ("c.addi4spn a0,0", "c.addi4spn immediate must not be zero"),
+        ("c.addi16sp 0", "c.addi16sp immediate must not be zero"),
+        ("c.lwsp zero,4", "c.lwsp must not load into x0"),
+        ("c.ldsp zero,8", "c.ldsp must not load into x0"),
+        ("c.addiw zero,1", "c.addiw must not write to x0"),
+        (
+            "c.lui sp,1",
+            "c.lui can not write to sp, whose encoding is c.addi16sp",
+        ),
+        ("c.jr zero", "c.jr must not jump to x0"),
+        (
+            "c.jalr zero",
+            "c.jalr can not jump to x0, whose encoding is c.ebreak",
+        ),
+        (
+            "c.mv a0,zero",
+            "c.mv can not copy x0, whose encoding is c.jr (use c.li instead)",
+        ),
+        (
+            "c.add a0,zero",
+            "c.add can not add x0, whose encoding is c.jalr",
+        ),
+    ] {
+        assert_eq!(
+            assemble_line(line).map(|_| ()),
+            Err(error.to_owned()),
+            "{line}"
+        );
+    }
+    // the HINTs that write to x0 are fine
+    for line in ["c.lui zero,1", "c.mv zero,a0", "c.add zero,a0"] {
+        assert!(assemble_line(line).is_ok(), "{line}");
+    }
+}
+
+#[test]
+fn every_encoding_round_trips() {
+    for xlen in [Xlen::Rv32, Xlen::Rv64] {
+        let decoder = DecoderConfig {
+            xlen,
+            ..Default::default()
+        };
+        let assembler = AssemblerConfig {
+            xlen,
+            ..Default::default()
+        };
+        for half in (0..=u16::MAX).filter(|h| h & 0b11 != 0b11) {
+            let Ok(c) = CInstruction::decode_with(half, &decoder) else {
+                continue;
+            };
+            assert_eq!(CInstruction::encode(&c), half, "{c}");
+            assert_eq!(
+                assemble_line_with(&c.to_string(), &assembler).map(|i| i.c()),
+                Ok(c),
+                "{half:#06x}"
+            );
+        }
+    }
+}
+
+#[test]
+fn raw_encoding() {
+    let decoded = DecodedCInstruction::decode(0x4501).unwrap();
+    assert_eq!(decoded.raw, 0x4501);
+    assert_eq!(decoded.bytes(), [0x01, 0x45]);
+    assert_eq!(decoded.to_string(), "c.li a0,0");
+    assert!(decoded.is_canonical());
+    assert!(DecodedCInstruction::decode(0x0000).is_err());
+}
+
+#[test]
+fn unified_instructions() {
+    let c = assemble_line("c.li a0,3").unwrap();
+    let i = assemble_line("addi a0,zero,3").unwrap();
+    assert_eq!((c.len(), i.len()), (2, 4));
+    assert_eq!(c.encode_bytes(), [0x0d, 0x45]);
+    assert_eq!(i.encode_bytes(), 0x00300513u32.to_le_bytes());
+    assert_eq!(c.full(), None);
+    assert_eq!(i.compressed(), None);
+    assert_eq!(c.expand(), Ok(i.i()));
+    assert_eq!(c.to_string(), "c.li a0,3");
+    assert_eq!(Inst::from(i.i()), i);
+
+    let (decoded, _) = decode_one(&c.encode_bytes()).unwrap();
+    assert_eq!(decoded.inst(), c);
+    assert!(matches!(c, Inst::Compressed(CInstruction::LI { .. })));
+}
+
+#[test]
+fn compress_instructions() {
+    // every decodable compressed instruction is found again from its expansion
+    for xlen in [Xlen::Rv32, Xlen::Rv64] {
+        let config = DecoderConfig {
+            xlen,
+            ..Default::default()
+        };
+        for half in (0..=u16::MAX).filter(|h| h & 0b11 != 0b11) {
+            let Ok(expanded) = CInstruction::decode_expanded_with(half, &config) else {
+                continue;
+            };
+            let compressed = CInstruction::compress(&expanded, xlen)
+                .unwrap_or_else(|| panic!("{expanded} ({half:#06x}) was not compressed"));
+            assert_eq!(compressed.try_expand(), Ok(expanded), "{half:#06x}");
+        }
+    }
+
+    let full = |line: &str| assemble_line(line).unwrap().full().unwrap();
+    let config = AssemblerConfig::default();
+    assert_eq!(
+        Instruction::encode_preferring_compressed(&full("addi a0,a0,1"), &config),
+        ([0x05, 0x05, 0, 0], 2)
+    );
+    assert_eq!(
+        Instruction::encode_preferring_compressed(&full("addi a0,a1,1"), &config),
+        (0x00158513u32.to_le_bytes(), 4)
+    );
+    // addi sp,sp,16 fits both c.addi and c.addi16sp, the first candidate is used
+    assert_eq!(
+        CInstruction::compress(&full("addi sp,sp,16"), Xlen::Rv64),
+        Some(CInstruction::ADDI16SP {
+            imm: C16SPImmediate::try_from(16).unwrap()
+        })
+    );
+    for line in [
+        "addi a0,a1,0",
+        "lui a0,0",
+        "lw a0,2(a1)",
+        "jalr a0,0(a1)",
+        "sub a0,a1,a0",
+    ] {
+        assert_eq!(
+            CInstruction::compress(&full(line), Xlen::Rv64),
+            None,
+            "{line}"
+        );
+    }
+    // c.jal and c.flw only exist on RV32
+    assert_eq!(CInstruction::compress(&full("jal ra,8"), Xlen::Rv64), None);
+    assert!(CInstruction::compress(&full("jal ra,8"), Xlen::Rv32).is_some());
+    let no_c = AssemblerConfig {
+        extensions: Extensions {
+            c: false,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert_eq!(
+        Instruction::encode_preferring_compressed(&full("addi a0,a0,1"), &no_c).1,
+        4
+    );
+}
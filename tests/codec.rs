@@ -0,0 +1,70 @@
+use riscv_codec::{
+    codec::Codec,
+    config::{FormatOptions, IsaString, Profile, RegisterStyle, Xlen},
+    instruction::{DecodedInst, Instruction},
+};
+
+fn rv32imc() -> Codec {
+    Codec::isa(&IsaString::try_from("rv32imc").unwrap())
+}
+
+#[test]
+fn decode() {
+    let rv32 = rv32imc();
+    // addi a0,a0,1 is decoded by every target, addiw a0,a0,1 only on RV64 and fadd.s fa0,fa1,fa2 only with F
+    assert_eq!(
+        rv32.decode(0x00150513).unwrap(),
+        Instruction::decode(0x00150513).unwrap()
+    );
+    assert_eq!(
+        rv32.decode(0x0015051b).map_err(String::from),
+        Err("unsupported instruction addiw a0,a0,1, which is only available on RV64".to_owned())
+    );
+    assert!(Instruction::decode(0x0015051b).is_ok());
+    assert!(rv32.decode(0x00c58553).is_err());
+    assert!(Codec::default().decode(0x00c58553).is_ok());
+
+    // the bytes of c.addi a0,1 followed by the start of another instruction
+    let (decoded, length) = rv32.decode_one(&[0x05, 0x05, 0x13]).unwrap();
+    assert_eq!(length, 2);
+    assert!(matches!(decoded, DecodedInst::Compressed(_)));
+    assert_eq!(decoded.to_string(), "c.addi a0,1");
+    let rv32i = Codec::profile(Profile::Rvi20U32);
+    assert_eq!(rv32i.config.xlen, Xlen::Rv32);
+    assert!(rv32i.decode_one(&[0x05, 0x05]).is_err());
+}
+
+#[test]
+fn assemble_line() {
+    let rv32 = rv32imc();
+    assert_eq!(
+        rv32.assemble_line("mul a0,a1,a2").unwrap().i(),
+        Instruction::decode(0x02c58533).unwrap()
+    );
+    assert_eq!(
+        rv32.assemble_line("fadd.s fa0,fa1,fa2").map(|_| ()),
+        Err("fadd.s requires the F or Zfinx extension".to_owned())
+    );
+    assert!(rv32.assemble_line("addiw a0,a0,1").is_err());
+    assert_eq!(rv32.assembler_config().xlen, Xlen::Rv32);
+}
+
+#[test]
+fn disassemble() {
+    let mut codec = rv32imc();
+    let i = codec.decode(0x00058513).unwrap();
+    assert_eq!(codec.disassemble(&i), "addi a0,a1,0");
+    codec.format = FormatOptions {
+        pseudo_instructions: true,
+        register_style: RegisterStyle::Numeric,
+        ..Default::default()
+    };
+    assert_eq!(codec.disassemble(&i), "mv x10,x11");
+
+    // disassembling what was assembled gives the same line with the default options
+    let codec = rv32imc();
+    for line in ["lui a0,1", "sw a0,-4(sp)", "divu t0,t1,t2"] {
+        let i = codec.assemble_line(line).unwrap().i();
+        assert_eq!(codec.disassemble(&i), line);
+    }
+}
@@ -0,0 +1,53 @@
+//! Configurations and checks shared by the extension tests, each test file only uses some of them.
+#![allow(dead_code)]
+
+use riscv_codec::{
+    assembly::assemble_line_with,
+    config::{AssemblerConfig, DecoderConfig, IsaString, Xlen},
+    instruction::Instruction,
+};
+
+/// The decoder and assembler configurations of an ISA string (`rv64gc_zabha`).
+pub fn isa(isa: &str) -> (DecoderConfig, AssemblerConfig) {
+    let isa = IsaString::try_from(isa).unwrap();
+    (DecoderConfig::isa(&isa), AssemblerConfig::isa(&isa))
+}
+
+/// The default configurations with another XLEN, for the XLENs that ISA strings can not give (RV128).
+pub fn xlen(xlen: Xlen) -> (DecoderConfig, AssemblerConfig) {
+    (
+        DecoderConfig {
+            xlen,
+            ..Default::default()
+        },
+        AssemblerConfig {
+            xlen,
+            ..Default::default()
+        },
+    )
+}
+
+/// Checks that each word decodes to its line, encodes back to the word, and that the line assembles to the same
+/// instruction.
+pub fn round_trips(decoder: &DecoderConfig, assembler: &AssemblerConfig, cases: &[(u32, &str)]) {
+    for &(bin, line) in cases {
+        let i = Instruction::decode_with(bin, decoder).unwrap();
+        assert_eq!(i.to_string(), line);
+        assert_eq!(Instruction::encode(&i), bin, "{line}");
+        assert_eq!(assemble_line_with(line, assembler).unwrap().i(), i);
+    }
+}
+
+/// Checks that the decoder rejects each word, the reason is printed if it does not.
+pub fn decode_errors(decoder: &DecoderConfig, cases: &[(u32, &str)]) {
+    for &(bin, reason) in cases {
+        assert!(Instruction::decode_with(bin, decoder).is_err(), "{reason}");
+    }
+}
+
+/// Checks that the assembler rejects each line.
+pub fn assemble_errors(assembler: &AssemblerConfig, lines: &[&str]) {
+    for &line in lines {
+        assert!(assemble_line_with(line, assembler).is_err(), "{line}");
+    }
+}
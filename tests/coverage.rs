@@ -0,0 +1,101 @@
+use riscv_codec::{
+    config::{DecoderConfig, Xlen},
+    coverage::{coverage, parse_opcodes},
+};
+
+const RV_I: &str = "
+# a few instructions of the base ISA
+add     rd rs1 rs2 31..25=0  14..12=0 6..2=0x0C 1..0=3
+addi    rd rs1 imm12       14..12=0 6..2=0x04 1..0=3
+fence   fm pred succ rs1 14..12=0 rd 6..2=0x03 1..0=3
+$pseudo_op rv_i::fence fence.tso 31..28=8 27..24=3 23..20=3 19..15=ignore 14..12=0 11..7=ignore 6..2=0x03 1..0=3
+";
+
+const RV64_I: &str = "
+ld      rd rs1 imm12 14..12=3 6..2=0x00 1..0=3
+";
+
+const RV_C: &str = "
+c.addi rd_rs1_n0 c_nzimm6lo c_nzimm6hi 1..0=1 15..13=0
+";
+
+/// An instruction in a reserved major opcode.
+const RV_XTEST: &str = "
+xtest   11..7=0 19..15=0 31..20=0x000 14..12=0 6..2=0x1A 1..0=3
+";
+
+/// `sub` with the encoding of `add`.
+const WRONG: &str = "
+sub     rd rs1 rs2 31..25=0  14..12=0 6..2=0x0C 1..0=3
+";
+
+#[test]
+fn opcode_parsing() {
+    let entries = parse_opcodes("rv_i", RV_I).unwrap();
+    assert_eq!(entries.len(), 4);
+    assert_eq!(entries[0].name, "add");
+    assert_eq!(entries[0].mask, 0xfe00_707f);
+    assert_eq!(entries[0].matches, 0x33);
+    assert_eq!(entries[3].name, "fence.tso");
+    assert_eq!(entries[3].pseudo_of.as_deref(), Some("fence"));
+    assert_eq!(entries[3].mask, 0xfff0_707f);
+    let entries = parse_opcodes("rv_xtest", RV_XTEST).unwrap();
+    assert_eq!(entries[0].mask, 0xffff_ffff);
+    assert_eq!(entries[0].matches, 0x6b);
+
+    let entries = parse_opcodes("rv64_i", RV64_I).unwrap();
+    assert_eq!(entries[0].xlen, Some(Xlen::Rv64));
+    assert!(parse_opcodes("rv_c", RV_C).unwrap()[0].compressed());
+
+    assert!(parse_opcodes("rv_i", "add rd 14..12=8 6..0=0x33").is_err());
+    assert!(parse_opcodes("rv_i", "add rd 14..12=0 13=1").is_err());
+}
+
+#[test]
+fn coverage_report() {
+    let mut entries = Vec::new();
+    for (name, text) in [
+        ("rv_i", RV_I),
+        ("rv64_i", RV64_I),
+        ("rv_c", RV_C),
+        ("rv_xtest", RV_XTEST),
+        ("rv_wrong", WRONG),
+    ] {
+        entries.extend(parse_opcodes(name, text).unwrap());
+    }
+    let report = coverage(&entries, &DecoderConfig::default());
+    let i = &report.extensions["rv_i"];
+    assert_eq!(i.supported, ["add", "addi", "fence"]);
+    assert!(i.missing.is_empty());
+    assert_eq!(report.extensions["rv64_i"].supported, ["ld"]);
+    assert_eq!(report.extensions["rv_c"].supported, ["c.addi"]);
+    assert_eq!(report.extensions["rv_xtest"].missing, ["xtest"]);
+    // add is in the database, so decoding its encoding as add is not a mismatch
+    assert!(report.extensions["rv_wrong"].mismatched.is_empty());
+    assert_eq!(report.extensions["rv_wrong"].missing, ["sub"]);
+
+    let extra: Vec<&str> = report
+        .extra
+        .iter()
+        .map(|e| e.split(' ').next().unwrap())
+        .collect();
+    assert!(extra.contains(&"xor"));
+    assert!(extra.contains(&"c.li"));
+    for name in ["add", "addi", "fence", "fence.tso", "ld", "c.addi"] {
+        assert!(!extra.contains(&name), "{name}");
+    }
+
+    // ld is only in RV64
+    let config = DecoderConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+    let report = coverage(&entries, &config);
+    assert!(!report.extensions.contains_key("rv64_i"));
+
+    let mismatched = coverage(&parse_opcodes("rv_wrong", WRONG).unwrap(), &config);
+    let mismatch = &mismatched.extensions["rv_wrong"].mismatched[0];
+    assert_eq!(mismatch.name, "sub");
+    assert!(mismatch.decoded.starts_with("add "));
+    assert!(mismatched.to_string().contains("mismatched: sub"));
+}
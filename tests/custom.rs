@@ -0,0 +1,181 @@
+use std::collections::HashSet;
+use std::fmt::Formatter;
+
+use riscv_codec::{
+    codec::Codec,
+    config::{DecoderConfig, Xlen},
+    custom::{CUSTOM_0, CUSTOM_1, CustomHandler},
+    instruction::Instruction,
+    register::IRegister,
+};
+
+/// A multiply-accumulate in custom-0 (`rd += rs1 * rs2`), which keeps the registers as the data
+struct Mac;
+
+impl CustomHandler for Mac {
+    fn decode(&self, word: u32, _xlen: Xlen) -> Result<u64, String> {
+        if word >> 25 != 0 {
+            return Err(format!(
+                "unknown mac instruction with funct7 {:#b}",
+                word >> 25
+            ));
+        }
+        Ok(((word >> 7) & 0x1f | ((word >> 15) & 0x1f) << 5 | ((word >> 20) & 0x1f) << 10) as u64)
+    }
+
+    fn encode(&self, data: u64) -> u32 {
+        let data = data as u32;
+        (data >> 10 & 0x1f) << 20 | (data >> 5 & 0x1f) << 15 | (data & 0x1f) << 7 | CUSTOM_0
+    }
+
+    fn fmt(&self, data: u64, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let data = data as u32;
+        write!(
+            f,
+            "mac {},{},{}",
+            IRegister::from_int(data & 0x1f),
+            IRegister::from_int(data >> 5 & 0x1f),
+            IRegister::from_int(data >> 10 & 0x1f)
+        )
+    }
+}
+
+/// Any custom-1 instruction, which keeps the raw word
+struct Raw;
+
+impl CustomHandler for Raw {
+    fn decode(&self, word: u32, xlen: Xlen) -> Result<u64, String> {
+        if xlen == Xlen::Rv32 {
+            return Err("raw instructions are only available on RV64".to_owned());
+        }
+        Ok(word as u64)
+    }
+
+    fn encode(&self, data: u64) -> u32 {
+        data as u32
+    }
+
+    fn fmt(&self, data: u64, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, ".insn {data:#010x}")
+    }
+}
+
+static MAC: Mac = Mac;
+static RAW: Raw = Raw;
+
+fn codec() -> Codec {
+    let mut codec = Codec::new(DecoderConfig::default());
+    codec.register_custom(0x707f, CUSTOM_0, &MAC).unwrap();
+    codec.register_custom(0x7f, CUSTOM_1, &RAW).unwrap();
+    codec
+}
+
+#[test]
+fn mac() {
+    let codec = codec();
+    // mac a0,a1,a2
+    let bin = 0x00c5850b;
+    let i = codec.decode(bin).unwrap();
+    let Instruction::Custom(custom) = i else {
+        panic!("expected a custom instruction, got {i:?}")
+    };
+    assert_eq!(custom.data, 10 | 11 << 5 | 12 << 10);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "mac a0,a1,a2");
+
+    // the handler's errors are decode errors
+    assert_eq!(
+        codec.decode(0x02c5850b).map_err(String::from),
+        Err("unknown mac instruction with funct7 0b1".to_owned())
+    );
+}
+
+#[test]
+fn dispatch() {
+    let codec = codec();
+    assert_eq!(
+        codec.decode(0x1234562b).unwrap().to_string(),
+        ".insn 0x1234562b"
+    );
+    // handlers get the xlen of the config
+    let mut rv32 = codec.clone();
+    rv32.config.xlen = Xlen::Rv32;
+    assert!(rv32.decode(0x1234562b).is_err());
+
+    // the custom-0 words the handler does not match, and the other opcodes, are decoded as before
+    assert_eq!(
+        codec.decode(0x00c5950b).map_err(String::from),
+        Instruction::decode(0x00c5950b).map_err(String::from)
+    );
+    assert_eq!(
+        codec.decode(0x00c58533).unwrap(),
+        Instruction::decode(0x00c58533).unwrap()
+    );
+    assert!(Instruction::decode(0x00c5850b).is_err());
+
+    // handlers registered earlier are tried first
+    let mut shadowed = Codec::default();
+    shadowed.register_custom(0x7f, CUSTOM_0, &RAW).unwrap();
+    shadowed
+        .register_custom(0xfe00707f, CUSTOM_0, &MAC)
+        .unwrap();
+    assert_eq!(
+        shadowed.decode(0x00c5850b).unwrap().to_string(),
+        ".insn 0x00c5850b"
+    );
+}
+
+#[test]
+fn comparisons() {
+    let codec = codec();
+    let a = codec.decode(0x00c5850b).unwrap();
+    let b = codec.decode(0x00c5850b).unwrap();
+    let c = codec.decode(0x00d5850b).unwrap();
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert!(a < c);
+    assert_eq!(HashSet::from([a, b, c]).len(), 2);
+}
+
+#[test]
+fn unwind_safety() {
+    let codec = codec();
+    let i = codec.decode(0x00c5850b).unwrap();
+    // custom instructions can be used across catch_unwind and sent between threads
+    assert_eq!(
+        std::panic::catch_unwind(|| i.to_string()).unwrap(),
+        "mac a0,a1,a2"
+    );
+    assert_eq!(std::thread::spawn(move || i).join().unwrap(), i);
+}
+
+#[test]
+fn registration() {
+    let mut codec = Codec::default();
+    assert_eq!(
+        codec.register_custom(0x7f, 0x33, &RAW),
+        Err("custom instructions must be in the custom-0 or custom-1 major opcode, got the pattern 0x00000033 with the mask 0x0000007f".to_owned())
+    );
+    assert_eq!(
+        codec.register_custom(0xfe000000, CUSTOM_0, &RAW),
+        Err("the pattern 0x0000000b has bits outside of the mask 0xfe000000".to_owned())
+    );
+    assert!(codec.register_custom(0x3f, CUSTOM_0, &RAW).is_err());
+    assert!(codec.register_custom(0x7f, CUSTOM_1, &RAW).is_ok());
+}
+
+#[test]
+fn bytes() {
+    let codec = codec();
+    let (decoded, length) = codec.decode_one(&[0x0b, 0x85, 0xc5, 0x00]).unwrap();
+    assert_eq!(length, 4);
+    assert_eq!(decoded.raw(), 0x00c5850b);
+    assert_eq!(decoded.to_string(), "mac a0,a1,a2");
+    assert_eq!(decoded.inst().i(), codec.decode(0x00c5850b).unwrap());
+    assert!(codec.decode_one(&[0x0b, 0x85, 0xc5]).is_err());
+    assert!(codec.decode_one(&[0x0b, 0x85, 0xc5, 0x02]).is_err());
+}
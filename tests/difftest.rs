@@ -0,0 +1,56 @@
+#![cfg(feature = "difftest")]
+
+use riscv_codec::{
+    config::DecoderConfig,
+    difftest::{ExternalAssembler, compare, random_instructions, standard_syntax},
+};
+
+#[test]
+fn standard_syntaxes() {
+    assert_eq!(standard_syntax("addi a0,a1,1"), "addi a0,a1,1");
+    assert_eq!(standard_syntax("lr.w.aq a0,a1"), "lr.w.aq a0,(a1)");
+    assert_eq!(standard_syntax("amoadd.w a0,a1,a2"), "amoadd.w a0,a2,(a1)");
+    assert_eq!(
+        standard_syntax("fcvt.w.s.rtz a0,fa0"),
+        "fcvt.w.s a0,fa0,rtz"
+    );
+    assert_eq!(standard_syntax("fence.tso rw,rw"), "fence.tso");
+    assert_eq!(standard_syntax("lui a0,-1"), "lui a0,1048575");
+    assert_eq!(standard_syntax("fence.i"), "fence.i");
+}
+
+#[test]
+fn random_instruction_strings() {
+    let config = DecoderConfig::default();
+    let instructions = random_instructions(1, 100, false, &config);
+    assert_eq!(instructions.len(), 100);
+    assert_eq!(instructions, random_instructions(1, 100, false, &config));
+    assert!(
+        random_instructions(1, 100, true, &config)
+            .iter()
+            .all(|i| i.starts_with("c."))
+    );
+}
+
+/// Compares against llvm-mc when `LLVM_MC` is set to its path.
+#[test]
+fn llvm_mc() {
+    let Ok(program) = std::env::var("LLVM_MC") else {
+        return;
+    };
+    let assembler = ExternalAssembler::llvm_mc(program);
+    let config = DecoderConfig::default();
+    for compressed in [false, true] {
+        let instructions = random_instructions(1, 1000, compressed, &config);
+        let mismatches = compare(&assembler, &instructions, compressed).unwrap();
+        for mismatch in &mismatches {
+            eprintln!("{mismatch}");
+        }
+        // instructions only one assembler supports are reported, but both must agree on the encodings
+        assert!(
+            mismatches
+                .iter()
+                .all(|m| m.ours.is_err() || m.theirs.is_err())
+        );
+    }
+}
@@ -0,0 +1,89 @@
+mod common;
+
+use riscv_codec::{
+    assembly::assemble_line_with,
+    cinstruction::CInstruction,
+    config::{IsaString, Xlen},
+    instruction::Instruction,
+    register::IRegister,
+};
+
+const ISA: &str = "rv32emc_zcmp";
+
+#[test]
+fn isa_string() {
+    let isa = IsaString::try_from("rv32emc_zcmp").unwrap();
+    assert!(isa.extensions.e && isa.extensions.m && isa.extensions.c);
+    assert_eq!(isa.xlen, Xlen::Rv32);
+    assert_eq!(isa.to_string(), "rv32emc_zcmp");
+    let isa = IsaString::try_from("rv64e2p0_c").unwrap();
+    assert!(isa.extensions.e && isa.extensions.c);
+    assert_eq!(isa.to_string(), "rv64ec");
+    assert!(!IsaString::try_from("rv64imc").unwrap().extensions.e);
+}
+
+#[test]
+fn registers_below_x16() {
+    let (decoder, assembler) = common::isa(ISA);
+    for (bin, line) in [
+        (0x00158513, "addi a0,a1,1"),
+        (0x00f78733, "add a4,a5,a5"),
+        (0x0007a403, "lw s0,0(a5)"),
+        (0x853e, "c.mv a0,a5"),
+        (0xb862, "cm.push {ra, s0-s1},-16"),
+        (0xac26, "cm.mvsa01 s0,s1"),
+    ] {
+        let i = assemble_line_with(line, &assembler).unwrap();
+        assert_eq!(i.encode(), bin, "{line}");
+        if i.len() == 2 {
+            let c = CInstruction::decode_with(bin as u16, &decoder).unwrap();
+            assert_eq!(c.to_string(), line);
+        } else {
+            let i = Instruction::decode_with(bin, &decoder).unwrap();
+            assert_eq!(i.to_string(), line);
+        }
+    }
+}
+
+#[test]
+fn registers_above_x15() {
+    let (decoder, assembler) = common::isa(ISA);
+    for (line, register) in [
+        ("addi a6,a1,1", "a6"),
+        ("add a0,a1,t3", "t3"),
+        ("lw s2,0(a0)", "s2"),
+        ("sw a0,0(s11)", "s11"),
+        ("c.mv a0,a6", "a6"),
+        ("cm.push {ra, s0-s2},-16", "s2"),
+        ("cm.mvsa01 s0,s2", "s2"),
+    ] {
+        let mnemonic = line.split_once(' ').unwrap().0;
+        assert_eq!(
+            assemble_line_with(line, &assembler).map(|_| ()),
+            Err(format!(
+                "{mnemonic} uses {register} but RVE only has x0-x15"
+            ))
+        );
+    }
+
+    // addi a6,a1,16 and c.mv a0,a6
+    assert_eq!(
+        Instruction::decode_with(0x01058813, &decoder).map_err(String::from),
+        Err(
+            "unsupported instruction addi a6,a1,16, which uses a6 but RVE only has x0-x15"
+                .to_owned()
+        )
+    );
+    assert!(CInstruction::decode_with(0x8542, &decoder).is_err());
+    assert!(CInstruction::decode(0x8542).is_ok());
+
+    // the immediates and CSRs are not registers
+    assert_eq!(
+        Instruction::decode(0x01054513).unwrap().rve_register(false),
+        None
+    );
+    assert_eq!(
+        Instruction::decode(0x01058813).unwrap().rve_register(false),
+        Some(IRegister::A6)
+    );
+}
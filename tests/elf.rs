@@ -0,0 +1,316 @@
+#![cfg(feature = "elf")]
+
+use riscv_codec::{
+    analysis::Symbol,
+    assembly::assemble_line,
+    elf::{Elf, LineRow, ListingOptions, disassemble_elf},
+    golden::{ListingMismatch, check_corpus, compare_listing},
+    instruction::Instruction,
+};
+
+/// A section of a test ELF file: name, type, flags, address, link and contents.
+type TestSection = (&'static str, u32, u64, u64, u32, Vec<u8>);
+
+/// Builds a little endian ELF64 file, the section header string table is added as the last section.
+fn build_elf(mut sections: Vec<TestSection>) -> Vec<u8> {
+    let mut names = vec![0u8];
+    let mut name_offsets = Vec::new();
+    for (name, ..) in sections.iter().chain([&(".shstrtab", 0, 0, 0, 0, vec![])]) {
+        name_offsets.push(names.len() as u32);
+        names.extend(name.as_bytes());
+        names.push(0);
+    }
+    sections.push((".shstrtab", 3, 0, 0, 0, names));
+
+    let mut data: Vec<u8> = Vec::new();
+    let mut offsets = Vec::new();
+    for (.., contents) in &sections {
+        offsets.push(64 + data.len() as u64);
+        data.extend(contents);
+    }
+    let section_offset = 64 + data.len() as u64;
+
+    let mut elf = Vec::new();
+    elf.extend(b"\x7fELF\x02\x01\x01\0\0\0\0\0\0\0\0\0");
+    elf.extend(1u16.to_le_bytes());
+    elf.extend(243u16.to_le_bytes());
+    elf.extend(1u32.to_le_bytes());
+    elf.extend(0u64.to_le_bytes());
+    elf.extend(0u64.to_le_bytes());
+    elf.extend(section_offset.to_le_bytes());
+    elf.extend(0u32.to_le_bytes());
+    elf.extend(64u16.to_le_bytes());
+    elf.extend(0u16.to_le_bytes());
+    elf.extend(0u16.to_le_bytes());
+    elf.extend(64u16.to_le_bytes());
+    elf.extend((sections.len() as u16 + 1).to_le_bytes());
+    elf.extend((sections.len() as u16).to_le_bytes());
+    elf.extend(data);
+    // the null section
+    elf.extend([0; 64]);
+    for (i, (_, kind, flags, address, link, contents)) in sections.iter().enumerate() {
+        elf.extend(name_offsets[i].to_le_bytes());
+        elf.extend(kind.to_le_bytes());
+        elf.extend(flags.to_le_bytes());
+        elf.extend(address.to_le_bytes());
+        elf.extend(offsets[i].to_le_bytes());
+        elf.extend((contents.len() as u64).to_le_bytes());
+        elf.extend(link.to_le_bytes());
+        elf.extend(0u32.to_le_bytes());
+        elf.extend(0u64.to_le_bytes());
+        elf.extend((if *kind == 2 { 24u64 } else { 0 }).to_le_bytes());
+    }
+    elf
+}
+
+/// A DWARF 4 line program for three instructions at 0x10000 on lines 3, 4 and 4 of src/main.c.
+fn line_program() -> Vec<u8> {
+    let mut header = vec![1, 1, 1, 0xfb, 14, 13, 0, 1, 1, 1, 1, 0, 0, 0, 1, 0, 0, 1];
+    header.extend(b"src\0\0main.c\0\x01\0\0\0");
+    let mut program = vec![0, 9, 2];
+    program.extend(0x10000u64.to_le_bytes());
+    // advance_line 2, copy, two special opcodes advancing by 4 bytes and 1 then 0 lines, advance_pc 4, end_sequence
+    program.extend([3, 2, 1, 75, 74, 2, 4, 0, 1, 1]);
+
+    let mut unit = Vec::new();
+    unit.extend(4u16.to_le_bytes());
+    unit.extend((header.len() as u32).to_le_bytes());
+    unit.extend(header);
+    unit.extend(program);
+    let mut section = (unit.len() as u32).to_le_bytes().to_vec();
+    section.extend(unit);
+    section
+}
+
+fn test_elf() -> Vec<u8> {
+    let text: Vec<u8> = ["addi a0,a0,1", "add a1,a0,a0", "jalr zero,0(ra)"]
+        .iter()
+        .flat_map(|line| Instruction::encode(&assemble_line(line).unwrap().i()).to_le_bytes())
+        .collect();
+    let mut symbols = vec![0; 24];
+    // main: a global function in .text
+    symbols.extend(1u32.to_le_bytes());
+    symbols.extend([0x12, 0]);
+    symbols.extend(1u16.to_le_bytes());
+    symbols.extend(0x10000u64.to_le_bytes());
+    symbols.extend(12u64.to_le_bytes());
+    build_elf(vec![
+        (".text", 1, 0x6, 0x10000, 0, text),
+        (".symtab", 2, 0, 0, 3, symbols),
+        (".strtab", 3, 0, 0, 0, b"\0main\0".to_vec()),
+        (".debug_line", 1, 0, 0, 0, line_program()),
+    ])
+}
+
+#[test]
+fn parse_elf() {
+    let data = test_elf();
+    let elf = Elf::parse(&data).unwrap();
+    assert!(elf.is_64);
+    assert_eq!(elf.machine, riscv_codec::elf::EM_RISCV);
+    let text = elf.section(".text").unwrap();
+    assert!(text.is_code());
+    assert_eq!(text.address, 0x10000);
+    assert_eq!(elf.section_data(text).unwrap().len(), 12);
+    assert_eq!(
+        elf.symbols(),
+        Ok(vec![Symbol {
+            address: 0x10000,
+            name: "main".to_owned(),
+        }])
+    );
+    assert!(Elf::parse(b"not an elf file").is_err());
+}
+
+#[test]
+fn line_table() {
+    let data = test_elf();
+    let elf = Elf::parse(&data).unwrap();
+    let row = |address, line, end_sequence| LineRow {
+        address,
+        file: "src/main.c".to_owned(),
+        line,
+        end_sequence,
+        section: None,
+    };
+    assert_eq!(
+        elf.line_table(),
+        Ok(vec![
+            row(0x10000, 3, false),
+            row(0x10004, 4, false),
+            row(0x10008, 4, false),
+            row(0x1000c, 4, true),
+        ])
+    );
+}
+
+#[test]
+fn line_annotated_disassembly() {
+    let data = test_elf();
+    let elf = Elf::parse(&data).unwrap();
+    let options = ListingOptions {
+        lines: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        disassemble_elf(&elf, &options).unwrap(),
+        "
+Disassembly of section .text:
+
+0000000000010000 <main>:
+src/main.c:3
+   10000:\t00150513          \taddi a0,a0,1
+src/main.c:4
+   10004:\t00a505b3          \tadd a1,a0,a0
+   10008:\t00008067          \tjalr zero,0(ra)
+"
+    );
+    assert!(
+        !disassemble_elf(&elf, &ListingOptions::default())
+            .unwrap()
+            .contains("main.c")
+    );
+}
+
+#[test]
+fn relocatable_line_table() {
+    // tests/fixtures/dwarf5.s assembled by llvm-mc, with relocations for the strings and addresses of .debug_line
+    let data = include_bytes!("fixtures/dwarf5.o");
+    let elf = Elf::parse(data).unwrap();
+    assert_eq!(elf.file_type, riscv_codec::elf::ET_REL);
+    let text = elf.sections.iter().position(|s| s.name == ".text");
+    let other = elf.sections.iter().position(|s| s.name == ".text.other");
+    let row = |address, line, end_sequence, section| LineRow {
+        address,
+        file: "tests/fixtures/dwarf5.s".to_owned(),
+        line,
+        end_sequence,
+        section,
+    };
+    assert_eq!(
+        elf.line_table(),
+        Ok(vec![
+            row(0, 5, false, text),
+            row(0, 12, false, other),
+            row(4, 6, false, text),
+            row(4, 13, false, other),
+            row(8, 13, true, other),
+            row(8, 7, false, text),
+            row(0xc, 7, true, text),
+        ])
+    );
+
+    // each code section starts at 0 and only uses its own symbols and rows
+    let options = ListingOptions {
+        lines: true,
+        source: true,
+        ..Default::default()
+    };
+    assert_eq!(
+        disassemble_elf(&elf, &options).unwrap(),
+        "
+Disassembly of section .text:
+
+0000000000000000 <main>:
+tests/fixtures/dwarf5.s:5
+\taddi a0,a0,1
+       0:\t00150513          \taddi a0,a0,1
+tests/fixtures/dwarf5.s:6
+\tadd a1,a0,a0
+       4:\t00a505b3          \tadd a1,a0,a0
+tests/fixtures/dwarf5.s:7
+\tret
+       8:\t00008067          \tjalr zero,0(ra)
+
+Disassembly of section .text.other:
+
+0000000000000000 <other>:
+tests/fixtures/dwarf5.s:12
+\taddi a0,zero,2
+       0:\t00200513          \taddi a0,zero,2
+tests/fixtures/dwarf5.s:13
+\tret
+       4:\t00008067          \tjalr zero,0(ra)
+"
+    );
+}
+
+#[test]
+fn frame_annotated_disassembly() {
+    let text: Vec<u8> = [
+        "addi sp,sp,-16",
+        "sd ra,8(sp)",
+        "addi sp,sp,16",
+        "jalr zero,0(ra)",
+    ]
+    .iter()
+    .flat_map(|line| assemble_line(line).unwrap().encode_bytes())
+    .collect();
+    let data = build_elf(vec![(".text", 1, 0x6, 0x10000, 0, text)]);
+    let options = ListingOptions {
+        frames: true,
+        ..Default::default()
+    };
+    let listing = disassemble_elf(&Elf::parse(&data).unwrap(), &options).unwrap();
+    let lines: Vec<&str> = listing.lines().skip(2).collect();
+    assert_eq!(
+        lines,
+        [
+            "   10000:\tff010113          \taddi sp,sp,-16\t# prologue, frame=16",
+            "   10004:\t00113423          \tsd ra,8(sp)",
+            "   10008:\t01010113          \taddi sp,sp,16\t# epilogue, frame=16",
+            "   1000c:\t00008067          \tjalr zero,0(ra)",
+        ]
+    );
+}
+
+#[test]
+fn golden_objdump() {
+    // GNU objdump output, with jalr printed as the ret pseudo-instruction
+    let gnu = "
+test:     file format elf64-littleriscv
+
+
+Disassembly of section .text:
+
+0000000000010000 <main>:
+   10000:\t00150513          \taddi\ta0,a0,1
+   10004:\t00a505b3          \tadd\ta1,a0,a0
+   10008:\t00008067          \tret
+";
+    let data = test_elf();
+    let actual = disassemble_elf(&Elf::parse(&data).unwrap(), &ListingOptions::default()).unwrap();
+    assert_eq!(
+        compare_listing(gnu, &actual, &[]),
+        vec![ListingMismatch {
+            address: 0x10008,
+            expected: "00008067 ret".to_owned(),
+            actual: Some("00008067 jalr zero,0(ra)".to_owned()),
+        }]
+    );
+    assert_eq!(compare_listing(gnu, &actual, &["ret"]), vec![]);
+
+    // llvm-objdump prints separate bytes and spaces between operands
+    let llvm =
+        "   10004: b3 05 a5 00  \tadd\ta1, a0, a0\n   10008: 67 80 00 00  \tjalr\tzero, 0(ra)\n";
+    assert_eq!(compare_listing(llvm, &actual, &[]), vec![]);
+    // encodings always have to match
+    let wrong =
+        "   10004:\t00a505b4          \tadd\ta1,a0,a0\n   1000c:\t00000013          \tnop\n";
+    assert_eq!(compare_listing(wrong, &actual, &["add", "nop"]).len(), 2);
+
+    let dir = std::env::temp_dir().join(format!("riscv-codec-golden-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("test"), &data).unwrap();
+    std::fs::write(dir.join("test.objdump"), gnu).unwrap();
+    std::fs::write(dir.join("unpaired"), b"not an elf file").unwrap();
+    let results = check_corpus(&dir, &ListingOptions::default(), &[]).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].0, dir.join("test"));
+    assert!(
+        check_corpus(&dir, &ListingOptions::default(), &["ret"])
+            .unwrap()
+            .is_empty()
+    );
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -0,0 +1,129 @@
+use riscv_codec::{
+    analysis::decode_region,
+    assembly::assemble_line,
+    config::{DecoderConfig, Xlen},
+    emit::CodeBuffer,
+    instruction::Instruction,
+    register::IRegister,
+};
+
+fn i(line: &str) -> Instruction {
+    assemble_line(line).unwrap().i()
+}
+
+/// Disassembles code emitted at address 0.
+fn disassemble(bytes: &[u8]) -> Vec<String> {
+    decode_region(bytes, 0, &DecoderConfig::default())
+        .iter()
+        .map(|d| format!("{:x}: {}", d.address, d.instruction))
+        .collect()
+}
+
+#[test]
+fn labels() {
+    let mut code = CodeBuffer::new(Xlen::Rv64);
+    let top = code.label();
+    let done = code.label();
+    code.emit(i("addi a0,zero,10"));
+    code.bind(top).unwrap();
+    code.branch(i("beq a0,zero,0"), done).unwrap();
+    code.emit(i("addi a0,a0,-1"));
+    code.branch(i("jal zero,0"), top).unwrap();
+    code.bind(done).unwrap();
+    code.emit(i("jalr zero,0(ra)"));
+    assert_eq!(code.len(), 5);
+    assert_eq!(
+        disassemble(&code.finish().unwrap()),
+        [
+            "0: addi a0,zero,10",
+            "4: beq a0,zero,12",
+            "8: addi a0,a0,-1",
+            "c: jal zero,-8",
+            "10: jalr zero,0(ra)",
+        ]
+    );
+
+    assert!(code.bind(top).is_err());
+    let unbound = code.label();
+    code.branch(i("jal ra,0"), unbound).unwrap();
+    assert!(code.finish().is_err());
+    assert!(code.branch(i("addi a0,a0,1"), top).is_err());
+}
+
+#[test]
+fn pc_relative() {
+    let mut code = CodeBuffer::new(Xlen::Rv64);
+    let function = code.label();
+    let data = code.label();
+    code.pc_relative(IRegister::ReturnAddress, i("jalr ra,0(ra)"), function)
+        .unwrap();
+    code.pc_relative(IRegister::A0, i("ld a1,0(a0)"), data)
+        .unwrap();
+    code.bind(function).unwrap();
+    for _ in 0..1536 {
+        code.emit(i("addi zero,zero,0"));
+    }
+    code.bind(data).unwrap();
+    let text = disassemble(&code.finish().unwrap());
+    assert_eq!(
+        text[..4],
+        [
+            "0: auipc ra,0",
+            "4: jalr ra,16(ra)",
+            "8: auipc a0,2",
+            "c: ld a1,-2040(a0)",
+        ]
+    );
+
+    assert!(
+        code.pc_relative(IRegister::A0, i("ld a1,0(a1)"), data)
+            .is_err()
+    );
+    assert!(
+        code.pc_relative(IRegister::A0, i("add a1,a0,a0"), data)
+            .is_err()
+    );
+}
+
+#[test]
+fn compression() {
+    let mut code = CodeBuffer::new(Xlen::Rv64);
+    code.compress(true);
+    let top = code.label();
+    let far = code.label();
+    code.bind(top).unwrap();
+    code.emit(i("addi a0,a0,1"));
+    code.branch(i("bne a0,zero,0"), top).unwrap();
+    // a0 and a1 can not be compared by a compressed branch
+    code.branch(i("blt a0,a1,0"), top).unwrap();
+    code.branch(i("beq a0,zero,0"), far).unwrap();
+    code.pc_relative(IRegister::A0, i("addi a0,a0,0"), top)
+        .unwrap();
+    // beyond the range of c.beqz once every instruction before it is compressed
+    for _ in 0..128 {
+        code.emit(i("addi a1,a1,1"));
+    }
+    code.bind(far).unwrap();
+    code.branch(i("jal zero,0"), top).unwrap();
+    let bytes = code.finish().unwrap();
+    let text = disassemble(&bytes);
+    assert_eq!(
+        text[..6],
+        [
+            "0: addi a0,a0,1",
+            "2: bne a0,zero,-2",
+            "4: blt a0,a1,-4",
+            "8: beq a0,zero,268",
+            "c: auipc a0,0",
+            "10: addi a0,a0,-12",
+        ]
+    );
+    assert_eq!(text[text.len() - 1], "114: jal zero,-276");
+    assert_eq!(bytes.len(), 0x116);
+    // beq is widened, while blt and PC-relative sequences have no compressed form
+    let lengths: Vec<u8> = decode_region(&bytes, 0, &DecoderConfig::default())
+        .iter()
+        .map(|d| d.length)
+        .collect();
+    assert_eq!(lengths[..7], [2, 2, 4, 4, 4, 4, 2]);
+}
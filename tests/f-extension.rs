@@ -1,7 +1,10 @@
 use riscv_codec::assembly::assemble_line;
+use riscv_codec::config::{DecoderConfig, FormatOptions, RegisterStyle};
 use riscv_codec::immediates::{IImmediate, SImmediate};
-use riscv_codec::instruction::{Instruction, RoundingMode, disassemble_instruction};
-use riscv_codec::register::{FRegister, IRegister};
+use riscv_codec::instruction::{
+    Instruction, RoundingMode, disassemble_instruction, disassemble_instruction_with,
+};
+use riscv_codec::register::{CFRegister, FRegister, IRegister};
 
 #[test]
 fn float_load_word() {
@@ -601,3 +604,160 @@ fn float_convert_single_from_unsigned_long() {
     let i3 = assemble_line(&disassemble_instruction(&i)).unwrap().i();
     assert_eq!(i, i3);
 }
+
+#[test]
+fn float_sqrt_nonzero_rs2() {
+    let lenient = DecoderConfig {
+        strict: false,
+        ..Default::default()
+    };
+    // fsqrt.s.rne ft3,fa3 with rs2 set to 1
+    let bin = 0x581681d3;
+    assert!(Instruction::decode(bin).is_err());
+    assert_eq!(
+        Instruction::decode_with(bin, &lenient).unwrap(),
+        Instruction::FSQRTS {
+            dest: FRegister::FT3,
+            src: FRegister::FA3,
+            rm: RoundingMode::RNE,
+        }
+    );
+}
+
+#[test]
+fn float_reserved_rounding_mode() {
+    let keep = DecoderConfig {
+        reserved_rounding_modes: true,
+        ..Default::default()
+    };
+    // fadd.s ft0,ft1,ft2 with rm set to 0b101
+    let bin = 0x0020d053;
+    assert_eq!(
+        Instruction::decode(bin).map_err(String::from),
+        Err("reserved rounding mode: 0b101".to_owned())
+    );
+    let i = Instruction::decode_with(bin, &keep).unwrap();
+    assert_eq!(
+        i,
+        Instruction::FADDS {
+            dest: FRegister::FT0,
+            src1: FRegister::FT1,
+            src2: FRegister::FT2,
+            rm: RoundingMode::Reserved(0b101),
+        }
+    );
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // the reserved modes round trip through the assembler in both forms, but not as numbers
+    assert_eq!(i.to_string(), "fadd.s.reserved5 ft0,ft1,ft2");
+    assert_eq!(assemble_line(&i.to_string()).unwrap().i(), i);
+    let operand = FormatOptions {
+        rounding_mode_operand: true,
+        ..Default::default()
+    };
+    let line = disassemble_instruction_with(&i, &operand);
+    assert_eq!(line, "fadd.s ft0,ft1,ft2,reserved5");
+    assert_eq!(assemble_line(&line).unwrap().i(), i);
+    let i = Instruction::decode_with(0x0020e053, &keep).unwrap();
+    assert_eq!(assemble_line(&i.to_string()).unwrap().i(), i);
+    assert!(assemble_line("fadd.s.reserved4 ft0,ft1,ft2").is_err());
+}
+
+#[test]
+fn float_register_names() {
+    let expected = Instruction::FADDS {
+        dest: FRegister::FT7,
+        src1: FRegister::FA5,
+        src2: FRegister::FS10,
+        rm: RoundingMode::DYN,
+    };
+    assert_eq!(assemble_line("fadd.s f7,f15,f26").unwrap().i(), expected);
+    assert_eq!(FRegister::try_from("f31"), Ok(FRegister::FT11));
+    assert!(FRegister::try_from("f32").is_err());
+    assert!(FRegister::try_from("f").is_err());
+    assert_eq!(CFRegister::try_from("f8"), Ok(CFRegister::FS0));
+    assert!(CFRegister::try_from("f16").is_err());
+
+    let numeric = FormatOptions {
+        register_style: RegisterStyle::Numeric,
+        ..Default::default()
+    };
+    let text = disassemble_instruction_with(&expected, &numeric);
+    assert_eq!(text, "fadd.s f7,f15,f26");
+    assert_eq!(assemble_line(&text).unwrap().i(), expected);
+}
+
+#[test]
+fn rounding_mode_display() {
+    let operand = FormatOptions {
+        rounding_mode_operand: true,
+        ..Default::default()
+    };
+    for (line, suffixed, objdump) in [
+        (
+            "fadd.s fa0,fa1,fa2",
+            "fadd.s fa0,fa1,fa2",
+            "fadd.s fa0,fa1,fa2",
+        ),
+        (
+            "fadd.s.dyn fa0,fa1,fa2",
+            "fadd.s fa0,fa1,fa2",
+            "fadd.s fa0,fa1,fa2",
+        ),
+        (
+            "fadd.s fa0,fa1,fa2,rtz",
+            "fadd.s.rtz fa0,fa1,fa2",
+            "fadd.s fa0,fa1,fa2,rtz",
+        ),
+        (
+            "fsqrt.s.rmm ft0,ft1",
+            "fsqrt.s.rmm ft0,ft1",
+            "fsqrt.s ft0,ft1,rmm",
+        ),
+        (
+            "fcvt.w.s a0,fa0,rdn",
+            "fcvt.w.s.rdn a0,fa0",
+            "fcvt.w.s a0,fa0,rdn",
+        ),
+        (
+            "fcvt.s.lu.dyn fa0,a0",
+            "fcvt.s.lu fa0,a0",
+            "fcvt.s.lu fa0,a0",
+        ),
+    ] {
+        let i = assemble_line(line).unwrap().i();
+        assert_eq!(i.to_string(), suffixed);
+        assert_eq!(disassemble_instruction_with(&i, &operand), objdump);
+        // both forms assemble back to the same instruction
+        assert_eq!(assemble_line(suffixed).unwrap().i(), i);
+        assert_eq!(assemble_line(objdump).unwrap().i(), i);
+    }
+}
+
+#[test]
+fn float_numeric_rounding_modes() {
+    let expected = Instruction::FADDS {
+        dest: FRegister::FA0,
+        src1: FRegister::FA1,
+        src2: FRegister::FA2,
+        rm: RoundingMode::RNE,
+    };
+    assert_eq!(assemble_line("fadd.s fa0,fa1,fa2,0").unwrap().i(), expected);
+    assert_eq!(
+        assemble_line("fadd.s fa0,fa1,fa2,rne").unwrap().i(),
+        expected
+    );
+    assert_eq!(assemble_line("fadd.s.0 fa0,fa1,fa2").unwrap().i(), expected);
+    assert_eq!(
+        assemble_line("fcvt.w.s a0,fa0,1").unwrap().i(),
+        assemble_line("fcvt.w.s.rtz a0,fa0").unwrap().i()
+    );
+    assert_eq!(
+        assemble_line("fsqrt.s ft0,ft1,7").unwrap().i(),
+        assemble_line("fsqrt.s.dyn ft0,ft1").unwrap().i()
+    );
+    assert_eq!(RoundingMode::from_str("4"), Ok(RoundingMode::RMM));
+    assert!(assemble_line("fadd.s fa0,fa1,fa2,5").is_err());
+    assert!(assemble_line("fadd.s.6 fa0,fa1,fa2").is_err());
+    assert!(assemble_line("fmin.s fa0,fa1,fa2,0").is_err());
+}
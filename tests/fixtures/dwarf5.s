@@ -0,0 +1,13 @@
+# llvm-mc -triple=riscv64 -g -dwarf-version=5 -fdebug-compilation-dir=tests/fixtures -filetype=obj dwarf5.s -o dwarf5.o
+	.text
+	.globl main
+main:
+	addi a0,a0,1
+	add a1,a0,a0
+	ret
+
+	.section .text.other,"ax",@progbits
+	.globl other
+other:
+	addi a0,zero,2
+	ret
@@ -0,0 +1,33 @@
+use riscv_codec::{
+    analysis::Symbol, assembly::assemble_line, config::DecoderConfig, html::html_listing,
+};
+
+#[test]
+fn html_listing_links() {
+    let mut bytes = Vec::new();
+    for line in ["addi a0,zero,1", "beq a0,zero,4", "c.li a1,3", "jal ra,-10"] {
+        bytes.extend(assemble_line(line).unwrap().encode_bytes());
+    }
+    let symbols = vec![
+        Symbol {
+            address: 0x1000,
+            name: "main".to_owned(),
+        },
+        Symbol {
+            address: 0x100a,
+            name: "a<b>".to_owned(),
+        },
+    ];
+    let html = html_listing(&bytes, 0x1000, &symbols, &DecoderConfig::default());
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains("<h2 id=\"main\">main</h2>\n<table>\n<tr id=\"x1000\">"));
+    assert!(html.contains(
+        "<tr id=\"x1004\"><td class=\"address\">1004</td><td class=\"bytes\">00050263</td>\
+<td class=\"mnemonic\">beq</td><td class=\"operands\">a0,zero,4</td>\
+<td class=\"target\"><a href=\"#x1008\">1008 &lt;main+0x8&gt;</a></td></tr>"
+    ));
+    assert!(html.contains("<td class=\"mnemonic\">c.li</td>"));
+    assert!(html.contains("</table>\n<h2 id=\"a&lt;b&gt;\">a&lt;b&gt;</h2>"));
+    assert!(html.contains("<a href=\"#x1000\">1000 &lt;main&gt;</a>"));
+    assert!(html.ends_with("</table>\n</body>\n</html>\n"));
+}
@@ -0,0 +1,96 @@
+use riscv_codec::immediates::{
+    BImmediate, CJImmediate, CSRImmediate, IImmediate, Immediate, Lmul, VType,
+};
+
+/// Checks that every value in the range can be encoded and decoded again.
+fn round_trip<T: Immediate>(min: i64, max: i64, step: i64) {
+    let mut value = min;
+    while value <= max {
+        let imm = T::try_new(value).unwrap();
+        assert_eq!(imm.value(), value);
+        assert_eq!(T::decode(imm.encode()).value(), value);
+        value += step;
+    }
+    assert!(T::try_new(min - step).is_err());
+    assert!(T::try_new(max + step).is_err());
+}
+
+#[test]
+fn immediate_round_trip() {
+    round_trip::<IImmediate>(-2048, 2047, 1);
+    round_trip::<BImmediate>(-4096, 4094, 2);
+    round_trip::<CJImmediate>(-2048, 2046, 2);
+    round_trip::<CSRImmediate>(0, 31, 1);
+}
+
+#[test]
+fn immediate_bits() {
+    assert_eq!(IImmediate::BITS, 12);
+    assert_eq!(BImmediate::BITS, 13);
+    assert_eq!(CJImmediate::BITS, 12);
+    assert_eq!(CSRImmediate::BITS, 5);
+}
+
+#[test]
+fn immediate_fields() {
+    // addi x0,x0,-1
+    assert_eq!(
+        IImmediate::try_from(-1).unwrap().to_u32_fields(),
+        0xfff00000
+    );
+    // c.j -2
+    assert_eq!(CJImmediate::try_from(-2).unwrap().to_u32_fields(), 0x1ffc);
+    assert_eq!(
+        CSRImmediate::try_from(31).unwrap().to_u32_fields(),
+        0b11111 << 15
+    );
+}
+
+#[test]
+fn immediate_limits() {
+    assert_eq!((IImmediate::MIN, IImmediate::MAX), (-2048, 2047));
+    assert_eq!((BImmediate::MIN, BImmediate::MAX), (-4096, 4094));
+    assert_eq!((CSRImmediate::MIN, CSRImmediate::MAX), (0, 31));
+
+    let imm = IImmediate::try_from(-1).unwrap();
+    assert_eq!(imm.as_i32(), -1);
+    assert_eq!(imm.as_u32(), 0xfff);
+    assert!(IImmediate::try_from(IImmediate::MAX + 1).is_err());
+}
+
+#[test]
+fn vector_types() {
+    // vsetvli a0,a1,e32,m1,ta,ma
+    let vtype = VType::from_u32(0b1101_0000).unwrap();
+    assert_eq!(
+        vtype,
+        VType {
+            sew: 32,
+            lmul: Lmul::M1,
+            ta: true,
+            ma: true
+        }
+    );
+    assert_eq!(vtype.to_string(), "e32,m1,ta,ma");
+    assert_eq!(vtype.to_u32(), 0b1101_0000);
+    assert_eq!(VType::from_str("e32, m1, ta, ma"), Ok(vtype));
+    assert_eq!(vtype.vlmax(128), 4);
+
+    let vtype = VType::from_str("e8,mf2").unwrap();
+    assert_eq!(vtype.to_string(), "e8,mf2,tu,mu");
+    assert_eq!(vtype.to_u32(), 0b000_111);
+    assert_eq!(vtype.vlmax(128), 8);
+
+    for bits in 0..0x100 {
+        if let Ok(vtype) = VType::from_u32(bits) {
+            assert_eq!(vtype.to_u32(), bits);
+            assert_eq!(VType::from_str(&vtype.to_string()), Ok(vtype));
+        }
+    }
+    assert!(VType::from_u32(0b100).is_err());
+    assert!(VType::from_u32(0b100_000).is_err());
+    assert!(VType::from_u32(0x100).is_err());
+    assert!(VType::from_str("e128,m1").is_err());
+    assert!(VType::from_str("e32,m3").is_err());
+    assert!(VType::from_str("e32,m1,ma,ta").is_err());
+}
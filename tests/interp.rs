@@ -0,0 +1,411 @@
+#![cfg(feature = "interp")]
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_expanded, assemble_line_with},
+    config::{AssemblerConfig, Extensions},
+    instruction::Instruction,
+    interp::{Event, Hart, Memory},
+    register::{FRegister, IRegister},
+};
+
+const BASE: u64 = 0x1000;
+
+/// Assembles a program into a hart with 4 KiB of memory.
+fn load(program: &[&str]) -> Hart {
+    let mut memory = Memory::new(BASE, 0x1000);
+    let mut address = BASE;
+    for line in program {
+        let inst = assemble_line(line).unwrap();
+        memory
+            .store(address, inst.len(), inst.encode() as u64)
+            .unwrap();
+        address += inst.len() as u64;
+    }
+    Hart::new(memory)
+}
+
+#[test]
+fn sum_loop() {
+    let mut hart = load(&[
+        "addi a0,zero,0",
+        "addi a1,zero,10",
+        "add a0,a0,a1",
+        "addi a1,a1,-1",
+        "bne a1,zero,-8",
+        "c.ebreak",
+    ]);
+    assert_eq!(hart.run(100), Ok(Event::Ebreak));
+    assert_eq!(hart.read(IRegister::A0), 55);
+    assert_eq!(hart.pc, BASE + 22);
+}
+
+#[test]
+fn compressed_program() {
+    let mut hart = load(&[
+        "c.li a0,-3",
+        "c.slli a0,4",
+        "c.mv a1,a0",
+        "c.addw a1,a0",
+        "c.ebreak",
+    ]);
+    assert_eq!(hart.run(100), Ok(Event::Ebreak));
+    assert_eq!(hart.read(IRegister::A0), -48i64 as u64);
+    assert_eq!(hart.read(IRegister::A1), -96i64 as u64);
+    assert_eq!(hart.pc, BASE + 10);
+}
+
+#[test]
+fn jumps_and_memory() {
+    let mut hart = load(&[
+        "jal ra,6",
+        "c.ebreak",
+        "lui a0,1",
+        "addi a0,a0,1024",
+        "addi a1,zero,-2",
+        "sw a1,0(a0)",
+        "lbu a2,0(a0)",
+        "lw a3,0(a0)",
+        "lwu a4,0(a0)",
+        "jalr zero,0(ra)",
+    ]);
+    assert_eq!(hart.run(100), Ok(Event::Ebreak));
+    assert_eq!(hart.read(IRegister::ReturnAddress), BASE + 4);
+    assert_eq!(hart.read(IRegister::A2), 0xfe);
+    assert_eq!(hart.read(IRegister::A3), -2i64 as u64);
+    assert_eq!(hart.read(IRegister::A4), 0xffff_fffe);
+    assert_eq!(hart.memory.load(0x1400, 4), Ok(0xffff_fffe));
+    assert!(hart.memory.load(0x2000, 1).is_err());
+}
+
+#[test]
+fn zero_register() {
+    let mut hart = load(&["addi zero,zero,5", "c.ebreak"]);
+    assert_eq!(hart.run(10), Ok(Event::Ebreak));
+    assert_eq!(hart.read(IRegister::Zero), 0);
+}
+
+#[test]
+fn division_edge_cases() {
+    let mut hart = load(&[
+        "addi a0,zero,7",
+        "div a1,a0,zero",
+        "divu a2,a0,zero",
+        "rem a3,a0,zero",
+        "addi t0,zero,-1",
+        "slli t1,t0,63",
+        "div a4,t1,t0",
+        "rem a5,t1,t0",
+        "mulhu a6,t0,t0",
+        "divw a7,a0,zero",
+        "c.ebreak",
+    ]);
+    assert_eq!(hart.run(100), Ok(Event::Ebreak));
+    assert_eq!(hart.read(IRegister::A1), u64::MAX);
+    assert_eq!(hart.read(IRegister::A2), u64::MAX);
+    assert_eq!(hart.read(IRegister::A3), 7);
+    assert_eq!(hart.read(IRegister::A4), 1 << 63);
+    assert_eq!(hart.read(IRegister::A5), 0);
+    assert_eq!(hart.read(IRegister::A6), u64::MAX - 1);
+    assert_eq!(hart.read(IRegister::A7), u64::MAX);
+}
+
+#[test]
+fn scalar_crypto_bit_manipulation() {
+    let config = AssemblerConfig {
+        extensions: Extensions {
+            zbkb: true,
+            zbkc: true,
+            zbkx: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut hart = load(&[]);
+    hart.x[10] = 0x0123_4567_89ab_cdef;
+    hart.x[11] = 0xfedc_ba98_7654_3210;
+    hart.x[12] = 0x0001_0203_0405_0607;
+    for (line, expected) in [
+        ("pack a3,a0,a1", 0x7654_3210_89ab_cdef),
+        ("packh a3,a0,a1", 0x10ef),
+        ("packw a3,a1,a0", 0xffff_ffff_cdef_3210),
+        ("brev8 a3,a0", 0x80c4_a2e6_91d5_b3f7),
+        ("rev8 a3,a0", 0xefcd_ab89_6745_2301),
+        ("andn a3,a0,a2", 0x0122_4564_89aa_c9e8),
+        ("orn a3,a0,a2", 0xffff_fdff_fbfb_fdff),
+        ("xnor a3,a0,a1", 0),
+        ("rol a3,a0,a2", 0x91a2_b3c4_d5e6_f780),
+        ("ror a3,a0,a2", 0xde02_468a_cf13_579b),
+        ("rori a3,a0,4", 0xf012_3456_789a_bcde),
+        ("rolw a3,a0,a2", 0xffff_ffff_d5e6_f7c4),
+        ("rorw a3,a1,a2", 0x20ec_a864),
+        ("roriw a3,a0,8", 0xffff_ffff_ef89_abcd),
+        ("clmul a3,a0,a1", 0x40a0_7898_28c8_10f0),
+        ("clmulh a3,a0,a1", 0x00e0_38d8_6888_50b0),
+        // every nibble selects itself
+        ("xperm4 a3,a0,a1", 0x0123_4567_89ab_cdef),
+        ("xperm8 a3,a0,a2", 0xefcd_ab89_6745_2301),
+        // indices past the end of the table select zero
+        ("xperm8 a3,a0,a1", 0),
+    ] {
+        let i = assemble_line_with(line, &config).unwrap().i();
+        assert_eq!(hart.execute(&i, 4), Ok(Event::Continue));
+        assert_eq!(hart.read(IRegister::A3), expected, "{line}");
+    }
+}
+
+#[test]
+fn sha_sigma_and_sum() {
+    let config = AssemblerConfig {
+        extensions: Extensions {
+            zknh: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut hart = load(&[]);
+    hart.x[10] = 0x0123_4567_89ab_cdef;
+    for (line, expected) in [
+        // the sha256 instructions sign extend their 32-bit result
+        ("sha256sig0 a3,a0", 0x3d5d_cc4c),
+        ("sha256sig1 a3,a0", 0xffff_ffff_9f68_5f13),
+        ("sha256sum0 a3,a0", 0x2221_0003),
+        ("sha256sum1 a3,a0", 0xffff_ffff_d631_6d8a),
+        ("sha512sig0 a3,a0", 0x6f92_c77c_6c4f_1aa1),
+        ("sha512sig1 a3,a0", 0x70a3_460d_bbd4_317a),
+        ("sha512sum0 a3,a0", 0xb7c5_7a10_0c7e_c1ab),
+        ("sha512sum1 a3,a0", 0x7703_1123_3347_5567),
+    ] {
+        let i = assemble_line_with(line, &config).unwrap().i();
+        assert_eq!(hart.execute(&i, 4), Ok(Event::Continue));
+        assert_eq!(hart.read(IRegister::A3), expected, "{line}");
+    }
+}
+
+#[test]
+fn atomics() {
+    let mut hart = load(&[
+        "lui a0,1",
+        "addi a0,a0,1024",
+        "addi a1,zero,5",
+        "amoadd.w a2,a0,a1",
+        "amoadd.w a2,a0,a1",
+        "lr.w a3,a0",
+        "sc.w a4,a0,a1",
+        "sc.w a5,a0,a1",
+        "c.ebreak",
+    ]);
+    assert_eq!(hart.run(100), Ok(Event::Ebreak));
+    assert_eq!(hart.read(IRegister::A2), 5);
+    assert_eq!(hart.read(IRegister::A3), 10);
+    assert_eq!(hart.read(IRegister::A4), 0);
+    assert_eq!(hart.read(IRegister::A5), 1);
+    assert_eq!(hart.memory.load(0x1400, 4), Ok(5));
+
+    let mut hart = load(&["addi a0,zero,2", "lr.w a1,a0"]);
+    assert!(hart.run(10).is_err());
+}
+
+#[test]
+fn byte_and_halfword_atomics() {
+    let config = AssemblerConfig {
+        extensions: Extensions {
+            zabha: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut hart = load(&[]);
+    hart.memory.store(0x1400, 8, 0x1234_5678_9abc_de7f).unwrap();
+    hart.x[10] = 0x1400;
+    hart.x[11] = 0x1ff;
+    for (line, old, memory) in [
+        // the byte is sign extended into rd and only the low bits of the source are stored
+        ("amoadd.b a2,a0,a1", 0x7f, 0x1234_5678_9abc_de7e),
+        ("amomax.b a2,a0,a1", 0x7e, 0x1234_5678_9abc_de7e),
+        ("amomaxu.b a2,a0,a1", 0x7e, 0x1234_5678_9abc_deff),
+        (
+            "amoswap.h a2,a0,a1",
+            0xffff_ffff_ffff_deff,
+            0x1234_5678_9abc_01ff,
+        ),
+        ("amomin.h a2,a0,a1", 0x1ff, 0x1234_5678_9abc_01ff),
+        // a1 holds the expected value, the low byte matches but the halfword does not
+        (
+            "amocas.b a1,a0,zero",
+            0xffff_ffff_ffff_ffff,
+            0x1234_5678_9abc_0100,
+        ),
+        ("amocas.h a1,a0,zero", 0x100, 0x1234_5678_9abc_0100),
+    ] {
+        let i = assemble_line_with(line, &config).unwrap().i();
+        assert_eq!(hart.execute(&i, 4), Ok(Event::Continue), "{line}");
+        let dest = if line.starts_with("amocas") { 11 } else { 12 };
+        assert_eq!(hart.x[dest], old, "{line}");
+        assert_eq!(hart.memory.load(0x1400, 8), Ok(memory), "{line}");
+    }
+
+    // halfwords must be aligned
+    hart.x[10] = 0x1401;
+    let i = assemble_line_with("amoadd.h a2,a0,a1", &config)
+        .unwrap()
+        .i();
+    assert!(hart.execute(&i, 4).is_err());
+}
+
+#[test]
+fn floating_point() {
+    let mut hart = load(&[
+        "addi a0,zero,7",
+        "fcvt.s.w fa0,a0",
+        "addi a1,zero,2",
+        "fcvt.s.w fa1,a1",
+        "fdiv.s fa2,fa0,fa1",
+        "fcvt.w.s.rne a2,fa2",
+        "fcvt.w.s.rtz a3,fa2",
+        "fcvt.w.s.rmm a4,fa2",
+        "fsub.s fa3,fa4,fa2",
+        "fcvt.wu.s.rtz a5,fa3",
+        "fclass.s a6,fa3",
+        "flt.s a7,fa3,fa2",
+        "c.ebreak",
+    ]);
+    assert_eq!(hart.run(100), Ok(Event::Ebreak));
+    assert_eq!(hart.read_f(FRegister::FA2), 3.5);
+    assert_eq!(hart.read(IRegister::A2), 4);
+    assert_eq!(hart.read(IRegister::A3), 3);
+    assert_eq!(hart.read(IRegister::A4), 4);
+    assert_eq!(hart.read_f(FRegister::FA3), -3.5);
+    assert_eq!(hart.read(IRegister::A5), 0);
+    assert_eq!(hart.read(IRegister::A6), 1 << 1);
+    assert_eq!(hart.read(IRegister::A7), 1);
+}
+
+#[test]
+fn bfloat16() {
+    let config = AssemblerConfig {
+        extensions: Extensions {
+            zfbfmin: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut hart = load(&[]);
+    // 1 + 2^-8 + 2^-23, which is just over halfway between two bfloat16 values
+    hart.f[11] = 0x3f80_8001;
+    hart.x[11] = 0x1234_c2f7;
+    for (line, expected) in [
+        ("fcvt.bf16.s.rne fa0,fa1", 0xffff_3f81),
+        ("fcvt.bf16.s.rtz fa0,fa1", 0xffff_3f80),
+        ("fcvt.bf16.s.rdn fa0,fa1", 0xffff_3f80),
+        ("fcvt.bf16.s.rup fa0,fa1", 0xffff_3f81),
+        ("fmv.h.x fa0,a1", 0xffff_c2f7),
+        // -123.5 as bfloat16
+        ("fcvt.s.bf16 fa0,fa0", 0xc2f7_0000),
+    ] {
+        let i = assemble_line_with(line, &config).unwrap().i();
+        assert_eq!(hart.execute(&i, 4), Ok(Event::Continue));
+        assert_eq!(hart.f[10], expected, "{line}");
+    }
+    let i = assemble_line_with("fmv.x.h a0,fa1", &config).unwrap().i();
+    assert_eq!(hart.execute(&i, 4), Ok(Event::Continue));
+    // the low 16 bits are sign extended
+    assert_eq!(hart.read(IRegister::A0), 0xffff_ffff_ffff_8001);
+}
+
+#[test]
+fn cache_block_operations() {
+    let config = AssemblerConfig {
+        extensions: Extensions {
+            zicbom: true,
+            zicboz: true,
+            zicbop: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let mut hart = load(&[]);
+    for address in (0x1400..0x1480).step_by(8) {
+        hart.memory.store(address, 8, u64::MAX).unwrap();
+    }
+    // anywhere inside the block zeroes all of it
+    hart.x[10] = 0x1452;
+    for line in [
+        "prefetch.w 32(a0)",
+        "cbo.clean (a0)",
+        "cbo.flush (a0)",
+        "cbo.inval (a0)",
+        "cbo.zero (a0)",
+    ] {
+        let i = assemble_line_with(line, &config).unwrap().i();
+        assert_eq!(hart.execute(&i, 4), Ok(Event::Continue), "{line}");
+    }
+    assert_eq!(hart.memory.load(0x1438, 8), Ok(u64::MAX));
+    assert_eq!(hart.memory.load(0x1440, 8), Ok(0));
+    assert_eq!(hart.memory.load(0x1478, 8), Ok(0));
+}
+
+#[test]
+fn floating_point_csrs() {
+    let mut hart = load(&[
+        "csrrwi zero,2,1",
+        "addi a0,zero,5",
+        "fcvt.s.w fa0,a0",
+        "addi a1,zero,2",
+        "fcvt.s.w fa1,a1",
+        "fdiv.s fa0,fa0,fa1",
+        "fcvt.w.s.dyn a2,fa0",
+        "csrrs a3,3,zero",
+        "c.ebreak",
+    ]);
+    assert_eq!(hart.run(100), Ok(Event::Ebreak));
+    // 2.5 rounded towards zero
+    assert_eq!(hart.read(IRegister::A2), 2);
+    assert_eq!(hart.read(IRegister::A3), 1 << 5);
+
+    let mut hart = load(&["csrrwi zero,2,5", "fcvt.w.s.dyn a0,fa0"]);
+    assert!(hart.run(10).is_err());
+}
+
+#[test]
+fn environment_calls() {
+    let mut hart = Hart::new(Memory::new(BASE, 0x100));
+    assert_eq!(hart.execute(&Instruction::ECALL, 4), Ok(Event::Ecall));
+    assert_eq!(hart.pc, BASE + 4);
+    assert_eq!(hart.execute(&Instruction::EBREAK, 4), Ok(Event::Ebreak));
+    assert_eq!(hart.pc, BASE + 8);
+}
+
+#[test]
+fn privileged_instructions() {
+    let mut hart = load(&["wfi", "sfence.vma a0,a1", "mret"]);
+    assert_eq!(hart.step(), Ok(Event::Continue));
+    assert_eq!(hart.step(), Ok(Event::Continue));
+    assert_eq!(hart.pc, BASE + 8);
+    assert!(hart.step().is_err());
+}
+
+#[test]
+fn load_immediate_sequences() {
+    for value in [
+        0,
+        2047,
+        -2048,
+        0x7ffff800,
+        0x7fffffff,
+        -0x80000000,
+        0x80000000,
+        0xfffff800,
+        0x123456789abcdef0,
+        0x7fffffffffffffff,
+        i64::MIN,
+        -0x123456789,
+        0xdeadbeef00000000u64 as i64,
+    ] {
+        let mut hart = Hart::new(Memory::new(BASE, 0x100));
+        for inst in assemble_line_expanded(&format!("li a0,{value}")).unwrap() {
+            let inst = inst.full().unwrap();
+            hart.execute(&inst, 4).unwrap();
+        }
+        assert_eq!(hart.read(IRegister::A0), value as u64, "{value:#x}");
+    }
+}
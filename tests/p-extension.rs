@@ -0,0 +1,153 @@
+#![cfg(feature = "p")]
+
+mod common;
+
+use riscv_codec::{
+    assembly::assemble_line_with,
+    config::{AssemblerConfig, DecoderConfig, IsaString},
+    immediates::PImmediate,
+    instruction::Instruction,
+    register::IRegister,
+    simd::{SimdImmOp, SimdInstruction, SimdOp},
+};
+
+const ISA: &str = "rv64gcp";
+const RV32_ISA: &str = "rv32gcp";
+
+#[test]
+fn add16() {
+    let (decoder, assembler) = common::isa(ISA);
+    let bin = 0x40c58577;
+    let expected = Instruction::P(SimdInstruction::Binary {
+        op: SimdOp::ADD16,
+        dest: IRegister::A0,
+        src1: IRegister::A1,
+        src2: IRegister::A2,
+    });
+
+    // check assembler
+    let i = assemble_line_with("add16 a0,a1,a2", &assembler)
+        .unwrap()
+        .i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "add16 a0,a1,a2");
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x48c58577, "add8 a0,a1,a2"),
+            (0x16c58577, "kcrsa16 a0,a1,a2"),
+            (0x60c58577, "sra16.u a0,a1,a2"),
+            (0x4cc58577, "cmpeq16 a0,a1,a2"),
+            (0xa0c58577, "smul16 a0,a1,a2"),
+            (0x9ec58577, "khmx8 a0,a1,a2"),
+            (0x9ac58577, "umax8 a0,a1,a2"),
+            (0xad158577, "kabs16 a0,a1"),
+            (0xae158577, "clz8 a0,a1"),
+            (0x70358577, "srai16 a0,a1,3"),
+            (0x71f58577, "srai16.u a0,a1,15"),
+            (0x7c858577, "kslli8 a0,a1,0"),
+            (0x84f58577, "sclip16 a0,a1,15"),
+            (0x8d558577, "uclip8 a0,a1,5"),
+            (0xe4758577, "sclip32 a0,a1,7"),
+            (0xf5f58577, "uclip32 a0,a1,31"),
+        ],
+    );
+}
+
+#[test]
+fn every_encoding_round_trips() {
+    let (decoder, _) = common::isa(ISA);
+    let mut count = 0;
+    for high in 0..1 << 12 {
+        let bin = high << 20 | 0x58577;
+        if let Ok(i) = Instruction::decode_with(bin, &decoder) {
+            assert_eq!(Instruction::encode(&i), bin, "{i}");
+            count += 1;
+        }
+    }
+    // 76 register-register operations with any rs2, 8 unary operations, 8 operations with 3-bit immediates, 8 with
+    // 4-bit immediates and 2 with 5-bit immediates
+    assert_eq!(count, 76 * 32 + 8 + 8 * 8 + 8 * 16 + 2 * 32);
+}
+
+#[test]
+fn immediates() {
+    let (_, assembler) = common::isa(ISA);
+    assert_eq!(
+        assemble_line_with("srai8 a0,a1,8", &assembler).map(|_| ()),
+        Err("the immediate of srai8 must be between 0 and 7, got 8".to_owned())
+    );
+    assert!(assemble_line_with("slli16 a0,a1,16", &assembler).is_err());
+    assert!(assemble_line_with("uclip32 a0,a1,32", &assembler).is_err());
+    assert_eq!(
+        assemble_line_with("slli16 a0,a1,15", &assembler)
+            .unwrap()
+            .i(),
+        Instruction::P(SimdInstruction::Immediate {
+            op: SimdImmOp::SLLI16,
+            dest: IRegister::A0,
+            src: IRegister::A1,
+            imm: PImmediate::try_from(15).unwrap(),
+        })
+    );
+}
+
+#[test]
+fn register_pairs() {
+    let (decoder, assembler) = common::isa(RV32_ISA);
+    // smul16 a0,a1,a2 and smul16 a1,a1,a2
+    assert!(Instruction::decode_with(0xa0c58577, &decoder).is_ok());
+    assert_eq!(
+        Instruction::decode_with(0xa0c585f7, &decoder).map_err(String::from),
+        Err(
+            "smul16 on RV32 writes a register pair and must use an even register, got a1"
+                .to_owned()
+        )
+    );
+    assert_eq!(
+        assemble_line_with("umul8 a1,a1,a2", &assembler).map(|_| ()),
+        Err(
+            "umul8 on RV32 writes a register pair and must use an even register, got a1".to_owned()
+        )
+    );
+    // RV64 has no pairs, nor do the other instructions
+    assert!(assemble_line_with("umul8 a1,a1,a2", &common::isa(ISA).1).is_ok());
+    assert!(assemble_line_with("add16 a1,a1,a2", &assembler).is_ok());
+}
+
+#[test]
+fn extension_requirements() {
+    // OP-P is the OP-VE opcode of the vector crypto instructions without P
+    let default = DecoderConfig::default();
+    assert_eq!(
+        Instruction::decode_with(0x40c58577, &default).map_err(String::from),
+        Err("unknown vector crypto instruction with funct3 0b000".to_owned())
+    );
+    assert_eq!(
+        assemble_line_with("add16 a0,a1,a2", &AssemblerConfig::default()).map(|_| ()),
+        Err("add16 requires the P extension".to_owned())
+    );
+
+    // and the vector crypto instructions are not available with P
+    let isa = IsaString::try_from("rv64gcvp_zvkned").unwrap();
+    assert!(isa.extensions.p);
+    assert_eq!(isa.to_string(), "rv64imafdcpv_zicsr_zifencei_zvkned");
+    assert_eq!(
+        assemble_line_with("vaesef.vv v1,v2", &AssemblerConfig::isa(&isa)).map(|_| ()),
+        Err("vaesef.vv is not available with P, which uses its opcode".to_owned())
+    );
+}
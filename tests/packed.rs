@@ -0,0 +1,73 @@
+use riscv_codec::{
+    assembly::assemble_line,
+    config::{DecoderConfig, Extensions, Xlen},
+    instruction::Instruction,
+    packed::PackedInst,
+    timing::ExecClass,
+};
+
+#[test]
+fn packed_instructions() {
+    assert_eq!(std::mem::size_of::<PackedInst>(), 8);
+    assert!(std::mem::size_of::<Instruction>() > std::mem::size_of::<PackedInst>());
+
+    for line in [
+        "add a0,a1,a2",
+        "ld a0,-8(sp)",
+        "fmul.s fa0,fa1,fa2",
+        "amoswap.d.aq a0,a1,(a2)",
+        "c.addi a0,1",
+        "c.sdsp ra,8",
+    ] {
+        let inst = assemble_line(line).unwrap();
+        let packed = PackedInst::decode(inst.encode()).unwrap();
+        let expanded = inst.expand().unwrap();
+        assert_eq!(packed.expand(), expanded, "{line}");
+        assert_eq!(packed.len(), inst.len());
+        assert_eq!(packed.raw(), inst.encode());
+        assert_eq!(packed.exec_class(), expanded.exec_class());
+    }
+    // only the low half of compressed instructions is kept
+    let packed = PackedInst::decode(0xdead_0505).unwrap();
+    assert!(packed.is_compressed());
+    assert_eq!(packed.raw(), 0x0505);
+    assert_eq!(packed.exec_class(), ExecClass::Alu);
+
+    assert!(PackedInst::decode(0).is_err());
+    assert!(PackedInst::decode(0xffff_ffff).is_err());
+}
+
+#[test]
+fn packed_configuration() {
+    // 0x2505 is c.addiw on RV64 and c.jal on RV32
+    let rv32 = DecoderConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+    let packed = PackedInst::decode_with(0x2505, &rv32).unwrap();
+    assert_eq!(packed.exec_class(), ExecClass::Branch);
+    assert!(matches!(packed.expand(), Instruction::JAL { .. }));
+    assert!(matches!(
+        PackedInst::decode(0x2505).unwrap().expand(),
+        Instruction::ADDIW { .. }
+    ));
+
+    // 0x6188 is c.flw on RV32 and c.ld of a register pair with Zclsd
+    let zclsd = DecoderConfig {
+        xlen: Xlen::Rv32,
+        extensions: Extensions {
+            zilsd: true,
+            zclsd: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(matches!(
+        PackedInst::decode_with(0x6188, &rv32).unwrap().expand(),
+        Instruction::FLW { .. }
+    ));
+    assert!(matches!(
+        PackedInst::decode_with(0x6188, &zclsd).unwrap().expand(),
+        Instruction::LD { .. }
+    ));
+}
@@ -0,0 +1,114 @@
+use riscv_codec::{
+    assembly::assemble_line,
+    config::{DecoderConfig, FormatOptions},
+    instruction::Instruction,
+    register::IRegister,
+};
+
+#[test]
+fn sfence_vma() {
+    let bin = 0x12b50073;
+    let expected = Instruction::SFENCEVMA {
+        vaddr: IRegister::A0,
+        asid: IRegister::A1,
+    };
+
+    // check assembler
+    let i = assemble_line("sfence.vma a0,a1").unwrap().i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode(bin).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "sfence.vma a0,a1");
+}
+
+#[test]
+fn round_trips() {
+    for (bin, line) in [
+        (0x00000073, "ecall"),
+        (0x00100073, "ebreak"),
+        (0x10200073, "sret"),
+        (0x30200073, "mret"),
+        (0x70200073, "mnret"),
+        (0x7b200073, "dret"),
+        (0x10500073, "wfi"),
+        (0x12000073, "sfence.vma zero,zero"),
+        (0x12050073, "sfence.vma a0,zero"),
+    ] {
+        let i = Instruction::decode(bin).unwrap();
+        assert_eq!(i.to_string(), line);
+        assert_eq!(Instruction::encode(&i), bin, "{line}");
+        assert_eq!(assemble_line(line).unwrap().i(), i);
+    }
+}
+
+#[test]
+fn operands_of_sfence_vma_default_to_zero() {
+    assert_eq!(
+        assemble_line("sfence.vma").unwrap().i(),
+        Instruction::SFENCEVMA {
+            vaddr: IRegister::Zero,
+            asid: IRegister::Zero,
+        }
+    );
+    assert_eq!(
+        assemble_line("sfence.vma a0").unwrap().i(),
+        Instruction::SFENCEVMA {
+            vaddr: IRegister::A0,
+            asid: IRegister::Zero,
+        }
+    );
+}
+
+#[test]
+fn pseudo_instructions() {
+    let options = FormatOptions {
+        pseudo_instructions: true,
+        ..Default::default()
+    };
+    for (bin, line) in [
+        (0x12000073, "sfence.vma"),
+        (0x12050073, "sfence.vma a0"),
+        (0x12b50073, "sfence.vma a0,a1"),
+    ] {
+        let i = Instruction::decode(bin).unwrap();
+        assert_eq!(i.display_with(&options).to_string(), line);
+    }
+}
+
+#[test]
+fn malformed() {
+    for (bin, reason) in [
+        (0x00200073, "unknown funct12"),
+        (0x30200173, "mret with rd set"),
+        (0x30258073, "mret with rs1 set"),
+        (0x12000173, "sfence.vma with rd set"),
+    ] {
+        assert!(Instruction::decode(bin).is_err(), "{reason}");
+    }
+    // the reserved fields are ignored without strict checking
+    let lenient = DecoderConfig {
+        strict: false,
+        ..Default::default()
+    };
+    assert_eq!(
+        Instruction::decode_with(0x30200173, &lenient).unwrap(),
+        Instruction::MRET
+    );
+    assert!(Instruction::decode_with(0x00200073, &lenient).is_err());
+    for line in [
+        "ecall.x",
+        "mret a0",
+        "wfi 1",
+        "sfence.vma a0,a1,a2",
+        "sfence.vma 1",
+        "sfence a0,a1",
+    ] {
+        assert!(assemble_line(line).is_err(), "{line}");
+    }
+}
@@ -0,0 +1,657 @@
+use riscv_codec::{
+    assembly::assemble_line,
+    config::{AssemblerConfig, Xlen},
+    program::{Binding, ProgramSymbol, Relocation, RelocationKind, assemble_program},
+};
+
+const SOURCE: &str = "
+    .globl _start
+    .weak handler
+_start:             # entry point
+    addi a0,zero,3
+loop: c.addi a0,-1
+    c.bnez a0,loop
+    jal ra,helper
+    beq a0,zero,handler
+    c.j _start
+helper:
+    jalr zero,0(ra)
+message: .asciz \"a # b\"
+    .half 0xffff,-1
+";
+
+#[test]
+fn labels_and_binding() {
+    let program = assemble_program(SOURCE, &AssemblerConfig::default()).unwrap();
+    let text = &program.sections[0];
+    assert_eq!(text.name, ".text");
+    assert_eq!(text.data.len(), 22 + 6 + 4);
+    // c.bnez a0,loop is a branch back 2 bytes
+    assert_eq!(&text.data[6..8], &0xfd7du16.to_le_bytes());
+    // jal ra,helper jumps forward 10 bytes
+    assert_eq!(&text.data[8..12], &0x00a000efu32.to_le_bytes());
+    assert_eq!(&text.data[22..28], b"a # b\0");
+    assert_eq!(&text.data[28..], &[0xff; 4]);
+    assert!(text.compressed);
+    assert_eq!(
+        text.relocations,
+        vec![Relocation {
+            offset: 12,
+            kind: RelocationKind::Branch,
+            symbol: "handler".to_owned(),
+            addend: 0,
+        }]
+    );
+
+    assert_eq!(
+        program.symbol("_start"),
+        Some(&ProgramSymbol {
+            name: "_start".to_owned(),
+            section: Some(0),
+            value: 0,
+            binding: Binding::Global,
+        })
+    );
+    assert_eq!(program.symbol("loop").unwrap().binding, Binding::Local);
+    assert_eq!(program.symbol("loop").unwrap().value, 4);
+    assert_eq!(program.symbol("handler").unwrap().binding, Binding::Weak);
+    assert_eq!(program.symbol("handler").unwrap().section, None);
+
+    // references to undefined symbols make them global
+    let program = assemble_program("jal ra,printf", &AssemblerConfig::default()).unwrap();
+    assert_eq!(program.symbol("printf").unwrap().binding, Binding::Global);
+    assert_eq!(program.sections[0].relocations[0].kind, RelocationKind::Jal);
+}
+
+#[test]
+fn program_errors() {
+    let config = AssemblerConfig::default();
+    assert_eq!(
+        assemble_program("a:\naddi zero,zero,0\na:", &config),
+        Err("line 3: a is already defined".to_owned())
+    );
+    assert_eq!(
+        assemble_program("\n.fill 1", &config),
+        Err("line 2: unknown directive .fill".to_owned())
+    );
+    assert!(assemble_program(".byte 256", &config).is_err());
+    assert!(assemble_program(".globl 1x", &config).is_err());
+    assert!(
+        assemble_program("addi a0,a0,1\nadd a0", &config)
+            .unwrap_err()
+            .starts_with("line 2:")
+    );
+}
+
+#[test]
+fn sections() {
+    let source = "
+        .text
+    start:
+        beq a0,zero,done
+        .data
+    value: .word 1
+        .section .text
+        beq a0,zero,done
+    done:
+        beq a0,zero,value
+        .section .init,\"ax\",@progbits
+        c.j start
+        .bss
+    buffer: .zero 16
+        .section .noinit,\"aw\",@nobits
+        .zero 4
+        .data
+    other: .byte 2
+    ";
+    let program = assemble_program(source, &AssemblerConfig::default()).unwrap();
+    let names: Vec<&str> = program.sections.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, [".text", ".data", ".init", ".bss", ".noinit"]);
+
+    // each section has its own location counter, which continues when switching back
+    let text = program.section(".text").unwrap();
+    assert_eq!(text.data.len(), 12);
+    assert!(text.executable && !text.writable);
+    assert_eq!(program.section(".data").unwrap().data, [1, 0, 0, 0, 2]);
+    assert_eq!(program.symbol("done").unwrap().value, 8);
+    assert_eq!(program.symbol("other").unwrap().section, Some(1));
+    assert_eq!(program.symbol("other").unwrap().value, 4);
+    let bss = program.section(".bss").unwrap();
+    assert!(bss.nobits && bss.writable && bss.data.len() == 16);
+    let noinit = program.section(".noinit").unwrap();
+    assert!(noinit.nobits && noinit.allocated && !noinit.executable);
+
+    // only labels in the same section are resolved
+    assert_eq!(
+        text.relocations,
+        [Relocation {
+            offset: 8,
+            kind: RelocationKind::Branch,
+            symbol: "value".to_owned(),
+            addend: 0
+        }]
+    );
+    assert_eq!(
+        program.section(".init").unwrap().relocations[0].symbol,
+        "start"
+    );
+
+    let config = AssemblerConfig::default();
+    assert_eq!(
+        assemble_program(".bss\n.byte 1", &config),
+        Err("line 2: .bss can only hold zeros, use .zero".to_owned())
+    );
+    assert_eq!(
+        assemble_program(".bss\naddi a0,a0,1", &config),
+        Err("line 2: .bss can not hold instructions".to_owned())
+    );
+    assert!(assemble_program(".section .x,\"q\"", &config).is_err());
+    assert!(assemble_program(".section .x,ax", &config).is_err());
+}
+
+#[test]
+fn string_escapes() {
+    // the expected bytes are the output of GNU as
+    let source = r##"
+        .ascii "a\x414" "b\101\0c" , "q\"#"  # comment
+        .asciz "x", "y" "z"
+        .string "\n\t\\\r\b\f\v\1234"
+    "##;
+    let config = AssemblerConfig::default();
+    let program = assemble_program(source, &config).unwrap();
+    assert_eq!(
+        program.sections[0].data,
+        b"a\x14bA\0cq\"#x\0yz\0\n\t\\\r\x08\x0c\x0bS4\0"
+    );
+
+    for source in [
+        ".ascii \"a\\q\"",
+        ".ascii \"\\x\"",
+        ".ascii \"a\" b",
+        ".ascii \"a\",",
+        ".ascii \"a",
+    ] {
+        assert!(assemble_program(source, &config).is_err(), "{source}");
+    }
+}
+
+#[test]
+fn float_data() {
+    // the expected bytes are the output of llvm-mc
+    let source = "
+        .float 1.5, -0.1, 0x1.8p1, inf, nan, 1e-45
+        .double 0.1, -0x1p-1074, -nan
+        .single 3, -0.0
+    ";
+    let config = AssemblerConfig::default();
+    let program = assemble_program(source, &config).unwrap();
+    let words: Vec<u32> = program.sections[0]
+        .data
+        .chunks(4)
+        .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+        .collect();
+    assert_eq!(
+        words,
+        [
+            0x3fc00000, 0xbdcccccd, 0x40400000, 0x7f800000, 0x7fffffff, 0x00000001, 0x9999999a,
+            0x3fb99999, 0x00000001, 0x80000000, 0xffffffff, 0xffffffff, 0x40400000, 0x80000000
+        ]
+    );
+
+    for source in [".float 1.5x", ".float 0x", ".double 0x1p", ".float --1"] {
+        assert!(assemble_program(source, &config).is_err(), "{source}");
+    }
+}
+
+#[test]
+fn symbolic_offsets() {
+    let source = "
+        lui a0,%hi(table+8)
+        lw a1,%lo(table+8)(a0)
+        sw a1,%lo(extern-4)(a0)
+        addi a2,a0,%lo(table)
+        lw a3,table+4(gp)
+        sb a3, later - 1 (gp)
+        .data
+        .word 0
+    table: .word 1,2,3
+    later:
+    ";
+    let config = AssemblerConfig::default();
+    let program = assemble_program(source, &config).unwrap();
+    let text = program.section(".text").unwrap();
+    let relocation = |offset, kind, symbol: &str, addend| Relocation {
+        offset,
+        kind,
+        symbol: symbol.to_owned(),
+        addend,
+    };
+    assert_eq!(
+        text.relocations,
+        [
+            relocation(0, RelocationKind::Hi20, "table", 8),
+            relocation(4, RelocationKind::Lo12I, "table", 8),
+            relocation(8, RelocationKind::Lo12S, "extern", -4),
+            relocation(12, RelocationKind::Lo12I, "table", 0),
+        ]
+    );
+    let words: Vec<u32> = text
+        .data
+        .chunks(4)
+        .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+        .collect();
+    assert_eq!(
+        words,
+        [
+            0x00000537, 0x00052583, 0x00b52023, 0x00050613, 0x0081a683, 0x00d187a3
+        ]
+    );
+    assert_eq!(program.symbol("extern").unwrap().binding, Binding::Global);
+
+    assert_eq!(
+        assemble_program("lw a0,missing(gp)", &config),
+        Err("line 1: missing is not defined".to_owned())
+    );
+    assert!(assemble_program("lw a0,%pcrel(x)(a0)", &config).is_err());
+    assert!(assemble_program("lw a0,%lo(x(a0)", &config).is_err());
+    assert!(assemble_program("lw a0,%lo(1)(a0)", &config).is_err());
+    assert!(assemble_program("c.lw a0,%lo(x)(a1)", &config).is_err());
+}
+
+#[test]
+fn pic_and_tls_relocations() {
+    let source = "
+    got: auipc a0,%got_pcrel_hi(counter)
+        ld a0,%pcrel_lo(got)(a0)
+    ie: auipc a1,%tls_ie_pcrel_hi(tls)
+        ld a1,%pcrel_lo(ie)(a1)
+        auipc a0,%tls_gd_pcrel_hi(tls)
+        lui a2,%tprel_hi(tls+4)
+        add a2,a2,tp,%tprel_add(tls+4)
+        sw a3,%tprel_lo(tls+4)(a2)
+        addi a4,a2,%tprel_lo(tls)
+    ";
+    let config = AssemblerConfig::default();
+    let program = assemble_program(source, &config).unwrap();
+    let text = program.section(".text").unwrap();
+    let relocation = |offset, kind, symbol: &str, addend| Relocation {
+        offset,
+        kind,
+        symbol: symbol.to_owned(),
+        addend,
+    };
+    assert_eq!(
+        text.relocations,
+        [
+            relocation(0, RelocationKind::GotHi20, "counter", 0),
+            relocation(4, RelocationKind::PcrelLo12I, "got", 0),
+            relocation(8, RelocationKind::TlsGotHi20, "tls", 0),
+            relocation(12, RelocationKind::PcrelLo12I, "ie", 0),
+            relocation(16, RelocationKind::TlsGdHi20, "tls", 0),
+            relocation(20, RelocationKind::TprelHi20, "tls", 4),
+            relocation(24, RelocationKind::TprelAdd, "tls", 4),
+            relocation(28, RelocationKind::TprelLo12S, "tls", 4),
+            relocation(32, RelocationKind::TprelLo12I, "tls", 0),
+        ]
+    );
+    assert_eq!(
+        text.relocations
+            .iter()
+            .map(|r| r.kind.elf_type())
+            .collect::<Vec<_>>(),
+        [20, 24, 21, 24, 22, 29, 32, 31, 30]
+    );
+    // the add keeps its three register operands
+    assert_eq!(&text.data[24..28], &0x00460633u32.to_le_bytes());
+
+    assert!(assemble_program("sub a0,a0,tp,%tprel_add(x)", &config).is_err());
+    assert!(assemble_program("add a0,%tprel_add(x),tp,a0", &config).is_err());
+    assert!(assemble_program("c.lui a0,%tprel_hi(x)", &config).is_err());
+}
+
+#[test]
+fn call_and_address_symbols() {
+    let source = "
+    main: call puts
+        tail exit
+        la a0,table+8
+        call local
+        la a1,local
+    local: ret
+        .data
+    table: .dword 1,2,3
+    ";
+    let config = AssemblerConfig::default();
+    let program = assemble_program(source, &config).unwrap();
+    let text = program.section(".text").unwrap();
+    let relocation = |offset, kind, symbol: &str, addend| Relocation {
+        offset,
+        kind,
+        symbol: symbol.to_owned(),
+        addend,
+    };
+    assert_eq!(
+        text.relocations,
+        [
+            relocation(0, RelocationKind::CallPlt, "puts", 0),
+            relocation(8, RelocationKind::CallPlt, "exit", 0),
+            relocation(16, RelocationKind::PcrelHi20, "table", 8),
+            relocation(20, RelocationKind::PcrelLo12I, ".Lpcrel_hi0", 0),
+        ]
+    );
+    assert_eq!(text.relocations[0].kind.elf_type(), 19);
+    let label = program.symbol(".Lpcrel_hi0").unwrap();
+    assert_eq!((label.section, label.value), (Some(0), 16));
+    // labels in the same section are resolved
+    let words: Vec<u32> = text
+        .data
+        .chunks(4)
+        .map(|c| u32::from_le_bytes(c.try_into().unwrap()))
+        .collect();
+    assert_eq!(
+        words,
+        [
+            0x00000097, 0x000080e7, 0x00000317, 0x00030067, 0x00000517, 0x00050513, 0x00000097,
+            0x010080e7, 0x00000597, 0x00858593, 0x00008067,
+        ]
+    );
+
+    assert!(assemble_program("la a0", &config).is_err());
+    assert!(assemble_program("call 1x", &config).is_err());
+}
+
+#[test]
+fn arch_attribute() {
+    let config = AssemblerConfig::default();
+    let source = "
+        .attribute arch, \"rv32i2p1_m2p0_zicsr\"
+        mul a0,a0,a1
+        slli a0,a0,31
+    ";
+    let program = assemble_program(source, &config).unwrap();
+    assert_eq!(program.xlen, Xlen::Rv32);
+    assert!(assemble_program(&format!("{source}\nslli a0,a0,32"), &config).is_err());
+    assert_eq!(
+        assemble_program(".attribute arch, \"rv64i\"\nmul a0,a0,a1", &config),
+        Err("line 2: mul requires the M extension".to_owned())
+    );
+    // the attribute only applies to the lines after it
+    assert!(assemble_program("c.addi a0,1\n.attribute arch, \"rv64i\"", &config).is_ok());
+    assert!(
+        assemble_program(
+            "c.addi a0,1\n.attribute arch, \"rv64i\"\nc.addi a0,1",
+            &config
+        )
+        .is_err()
+    );
+    assert!(assemble_program(".attribute stack_align, 16\n.attribute 6, 1", &config).is_ok());
+
+    assert_eq!(
+        assemble_program(".word 0\n.attribute arch, \"rv32i\"", &config),
+        Err(
+            "line 2: the arch attribute can not change the XLEN to 32 after code or data"
+                .to_owned()
+        )
+    );
+    assert!(assemble_program(".attribute arch, \"rv128i\"", &config).is_err());
+    assert!(assemble_program(".attribute arch, rv64i", &config).is_err());
+    assert!(assemble_program(".attribute vendor, 1", &config).is_err());
+}
+
+#[test]
+fn option_arch() {
+    let config = AssemblerConfig::default();
+    let source = "
+        .option arch, rv64i
+        .option push
+        .option arch, +m, +zicsr
+        mul a0,a0,a1
+        csrrs a0,0x001,zero
+        .option pop
+        addi a0,a0,1
+        .option rvc
+        c.addi a0,1
+        .option norvc
+        .option arch, +F
+        flw fa0,0(a0)
+        .option relax
+        .option nopic
+    ";
+    let program = assemble_program(source, &config).unwrap();
+    assert_eq!(program.section(".text").unwrap().data.len(), 18);
+
+    // pop restores the extensions from before the push
+    assert_eq!(
+        assemble_program(
+            ".option push\n.option arch, -m\n.option pop\nmul a0,a0,a1",
+            &config
+        )
+        .map(|p| p.section(".text").unwrap().data.len()),
+        Ok(4)
+    );
+    assert_eq!(
+        assemble_program(
+            ".option push\n.option arch, -m\nmul a0,a0,a1\n.option pop",
+            &config
+        ),
+        Err("line 3: mul requires the M extension".to_owned())
+    );
+    // disabling an extension disables the ones that depend on it
+    assert!(assemble_program(".option arch, -zicsr\nflw fa0,0(a0)", &config).is_err());
+    assert!(assemble_program(".option norvc\nc.addi a0,1", &config).is_err());
+    // extensions this crate does not support are ignored
+    assert!(assemble_program(".option arch, +zbb, -zba", &config).is_ok());
+
+    assert_eq!(
+        assemble_program(".option pop", &config),
+        Err("line 1: there is no .option push to pop".to_owned())
+    );
+    assert!(assemble_program(".option arch, rv32i", &config).is_err());
+    assert!(assemble_program(".option arch, zbb", &config).is_err());
+    assert!(assemble_program(".option unknown", &config).is_err());
+}
+
+#[test]
+fn org() {
+    let source = "
+    vectors:
+        jal zero,reset
+        .org 0x10
+        jal zero,reset
+        . = vectors + 0x20 - 4
+    reset:
+        .byte 1
+        .org . + 3, 0xff
+        .data
+        .byte 2
+        .org 4
+    ";
+    let config = AssemblerConfig::default();
+    let program = assemble_program(source, &config).unwrap();
+    let text = &program.section(".text").unwrap().data;
+    assert_eq!(text.len(), 0x20);
+    assert_eq!(text[4..0x10], [0; 12]);
+    assert_eq!(text[0x1c..], [1, 0xff, 0xff, 0xff]);
+    assert_eq!(program.symbol("reset").unwrap().value, 0x1c);
+    assert_eq!(program.section(".data").unwrap().data, [2, 0, 0, 0]);
+    assert!(program.section(".text").unwrap().relocations.is_empty());
+
+    assert_eq!(
+        assemble_program(".word 0\n.org 2", &config),
+        Err("line 2: can not move the location counter backwards from 0x4 to 0x2".to_owned())
+    );
+    assert_eq!(
+        assemble_program(".org end\nend:", &config),
+        Err("line 1: end is not defined earlier in this section".to_owned())
+    );
+    assert!(assemble_program(".org 4,256", &config).is_err());
+    assert!(assemble_program(". = 4 +", &config).is_err());
+    assert!(assemble_program(".bss\n.org 4,1", &config).is_err());
+}
+
+#[cfg(feature = "elf")]
+#[test]
+fn program_elf() {
+    use riscv_codec::analysis::Symbol;
+    use riscv_codec::elf::Elf;
+
+    for xlen in [Xlen::Rv64, Xlen::Rv32] {
+        let program = assemble_program(
+            SOURCE,
+            &AssemblerConfig {
+                xlen,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let data = program.to_elf();
+        let elf = Elf::parse(&data).unwrap();
+        assert_eq!(elf.is_64, xlen == Xlen::Rv64);
+        let text = elf.section(".text").unwrap();
+        assert_eq!(elf.section_data(text).unwrap(), program.sections[0].data);
+        assert!(elf.section(".rela.text").is_some());
+        assert_eq!(
+            elf.symbols().unwrap()[..2],
+            [
+                Symbol {
+                    address: 0,
+                    name: "_start".to_owned()
+                },
+                Symbol {
+                    address: 4,
+                    name: "loop".to_owned()
+                }
+            ]
+        );
+
+        // locals are first, the binding is the high nibble of st_info
+        let symtab = elf.section(".symtab").unwrap();
+        let entry_size = if elf.is_64 { 24 } else { 16 };
+        let info_offset = if elf.is_64 { 4 } else { 12 };
+        let bindings: Vec<u8> = elf
+            .section_data(symtab)
+            .unwrap()
+            .chunks(entry_size)
+            .skip(1)
+            .map(|entry| entry[info_offset] >> 4)
+            .collect();
+        assert_eq!(bindings, vec![0, 0, 0, 1, 2]);
+    }
+
+    let source = ".data\nvalue: .word 7\n.bss\n.zero 8\n.text\nbeq a0,zero,value";
+    let program = assemble_program(source, &AssemblerConfig::default()).unwrap();
+    let data = program.to_elf();
+    let elf = Elf::parse(&data).unwrap();
+    let flags = |name: &str| {
+        let section = elf.section(name).unwrap();
+        (section.kind, section.flags, section.size, section.align)
+    };
+    assert_eq!(flags(".text"), (1, 0x6, 4, 2));
+    assert_eq!(flags(".data"), (1, 0x3, 4, 4));
+    assert_eq!(flags(".bss"), (8, 0x3, 8, 1));
+    assert_eq!(flags(".rela.text").0, 4);
+    assert_eq!(
+        elf.section_data(elf.section(".data").unwrap()).unwrap(),
+        [7, 0, 0, 0]
+    );
+
+    // data is aligned to the size of its values
+    let program = assemble_program(
+        ".data\n.byte 1\n.dword 2\n.half 3",
+        &AssemblerConfig::default(),
+    )
+    .unwrap();
+    assert_eq!(program.section(".data").unwrap().align, 8);
+}
+
+#[test]
+fn pseudo_instructions() {
+    let source = "
+    loop:
+        li a0,0x12345678
+        beqz a0,loop
+        j loop
+        ret
+    ";
+    let program = assemble_program(source, &AssemblerConfig::default()).unwrap();
+    let words: Vec<u32> = program.sections[0]
+        .data
+        .chunks(4)
+        .map(|w| u32::from_le_bytes(w.try_into().unwrap()))
+        .collect();
+    assert_eq!(
+        words,
+        [0x12345537, 0x6785051b, 0xfe050ce3, 0xff5ff06f, 0x00008067]
+    );
+}
+
+#[test]
+fn literal_pools() {
+    let source = "
+        li a0,=0x123456789abcdef0
+        li a1,=table+8
+        li a3,=0x123456789abcdef0
+        ret
+        .pool
+        li a2,=0x123456789abcdef0
+    table:
+    ";
+    let program = assemble_program(source, &AssemblerConfig::default()).unwrap();
+    let text = program.section(".text").unwrap();
+    let expected: Vec<u8> = [
+        "auipc a0,0",
+        "ld a0,32(a0)",
+        "auipc a1,0",
+        "ld a1,32(a1)",
+        "auipc a3,0",
+        "ld a3,16(a3)",
+        "jalr zero,0(ra)",
+    ]
+    .iter()
+    .flat_map(|line| assemble_line(line).unwrap().encode_bytes())
+    .collect();
+    assert_eq!(text.data[..28], expected);
+    // the pool is aligned to 8 bytes, entries with the same value are shared and symbols are relocated
+    assert_eq!(text.data[28..32], [0; 4]);
+    assert_eq!(text.data[32..40], 0x123456789abcdef0u64.to_le_bytes());
+    assert_eq!(text.data[40..48], [0; 8]);
+    assert_eq!(
+        text.relocations,
+        [Relocation {
+            offset: 40,
+            kind: RelocationKind::Abs64,
+            symbol: "table".to_owned(),
+            addend: 8,
+        }]
+    );
+    // the second pool is at the end of the section
+    assert_eq!(
+        text.data[48..56],
+        [
+            assemble_line("auipc a2,0").unwrap().encode_bytes(),
+            assemble_line("ld a2,8(a2)").unwrap().encode_bytes()
+        ]
+        .concat()
+    );
+    assert_eq!(text.data[56..], 0x123456789abcdef0u64.to_le_bytes());
+    // the section is aligned like its pools, so the entries stay aligned once it is linked
+    assert_eq!(text.align, 8);
+    let data = program.to_elf();
+    let elf = riscv_codec::elf::Elf::parse(&data).unwrap();
+    assert_eq!(elf.section(".text").unwrap().align, 8);
+
+    let rv32 = AssemblerConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+    let program = assemble_program("li a0,=-1\nli a1,=printf", &rv32).unwrap();
+    let text = program.section(".text").unwrap();
+    assert_eq!(text.data[16..], [0xff, 0xff, 0xff, 0xff, 0, 0, 0, 0]);
+    assert_eq!(text.relocations[0].kind, RelocationKind::Abs32);
+    assert_eq!(text.align, 4);
+    assert_eq!(program.symbol("printf").unwrap().binding, Binding::Global);
+    assert_eq!(
+        assemble_program("li a0,=0x100000000", &rv32),
+        Err("literal pool of .text: 4294967296 does not fit in 4 bytes".to_owned())
+    );
+}
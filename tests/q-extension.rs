@@ -0,0 +1,167 @@
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    config::{DecoderConfig, IsaString},
+    instruction::{Instruction, RoundingMode},
+    register::{FRegister, IRegister},
+};
+
+const ISA: &str = "rv64gcq";
+
+#[test]
+fn fadd_q() {
+    let (decoder, assembler) = common::isa(ISA);
+    let bin = 0x06c5f553;
+    let expected = Instruction::FADDQ {
+        dest: FRegister::FA0,
+        src1: FRegister::FA1,
+        src2: FRegister::FA2,
+        rm: RoundingMode::DYN,
+    };
+
+    // check assembler
+    let i = assemble_line_with("fadd.q fa0,fa1,fa2", &assembler)
+        .unwrap()
+        .i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "fadd.q fa0,fa1,fa2");
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x0105c507, "flq fa0,16(a1)"),
+            (0x00a5c827, "fsq fa0,16(a1)"),
+            (0x6ec5f543, "fmadd.q fa0,fa1,fa2,fa3"),
+            (0x6ec5f547, "fmsub.q fa0,fa1,fa2,fa3"),
+            (0x6ec5f54b, "fnmsub.q fa0,fa1,fa2,fa3"),
+            (0x6ec5f54f, "fnmadd.q fa0,fa1,fa2,fa3"),
+            (0x0ec5f553, "fsub.q fa0,fa1,fa2"),
+            (0x16c58553, "fmul.q.rne fa0,fa1,fa2"),
+            (0x1ec5f553, "fdiv.q fa0,fa1,fa2"),
+            (0x5e05f553, "fsqrt.q fa0,fa1"),
+            (0x26c58553, "fsgnj.q fa0,fa1,fa2"),
+            (0x26c59553, "fsgnjn.q fa0,fa1,fa2"),
+            (0x26c5a553, "fsgnjx.q fa0,fa1,fa2"),
+            (0x2ec58553, "fmin.q fa0,fa1,fa2"),
+            (0x2ec59553, "fmax.q fa0,fa1,fa2"),
+            (0x4035f553, "fcvt.s.q fa0,fa1"),
+            (0x4605f553, "fcvt.q.s fa0,fa1"),
+            (0x4235f553, "fcvt.d.q fa0,fa1"),
+            (0x4615f553, "fcvt.q.d fa0,fa1"),
+            (0xa6c5a553, "feq.q a0,fa1,fa2"),
+            (0xa6c59553, "flt.q a0,fa1,fa2"),
+            (0xa6c58553, "fle.q a0,fa1,fa2"),
+            (0xe6059553, "fclass.q a0,fa1"),
+            (0xc605f553, "fcvt.w.q a0,fa1"),
+            (0xc615f553, "fcvt.wu.q a0,fa1"),
+            (0xc6259553, "fcvt.l.q.rtz a0,fa1"),
+            (0xc635f553, "fcvt.lu.q a0,fa1"),
+            (0xd605f553, "fcvt.q.w fa0,a1"),
+            (0xd615f553, "fcvt.q.wu fa0,a1"),
+            (0xd625f553, "fcvt.q.l fa0,a1"),
+            (0xd635f553, "fcvt.q.lu fa0,a1"),
+        ],
+    );
+    // the rounding mode may also be given as an operand
+    assert_eq!(
+        assemble_line_with("fmadd.q fa0,fa1,fa2,fa3,rtz", &assembler)
+            .unwrap()
+            .i(),
+        Instruction::FMADDQ {
+            dest: FRegister::FA0,
+            src1: FRegister::FA1,
+            src2: FRegister::FA2,
+            src3: FRegister::FA3,
+            rm: RoundingMode::RTZ,
+        }
+    );
+    assert_eq!(
+        assemble_line_with("fcvt.l.q a0,fa1,rtz", &assembler)
+            .unwrap()
+            .i(),
+        Instruction::FCVTLQ {
+            dest: IRegister::A0,
+            src: FRegister::FA1,
+            rm: RoundingMode::RTZ,
+        }
+    );
+}
+
+#[test]
+fn malformed() {
+    let (decoder, assembler) = common::isa(ISA);
+    // fsqrt.q and fclass.q with rs2 set
+    let strict = DecoderConfig {
+        strict: true,
+        ..decoder
+    };
+    assert!(Instruction::decode_with(0x5e15f553, &strict).is_err());
+    assert!(Instruction::decode_with(0xe6159553, &strict).is_err());
+    // fmin.q with an unknown funct3
+    assert!(Instruction::decode_with(0x2ec5a553, &decoder).is_err());
+    common::assemble_errors(
+        &assembler,
+        &[
+            "fmin.q.rne fa0,fa1,fa2",
+            "fadd.q.rne.rtz fa0,fa1,fa2",
+            "fadd.q fa0,fa1",
+            "fmadd.q fa0,fa1,fa2",
+            "fcvt.q.q fa0,fa1",
+        ],
+    );
+}
+
+#[test]
+fn extension_requirements() {
+    // Q is not part of G, so the default configuration rejects it
+    assert_eq!(
+        Instruction::decode_with(0x06c5f553, &DecoderConfig::default()).map_err(String::from),
+        Err(
+            "unsupported instruction fadd.q fa0,fa1,fa2, which requires the Q extension".to_owned()
+        )
+    );
+    assert!(assemble_line("flq fa0,0(a0)").is_err());
+    assert!(assemble_line("fadd.s fa0,fa1,fa2").is_ok());
+
+    // the conversions from quad precision have the fmt of the result
+    let (quad, quad_asm) = common::isa(ISA);
+    for (bin, line) in [
+        (0x4035f553, "fcvt.s.q fa0,fa1"),
+        (0x4235f553, "fcvt.d.q fa0,fa1"),
+    ] {
+        assert_eq!(
+            Instruction::decode(bin).map_err(String::from),
+            Err(format!(
+                "unsupported instruction {line}, which requires the Q extension"
+            ))
+        );
+        assert!(assemble_line(line).is_err(), "{line}");
+        assert_eq!(
+            Instruction::decode_with(bin, &quad).unwrap().to_string(),
+            line
+        );
+        assert!(assemble_line_with(line, &quad_asm).is_ok(), "{line}");
+    }
+
+    // q implies d, which implies f
+    let isa = IsaString::try_from("rv64iq").unwrap();
+    assert!(isa.extensions.q && isa.extensions.d && isa.extensions.f);
+    assert_eq!(isa.to_string(), "rv64ifdq_zicsr");
+    let mut extensions = isa.extensions;
+    extensions.set("d", false);
+    assert!(!extensions.q);
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "rv128")]
+
+mod common;
+
+use riscv_codec::{
+    assembly::assemble_line_with,
+    cinstruction::CInstruction,
+    config::{AssemblerConfig, Xlen},
+    immediates::IImmediate,
+    instruction::Instruction,
+    packed::PackedInst,
+    register::IRegister,
+};
+
+#[test]
+fn lq() {
+    let (decoder, assembler) = common::xlen(Xlen::Rv128);
+    let bin = 0x0105a50f;
+    let expected = Instruction::LQ {
+        dest: IRegister::A0,
+        base: IRegister::A1,
+        offset: IImmediate::try_from(16).unwrap(),
+    };
+
+    // check assembler
+    let i = assemble_line_with("lq a0,16(a1)", &assembler).unwrap().i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "lq a0,16(a1)");
+
+    // the packed form decodes it again on RV128
+    assert_eq!(
+        PackedInst::decode_with(bin, &decoder).unwrap().expand(),
+        expected
+    );
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::xlen(Xlen::Rv128);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x0105f503, "ldu a0,16(a1)"),
+            (0x00a5c823, "sq a0,16(a1)"),
+            (0x0015855b, "addid a0,a1,1"),
+            (0x03f5955b, "sllid a0,a1,63"),
+            (0x0035d55b, "srlid a0,a1,3"),
+            (0x4035d55b, "sraid a0,a1,3"),
+            (0x00c5857b, "addd a0,a1,a2"),
+            (0x40c5857b, "subd a0,a1,a2"),
+            (0x00c5957b, "slld a0,a1,a2"),
+            (0x00c5d57b, "srld a0,a1,a2"),
+            (0x40c5d57b, "srad a0,a1,a2"),
+            // the RV64 instructions remain
+            (0x0015851b, "addiw a0,a1,1"),
+            (0x0005b503, "ld a0,0(a1)"),
+        ],
+    );
+}
+
+#[test]
+fn only_on_rv128() {
+    let rv64_asm = AssemblerConfig::default();
+    for (bin, line) in [
+        (0x0105f503, "ldu a0,16(a1)"),
+        (0x00a5c823, "sq a0,16(a1)"),
+        (0x40c5857b, "subd a0,a1,a2"),
+        (0x40c5d57b, "srad a0,a1,a2"),
+    ] {
+        assert_eq!(
+            Instruction::decode(bin).map_err(String::from),
+            Err(format!(
+                "unsupported instruction {line}, which is only available on RV128"
+            ))
+        );
+        let mnemonic = line.split_once(' ').unwrap().0;
+        assert_eq!(
+            assemble_line_with(line, &rv64_asm).map(|_| ()),
+            Err(format!("{mnemonic} is only available on RV128"))
+        );
+    }
+    // OP-IMM-64 is the custom-2 opcode elsewhere, which XAndesPerf uses
+    assert_eq!(
+        assemble_line_with("addid a0,a1,1", &rv64_asm).map(|_| ()),
+        Err("addid is only available on RV128".to_owned())
+    );
+    // the encoding of lq is cbo.inval elsewhere
+    assert_eq!(
+        Instruction::decode(0x0005200f).map_err(String::from),
+        Err(
+            "unsupported instruction cbo.inval (a0), which requires the Zicbom extension"
+                .to_owned()
+        )
+    );
+    assert!(
+        assemble_line_with("lq a0,16(a1)", &rv64_asm)
+            .unwrap_err()
+            .ends_with("is only available on RV128")
+    );
+}
+
+#[test]
+fn compressed_quad_loads_and_stores() {
+    let (decoder, assembler) = common::xlen(Xlen::Rv128);
+    // c.fld fa0,0(a1), c.fsd fa0,0(a1), c.fldsp fa0,0 and c.fsdsp fa0,0
+    for bin in [0x2188, 0xa188, 0x2502, 0xa02a] {
+        assert!(CInstruction::decode_with(bin, &decoder).is_err());
+        assert!(CInstruction::decode(bin).is_ok());
+    }
+    assert_eq!(
+        assemble_line_with("c.fld fa0,0(a1)", &assembler).map(|_| ()),
+        Err(
+            "c.fld is not available on RV128, which uses its encoding for the quad loads and stores"
+                .to_owned()
+        )
+    );
+    assert!(assemble_line_with("c.flw fa0,0(a1)", &assembler).is_err());
+    assert!(assemble_line_with("c.ld a0,0(a1)", &assembler).is_ok());
+}
@@ -0,0 +1,78 @@
+use riscv_codec::{
+    cinstruction::DecodedCInstruction,
+    config::{DecoderConfig, Xlen},
+    instruction::DecodedInstruction,
+    scan::{Classification, Counts, classify, classify_compressed, scan_compressed, scan_words},
+};
+
+#[test]
+fn classify_encodings() {
+    let config = DecoderConfig::default();
+    assert_eq!(classify(0x00000013, &config), Classification::Decoded);
+    // add with an unknown func7
+    assert_eq!(classify(0xfe000033, &config), Classification::Invalid);
+    // fence with a reserved fm
+    assert_eq!(classify(0x1000000f, &config), Classification::Reserved);
+    assert_eq!(
+        classify_compressed(0x4501, &config),
+        Classification::Decoded
+    );
+    // the all zero halfword
+    assert_eq!(classify_compressed(0, &config), Classification::Reserved);
+}
+
+#[test]
+fn scan_encoding_space() {
+    let config = DecoderConfig::default();
+    let summary = scan_compressed(&config);
+    let key = |funct3: u8, quadrant: u8| funct3 << 2 | quadrant;
+    assert_eq!(summary.total().total(), 49152);
+    // the reserved first entry of quadrant 0 funct3=100
+    assert_eq!(
+        summary.opcodes[&key(0b100, 0)],
+        Counts {
+            decoded: 0,
+            reserved: 2048,
+            invalid: 0
+        }
+    );
+    // c.jr x0 is the only reserved encoding of quadrant 2 funct3=100
+    assert_eq!(summary.opcodes[&key(0b100, 2)].reserved, 1);
+    assert!(summary.to_string().contains("q2 funct3=100"));
+
+    // c.addw and c.subw are reserved on RV32
+    let rv32 = DecoderConfig {
+        xlen: Xlen::Rv32,
+        ..Default::default()
+    };
+    assert!(
+        scan_compressed(&rv32).opcodes[&key(0b100, 1)].reserved
+            > summary.opcodes[&key(0b100, 1)].reserved
+    );
+
+    // every lui decodes, words that are not 32-bit encodings are skipped
+    let summary = scan_words((0..1 << 12).map(|w| w << 20 | 0b0110111), &config);
+    assert_eq!(summary.opcodes.len(), 1);
+    assert_eq!(summary.opcodes[&0b0110111].decoded, 4096);
+    assert!(scan_words([0, 1, 2], &config).opcodes.is_empty());
+}
+
+#[test]
+fn lossless_round_trip() {
+    for xlen in [Xlen::Rv32, Xlen::Rv64] {
+        let config = DecoderConfig {
+            xlen,
+            ..Default::default()
+        };
+        for word in (0..1u32 << 30).step_by(4099).map(|w| w << 2 | 0b11) {
+            if let Ok(decoded) = DecodedInstruction::decode_lossless(word, &config) {
+                assert_eq!(decoded.encode(), word, "{decoded}");
+            }
+        }
+        for half in (0..=u16::MAX).filter(|h| h & 0b11 != 0b11) {
+            if let Ok(decoded) = DecodedCInstruction::decode_lossless(half, &config) {
+                assert_eq!(decoded.encode(), half, "{decoded}");
+            }
+        }
+    }
+}
@@ -0,0 +1,400 @@
+#![cfg(feature = "interp")]
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    config::{AssemblerConfig, Extensions},
+    instruction::Instruction,
+    interp::{Event, Hart, Memory},
+    register::IRegister,
+    semantics::{Effect, Expr, Width},
+};
+
+const BASE: u64 = 0x1000;
+const DATA: u64 = 0x1800;
+
+fn eval(expr: &Expr, hart: &Hart) -> u64 {
+    let binary = |a: &Expr, b: &Expr| (eval(a, hart), eval(b, hart));
+    let extend = |width: Width, value: u64, signed: bool| {
+        let shift = 64 - width.bits();
+        if signed {
+            (((value << shift) as i64) >> shift) as u64
+        } else {
+            (value << shift) >> shift
+        }
+    };
+    match expr {
+        Expr::Imm(x) => *x as u64,
+        Expr::Read(r) => hart.read(*r),
+        Expr::Pc => hart.pc,
+        Expr::Load(width, addr) => hart
+            .memory
+            .load(eval(addr, hart), width.bits() as usize / 8)
+            .unwrap(),
+        Expr::SignExtend(width, x) => extend(*width, eval(x, hart), true),
+        Expr::ZeroExtend(width, x) => extend(*width, eval(x, hart), false),
+        Expr::Add(a, b) => {
+            let (a, b) = binary(a, b);
+            a.wrapping_add(b)
+        }
+        Expr::Sub(a, b) => {
+            let (a, b) = binary(a, b);
+            a.wrapping_sub(b)
+        }
+        Expr::And(a, b) => {
+            let (a, b) = binary(a, b);
+            a & b
+        }
+        Expr::Or(a, b) => {
+            let (a, b) = binary(a, b);
+            a | b
+        }
+        Expr::Xor(a, b) => {
+            let (a, b) = binary(a, b);
+            a ^ b
+        }
+        Expr::Shl(a, b) => {
+            let (a, b) = binary(a, b);
+            a << b
+        }
+        Expr::Shr(a, b) => {
+            let (a, b) = binary(a, b);
+            a >> b
+        }
+        Expr::Sra(a, b) => {
+            let (a, b) = binary(a, b);
+            ((a as i64) >> b) as u64
+        }
+        Expr::Eq(a, b) => {
+            let (a, b) = binary(a, b);
+            (a == b) as u64
+        }
+        Expr::Ne(a, b) => {
+            let (a, b) = binary(a, b);
+            (a != b) as u64
+        }
+        Expr::Lt(a, b) => {
+            let (a, b) = binary(a, b);
+            ((a as i64) < b as i64) as u64
+        }
+        Expr::Ltu(a, b) => {
+            let (a, b) = binary(a, b);
+            (a < b) as u64
+        }
+        Expr::Ge(a, b) => {
+            let (a, b) = binary(a, b);
+            (a as i64 >= b as i64) as u64
+        }
+        Expr::Geu(a, b) => {
+            let (a, b) = binary(a, b);
+            (a >= b) as u64
+        }
+        Expr::Mul(a, b) => {
+            let (a, b) = binary(a, b);
+            a.wrapping_mul(b)
+        }
+        Expr::MulH(a, b) => {
+            let (a, b) = binary(a, b);
+            ((a as i64 as i128 * b as i64 as i128) >> 64) as u64
+        }
+        Expr::MulHSU(a, b) => {
+            let (a, b) = binary(a, b);
+            ((a as i64 as i128 * b as i128) >> 64) as u64
+        }
+        Expr::MulHU(a, b) => {
+            let (a, b) = binary(a, b);
+            ((a as u128 * b as u128) >> 64) as u64
+        }
+        Expr::Div(a, b) => {
+            let (a, b) = binary(a, b);
+            if b == 0 {
+                u64::MAX
+            } else {
+                (a as i64).wrapping_div(b as i64) as u64
+            }
+        }
+        Expr::DivU(a, b) => {
+            let (a, b) = binary(a, b);
+            a.checked_div(b).unwrap_or(u64::MAX)
+        }
+        Expr::Rem(a, b) => {
+            let (a, b) = binary(a, b);
+            if b == 0 {
+                a
+            } else {
+                (a as i64).wrapping_rem(b as i64) as u64
+            }
+        }
+        Expr::RemU(a, b) => {
+            let (a, b) = binary(a, b);
+            a.checked_rem(b).unwrap_or(a)
+        }
+        Expr::Min(a, b) => {
+            let (a, b) = binary(a, b);
+            (a as i64).min(b as i64) as u64
+        }
+        Expr::Max(a, b) => {
+            let (a, b) = binary(a, b);
+            (a as i64).max(b as i64) as u64
+        }
+        Expr::MinU(a, b) => {
+            let (a, b) = binary(a, b);
+            a.min(b)
+        }
+        Expr::MaxU(a, b) => {
+            let (a, b) = binary(a, b);
+            a.max(b)
+        }
+    }
+}
+
+/// Applies effects to a copy of the hart, evaluating every expression against the original state.
+fn apply(effects: &[Effect], before: &Hart, after: &mut Hart) {
+    for effect in effects {
+        match effect {
+            Effect::Write(r, value) => after.write(*r, eval(value, before)),
+            Effect::Store { width, addr, value } => after
+                .memory
+                .store(
+                    eval(addr, before),
+                    width.bits() as usize / 8,
+                    eval(value, before),
+                )
+                .unwrap(),
+            Effect::Jump(target) => after.pc = eval(target, before),
+            Effect::If(condition, effect) => {
+                if eval(condition, before) != 0 {
+                    apply(std::slice::from_ref(effect), before, after);
+                }
+            }
+            effect => panic!("unexpected effect {effect:?}"),
+        }
+    }
+}
+
+/// Checks that the semantics of an instruction agree with the interpreter for several register values.
+fn check(line: &str) {
+    let config = AssemblerConfig {
+        extensions: Extensions {
+            zabha: true,
+            zbkb: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let instruction = assemble_line_with(line, &config).unwrap().i();
+    let effects = instruction.semantics().unwrap();
+    let values: [u64; 6] = [0, 1, 7, u64::MAX, 1 << 63, 0xffff_ffff_8000_0001];
+    for a in values {
+        for b in values {
+            let mut hart = Hart::new(Memory::new(BASE, 0x1000));
+            hart.write(IRegister::A1, a);
+            hart.write(IRegister::A2, b);
+            hart.write(IRegister::A3, DATA);
+            hart.memory
+                .store(DATA, 8, a ^ 0x1234_5678_9abc_def0)
+                .unwrap();
+
+            let mut expected = hart.clone();
+            assert_eq!(
+                expected.execute(&instruction, 4),
+                Ok(Event::Continue),
+                "{line}"
+            );
+
+            let mut actual = hart.clone();
+            actual.pc += 4;
+            apply(&effects, &hart, &mut actual);
+            assert_eq!(actual, expected, "{line} with a1={a:#x} a2={b:#x}");
+        }
+    }
+}
+
+#[test]
+fn base_semantics() {
+    for line in [
+        "lui a0,0x80000",
+        "auipc a0,-1",
+        "jal a1,-16",
+        "jalr a1,3(a1)",
+        "beq a1,a2,16",
+        "bne a1,a2,16",
+        "blt a1,a2,16",
+        "bge a1,a2,16",
+        "bltu a1,a2,16",
+        "bgeu a1,a2,16",
+        "lb a0,3(a3)",
+        "lh a0,2(a3)",
+        "lw a0,4(a3)",
+        "ld a0,0(a3)",
+        "lbu a0,3(a3)",
+        "lhu a0,6(a3)",
+        "lwu a0,4(a3)",
+        "sb a1,1(a3)",
+        "sh a1,2(a3)",
+        "sw a1,4(a3)",
+        "sd a1,8(a3)",
+        "addi a0,a1,-5",
+        "slti a0,a1,-1",
+        "sltiu a0,a1,-1",
+        "xori a0,a1,-1",
+        "ori a0,a1,0x70",
+        "andi a0,a1,-16",
+        "slli a0,a1,63",
+        "srli a0,a1,33",
+        "srai a0,a1,31",
+        "add a0,a1,a2",
+        "sub a0,a1,a2",
+        "sll a0,a1,a2",
+        "slt a0,a1,a2",
+        "sltu a0,a1,a2",
+        "xor a0,a1,a2",
+        "srl a0,a1,a2",
+        "sra a0,a1,a2",
+        "or a0,a1,a2",
+        "and a0,a1,a2",
+        "addiw a0,a1,-1",
+        "slliw a0,a1,31",
+        "srliw a0,a1,1",
+        "sraiw a0,a1,1",
+        "addw a0,a1,a2",
+        "subw a0,a1,a2",
+        "sllw a0,a1,a2",
+        "srlw a0,a1,a2",
+        "sraw a0,a1,a2",
+    ] {
+        check(line);
+    }
+}
+
+#[test]
+fn multiply_semantics() {
+    for line in [
+        "mul a0,a1,a2",
+        "mulh a0,a1,a2",
+        "mulhsu a0,a1,a2",
+        "mulhu a0,a1,a2",
+        "div a0,a1,a2",
+        "divu a0,a1,a2",
+        "rem a0,a1,a2",
+        "remu a0,a1,a2",
+        "mulw a0,a1,a2",
+        "divw a0,a1,a2",
+        "divuw a0,a1,a2",
+        "remw a0,a1,a2",
+        "remuw a0,a1,a2",
+    ] {
+        check(line);
+    }
+}
+
+#[test]
+fn atomic_semantics() {
+    for line in [
+        "amoswap.w a0,a3,a2",
+        "amoadd.w a0,a3,a2",
+        "amoxor.w a0,a3,a2",
+        "amoand.w a0,a3,a2",
+        "amoor.w a0,a3,a2",
+        "amomin.w a0,a3,a2",
+        "amomax.w a0,a3,a2",
+        "amominu.w a0,a3,a2",
+        "amomaxu.w a0,a3,a2",
+        "amoswap.d a0,a3,a2",
+        "amoadd.d a0,a3,a2",
+        "amoxor.d a0,a3,a2",
+        "amoand.d a0,a3,a2",
+        "amoor.d a0,a3,a2",
+        "amomin.d a0,a3,a2",
+        "amomax.d a0,a3,a2",
+        "amominu.d.aqrl a0,a3,a2",
+        "amomaxu.d a0,a3,a2",
+        "amoswap.b a0,a3,a2",
+        "amoadd.h a0,a3,a2",
+        "amoxor.b a0,a3,a2",
+        "amoand.h a0,a3,a2",
+        "amoor.b a0,a3,a2",
+        "amomin.b a0,a3,a2",
+        "amomax.h a0,a3,a2",
+        "amominu.b a0,a3,a2",
+        "amomaxu.h.aq a0,a3,a2",
+        "amocas.b a0,a3,a2",
+        "amocas.h a1,a3,a2",
+    ] {
+        check(line);
+    }
+
+    let i = assemble_line("lr.w a0,a3").unwrap().i();
+    assert_eq!(
+        i.semantics(),
+        Some(vec![
+            Effect::Write(
+                IRegister::A0,
+                Expr::SignExtend(
+                    Width::Word,
+                    Box::new(Expr::Load(Width::Word, Box::new(Expr::Read(IRegister::A3))))
+                )
+            ),
+            Effect::Reserve(Width::Word, Expr::Read(IRegister::A3)),
+        ])
+    );
+    let i = assemble_line("sc.d a0,a3,a1").unwrap().i();
+    assert_eq!(
+        i.semantics(),
+        Some(vec![Effect::StoreConditional {
+            dest: IRegister::A0,
+            width: Width::Double,
+            addr: Expr::Read(IRegister::A3),
+            value: Expr::Read(IRegister::A1),
+        }])
+    );
+}
+
+#[test]
+fn bit_manipulation_semantics() {
+    for line in [
+        "pack a0,a1,a2",
+        "packh a0,a1,a2",
+        "packw a0,a1,a2",
+        "andn a0,a1,a2",
+        "orn a0,a1,a2",
+        "xnor a0,a1,a2",
+        "rol a0,a1,a2",
+        "ror a0,a1,a2",
+        "rori a0,a1,0",
+        "rori a0,a1,63",
+        "rolw a0,a1,a2",
+        "rorw a0,a1,a2",
+        "roriw a0,a1,0",
+        "roriw a0,a1,31",
+    ] {
+        check(line);
+    }
+}
+
+#[test]
+fn other_semantics() {
+    let i = assemble_line("addi t0,t1,1024").unwrap().i();
+    assert_eq!(
+        i.semantics(),
+        Some(vec![Effect::Write(
+            IRegister::T0,
+            Expr::Add(
+                Box::new(Expr::Read(IRegister::T1)),
+                Box::new(Expr::Imm(1024))
+            )
+        )])
+    );
+    assert_eq!(Instruction::ECALL.semantics(), Some(vec![Effect::Ecall]));
+    assert_eq!(Instruction::FENCEI.semantics(), Some(vec![Effect::FenceI]));
+    let i = assemble_line("fence rw,w").unwrap().i();
+    assert_eq!(
+        i.semantics(),
+        Some(vec![Effect::Fence {
+            predecessor: 0b0011,
+            successor: 0b0001,
+        }])
+    );
+    // the F extension is not described
+    let i = assemble_line("fadd.s fa0,fa1,fa2").unwrap().i();
+    assert_eq!(i.semantics(), None);
+}
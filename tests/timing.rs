@@ -0,0 +1,32 @@
+use riscv_codec::{assembly::assemble_line, timing::ExecClass};
+
+#[test]
+fn execution_classes() {
+    let class = |line: &str| assemble_line(line).unwrap().expand().unwrap().exec_class();
+    for (line, expected) in [
+        ("add a0,a1,a2", ExecClass::Alu),
+        ("lui a0,1", ExecClass::Alu),
+        ("mulhu a0,a1,a2", ExecClass::Mul),
+        ("remw a0,a1,a2", ExecClass::Div),
+        ("lbu a0,0(a1)", ExecClass::Load),
+        ("flw fa0,4(sp)", ExecClass::Load),
+        ("c.sdsp ra,8", ExecClass::Store),
+        ("jalr zero,0(ra)", ExecClass::Branch),
+        ("bgeu a0,a1,8", ExecClass::Branch),
+        ("fmul.s fa0,fa1,fa2", ExecClass::Fpu),
+        ("fcvt.w.s a0,fa0", ExecClass::Fpu),
+        ("csrrs a0,0xc00,zero", ExecClass::Csr),
+        ("amoadd.w a0,a1,a2", ExecClass::Amo),
+        ("lr.d a0,a1", ExecClass::Amo),
+        ("fence.i", ExecClass::System),
+        ("c.ebreak", ExecClass::System),
+    ] {
+        assert_eq!(class(line), expected, "{line}");
+    }
+
+    let latency = |line: &str| assemble_line(line).unwrap().expand().unwrap().latency();
+    assert_eq!(latency("addi a0,a0,1"), 1);
+    assert_eq!(latency("ld a0,0(sp)"), ExecClass::Load.latency());
+    assert_eq!(latency("div a0,a1,a2"), latency("fdiv.s fa0,fa1,fa2"));
+    assert!(latency("fadd.s fa0,fa1,fa2") < latency("fsqrt.s fa0,fa1"));
+}
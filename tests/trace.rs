@@ -0,0 +1,131 @@
+use riscv_codec::{
+    analysis::Symbol,
+    assembly::assemble_line,
+    config::{DecoderConfig, FormatOptions},
+    instruction::Instruction,
+    trace::{BranchTrace, TraceEntry, format_trace, parse_trace, reconstruct},
+};
+
+#[test]
+fn parse_traces() {
+    let csv = "pc,instruction\n0x1000,0x00100513\n1004,4501\n\n# done\n";
+    assert_eq!(
+        parse_trace(csv).unwrap(),
+        vec![
+            TraceEntry {
+                pc: 0x1000,
+                raw: 0x00100513
+            },
+            TraceEntry {
+                pc: 0x1004,
+                raw: 0x4501
+            }
+        ]
+    );
+    assert!(parse_trace("0x1000,0x13\n0x1004\n").is_err());
+
+    let spike = "core   0: 0x0000000000001000 (0x00000297) auipc   t0, 0x0
+core   0: >>>>  main
+core   0: 3 0x0000000000001004 (0x8082) x0 0x0000000000000000
+";
+    assert_eq!(
+        parse_trace(spike).unwrap(),
+        vec![
+            TraceEntry {
+                pc: 0x1000,
+                raw: 0x00000297
+            },
+            TraceEntry {
+                pc: 0x1004,
+                raw: 0x8082
+            }
+        ]
+    );
+}
+
+#[test]
+fn format_traces() {
+    let trace = "0x1000,0x00100513
+0x1004,0x00050463
+0x1008,0x008000ef
+0x1010,0x00008067
+0x100c,0xfe051ae3
+0x1010,0x0000
+";
+    let symbols = vec![
+        Symbol {
+            address: 0x1000,
+            name: "main".to_owned(),
+        },
+        Symbol {
+            address: 0x1010,
+            name: "f".to_owned(),
+        },
+    ];
+    let options = FormatOptions {
+        pseudo_instructions: true,
+        ..Default::default()
+    };
+    let listing = format_trace(
+        &parse_trace(trace).unwrap(),
+        &symbols,
+        &DecoderConfig::default(),
+        &options,
+    );
+    assert_eq!(
+        listing,
+        "    1000:\t00100513\tli a0,1
+    1004:\t00050463\tbeqz a0,8\t# 100c <main+0xc> (not taken)
+    1008:\t008000ef\tjal 8\t# 1010 <f>
+    1010:\t00008067\tret\t# 100c <main+0xc>
+    100c:\tfe051ae3\tbnez a0,-12\t# 1000 <main> (not taken)
+    1010:\t0000    \t.2byte 0x0
+"
+    );
+}
+
+#[test]
+fn reconstruct_branch_trace() {
+    let image: Vec<u8> = [
+        "addi a0,zero,2",
+        "addi a0,a0,-1",
+        "bne a0,zero,-4",
+        "jal ra,12",
+        "addi a1,zero,1",
+        "beq a0,zero,-20",
+        "jalr zero,0(ra)",
+    ]
+    .iter()
+    .flat_map(|line| Instruction::encode(&assemble_line(line).unwrap().i()).to_le_bytes())
+    .collect();
+    let config = DecoderConfig::default();
+    let trace = BranchTrace {
+        taken: vec![true, false],
+        targets: vec![0x1010],
+    };
+    let entries = reconstruct(&image, 0x1000, 0x1000, &trace, &config, 100).unwrap();
+    assert_eq!(
+        entries.iter().map(|e| e.pc).collect::<Vec<_>>(),
+        vec![
+            0x1000, 0x1004, 0x1008, 0x1004, 0x1008, 0x100c, 0x1018, 0x1010
+        ]
+    );
+    assert_eq!(entries[6].raw, 0x00008067);
+
+    let trace = BranchTrace {
+        taken: vec![true],
+        targets: vec![0x1010],
+    };
+    assert!(reconstruct(&image, 0x1000, 0x1000, &trace, &config, 100).is_err());
+    let trace = BranchTrace {
+        taken: vec![true, false],
+        targets: vec![0x3000],
+    };
+    assert!(reconstruct(&image, 0x1000, 0x1000, &trace, &config, 100).is_err());
+    // an infinite loop
+    let trace = BranchTrace {
+        taken: vec![true; 1000],
+        targets: vec![],
+    };
+    assert!(reconstruct(&image, 0x1000, 0x1000, &trace, &config, 100).is_err());
+}
@@ -0,0 +1,464 @@
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    config::{DecoderConfig, FormatOptions, ImmediateStyle, IsaString, RegisterStyle},
+    immediates::{Lmul, VImmediate, VType, VTypeImmediate},
+    instruction::Instruction,
+    register::{IRegister, VRegister},
+    vector::{VOperand, VectorAddressing, VectorInstruction, VectorOp},
+};
+
+const ISA: &str = "rv64gcv";
+
+#[test]
+fn vadd_vx() {
+    let (decoder, assembler) = common::isa(ISA);
+    let bin = 0x022540d7;
+    let expected = Instruction::Vector(VectorInstruction::Binary {
+        op: VectorOp::VADD,
+        dest: VRegister::V1,
+        src2: VRegister::V2,
+        src1: VOperand::Scalar(IRegister::A0),
+        masked: false,
+    });
+
+    // check assembler
+    let i = assemble_line_with("vadd.vx v1,v2,a0", &assembler)
+        .unwrap()
+        .i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "vadd.vx v1,v2,a0");
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x022180d7, "vadd.vv v1,v2,v3"),
+            (0x022db0d7, "vadd.vi v1,v2,-5"),
+            (0x0a2540d7, "vsub.vx v1,v2,a0"),
+            (0x0e2db0d7, "vrsub.vi v1,v2,-5"),
+            (0x322eb0d7, "vrgather.vi v1,v2,29"),
+            (0x3a2540d7, "vslideup.vx v1,v2,a0"),
+            (0x462db0d7, "vmadc.vi v1,v2,-5"),
+            (0x7a2db0d7, "vmsgtu.vi v1,v2,-5"),
+            (0x962eb0d7, "vsll.vi v1,v2,29"),
+            (0xb22eb0d7, "vnsrl.wi v1,v2,29"),
+            (0xbe2540d7, "vnclip.wx v1,v2,a0"),
+            (0xc62180d7, "vwredsum.vs v1,v2,v3"),
+            (0x0221a0d7, "vredsum.vs v1,v2,v3"),
+            (0x2621a0d7, "vaadd.vv v1,v2,v3"),
+            (0x5e21a0d7, "vcompress.vm v1,v2,v3"),
+            (0x6621a0d7, "vmand.mm v1,v2,v3"),
+            (0x822560d7, "vdivu.vx v1,v2,a0"),
+            (0x9621a0d7, "vmul.vv v1,v2,v3"),
+            (0xb62560d7, "vmacc.vx v1,a0,v2"),
+            (0xc621a0d7, "vwadd.vv v1,v2,v3"),
+            (0xd62560d7, "vwadd.wx v1,v2,a0"),
+            (0xfa2560d7, "vwmaccus.vx v1,a0,v2"),
+            (0x022550d7, "vfadd.vf v1,v2,fa0"),
+            (0x0e2190d7, "vfredosum.vs v1,v2,v3"),
+            (0x3e2550d7, "vfslide1down.vf v1,v2,fa0"),
+            (0x7e2550d7, "vmfge.vf v1,v2,fa0"),
+            (0xb22190d7, "vfmacc.vv v1,v3,v2"),
+            (0xd22550d7, "vfwadd.wf v1,v2,fa0"),
+            (0x4a2320d7, "vzext.vf2 v1,v2"),
+            (0x522820d7, "viota.m v1,v2"),
+            (0x4a2590d7, "vfwcvt.f.x.v v1,v2"),
+            (0x4a2a90d7, "vfncvt.rod.f.f.w v1,v2"),
+            (0x4e2010d7, "vfsqrt.v v1,v2"),
+            (0x5208a0d7, "vid.v v1"),
+            (0x42202557, "vmv.x.s a0,v2"),
+            (0x42282557, "vcpop.m a0,v2"),
+            (0x42201557, "vfmv.f.s fa0,v2"),
+            (0x420560d7, "vmv.s.x v1,a0"),
+            (0x420550d7, "vfmv.s.f v1,fa0"),
+            (0x5e0db0d7, "vmv.v.i v1,-5"),
+            (0x5e0550d7, "vfmv.v.f v1,fa0"),
+            (0x9e81b257, "vmv4r.v v4,v8"),
+        ],
+    );
+}
+
+#[test]
+fn operands() {
+    // vector registers are printed the same way in every style, the others follow the options
+    let i = assemble_line_with("vmacc.vx v8,s0,v16", &common::isa(ISA).1)
+        .unwrap()
+        .i();
+    let options = FormatOptions {
+        register_style: RegisterStyle::Numeric,
+        ..Default::default()
+    };
+    assert_eq!(i.display_with(&options).to_string(), "vmacc.vx v8,x8,v16");
+    let i = Instruction::Vector(VectorInstruction::VMV {
+        dest: VRegister::V31,
+        src: VOperand::Immediate(VImmediate::try_from(-16).unwrap()),
+    });
+    let options = FormatOptions {
+        immediate_style: ImmediateStyle::Hex,
+        ..Default::default()
+    };
+    assert_eq!(i.display_with(&options).to_string(), "vmv.v.i v31,-0x10");
+}
+
+#[test]
+fn loads_and_stores() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x02050087, "vle8.v v1,(a0)"),
+            (0x02055087, "vle16.v v1,(a0)"),
+            (0x02056087, "vle32.v v1,(a0)"),
+            (0x02057087, "vle64.v v1,(a0)"),
+            (0x020500a7, "vse8.v v1,(a0)"),
+            (0x02017fa7, "vse64.v v31,(sp)"),
+            (0x03050087, "vle8ff.v v1,(a0)"),
+            (0x03057087, "vle64ff.v v1,(a0)"),
+            (0x0ab56087, "vlse32.v v1,(a0),a1"),
+            (0x0ab550a7, "vsse16.v v1,(a0),a1"),
+            (0x06250087, "vluxei8.v v1,(a0),v2"),
+            (0x0e257087, "vloxei64.v v1,(a0),v2"),
+            (0x062550a7, "vsuxei16.v v1,(a0),v2"),
+            (0x0e2560a7, "vsoxei32.v v1,(a0),v2"),
+            (0x22056207, "vlseg2e32.v v4,(a0)"),
+            (0xe2050207, "vlseg8e8.v v4,(a0)"),
+            (0x42055227, "vsseg3e16.v v4,(a0)"),
+            (0x83057207, "vlseg5e64ff.v v4,(a0)"),
+            (0x2a556207, "vlsseg2e32.v v4,(a0),t0"),
+            (0xca550227, "vssseg7e8.v v4,(a0),t0"),
+            (0x66855207, "vluxseg4ei16.v v4,(a0),v8"),
+            (0x2e850207, "vloxseg2ei8.v v4,(a0),v8"),
+            (0xa6857227, "vsuxseg6ei64.v v4,(a0),v8"),
+            (0xee856227, "vsoxseg8ei32.v v4,(a0),v8"),
+            (0x02850087, "vl1re8.v v1,(a0)"),
+            (0x22855107, "vl2re16.v v2,(a0)"),
+            (0x62856207, "vl4re32.v v4,(a0)"),
+            (0xe2857407, "vl8re64.v v8,(a0)"),
+            (0x028500a7, "vs1r.v v1,(a0)"),
+            (0x22850127, "vs2r.v v2,(a0)"),
+            (0x62850227, "vs4r.v v4,(a0)"),
+            (0xe2850427, "vs8r.v v8,(a0)"),
+            (0x02b50087, "vlm.v v1,(a0)"),
+            (0x02b500a7, "vsm.v v1,(a0)"),
+        ],
+    );
+    // the base may be written with a zero offset, and vl1r.v is an alias of vl1re8.v
+    assert_eq!(
+        assemble_line_with("vle32.v v1,0(a0)", &assembler)
+            .unwrap()
+            .i(),
+        Instruction::Vector(VectorInstruction::Load {
+            addressing: VectorAddressing::UnitStride,
+            width: 32,
+            fields: 1,
+            dest: VRegister::V1,
+            base: IRegister::A0,
+            masked: false,
+        })
+    );
+    assert_eq!(
+        assemble_line_with("vl1r.v v1,(a0)", &assembler)
+            .unwrap()
+            .i()
+            .to_string(),
+        "vl1re8.v v1,(a0)"
+    );
+}
+
+#[test]
+fn masks() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x002180d7, "vadd.vv v1,v2,v3,v0.t"),
+            (0x002540d7, "vadd.vx v1,v2,a0,v0.t"),
+            (0x002db0d7, "vadd.vi v1,v2,-5,v0.t"),
+            (0x942eb0d7, "vsll.vi v1,v2,29,v0.t"),
+            (0xb421a0d7, "vmacc.vv v1,v3,v2,v0.t"),
+            (0xd421a0d7, "vwadd.wv v1,v2,v3,v0.t"),
+            (0x0021a0d7, "vredsum.vs v1,v2,v3,v0.t"),
+            (0x002550d7, "vfadd.vf v1,v2,fa0,v0.t"),
+            (0x6022b057, "vmseq.vi v0,v2,5,v0.t"),
+            (0x402180d7, "vadc.vvm v1,v2,v3,v0"),
+            (0x402540d7, "vadc.vxm v1,v2,a0,v0"),
+            (0x402db0d7, "vadc.vim v1,v2,-5,v0"),
+            (0x442180d7, "vmadc.vvm v1,v2,v3,v0"),
+            (0x442540d7, "vmadc.vxm v1,v2,a0,v0"),
+            (0x4422b0d7, "vmadc.vim v1,v2,5,v0"),
+            (0x462180d7, "vmadc.vv v1,v2,v3"),
+            (0x482180d7, "vsbc.vvm v1,v2,v3,v0"),
+            (0x482540d7, "vsbc.vxm v1,v2,a0,v0"),
+            (0x4c2180d7, "vmsbc.vvm v1,v2,v3,v0"),
+            (0x4c2540d7, "vmsbc.vxm v1,v2,a0,v0"),
+            (0x5c2180d7, "vmerge.vvm v1,v2,v3,v0"),
+            (0x5c2540d7, "vmerge.vxm v1,v2,a0,v0"),
+            (0x5c2db0d7, "vmerge.vim v1,v2,-5,v0"),
+            (0x5c2550d7, "vfmerge.vfm v1,v2,fa0,v0"),
+            (0x482320d7, "vzext.vf2 v1,v2,v0.t"),
+            (0x4c2010d7, "vfsqrt.v v1,v2,v0.t"),
+            (0x502820d7, "viota.m v1,v2,v0.t"),
+            (0x5020a0d7, "vmsbf.m v1,v2,v0.t"),
+            (0x5008a0d7, "vid.v v1,v0.t"),
+            (0x40282557, "vcpop.m a0,v2,v0.t"),
+            (0x4028a557, "vfirst.m a0,v2,v0.t"),
+            (0x00056087, "vle32.v v1,(a0),v0.t"),
+            (0x000500a7, "vse8.v v1,(a0),v0.t"),
+            (0x08b56087, "vlse32.v v1,(a0),a1,v0.t"),
+            (0x04250087, "vluxei8.v v1,(a0),v2,v0.t"),
+            (0x2c856227, "vsoxseg2ei32.v v4,(a0),v8,v0.t"),
+            (0x01055087, "vle16ff.v v1,(a0),v0.t"),
+        ],
+    );
+    // vmadc.vv is the same instruction without a carry in
+    assert_eq!(
+        assemble_line_with("vmadc.vvm v1,v2,v3,v0", &assembler)
+            .unwrap()
+            .i(),
+        Instruction::Vector(VectorInstruction::Binary {
+            op: VectorOp::VMADCM,
+            dest: VRegister::V1,
+            src2: VRegister::V2,
+            src1: VOperand::Vector(VRegister::V3),
+            masked: false,
+        })
+    );
+}
+
+#[test]
+fn configuration() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x0d05f557, "vsetvli a0,a1,e32,m1,ta,ma"),
+            (0x00507057, "vsetvli zero,zero,e8,mf8,tu,mu"),
+            (0x05b672d7, "vsetvli t0,a2,e64,m8,ta,mu"),
+            (0x08f5f557, "vsetvli a0,a1,e16,mf2,tu,ma"),
+            (0xcd187557, "vsetivli a0,16,e32,m2,ta,ma"),
+            (0xc02ff057, "vsetivli zero,31,e8,m4,tu,mu"),
+            (0x80c5f557, "vsetvl a0,a1,a2"),
+        ],
+    );
+    // the policies default to undisturbed, and the vtype may be spaced like the other operands
+    assert_eq!(
+        assemble_line_with("vsetvli a0, a1, e32, m1", &assembler)
+            .unwrap()
+            .i(),
+        Instruction::Vector(VectorInstruction::VSETVLI {
+            dest: IRegister::A0,
+            avl: IRegister::A1,
+            vtype: VTypeImmediate::Valid(VType {
+                sew: 32,
+                lmul: Lmul::M1,
+                ta: false,
+                ma: false,
+            }),
+        })
+    );
+}
+
+#[test]
+fn reserved_vtypes() {
+    let (decoder, assembler) = common::isa(ISA);
+    let permissive = DecoderConfig {
+        strict: false,
+        ..decoder
+    };
+    // a reserved vsew and vlmul with the reserved bits set, and vsetivli with vsew e128
+    for (bin, line) in [
+        (0x58ea7a57, "vsetvli s4,s4,0x58e"),
+        (0xc202f557, "vsetivli a0,5,0x20"),
+    ] {
+        assert!(Instruction::decode_with(bin, &decoder).is_err(), "{line}");
+        let i = Instruction::decode_with(bin, &permissive).unwrap();
+        assert_eq!(i.to_string(), line);
+        assert_eq!(Instruction::encode(&i), bin, "{line}");
+        assert_eq!(assemble_line_with(line, &assembler).unwrap().i(), i);
+    }
+    // a number that is a valid vtype is the same as its name
+    assert_eq!(
+        assemble_line_with("vsetvli a0,a1,0xd0", &assembler)
+            .unwrap()
+            .i(),
+        assemble_line_with("vsetvli a0,a1,e32,m1,ta,ma", &assembler)
+            .unwrap()
+            .i()
+    );
+    assert!(assemble_line_with("vsetvli a0,a1,0x800", &assembler).is_err());
+    assert!(assemble_line_with("vsetivli a0,5,0x400", &assembler).is_err());
+}
+
+#[test]
+fn malformed() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::decode_errors(
+        &decoder,
+        &[
+            (0x6421a0d7, "masked vmand.mm"),
+            (0x422180d7, "vadc.vvm with vm set"),
+            (0x40202557, "masked vmv.x.s"),
+            (0x00850087, "masked vl1re8.v"),
+            (0x062550d7, "reductions only have a vector form"),
+            (0x5e2180d7, "vmv.v.v with a vs2 other than v0"),
+            (0x9e213257, "vmv3r.v"),
+            (0x1d05f557, "vsetvli with a reserved vtype bit set"),
+            (0x82c5f557, "vsetvl with a nonzero funct7"),
+            (0x12056087, "vle32.v with mew set"),
+            (0x42850087, "vl3re8.v"),
+            (0x228550a7, "vs2r.v with a width of 16"),
+            (0x22b50087, "vlm.v with two fields"),
+            (0x030500a7, "fault-only-first store"),
+            (0x02150087, "unit-stride load with an unknown lumop"),
+        ],
+    );
+    common::assemble_errors(
+        &assembler,
+        &[
+            "vadd.vf v1,v2,fa0",
+            "vadd.wv v1,v2,v3",
+            "vadd.vi v1,v2,16",
+            "vsll.vi v1,v2,-1",
+            "vadd.vv v1,v2",
+            "vadd.vv v1,v2,a0",
+            "vadd.vv v1,v2,v32",
+            "vmv3r.v v1,v2",
+            "vid.v v1,v2",
+            "vredsum.vv v1,v2,v3",
+            "vmand.mm v1,v2,v3,v0.t",
+            "vmadc.vv v1,v2,v3,v0.t",
+            "vadc.vvm v1,v2,v3",
+            "vadc.vvm v1,v2,v3,v0.t",
+            "vmv.x.s a0,v2,v0.t",
+            "vl1re8.v v1,(a0),v0.t",
+            "vadd.vv v1,v2,v3,v1.t",
+            "vsetvli a0,a1",
+            "vsetvli a0,a1,e32,m3",
+            "vsetvli a0,a1,e32,m1,ma,ta",
+            "vsetvli a0,5,e32,m1",
+            "vsetivli a0,32,e32,m1",
+            "vsetivli a0,a1,e32,m1",
+            "vsetvl a0,a1,e32,m1",
+            "vle32.v v1,4(a0)",
+            "vle32.v v1,a0",
+            "vle32.v v1,(a0),a1",
+            "vlse32.v v1,(a0)",
+            "vluxei8.v v1,(a0),a1",
+            "vse32ff.v v1,(a0)",
+            "vlseg9e32.v v1,(a0)",
+            "vle128.v v1,(a0)",
+            "vl3re8.v v3,(a0)",
+            "vs2re8.v v2,(a0)",
+        ],
+    );
+}
+
+#[test]
+fn misaligned_register_groups() {
+    let (decoder, assembler) = common::isa(ISA);
+    let permissive = DecoderConfig {
+        strict: false,
+        ..decoder
+    };
+    // vmv2r.v v1,v3 and vmv4r.v v4,v6
+    for (bin, line, error) in [
+        (
+            0x9e30b0d7,
+            "vmv2r.v v1,v3",
+            "the registers of vmv2r.v must be a multiple of 2, got v1",
+        ),
+        (
+            0x9e61b257,
+            "vmv4r.v v4,v6",
+            "the registers of vmv4r.v must be a multiple of 4, got v6",
+        ),
+    ] {
+        assert_eq!(
+            Instruction::decode_with(bin, &decoder).map_err(String::from),
+            Err(error.to_owned())
+        );
+        assert_eq!(
+            assemble_line_with(line, &assembler).map(|_| ()),
+            Err(error.to_owned())
+        );
+        let i = Instruction::decode_with(bin, &permissive).unwrap();
+        assert_eq!(i.to_string(), line);
+        assert_eq!(Instruction::encode(&i), bin);
+    }
+    assert!(Instruction::decode_with(0x9e40b157, &decoder).is_ok());
+    assert!(Instruction::decode_with(0x9e83b457, &decoder).is_ok());
+    assert!(assemble_line_with("vmv1r.v v1,v3", &assembler).is_ok());
+
+    // vl2re8.v v1,(a0) and vs4r.v v2,(a0)
+    for (bin, line, error) in [
+        (
+            0x22850087,
+            "vl2re8.v v1,(a0)",
+            "the registers of vl2re8.v must be a multiple of 2, got v1",
+        ),
+        (
+            0x62850127,
+            "vs4r.v v2,(a0)",
+            "the registers of vs4r.v must be a multiple of 4, got v2",
+        ),
+    ] {
+        assert_eq!(
+            Instruction::decode_with(bin, &decoder).map_err(String::from),
+            Err(error.to_owned())
+        );
+        assert_eq!(
+            assemble_line_with(line, &assembler).map(|_| ()),
+            Err(error.to_owned())
+        );
+        let i = Instruction::decode_with(bin, &permissive).unwrap();
+        assert_eq!(i.to_string(), line);
+        assert_eq!(Instruction::encode(&i), bin);
+    }
+    assert!(Instruction::decode_with(0xe2856407, &decoder).is_ok());
+    assert!(assemble_line_with("vl1re32.v v3,(a0)", &assembler).is_ok());
+    assert!(assemble_line_with("vl8r.v v9,(a0)", &assembler).is_err());
+}
+
+#[test]
+fn extension_requirements() {
+    // V is not part of G
+    assert_eq!(
+        Instruction::decode(0x022180d7).map_err(String::from),
+        Err("unsupported instruction vadd.vv v1,v2,v3, which requires the V extension".to_owned())
+    );
+    assert_eq!(
+        assemble_line("vadd.vv v1,v2,v3").map(|_| ()),
+        Err("vadd.vv requires the V extension".to_owned())
+    );
+    // the vector loads and stores share their major opcodes with the float ones
+    assert_eq!(
+        Instruction::decode(0x02056087).map_err(String::from),
+        Err("unsupported instruction vle32.v v1,(a0), which requires the V extension".to_owned())
+    );
+    assert!(Instruction::decode(0x00052507).is_ok());
+
+    // v implies d, which implies f
+    let isa = IsaString::try_from("rv64iv").unwrap();
+    assert!(isa.extensions.v && isa.extensions.d && isa.extensions.f);
+    assert_eq!(isa.to_string(), "rv64ifdv_zicsr");
+    let mut extensions = isa.extensions;
+    extensions.set("f", false);
+    assert!(!extensions.v);
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "xandesperf")]
+
+mod common;
+
+use riscv_codec::{
+    andes::AndesInstruction,
+    assembly::{assemble_line, assemble_line_with},
+    instruction::{Instruction, disassemble_instruction},
+    register::IRegister,
+};
+
+const ISA: &str = "rv64gc_xandesperf";
+const RV32_ISA: &str = "rv32gc_xandesperf";
+
+#[test]
+fn branch_on_bit() {
+    let (config, asm) = common::isa(ISA);
+    let bin = 0x0035785b;
+    let i = assemble_line_with("nds.bbc a0,3,16", &asm).unwrap().i();
+
+    // check assembler
+    let Instruction::XAndesPerf(AndesInstruction::BBC { src, bit, offset }) = i else {
+        panic!("expected nds.bbc, got {i:?}");
+    };
+    assert_eq!(src, IRegister::A0);
+    assert_eq!(bit.val(), 3);
+    assert_eq!(offset.val(), 16);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &config).unwrap(), i);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(disassemble_instruction(&i), "nds.bbc a0,3,16");
+    assert_eq!(i.branch_target(0x1000), Some(0x1010));
+
+    // bit 5 of the bit number is stored in bit 7
+    let i = Instruction::decode_with(0xfe85fcdb, &config).unwrap();
+    assert_eq!(i.to_string(), "nds.bbs a1,40,-8");
+}
+
+#[test]
+fn instructions() {
+    let (config, asm) = common::isa(ISA);
+    for (bin, line) in [
+        (0xc04550db, "nds.beqc a0,100,-1024"),
+        (0x5045a55b, "nds.bfoz a0,a1,20,4"),
+        (0x0ac5855b, "nds.lea.h a0,a1,a2"),
+    ] {
+        let i = Instruction::decode_with(bin, &config).unwrap();
+        assert_eq!(i.to_string(), line);
+        assert_eq!(assemble_line_with(line, &asm).unwrap().i(), i);
+    }
+    for line in [
+        "nds.bnec t0,127,1022",
+        "nds.bfos s0,s1,63,0",
+        "nds.lea.w a0,a1,a2",
+        "nds.lea.d a0,a1,a2",
+        "nds.lea.b.ze t0,t1,t2",
+        "nds.lea.h.ze t0,t1,t2",
+        "nds.lea.w.ze t0,t1,t2",
+        "nds.ffb a0,a1,a2",
+        "nds.ffzmism a0,a1,a2",
+        "nds.ffmism a0,a1,a2",
+        "nds.flmism a0,a1,a2",
+    ] {
+        let i = assemble_line_with(line, &asm).unwrap().i();
+        assert_eq!(i.to_string(), line);
+        assert_eq!(
+            Instruction::decode_with(Instruction::encode(&i), &config).unwrap(),
+            i
+        );
+    }
+    common::assemble_errors(
+        &asm,
+        &[
+            "nds.bbc a0,64,16",
+            "nds.bbc a0,3,17",
+            "nds.beqc a0,128,16",
+            "nds.bfoz a0,a1,20",
+            "nds.lea.q a0,a1,a2",
+        ],
+    );
+}
+
+#[test]
+fn extension_requirements() {
+    // custom-2 is only XAndesPerf when it is enabled
+    assert_eq!(
+        Instruction::decode(0x0ac5855b).map_err(String::from),
+        Err(
+            "unsupported instruction nds.lea.h a0,a1,a2, which requires the XAndesPerf extension"
+                .to_owned()
+        )
+    );
+    assert!(assemble_line("nds.lea.h a0,a1,a2").is_err());
+
+    // bit numbers, bit fields and the zero extending and doubleword forms are limited on RV32
+    let (rv32, rv32_asm) = common::isa(RV32_ISA);
+    assert!(assemble_line_with("nds.bbs a0,31,16", &rv32_asm).is_ok());
+    assert!(assemble_line_with("nds.lea.w a0,a1,a2", &rv32_asm).is_ok());
+    common::assemble_errors(
+        &rv32_asm,
+        &[
+            "nds.bbs a0,32,16",
+            "nds.bfoz a0,a1,32,0",
+            "nds.lea.d a0,a1,a2",
+            "nds.lea.w.ze a0,a1,a2",
+        ],
+    );
+    assert!(Instruction::decode_with(0xfe85fcdb, &rv32).is_err());
+}
@@ -0,0 +1,198 @@
+#![cfg(feature = "xthead")]
+
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    config::{AssemblerConfig, DecoderConfig, IsaString},
+    instruction::{Instruction, disassemble_instruction},
+    register::IRegister,
+    thead::{CacheLineOp, TheadInstruction},
+};
+
+const ISA: &str = "rv64gc_xtheadba_xtheadbb_xtheadcmo_xtheadcondmov_xtheadmempair";
+const RV32_ISA: &str = "rv32gc_xtheadba_xtheadbb_xtheadcmo_xtheadcondmov_xtheadmempair";
+
+#[test]
+fn load_pair() {
+    let (config, asm) = common::isa(ISA);
+    let bin = 0xfeb1450b;
+    let i = assemble_line_with("th.ldd a0,a1,(sp),3,4", &asm)
+        .unwrap()
+        .i();
+
+    // check assembler
+    let Instruction::XThead(TheadInstruction::LDD {
+        dest1,
+        dest2,
+        base,
+        offset,
+    }) = i
+    else {
+        panic!("expected th.ldd, got {i:?}");
+    };
+    assert_eq!(dest1, IRegister::A0);
+    assert_eq!(dest2, IRegister::A1);
+    assert_eq!(base, IRegister::StackPointer);
+    assert_eq!(offset.val(), 3);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &config).unwrap(), i);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(disassemble_instruction(&i), "th.ldd a0,a1,(sp),3,4");
+}
+
+#[test]
+fn instructions() {
+    let (config, asm) = common::isa(ISA);
+    common::round_trips(
+        &config,
+        &asm,
+        &[
+            (0x02c5950b, "th.addsl a0,a1,a2,1"),
+            (0x1285950b, "th.srri a0,a1,40"),
+            (0x1455950b, "th.srriw a0,a1,5"),
+            (0x3c85a50b, "th.ext a0,a1,15,8"),
+            (0x7c05b50b, "th.extu a0,a1,31,0"),
+            (0x8405950b, "th.ff0 a0,a1"),
+            (0x8605950b, "th.ff1 a0,a1"),
+            (0x8205950b, "th.rev a0,a1"),
+            (0x9005950b, "th.revw a0,a1"),
+            (0x8005950b, "th.tstnbz a0,a1"),
+            (0x8bf5950b, "th.tst a0,a1,63"),
+            (0x40c5950b, "th.mveqz a0,a1,a2"),
+            (0x42c5950b, "th.mvnez a0,a1,a2"),
+            (0xe4b6450b, "th.lwd a0,a1,(a2),2,3"),
+            (0xf0b6450b, "th.lwud a0,a1,(a2),0,3"),
+            (0xe2b6550b, "th.swd a0,a1,(a2),1,3"),
+            (0xf8b1550b, "th.sdd a0,a1,(sp),0,4"),
+            (0x0255000b, "th.dcache.cva a0"),
+            (0x0385000b, "th.icache.ipa a0"),
+            (0x0030000b, "th.dcache.ciall"),
+            (0x0160000b, "th.l2cache.iall"),
+            (0x0100000b, "th.icache.iall"),
+        ],
+    );
+    assert_eq!(
+        Instruction::decode_with(0x0255000b, &config).unwrap(),
+        Instruction::XThead(TheadInstruction::CACHELINE {
+            op: CacheLineOp::DCACHECVA,
+            src: IRegister::A0
+        })
+    );
+}
+
+#[test]
+fn every_encoding_round_trips() {
+    let (config, _) = common::isa(ISA);
+    for funct3 in 0..8 {
+        for high in 0..1 << 12 {
+            let bin = high << 20 | 0b01011 << 15 | funct3 << 12 | 0b01010 << 7 | 0b0001011;
+            if let Ok(i) = Instruction::decode_with(bin, &config) {
+                assert_eq!(Instruction::encode(&i), bin, "{i}");
+            }
+        }
+    }
+}
+
+#[test]
+fn malformed() {
+    let (config, asm) = common::isa(ISA);
+    for (line, error) in [
+        (
+            "th.ldd a0,a0,(sp),0,4",
+            "th.ldd must load two registers that differ from each other and from the base, got a0, a0 and sp",
+        ),
+        (
+            "th.lwd a0,a1,(a0),0,3",
+            "th.lwd must load two registers that differ from each other and from the base, got a0, a1 and a0",
+        ),
+        (
+            "th.ldd a0,a1,(sp),0,3",
+            "the last operand of th.ldd must be 4, got 3",
+        ),
+        (
+            "th.swd a0,a1,sp,0,3",
+            "the base of th.swd must be in parentheses, got sp",
+        ),
+        ("th.dcache.call a0", "th.dcache.call takes no operands"),
+        ("th.dcache.cva", "th.dcache.cva requires 1 operand"),
+        ("th.addsl a0,a1,a2", "th.addsl requires 4 operands"),
+        ("th.sync", "unknown mnemonic: th.sync"),
+    ] {
+        assert_eq!(
+            assemble_line_with(line, &asm).map(|_| ()),
+            Err(error.to_owned()),
+            "{line}"
+        );
+    }
+    common::assemble_errors(
+        &asm,
+        &[
+            "th.addsl a0,a1,a2,4",
+            "th.srri a0,a1,64",
+            "th.srriw a0,a1,32",
+            "th.ext a0,a1,64,0",
+            "th.lwd a0,a1,(a2),4,3",
+        ],
+    );
+    // the stores may use the same register twice
+    assert!(assemble_line_with("th.sdd a0,a0,(a0),0,4", &asm).is_ok());
+
+    // th.dcache.cva writing a0, th.dcache.call with a register, th.ff0 with rs2 and an unused funct3
+    for bin in [0x0255050b_u32, 0x0015000b, 0x84c5950b, 0x0000600b] {
+        assert!(Instruction::decode_with(bin, &config).is_err(), "{bin:#x}");
+    }
+}
+
+#[test]
+fn extension_requirements() {
+    // custom-0 is only XThead when each extension is enabled
+    assert_eq!(
+        Instruction::decode(0x02c5950b).map_err(String::from),
+        Err(
+            "unsupported instruction th.addsl a0,a1,a2,1, which requires the XTheadBa extension"
+                .to_owned()
+        )
+    );
+    let isa = IsaString::try_from("rv64gc_xtheadbb").unwrap();
+    let (config, asm) = (DecoderConfig::isa(&isa), AssemblerConfig::isa(&isa));
+    assert!(Instruction::decode_with(0x8605950b, &config).is_ok());
+    for (line, extension) in [
+        ("th.addsl a0,a1,a2,1", "XTheadBa"),
+        ("th.dcache.ciall", "XTheadCmo"),
+        ("th.mveqz a0,a1,a2", "XTheadCondMov"),
+        ("th.swd a0,a1,(a2),1,3", "XTheadMemPair"),
+    ] {
+        let mnemonic = line.split_once(' ').map_or(line, |(m, _)| m);
+        assert_eq!(
+            assemble_line_with(line, &asm).map(|_| ()),
+            Err(format!("{mnemonic} requires the {extension} extension"))
+        );
+    }
+    assert!(assemble_line("th.ext a0,a1,15,8").is_err());
+    assert_eq!(isa.to_string(), "rv64imafdc_zicsr_zifencei_xtheadbb");
+
+    // bit positions and the word and doubleword forms are limited on RV32
+    let (rv32, rv32_asm) = common::isa(RV32_ISA);
+    assert!(assemble_line_with("th.srri a0,a1,31", &rv32_asm).is_ok());
+    assert!(assemble_line_with("th.lwd a0,a1,(a2),0,3", &rv32_asm).is_ok());
+    common::assemble_errors(
+        &rv32_asm,
+        &[
+            "th.srri a0,a1,32",
+            "th.tst a0,a1,32",
+            "th.ext a0,a1,32,0",
+            "th.srriw a0,a1,1",
+            "th.revw a0,a1",
+            "th.lwud a0,a1,(a2),0,3",
+            "th.ldd a0,a1,(a2),0,4",
+            "th.sdd a0,a1,(a2),0,4",
+        ],
+    );
+    assert!(Instruction::decode_with(0x1285950b, &rv32).is_err());
+}
@@ -0,0 +1,114 @@
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    config::{Extensions, IsaString},
+    instruction::Instruction,
+    register::IRegister,
+};
+
+const ISA: &str = "rv64gc_zabha";
+
+#[test]
+fn amoadd_byte() {
+    let (decoder, assembler) = common::isa(ISA);
+    let bin = 0x00c5852f;
+    let expected = Instruction::AMOADDB {
+        dest: IRegister::A0,
+        addr: IRegister::A1,
+        src: IRegister::A2,
+        aq: false,
+        rl: false,
+    };
+
+    // check assembler
+    let i = assemble_line_with("amoadd.b a0,a1,a2", &assembler)
+        .unwrap()
+        .i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "amoadd.b a0,a1,a2");
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x0cc5952f, "amoswap.h.aq a0,a1,a2"),
+            (0x227302af, "amoxor.b.rl t0,t1,t2"),
+            (0x66c5952f, "amoand.h.aqrl a0,a1,a2"),
+            (0x40c5852f, "amoor.b a0,a1,a2"),
+            (0x80c5952f, "amomin.h a0,a1,a2"),
+            (0xa0c5852f, "amomax.b a0,a1,a2"),
+            (0xc0c5952f, "amominu.h a0,a1,a2"),
+            (0xe0c5852f, "amomaxu.b a0,a1,a2"),
+            (0x28c5852f, "amocas.b a0,a1,a2"),
+            (0x2ec5952f, "amocas.h.aqrl a0,a1,a2"),
+        ],
+    );
+}
+
+#[test]
+fn malformed() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::decode_errors(
+        &decoder,
+        &[
+            (0x1005852f, "lr.b"),
+            (0x18c5852f, "sc.b"),
+            (0x28c5a52f, "amocas.w, which is part of Zacas"),
+        ],
+    );
+    common::assemble_errors(
+        &assembler,
+        &[
+            "amoadd.q a0,a1,a2",
+            "amocas.w a0,a1,a2",
+            "amoswap.b.ac a0,a1,a2",
+            "amoor.h a0,a1",
+            "lr.b a0,a1",
+            "sc.h a0,a1,a2",
+        ],
+    );
+    assert_eq!(
+        assemble_line_with("amoadd.q a0,a1,a2", &assembler).map(|_| ()),
+        Err(
+            "size of amoadd instruction must be byte (b), halfword (h), word (w) or doubleword (d)"
+                .to_owned()
+        )
+    );
+}
+
+#[test]
+fn extension_requirements() {
+    assert_eq!(
+        Instruction::decode(0x00c5852f).map_err(String::from),
+        Err(
+            "unsupported instruction amoadd.b a0,a1,a2, which requires the Zabha extension"
+                .to_owned()
+        )
+    );
+    assert_eq!(
+        assemble_line("amocas.h a0,a1,a2").map(|_| ()),
+        Err("amocas.h requires the Zabha extension".to_owned())
+    );
+
+    // zabha implies a
+    let isa = IsaString::try_from("rv64i_zabha").unwrap();
+    assert!(isa.extensions.zabha && isa.extensions.a);
+    assert_eq!(isa.to_string(), "rv64ia_zabha");
+    let mut extensions = Extensions::default();
+    extensions.set("zabha", true);
+    extensions.set("a", false);
+    assert!(!extensions.zabha);
+}
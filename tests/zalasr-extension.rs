@@ -0,0 +1,132 @@
+#![cfg(feature = "zalasr")]
+
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    config::{AssemblerConfig, DecoderConfig, FormatOptions, IsaString},
+    instruction::{Instruction, disassemble_instruction, disassemble_instruction_with},
+    register::IRegister,
+};
+
+const ISA: &str = "rv64gc_zalasr";
+const RV32_ISA: &str = "rv32gc_zalasr";
+
+#[test]
+fn load_acquire() {
+    let (config, asm) = common::isa(ISA);
+    let bin = 0x3405a52f;
+    let expected = Instruction::LWAQ {
+        dest: IRegister::A0,
+        addr: IRegister::A1,
+        rl: false,
+    };
+
+    // check assembler
+    let i = assemble_line_with("lw.aq a0,a1", &asm).unwrap().i();
+    assert_eq!(i, expected);
+    assert_eq!(
+        assemble_line_with("lw.aq a0,(a1)", &asm).unwrap().i(),
+        expected
+    );
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &config).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(disassemble_instruction(&i), "lw.aq a0,a1");
+    let i3 = assemble_line_with(&disassemble_instruction(&i), &asm)
+        .unwrap()
+        .i();
+    assert_eq!(i, i3);
+}
+
+#[test]
+fn store_release() {
+    let (config, asm) = common::isa(ISA);
+    let bin = 0x3ab5202f;
+    let expected = Instruction::SWRL {
+        src: IRegister::A1,
+        addr: IRegister::A0,
+        aq: false,
+    };
+
+    // check assembler
+    let i = assemble_line_with("sw.rl a1,a0", &asm).unwrap().i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &config).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    let options = FormatOptions {
+        parenthesized_atomics: true,
+        ..Default::default()
+    };
+    assert_eq!(disassemble_instruction_with(&i, &options), "sw.rl a1,(a0)");
+    let i3 = assemble_line_with(&disassemble_instruction_with(&i, &options), &asm)
+        .unwrap()
+        .i();
+    assert_eq!(i, i3);
+}
+
+#[test]
+fn orderings_and_widths() {
+    let (config, asm) = common::isa(ISA);
+    for line in [
+        "lb.aq t0,sp",
+        "lh.aqrl a0,a1",
+        "ld.aq s0,s1",
+        "sb.aqrl zero,a0",
+        "sh.rl a2,a3",
+        "sd.rl t1,t2",
+    ] {
+        let i = assemble_line_with(line, &asm).unwrap().i();
+        assert_eq!(i.to_string(), line);
+        assert_eq!(
+            Instruction::decode_with(Instruction::encode(&i), &config).unwrap(),
+            i
+        );
+    }
+    // loads must acquire and stores must release
+    for line in ["lw a0,a1", "lw.rl a0,a1", "sw.aq a1,a0", "sw.aqrl a1"] {
+        assert!(assemble_line_with(line, &asm).is_err(), "{line}");
+    }
+    // lw.rl and sw.aq
+    assert!(Instruction::decode_with(0x3205a52f, &config).is_err());
+    assert!(Instruction::decode_with(0x3cb5202f, &config).is_err());
+    // sw.rl with rd set
+    assert!(Instruction::decode_with(0x3ab520af, &config).is_err());
+}
+
+#[test]
+fn extension_requirements() {
+    // Zalasr is not part of rv64gc
+    assert_eq!(
+        Instruction::decode(0x3405a52f).map_err(String::from),
+        Err("unsupported instruction lw.aq a0,a1, which requires the Zalasr extension".to_owned())
+    );
+    assert_eq!(
+        assemble_line("lw.aq a0,a1").map(|_| ()),
+        Err("lw.aq requires the Zalasr extension".to_owned())
+    );
+    let isa = IsaString::try_from("rv64imc_zalasr").unwrap();
+    assert_eq!(isa.to_string(), "rv64imc_zalasr");
+    assert!(!isa.extensions.a);
+    assert!(Instruction::decode_with(0x3405a52f, &DecoderConfig::isa(&isa)).is_ok());
+    assert!(assemble_line_with("sw.rl a1,a0", &AssemblerConfig::isa(&isa)).is_ok());
+
+    // ld.aq and sd.rl only exist on RV64
+    let (rv32, rv32_asm) = common::isa(RV32_ISA);
+    let (_, asm) = common::isa(ISA);
+    let ld = Instruction::encode(&assemble_line_with("ld.aq a0,a1", &asm).unwrap().i());
+    assert!(Instruction::decode_with(ld, &rv32).is_err());
+    assert!(assemble_line_with("ld.aq a0,a1", &rv32_asm).is_err());
+    assert!(assemble_line_with("lw.aq a0,a1", &rv32_asm).is_ok());
+}
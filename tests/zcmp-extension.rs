@@ -0,0 +1,173 @@
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    cinstruction::CInstruction,
+    config::{DecoderConfig, Extensions, IsaString},
+    immediates::StackAdjustment,
+    register::{CSRegister, IRegister, RegisterList},
+};
+
+#[test]
+fn cm_push() {
+    let (decoder, assembler) = common::isa("rv32imc_zcmp");
+    let bin = 0xb862;
+    let rlist = RegisterList::try_from("{ra, s0-s1}").unwrap();
+    let expected = CInstruction::CMPUSH {
+        rlist,
+        stack_adj: StackAdjustment::new(rlist, 16, decoder.xlen).unwrap(),
+    };
+
+    // check assembler
+    let c = assemble_line_with("cm.push {ra, s0-s1}, -16", &assembler)
+        .unwrap()
+        .c();
+    assert_eq!(c, expected);
+
+    // check decoder
+    assert_eq!(CInstruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(CInstruction::encode(&c), bin);
+
+    // check disassembler
+    assert_eq!(c.to_string(), "cm.push {ra, s0-s1},-16");
+}
+
+#[test]
+fn round_trips() {
+    for (isa, bin, line) in [
+        ("rv32imc_zcmp", 0xb842, "cm.push {ra},-16"),
+        ("rv32imc_zcmp", 0xb8fe, "cm.push {ra, s0-s11},-112"),
+        ("rv64imc_zcmp", 0xb8f2, "cm.push {ra, s0-s11},-112"),
+        ("rv64imc_zcmp", 0xb8fe, "cm.push {ra, s0-s11},-160"),
+        ("rv32imc_zcmp", 0xba62, "cm.pop {ra, s0-s1},16"),
+        ("rv64imc_zcmp", 0xba62, "cm.pop {ra, s0-s1},32"),
+        ("rv32imc_zcmp", 0xba56, "cm.pop {ra, s0},32"),
+        ("rv32imc_zcmp", 0xbc82, "cm.popretz {ra, s0-s3},32"),
+        ("rv32imc_zcmp", 0xbe42, "cm.popret {ra},16"),
+        ("rv32imc_zcmp", 0xbe6e, "cm.popret {ra, s0-s1},64"),
+        ("rv32imc_zcmp", 0xaca2, "cm.mvsa01 s1,s0"),
+        ("rv32imc_zcmp", 0xace2, "cm.mva01s s1,s0"),
+        ("rv64imc_zcmp", 0xaffe, "cm.mva01s s7,s7"),
+    ] {
+        let (decoder, assembler) = common::isa(isa);
+        let c = CInstruction::decode_with(bin, &decoder).unwrap();
+        assert_eq!(c.to_string(), line, "{isa}");
+        assert_eq!(CInstruction::encode(&c), bin, "{line}");
+        assert_eq!(assemble_line_with(line, &assembler).unwrap().c(), c);
+    }
+}
+
+#[test]
+fn register_lists() {
+    for (list, count, display) in [
+        ("{ra}", 1, "{ra}"),
+        ("{x1}", 1, "{ra}"),
+        ("{ra,s0}", 2, "{ra, s0}"),
+        ("{ra, fp}", 2, "{ra, s0}"),
+        ("{ ra, s0-s2 }", 4, "{ra, s0-s2}"),
+        ("{x1, x8-x9, x18-x20}", 6, "{ra, s0-s4}"),
+        ("{ra, s0-s11}", 13, "{ra, s0-s11}"),
+    ] {
+        let rlist = RegisterList::try_from(list).unwrap();
+        assert_eq!(rlist.count(), count, "{list}");
+        assert_eq!(rlist.to_string(), display);
+        assert_eq!(rlist.registers().count(), count);
+    }
+    assert_eq!(
+        RegisterList::try_from("{ra, s0-s2}")
+            .unwrap()
+            .registers()
+            .last(),
+        Some(IRegister::S2)
+    );
+    for list in [
+        "ra",
+        "{}",
+        "{s0}",
+        "{ra, s1}",
+        "{ra, s0-s10}",
+        "{ra, s0, s2}",
+        "{ra, s1-s0}",
+        "{ra, a0}",
+        "{x1, x8-x18}",
+    ] {
+        assert!(RegisterList::try_from(list).is_err(), "{list}");
+    }
+    assert!(RegisterList::try_from(3).is_err());
+    assert_eq!(
+        RegisterList::try_from(15).unwrap().to_string(),
+        "{ra, s0-s11}"
+    );
+    assert_eq!(CSRegister::try_from("s7").unwrap().expand(), IRegister::S7);
+    assert!(CSRegister::try_from("s8").is_err());
+}
+
+#[test]
+fn malformed() {
+    let (decoder, assembler) = common::isa("rv32imc_zcmp");
+    for (bin, reason) in [
+        (0xb832, "reserved rlist"),
+        (0xbb42, "unknown push and pop encoding"),
+        (0xa422, "unknown move encoding"),
+        (0xac86, "cm.mvsa01 into the same register"),
+    ] {
+        assert!(
+            CInstruction::decode_with(bin, &decoder).is_err(),
+            "{reason}"
+        );
+    }
+    common::assemble_errors(
+        &assembler,
+        &[
+            "cm.push {ra}, 16",
+            "cm.push {ra}, -24",
+            "cm.push {ra}, -80",
+            "cm.pop {ra}, -16",
+            "cm.pop {ra, s0-s10}, 64",
+            "cm.popret {ra}",
+            "cm.popret ra, 16",
+            "cm.mvsa01 s0,s0",
+            "cm.mvsa01 s0,a0",
+            "cm.mva01s s0",
+            "cm.jt 1",
+            "c.fsdsp fa0,8",
+        ],
+    );
+    assert!(
+        assemble_line_with("cm.push {ra, s0-s11}, -112", &common::isa("rv64imc_zcmp").1)
+            .unwrap()
+            .c()
+            .try_expand()
+            .is_err()
+    );
+}
+
+#[test]
+fn extension_requirements() {
+    // without Zcmp the encodings are c.fsdsp
+    let g = DecoderConfig::default();
+    assert_eq!(
+        CInstruction::decode_with(0xb862, &g).unwrap().to_string(),
+        "c.fsdsp fs8,48"
+    );
+    assert_eq!(
+        assemble_line("cm.push {ra, s0-s1}, -32").map(|_| ()),
+        Err("cm.push requires the Zcmp extension".to_owned())
+    );
+    assert_eq!(
+        assemble_line("cm.mvsa01 s0, s1").map(|_| ()),
+        Err("cm.mvsa01 requires the Zcmp extension".to_owned())
+    );
+
+    // Zcmp implies C
+    let isa = IsaString::try_from("rv32i_zcmp").unwrap();
+    assert!(isa.extensions.zcmp && isa.extensions.c);
+    assert_eq!(isa.to_string(), "rv32ic_zcmp");
+    let mut extensions = Extensions::none();
+    extensions.set("zcmp", true);
+    assert!(extensions.c);
+    extensions.set("c", false);
+    assert!(!extensions.zcmp);
+}
@@ -0,0 +1,125 @@
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    config::{AssemblerConfig, DecoderConfig, Extensions, IsaString},
+    instruction::{Instruction, RoundingMode},
+    register::FRegister,
+};
+
+const ISA: &str = "rv64gcv_zfbfmin_zvfbfmin_zvfbfwma_zvfh";
+
+#[test]
+fn fcvt_bf16_s() {
+    let (decoder, assembler) = common::isa(ISA);
+    let bin = 0x4485f553;
+    let expected = Instruction::FCVTBF16S {
+        dest: FRegister::FA0,
+        src: FRegister::FA1,
+        rm: RoundingMode::DYN,
+    };
+
+    // check assembler
+    let i = assemble_line_with("fcvt.bf16.s fa0,fa1", &assembler)
+        .unwrap()
+        .i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "fcvt.bf16.s fa0,fa1");
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x01059507, "flh fa0,16(a1)"),
+            (0x00a59827, "fsh fa0,16(a1)"),
+            (0xe4058553, "fmv.x.h a0,fa1"),
+            (0xf4058553, "fmv.h.x fa0,a1"),
+            (0x44859553, "fcvt.bf16.s.rtz fa0,fa1"),
+            (0x40658553, "fcvt.s.bf16.rne fa0,fa1"),
+            (0x4065f553, "fcvt.s.bf16 fa0,fa1"),
+            (0x4a469457, "vfwcvtbf16.f.f.v v8,v4"),
+            (0x484e9457, "vfncvtbf16.f.f.w v8,v4,v0.t"),
+            (0xee4a1457, "vfwmaccbf16.vv v8,v20,v4"),
+            (0xec455457, "vfwmaccbf16.vf v8,fa0,v4,v0.t"),
+        ],
+    );
+}
+
+#[test]
+fn malformed() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::decode_errors(
+        &decoder,
+        &[
+            (0x4495f553, "fcvt.bf16.s with an rs2 other than 8"),
+            (0x4075f553, "fcvt.s.bf16 with an rs2 other than 6"),
+            (0xe4059553, "fmv.x.h with a funct3 of 1"),
+            (0xe4158553, "fmv.x.h with rs2 set"),
+        ],
+    );
+    common::assemble_errors(
+        &assembler,
+        &[
+            "fcvt.bf16.d fa0,fa1",
+            "fcvt.bf16.s fa0",
+            "flh fa0,a1",
+            "fmv.x.h fa0,fa1",
+            "vfwmaccbf16.vi v8,1,v4",
+        ],
+    );
+}
+
+#[test]
+fn extension_requirements() {
+    let g = DecoderConfig::default();
+    for (bin, extension) in [
+        (0x01059507, "Zfbfmin"),
+        (0x00a59827, "Zfbfmin"),
+        (0xe4058553, "Zfbfmin"),
+        (0x4485f553, "Zfbfmin"),
+        (0x4065f553, "Zfbfmin"),
+    ] {
+        let error = String::from(Instruction::decode_with(bin, &g).unwrap_err());
+        assert!(
+            error.ends_with(&format!("requires the {extension} extension")),
+            "{error}"
+        );
+    }
+    let v = IsaString::try_from("rv64gcv").unwrap();
+    assert_eq!(
+        Instruction::decode_with(0x4a469457, &DecoderConfig::isa(&v)).map_err(String::from),
+        Err(
+            "unsupported instruction vfwcvtbf16.f.f.v v8,v4, which requires the Zvfbfmin extension"
+                .to_owned()
+        )
+    );
+    assert_eq!(
+        assemble_line_with("vfwmaccbf16.vv v8,v20,v4", &AssemblerConfig::isa(&v)).map(|_| ()),
+        Err("vfwmaccbf16.vv requires the Zvfbfwma extension".to_owned())
+    );
+    assert!(assemble_line("fcvt.s.bf16 fa0,fa1").is_err());
+
+    // zvfbfwma implies zvfbfmin and zfbfmin, the vector ones imply v and zfbfmin implies f
+    let isa = IsaString::try_from("rv64i_zvfbfwma").unwrap();
+    assert!(isa.extensions.zvfbfmin && isa.extensions.zfbfmin && isa.extensions.v);
+    assert_eq!(isa.to_string(), "rv64ifdv_zicsr_zfbfmin_zvfbfmin_zvfbfwma");
+    let isa = IsaString::try_from("rv64i_zvfh").unwrap();
+    assert!(isa.extensions.zvfh && isa.extensions.v);
+    let mut extensions = Extensions::none();
+    extensions.set("zfbfmin", true);
+    assert!(extensions.f && !extensions.d);
+    extensions.set("f", false);
+    assert!(!extensions.zfbfmin);
+}
@@ -0,0 +1,117 @@
+mod common;
+
+use riscv_codec::{
+    assembly::assemble_line_with,
+    config::{AssemblerConfig, Extensions, FormatOptions, IsaString},
+    instruction::{Instruction, RoundingMode},
+    register::FRegister,
+};
+
+const ISA: &str = "rv32imc_zfinx";
+
+fn registers() -> FormatOptions {
+    FormatOptions {
+        zfinx: true,
+        ..Default::default()
+    }
+}
+
+#[test]
+fn fadd_s() {
+    let (decoder, assembler) = common::isa(ISA);
+    let bin = 0x00c5f553;
+    let expected = Instruction::FADDS {
+        dest: FRegister::FA0,
+        src1: FRegister::FA1,
+        src2: FRegister::FA2,
+        rm: RoundingMode::DYN,
+    };
+
+    // check assembler
+    let i = assemble_line_with("fadd.s a0,a1,a2", &assembler)
+        .unwrap()
+        .i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.display_with(&registers()).to_string(), "fadd.s a0,a1,a2");
+    assert_eq!(i.to_string(), "fadd.s fa0,fa1,fa2");
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::isa(ISA);
+    for (bin, line) in [
+        (0xc0059553, "fcvt.w.s.rtz a0,a1"),
+        (0xe0059553, "fclass.s a0,a1"),
+        (0x68c5f543, "fmadd.s a0,a1,a2,a3"),
+        (0x20b58553, "fsgnj.s a0,a1,a1"),
+        (0xa0c5a553, "feq.s a0,a1,a2"),
+        (0xd005f553, "fcvt.s.w a0,a1"),
+        (0x280482d3, "fmin.s t0,s1,zero"),
+    ] {
+        let i = Instruction::decode_with(bin, &decoder).unwrap();
+        assert_eq!(i.display_with(&registers()).to_string(), line);
+        assert_eq!(Instruction::encode(&i), bin, "{line}");
+        assert_eq!(assemble_line_with(line, &assembler).unwrap().i(), i);
+    }
+    // the rounding mode may also be given as an operand
+    assert_eq!(
+        assemble_line_with("fcvt.w.s a0,a1,rtz", &assembler)
+            .unwrap()
+            .i(),
+        Instruction::decode(0xc0059553).unwrap()
+    );
+}
+
+#[test]
+fn pseudo_instructions() {
+    let options = FormatOptions {
+        pseudo_instructions: true,
+        ..registers()
+    };
+    let i = Instruction::decode(0x20b58553).unwrap();
+    assert_eq!(i.display_with(&options).to_string(), "fmv.s a0,a1");
+}
+
+#[test]
+fn extension_requirements() {
+    let (decoder, assembler) = common::isa(ISA);
+    for (bin, line) in [
+        (0x0005a507, "flw fa0,0(a1)"),
+        (0x00a5a027, "fsw fa0,0(a1)"),
+        (0xe0058553, "fmv.x.w a0,fa1"),
+        (0xf0058553, "fmv.w.x fa0,a1"),
+    ] {
+        let error = String::from(Instruction::decode_with(bin, &decoder).unwrap_err());
+        assert!(error.ends_with("requires the F extension"), "{error}");
+        let mnemonic = line.split_once(' ').unwrap().0;
+        assert_eq!(
+            assemble_line_with(line, &assembler).map(|_| ()),
+            Err(format!("{mnemonic} requires the F extension"))
+        );
+    }
+    let i = IsaString::try_from("rv32imc").unwrap();
+    assert_eq!(
+        assemble_line_with("fadd.s fa0,fa1,fa2", &AssemblerConfig::isa(&i)).map(|_| ()),
+        Err("fadd.s requires the F or Zfinx extension".to_owned())
+    );
+    // integer registers are only float operands with Zfinx
+    assert!(assemble_line_with("fadd.s a0,a1,a2", &AssemblerConfig::default()).is_err());
+
+    // zfinx implies zicsr
+    let isa = IsaString::try_from("rv32imc_zfinx").unwrap();
+    assert!(isa.extensions.zfinx && isa.extensions.zicsr && !isa.extensions.f);
+    assert_eq!(isa.to_string(), "rv32imc_zicsr_zfinx");
+    let mut extensions = Extensions::none();
+    extensions.set("zfinx", true);
+    assert!(extensions.zicsr);
+    extensions.set("zicsr", false);
+    assert!(!extensions.zfinx);
+}
@@ -0,0 +1,127 @@
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    config::DecoderConfig,
+    immediates::PrefetchImmediate,
+    instruction::Instruction,
+    register::IRegister,
+};
+
+const ISA: &str = "rv64gc_zicbom_zicboz_zicbop";
+
+#[test]
+fn prefetch_r() {
+    let (decoder, assembler) = common::isa(ISA);
+    let bin = 0x02156013;
+    let expected = Instruction::PREFETCHR {
+        base: IRegister::A0,
+        offset: PrefetchImmediate::try_from(32).unwrap(),
+    };
+
+    // check assembler
+    let i = assemble_line_with("prefetch.r 32(a0)", &assembler)
+        .unwrap()
+        .i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "prefetch.r 32(a0)");
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x0015200f, "cbo.clean (a0)"),
+            (0x0025200f, "cbo.flush (a0)"),
+            (0x0005200f, "cbo.inval (a0)"),
+            (0x0045200f, "cbo.zero (a0)"),
+            (0x00056013, "prefetch.i 0(a0)"),
+            (0xfc316013, "prefetch.w -64(sp)"),
+            (0x7e15e013, "prefetch.r 2016(a1)"),
+        ],
+    );
+
+    // an explicit zero offset is allowed on the block operations, and the prefetches can leave it out
+    for (line, bin) in [
+        ("cbo.zero 0(a0)", 0x0045200f),
+        ("prefetch.i (a0)", 0x00056013),
+    ] {
+        let i = assemble_line_with(line, &assembler).unwrap().i();
+        assert_eq!(Instruction::encode(&i), bin, "{line}");
+    }
+}
+
+#[test]
+fn malformed() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::decode_errors(
+        &decoder,
+        &[
+            (0x0035200f, "unknown cache block operation"),
+            (0x0085200f, "unknown cache block operation"),
+            (0x0015250f, "cbo.clean with rd set"),
+        ],
+    );
+    common::assemble_errors(
+        &assembler,
+        &[
+            "cbo.clean 8(a0)",
+            "cbo.clean a0",
+            "cbo.zero",
+            "cbo.dirty (a0)",
+            "prefetch.r 33(a0)",
+            "prefetch.r 2048(a0)",
+            "prefetch.x 0(a0)",
+        ],
+    );
+}
+
+#[test]
+fn extension_requirements() {
+    // without Zicbop the prefetches are plain ori instructions with a zero destination
+    let g = DecoderConfig::default();
+    assert_eq!(
+        Instruction::decode_with(0x02156013, &g)
+            .unwrap()
+            .to_string(),
+        "ori zero,a0,33"
+    );
+    let (decoder, _) = common::isa(ISA);
+    assert_eq!(
+        Instruction::decode_with(0x02456013, &decoder)
+            .unwrap()
+            .to_string(),
+        "ori zero,a0,36"
+    );
+    for (bin, extension) in [
+        (0x0015200f, "Zicbom"),
+        (0x0025200f, "Zicbom"),
+        (0x0005200f, "Zicbom"),
+        (0x0045200f, "Zicboz"),
+    ] {
+        let error = String::from(Instruction::decode_with(bin, &g).unwrap_err());
+        assert!(
+            error.ends_with(&format!("requires the {extension} extension")),
+            "{error}"
+        );
+    }
+    assert_eq!(
+        assemble_line("prefetch.r 32(a0)").map(|_| ()),
+        Err("prefetch.r requires the Zicbop extension".to_owned())
+    );
+    assert_eq!(
+        assemble_line("cbo.zero (a0)").map(|_| ()),
+        Err("cbo.zero requires the Zicboz extension".to_owned())
+    );
+}
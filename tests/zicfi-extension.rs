@@ -0,0 +1,157 @@
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    cinstruction::CInstruction,
+    config::{AssemblerConfig, DecoderConfig, IsaString, Xlen},
+    immediates::LpadImmediate,
+    instruction::Instruction,
+    packed::PackedInst,
+    register::IRegister,
+};
+
+const ISA: &str = "rv64gc_zicfilp_zicfiss";
+
+#[test]
+fn lpad() {
+    let (decoder, assembler) = common::isa(ISA);
+    let bin = 0x12345017;
+    let expected = Instruction::LPAD {
+        label: LpadImmediate::try_from(0x12345).unwrap(),
+    };
+
+    // check assembler
+    let i = assemble_line_with("lpad 0x12345", &assembler).unwrap().i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "lpad 74565");
+
+    // the packed form keeps it
+    assert_eq!(
+        PackedInst::decode_with(bin, &decoder).unwrap().expand(),
+        expected
+    );
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::isa(ISA);
+    for (bin, line) in [
+        (0x00001017, "lpad 1"),
+        (0xfffff017, "lpad 1048575"),
+        (0xce104073, "sspush ra"),
+        (0xce504073, "sspush t0"),
+        (0xcdc0c073, "sspopchk ra"),
+        (0xcdc2c073, "sspopchk t0"),
+        (0xcdc04573, "ssrdp a0"),
+        (0x48c5a52f, "ssamoswap.w a0,a1,a2"),
+        (0x4cc5b52f, "ssamoswap.d.aq a0,a1,a2"),
+    ] {
+        let i = Instruction::decode_with(bin, &decoder).unwrap();
+        assert_eq!(i.to_string(), line);
+        assert_eq!(Instruction::encode(&i), bin, "{line}");
+        assert_eq!(assemble_line_with(line, &assembler).unwrap().i(), i);
+        assert_eq!(
+            PackedInst::decode_with(bin, &decoder).unwrap().expand(),
+            i,
+            "{line}"
+        );
+    }
+    for (bin, line, expanded) in [
+        (0x6081, "c.sspush ra", "sspush ra"),
+        (0x6281, "c.sspopchk t0", "sspopchk t0"),
+    ] {
+        let c = CInstruction::decode_with(bin, &decoder).unwrap();
+        assert_eq!(c.to_string(), line);
+        assert_eq!(CInstruction::encode(&c), bin, "{line}");
+        assert_eq!(assemble_line_with(line, &assembler).unwrap().c(), c);
+        assert_eq!(c.expand().to_string(), expanded);
+    }
+
+    // auipc to other registers and c.lui with a zero immediate to other registers are unchanged
+    assert_eq!(
+        Instruction::decode_with(0x00001517, &decoder)
+            .unwrap()
+            .to_string(),
+        "auipc a0,1"
+    );
+    assert!(CInstruction::decode_with(0x6501, &decoder).is_err());
+}
+
+#[test]
+fn malformed() {
+    let (decoder, assembler) = common::isa(ISA);
+    for (line, error) in [
+        ("sspush a0", "sspush only takes ra or t0, got a0"),
+        ("sspopchk sp", "sspopchk only takes ra or t0, got sp"),
+        ("ssrdp zero", "ssrdp must not write to x0"),
+        ("c.sspush t0", "c.sspush only takes ra"),
+        ("c.sspopchk ra", "c.sspopchk only takes t0"),
+    ] {
+        assert_eq!(
+            assemble_line_with(line, &assembler).map(|_| ()),
+            Err(error.to_owned()),
+            "{line}"
+        );
+    }
+    assert!(assemble_line_with("lpad 0x100000", &assembler).is_err());
+    assert!(assemble_line_with("lpad", &assembler).is_err());
+
+    // the other may-be-operations, such as sspush a0 and ssrdp zero
+    for bin in [0xcea04073_u32, 0xcdc04073] {
+        assert!(Instruction::decode_with(bin, &decoder).is_err(), "{bin:#x}");
+    }
+}
+
+#[test]
+fn extension_requirements() {
+    // without Zicfilp lpad is decoded as the auipc whose encoding it uses
+    let g = DecoderConfig::default();
+    assert_eq!(
+        Instruction::decode_with(0x00001017, &g)
+            .unwrap()
+            .to_string(),
+        "auipc zero,1"
+    );
+    // and the shadow stack instructions are rejected
+    for bin in [0xce104073, 0xcdc04573, 0x48c5a52f] {
+        assert!(Instruction::decode_with(bin, &g).is_err(), "{bin:#x}");
+    }
+    assert!(CInstruction::decode_with(0x6081, &g).is_err());
+    for (line, extension) in [
+        ("lpad 1", "Zicfilp"),
+        ("sspush ra", "Zicfiss"),
+        ("ssrdp a0", "Zicfiss"),
+        ("ssamoswap.w a0,a1,a2", "Zicfiss"),
+        ("c.sspopchk t0", "Zicfiss"),
+    ] {
+        let mnemonic = line.split_once(' ').unwrap().0;
+        assert_eq!(
+            assemble_line(line).map(|_| ()),
+            Err(format!("{mnemonic} requires the {extension} extension"))
+        );
+    }
+
+    // Zicfiss implies Zicsr, and ssamoswap.d is only available on RV64
+    let isa = IsaString::try_from("rv32i_zicfiss").unwrap();
+    assert!(isa.extensions.zicsr);
+    assert_eq!(isa.to_string(), "rv32i_zicfiss_zicsr");
+    assert_eq!(
+        assemble_line_with("ssamoswap.d a0,a1,a2", &AssemblerConfig::isa(&isa)).map(|_| ()),
+        Err("ssamoswap.d is only available on RV64".to_owned())
+    );
+    assert_eq!(isa.xlen, Xlen::Rv32);
+    assert_eq!(
+        Instruction::decode_with(0xce104073, &DecoderConfig::isa(&isa)).unwrap(),
+        Instruction::SSPUSH {
+            src: IRegister::ReturnAddress
+        }
+    );
+}
@@ -160,3 +160,43 @@ fn read_clear_csr_immediate() {
     let i3 = assemble_line(&disassemble_instruction(&i)).unwrap().i();
     assert_eq!(i, i3);
 }
+
+#[test]
+fn csr_immediate_range() {
+    // zimm is an unsigned 5-bit field, so all ones decodes as 31 rather than -1
+    let i = Instruction::decode(0x064fddf3).unwrap();
+    if let Instruction::CSRRWI { imm, .. } = i {
+        assert_eq!(imm.val(), 31);
+    } else {
+        panic!("expected csrrwi, got {i}");
+    }
+
+    assert!(assemble_line("csrrwi a0, 100, 0").is_ok());
+    assert!(assemble_line("csrrsi a0, 100, 31").is_ok());
+    assert_eq!(
+        assemble_line("csrrwi a0, 100, 32"),
+        Err("csrrwi immediate must be in the range 0-31, got 32".to_owned())
+    );
+    assert!(assemble_line("csrrsi a0, 100, -1").is_err());
+    assert!(assemble_line("csrrci a0, 100, 100").is_err());
+
+    assert!(CSRImmediate::try_from(32).is_err());
+    assert!(CSRImmediate::try_from(-1).is_err());
+}
+
+#[test]
+fn hex_csr_numbers() {
+    let expected = Instruction::CSRRW {
+        dest: IRegister::A0,
+        src: IRegister::Zero,
+        csr: CSR::try_from(0xc00).unwrap(),
+    };
+    assert_eq!(assemble_line("csrrw a0, 0xC00, x0").unwrap().i(), expected);
+    assert_eq!(assemble_line("csrrw a0,0xc00,zero").unwrap().i(), expected);
+    assert_eq!(disassemble_instruction(&expected), "csrrw a0,0xc00,zero");
+    assert_eq!(
+        disassemble_instruction(&Instruction::decode(0x7c0fd073).unwrap()),
+        "csrrwi zero,0x7c0,31"
+    );
+    assert!(assemble_line("csrrw a0, 0x1000, x0").is_err());
+}
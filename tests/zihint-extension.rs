@@ -0,0 +1,138 @@
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    cinstruction::CInstruction,
+    config::{DecoderConfig, Profile},
+    instruction::Instruction,
+};
+
+const ISA: &str = "rv64gc_zihintntl_zihintpause";
+
+#[test]
+fn pause() {
+    let (decoder, assembler) = common::isa(ISA);
+    let bin = 0x0100000f;
+
+    // check assembler
+    let i = assemble_line_with("pause", &assembler).unwrap().i();
+    assert_eq!(i, Instruction::PAUSE);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), i);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "pause");
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x00200033, "ntl.p1"),
+            (0x00300033, "ntl.pall"),
+            (0x00400033, "ntl.s1"),
+            (0x00500033, "ntl.all"),
+        ],
+    );
+    for (bin, line) in [
+        (0x900a, "c.ntl.p1"),
+        (0x900e, "c.ntl.pall"),
+        (0x9012, "c.ntl.s1"),
+        (0x9016, "c.ntl.all"),
+    ] {
+        let c = CInstruction::decode_with(bin, &decoder).unwrap();
+        assert_eq!(c.to_string(), line);
+        assert_eq!(CInstruction::encode(&c), bin, "{line}");
+        assert_eq!(assemble_line_with(line, &assembler).unwrap().c(), c);
+        assert_eq!(
+            c.expand().to_string(),
+            line.strip_prefix("c.").unwrap(),
+            "{line}"
+        );
+    }
+
+    // the other fences and adds to x0 are unchanged
+    for (bin, line) in [
+        (0x0200000f, "fence r,"),
+        (0x0110000f, "fence w,w"),
+        (0x00600033, "add zero,zero,t1"),
+        (0x00208033, "add zero,ra,sp"),
+    ] {
+        let i = Instruction::decode_with(bin, &decoder).unwrap();
+        assert_eq!(i.to_string(), line);
+    }
+    assert_eq!(
+        CInstruction::decode_with(0x901a, &decoder)
+            .unwrap()
+            .to_string(),
+        "c.add zero,t1"
+    );
+}
+
+#[test]
+fn malformed() {
+    let (_, assembler) = common::isa(ISA);
+    common::assemble_errors(
+        &assembler,
+        &[
+            "pause a0",
+            "ntl.p2",
+            "ntl",
+            "ntl.all a0",
+            "c.ntl.s2",
+            "c.pause",
+        ],
+    );
+}
+
+#[test]
+fn extension_requirements() {
+    // without the extensions the hints are decoded as the instructions whose encodings they use
+    let g = DecoderConfig::default();
+    for (bin, line) in [
+        (0x0100000f, "fence w,"),
+        (0x00200033, "add zero,zero,sp"),
+        (0x00500033, "add zero,zero,t0"),
+    ] {
+        assert_eq!(Instruction::decode_with(bin, &g).unwrap().to_string(), line);
+    }
+    assert_eq!(
+        CInstruction::decode_with(0x900a, &g).unwrap().to_string(),
+        "c.add zero,sp"
+    );
+    for (line, extension) in [
+        ("pause", "Zihintpause"),
+        ("ntl.p1", "Zihintntl"),
+        ("c.ntl.all", "Zihintntl"),
+    ] {
+        assert_eq!(
+            assemble_line(line).map(|_| ()),
+            Err(format!("{line} requires the {extension} extension"))
+        );
+    }
+
+    // RVA23 has both, RVA22 only Zihintpause
+    let rva22 = DecoderConfig::profile(Profile::Rva22U64);
+    assert_eq!(
+        Instruction::decode_with(0x0100000f, &rva22).unwrap(),
+        Instruction::PAUSE
+    );
+    assert_eq!(
+        Instruction::decode_with(0x00200033, &rva22)
+            .unwrap()
+            .to_string(),
+        "add zero,zero,sp"
+    );
+    let rva23 = DecoderConfig::profile(Profile::Rva23U64);
+    assert_eq!(
+        Instruction::decode_with(0x00200033, &rva23).unwrap(),
+        Instruction::NTLP1
+    );
+}
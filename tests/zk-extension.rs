@@ -0,0 +1,235 @@
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    config::{AssemblerConfig, DecoderConfig, Extensions, IsaString},
+    instruction::Instruction,
+    register::IRegister,
+};
+
+const ISA: &str = "rv64gc_zbkb_zbkc_zbkx_zkne_zknd_zknh";
+const RV32_ISA: &str = "rv32gc_zbkb_zbkc_zbkx_zkne_zknd_zknh";
+
+#[test]
+fn pack() {
+    let (decoder, assembler) = common::isa(ISA);
+    let bin = 0x08c5c533;
+    let expected = Instruction::PACK {
+        dest: IRegister::A0,
+        src1: IRegister::A1,
+        src2: IRegister::A2,
+    };
+
+    // check assembler
+    let i = assemble_line_with("pack a0,a1,a2", &assembler).unwrap().i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "pack a0,a1,a2");
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            (0x08c5f533, "packh a0,a1,a2"),
+            (0x08c5c53b, "packw a0,a1,a2"),
+            (0x6875d513, "brev8 a0,a1"),
+            (0x0ac59533, "clmul a0,a1,a2"),
+            (0x0ac5b533, "clmulh a0,a1,a2"),
+            (0x28c5a533, "xperm4 a0,a1,a2"),
+            (0x28c5c533, "xperm8 a0,a1,a2"),
+            (0x40c5f533, "andn a0,a1,a2"),
+            (0x40c5e533, "orn a0,a1,a2"),
+            (0x40c5c533, "xnor a0,a1,a2"),
+            (0x60c59533, "rol a0,a1,a2"),
+            (0x60c5d533, "ror a0,a1,a2"),
+            (0x6255d513, "rori a0,a1,37"),
+            (0x60c5953b, "rolw a0,a1,a2"),
+            (0x60c5d53b, "rorw a0,a1,a2"),
+            (0x6055d51b, "roriw a0,a1,5"),
+            (0x6b85d513, "rev8 a0,a1"),
+        ],
+    );
+
+    // zip and unzip only exist on RV32, where the shamt of their encodings is reserved
+    let (rv32_decoder, rv32_assembler) = common::isa(RV32_ISA);
+    let zip = [(0x08f59513, "zip a0,a1"), (0x08f5d513, "unzip a0,a1")];
+    common::round_trips(&rv32_decoder, &rv32_assembler, &zip);
+    common::decode_errors(&decoder, &zip);
+    common::assemble_errors(&assembler, &zip.map(|(_, line)| line));
+
+    // rev8 has a different encoding on RV32, and the word rotations and larger rotate amounts only exist on RV64
+    let i = assemble_line_with("rev8 a0,a1", &rv32_assembler)
+        .unwrap()
+        .i();
+    assert_eq!(Instruction::encode(&i), 0x6985d513);
+    assert_eq!(
+        Instruction::decode_with(0x6985d513, &rv32_decoder).unwrap(),
+        i
+    );
+    assert_eq!(i.to_string(), "rev8 a0,a1");
+    assert!(Instruction::decode_with(0x6b85d513, &rv32_decoder).is_err());
+    assert!(Instruction::decode_with(0x6985d513, &decoder).is_err());
+    assert!(assemble_line_with("rori a0,a1,31", &rv32_assembler).is_ok());
+    common::assemble_errors(
+        &rv32_assembler,
+        &[
+            "rori a0,a1,32",
+            "rolw a0,a1,a2",
+            "rorw a0,a1,a2",
+            "roriw a0,a1,5",
+        ],
+    );
+    assert!(Instruction::decode_with(0x6255d513, &rv32_decoder).is_err());
+    assert!(Instruction::decode_with(0x60c5d53b, &rv32_decoder).is_err());
+}
+
+#[test]
+fn extension_requirements() {
+    // none of the scalar crypto extensions are part of G
+    for (line, extension) in [
+        ("pack a0,a1,a2", "Zbkb"),
+        ("brev8 a0,a1", "Zbkb"),
+        ("andn a0,a1,a2", "Zbkb"),
+        ("rol a0,a1,a2", "Zbkb"),
+        ("rori a0,a1,3", "Zbkb"),
+        ("roriw a0,a1,3", "Zbkb"),
+        ("rev8 a0,a1", "Zbkb"),
+        ("clmul a0,a1,a2", "Zbkc"),
+        ("xperm8 a0,a1,a2", "Zbkx"),
+    ] {
+        let bin = Instruction::encode(&assemble_line_with(line, &common::isa(ISA).1).unwrap().i());
+        assert_eq!(
+            Instruction::decode(bin).map_err(String::from),
+            Err(format!(
+                "unsupported instruction {line}, which requires the {extension} extension"
+            ))
+        );
+        assert!(assemble_line(line).is_err());
+    }
+    // each extension is enabled on its own
+    let zbkc = DecoderConfig {
+        extensions: Extensions {
+            zbkc: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(Instruction::decode_with(0x0ac59533, &zbkc).is_ok());
+    assert!(Instruction::decode_with(0x28c5c533, &zbkc).is_err());
+
+    for (line, extension) in [
+        ("aes64es a0,a1,a2", "Zkne"),
+        ("aes64dsm a0,a1,a2", "Zknd"),
+        ("aes64im a0,a1", "Zknd"),
+        ("aes64ks1i a0,a1,10", "Zkne or Zknd"),
+        ("aes64ks2 a0,a1,a2", "Zkne or Zknd"),
+        ("sha256sig0 a0,a1", "Zknh"),
+        ("sha512sum1 a0,a1", "Zknh"),
+    ] {
+        let bin = Instruction::encode(&assemble_line_with(line, &common::isa(ISA).1).unwrap().i());
+        assert_eq!(
+            Instruction::decode(bin).map_err(String::from),
+            Err(format!(
+                "unsupported instruction {line}, which requires the {extension} extension"
+            ))
+        );
+        assert!(assemble_line(line).is_err());
+    }
+    // the key schedule instructions are shared by Zkne and Zknd
+    let zknd = DecoderConfig {
+        extensions: Extensions {
+            zknd: true,
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    assert!(Instruction::decode_with(0x7ec58533, &zknd).is_ok());
+    assert!(Instruction::decode_with(0x31a59513, &zknd).is_ok());
+    assert!(Instruction::decode_with(0x32c58533, &zknd).is_err());
+
+    // the RV32 SHA-512 instructions
+    let rv32 = IsaString::try_from("rv32gc").unwrap();
+    for (bin, line) in [
+        (0x50c58533, "sha512sum0r a0,a1,a2"),
+        (0x54c58533, "sha512sig0l a0,a1,a2"),
+        (0x5ec58533, "sha512sig1h a0,a1,a2"),
+    ] {
+        assert_eq!(
+            Instruction::decode_with(bin, &DecoderConfig::isa(&rv32)).map_err(String::from),
+            Err(format!(
+                "unsupported instruction {line}, which requires the Zknh extension"
+            ))
+        );
+        assert_eq!(
+            assemble_line_with(line, &AssemblerConfig::isa(&rv32)).map(|_| ()),
+            Err(format!(
+                "{} requires the Zknh extension",
+                line.split(' ').next().unwrap()
+            ))
+        );
+        assert!(Instruction::decode_with(bin, &common::isa(RV32_ISA).0).is_ok());
+    }
+}
+
+#[test]
+fn aes_and_sha() {
+    let (decoder, assembler) = common::isa(ISA);
+    let (rv32_decoder, rv32_assembler) = common::isa(RV32_ISA);
+    let rv64_only = [
+        (0x32c58533, "aes64es a0,a1,a2"),
+        (0x36c58533, "aes64esm a0,a1,a2"),
+        (0x3ac58533, "aes64ds a0,a1,a2"),
+        (0x3ec58533, "aes64dsm a0,a1,a2"),
+        (0x30059513, "aes64im a0,a1"),
+        (0x31a59513, "aes64ks1i a0,a1,10"),
+        (0x7ec58533, "aes64ks2 a0,a1,a2"),
+        (0x10659513, "sha512sig0 a0,a1"),
+        (0x10759513, "sha512sig1 a0,a1"),
+        (0x10459513, "sha512sum0 a0,a1"),
+        (0x10559513, "sha512sum1 a0,a1"),
+    ];
+    common::round_trips(&decoder, &assembler, &rv64_only);
+    common::decode_errors(&rv32_decoder, &rv64_only);
+    common::assemble_errors(&rv32_assembler, &rv64_only.map(|(_, line)| line));
+    let rv32_only = [
+        (0xe2c58533, "aes32esi a0,a1,a2,3"),
+        (0x66c58533, "aes32esmi a0,a1,a2,1"),
+        (0xaac58533, "aes32dsi a0,a1,a2,2"),
+        (0x2ec58533, "aes32dsmi a0,a1,a2,0"),
+        (0x50c58533, "sha512sum0r a0,a1,a2"),
+        (0x52c58533, "sha512sum1r a0,a1,a2"),
+        (0x54c58533, "sha512sig0l a0,a1,a2"),
+        (0x5cc58533, "sha512sig0h a0,a1,a2"),
+        (0x56c58533, "sha512sig1l a0,a1,a2"),
+        (0x5ec58533, "sha512sig1h a0,a1,a2"),
+    ];
+    common::round_trips(&rv32_decoder, &rv32_assembler, &rv32_only);
+    common::decode_errors(&decoder, &rv32_only);
+    common::assemble_errors(&assembler, &rv32_only.map(|(_, line)| line));
+    // the sha256 instructions exist on both
+    let sha256 = [
+        (0x10259513, "sha256sig0 a0,a1"),
+        (0x10359513, "sha256sig1 a0,a1"),
+        (0x10059513, "sha256sum0 a0,a1"),
+        (0x10159513, "sha256sum1 a0,a1"),
+    ];
+    common::round_trips(&decoder, &assembler, &sha256);
+    common::round_trips(&rv32_decoder, &rv32_assembler, &sha256);
+
+    // round numbers past 10 are reserved, and bs is two bits
+    assert!(Instruction::decode_with(0x31b59513, &decoder).is_err());
+    assert!(assemble_line_with("aes64ks1i a0,a1,11", &assembler).is_err());
+    assert!(assemble_line_with("aes32esi a0,a1,a2,4", &rv32_assembler).is_err());
+    assert!(assemble_line_with("aes32esi a0,a1,a2", &rv32_assembler).is_err());
+}
@@ -0,0 +1,179 @@
+mod common;
+
+use riscv_codec::{
+    assembly::{assemble_line, assemble_line_with},
+    config::{AssemblerConfig, DecoderConfig, Extensions, IsaString},
+    immediates::VShamt,
+    instruction::Instruction,
+    register::VRegister,
+    vector::{VOperand, VectorInstruction, VectorOp},
+};
+
+const ISA: &str = "rv64gcv_zvbb_zvbc_zvkg_zvkned_zvknha_zvksed_zvksh";
+
+#[test]
+fn vror_vi() {
+    let (decoder, assembler) = common::isa(ISA);
+    let bin = 0x569fb557;
+    let expected = Instruction::Vector(VectorInstruction::Binary {
+        op: VectorOp::VROR,
+        dest: VRegister::V10,
+        src2: VRegister::V9,
+        src1: VOperand::Shamt(VShamt::try_from(63).unwrap()),
+        masked: false,
+    });
+
+    // check assembler
+    let i = assemble_line_with("vror.vi v10,v9,63", &assembler)
+        .unwrap()
+        .i();
+    assert_eq!(i, expected);
+
+    // check decoder
+    assert_eq!(Instruction::decode_with(bin, &decoder).unwrap(), expected);
+
+    // check encoder
+    assert_eq!(Instruction::encode(&i), bin);
+
+    // check disassembler
+    assert_eq!(i.to_string(), "vror.vi v10,v9,63");
+}
+
+#[test]
+fn round_trips() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::round_trips(
+        &decoder,
+        &assembler,
+        &[
+            // Zvbb
+            (0x06940557, "vandn.vv v10,v9,v8"),
+            (0x04954557, "vandn.vx v10,v9,a0,v0.t"),
+            (0x4a952557, "vbrev.v v10,v9"),
+            (0x4a942557, "vbrev8.v v10,v9"),
+            (0x4894a557, "vrev8.v v10,v9,v0.t"),
+            (0x4a962557, "vclz.v v10,v9"),
+            (0x4a96a557, "vctz.v v10,v9"),
+            (0x4a972557, "vcpop.v v10,v9"),
+            (0x56940557, "vrol.vv v10,v9,v8"),
+            (0x56954557, "vrol.vx v10,v9,a0"),
+            (0x52940557, "vror.vv v10,v9,v8"),
+            (0x52954557, "vror.vx v10,v9,a0"),
+            (0x5291b557, "vror.vi v10,v9,3"),
+            (0x549fb557, "vror.vi v10,v9,63,v0.t"),
+            (0xd6860557, "vwsll.vv v10,v8,v12"),
+            (0xd6854557, "vwsll.vx v10,v8,a0"),
+            (0xd68fb557, "vwsll.vi v10,v8,31"),
+            // Zvbc
+            (0x32942557, "vclmul.vv v10,v9,v8"),
+            (0x32956557, "vclmul.vx v10,v9,a0"),
+            (0x36942557, "vclmulh.vv v10,v9,v8"),
+            (0x34956557, "vclmulh.vx v10,v9,a0,v0.t"),
+            // Zvkg
+            (0xb2942577, "vghsh.vv v10,v9,v8"),
+            (0xa298a577, "vgmul.vv v10,v9"),
+            // Zvkned
+            (0xa290a577, "vaesdf.vv v10,v9"),
+            (0xa690a577, "vaesdf.vs v10,v9"),
+            (0xa291a577, "vaesef.vv v10,v9"),
+            (0xa691a577, "vaesef.vs v10,v9"),
+            (0xa2912577, "vaesem.vv v10,v9"),
+            (0xa6912577, "vaesem.vs v10,v9"),
+            (0xa2902577, "vaesdm.vv v10,v9"),
+            (0xa6902577, "vaesdm.vs v10,v9"),
+            (0x8a90a577, "vaeskf1.vi v10,v9,1"),
+            (0xaa912577, "vaeskf2.vi v10,v9,2"),
+            (0xa693a577, "vaesz.vs v10,v9"),
+            // Zvknha
+            (0xb6942577, "vsha2ms.vv v10,v9,v8"),
+            (0xba942577, "vsha2ch.vv v10,v9,v8"),
+            (0xbe942577, "vsha2cl.vv v10,v9,v8"),
+            // Zvksed
+            (0x86902577, "vsm4k.vi v10,v9,0"),
+            (0xa2982577, "vsm4r.vv v10,v9"),
+            (0xa6982577, "vsm4r.vs v10,v9"),
+            // Zvksh
+            (0x82942577, "vsm3me.vv v10,v9,v8"),
+            (0xae93a577, "vsm3c.vi v10,v9,7"),
+        ],
+    );
+}
+
+#[test]
+fn malformed() {
+    let (decoder, assembler) = common::isa(ISA);
+    common::decode_errors(
+        &decoder,
+        &[
+            (0xa0912577, "masked vaesem.vv"),
+            (0xb4942577, "masked vsha2ms.vv"),
+            (0xa2916577, "OP-VE with the funct3 of OPMVX"),
+            (0xa2922577, "unknown vs1 of the AES rounds"),
+            (0xc293a577, "unknown funct6"),
+        ],
+    );
+    common::assemble_errors(
+        &assembler,
+        &[
+            "vaesem.vv v10,v9,v0.t",
+            "vsha2ms.vv v10,v9,v8,v0.t",
+            "vror.vi v10,v9,64",
+            "vaeskf1.vi v10,v9,32",
+            "vaeskf1.vv v10,v9,v8",
+            "vandn.vi v10,v9,1",
+            "vclmul.vi v10,v9,1",
+            "vaesz.vv v10,v9",
+            "vbrev.v v10,v9,v8",
+        ],
+    );
+}
+
+#[test]
+fn extension_requirements() {
+    let v = IsaString::try_from("rv64gcv").unwrap();
+    assert_eq!(
+        Instruction::decode_with(0x06940557, &DecoderConfig::isa(&v)).map_err(String::from),
+        Err(
+            "unsupported instruction vandn.vv v10,v9,v8, which requires the Zvbb extension"
+                .to_owned()
+        )
+    );
+    assert_eq!(
+        Instruction::decode_with(0x32942557, &DecoderConfig::isa(&v)).map_err(String::from),
+        Err(
+            "unsupported instruction vclmul.vv v10,v9,v8, which requires the Zvbc extension"
+                .to_owned()
+        )
+    );
+    for (bin, extension) in [
+        (0xa298a577, "Zvkg"),
+        (0xa2912577, "Zvkned"),
+        (0x8a90a577, "Zvkned"),
+        (0xb6942577, "Zvknha"),
+        (0xa2982577, "Zvksed"),
+        (0x86902577, "Zvksed"),
+        (0x82942577, "Zvksh"),
+    ] {
+        let error =
+            String::from(Instruction::decode_with(bin, &DecoderConfig::isa(&v)).unwrap_err());
+        assert!(
+            error.ends_with(&format!("requires the {extension} extension")),
+            "{error}"
+        );
+    }
+    assert_eq!(
+        assemble_line_with("vaesem.vv v10,v9", &AssemblerConfig::isa(&v)).map(|_| ()),
+        Err("vaesem.vv requires the Zvkned extension".to_owned())
+    );
+    assert!(assemble_line("vror.vi v10,v9,3").is_err());
+
+    // each of them implies v
+    let isa = IsaString::try_from("rv64i_zvkned").unwrap();
+    assert!(isa.extensions.zvkned && isa.extensions.v);
+    assert_eq!(isa.to_string(), "rv64ifdv_zicsr_zvkned");
+    let mut extensions = Extensions::default();
+    extensions.set("zvbb", true);
+    assert!(extensions.v);
+    extensions.set("v", false);
+    assert!(!extensions.zvbb);
+}